@@ -14,7 +14,10 @@ enum Message {
     Decrement,
 }
 
-// Properties or the `Counter` component, in this case the initial value.
+// Properties or the `Counter` component, in this case the initial value. The
+// derive macro generates a `Properties::new` constructor which takes the
+// initial value (available with the `macros` feature of zi).
+#[derive(zi::Properties)]
 struct Properties {
     initial_count: usize,
 }
@@ -120,6 +123,6 @@ const STYLE: Style = Style::bold(BACKGROUND, FOREGROUND);
 
 fn main() -> Result<()> {
     env_logger::init();
-    let counter = Counter::with(Properties { initial_count: 0 });
+    let counter = Counter::with(Properties::new(0));
     zi_term::incremental()?.run_event_loop(counter)
 }
@@ -47,7 +47,7 @@ impl Mandelbrot {
                             break;
                         }
                     }
-                    let conv = (num_steps as f64 / 1000.0).max(0.0).min(1.0);
+                    let conv = (num_steps as f64 / 1000.0).clamp(0.0, 1.0);
                     // let conv2 = 1.0 - (z.norm_sqr() / target).max(0.0).min(1.0);
                     // let conv = conv1 * conv2;
                     // let xx = (conv * 255.0).floor() as u8;
@@ -1,36 +1,37 @@
-use std::cmp;
-use unicode_width::UnicodeWidthStr;
 use zi::{
-    components::border::{Border, BorderProperties},
+    components::{
+        border::{Border, BorderProperties},
+        text::{Text, TextAlign, TextProperties},
+    },
     prelude::*,
+    Theme,
 };
 use zi_term::Result;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct Theme {
-    logo: Style,
-    tagline: Style,
-    credits: Style,
+const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+const LIGHT0_SOFT: Colour = Colour::rgb(242, 229, 188);
+const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+const DARK2: Colour = Colour::rgb(80, 73, 69);
+const GRAY_245: Colour = Colour::rgb(146, 131, 116);
+const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
+const FADED_BLUE: Colour = Colour::rgb(7, 102, 120);
+
+fn dark_theme() -> Theme {
+    Theme::new("dark")
+        .style("logo", Style::normal(DARK0_SOFT, LIGHT2))
+        .style("tagline", Style::normal(DARK0_SOFT, BRIGHT_BLUE))
+        .style("credits", Style::normal(DARK0_SOFT, GRAY_245))
 }
 
-impl Default for Theme {
-    fn default() -> Self {
-        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
-        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
-        const GRAY_245: Colour = Colour::rgb(146, 131, 116);
-        const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
-
-        Self {
-            logo: Style::normal(DARK0_SOFT, LIGHT2),
-            tagline: Style::normal(DARK0_SOFT, BRIGHT_BLUE),
-            credits: Style::normal(DARK0_SOFT, GRAY_245),
-        }
-    }
+fn light_theme() -> Theme {
+    Theme::new("light")
+        .style("logo", Style::normal(LIGHT0_SOFT, DARK2))
+        .style("tagline", Style::normal(LIGHT0_SOFT, FADED_BLUE))
+        .style("credits", Style::normal(LIGHT0_SOFT, GRAY_245))
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 struct SplashProperties {
-    theme: Theme,
     logo: String,
     tagline: String,
     credits: String,
@@ -40,15 +41,15 @@ struct SplashProperties {
 #[derive(Debug)]
 struct Splash {
     properties: SplashProperties,
-    frame: Rect,
+    link: ComponentLink<Self>,
 }
 
 impl Component for Splash {
     type Message = usize;
     type Properties = SplashProperties;
 
-    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
-        Self { properties, frame }
+    fn create(properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self { properties, link }
     }
 
     fn change(&mut self, properties: Self::Properties) -> ShouldRender {
@@ -60,53 +61,40 @@ impl Component for Splash {
         }
     }
 
-    fn resize(&mut self, frame: Rect) -> ShouldRender {
-        self.frame = frame;
-        ShouldRender::Yes
-    }
-
     #[inline]
     fn view(&self) -> Layout {
-        let logo_size = text_block_size(&self.properties.logo);
-        let tagline_size = text_block_size(&self.properties.tagline);
-        let credits_size = text_block_size(&self.properties.credits);
-
-        let theme = Theme::default();
-        let mut canvas = Canvas::new(self.frame.size);
-        canvas.clear(theme.logo);
-
-        // Draw logo
-        let middle_x = (self.frame.size.width / 2).saturating_sub(logo_size.width / 2);
-        let mut middle_y = cmp::min(8, self.frame.size.height.saturating_sub(logo_size.height))
-            + self.properties.offset;
-        for line in self.properties.logo.lines() {
-            canvas.draw_str(middle_x, middle_y, theme.logo, line);
-            middle_y += 1;
-        }
-
-        // Draw tagline
-        middle_y += 2;
-        let middle_x = (self.frame.size.width / 2).saturating_sub(tagline_size.width / 2);
-        for line in self.properties.tagline.lines() {
-            canvas.draw_str(middle_x, middle_y, theme.tagline, line);
-            middle_y += 1;
-        }
-
-        // Draw credits
-        middle_y += 1;
-        let middle_x = (self.frame.size.width / 2).saturating_sub(credits_size.width / 2);
-        for line in self.properties.credits.lines() {
-            canvas.draw_str(middle_x, middle_y, theme.credits, line);
-            middle_y += 1;
-        }
+        // The theme is shared by all components and they are re-rendered
+        // when it changes
+        let theme = self.link.theme();
+        let style = |slot| theme.get_or(slot, Style::default());
+        let spacer = |key: &str| Text::with_key(key, TextProperties::new().style(style("logo")));
+        let centred = |key: &str, content: &str, style: Style| {
+            Item::content(Text::with_key(
+                key,
+                TextProperties::new()
+                    .content(content)
+                    .style(style)
+                    .align(TextAlign::Centre),
+            ))
+        };
 
-        canvas.into()
+        // The logo, tagline and credits are sized to fit their content and
+        // centred vertically by the stretched spacers around them
+        Layout::column([
+            Item::fixed(self.properties.offset)(spacer("offset")),
+            Item::auto(spacer("top")),
+            centred("logo", &self.properties.logo, style("logo")),
+            Item::fixed(2)(spacer("logo-gap")),
+            centred("tagline", &self.properties.tagline, style("tagline")),
+            Item::fixed(1)(spacer("tagline-gap")),
+            centred("credits", &self.properties.credits, style("credits")),
+            Item::auto(spacer("bottom")),
+        ])
     }
 }
 
 #[derive(Debug)]
 struct SplashScreen {
-    theme: Theme,
     link: ComponentLink<Self>,
 }
 
@@ -115,18 +103,14 @@ impl Component for SplashScreen {
     type Properties = ();
 
     fn create(_properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
-        Self {
-            theme: Default::default(),
-            link,
-        }
+        link.set_theme(dark_theme());
+        Self { link }
     }
 
     fn view(&self) -> Layout {
         // Instantiate our "splash screen" component
-        let theme = self.theme.clone();
         let splash = move || {
             Splash::with(SplashProperties {
-                theme: theme.clone(),
                 logo: SPLASH_LOGO.into(),
                 tagline: SPLASH_TAGLINE.into(),
                 credits: SPLASH_CREDITS.into(),
@@ -135,7 +119,8 @@ impl Component for SplashScreen {
         };
 
         // Adding a border
-        Border::with(BorderProperties::new(splash).style(self.theme.credits))
+        let border = self.link.theme().get_or("credits", Style::default());
+        Border::with(BorderProperties::new(splash).style(border))
     }
 
     fn bindings(&self, bindings: &mut Bindings<Self>) {
@@ -147,17 +132,20 @@ impl Component for SplashScreen {
         // Set focus to `true` in order to react to key presses
         bindings.set_focus(true);
 
-        // Only one binding, for exiting
         bindings.add("exit", [Key::Ctrl('x'), Key::Ctrl('c')], |this: &Self| {
             this.link.exit()
         });
-    }
-}
 
-fn text_block_size(text: &str) -> Size {
-    let width = text.lines().map(UnicodeWidthStr::width).max().unwrap_or(0);
-    let height = text.lines().count();
-    Size::new(width, height)
+        // Switches between the dark and light themes
+        bindings.add("toggle-theme", [Key::Ctrl('t')], |this: &Self| {
+            let theme = if this.link.theme().name() == "dark" {
+                light_theme()
+            } else {
+                dark_theme()
+            };
+            this.link.set_theme(theme)
+        });
+    }
 }
 
 fn main() -> Result<()> {
@@ -176,4 +164,4 @@ const SPLASH_LOGO: &str = r#"
  ▄████████▀   ▄████▀      █████▄▄██   ███    █▀   ▄████████▀    ███    █▀
 "#;
 const SPLASH_TAGLINE: &str = "a splash screen for the terminal";
-const SPLASH_CREDITS: &str = "C-x C-c to quit";
+const SPLASH_CREDITS: &str = "C-t to switch theme, C-x C-c to quit";
@@ -419,6 +419,7 @@ impl Component for TodoMvc {
                 item_size: 1,
                 focused: true,
                 on_change: Some(link.callback(Message::FocusItem)),
+                scroll: None,
             },
         ));
 
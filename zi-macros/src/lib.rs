@@ -0,0 +1,252 @@
+//! Derive macros for [Zi](https://docs.rs/zi), re-exported by `zi` when its
+//! `macros` feature is enabled.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, Ident, Type};
+
+/// Derives the boilerplate of a component's `Properties` struct.
+///
+/// The generated code consists of
+///   1. A `new` constructor taking the required fields in declaration order.
+///      Fields marked `#[prop(default)]` are initialised with
+///      `Default::default()` and fields marked `#[prop(default = <expr>)]`
+///      with the expression.
+///   2. A builder method for every optional field, named after the field and
+///      documented with the field's doc comment.
+///   3. A `Default` implementation if all fields are optional.
+///   4. A structural `PartialEq` implementation comparing the fields one by
+///      one, to tell in `Component::change` whether the properties changed.
+///      Unlike `#[derive(PartialEq)]`, it doesn't require type parameters to
+///      be comparable.
+///
+/// Fields marked `#[prop(into)]` accept anything which converts into the
+/// type of the field in the constructor and builder methods, e.g. a `&str`
+/// for a `String` or a closure for a `Callback`. The attributes can be
+/// combined, e.g. `#[prop(default, into)]`.
+///
+/// ```
+/// # use zi::{Callback, Component, ComponentLink, Layout, Rect, ShouldRender, Style};
+/// use zi_macros::Properties; // `zi::Properties` with the `macros` feature
+///
+/// #[derive(Properties)]
+/// pub struct BadgeProperties {
+///     #[prop(into)]
+///     label: String,
+///     /// The style of the label.
+///     #[prop(default)]
+///     style: Style,
+///     #[prop(default = 1)]
+///     padding: usize,
+///     #[prop(default, into)]
+///     on_click: Option<Callback<()>>,
+/// }
+///
+/// let properties = BadgeProperties::new("new")
+///     .padding(2)
+///     .on_click(Callback::from(|_| {}));
+/// assert_eq!(properties.label, "new");
+/// assert_eq!(properties.style, Style::default());
+/// assert_eq!(properties.padding, 2);
+/// assert!(properties != BadgeProperties::new("new"));
+///
+/// struct Badge {
+///     properties: BadgeProperties,
+/// }
+///
+/// impl Component for Badge {
+///     type Message = ();
+///     type Properties = BadgeProperties;
+///
+///     fn create(properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+///         Self { properties }
+///     }
+///
+///     fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+///         let changed = self.properties != properties;
+///         self.properties = properties;
+///         changed.into()
+///     }
+///
+///     fn view(&self) -> Layout {
+///         Layout::empty()
+///     }
+/// }
+/// ```
+#[proc_macro_derive(Properties, attributes(prop))]
+pub fn derive_properties(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_properties(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// A field of a properties struct.
+struct Field<'a> {
+    name: &'a Ident,
+    ty: &'a Type,
+    docs: Vec<&'a Attribute>,
+    /// The initial value of an optional field, `None` for required fields.
+    default: Option<TokenStream2>,
+    into: bool,
+}
+
+impl<'a> Field<'a> {
+    fn parse(field: &'a syn::Field) -> syn::Result<Self> {
+        let mut default = None;
+        let mut into = false;
+        for attribute in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("prop"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    default = Some(if meta.input.peek(syn::Token![=]) {
+                        let value: Expr = meta.value()?.parse()?;
+                        quote! { #value }
+                    } else {
+                        quote! { ::core::default::Default::default() }
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("into") {
+                    into = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown property attribute, expected `default` or `into`"))
+                }
+            })?;
+        }
+        Ok(Self {
+            name: field.ident.as_ref().expect("named field"),
+            ty: &field.ty,
+            docs: field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("doc"))
+                .collect(),
+            default,
+            into,
+        })
+    }
+
+    /// The type of the argument which sets the field.
+    fn argument_type(&self) -> TokenStream2 {
+        let ty = self.ty;
+        if self.into {
+            quote! { impl ::core::convert::Into<#ty> }
+        } else {
+            quote! { #ty }
+        }
+    }
+
+    /// Converts the argument which sets the field to the type of the field.
+    fn argument_value(&self) -> TokenStream2 {
+        let name = self.name;
+        if self.into {
+            quote! { ::core::convert::Into::into(#name) }
+        } else {
+            quote! { #name }
+        }
+    }
+}
+
+fn expand_properties(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => fields
+                .named
+                .iter()
+                .map(Field::parse)
+                .collect::<syn::Result<Vec<_>>>()?,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`Properties` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`Properties` can only be derived for structs",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let visibility = &input.vis;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let required: Vec<_> = fields
+        .iter()
+        .filter(|field| field.default.is_none())
+        .collect();
+    let parameters = required.iter().map(|field| {
+        let (name, argument) = (field.name, field.argument_type());
+        quote! { #name: #argument }
+    });
+    let initialisers = fields.iter().map(|field| {
+        let name = field.name;
+        match field.default {
+            Some(ref default) => quote! { #name: #default },
+            None => {
+                let value = field.argument_value();
+                quote! { #name: #value }
+            }
+        }
+    });
+    let builders = fields
+        .iter()
+        .filter(|field| field.default.is_some())
+        .map(|field| {
+            let (name, docs) = (field.name, &field.docs);
+            let (argument, value) = (field.argument_type(), field.argument_value());
+            quote! {
+                #(#docs)*
+                #[inline]
+                #visibility fn #name(mut self, #name: #argument) -> Self {
+                    self.#name = #value;
+                    self
+                }
+            }
+        });
+    let default = if required.is_empty() {
+        quote! {
+            impl #impl_generics ::core::default::Default for #name #type_generics #where_clause {
+                fn default() -> Self {
+                    Self::new()
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let comparisons = fields.iter().map(|Field { name, .. }| {
+        quote! { && self.#name == other.#name }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #name #type_generics #where_clause {
+            /// Creates the properties from the required fields, the optional
+            /// ones take their default values.
+            #[inline]
+            #[allow(clippy::too_many_arguments)]
+            #visibility fn new(#(#parameters),*) -> Self {
+                Self {
+                    #(#initialisers),*
+                }
+            }
+
+            #(#builders)*
+        }
+
+        #default
+
+        impl #impl_generics ::core::cmp::PartialEq for #name #type_generics #where_clause {
+            fn eq(&self, other: &Self) -> bool {
+                true #(#comparisons)*
+            }
+        }
+    })
+}
@@ -0,0 +1,39 @@
+use std::env;
+use zi::terminal::{BackendCapabilities, ColourSupport, GraphicsProtocol};
+
+/// Guesses the capabilities of the terminal from the environment variables
+/// set by terminal emulators. Mouse reporting is set by the backend.
+pub(crate) fn detect() -> BackendCapabilities {
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    let (term, term_program) = (var("TERM"), var("TERM_PROGRAM"));
+    let kitty = term == "xterm-kitty" || env::var_os("KITTY_WINDOW_ID").is_some();
+
+    let colours = match var("COLORTERM").as_str() {
+        "truecolor" | "24bit" => ColourSupport::TrueColour,
+        _ if kitty || term_program == "iTerm.app" || term_program == "WezTerm" => {
+            ColourSupport::TrueColour
+        }
+        _ if term.contains("256color") => ColourSupport::Ansi256,
+        _ if term == "dumb" => ColourSupport::Monochrome,
+        _ => ColourSupport::Ansi16,
+    };
+    let graphics = if kitty {
+        Some(GraphicsProtocol::Kitty)
+    } else if term_program == "iTerm.app" || term_program == "WezTerm" {
+        Some(GraphicsProtocol::ITerm2)
+    } else if term.starts_with("foot") || term.starts_with("mlterm") || term.contains("sixel") {
+        Some(GraphicsProtocol::Sixel)
+    } else {
+        None
+    };
+    let hyperlinks = kitty
+        || matches!(term_program.as_str(), "iTerm.app" | "WezTerm" | "vscode")
+        || var("VTE_VERSION")
+            .parse::<u32>()
+            .is_ok_and(|version| version >= 5000);
+
+    BackendCapabilities::new()
+        .colours(colours)
+        .hyperlinks(hyperlinks)
+        .graphics(graphics)
+}
@@ -0,0 +1,71 @@
+use tokio::sync::mpsc::UnboundedSender;
+use zi::app::Clipboard;
+
+use crate::graphics::base64;
+
+/// Copies text to the terminal's clipboard with the OSC 52 escape sequence,
+/// which works over SSH too. The sequence is sent to the backend, which
+/// writes it to the terminal along with everything else it draws.
+///
+/// Most terminals don't let applications read their clipboard, so
+/// [`paste`](#method.paste) only returns the text copied last by the app
+/// itself. Text pasted by the user in the terminal arrives as key presses.
+#[derive(Debug)]
+pub(crate) struct Osc52Clipboard {
+    sequences: UnboundedSender<String>,
+    copied: Option<String>,
+}
+
+impl Osc52Clipboard {
+    pub fn new(sequences: UnboundedSender<String>) -> Self {
+        Self {
+            sequences,
+            copied: None,
+        }
+    }
+}
+
+impl Clipboard for Osc52Clipboard {
+    fn copy(&mut self, text: &str) {
+        if self.sequences.send(osc52(text)).is_err() {
+            log::warn!("Could not copy to the clipboard, the backend was dropped");
+        }
+        self.copied = Some(text.to_owned());
+    }
+
+    /// Returns the text copied last by the app, not the contents of the
+    /// terminal's clipboard, which can't be read.
+    fn paste(&mut self) -> Option<String> {
+        self.copied.clone()
+    }
+}
+
+/// The escape sequence setting the clipboard to `text`. Inside tmux, it's
+/// passed through to the outer terminal.
+fn osc52(text: &str) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    if std::env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;\x1b{}\x1b\\", sequence)
+    } else {
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn copy_sends_the_sequence_and_paste_returns_the_copied_text() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut clipboard = Osc52Clipboard::new(sender);
+        assert_eq!(clipboard.paste(), None);
+
+        clipboard.copy("Hello");
+        let sequence = receiver.try_recv().unwrap();
+        assert!(sequence.contains("\x1b]52;c;SGVsbG8=\x07"));
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(clipboard.paste(), Some("Hello".into()));
+    }
+}
@@ -0,0 +1,387 @@
+//! Drawing images with the kitty graphics protocol, iTerm2's inline images and
+//! with sixels.
+use std::{
+    cmp,
+    collections::BTreeSet,
+    io::{self, Write},
+};
+use zi::terminal::{GraphicsProtocol, Image, ImagePlacement, Size};
+
+/// Writes an image s.t. it fills the cells of its region, starting at the
+/// cursor.
+pub(crate) fn write_image(
+    target: &mut impl Write,
+    protocol: GraphicsProtocol,
+    placement: &ImagePlacement,
+    cell_pixel_size: Size,
+) -> io::Result<()> {
+    match protocol {
+        GraphicsProtocol::Kitty => write_kitty(target, placement),
+        GraphicsProtocol::Sixel => {
+            // Terminals scroll when a sixel image reaches the bottom of the
+            // screen unless its height is a multiple of 6 pixels
+            let height = placement.region.size.height * cell_pixel_size.height;
+            let size = Size::new(
+                placement.region.size.width * cell_pixel_size.width,
+                height - height % 6,
+            );
+            // Regions shorter than 6 pixels keep the block characters drawn
+            // by the canvas
+            if size.width == 0 || size.height == 0 {
+                return Ok(());
+            }
+            write_sixel(target, &placement.image.resized(size))
+        }
+        GraphicsProtocol::ITerm2 => write_iterm2(target, placement),
+    }
+}
+
+/// Removes all the images drawn before. Sixel and iTerm2 images are part of
+/// the text of the cells, they are removed when the cells are redrawn.
+pub(crate) fn clear_images(target: &mut impl Write, protocol: GraphicsProtocol) -> io::Result<()> {
+    match protocol {
+        GraphicsProtocol::Kitty => write!(target, "\x1b_Ga=d,q=2\x1b\\"),
+        GraphicsProtocol::Sixel | GraphicsProtocol::ITerm2 => Ok(()),
+    }
+}
+
+/// Transmits and displays an RGBA image, scaled by the terminal to the
+/// region. The cursor is not moved and responses from the terminal are
+/// suppressed s.t. they don't show up as input.
+fn write_kitty(target: &mut impl Write, placement: &ImagePlacement) -> io::Result<()> {
+    let size = placement.image.size();
+    let encoded = base64(placement.image.rgba());
+    let mut chunks = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).peekable();
+    let mut first = true;
+    while let Some(chunk) = chunks.next() {
+        let more = u8::from(chunks.peek().is_some());
+        if first {
+            write!(
+                target,
+                "\x1b_Ga=T,f=32,s={},v={},c={},r={},C=1,q=2,m={};",
+                size.width,
+                size.height,
+                placement.region.size.width,
+                placement.region.size.height,
+                more
+            )?;
+            first = false;
+        } else {
+            write!(target, "\x1b_Gm={};", more)?;
+        }
+        target.write_all(chunk)?;
+        target.write_all(b"\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// The maximum size of the payload of a kitty graphics escape sequence.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Transmits an image as a PNG file with iTerm2's inline image protocol,
+/// which is also supported by WezTerm. The image is stretched by the terminal
+/// to fill the region.
+fn write_iterm2(target: &mut impl Write, placement: &ImagePlacement) -> io::Result<()> {
+    let png = png(&placement.image);
+    write!(
+        target,
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0;doNotMoveCursor=1:{}\x07",
+        png.len(),
+        placement.region.size.width,
+        placement.region.size.height,
+        base64(&png)
+    )
+}
+
+/// Encodes an image as an RGBA PNG file. The pixels are stored without
+/// compression, as the file is only sent to the terminal.
+fn png(image: &Image) -> Vec<u8> {
+    let size = image.size();
+    let mut pixels = Vec::with_capacity((size.width * 4 + 1) * size.height);
+    for row in image.rgba().chunks_exact(size.width * 4) {
+        // Each row starts with its filter type, none
+        pixels.push(0);
+        pixels.extend_from_slice(row);
+    }
+
+    // A zlib stream made of stored deflate blocks
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = pixels.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let length = block.len() as u16;
+        zlib.push(u8::from(blocks.peek().is_none()));
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&pixels).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(size.width as u32).to_be_bytes());
+    header.extend_from_slice(&(size.height as u32).to_be_bytes());
+    // 8 bits per channel, RGBA, default compression, filtering and no
+    // interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", &header), (b"IDAT", &zlib), (b"IEND", &Vec::new())] {
+        png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend_from_slice(kind);
+        png.extend_from_slice(data);
+        let crc = crc32(&png[start..]);
+        png.extend_from_slice(&crc.to_be_bytes());
+    }
+    png
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (!(crc & 1)).wrapping_add(1))
+        })
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    b << 16 | a
+}
+
+/// Encodes an image as sixels using a palette of 216 colours. Pixels which
+/// are mostly transparent are left undrawn.
+fn write_sixel(target: &mut impl Write, image: &Image) -> io::Result<()> {
+    let size = image.size();
+    let colours: Vec<Option<u8>> = image
+        .rgba()
+        .chunks_exact(4)
+        .map(|pixel| {
+            let level = |channel: u8| (channel as u16 * 5 + 127) / 255;
+            if pixel[3] < 128 {
+                None
+            } else {
+                Some((level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2])) as u8)
+            }
+        })
+        .collect();
+
+    write!(target, "\x1bP0;1;0q\"1;1;{};{}", size.width, size.height)?;
+    for colour in colours.iter().flatten().collect::<BTreeSet<_>>() {
+        let percent = |level: u8| level as usize * 100 / 5;
+        write!(
+            target,
+            "#{};2;{};{};{}",
+            colour,
+            percent(colour / 36),
+            percent(colour / 6 % 6),
+            percent(colour % 6)
+        )?;
+    }
+
+    let mut sixels = Vec::with_capacity(size.width);
+    for band in (0..size.height).step_by(6) {
+        let rows = band..cmp::min(band + 6, size.height);
+        let band_colours: BTreeSet<_> = rows
+            .clone()
+            .flat_map(|y| {
+                colours[y * size.width..(y + 1) * size.width]
+                    .iter()
+                    .flatten()
+            })
+            .collect();
+        for &colour in band_colours {
+            sixels.clear();
+            sixels.extend((0..size.width).map(|x| {
+                rows.clone()
+                    .enumerate()
+                    .filter(|&(_, y)| colours[y * size.width + x] == Some(colour))
+                    .fold(0, |bits, (bit, _)| bits | (1 << bit))
+                    + b'?'
+            }));
+            write!(target, "#{}", colour)?;
+            write_run_length_encoded(target, &sixels)?;
+            // Return to the start of the band for the next colour
+            target.write_all(b"$")?;
+        }
+        target.write_all(b"-")?;
+    }
+    target.write_all(b"\x1b\\")
+}
+
+fn write_run_length_encoded(target: &mut impl Write, sixels: &[u8]) -> io::Result<()> {
+    let mut start = 0;
+    while start < sixels.len() {
+        let sixel = sixels[start];
+        let length = sixels[start..]
+            .iter()
+            .take_while(|&&other| other == sixel)
+            .count();
+        if length > 3 {
+            write!(target, "!{}{}", length, sixel as char)?;
+        } else {
+            target.write_all(&sixels[start..start + length])?;
+        }
+        start += length;
+    }
+    Ok(())
+}
+
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |triple, (index, &byte)| {
+                triple | (byte as u32) << (16 - 8 * index)
+            });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zi::terminal::{Position, Rect};
+
+    fn placement(image: Image, width: usize, height: usize) -> ImagePlacement {
+        ImagePlacement {
+            region: Rect::new(Position::new(0, 0), Size::new(width, height)),
+            image,
+        }
+    }
+
+    #[test]
+    fn base64_test_vectors() {
+        // From RFC 4648
+        for (bytes, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(bytes.as_bytes()), encoded);
+        }
+        assert_eq!(base64(&[0xff, 0xfe, 0x00]), "//4A");
+    }
+
+    #[test]
+    fn kitty_payloads_are_chunked() {
+        // 32x32 RGBA pixels are 4096 bytes, 5464 once encoded
+        let image = Image::new(Size::new(32, 32), vec![7; 4096]);
+        let mut output = Vec::new();
+        write_image(
+            &mut output,
+            GraphicsProtocol::Kitty,
+            &placement(image, 4, 2),
+            Size::new(10, 20),
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let header = "\x1b_Ga=T,f=32,s=32,v=32,c=4,r=2,C=1,q=2,m=1;";
+        assert!(output.starts_with(header));
+        let chunks: Vec<_> = output[header.len()..].split("\x1b\\").collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].len(), KITTY_CHUNK_SIZE);
+        assert!(chunks[1].starts_with("\x1b_Gm=0;"));
+        assert_eq!(
+            chunks[1].len() - "\x1b_Gm=0;".len(),
+            5464 - KITTY_CHUNK_SIZE
+        );
+        assert_eq!(chunks[2], "");
+    }
+
+    #[test]
+    fn sixels_are_run_length_encoded() {
+        let encoded = |sixels: &[u8]| {
+            let mut output = Vec::new();
+            write_run_length_encoded(&mut output, sixels).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        assert_eq!(encoded(b"??????AAB"), "!6?AAB");
+        assert_eq!(encoded(b"@@@~~~~"), "@@@!4~");
+        assert_eq!(encoded(b""), "");
+    }
+
+    #[test]
+    fn sixel_images() {
+        // A red pixel above a transparent one, in a band of 6 pixel rows
+        let image = Image::new(Size::new(1, 2), [255, 0, 0, 255, 0, 0, 0, 0]);
+        let mut output = Vec::new();
+        write_sixel(&mut output, &image).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\x1bP0;1;0q\"1;1;1;2#180;2;100;0;0#180@$-\x1b\\"
+        );
+
+        // Regions shorter than 6 pixels aren't drawn
+        let mut output = Vec::new();
+        write_image(
+            &mut output,
+            GraphicsProtocol::Sixel,
+            &placement(image, 1, 1),
+            Size::new(10, 5),
+        )
+        .unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn checksums_test_vectors() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn iterm2_images_are_sent_as_png() {
+        let image = Image::new(Size::new(1, 1), [255, 0, 0, 255]);
+        let png = png(&image);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x01\0\0\0\x01\x08\x06"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+        // One stored block with the filter type and the pixel
+        let idat = png.windows(4).position(|window| window == b"IDAT").unwrap();
+        assert_eq!(
+            &png[idat + 4..idat + 4 + 12],
+            [0x78, 0x01, 1, 5, 0, 0xfa, 0xff, 0, 255, 0, 0, 255]
+        );
+
+        let mut output = Vec::new();
+        write_image(
+            &mut output,
+            GraphicsProtocol::ITerm2,
+            &placement(image, 2, 1),
+            Size::new(10, 20),
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "\x1b]1337;File=inline=1;size={};width=2;height=1;\
+                 preserveAspectRatio=0;doNotMoveCursor=1:{}\x07",
+                png.len(),
+                base64(&png)
+            )
+        );
+    }
+}
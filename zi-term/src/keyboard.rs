@@ -0,0 +1,628 @@
+//! Input with the [kitty keyboard
+//! protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/), which
+//! reports key repeats and releases.
+//!
+//! crossterm 0.23 parses the protocol's `CSI u` sequences but drops the event
+//! types, so while the protocol is enabled stdin is read and parsed here
+//! instead. Terminals without support for the protocol ignore the request to
+//! enable it and keep sending legacy sequences, which are parsed too.
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+/// Pushes the protocol's flags on the terminal's stack: disambiguate escape
+/// codes (1), report event types (2) and report alternate keys (4).
+pub(crate) const ENABLE: &str = "\x1b[>7u";
+
+/// Pops the flags pushed by [`ENABLE`], restoring the previous mode.
+pub(crate) const DISABLE: &str = "\x1b[<u";
+
+/// Whether a key event is a press, a repeat of a held key or a release.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyKind {
+    Press,
+    Repeat,
+    Release,
+}
+
+/// Parses input events from the bytes read from stdin. Sequences split over
+/// several reads are buffered until they are complete.
+#[derive(Debug, Default)]
+pub(crate) struct Parser {
+    buffer: Vec<u8>,
+}
+
+impl Parser {
+    /// Parses the bytes read, returning the events they complete. With
+    /// `more` set, more input is available right away and incomplete
+    /// sequences are kept for the next read. Otherwise a lone `Esc` is the
+    /// `Esc` key, rather than the start of a sequence.
+    pub(crate) fn parse(&mut self, bytes: &[u8], more: bool) -> Vec<(Event, KeyKind)> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        let mut start = 0;
+        while start < self.buffer.len() {
+            match parse_event(&self.buffer[start..]) {
+                Parsed::Event(length, event) => {
+                    events.extend(event);
+                    start += length;
+                }
+                Parsed::Incomplete if more => break,
+                Parsed::Incomplete => {
+                    // An `Esc` followed by a character is typed with alt
+                    let rest = &self.buffer[start..];
+                    let event = match parse_char(&rest[1..]) {
+                        _ if rest.len() == 1 => Some(key(KeyCode::Esc, KeyModifiers::NONE)),
+                        Parsed::Event(length, Some((Event::Key(key), _)))
+                            if rest[0] == ESC && length + 1 == rest.len() =>
+                        {
+                            Some(press(KeyEvent::new(key.code, KeyModifiers::ALT)))
+                        }
+                        _ => {
+                            log::debug!("Dropped an incomplete input sequence {:?}", rest);
+                            None
+                        }
+                    };
+                    events.extend(event);
+                    start = self.buffer.len();
+                }
+            }
+        }
+        self.buffer.drain(..start);
+        events
+    }
+}
+
+enum Parsed {
+    /// The input is the start of a longer sequence.
+    Incomplete,
+    /// The length of a complete sequence and its event, if it's recognised.
+    Event(usize, Option<(Event, KeyKind)>),
+}
+
+const ESC: u8 = 0x1b;
+
+/// The longest sequence parsed, longer ones are dropped.
+const MAX_SEQUENCE_LENGTH: usize = 64;
+
+fn parse_event(buffer: &[u8]) -> Parsed {
+    let event = |length, code| Parsed::Event(length, Some(key(code, KeyModifiers::NONE)));
+    let ctrl = |character: u8| {
+        Parsed::Event(
+            1,
+            Some(key(KeyCode::Char(character as char), KeyModifiers::CONTROL)),
+        )
+    };
+    match buffer[0] {
+        ESC => match buffer.get(1) {
+            None => Parsed::Incomplete,
+            Some(b'[') => parse_csi(buffer),
+            Some(b'O') => match buffer.get(2) {
+                None => Parsed::Incomplete,
+                Some(&final_byte) => Parsed::Event(
+                    3,
+                    legacy_key_code(b"1", final_byte).map(|code| key(code, KeyModifiers::NONE)),
+                ),
+            },
+            Some(&ESC) => event(1, KeyCode::Esc),
+            // Keys typed with alt are prefixed with `Esc`
+            Some(_) => match parse_event(&buffer[1..]) {
+                Parsed::Event(length, Some((Event::Key(key), kind))) => Parsed::Event(
+                    length + 1,
+                    Some((
+                        Event::Key(KeyEvent::new(key.code, key.modifiers | KeyModifiers::ALT)),
+                        kind,
+                    )),
+                ),
+                parsed => parsed,
+            },
+        },
+        b'\r' => event(1, KeyCode::Enter),
+        b'\t' => event(1, KeyCode::Tab),
+        0x7f | 0x08 => event(1, KeyCode::Backspace),
+        0x00 => ctrl(b' '),
+        character @ 0x01..=0x1a => ctrl(character - 0x01 + b'a'),
+        character @ 0x1c..=0x1f => ctrl(character - 0x1c + b'4'),
+        _ => parse_char(buffer),
+    }
+}
+
+/// Parses a UTF-8 encoded character.
+fn parse_char(buffer: &[u8]) -> Parsed {
+    let length = match buffer.first() {
+        None => return Parsed::Incomplete,
+        Some(byte) if byte & 0x80 == 0 => 1,
+        Some(byte) if byte & 0xe0 == 0xc0 => 2,
+        Some(byte) if byte & 0xf0 == 0xe0 => 3,
+        Some(byte) if byte & 0xf8 == 0xf0 => 4,
+        Some(_) => return Parsed::Event(1, None),
+    };
+    if buffer.len() < length {
+        return Parsed::Incomplete;
+    }
+    let event = std::str::from_utf8(&buffer[..length])
+        .ok()
+        .and_then(|text| text.chars().next())
+        .map(|character| key(KeyCode::Char(character), KeyModifiers::NONE));
+    Parsed::Event(length, event)
+}
+
+/// Parses a sequence starting with `CSI`, i.e. `Esc [`.
+fn parse_csi(buffer: &[u8]) -> Parsed {
+    // Legacy mouse reports are followed by three bytes
+    if buffer.get(2) == Some(&b'M') {
+        return if buffer.len() < 6 {
+            Parsed::Incomplete
+        } else {
+            Parsed::Event(6, None)
+        };
+    }
+    let end = match buffer
+        .iter()
+        .skip(2)
+        .position(|byte| (0x40..=0x7e).contains(byte))
+    {
+        Some(position) => position + 2,
+        None if buffer.len() > MAX_SEQUENCE_LENGTH => return Parsed::Event(buffer.len(), None),
+        None => return Parsed::Incomplete,
+    };
+    let parameters = &buffer[2..end];
+    let event = match (parameters.first(), buffer[end]) {
+        (Some(b'<'), b'M' | b'm') => parse_sgr_mouse(&parameters[1..], buffer[end] == b'm'),
+        (_, b'u') => parse_csi_u(parameters),
+        (_, b'~') => parse_numbered_key(parameters),
+        (_, b'Z') => Some(key(KeyCode::BackTab, KeyModifiers::SHIFT)),
+        (_, final_byte) => {
+            let mut fields = parameters.split(|&byte| byte == b';');
+            let number = fields.next().unwrap_or(b"1");
+            legacy_key_code(number, final_byte).map(|code| {
+                let (modifiers, kind) = parse_modifiers(fields.next());
+                (Event::Key(KeyEvent::new(code, modifiers)), kind)
+            })
+        }
+    };
+    Parsed::Event(end + 1, event)
+}
+
+/// Keys sent as `CSI 1 ; modifiers X` or as `SS3 X`.
+fn legacy_key_code(number: &[u8], final_byte: u8) -> Option<KeyCode> {
+    if !number.is_empty() && number != b"1" {
+        return None;
+    }
+    Some(match final_byte {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'S' => KeyCode::F(4),
+        _ => return None,
+    })
+}
+
+/// Keys sent as `CSI number ; modifiers ~`.
+fn parse_numbered_key(parameters: &[u8]) -> Option<(Event, KeyKind)> {
+    let mut fields = parameters.split(|&byte| byte == b';');
+    let code = match parse_number(fields.next()?)? {
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        number @ 11..=15 => KeyCode::F(number as u8 - 10),
+        number @ 17..=21 => KeyCode::F(number as u8 - 11),
+        number @ 23..=26 => KeyCode::F(number as u8 - 12),
+        number @ 28..=29 => KeyCode::F(number as u8 - 15),
+        number @ 31..=34 => KeyCode::F(number as u8 - 17),
+        _ => return None,
+    };
+    let (modifiers, kind) = parse_modifiers(fields.next());
+    Some((Event::Key(KeyEvent::new(code, modifiers)), kind))
+}
+
+/// Keys sent as `CSI code[:shifted[:base]] ; modifiers[:event] u`.
+fn parse_csi_u(parameters: &[u8]) -> Option<(Event, KeyKind)> {
+    let mut fields = parameters.split(|&byte| byte == b';');
+    let mut codes = fields.next()?.split(|&byte| byte == b':');
+    let code = parse_number(codes.next()?)?;
+    let shifted = codes.next().and_then(parse_number);
+    let (mut modifiers, kind) = parse_modifiers(fields.next());
+
+    let code = match code {
+        27 => KeyCode::Esc,
+        13 | 57414 => KeyCode::Enter,
+        9 if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        9 => KeyCode::Tab,
+        127 | 8 => KeyCode::Backspace,
+        // Function keys from F13 onwards and the keypad
+        57376..=57398 => KeyCode::F((code - 57376 + 13) as u8),
+        57399..=57408 => KeyCode::Char(char::from(b'0' + (code - 57399) as u8)),
+        57409 => KeyCode::Char('.'),
+        57410 => KeyCode::Char('/'),
+        57411 => KeyCode::Char('*'),
+        57412 => KeyCode::Char('-'),
+        57413 => KeyCode::Char('+'),
+        57415 => KeyCode::Char('='),
+        57417 => KeyCode::Left,
+        57418 => KeyCode::Right,
+        57419 => KeyCode::Up,
+        57420 => KeyCode::Down,
+        57421 => KeyCode::PageUp,
+        57422 => KeyCode::PageDown,
+        57423 => KeyCode::Home,
+        57424 => KeyCode::End,
+        57425 => KeyCode::Insert,
+        57426 => KeyCode::Delete,
+        // Other functional keys, e.g. the modifiers and media keys
+        57344..=63743 => return None,
+        _ if modifiers.contains(KeyModifiers::SHIFT)
+            && !modifiers.contains(KeyModifiers::CONTROL) =>
+        {
+            // Shifted characters are typed as text, s.t. their repeats and
+            // releases should match the shifted key
+            modifiers.remove(KeyModifiers::SHIFT);
+            let character = char::from_u32(shifted.unwrap_or(code))?;
+            KeyCode::Char(if shifted.is_some() {
+                character
+            } else {
+                character.to_ascii_uppercase()
+            })
+        }
+        _ => KeyCode::Char(char::from_u32(code)?),
+    };
+    Some((Event::Key(KeyEvent::new(code, modifiers)), kind))
+}
+
+/// Parses `modifiers[:event]`, the modifiers are encoded as one plus a bit
+/// mask and the event is 1 for presses, 2 for repeats and 3 for releases.
+fn parse_modifiers(field: Option<&[u8]>) -> (KeyModifiers, KeyKind) {
+    let mut parts = field.unwrap_or(b"").split(|&byte| byte == b':');
+    let mask = parts
+        .next()
+        .and_then(parse_number)
+        .unwrap_or(1)
+        .saturating_sub(1);
+    let kind = match parts.next().and_then(parse_number) {
+        Some(2) => KeyKind::Repeat,
+        Some(3) => KeyKind::Release,
+        _ => KeyKind::Press,
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    if mask & 1 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if mask & 2 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if mask & 4 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    (modifiers, kind)
+}
+
+/// Parses a mouse report in the SGR encoding, `CSI < button ; x ; y M` for
+/// presses and motion, with a trailing `m` instead for releases.
+fn parse_sgr_mouse(parameters: &[u8], released: bool) -> Option<(Event, KeyKind)> {
+    let mut fields = parameters.split(|&byte| byte == b';').map(parse_number);
+    let button = fields.next()??;
+    let column = fields.next()??.checked_sub(1)?;
+    let row = fields.next()??.checked_sub(1)?;
+
+    let mouse_button = match button & 0b11 {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        _ => None,
+    };
+    let kind = match (button & 0b110_0000, mouse_button) {
+        (0b100_0000, _) if button & 1 == 0 => MouseEventKind::ScrollUp,
+        (0b100_0000, _) => MouseEventKind::ScrollDown,
+        (0b010_0000, Some(mouse_button)) => MouseEventKind::Drag(mouse_button),
+        (0b010_0000, None) => MouseEventKind::Moved,
+        (_, Some(mouse_button)) if released => MouseEventKind::Up(mouse_button),
+        (_, Some(mouse_button)) => MouseEventKind::Down(mouse_button),
+        (_, None) => return None,
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    if button & 4 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if button & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if button & 16 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    Some((
+        Event::Mouse(MouseEvent {
+            kind,
+            column: column as u16,
+            row: row as u16,
+            modifiers,
+        }),
+        KeyKind::Press,
+    ))
+}
+
+fn parse_number(digits: &[u8]) -> Option<u32> {
+    std::str::from_utf8(digits).ok()?.parse().ok()
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> (Event, KeyKind) {
+    press(KeyEvent::new(code, modifiers))
+}
+
+fn press(key: KeyEvent) -> (Event, KeyKind) {
+    (Event::Key(key), KeyKind::Press)
+}
+
+#[cfg(unix)]
+pub(crate) use self::reader::KittyEvents;
+
+#[cfg(unix)]
+mod reader {
+    use futures::stream::Stream;
+    use std::{
+        io,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::{Context, Poll},
+        thread::{self, JoinHandle},
+    };
+    use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+    use super::{KeyKind, Parser};
+    use crossterm::event::Event;
+
+    /// The events read from stdin by a background thread, which is stopped
+    /// when they are dropped, s.t. stdin is free for another process while the
+    /// app is suspended.
+    pub(crate) struct KittyEvents {
+        receiver: UnboundedReceiver<io::Result<(Event, KeyKind)>>,
+        stop: Arc<AtomicBool>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl KittyEvents {
+        pub(crate) fn new() -> Self {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread = thread::spawn({
+                let stop = stop.clone();
+                move || {
+                    if let Err(error) = read_events(&sender, &stop) {
+                        let _ = sender.send(Err(error));
+                    }
+                }
+            });
+            Self {
+                receiver,
+                stop,
+                thread: Some(thread),
+            }
+        }
+    }
+
+    impl Stream for KittyEvents {
+        type Item = io::Result<(Event, KeyKind)>;
+
+        fn poll_next(
+            mut self: Pin<&mut Self>,
+            context: &mut Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            self.receiver.poll_recv(context)
+        }
+    }
+
+    impl Drop for KittyEvents {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    fn read_events(
+        sender: &UnboundedSender<io::Result<(Event, KeyKind)>>,
+        stop: &AtomicBool,
+    ) -> io::Result<()> {
+        use signal_hook::{consts::SIGWINCH, iterator::Signals};
+
+        let mut resizes = Signals::new([SIGWINCH])?;
+        let mut parser = Parser::default();
+        let mut buffer = [0; 1024];
+        while !stop.load(Ordering::Relaxed) {
+            if resizes.pending().next().is_some() {
+                let (width, height) = crossterm::terminal::size()?;
+                if sender
+                    .send(Ok((Event::Resize(width, height), KeyKind::Press)))
+                    .is_err()
+                {
+                    return Ok(());
+                }
+            }
+            if !poll_stdin(POLL_TIMEOUT_MS)? {
+                continue;
+            }
+            let length = match unsafe {
+                libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len())
+            } {
+                -1 => match io::Error::last_os_error() {
+                    error if error.kind() == io::ErrorKind::Interrupted => continue,
+                    error => return Err(error),
+                },
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                length => length as usize,
+            };
+            for event in parser.parse(&buffer[..length], poll_stdin(0)?) {
+                if sender.send(Ok(event)).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits up to `timeout` milliseconds for stdin to be readable.
+    fn poll_stdin(timeout: libc::c_int) -> io::Result<bool> {
+        let mut descriptor = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        match unsafe { libc::poll(&mut descriptor, 1, timeout) } {
+            -1 => match io::Error::last_os_error() {
+                error if error.kind() == io::ErrorKind::Interrupted => Ok(false),
+                error => Err(error),
+            },
+            ready => Ok(ready > 0),
+        }
+    }
+
+    /// How often the thread checks whether it should stop.
+    const POLL_TIMEOUT_MS: libc::c_int = 20;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(bytes: &[u8]) -> Vec<(Event, KeyKind)> {
+        Parser::default().parse(bytes, false)
+    }
+
+    fn key_event(code: KeyCode, modifiers: KeyModifiers, kind: KeyKind) -> (Event, KeyKind) {
+        (Event::Key(KeyEvent::new(code, modifiers)), kind)
+    }
+
+    #[test]
+    fn text_and_control_characters() {
+        assert_eq!(
+            parse("aé\r\x7f\x01".as_bytes()),
+            [
+                key(KeyCode::Char('a'), KeyModifiers::NONE),
+                key(KeyCode::Char('é'), KeyModifiers::NONE),
+                key(KeyCode::Enter, KeyModifiers::NONE),
+                key(KeyCode::Backspace, KeyModifiers::NONE),
+                key(KeyCode::Char('a'), KeyModifiers::CONTROL),
+            ]
+        );
+        assert_eq!(
+            parse(b"\x1bx"),
+            [key(KeyCode::Char('x'), KeyModifiers::ALT)]
+        );
+        assert_eq!(parse(b"\x1b"), [key(KeyCode::Esc, KeyModifiers::NONE)]);
+    }
+
+    #[test]
+    fn key_events_with_their_types() {
+        use KeyKind::*;
+
+        assert_eq!(
+            parse(b"\x1b[97;1:2u\x1b[97;1:3u\x1b[27u\x1b[97;5u"),
+            [
+                key_event(KeyCode::Char('a'), KeyModifiers::NONE, Repeat),
+                key_event(KeyCode::Char('a'), KeyModifiers::NONE, Release),
+                key_event(KeyCode::Esc, KeyModifiers::NONE, Press),
+                key_event(KeyCode::Char('a'), KeyModifiers::CONTROL, Press),
+            ]
+        );
+        // Shifted keys are released with the shifted character
+        assert_eq!(
+            parse(b"\x1b[97;2:3u\x1b[49:33;2:3u\x1b[9;2u"),
+            [
+                key_event(KeyCode::Char('A'), KeyModifiers::NONE, Release),
+                key_event(KeyCode::Char('!'), KeyModifiers::NONE, Release),
+                key_event(KeyCode::BackTab, KeyModifiers::SHIFT, Press),
+            ]
+        );
+        // Legacy functional keys with event types
+        assert_eq!(
+            parse(b"\x1b[1;1:3A\x1b[5;3:2~\x1bOP\x1b[57441u"),
+            [
+                key_event(KeyCode::Up, KeyModifiers::NONE, Release),
+                key_event(KeyCode::PageUp, KeyModifiers::ALT, Repeat),
+                key_event(KeyCode::F(1), KeyModifiers::NONE, Press),
+            ]
+        );
+    }
+
+    #[test]
+    fn sgr_mouse_reports() {
+        let mouse = |kind, column, row, modifiers| {
+            (
+                Event::Mouse(MouseEvent {
+                    kind,
+                    column,
+                    row,
+                    modifiers,
+                }),
+                KeyKind::Press,
+            )
+        };
+        assert_eq!(
+            parse(b"\x1b[<0;3;5M\x1b[<32;4;5M\x1b[<0;4;5m\x1b[<65;1;1M\x1b[<18;2;2M"),
+            [
+                mouse(
+                    MouseEventKind::Down(MouseButton::Left),
+                    2,
+                    4,
+                    KeyModifiers::NONE
+                ),
+                mouse(
+                    MouseEventKind::Drag(MouseButton::Left),
+                    3,
+                    4,
+                    KeyModifiers::NONE
+                ),
+                mouse(
+                    MouseEventKind::Up(MouseButton::Left),
+                    3,
+                    4,
+                    KeyModifiers::NONE
+                ),
+                mouse(MouseEventKind::ScrollDown, 0, 0, KeyModifiers::NONE),
+                mouse(
+                    MouseEventKind::Down(MouseButton::Right),
+                    1,
+                    1,
+                    KeyModifiers::CONTROL
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn sequences_split_over_reads() {
+        let mut parser = Parser::default();
+        assert_eq!(parser.parse(b"\x1b[97;", true), []);
+        assert_eq!(parser.parse(b"1:3", true), []);
+        assert_eq!(
+            parser.parse(b"u\x1b", true),
+            [key_event(
+                KeyCode::Char('a'),
+                KeyModifiers::NONE,
+                KeyKind::Release
+            )]
+        );
+        assert_eq!(
+            parser.parse(b"", false),
+            [key(KeyCode::Esc, KeyModifiers::NONE)]
+        );
+
+        // Multi-byte characters split over reads
+        assert_eq!(parser.parse(&"é".as_bytes()[..1], true), []);
+        assert_eq!(
+            parser.parse(&"é".as_bytes()[1..], false),
+            [key(KeyCode::Char('é'), KeyModifiers::NONE)]
+        );
+    }
+}
@@ -1,16 +1,33 @@
 //! A terminal backend implementation for [Zi](https://docs.rs/zi) using
 //! [crossterm](https://docs.rs/crossterm)
+mod capabilities;
+mod clipboard;
 mod error;
+mod graphics;
+mod keyboard;
+mod mouse;
 mod painter;
+mod print;
+mod suspend;
 mod utils;
 
-pub use self::error::{Error, Result};
+pub use crossterm;
 
-use crossterm::{self, queue, QueueableCommand};
-use futures::stream::{Stream, StreamExt};
+pub use self::{
+    error::{Error, Result},
+    mouse::{MouseCapture, MouseCaptureHandle},
+    print::print,
+};
+
+use crossterm::{queue, QueueableCommand};
+use futures::stream::{self, Stream, StreamExt};
 use std::{
-    io::{self, BufWriter, Stdout, Write},
+    borrow::Cow,
+    fs::File,
+    io::{self, BufReader, BufWriter, Stdout, Write},
+    path::Path,
     pin::Pin,
+    sync::Arc,
     time::{Duration, Instant},
 };
 use tokio::{
@@ -20,13 +37,24 @@ use tokio::{
 };
 
 use self::{
+    clipboard::Osc52Clipboard,
+    keyboard::KeyKind,
+    mouse::SetMouseCapture,
     painter::{FullPainter, IncrementalPainter, PaintOperation, Painter},
+    suspend::SuspendSignals,
     utils::MeteredWriter,
 };
 use zi::{
-    app::{App, ComponentMessage, MessageSender},
-    terminal::{Canvas, Colour, Key, Size, Style},
-    Layout,
+    app::{App, ComponentMessage, KeyTiming, MessageSender},
+    backend::{self, Backend, BackendEvent},
+    recording::{RecordedEvent, Recording, RecordingWriter, Replay},
+    terminal::{
+        BackendCapabilities, BaseColour, Canvas, Colour, ColourSupport, CursorShape, Event,
+        Grapheme, GraphicsProtocol, Key, Modifiers, MouseButton, MouseEvent, MouseEventKind,
+        Position, Size, Style,
+    },
+    unicode_segmentation::UnicodeSegmentation,
+    KeyPattern, KeymapOverrides, Layout,
 };
 
 /// Creates a new backend with an incremental painter. It only draws those
@@ -74,15 +102,57 @@ pub struct Crossterm<PainterT: Painter = IncrementalPainter> {
     target: MeteredWriter<BufWriter<Stdout>>,
     painter: PainterT,
     events: Option<EventStream>,
+    event_filter: Option<EventFilter>,
+    /// Whether the kitty keyboard protocol is enabled, see
+    /// [`kitty_keyboard`](#method.kitty_keyboard).
+    kitty_keyboard: bool,
     link: LinkChannel,
+    capabilities: BackendCapabilities,
+    mouse_capture: MouseCapture,
+    mouse_capture_channel: MouseCaptureChannel,
+    /// The OSC 52 sequences of the text copied to the clipboard, which are
+    /// written to the terminal by the event loop.
+    clipboard_channel: ClipboardChannel,
+    resize_debounce: Duration,
+    input_recorder: Option<RecordingWriter<BufWriter<File>>>,
+    /// The recording to replay when the event loop starts.
+    replay: Option<Replay>,
+    global_bindings: Vec<GlobalBinding>,
+    keymap_overrides: KeymapOverrides,
+    key_timing: KeyTiming,
+    leader: Option<Key>,
+    suspend_key: Option<Key>,
+    /// Requests to suspend the process, from the suspend key or `SIGTSTP`.
+    suspend_channel: SuspendChannel,
+    /// Forwards `SIGTSTP` to the suspend channel while the event loop runs.
+    suspend_signals: Option<SuspendSignals>,
+    /// Runs the event stream and the futures spawned by components.
+    runtime: Runtime,
+    /// The lines the app is drawn in below the prompt, if it's rendered
+    /// inline rather than in the alternate screen.
+    inline: Option<InlineViewport>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct InlineViewport {
+    height: usize,
+    /// The row of the terminal the viewport starts at.
+    row: u16,
 }
 
+type GlobalBinding = (
+    Cow<'static, str>,
+    KeyPattern,
+    Arc<dyn Fn(&mut App) + Send + Sync>,
+);
+
 impl<PainterT: Painter> Crossterm<PainterT> {
     /// Create a new backend instance.
     ///
     /// This method initialises the underlying tty device, enables raw mode,
-    /// hides the cursor and enters alternative screen mode. Additionally, an
-    /// async event stream with input events from stdin is started.
+    /// hides the cursor, enters alternative screen mode and captures the
+    /// mouse. Additionally, an async event stream with input events from stdin
+    /// is started.
     pub fn new() -> Result<Self> {
         let mut backend = Self {
             target: MeteredWriter::new(BufWriter::with_capacity(1 << 20, io::stdout())),
@@ -90,13 +160,388 @@ impl<PainterT: Painter> Crossterm<PainterT> {
                 crossterm::terminal::size()
                     .map(|(width, height)| Size::new(width as usize, height as usize))?,
             ),
-            events: Some(new_event_stream()),
+            events: Some(new_event_stream(None)),
+            event_filter: None,
+            kitty_keyboard: false,
             link: LinkChannel::new(),
+            capabilities: capabilities::detect(),
+            mouse_capture: MouseCapture::default(),
+            mouse_capture_channel: MouseCaptureChannel::new(),
+            clipboard_channel: ClipboardChannel::new(),
+            resize_debounce: Duration::ZERO,
+            input_recorder: None,
+            replay: None,
+            global_bindings: Vec::new(),
+            keymap_overrides: KeymapOverrides::new(),
+            key_timing: KeyTiming::default(),
+            leader: None,
+            suspend_key: if cfg!(unix) {
+                Some(Key::Ctrl('z'))
+            } else {
+                None
+            },
+            suspend_channel: SuspendChannel::new(),
+            suspend_signals: None,
+            runtime: RuntimeBuilder::new_current_thread().enable_all().build()?,
+            inline: None,
         };
-        initialise_tty::<PainterT, _>(&mut backend.target)?;
+        initialise_tty::<PainterT, _>(
+            &mut backend.target,
+            backend.mouse_capture,
+            backend.capabilities.colours,
+            None,
+            false,
+        )?;
         Ok(backend)
     }
 
+    /// Renders the app inline, in a viewport of `height` lines below the
+    /// shell prompt like `fzf`, instead of in the alternate screen. The
+    /// scrollback of the terminal is kept intact, the lines above are
+    /// scrolled up if the viewport doesn't fit below the prompt. The viewport
+    /// is cleared when the app exits, leaving the cursor at its top.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .inline(10)?
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn inline(mut self, height: usize) -> Result<Self> {
+        self.suspend()?;
+        let row = initialise_tty::<PainterT, _>(
+            &mut self.target,
+            self.mouse_capture,
+            self.capabilities.colours,
+            Some(height),
+            self.kitty_keyboard,
+        )?;
+        self.events = Some(self.new_event_stream());
+        self.inline = Some(InlineViewport { height, row });
+        self.painter = PainterT::create(self.size()?);
+        Ok(self)
+    }
+
+    /// Sets which mouse events are captured, e.g. to only report clicks or to
+    /// leave the mouse to the terminal s.t. text can be selected natively.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// use zi_term::MouseCapture;
+    ///
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .mouse_capture(MouseCapture::Buttons)?
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn mouse_capture(mut self, capture: MouseCapture) -> Result<Self> {
+        self.set_mouse_capture(capture)?;
+        Ok(self)
+    }
+
+    /// Changes which mouse events are captured. While the event loop is
+    /// running, use a [`MouseCaptureHandle`](struct.MouseCaptureHandle.html)
+    /// instead.
+    pub fn set_mouse_capture(&mut self, capture: MouseCapture) -> Result<()> {
+        self.mouse_capture = capture;
+        queue!(self.target, SetMouseCapture(capture))?;
+        self.target.flush()?;
+        Ok(())
+    }
+
+    /// Returns the current mouse capture.
+    pub fn mouse_capture_mode(&self) -> MouseCapture {
+        self.mouse_capture
+    }
+
+    /// Returns the capabilities of the terminal, which are guessed from the
+    /// environment variables set by terminal emulators. Components read them
+    /// with [`ComponentLink::capabilities`](https://docs.rs/zi/latest/zi/struct.ComponentLink.html#method.capabilities).
+    pub fn capabilities(&self) -> BackendCapabilities {
+        self.capabilities
+            .mouse(self.mouse_capture != MouseCapture::Disabled)
+    }
+
+    /// Overrides the graphics protocol used to draw images, which is otherwise
+    /// guessed from the environment. Images are drawn with the kitty graphics
+    /// protocol, iTerm2's inline images or with sixels. Without a protocol, or
+    /// with one that isn't supported, they are drawn with block characters.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// use zi::terminal::GraphicsProtocol;
+    ///
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .graphics(Some(GraphicsProtocol::Sixel))
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn graphics(mut self, protocol: Option<GraphicsProtocol>) -> Self {
+        self.capabilities = self.capabilities.graphics(protocol);
+        self
+    }
+
+    /// Returns a handle which can change the mouse capture while the event
+    /// loop is running. It can be cloned and passed to components.
+    pub fn mouse_capture_handle(&self) -> MouseCaptureHandle {
+        MouseCaptureHandle(self.mouse_capture_channel.sender.clone())
+    }
+
+    /// Sets how long to wait for more resize events before redrawing, which
+    /// coalesces the bursts of events sent while a terminal window is being
+    /// dragged into a single relayout. The final size is applied once no
+    /// resize event arrived within the window, or at the latest after a
+    /// short, fixed latency under sustained input. By default resizes are
+    /// applied immediately.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .resize_debounce(Duration::from_millis(30))
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn resize_debounce(mut self, window: Duration) -> Self {
+        self.resize_debounce = window;
+        self
+    }
+
+    /// Installs a filter over the raw crossterm events, before they are
+    /// converted to Zi events. The filter can drop an event by returning
+    /// `None` or replace it with another one, e.g. to remap keys sent by a
+    /// terminal multiplexer. Filters installed by successive calls are applied
+    /// in order.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// use zi_term::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    ///
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         // Treat F1 as C-h
+    ///         .event_filter(|event| match event {
+    ///             Event::Key(KeyEvent { code: KeyCode::F(1), .. }) => Some(Event::Key(
+    ///                 KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL),
+    ///             )),
+    ///             event => Some(event),
+    ///         })
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn event_filter(
+        mut self,
+        filter: impl Fn(crossterm::event::Event) -> Option<crossterm::event::Event>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        let filter: EventFilter = match self.event_filter.take() {
+            Some(previous) => Arc::new(move |event| previous(event).and_then(&filter)),
+            None => Arc::new(filter),
+        };
+        self.event_filter = Some(filter);
+        if self.events.take().is_some() {
+            self.events = Some(self.new_event_stream());
+        }
+        self
+    }
+
+    /// Enables the [kitty keyboard
+    /// protocol](https://sw.kovidgoyal.net/kitty/keyboard-protocol/), s.t.
+    /// terminals which support it report key repeats and releases as
+    /// [`Event::KeyRepeat`](https://docs.rs/zi/latest/zi/terminal/enum.Event.html#variant.KeyRepeat)
+    /// and
+    /// [`Event::KeyRelease`](https://docs.rs/zi/latest/zi/terminal/enum.Event.html#variant.KeyRelease).
+    /// It also tells apart keys which are otherwise sent as the same bytes,
+    /// e.g. `Esc` from `C-[`. Terminals without support for the protocol
+    /// ignore it and keep reporting repeats as presses.
+    ///
+    /// As crossterm can't parse the protocol, stdin is read and parsed by the
+    /// backend while it's enabled. It's only supported on Unix, elsewhere it
+    /// does nothing.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .kitty_keyboard(true)?
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn kitty_keyboard(mut self, enable: bool) -> Result<Self> {
+        if !cfg!(unix) || enable == self.kitty_keyboard {
+            return Ok(self);
+        }
+        let events = self.events.take();
+        self.target.write_all(if enable {
+            keyboard::ENABLE.as_bytes()
+        } else {
+            keyboard::DISABLE.as_bytes()
+        })?;
+        self.target.flush()?;
+        self.kitty_keyboard = enable;
+        if events.is_some() {
+            self.events = Some(self.new_event_stream());
+        }
+        Ok(self)
+    }
+
+    /// Binds keys to an app-level command which runs regardless of the
+    /// focused component, see
+    /// [`App::add_global_binding`](https://docs.rs/zi/latest/zi/app/struct.App.html#method.add_global_binding).
+    ///
+    /// ```no_run
+    /// # use zi::{app::App, prelude::*};
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .global_binding("exit", [Key::Ctrl('x'), Key::Ctrl('c')], App::exit)
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn global_binding(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        keys: impl Into<KeyPattern>,
+        command: impl Fn(&mut App) + Send + Sync + 'static,
+    ) -> Self {
+        self.global_bindings
+            .push((name.into(), keys.into(), Arc::new(command)));
+        self
+    }
+
+    /// Overrides the key bindings of components' named commands, e.g. with
+    /// the user's keybindings loaded from a config file, see
+    /// [`App::set_keymap_overrides`](https://docs.rs/zi/latest/zi/app/struct.App.html#method.set_keymap_overrides).
+    ///
+    /// ```no_run
+    /// # use zi::{prelude::*, KeymapOverrides};
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     let mut overrides = KeymapOverrides::new();
+    ///     overrides.bind("Pager", "line-down", ["x".parse().unwrap()]);
+    ///     zi_term::incremental()?
+    ///         .keymap_overrides(overrides)
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn keymap_overrides(mut self, overrides: KeymapOverrides) -> Self {
+        self.keymap_overrides = overrides;
+        self
+    }
+
+    /// Sets the thresholds used to detect double presses and press-and-hold
+    /// and the timeout of pending key sequences, see
+    /// [`KeyTiming`](https://docs.rs/zi/latest/zi/app/struct.KeyTiming.html).
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use zi::{app::KeyTiming, prelude::*};
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .key_timing(KeyTiming::default().prefix_timeout(Duration::from_secs(1)))
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn key_timing(mut self, timing: KeyTiming) -> Self {
+        self.key_timing = timing;
+        self
+    }
+
+    /// Sets the leader key, which bindings refer to as `<leader>`, see
+    /// [`App::set_leader`](https://docs.rs/zi/latest/zi/app/struct.App.html#method.set_leader).
+    ///
+    /// ```no_run
+    /// # use zi::{app::App, prelude::*, KeyPattern};
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .leader(Key::Char(' '))
+    ///         .global_binding("exit", KeyPattern::leader([Key::Char('q')]), App::exit)
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn leader(mut self, leader: Key) -> Self {
+        self.leader = Some(leader);
+        self
+    }
+
+    /// Sets the key which suspends the app to the background of the shell,
+    /// `C-z` by default on Unix, or `None` to disable it. The terminal is
+    /// restored while the app is suspended and repainted once it's continued,
+    /// e.g. with `fg`. The app is suspended the same way on `SIGTSTP`.
+    ///
+    /// The key is bound globally, s.t. the bindings of components take
+    /// precedence, e.g. an editor may bind `C-z` to undo. It isn't bound if
+    /// there is a global binding for it already.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .suspend_key(Some(Key::Ctrl('s')))
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn suspend_key(mut self, key: Option<Key>) -> Self {
+        self.suspend_key = key;
+        self
+    }
+
+    /// Records the input events and resizes handled by the app to a file as
+    /// they happen, e.g. to reproduce a bug. The file is overwritten and can
+    /// be replayed with [`replay_input`](#method.replay_input), see the
+    /// [`zi::recording`](https://docs.rs/zi/latest/zi/recording/index.html)
+    /// module for its format.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .record_input("session.zirec")?
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn record_input(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.input_recorder = Some(RecordingWriter::new(BufWriter::new(File::create(path)?))?);
+        Ok(self)
+    }
+
+    /// Replays the input events and resizes recorded in a file by
+    /// [`record_input`](#method.record_input), at their original pace from
+    /// when the event loop starts. Input from the terminal is ignored until
+    /// the replay is finished, then the app carries on as usual.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     zi_term::incremental()?
+    ///         .replay_input("session.zirec")?
+    ///         .run_event_loop(Text::with(TextProperties::new().content("Hello, world!")))
+    /// }
+    /// ```
+    pub fn replay_input(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let recording = Recording::read(BufReader::new(File::open(path)?))?;
+        self.replay = Some(Replay::new(&recording));
+        Ok(self)
+    }
+
     /// Starts the event loop. This is the main entry point of a Zi application.
     /// It draws and presents the components to the backend, handles user input
     /// and delivers messages to components. This method returns either when
@@ -112,41 +557,50 @@ impl<PainterT: Painter> Crossterm<PainterT> {
     /// }
     /// ```
     pub fn run_event_loop(&mut self, layout: Layout) -> Result<()> {
-        let mut tokio_runtime = RuntimeBuilder::new_current_thread().enable_all().build()?;
-        let mut app = App::new(
-            UnboundedMessageSender(self.link.sender.clone()),
-            self.size()?,
-            layout,
-        );
-
-        while !app.poll_state().exit() {
-            let canvas = app.draw();
-
-            let last_drawn = Instant::now();
-            let num_bytes_presented = self.present(canvas)?;
-            let presented_time = last_drawn.elapsed();
-
-            log::debug!(
-                "Frame: pres {:.1}ms diff {}b",
-                presented_time.as_secs_f64() * 1000.0,
-                num_bytes_presented,
-            );
-
-            self.poll_events_batch(&mut tokio_runtime, &mut app, last_drawn)?;
-        }
+        self.run_with_exit_value::<()>(layout).map(|_| ())
+    }
 
-        Ok(())
+    /// Starts the event loop like [`run_event_loop`](#method.run_event_loop),
+    /// returning the value passed to
+    /// [`ComponentLink::exit_with`](struct.ComponentLink.html#method.exit_with)
+    /// by the component which stopped the app, e.g. the file selected in a
+    /// picker.
+    ///
+    /// Returns `None` if the app was stopped with `ComponentLink::exit` or
+    /// with a value which isn't a `ValueT`.
+    ///
+    /// ```no_run
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// fn main() -> zi_term::Result<()> {
+    ///     let selected: Option<String> = zi_term::incremental()?
+    ///         .run_with_exit_value(Text::with(TextProperties::new().content("Pick a file")))?;
+    ///     println!("{:?}", selected);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn run_with_exit_value<ValueT: 'static>(
+        &mut self,
+        layout: Layout,
+    ) -> Result<Option<ValueT>> {
+        let exit_value = backend::run_with_exit_value(self, layout);
+        // `SIGTSTP` stops the process as usual once the app is done
+        self.suspend_signals = None;
+        exit_value
     }
 
-    /// Suspends the event stream.
+    /// Suspends the event stream and restores the terminal to its normal
+    /// state, i.e. leaves the alternative screen and disables raw mode.
     ///
     /// This is used when running something that needs exclusive access to the underlying
     /// terminal (i.e. to stdin and stdout). For example spawning an external editor to collect
-    /// or display text. The `resume` function is called upon returning to the application.
+    /// or display text, see `ComponentLink::run_exclusive`. The `resume` function is called
+    /// upon returning to the application.
     #[inline]
     pub fn suspend(&mut self) -> Result<()> {
         self.events = None;
-        Ok(())
+        let inline_row = self.inline_row();
+        restore_tty(&mut self.target, inline_row, self.kitty_keyboard)
     }
 
     /// Recreates the event stream and reinitialises the underlying terminal.
@@ -161,125 +615,286 @@ impl<PainterT: Painter> Crossterm<PainterT> {
     /// to restore the previous terminal content on exit.
     #[inline]
     pub fn resume(&mut self) -> Result<()> {
+        let row = initialise_tty::<PainterT, _>(
+            &mut self.target,
+            self.mouse_capture,
+            self.capabilities.colours,
+            self.inline.map(|viewport| viewport.height),
+            self.kitty_keyboard,
+        )?;
+        // Started after the terminal is initialised, as stdin is read for the
+        // cursor position of an inline viewport
+        self.events = Some(self.new_event_stream());
+        if let Some(ref mut viewport) = self.inline {
+            viewport.row = row;
+        }
         self.painter = PainterT::create(self.size()?);
-        self.events = Some(new_event_stream());
-        initialise_tty::<PainterT, _>(&mut self.target)
+        Ok(())
     }
 
-    /// Poll as many events as we can respecting REDRAW_LATENCY and REDRAW_LATENCY_SUSTAINED_IO
+    /// Suspends the process to the background of the shell, restoring the
+    /// terminal until the process is continued. The screen is repainted in
+    /// full afterwards, as it was overwritten in the meantime.
+    fn suspend_process(&mut self) -> Result<()> {
+        self.suspend()?;
+        suspend::stop_process()?;
+        self.resume()
+    }
+
+    /// The row the viewport starts at, if the app is rendered inline.
     #[inline]
-    fn poll_events_batch(
-        &mut self,
-        runtime: &mut Runtime,
-        app: &mut App,
-        last_drawn: Instant,
-    ) -> Result<()> {
-        let Self {
-            ref mut link,
-            ref mut events,
-            ..
-        } = *self;
-        let mut force_redraw = false;
-        let mut first_event_time: Option<Instant> = None;
-
-        while !force_redraw && !app.poll_state().exit() {
-            let timeout_duration = {
-                let since_last_drawn = last_drawn.elapsed();
-                if app.poll_state().dirty() && since_last_drawn >= REDRAW_LATENCY {
-                    Duration::from_millis(0)
-                } else if app.poll_state().dirty() {
-                    REDRAW_LATENCY - since_last_drawn
-                } else {
-                    Duration::from_millis(if app.is_tickable() { 60 } else { 60_000 })
-                }
-            };
-            (runtime.block_on(async {
-                tokio::select! {
-                    link_message = link.receiver.recv() => {
-                        app.handle_message(
-                            link_message.expect("at least one sender exists"),
-                        );
-                        Ok(())
-                    }
-                    input_event = events.as_mut().expect("backend events are suspended").next() => {
-                        match input_event.expect(
-                            "at least one sender exists",
-                        )? {
-                            FilteredEvent::Input(input_event) => app.handle_input(input_event),
-                            FilteredEvent::Resize(size) => app.handle_resize(size),
-                        };
-                        force_redraw = app.poll_state().dirty()
-                            && (first_event_time.get_or_insert_with(Instant::now).elapsed()
-                                >= SUSTAINED_IO_REDRAW_LATENCY
-                                || app.poll_state().resized());
-                        Ok(())
-                    }
-                    _ = tokio::time::sleep(timeout_duration) => {
-                        app.tick();
-                        force_redraw = true;
-                        Ok(())
-                    }
-                }
-            }) as Result<()>)?;
-        }
+    fn inline_row(&self) -> Option<u16> {
+        self.inline.map(|viewport| viewport.row)
+    }
 
-        Ok(())
+    /// Creates a stream of the input events, parsed by crossterm unless the
+    /// kitty keyboard protocol is enabled.
+    fn new_event_stream(&self) -> EventStream {
+        let filter = self.event_filter.clone();
+        #[cfg(unix)]
+        if self.kitty_keyboard {
+            return new_kitty_event_stream(filter);
+        }
+        new_event_stream(filter)
     }
+}
+
+impl<PainterT: Painter> Backend for Crossterm<PainterT> {
+    type Error = Error;
+    type Sender = Box<dyn MessageSender>;
 
-    /// Returns the size of the underlying terminal.
+    /// Returns the size of the underlying terminal, or of the viewport if the
+    /// app is rendered inline.
     #[inline]
     fn size(&self) -> Result<Size> {
-        Ok(crossterm::terminal::size()
-            .map(|(width, height)| Size::new(width as usize, height as usize))?)
+        let size = crossterm::terminal::size()
+            .map(|(width, height)| Size::new(width as usize, height as usize))?;
+        Ok(match self.inline {
+            Some(viewport) => Size::new(size.width, viewport.height.min(size.height)),
+            None => size,
+        })
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        Crossterm::capabilities(self)
+    }
+
+    fn message_sender(&self) -> Self::Sender {
+        Box::new(UnboundedMessageSender(self.link.sender.clone()))
+    }
+
+    fn setup(&mut self, app: &mut App) -> Result<()> {
+        app.set_clipboard(Osc52Clipboard::new(self.clipboard_channel.sender.clone()));
+        // Futures spawned by components run on the event loop's runtime,
+        // while it waits for events
+        let handle = self.runtime.handle().clone();
+        app.set_executor(move |task| {
+            handle.spawn(task);
+        });
+        app.set_keymap_overrides(self.keymap_overrides.clone());
+        app.set_key_timing(self.key_timing);
+        app.set_leader(self.leader);
+        for (name, keys, command) in self.global_bindings.iter() {
+            let command = command.clone();
+            app.add_global_binding(name.clone(), keys.clone(), move |app| command(app));
+        }
+        self.suspend_signals = Some(SuspendSignals::forward(
+            self.suspend_channel.sender.clone(),
+        )?);
+        if let Some(key) = self.suspend_key {
+            if app.global_keymap().check_sequence(&[key]).is_none() {
+                let sender = self.suspend_channel.sender.clone();
+                app.add_global_binding("suspend", [key], move |_| {
+                    let _ = sender.send(());
+                });
+            }
+        }
+        Ok(())
     }
 
     /// Draws the [`Canvas`](../terminal/struct.Canvas.html) to the terminal.
     #[inline]
     fn present(&mut self, canvas: &Canvas) -> Result<usize> {
+        // The offset of the canvas in the terminal
+        let top = self.inline_row().unwrap_or(0);
         let Self {
             ref mut target,
             ref mut painter,
+            ref capabilities,
+            ref mut clipboard_channel,
             ..
         } = *self;
+        // Copied text is written before the frame and flushed with it
+        clipboard_channel.write_to(target)?;
+        let cell_pixel_size = capabilities
+            .cell_pixel_size
+            .unwrap_or(DEFAULT_CELL_PIXEL_SIZE);
         let initial_num_bytes_written = target.num_bytes_written();
         painter.paint(canvas, |operation| {
             match operation {
                 PaintOperation::WriteContent(grapheme) => {
                     queue!(target, crossterm::style::Print(grapheme))?
                 }
-                PaintOperation::SetStyle(style) => queue_set_style(target, style)?,
+                PaintOperation::SetStyle(style) => {
+                    queue_set_style(target, style, capabilities.colours)?
+                }
                 PaintOperation::MoveTo(position) => queue!(
                     target,
-                    crossterm::cursor::MoveTo(position.x as u16, position.y as u16)
+                    crossterm::cursor::MoveTo(position.x as u16, top + position.y as u16)
                 )?, // Go to the begining of line (`MoveTo` uses 0-based indexing)
+                PaintOperation::SetHyperlink(link) => {
+                    // Terminals without support for OSC 8 may print the escape
+                    // sequence, the text is drawn without the link instead
+                    if capabilities.hyperlinks {
+                        write!(target, "\x1b]8;;{}\x1b\\", link.unwrap_or(""))?;
+                    }
+                }
+                PaintOperation::SetCursor(None) => queue!(target, crossterm::cursor::Hide)?,
+                PaintOperation::SetCursor(Some(cursor)) => {
+                    use crossterm::cursor::{CursorShape as Shape, MoveTo, SetCursorShape, Show};
+                    let shape = match cursor.shape {
+                        CursorShape::Block => Shape::Block,
+                        CursorShape::Bar => Shape::Line,
+                        CursorShape::Underline => Shape::UnderScore,
+                    };
+                    let position = cursor.position;
+                    queue!(
+                        target,
+                        MoveTo(position.x as u16, top + position.y as u16),
+                        SetCursorShape(shape),
+                        Show
+                    )?
+                }
+                PaintOperation::ClearImages => {
+                    if let Some(protocol) = capabilities.graphics {
+                        graphics::clear_images(target, protocol)?;
+                    }
+                }
+                PaintOperation::DrawImage(placement) => {
+                    if let Some(protocol) = capabilities.graphics {
+                        let origin = placement.region.origin;
+                        queue!(
+                            target,
+                            crossterm::cursor::MoveTo(origin.x as u16, top + origin.y as u16)
+                        )?;
+                        graphics::write_image(target, protocol, placement, cell_pixel_size)?;
+                    }
+                }
             }
             Ok(())
         })?;
         target.flush()?;
         Ok(target.num_bytes_written() - initial_num_bytes_written)
     }
+
+    fn poll_event(&mut self, timeout: Duration) -> Result<Option<BackendEvent>> {
+        let Self {
+            ref runtime,
+            ref mut target,
+            ref mut link,
+            ref mut events,
+            ref capabilities,
+            ref mut mouse_capture,
+            ref mut mouse_capture_channel,
+            ref mut clipboard_channel,
+            ref mut suspend_channel,
+            ref mut inline,
+            ..
+        } = *self;
+        // Text copied while handling the last event
+        clipboard_channel.write_to(target)?;
+        target.flush()?;
+
+        let mut suspend = false;
+        let event = runtime.block_on(async {
+            tokio::select! {
+                link_message = link.receiver.recv() => {
+                    Ok(Some(BackendEvent::Message(
+                        link_message.expect("at least one sender exists"),
+                    )))
+                }
+                capture = mouse_capture_channel.receiver.recv() => {
+                    *mouse_capture = capture.expect("the backend owns a sender");
+                    queue!(target, SetMouseCapture(*mouse_capture))?;
+                    target.flush()?;
+                    Ok(Some(BackendEvent::Capabilities(
+                        capabilities.mouse(*mouse_capture != MouseCapture::Disabled),
+                    )))
+                }
+                request = suspend_channel.receiver.recv() => {
+                    request.expect("the backend owns a sender");
+                    suspend = true;
+                    Ok(None)
+                }
+                input_event = events.as_mut().expect("backend events are suspended").next() => {
+                    match input_event.expect("at least one sender exists")? {
+                        FilteredEvent::Input(input_event) => {
+                            Ok(Some(BackendEvent::Input(input_event)))
+                        }
+                        FilteredEvent::Resize(size) => {
+                            // An inline viewport keeps its height, moving up
+                            // if the terminal got shorter
+                            let size = match inline {
+                                Some(viewport) => {
+                                    let height = viewport.height.min(size.height);
+                                    viewport.row =
+                                        viewport.row.min((size.height - height) as u16);
+                                    Size::new(size.width, height)
+                                }
+                                None => size,
+                            };
+                            Ok(Some(BackendEvent::Resize(size)))
+                        }
+                    }
+                }
+                _ = tokio::time::sleep(timeout) => Ok(None),
+            }
+        }) as Result<Option<BackendEvent>>;
+        let event = event?;
+
+        if suspend {
+            // The app is resized to repaint it in full
+            self.suspend_process()?;
+            return Ok(Some(BackendEvent::Resize(self.size()?)));
+        }
+        Ok(event)
+    }
+
+    fn suspend(&mut self) -> Result<()> {
+        Crossterm::suspend(self)
+    }
+
+    fn resume(&mut self) -> Result<()> {
+        Crossterm::resume(self)
+    }
+
+    fn resize_debounce(&self) -> Duration {
+        self.resize_debounce
+    }
+
+    fn record(&mut self, event: &RecordedEvent) -> Result<()> {
+        if let Some(ref mut recorder) = self.input_recorder {
+            recorder.write(event)?;
+        }
+        Ok(())
+    }
+
+    fn take_replay(&mut self) -> Option<Replay> {
+        self.replay.take()
+    }
 }
 
 impl<PainterT: Painter> Drop for Crossterm<PainterT> {
     fn drop(&mut self) {
-        queue!(
-            self.target,
-            crossterm::style::ResetColor,
-            crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
-            crossterm::cursor::Show,
-            crossterm::terminal::LeaveAlternateScreen
-        )
-        .expect("Failed to clear screen when closing `crossterm` backend");
-        crossterm::terminal::disable_raw_mode()
-            .expect("Failed to disable raw mode when closing `crossterm` backend");
-        self.target
-            .flush()
-            .expect("Failed to flush when closing `crossterm` backend");
+        let inline_row = self.inline_row();
+        restore_tty(&mut self.target, inline_row, self.kitty_keyboard)
+            .expect("Failed to restore the terminal when closing `crossterm` backend");
     }
 }
 
-const REDRAW_LATENCY: Duration = Duration::from_millis(10);
-const SUSTAINED_IO_REDRAW_LATENCY: Duration = Duration::from_millis(100);
+/// The size of a cell in pixels used to scale sixel images when the
+/// terminal doesn't report it.
+const DEFAULT_CELL_PIXEL_SIZE: Size = Size::new(10, 20);
 
 struct LinkChannel {
     sender: UnboundedSender<ComponentMessage>,
@@ -293,6 +908,50 @@ impl LinkChannel {
     }
 }
 
+struct SuspendChannel {
+    sender: UnboundedSender<()>,
+    receiver: UnboundedReceiver<()>,
+}
+
+impl SuspendChannel {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self { sender, receiver }
+    }
+}
+
+struct MouseCaptureChannel {
+    sender: UnboundedSender<MouseCapture>,
+    receiver: UnboundedReceiver<MouseCapture>,
+}
+
+impl MouseCaptureChannel {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self { sender, receiver }
+    }
+}
+
+struct ClipboardChannel {
+    sender: UnboundedSender<String>,
+    receiver: UnboundedReceiver<String>,
+}
+
+impl ClipboardChannel {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self { sender, receiver }
+    }
+
+    /// Queues the sequences of the text copied since last called.
+    fn write_to(&mut self, target: &mut impl Write) -> Result<()> {
+        while let Ok(sequence) = self.receiver.try_recv() {
+            target.write_all(sequence.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 struct UnboundedMessageSender(UnboundedSender<ComponentMessage>);
 
@@ -309,26 +968,100 @@ impl MessageSender for UnboundedMessageSender {
     }
 }
 
-#[inline]
-fn initialise_tty<PainterT: Painter, TargetT: Write>(target: &mut TargetT) -> Result<()> {
-    target
-        .queue(crossterm::terminal::EnterAlternateScreen)?
-        .queue(crossterm::cursor::Hide)?;
-    crossterm::terminal::enable_raw_mode()?;
-    queue_set_style(target, &PainterT::INITIAL_STYLE)?;
+/// Restores the terminal to its normal state. An inline viewport starting at
+/// `inline_row` is cleared, otherwise the alternate screen is left.
+fn restore_tty<TargetT: Write>(
+    target: &mut TargetT,
+    inline_row: Option<u16>,
+    kitty_keyboard: bool,
+) -> Result<()> {
+    use crossterm::terminal::{Clear, ClearType};
+
+    if kitty_keyboard {
+        target.write_all(keyboard::DISABLE.as_bytes())?;
+    }
+    queue!(target, crossterm::style::ResetColor)?;
+    match inline_row {
+        Some(row) => queue!(
+            target,
+            crossterm::cursor::MoveTo(0, row),
+            Clear(ClearType::FromCursorDown)
+        )?,
+        None => queue!(target, Clear(ClearType::All))?,
+    }
+    queue!(
+        target,
+        crossterm::cursor::Show,
+        SetMouseCapture(MouseCapture::Disabled)
+    )?;
+    if inline_row.is_none() {
+        queue!(target, crossterm::terminal::LeaveAlternateScreen)?;
+    }
+    crossterm::terminal::disable_raw_mode()?;
     target.flush()?;
     Ok(())
 }
 
+/// Prepares the terminal to draw the app, in the alternate screen or in an
+/// inline viewport of `inline_height` lines below the cursor. Returns the row
+/// the viewport starts at.
 #[inline]
-fn queue_set_style(target: &mut impl Write, style: &Style) -> Result<()> {
-    use crossterm::style::{
-        Attribute, Color, SetAttribute, SetBackgroundColor, SetForegroundColor,
+fn initialise_tty<PainterT: Painter, TargetT: Write>(
+    target: &mut TargetT,
+    mouse_capture: MouseCapture,
+    colours: ColourSupport,
+    inline_height: Option<usize>,
+    kitty_keyboard: bool,
+) -> Result<u16> {
+    crossterm::terminal::enable_raw_mode()?;
+    let row = match inline_height {
+        Some(height) => {
+            // Scroll the lines above into the scrollback if the viewport
+            // doesn't fit below the cursor
+            let (_, num_rows) = crossterm::terminal::size()?;
+            let (_, row) = crossterm::cursor::position()?;
+            let height = height.min(num_rows as usize) as u16;
+            let overflow = (row + height).saturating_sub(num_rows);
+            queue!(
+                target,
+                crossterm::cursor::MoveTo(0, num_rows.saturating_sub(1))
+            )?;
+            for _ in 0..overflow {
+                target.write_all(b"\n")?;
+            }
+            // Painters start drawing at the cursor
+            let row = row - overflow;
+            queue!(target, crossterm::cursor::MoveTo(0, row))?;
+            row
+        }
+        None => {
+            queue!(target, crossterm::terminal::EnterAlternateScreen)?;
+            0
+        }
     };
+    target
+        .queue(crossterm::cursor::Hide)?
+        .queue(SetMouseCapture(mouse_capture))?;
+    if kitty_keyboard {
+        target.write_all(keyboard::ENABLE.as_bytes())?;
+    }
+    queue_set_style(target, &PainterT::INITIAL_STYLE, colours)?;
+    target.flush()?;
+    Ok(row)
+}
+
+#[inline]
+fn queue_set_style(target: &mut impl Write, style: &Style, colours: ColourSupport) -> Result<()> {
+    use crossterm::style::{Attribute, SetAttribute, SetBackgroundColor, SetForegroundColor};
 
     // Bold
     if style.bold {
-        queue!(target, SetAttribute(Attribute::Bold))?;
+        // Clears dim, which shares the intensity attribute with bold
+        queue!(
+            target,
+            SetAttribute(Attribute::NormalIntensity),
+            SetAttribute(Attribute::Bold)
+        )?;
     } else {
         // Using Reset is not ideal as it resets all style attributes. The correct thing to do
         // would be to use `NoBold`, but it seems this is not reliably supported (at least it
@@ -337,42 +1070,75 @@ fn queue_set_style(target: &mut impl Write, style: &Style) -> Result<()> {
         queue!(target, SetAttribute(Attribute::Reset))?;
     }
 
-    // Underline
-    if style.underline {
-        queue!(target, SetAttribute(Attribute::Underlined))?;
-    } else {
-        queue!(target, SetAttribute(Attribute::NoUnderline))?;
+    // The remaining attributes are set after bold, as it may reset them
+    let attributes = [
+        (
+            style.underline,
+            Attribute::Underlined,
+            Attribute::NoUnderline,
+        ),
+        (style.italic, Attribute::Italic, Attribute::NoItalic),
+        (
+            style.strikethrough,
+            Attribute::CrossedOut,
+            Attribute::NotCrossedOut,
+        ),
+        (style.reverse, Attribute::Reverse, Attribute::NoReverse),
+        (style.blink, Attribute::SlowBlink, Attribute::NoBlink),
+    ];
+    for (enabled, on, off) in attributes {
+        queue!(target, SetAttribute(if enabled { on } else { off }))?;
     }
-
-    // Background
-    {
-        let Colour { red, green, blue } = style.background;
-        queue!(
-            target,
-            SetBackgroundColor(Color::Rgb {
-                r: red,
-                g: green,
-                b: blue
-            })
-        )?;
+    if style.dim {
+        queue!(target, SetAttribute(Attribute::Dim))?;
     }
 
-    // Foreground
-    {
-        let Colour { red, green, blue } = style.foreground;
-        queue!(
-            target,
-            SetForegroundColor(Color::Rgb {
-                r: red,
-                g: green,
-                b: blue
-            })
-        )?;
-    }
+    // Colours
+    queue!(
+        target,
+        SetBackgroundColor(to_crossterm_colour(style.background, colours)),
+        SetForegroundColor(to_crossterm_colour(style.foreground, colours))
+    )?;
 
     Ok(())
 }
 
+/// Converts a colour to the closest one supported by the terminal.
+#[inline]
+fn to_crossterm_colour(colour: Colour, colours: ColourSupport) -> crossterm::style::Color {
+    use crossterm::style::Color;
+
+    match colour.downsample(colours) {
+        Colour::Default => Color::Reset,
+        // The named colours are used as some terminals with 16 colours don't
+        // support the 256 colour escape sequences
+        Colour::Base(base) => match base {
+            BaseColour::Black => Color::Black,
+            BaseColour::Red => Color::DarkRed,
+            BaseColour::Green => Color::DarkGreen,
+            BaseColour::Yellow => Color::DarkYellow,
+            BaseColour::Blue => Color::DarkBlue,
+            BaseColour::Magenta => Color::DarkMagenta,
+            BaseColour::Cyan => Color::DarkCyan,
+            BaseColour::White => Color::Grey,
+            BaseColour::BrightBlack => Color::DarkGrey,
+            BaseColour::BrightRed => Color::Red,
+            BaseColour::BrightGreen => Color::Green,
+            BaseColour::BrightYellow => Color::Yellow,
+            BaseColour::BrightBlue => Color::Blue,
+            BaseColour::BrightMagenta => Color::Magenta,
+            BaseColour::BrightCyan => Color::Cyan,
+            BaseColour::BrightWhite => Color::White,
+        },
+        Colour::Indexed(index) => Color::AnsiValue(index),
+        Colour::Rgb { red, green, blue } => Color::Rgb {
+            r: red,
+            g: green,
+            b: blue,
+        },
+    }
+}
+
 enum FilteredEvent {
     Input(zi::terminal::Event),
     Resize(Size),
@@ -380,26 +1146,148 @@ enum FilteredEvent {
 
 type EventStream = Pin<Box<dyn Stream<Item = Result<FilteredEvent>> + Send + 'static>>;
 
+type EventFilter =
+    Arc<dyn Fn(crossterm::event::Event) -> Option<crossterm::event::Event> + Send + Sync>;
+
 #[inline]
-fn new_event_stream() -> EventStream {
+fn new_event_stream(filter: Option<EventFilter>) -> EventStream {
     Box::pin(
         crossterm::event::EventStream::new()
-            .filter_map(|event| async move {
-                match event {
-                    Ok(crossterm::event::Event::Key(key_event)) => Some(Ok(FilteredEvent::Input(
-                        zi::terminal::Event::KeyPress(map_key(key_event)),
-                    ))),
-                    Ok(crossterm::event::Event::Resize(width, height)) => Some(Ok(
-                        FilteredEvent::Resize(Size::new(width as usize, height as usize)),
-                    )),
-                    Ok(_) => None,
+            .filter_map(move |event| {
+                // Without the kitty keyboard protocol, repeats arrive as
+                // presses and releases aren't reported
+                let event = match event {
+                    Ok(event) => filter_event(event, KeyKind::Press, filter.as_ref()).map(Ok),
                     Err(error) => Some(Err(error.into())),
-                }
+                };
+                async move { event }
+            })
+            // Terminals send all the `char`s of a grapheme at once
+            .ready_chunks(MAX_EVENTS_BATCH)
+            .flat_map(|events| stream::iter(coalesce_graphemes(events)))
+            .fuse(),
+    )
+}
+
+/// Like [`new_event_stream`], for input with the kitty keyboard protocol,
+/// which is read from stdin and parsed by the backend.
+#[cfg(unix)]
+fn new_kitty_event_stream(filter: Option<EventFilter>) -> EventStream {
+    Box::pin(
+        keyboard::KittyEvents::new()
+            .filter_map(move |event| {
+                let event = match event {
+                    Ok((event, kind)) => filter_event(event, kind, filter.as_ref()).map(Ok),
+                    Err(error) => Some(Err(error.into())),
+                };
+                async move { event }
             })
+            .ready_chunks(MAX_EVENTS_BATCH)
+            .flat_map(|events| stream::iter(coalesce_graphemes(events)))
             .fuse(),
     )
 }
 
+/// Applies the event filter to a crossterm event and converts it to a Zi
+/// event. Key events are presses, repeats or releases depending on `kind`.
+fn filter_event(
+    event: crossterm::event::Event,
+    kind: KeyKind,
+    filter: Option<&EventFilter>,
+) -> Option<FilteredEvent> {
+    let event = match filter {
+        Some(filter) => filter(event)?,
+        None => event,
+    };
+    Some(match event {
+        crossterm::event::Event::Key(key_event) => {
+            let key = map_key(key_event);
+            FilteredEvent::Input(match kind {
+                KeyKind::Press => Event::key_press(key),
+                KeyKind::Repeat => Event::KeyRepeat(key, Instant::now()),
+                KeyKind::Release => Event::KeyRelease(key, Instant::now()),
+            })
+        }
+        crossterm::event::Event::Mouse(mouse_event) => {
+            FilteredEvent::Input(Event::Mouse(map_mouse(mouse_event)))
+        }
+        crossterm::event::Event::Resize(width, height) => {
+            FilteredEvent::Resize(Size::new(width as usize, height as usize))
+        }
+    })
+}
+
+const MAX_EVENTS_BATCH: usize = 64;
+
+/// Merges the key presses of `char`s which form a single extended grapheme
+/// cluster, e.g. an emoji with a skin tone modifier, into a `Key::Grapheme`.
+fn coalesce_graphemes(events: Vec<Result<FilteredEvent>>) -> Vec<Result<FilteredEvent>> {
+    let mut coalesced = Vec::with_capacity(events.len());
+    let mut text = String::new();
+    let mut text_timestamp = None;
+    for event in events {
+        match event {
+            Ok(FilteredEvent::Input(Event::KeyPress(Key::Char(character), timestamp)))
+                if !character.is_control() =>
+            {
+                text.push(character);
+                text_timestamp.get_or_insert(timestamp);
+            }
+            event => {
+                if let Some(timestamp) = text_timestamp.take() {
+                    push_graphemes(&mut coalesced, &text, timestamp);
+                    text.clear();
+                }
+                coalesced.push(event);
+            }
+        }
+    }
+    if let Some(timestamp) = text_timestamp {
+        push_graphemes(&mut coalesced, &text, timestamp);
+    }
+    coalesced
+}
+
+fn push_graphemes(events: &mut Vec<Result<FilteredEvent>>, text: &str, timestamp: Instant) {
+    let mut push = |key| events.push(Ok(FilteredEvent::Input(Event::KeyPress(key, timestamp))));
+    for grapheme in text.graphemes(true) {
+        match Grapheme::new(grapheme) {
+            Some(grapheme) if grapheme.chars().nth(1).is_some() => push(Key::Grapheme(grapheme)),
+            _ => grapheme.chars().map(Key::Char).for_each(&mut push),
+        }
+    }
+}
+
+#[inline]
+fn map_mouse(event: crossterm::event::MouseEvent) -> MouseEvent {
+    use crossterm::event::{
+        KeyModifiers, MouseButton as CrosstermButton, MouseEventKind as CrosstermKind,
+    };
+    let map_button = |button| match button {
+        CrosstermButton::Left => MouseButton::Left,
+        CrosstermButton::Right => MouseButton::Right,
+        CrosstermButton::Middle => MouseButton::Middle,
+    };
+    let kind = match event.kind {
+        CrosstermKind::Moved => MouseEventKind::Moved,
+        CrosstermKind::Down(button) => MouseEventKind::Down(map_button(button)),
+        CrosstermKind::Up(button) => MouseEventKind::Up(map_button(button)),
+        CrosstermKind::Drag(button) => MouseEventKind::Drag(map_button(button)),
+        CrosstermKind::ScrollUp => MouseEventKind::ScrollUp,
+        CrosstermKind::ScrollDown => MouseEventKind::ScrollDown,
+    };
+    MouseEvent {
+        kind,
+        position: Position::new(event.column as usize, event.row as usize),
+        modifiers: Modifiers {
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+            alt: event.modifiers.contains(KeyModifiers::ALT),
+            shift: event.modifiers.contains(KeyModifiers::SHIFT),
+        },
+        timestamp: Instant::now(),
+    }
+}
+
 #[inline]
 fn map_key(key: crossterm::event::KeyEvent) -> Key {
     use crossterm::event::{KeyCode, KeyModifiers};
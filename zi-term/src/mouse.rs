@@ -0,0 +1,84 @@
+use std::fmt;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Which mouse events the terminal reports to the application.
+///
+/// While the mouse is captured, the terminal doesn't handle it itself, in
+/// particular selecting text with the mouse is not possible. Most terminals
+/// allow selecting text regardless when holding Shift (Option in some macOS
+/// terminals).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum MouseCapture {
+    /// Mouse events are not reported, leaving native text selection working.
+    Disabled,
+    /// Report clicks and movement while a button is held down.
+    Buttons,
+    /// Report all mouse movement, which is required for hover events. This is
+    /// the default.
+    #[default]
+    Motion,
+}
+
+/// A handle to change the mouse capture of a running
+/// [`Crossterm`](struct.Crossterm.html) backend, e.g. from a component.
+///
+/// ```no_run
+/// # use zi_term::MouseCapture;
+/// # fn main() -> zi_term::Result<()> {
+/// let backend = zi_term::incremental()?;
+/// let mouse_capture = backend.mouse_capture_handle();
+/// // Pass the handle to a component, which can then call
+/// mouse_capture.set(MouseCapture::Disabled);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MouseCaptureHandle(pub(crate) UnboundedSender<MouseCapture>);
+
+impl MouseCaptureHandle {
+    /// Changes the mouse capture, taking effect before the next frame is
+    /// drawn. It does nothing if the backend was dropped.
+    pub fn set(&self, capture: MouseCapture) {
+        let _ = self.0.send(capture);
+    }
+}
+
+/// Crossterm command enabling the mouse reporting modes of a `MouseCapture`
+/// and disabling the others.
+pub(crate) struct SetMouseCapture(pub MouseCapture);
+
+impl crossterm::Command for SetMouseCapture {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        // Normal tracking (1000) reports presses and releases, button-event
+        // tracking (1002) adds dragging and any-event tracking (1003) adds
+        // all motion. 1015 and 1006 are the RXVT and SGR encodings, which
+        // allow coordinates larger than 223
+        match self.0 {
+            MouseCapture::Disabled => {
+                f.write_str("\x1b[?1006l\x1b[?1015l\x1b[?1003l\x1b[?1002l\x1b[?1000l")
+            }
+            MouseCapture::Buttons => {
+                f.write_str("\x1b[?1003l\x1b[?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h")
+            }
+            MouseCapture::Motion => {
+                f.write_str("\x1b[?1000h\x1b[?1002h\x1b[?1003h\x1b[?1015h\x1b[?1006h")
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> crossterm::Result<()> {
+        // The Windows console only supports turning mouse input on or off
+        match self.0 {
+            MouseCapture::Disabled => crossterm::event::DisableMouseCapture.execute_winapi(),
+            MouseCapture::Buttons | MouseCapture::Motion => {
+                crossterm::event::EnableMouseCapture.execute_winapi()
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    fn is_ansi_code_supported(&self) -> bool {
+        false
+    }
+}
@@ -1,6 +1,6 @@
 //! Module with utilities to convert a `Canvas` to a set of abstract paint operations.
 use zi::{
-    terminal::{Canvas, Position, Size, Style, Textel},
+    terminal::{Canvas, ImagePlacement, Position, Size, Style, TerminalCursor, Textel},
     unicode_width::UnicodeWidthStr,
 };
 
@@ -23,12 +23,25 @@ pub enum PaintOperation<'a> {
     WriteContent(&'a str),
     SetStyle(&'a Style),
     MoveTo(Position),
+    /// Starts a hyperlink to a target, or ends the current one if `None`.
+    /// Content written in between is part of the hyperlink.
+    SetHyperlink(Option<&'a str>),
+    /// Removes the images drawn by earlier `DrawImage` operations.
+    ClearImages,
+    /// Draws an image over the cells of its region. The cells are painted
+    /// before, with the fallback drawn by the canvas.
+    DrawImage(&'a ImagePlacement),
+    /// Shows the cursor at a position or hides it if `None`. Always the last
+    /// operation of a frame, as writing content moves the cursor.
+    SetCursor(Option<TerminalCursor>),
 }
 
 pub struct IncrementalPainter {
     screen: Canvas,
     current_position: Position,
     current_style: Style,
+    images: Vec<ImagePlacement>,
+    cursor: Option<TerminalCursor>,
 }
 
 impl Painter for IncrementalPainter {
@@ -40,6 +53,8 @@ impl Painter for IncrementalPainter {
             screen: Canvas::new(size),
             current_position: Self::INITIAL_POSITION,
             current_style: Self::INITIAL_STYLE,
+            images: Vec::new(),
+            cursor: None,
         }
     }
 
@@ -53,6 +68,8 @@ impl Painter for IncrementalPainter {
             ref mut screen,
             ref mut current_position,
             ref mut current_style,
+            ref mut images,
+            ref mut cursor,
         } = *self;
         let size = target.size();
         let force_redraw = size != screen.size();
@@ -60,6 +77,11 @@ impl Painter for IncrementalPainter {
             screen.resize(size);
         }
 
+        // Hyperlinks are only open while their content is written, s.t.
+        // moving the cursor never extends a hyperlink to other cells
+        let mut current_link = None;
+        let mut painted = false;
+
         screen
             .buffer_mut()
             .iter_mut()
@@ -75,8 +97,13 @@ impl Painter for IncrementalPainter {
                 }
 
                 if let Some(new) = new {
+                    painted = true;
                     let position = Position::new(index % size.width, index / size.width);
                     if position != *current_position {
+                        if current_link.is_some() {
+                            paint(PaintOperation::SetHyperlink(None))?;
+                            current_link = None;
+                        }
                         // eprintln!("MoveTo({})", position);
                         paint(PaintOperation::MoveTo(position))?;
                         *current_position = position;
@@ -88,6 +115,11 @@ impl Painter for IncrementalPainter {
                         *current_style = new.style;
                     }
 
+                    if new.link.as_deref() != current_link {
+                        current_link = new.link.as_deref();
+                        paint(PaintOperation::SetHyperlink(current_link))?;
+                    }
+
                     let content_width = UnicodeWidthStr::width(&new.grapheme[..]);
                     // eprintln!("Content({:?}) {}", new.grapheme, content_width);
                     paint(PaintOperation::WriteContent(&new.grapheme))?;
@@ -97,7 +129,33 @@ impl Painter for IncrementalPainter {
                 *current = new.clone();
 
                 Ok(())
-            })
+            })?;
+        if current_link.is_some() {
+            paint(PaintOperation::SetHyperlink(None))?;
+        }
+
+        // Images are redrawn whenever any of them changed, as drawing text
+        // over an image in the terminal may not remove it
+        if force_redraw || target.images() != images.as_slice() {
+            paint(PaintOperation::ClearImages)?;
+            for placement in target.images() {
+                paint(PaintOperation::DrawImage(placement))?;
+            }
+            images.clear();
+            images.extend_from_slice(target.images());
+            *current_position = UNKNOWN_POSITION;
+            painted = true;
+        }
+
+        // The cursor is moved back after writing any content
+        if target.cursor() != *cursor || (painted && cursor.is_some()) {
+            paint(PaintOperation::SetCursor(target.cursor()))?;
+            *cursor = target.cursor();
+            if let Some(cursor) = cursor {
+                *current_position = cursor.position;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -125,26 +183,50 @@ impl Painter for FullPainter {
             ref mut current_style,
         } = *self;
         let size = target.size();
+        let mut current_link = None;
         target
             .buffer()
             .chunks(size.width)
             .enumerate()
             .try_for_each(|(y, line)| -> Result<()> {
+                if current_link.is_some() {
+                    paint(PaintOperation::SetHyperlink(None))?;
+                    current_link = None;
+                }
                 paint(PaintOperation::MoveTo(Position::new(0, y)))?;
                 line.iter().try_for_each(|textel| -> Result<()> {
                     if let Some(Textel {
                         ref style,
                         ref grapheme,
+                        ref link,
                     }) = textel
                     {
                         if *style != *current_style {
                             paint(PaintOperation::SetStyle(style))?;
                             *current_style = *style;
                         }
+                        if link.as_deref() != current_link {
+                            current_link = link.as_deref();
+                            paint(PaintOperation::SetHyperlink(current_link))?;
+                        }
                         paint(PaintOperation::WriteContent(grapheme))?;
                     }
                     Ok(())
                 })
-            })
+            })?;
+        if current_link.is_some() {
+            paint(PaintOperation::SetHyperlink(None))?;
+        }
+
+        paint(PaintOperation::ClearImages)?;
+        target
+            .images()
+            .iter()
+            .try_for_each(|placement| paint(PaintOperation::DrawImage(placement)))?;
+        paint(PaintOperation::SetCursor(target.cursor()))
     }
 }
+
+/// The cursor position after drawing an image, which forces the next
+/// content to be preceded by a `MoveTo`.
+const UNKNOWN_POSITION: Position = Position::new(usize::MAX, usize::MAX);
@@ -0,0 +1,120 @@
+use crossterm::{queue, tty::IsTty};
+use std::io::{self, BufWriter, Write};
+use tokio::sync::mpsc;
+use zi::{
+    app::App,
+    terminal::{Canvas, ColourSupport, Size},
+    unicode_width::UnicodeWidthStr,
+    Layout,
+};
+
+use crate::{capabilities, queue_set_style, Result, UnboundedMessageSender};
+
+/// Renders a layout once to stdout and returns, without raw mode or the
+/// alternate screen, e.g. to pretty-print the output of a CLI tool with zi
+/// components.
+///
+/// The layout is rendered `height` lines tall and as wide as the terminal, or
+/// 80 columns if stdout isn't one or its width is unknown. Colours are only
+/// written to a terminal, otherwise the trailing spaces of each line are
+/// trimmed, s.t. the output can be piped to other tools.
+///
+/// ```no_run
+/// # use zi::prelude::*;
+/// # use zi::components::text::{Text, TextProperties};
+/// fn main() -> zi_term::Result<()> {
+///     zi_term::print(Text::with(TextProperties::new().content("Hello, world!")), 1)
+/// }
+/// ```
+pub fn print(layout: Layout, height: usize) -> Result<()> {
+    let stdout = io::stdout();
+    let is_tty = stdout.is_tty();
+    let width = match crossterm::terminal::size() {
+        Ok((width, _)) if is_tty && width > 0 => width as usize,
+        _ => DEFAULT_WIDTH,
+    };
+    let capabilities = capabilities::detect();
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut app = App::new(
+        UnboundedMessageSender(sender),
+        Size::new(width, height),
+        layout,
+    );
+    app.set_capabilities(capabilities);
+    app.draw();
+    // Components may send messages as they are mounted
+    while let Ok(message) = receiver.try_recv() {
+        app.handle_message(message);
+    }
+
+    let mut target = BufWriter::new(stdout.lock());
+    let colours = if is_tty {
+        Some(capabilities.colours)
+    } else {
+        None
+    };
+    write_canvas(&mut target, app.draw(), colours)?;
+    target.flush()?;
+    Ok(())
+}
+
+/// Writes the canvas line by line, with colours if `colours` is set.
+fn write_canvas(
+    target: &mut impl Write,
+    canvas: &Canvas,
+    colours: Option<ColourSupport>,
+) -> Result<()> {
+    use crossterm::style::{Attribute, ResetColor, SetAttribute};
+
+    let size = canvas.size();
+    for y in 0..size.height {
+        let mut line = String::new();
+        let mut covered = 0;
+        let mut current_style = None;
+        for x in 0..size.width {
+            let textel = canvas
+                .textel(x, y)
+                .as_ref()
+                .filter(|textel| !textel.grapheme.is_empty());
+            match textel {
+                // The cells covered by a wide grapheme are unset
+                None if covered > 0 => {}
+                None => {
+                    if let (Some(_), Some(_)) = (colours, current_style.take()) {
+                        write!(target, "{}", line)?;
+                        line.clear();
+                        queue!(target, SetAttribute(Attribute::Reset), ResetColor)?;
+                    }
+                    line.push(' ');
+                }
+                Some(textel) => {
+                    match colours {
+                        Some(colours) if current_style != Some(textel.style) => {
+                            write!(target, "{}", line)?;
+                            line.clear();
+                            queue_set_style(target, &textel.style, colours)?;
+                            current_style = Some(textel.style);
+                        }
+                        _ => {}
+                    }
+                    line.push_str(textel.grapheme.as_str());
+                    covered = textel.grapheme.as_str().width();
+                }
+            }
+            covered = covered.saturating_sub(1);
+        }
+        match colours {
+            Some(_) => {
+                write!(target, "{}", line)?;
+                queue!(target, SetAttribute(Attribute::Reset), ResetColor)?;
+                writeln!(target)?;
+            }
+            None => writeln!(target, "{}", line.trim_end())?,
+        }
+    }
+    Ok(())
+}
+
+/// The width of the output when stdout isn't a terminal.
+const DEFAULT_WIDTH: usize = 80;
@@ -0,0 +1,49 @@
+use std::io;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Forwards `SIGTSTP` to the event loop while it's alive, s.t. the app is
+/// suspended properly when the signal is sent by another process, e.g. with
+/// `kill -TSTP`. In raw mode, Ctrl-Z is read as a key instead.
+pub(crate) struct SuspendSignals {
+    #[cfg(unix)]
+    handle: signal_hook::iterator::Handle,
+}
+
+impl SuspendSignals {
+    #[cfg(unix)]
+    pub(crate) fn forward(sender: UnboundedSender<()>) -> io::Result<Self> {
+        use signal_hook::{consts::SIGTSTP, iterator::Signals};
+
+        let mut signals = Signals::new([SIGTSTP])?;
+        let handle = signals.handle();
+        std::thread::spawn(move || {
+            for _ in signals.forever() {
+                if sender.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self { handle })
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn forward(_sender: UnboundedSender<()>) -> io::Result<Self> {
+        Ok(Self {})
+    }
+}
+
+impl Drop for SuspendSignals {
+    fn drop(&mut self) {
+        // Stops the forwarding thread and restores the default handler
+        #[cfg(unix)]
+        self.handle.close();
+    }
+}
+
+/// Stops the process until it's continued, e.g. with `fg` in the shell. It
+/// does nothing on platforms without job control.
+pub(crate) fn stop_process() -> io::Result<()> {
+    #[cfg(unix)]
+    signal_hook::low_level::raise(signal_hook::consts::SIGSTOP)?;
+    Ok(())
+}
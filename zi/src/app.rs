@@ -3,16 +3,45 @@
 //! end application.
 
 use smallvec::SmallVec;
-use std::{collections::HashMap, fmt::Debug, time::Instant};
+use std::{
+    any::{self, TypeId},
+    borrow::Cow,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    future::Future,
+    mem,
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+    task::{Context as TaskContext, Wake, Waker},
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::{
     component::{
-        bindings::{BindingQuery, DynamicBindings, KeySequenceSlice, NamedBindingQuery},
-        layout::{LaidCanvas, LaidComponent, Layout},
+        bindings::{
+            BindingQuery, CommandId, DynamicBindings, KeyContinuation, KeyGesture, KeyPattern,
+            KeySequenceSlice, Keymap, NamedBindingQuery,
+        },
+        bus::{MessageBus, Publication},
+        clock_now,
+        context::{ComponentContext, Context, SharedContext},
+        get_service,
+        keymap_config::KeymapOverrides,
+        layout::{ComponentKey, LaidCanvas, LaidComponent, Layout, TabOrder},
         template::{ComponentId, DynamicMessage, DynamicProperties, Renderable},
-        LinkMessage, ShouldRender,
+        timer::Timers,
+        Broadcast, Component, ComponentHandle, LinkMessage, RenderPriority, ReportedError,
+        SharedCapabilities, SharedClock, SharedFrame, SharedServices, SharedTheme, ShouldRender,
+        StreamHandle,
+    },
+    recording::{self, CloneMessage, RecordedEvent, RecordedMessage, Recorder, Recording},
+    terminal::{
+        BackendCapabilities, Canvas, Event, HoverEvent, Key, MouseEvent, MouseEventKind, Position,
+        Rect, Size, TerminalCursor,
     },
-    terminal::{Canvas, Event, Key, Position, Rect, Size},
+    theme::Theme,
 };
 
 pub trait MessageSender: Debug + Send + 'static {
@@ -21,9 +50,436 @@ pub trait MessageSender: Debug + Send + 'static {
     fn clone_box(&self) -> Box<dyn MessageSender>;
 }
 
+impl MessageSender for Box<dyn MessageSender> {
+    fn send(&self, message: ComponentMessage) {
+        (**self).send(message)
+    }
+
+    fn clone_box(&self) -> Box<dyn MessageSender> {
+        (**self).clone_box()
+    }
+}
+
 #[derive(Debug)]
 pub struct ComponentMessage(pub(crate) LinkMessage);
 
+impl ComponentMessage {
+    /// Returns `true` if this is a request to stop the app, sent by
+    /// [`ComponentLink::exit`](../struct.ComponentLink.html#method.exit).
+    pub fn is_exit(&self) -> bool {
+        matches!(self.0, LinkMessage::Exit(_))
+    }
+
+    /// The type name of the component the message is sent to. Like
+    /// `std::any::type_name`, it should only be used for debugging.
+    pub fn component_type_name(&self) -> Option<&'static str> {
+        match self.0 {
+            LinkMessage::Component(component_id, _) => Some(component_id.type_name()),
+            LinkMessage::Keyed(ref keyed) => Some(keyed.type_name),
+            LinkMessage::Broadcast(ref broadcast) => Some(broadcast.type_name),
+            LinkMessage::Subscribe(_)
+            | LinkMessage::Unsubscribe(..)
+            | LinkMessage::Publish(_)
+            | LinkMessage::Error(_)
+            | LinkMessage::CopyToClipboard(_)
+            | LinkMessage::SetTheme(_)
+            | LinkMessage::SetKeymapOverrides(_)
+            | LinkMessage::Exit(_) => None,
+            LinkMessage::Spawn(component_id, ..) | LinkMessage::Paste(component_id, _) => {
+                Some(component_id.type_name())
+            }
+            LinkMessage::RunExclusive(ref task) => Some(task.component_type_name()),
+            LinkMessage::Timer(ref timer) => Some(timer.component_id().type_name()),
+        }
+    }
+
+    /// Returns `true` if the message is sent to a component of type
+    /// `ComponentT`.
+    pub fn is_for<ComponentT: Component>(&self) -> bool {
+        match self.0 {
+            LinkMessage::Component(component_id, _) => {
+                component_id.type_id() == TypeId::of::<ComponentT>()
+            }
+            LinkMessage::Keyed(ref keyed) => keyed.type_id == TypeId::of::<ComponentT>(),
+            LinkMessage::Broadcast(ref broadcast) => {
+                broadcast.type_id == TypeId::of::<ComponentT>()
+            }
+            LinkMessage::Subscribe(_)
+            | LinkMessage::Unsubscribe(..)
+            | LinkMessage::Publish(_)
+            | LinkMessage::Timer(_)
+            | LinkMessage::Spawn(..)
+            | LinkMessage::RunExclusive(_)
+            | LinkMessage::CopyToClipboard(_)
+            | LinkMessage::Paste(..)
+            | LinkMessage::Error(_)
+            | LinkMessage::SetTheme(_)
+            | LinkMessage::SetKeymapOverrides(_)
+            | LinkMessage::Exit(_) => false,
+        }
+    }
+
+    /// The published value, if this is a value of type `TopicT` published
+    /// with [`ComponentLink::publish`](../struct.ComponentLink.html#method.publish).
+    pub fn topic<TopicT: 'static>(&self) -> Option<&TopicT> {
+        match self.0 {
+            LinkMessage::Publish(ref publication) => publication.value.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    /// The error, if this is an error reported by a component using
+    /// [`ComponentLink::report_error`](../struct.ComponentLink.html#method.report_error).
+    pub fn error(&self) -> Option<&ReportedError> {
+        match self.0 {
+            LinkMessage::Error(ref error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// The message, if it is sent to a component of type `ComponentT`.
+    pub fn message<ComponentT: Component>(&self) -> Option<&ComponentT::Message> {
+        match self.0 {
+            LinkMessage::Component(_, ref message) if self.is_for::<ComponentT>() => {
+                message.0.downcast_ref()
+            }
+            LinkMessage::Keyed(ref keyed) if self.is_for::<ComponentT>() => {
+                keyed.message.0.downcast_ref()
+            }
+            LinkMessage::Broadcast(ref broadcast) if self.is_for::<ComponentT>() => {
+                broadcast.message.0.downcast_ref()
+            }
+            _ => None,
+        }
+    }
+
+    /// The message as mutable, if it is sent to a component of type
+    /// `ComponentT`. Useful for transforming messages in a
+    /// [`Middleware`](trait.Middleware.html).
+    pub fn message_mut<ComponentT: Component>(&mut self) -> Option<&mut ComponentT::Message> {
+        let is_for = self.is_for::<ComponentT>();
+        match self.0 {
+            LinkMessage::Component(_, ref mut message) if is_for => message.0.downcast_mut(),
+            LinkMessage::Keyed(ref mut keyed) if is_for => keyed.message.0.downcast_mut(),
+            LinkMessage::Broadcast(ref mut broadcast) if is_for => {
+                broadcast.message.0.downcast_mut()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Observes, transforms or filters the messages handled by an
+/// [`App`](struct.App.html) before they are delivered, e.g. for logging or
+/// rate limiting.
+///
+/// Middleware is registered with
+/// [`App::add_middleware`](struct.App.html#method.add_middleware) and it runs
+/// in the order it was added. Closures taking and returning a message
+/// implement the trait.
+///
+/// ```
+/// # use zi::{app::ComponentMessage, components::text::Text};
+/// let mut dropped = 0;
+/// let mut middleware = move |message: ComponentMessage| {
+///     if message.is_for::<Text>() {
+///         dropped += 1;
+///         None
+///     } else {
+///         Some(message)
+///     }
+/// };
+/// # let _: &mut dyn zi::app::Middleware = &mut middleware;
+/// ```
+pub trait Middleware: 'static {
+    /// Called with every message before it is delivered. Returns the message
+    /// to pass on to the next middleware, or `None` to drop it.
+    fn process(&mut self, message: ComponentMessage) -> Option<ComponentMessage>;
+}
+
+impl<FnT> Middleware for FnT
+where
+    FnT: FnMut(ComponentMessage) -> Option<ComponentMessage> + 'static,
+{
+    fn process(&mut self, message: ComponentMessage) -> Option<ComponentMessage> {
+        self(message)
+    }
+}
+
+/// A future spawned by a component with
+/// [`ComponentLink::spawn_future`](../struct.ComponentLink.html#method.spawn_future).
+/// It delivers its result to the component itself.
+pub type Task = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Runs the futures spawned by components, typically on the async runtime
+/// the backend already uses for its event loop.
+///
+/// An executor is set with
+/// [`App::set_executor`](struct.App.html#method.set_executor). Closures
+/// taking a [`Task`](type.Task.html) implement the trait. Without an
+/// executor, each task is run to completion on a thread of its own.
+pub trait Executor: Send + 'static {
+    /// Starts running a task in the background. It must not block.
+    fn spawn(&self, task: Task);
+}
+
+impl<FnT> Executor for FnT
+where
+    FnT: Fn(Task) + Send + 'static,
+{
+    fn spawn(&self, task: Task) {
+        self(task)
+    }
+}
+
+/// The clipboard of the system, which components copy text to with
+/// [`ComponentLink::copy_to_clipboard`](../struct.ComponentLink.html#method.copy_to_clipboard)
+/// and paste from with
+/// [`ComponentLink::request_paste`](../struct.ComponentLink.html#method.request_paste).
+///
+/// A clipboard is set by the backend with
+/// [`App::set_clipboard`](struct.App.html#method.set_clipboard). Without
+/// one, the app uses a [`LocalClipboard`](struct.LocalClipboard.html), s.t.
+/// copying and pasting still work within the app.
+pub trait Clipboard: Send + 'static {
+    /// Copies text to the clipboard. It must not block.
+    fn copy(&mut self, text: &str);
+
+    /// Returns the text in the clipboard, or `None` if it's empty or can't be
+    /// read.
+    fn paste(&mut self) -> Option<String>;
+}
+
+/// A clipboard kept in memory, only shared by the components of the app.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocalClipboard(Option<String>);
+
+impl Clipboard for LocalClipboard {
+    fn copy(&mut self, text: &str) {
+        self.0 = Some(text.to_owned());
+    }
+
+    fn paste(&mut self) -> Option<String> {
+        self.0.clone()
+    }
+}
+
+/// A blocking task which needs exclusive access to the terminal, e.g.
+/// running `$EDITOR`, requested by a component with
+/// [`ComponentLink::run_exclusive`](../struct.ComponentLink.html#method.run_exclusive).
+///
+/// Backends take pending tasks with
+/// [`App::take_exclusive_task`](struct.App.html#method.take_exclusive_task),
+/// suspend themselves (restoring the terminal), run the task and resume,
+/// passing the message it returns back to the app.
+pub struct ExclusiveTask {
+    component_id: ComponentId,
+    task: Box<dyn FnOnce() -> Option<DynamicMessage> + Send>,
+}
+
+impl ExclusiveTask {
+    pub(crate) fn new(
+        component_id: ComponentId,
+        task: impl FnOnce() -> Option<DynamicMessage> + Send + 'static,
+    ) -> Self {
+        Self {
+            component_id,
+            task: Box::new(task),
+        }
+    }
+
+    /// Runs the task, blocking until it completes. Returns the message for
+    /// the component which requested it, if any, to pass to
+    /// [`App::handle_message`](struct.App.html#method.handle_message).
+    pub fn run(self) -> Option<ComponentMessage> {
+        let component_id = self.component_id;
+        (self.task)().map(|message| ComponentMessage(LinkMessage::Component(component_id, message)))
+    }
+
+    /// The type name of the component which requested the task. Like
+    /// `std::any::type_name`, it should only be used for debugging.
+    pub fn component_type_name(&self) -> &'static str {
+        self.component_id.type_name()
+    }
+}
+
+impl Debug for ExclusiveTask {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("ExclusiveTask")
+            .field("component_id", &self.component_id)
+            .finish()
+    }
+}
+
+/// Runs a task on the current thread until it completes, parking the thread
+/// while the task waits.
+fn block_on(mut task: Task) {
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = TaskContext::from_waker(&waker);
+    while task.as_mut().poll(&mut context).is_pending() {
+        thread::park();
+    }
+}
+
+/// Selects mounted components when addressing them from outside the
+/// component tree, e.g. when invoking a command by name with
+/// [`App::execute_command`](struct.App.html#method.execute_command).
+///
+/// ```
+/// # use zi::{app::ComponentSelector, components::text::Text};
+/// // Any mounted `Text` component
+/// let any_text = ComponentSelector::of::<Text>();
+///
+/// // Only the `Text` components created with `Text::with_key("status", ...)`
+/// let status = ComponentSelector::of::<Text>().key("status");
+///
+/// // All the components that currently have focus
+/// let focused = ComponentSelector::focused();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ComponentSelector(SelectorKind);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SelectorKind {
+    All,
+    Focused,
+    Type {
+        type_id: TypeId,
+        key: Option<ComponentKey>,
+    },
+}
+
+impl ComponentSelector {
+    /// Selects all mounted components of type `ComponentT`.
+    pub fn of<ComponentT: Component>() -> Self {
+        Self(SelectorKind::Type {
+            type_id: TypeId::of::<ComponentT>(),
+            key: None,
+        })
+    }
+
+    /// Selects the components that currently have focus.
+    pub fn focused() -> Self {
+        Self(SelectorKind::Focused)
+    }
+
+    /// Selects all mounted components.
+    pub fn all() -> Self {
+        Self(SelectorKind::All)
+    }
+
+    /// Restricts the selection to components created with the given key (see
+    /// [`ComponentExt::with_key`](../trait.ComponentExt.html#method.with_key)).
+    ///
+    /// This has no effect when selecting all or focused components.
+    pub fn key(self, key: impl Into<ComponentKey>) -> Self {
+        match self.0 {
+            SelectorKind::Type { type_id, .. } => Self(SelectorKind::Type {
+                type_id,
+                key: Some(key.into()),
+            }),
+            SelectorKind::All | SelectorKind::Focused => self,
+        }
+    }
+
+    #[inline]
+    fn matches(
+        &self,
+        component_id: &ComponentId,
+        component: &MountedComponent,
+        subscriptions: &ComponentSubscriptions,
+    ) -> bool {
+        match self.0 {
+            SelectorKind::All => true,
+            SelectorKind::Focused => subscriptions.focused.contains(component_id),
+            SelectorKind::Type { type_id, key } => {
+                component_id.type_id() == type_id && (key.is_none() || key == component.key)
+            }
+        }
+    }
+}
+
+/// Thresholds used to classify key presses as double presses or
+/// press-and-hold, see [`DoublePress`](../struct.DoublePress.html) and
+/// [`Hold`](../struct.Hold.html).
+///
+/// Backends which report key repeats as
+/// [`Event::KeyRepeat`](../terminal/enum.Event.html#variant.KeyRepeat), e.g.
+/// with the kitty keyboard protocol, tell held keys apart from presses.
+/// Otherwise a held key is detected from the rate of the presses, as most
+/// terminals send its repeats as presses. The double press threshold should
+/// be shorter than the keyboard's repeat delay, otherwise the first repeat of
+/// a held key is classified as a double press.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyTiming {
+    /// The maximum time between two presses of a key for them to count as a
+    /// double press.
+    pub double_press: Duration,
+    /// How long a key has to be repeated for to count as held.
+    pub hold: Duration,
+    /// The maximum time between the first press of a held key and its first
+    /// repeat, i.e. the keyboard's repeat delay.
+    pub repeat: Duration,
+    /// The maximum time between the following repeats of a held key, i.e.
+    /// the keyboard's repeat rate. It should be well under the double press
+    /// threshold, s.t. separate taps aren't mistaken for a held key.
+    pub repeat_interval: Duration,
+    /// How long to wait for the next key of a sequence which is the prefix of
+    /// some bindings, `None` to wait forever, the default. Once it times out,
+    /// the sequence runs the first exact binding for it on the focus path,
+    /// e.g. `Esc` bound by a parent while its child binds `Esc x`, or the
+    /// global bindings. Otherwise the sequence is cancelled.
+    pub prefix_timeout: Option<Duration>,
+}
+
+impl KeyTiming {
+    pub fn double_press(mut self, double_press: Duration) -> Self {
+        self.double_press = double_press;
+        self
+    }
+
+    pub fn hold(mut self, hold: Duration) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Duration) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    pub fn repeat_interval(mut self, repeat_interval: Duration) -> Self {
+        self.repeat_interval = repeat_interval;
+        self
+    }
+
+    pub fn prefix_timeout(mut self, prefix_timeout: Duration) -> Self {
+        self.prefix_timeout = Some(prefix_timeout);
+        self
+    }
+}
+
+impl Default for KeyTiming {
+    fn default() -> Self {
+        Self {
+            double_press: Duration::from_millis(250),
+            hold: Duration::from_millis(500),
+            repeat: Duration::from_millis(600),
+            repeat_interval: Duration::from_millis(100),
+            prefix_timeout: None,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum PollState {
     Clean,
@@ -36,6 +492,12 @@ struct AppRuntime {
     screen: Canvas,
     poll_state: PollState,
     num_frame: usize,
+    /// When the components were last ticked, `None` if no component is
+    /// tickable.
+    last_tick: Option<Instant>,
+    frame_budget: Option<Duration>,
+    /// The metrics, except for those tracked by the mounted components.
+    metrics: Metrics,
 }
 
 impl AppRuntime {
@@ -44,10 +506,61 @@ impl AppRuntime {
             screen: Canvas::new(size),
             poll_state: PollState::Dirty(None),
             num_frame: 0,
+            last_tick: None,
+            frame_budget: None,
+            metrics: Metrics::default(),
         }
     }
 }
 
+/// Counters and gauges describing the work done by an [`App`](struct.App.html),
+/// e.g. to monitor a long-running dashboard. See
+/// [`App::metrics`](struct.App.html#method.metrics).
+///
+/// The counters are cumulative since the app was created. The `Display` impl
+/// formats them on one line, suitable for periodic logging.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metrics {
+    /// The number of frames drawn.
+    pub frames_drawn: u64,
+    /// The total time spent drawing frames.
+    pub draw_time: Duration,
+    /// The time spent drawing the last frame.
+    pub last_draw_time: Duration,
+    /// The number of bytes written by the backend when presenting frames, as
+    /// reported with [`App::record_presented`](struct.App.html#method.record_presented).
+    pub bytes_presented: u64,
+    /// The number of component messages handled, not counting those dropped
+    /// by a middleware.
+    pub messages_processed: u64,
+    /// The number of input events handled.
+    pub input_events: u64,
+    /// The number of components currently mounted.
+    pub components_mounted: usize,
+    /// The number of updates by component type name, including components
+    /// that were since unmounted. Like `std::any::type_name`, the names should
+    /// only be used for debugging.
+    pub component_updates: HashMap<&'static str, u64>,
+}
+
+impl std::fmt::Display for Metrics {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{} frames ({:.1}ms avg) {}b presented {} messages {} inputs {} comps {} updates",
+            self.frames_drawn,
+            self.draw_time.as_secs_f64() * 1000.0 / self.frames_drawn.max(1) as f64,
+            self.bytes_presented,
+            self.messages_processed,
+            self.input_events,
+            self.components_mounted,
+            self.component_updates.values().sum::<u64>(),
+        )
+    }
+}
+
+type ErrorHandler = Box<dyn FnMut(&ReportedError)>;
+
 /// The application runtime.
 ///
 /// The runtime encapsulates the whole state of the application. It performs
@@ -67,12 +580,38 @@ impl AppRuntime {
 /// normally use a backend that wraps an App in an event loop, see the examples.
 pub struct App {
     root: Layout,
+    root_changed: bool,
     components: HashMap<ComponentId, MountedComponent>,
     layouts: HashMap<ComponentId, Layout>,
     subscriptions: ComponentSubscriptions,
     controller: InputController,
     runtime: AppRuntime,
     sender: Box<dyn MessageSender>,
+    capabilities: SharedCapabilities,
+    theme: SharedTheme,
+    services: SharedServices,
+    clock: SharedClock,
+    bus: MessageBus,
+    timers: Timers,
+    /// The streams consumed by each component, cancelled when it's unmounted.
+    streams: HashMap<ComponentId, Vec<StreamHandle>>,
+    recorder: Option<Recorder>,
+    /// How to copy the messages of the component types whose messages are
+    /// recorded.
+    recorded_messages: HashMap<TypeId, CloneMessage>,
+    /// The component types whose messages are re-delivered by an ongoing
+    /// replay, the messages sent to them through the sender are ignored.
+    replayed_component_types: HashSet<TypeId>,
+    middleware: Vec<Box<dyn Middleware>>,
+    error_handler: Option<ErrorHandler>,
+    executor: Option<Box<dyn Executor>>,
+    clipboard: Box<dyn Clipboard>,
+    exclusive_tasks: VecDeque<ExclusiveTask>,
+    exit_value: Option<Box<dyn any::Any + Send>>,
+    global_bindings: GlobalBindings,
+    keymap_overrides: KeymapOverrides,
+    /// The key `<leader>` stands for in the keymaps, see `set_leader`.
+    leader: Option<Key>,
 }
 
 impl App {
@@ -129,17 +668,197 @@ impl App {
     /// }
     /// # }
     /// ```
-
     pub fn new(sender: impl MessageSender, size: Size, root: Layout) -> Self {
         Self {
             root,
+            root_changed: false,
             components: HashMap::new(),
             layouts: HashMap::new(),
             subscriptions: ComponentSubscriptions::new(),
             controller: InputController::new(),
             runtime: AppRuntime::new(size),
             sender: Box::new(sender),
+            capabilities: Default::default(),
+            theme: Default::default(),
+            services: Default::default(),
+            clock: Default::default(),
+            bus: MessageBus::default(),
+            timers: Timers::default(),
+            streams: HashMap::new(),
+            recorder: None,
+            recorded_messages: HashMap::new(),
+            replayed_component_types: HashSet::new(),
+            middleware: Vec::new(),
+            error_handler: None,
+            executor: None,
+            clipboard: Box::new(LocalClipboard::default()),
+            exclusive_tasks: VecDeque::new(),
+            exit_value: None,
+            global_bindings: GlobalBindings::default(),
+            keymap_overrides: KeymapOverrides::default(),
+            leader: None,
+        }
+    }
+
+    /// Sets the root-level handler for errors reported by components using
+    /// [`ComponentLink::report_error`](../struct.ComponentLink.html#method.report_error).
+    ///
+    /// Errors are also passed to every mounted component implementing
+    /// [`Component::error`](../trait.Component.html#method.error). Errors which
+    /// are not handled by anyone are logged.
+    pub fn on_error(&mut self, handler: impl FnMut(&ReportedError) + 'static) {
+        self.error_handler = Some(Box::new(handler));
+    }
+
+    /// Sets the executor which runs the futures spawned by components with
+    /// [`ComponentLink::spawn_future`](../struct.ComponentLink.html#method.spawn_future),
+    /// e.g. the async runtime of the backend. Tasks spawned before an
+    /// executor is set run on threads of their own.
+    pub fn set_executor(&mut self, executor: impl Executor) {
+        self.executor = Some(Box::new(executor));
+    }
+
+    /// Sets the clipboard components copy text to and paste from, e.g. the
+    /// terminal's clipboard. Without one, the app keeps the copied text in
+    /// memory.
+    pub fn set_clipboard(&mut self, clipboard: impl Clipboard) {
+        self.clipboard = Box::new(clipboard);
+    }
+
+    /// Returns the clipboard, e.g. to paste its text from a global binding.
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        &mut *self.clipboard
+    }
+
+    /// Sets the capabilities of the backend, which components can read with
+    /// [`ComponentLink::capabilities`](../struct.ComponentLink.html#method.capabilities).
+    ///
+    /// Backends should call this before the first draw. If the capabilities
+    /// change afterwards, e.g. the size of a cell in pixels when the font
+    /// size changes, all components are re-rendered.
+    pub fn set_capabilities(&mut self, capabilities: BackendCapabilities) {
+        let mut current = self
+            .capabilities
+            .write()
+            .expect("capabilities lock is not poisoned");
+        if *current == capabilities {
+            return;
+        }
+        *current = capabilities;
+        for component in self.components.values_mut() {
+            component.should_render = true;
+        }
+        self.runtime.poll_state.merge(PollState::Dirty(None));
+    }
+
+    /// Returns the capabilities of the backend.
+    pub fn capabilities(&self) -> BackendCapabilities {
+        *self
+            .capabilities
+            .read()
+            .expect("capabilities lock is not poisoned")
+    }
+
+    /// Switches to a new [`Theme`](../theme/struct.Theme.html), which
+    /// components read with
+    /// [`ComponentLink::theme`](../struct.ComponentLink.html#method.theme).
+    /// All components are re-rendered if the theme changed.
+    pub fn set_theme(&mut self, theme: Theme) {
+        let mut current = self.theme.write().expect("theme lock is not poisoned");
+        if **current == theme {
+            return;
+        }
+        *current = Arc::new(theme);
+        for component in self.components.values_mut() {
+            component.should_render = true;
+        }
+        self.runtime.poll_state.merge(PollState::Dirty(None));
+    }
+
+    /// Returns the current theme.
+    pub fn theme(&self) -> Arc<Theme> {
+        self.theme
+            .read()
+            .expect("theme lock is not poisoned")
+            .clone()
+    }
+
+    /// Registers a long-lived service, e.g. a database handle, an HTTP client
+    /// or an LSP connection, which components can fetch with
+    /// [`ComponentLink::service`](../struct.ComponentLink.html#method.service)
+    /// instead of using global statics.
+    ///
+    /// Services are keyed by their type, registering a service of the same
+    /// type again replaces it. Components fetching the service afterwards get
+    /// the new one.
+    pub fn register_service<ServiceT: Send + Sync + 'static>(&mut self, service: ServiceT) {
+        self.services
+            .write()
+            .expect("services lock is not poisoned")
+            .insert(TypeId::of::<ServiceT>(), Arc::new(service));
+    }
+
+    /// Returns the service of type `ServiceT`, if one was registered.
+    pub fn service<ServiceT: Send + Sync + 'static>(&self) -> Option<Arc<ServiceT>> {
+        get_service(&self.services)
+    }
+
+    /// Sets the thresholds used to detect double presses and press-and-hold
+    /// and the timeout of pending key sequences.
+    pub fn set_key_timing(&mut self, timing: KeyTiming) {
+        self.controller.timing = timing;
+    }
+
+    /// Sets how long drawing a frame may take. Once the budget is exceeded,
+    /// re-rendering components with a
+    /// [`RenderPriority::Low`](../enum.RenderPriority.html) is deferred to
+    /// the next frame, keeping input latency low in heavy UIs. Components are
+    /// never deferred twice in a row. There is no budget by default.
+    pub fn set_frame_budget(&mut self, budget: Option<Duration>) {
+        self.runtime.frame_budget = budget;
+    }
+
+    /// Returns the metrics of the app, e.g. to export them for scraping or to
+    /// log them periodically.
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = self.runtime.metrics.clone();
+        metrics.components_mounted = self.components.len();
+        for (component_id, component) in self.components.iter() {
+            *metrics
+                .component_updates
+                .entry(component_id.type_name())
+                .or_default() += component.num_updates;
         }
+        metrics
+    }
+
+    /// Records the number of bytes written by the backend when presenting a
+    /// frame, see [`Metrics::bytes_presented`](struct.Metrics.html#structfield.bytes_presented).
+    pub fn record_presented(&mut self, num_bytes: usize) {
+        self.runtime.metrics.bytes_presented += num_bytes as u64;
+    }
+
+    /// Registers a middleware which runs on every message before it is
+    /// delivered. Middleware runs in the order it was added.
+    pub fn add_middleware(&mut self, middleware: impl Middleware) {
+        self.middleware.push(Box::new(middleware));
+    }
+
+    /// Replaces the root layout of the application.
+    ///
+    /// This allows swapping the entire UI (e.g. going from a login screen to
+    /// the main screen) without recreating the `App` and losing any backend
+    /// state. The change is applied lazily, when the app is next drawn.
+    ///
+    /// Components in the new layout are matched against the currently mounted
+    /// ones by type, key and position, like on any other re-render. Matching
+    /// components keep their state and receive the new properties through
+    /// `change`, the rest are created from scratch, while components that are
+    /// no longer part of the layout are dropped.
+    pub fn set_root(&mut self, root: Layout) {
+        self.root = root;
+        self.root_changed = true;
+        self.runtime.poll_state.merge(PollState::Dirty(None));
     }
 
     /// Return the application's poll state
@@ -154,30 +873,117 @@ impl App {
         !self.subscriptions.tickable.is_empty()
     }
 
-    /// Resizes the application's canvas lazily
+    /// Ticks the components which are currently tickable, passing them the
+    /// time elapsed since the previous tick.
+    ///
+    /// Backends should call `tick` periodically, e.g. once per frame, while
+    /// [`is_tickable`](#method.is_tickable) returns `true`. The elapsed time
+    /// is measured by the app, so animations advance correctly regardless of
+    /// the actual frame pacing.
     #[inline]
     pub fn tick(&mut self) {
-        for TickSubscription {
-            component_id,
-            message,
-        } in self.subscriptions.tickable.drain(..)
-        {
-            match self.components.get_mut(&component_id) {
-                Some(component) => {
-                    if component.update(message) {
-                        self.runtime.poll_state.merge(PollState::Dirty(None));
-                    }
-                }
-                None => {
-                    log::debug!(
-                        "Received message for nonexistent component (id: {}).",
-                        component_id,
-                    );
+        let now = self.now();
+        let delta = self
+            .runtime
+            .last_tick
+            .map(|last_tick| now.saturating_duration_since(last_tick))
+            .unwrap_or_default();
+        self.runtime.last_tick = Some(now);
+        self.tick_elapsed(delta);
+    }
+
+    /// Ticks the tickable components with a given elapsed time, e.g. the
+    /// simulated time of a test.
+    pub(crate) fn tick_elapsed(&mut self, delta: Duration) {
+        for component_id in self.subscriptions.tickable.iter() {
+            let component = match self.components.get_mut(component_id) {
+                Some(component) => component,
+                None => continue,
+            };
+            if let Some(message) = component.tick(delta) {
+                if component.update(message) {
+                    self.runtime.poll_state.merge(PollState::Dirty(None));
                 }
             }
         }
     }
 
+    /// The current time of the app's clock, see
+    /// [`ComponentLink::now`](../struct.ComponentLink.html#method.now).
+    pub(crate) fn now(&self) -> Instant {
+        clock_now(&self.clock)
+    }
+
+    /// Stops the app's clock at `now`, e.g. to simulate time in tests.
+    pub(crate) fn set_now(&mut self, now: Instant) {
+        *self.clock.write().expect("clock lock is not poisoned") = Some(now);
+    }
+
+    /// Takes the value passed to
+    /// [`ComponentLink::exit_with`](../struct.ComponentLink.html#method.exit_with)
+    /// by the component which stopped the app.
+    ///
+    /// Returns `None` if the app exited without a value or if the value is
+    /// not a `ValueT`, in which case it is dropped.
+    pub fn take_exit_value<ValueT: 'static>(&mut self) -> Option<ValueT> {
+        self.exit_value
+            .take()
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Returns `true` if a component requested to run a task with exclusive
+    /// access to the terminal, see
+    /// [`take_exclusive_task`](#method.take_exclusive_task).
+    pub fn has_exclusive_task(&self) -> bool {
+        !self.exclusive_tasks.is_empty()
+    }
+
+    /// Takes the next task requested with
+    /// [`ComponentLink::run_exclusive`](../struct.ComponentLink.html#method.run_exclusive),
+    /// in the order they were requested.
+    ///
+    /// Backends should check for tasks after handling messages. To run one,
+    /// the backend suspends itself, restoring the terminal to its normal
+    /// state, runs the task and then resumes and redraws the whole screen.
+    pub fn take_exclusive_task(&mut self) -> Option<ExclusiveTask> {
+        self.exclusive_tasks.pop_front()
+    }
+
+    /// Returns when the next timer scheduled by a component with
+    /// [`ComponentLink::send_after`](../struct.ComponentLink.html#method.send_after)
+    /// or [`ComponentLink::send_every`](../struct.ComponentLink.html#method.send_every)
+    /// is due, `None` if there are no timers. A pending key sequence which
+    /// times out, see
+    /// [`KeyTiming::prefix_timeout`](struct.KeyTiming.html#structfield.prefix_timeout),
+    /// counts as a timer too.
+    ///
+    /// Backends should wake up by then to call
+    /// [`fire_timers`](#method.fire_timers).
+    pub fn next_timer(&self) -> Option<Instant> {
+        match (self.timers.next_deadline(), self.controller.prefix_deadline) {
+            (Some(timer), Some(prefix)) => Some(timer.min(prefix)),
+            (timer, prefix) => timer.or(prefix),
+        }
+    }
+
+    /// Sends the messages of the timers which are due and resolves the
+    /// pending key sequence if it timed out. Like messages sent through a
+    /// `ComponentLink`, the messages pass through the middleware.
+    pub fn fire_timers(&mut self) {
+        self.fire_timers_at(self.now());
+    }
+
+    pub(crate) fn fire_timers_at(&mut self, now: Instant) {
+        for (component_id, message) in self.timers.fire(now) {
+            self.handle_message(ComponentMessage(LinkMessage::Component(
+                component_id,
+                message,
+            )));
+        }
+        self.expire_pending_keys(now);
+    }
+
     /// Compute component layout and draw the application to a canvas
     ///
     /// This function flushes all pending changes to the component tree,
@@ -185,6 +991,7 @@ impl App {
     /// function `poll_state()` will be `PollState::Clean`
     #[inline]
     pub fn draw(&mut self) -> &Canvas {
+        let mut next_poll_state = PollState::Clean;
         match self.runtime.poll_state {
             PollState::Dirty(maybe_new_size) => {
                 // Draw
@@ -201,8 +1008,26 @@ impl App {
                 }
 
                 let frame = Rect::new(Position::new(0, 0), self.runtime.screen.size());
-                let statistics = self.draw_tree(frame, self.runtime.num_frame);
+                let statistics = self.draw_tree(frame, self.runtime.num_frame, now, self.now());
                 let drawn_time = now.elapsed();
+                self.runtime.metrics.frames_drawn += 1;
+                self.runtime.metrics.draw_time += drawn_time;
+                self.runtime.metrics.last_draw_time = drawn_time;
+                if statistics.deferred > 0 {
+                    next_poll_state = PollState::Dirty(None);
+                }
+
+                // Measure the first tick from when components became tickable
+                if self.subscriptions.tickable.is_empty() {
+                    self.runtime.last_tick = None;
+                } else if self.runtime.last_tick.is_none() {
+                    self.runtime.last_tick = Some(now);
+                }
+
+                // The layout may have moved under a stationary mouse
+                if self.update_hover() {
+                    next_poll_state = PollState::Dirty(None);
+                }
 
                 // Present
                 // let now = Instant::now();
@@ -225,67 +1050,704 @@ impl App {
             }
             _ => {}
         }
-        self.runtime.poll_state = PollState::Clean;
+        self.runtime.poll_state = next_poll_state;
+        let cursor = self.cursor();
+        self.runtime.screen.set_cursor(cursor);
         &self.runtime.screen
     }
 
+    /// The terminal cursor requested by the components on the focus path,
+    /// in screen coordinates. See
+    /// [`Component::cursor`](../trait.Component.html#method.cursor).
+    fn cursor(&self) -> Option<TerminalCursor> {
+        self.subscriptions
+            .focus_path
+            .iter()
+            .find_map(|component_id| {
+                let component = self.components.get(component_id)?;
+                let cursor = component.renderable.cursor()?;
+                let frame = component.frame;
+                let position = frame.origin + cursor.position.to_vector();
+                frame
+                    .contains(position)
+                    .then(|| TerminalCursor { position, ..cursor })
+            })
+    }
+
     /// Resizes the application canvas. This operation is lazy and the mounted
     /// components won't be notified until [`draw`](method.draw.html) is called.
     pub fn handle_resize(&mut self, size: Size) {
+        self.record(|| RecordedEvent::Resize(size));
         self.runtime.poll_state.merge(PollState::Dirty(Some(size)));
     }
 
     #[inline]
-    pub fn handle_message(&mut self, message: ComponentMessage) {
+    pub fn handle_message(&mut self, mut message: ComponentMessage) {
+        for middleware in self.middleware.iter_mut() {
+            message = match middleware.process(message) {
+                Some(message) => message,
+                None => return,
+            };
+        }
+        self.runtime.metrics.messages_processed += 1;
         match message.0 {
             LinkMessage::Component(component_id, dyn_message) => {
-                let should_render = self
+                if self.is_replayed(component_id.type_id()) {
+                    return;
+                }
+                let clone = self.recorded_messages.get(&component_id.type_id()).copied();
+                self.record(|| RecordedEvent::Message {
+                    component: component_id.to_string(),
+                    message: clone
+                        .map(|clone| RecordedMessage::new(component_id, &dyn_message, clone)),
+                });
+                self.deliver(component_id, dyn_message);
+            }
+            LinkMessage::Keyed(keyed) => {
+                if self.is_replayed(keyed.type_id) {
+                    return;
+                }
+                let component_id = self
                     .components
-                    .get_mut(&component_id)
-                    .map(|component| component.update(dyn_message))
-                    .unwrap_or_else(|| {
-                        log::debug!(
-                            "Received message for nonexistent component (id: {}).",
-                            component_id,
-                        );
-                        false
-                    });
-                self.runtime.poll_state.merge(if should_render {
-                    PollState::Dirty(None)
-                } else {
-                    PollState::Clean
+                    .iter()
+                    .find(|(component_id, component)| {
+                        component_id.type_id() == keyed.type_id && component.key == Some(keyed.key)
+                    })
+                    .map(|(component_id, _)| *component_id);
+                // Keyed messages are replayed to the component they were
+                // delivered to
+                let clone = self.recorded_messages.get(&keyed.type_id).copied();
+                self.record(|| RecordedEvent::Message {
+                    component: format!("{} / key {}", keyed.type_name, keyed.key),
+                    message: component_id.zip(clone).map(|(component_id, clone)| {
+                        RecordedMessage::new(component_id, &keyed.message, clone)
+                    }),
+                });
+                match component_id {
+                    Some(component_id) => self.deliver(component_id, keyed.message),
+                    None => log::debug!(
+                        "Received message for nonexistent component (type: {}, key: {}).",
+                        keyed.type_name,
+                        keyed.key,
+                    ),
+                }
+            }
+            LinkMessage::Broadcast(broadcast) => {
+                self.record(|| RecordedEvent::Message {
+                    component: broadcast.type_name.to_string(),
+                    message: None,
+                });
+                let mut should_render = false;
+                for (component_id, component) in self.components.iter_mut() {
+                    if component_id.type_id() == broadcast.type_id {
+                        should_render = component.update(broadcast.message()) || should_render;
+                    }
+                }
+                if should_render {
+                    self.runtime.poll_state.merge(PollState::Dirty(None));
+                }
+            }
+            LinkMessage::Error(error) => {
+                self.record(|| RecordedEvent::Error(error.to_string()));
+                let mut handled = false;
+                if let Some(handler) = self.error_handler.as_mut() {
+                    handler(&error);
+                    handled = true;
+                }
+                let mut should_render = false;
+                for component in self.components.values_mut() {
+                    if let Some(message) = component.renderable.error(&error) {
+                        should_render = component.update(message) || should_render;
+                        handled = true;
+                    }
+                }
+                if !handled {
+                    log::error!(
+                        "Unhandled error reported by {}: {}",
+                        error.component_type_name(),
+                        error
+                    );
+                }
+                if should_render {
+                    self.runtime.poll_state.merge(PollState::Dirty(None));
+                }
+            }
+            LinkMessage::Subscribe(subscription) => self.bus.subscribe(subscription),
+            LinkMessage::Unsubscribe(topic, component_id) => {
+                self.bus.unsubscribe(topic, component_id)
+            }
+            LinkMessage::Publish(publication) => {
+                self.record(|| RecordedEvent::Message {
+                    component: publication.topic_name.to_string(),
+                    message: None,
                 });
+                let mut should_render = false;
+                for (component_id, message) in self.bus.messages(&publication) {
+                    if let Some(component) = self.components.get_mut(&component_id) {
+                        should_render = component.update(message) || should_render;
+                    }
+                }
+                if should_render {
+                    self.runtime.poll_state.merge(PollState::Dirty(None));
+                }
+            }
+            LinkMessage::Timer(timer) => self.timers.schedule(timer),
+            LinkMessage::Spawn(component_id, task, stream) => {
+                if let Some(stream) = stream {
+                    let streams = self.streams.entry(component_id).or_default();
+                    streams.retain(|stream| !stream.is_finished());
+                    streams.push(stream);
+                }
+                match self.executor {
+                    Some(ref executor) => executor.spawn(task),
+                    None => {
+                        thread::spawn(move || block_on(task));
+                    }
+                }
+            }
+            LinkMessage::RunExclusive(task) => self.exclusive_tasks.push_back(task),
+            LinkMessage::CopyToClipboard(text) => self.clipboard.copy(&text),
+            LinkMessage::Paste(component_id, message) => {
+                let text = self.clipboard.paste();
+                self.deliver(component_id, message(text));
             }
-            LinkMessage::Exit => {
-                self.runtime.poll_state.merge(PollState::Exit);
+            LinkMessage::SetTheme(theme) => self.set_theme(theme),
+            LinkMessage::SetKeymapOverrides(overrides) => self.set_keymap_overrides(overrides),
+            LinkMessage::Exit(value) => {
+                if value.is_some() {
+                    self.exit_value = value;
+                }
+                self.exit();
             }
         }
     }
 
-    #[inline]
-    pub fn handle_input(&mut self, event: Event) {
-        match event {
-            Event::KeyPress(key) => {
-                self.handle_key(key);
+    /// Stops the app, like
+    /// [`ComponentLink::exit`](../struct.ComponentLink.html#method.exit).
+    pub fn exit(&mut self) {
+        self.record(|| RecordedEvent::Exit);
+        self.runtime.poll_state.merge(PollState::Exit);
+    }
+
+    /// Delivers a message to a mounted component.
+    fn deliver(&mut self, component_id: ComponentId, dyn_message: DynamicMessage) {
+        let should_render = self
+            .components
+            .get_mut(&component_id)
+            .map(|component| component.update(dyn_message))
+            .unwrap_or_else(|| {
+                log::debug!(
+                    "Received message for nonexistent component (id: {}).",
+                    component_id,
+                );
+                false
+            });
+        self.runtime.poll_state.merge(if should_render {
+            PollState::Dirty(None)
+        } else {
+            PollState::Clean
+        });
+    }
+
+    /// Sends a copy of a message to every mounted component of type
+    /// `ComponentT`, e.g. to notify all panels of a theme change or to
+    /// invalidate their caches. Like [`send_to`](#method.send_to), the message
+    /// passes through the middleware once and is delivered immediately.
+    pub fn broadcast<ComponentT: Component>(&mut self, message: ComponentT::Message)
+    where
+        ComponentT::Message: Clone,
+    {
+        self.handle_message(ComponentMessage(LinkMessage::Broadcast(Broadcast::new::<
+            ComponentT,
+        >(message))));
+    }
+
+    /// Publishes a value on the topic of its type, delivering a copy to every
+    /// component subscribed with
+    /// [`ComponentLink::subscribe`](../struct.ComponentLink.html#method.subscribe).
+    /// Like [`broadcast`](#method.broadcast), the value passes through the
+    /// middleware once and is delivered immediately.
+    pub fn publish<TopicT: Clone + Send + 'static>(&mut self, value: TopicT) {
+        self.handle_message(ComponentMessage(LinkMessage::Publish(Publication::new(
+            value,
+        ))));
+    }
+
+    /// Starts recording the input events, resizes and component messages
+    /// handled by the app, see the [`recording`](../recording/index.html)
+    /// module. Any ongoing recording is discarded.
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(Recorder::new());
+    }
+
+    /// Stops recording and returns the recorded events, or `None` if the app
+    /// wasn't recording.
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recorder.take().map(Recorder::finish)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Records a copy of every message sent to the components of type
+    /// `ComponentT`, s.t. a [`Replay`](../recording/struct.Replay.html)
+    /// re-delivers them, including the messages which originated outside the
+    /// app, e.g. the results of background tasks. See the
+    /// [`recording`](../recording/index.html) module.
+    pub fn record_messages<ComponentT: Component>(&mut self)
+    where
+        ComponentT::Message: Clone,
+    {
+        self.recorded_messages.insert(
+            TypeId::of::<ComponentT>(),
+            recording::clone_message::<ComponentT::Message>,
+        );
+    }
+
+    /// Re-delivers a recorded message, bypassing the middleware which has
+    /// already processed it.
+    pub(crate) fn replay_message(&mut self, message: &RecordedMessage) {
+        self.record(|| RecordedEvent::Message {
+            component: message.component_id().to_string(),
+            message: Some(message.clone()),
+        });
+        self.deliver(message.component_id(), message.message());
+    }
+
+    pub(crate) fn set_replayed_component_types(&mut self, types: Option<&HashSet<TypeId>>) {
+        match types {
+            Some(types) if self.replayed_component_types != *types => {
+                self.replayed_component_types = types.clone();
+            }
+            Some(_) => {}
+            None => self.replayed_component_types.clear(),
+        }
+    }
+
+    fn is_replayed(&self, type_id: TypeId) -> bool {
+        let is_replayed = self.replayed_component_types.contains(&type_id);
+        if is_replayed {
+            log::debug!("Ignored a message which is replayed from a recording.");
+        }
+        is_replayed
+    }
+
+    #[inline]
+    fn record(&mut self, event: impl FnOnce() -> RecordedEvent) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(event());
+        }
+    }
+
+    /// Binds keys to an app-level command, e.g. to exit or to open a help
+    /// screen or a command palette. The global keymap is consulted regardless
+    /// of which component is focused, with lower precedence than the bindings
+    /// of the focused components: it only sees the keys they don't handle.
+    ///
+    /// The command is run with the app, e.g. to
+    /// [`exit`](#method.exit), [`publish`](#method.publish) a value or
+    /// [`send_to`](#method.send_to) a component. Like component bindings,
+    /// the keys of a global binding can't overlap with those of another one.
+    ///
+    /// ```
+    /// # use std::sync::mpsc;
+    /// # use zi::{app::{App, ComponentMessage, MessageSender}, prelude::*, terminal::Event};
+    /// # use zi::components::text::{Text, TextProperties};
+    /// # #[derive(Clone, Debug)]
+    /// # struct MessageQueue(mpsc::Sender<ComponentMessage>);
+    /// # impl MessageSender for MessageQueue {
+    /// #     fn send(&self, message: ComponentMessage) {
+    /// #         self.0.send(message).unwrap();
+    /// #     }
+    /// #     fn clone_box(&self) -> Box<dyn MessageSender> {
+    /// #         Box::new(self.clone())
+    /// #     }
+    /// # }
+    /// # let (sender, _receiver) = mpsc::channel();
+    /// let mut app = App::new(
+    ///     MessageQueue(sender),
+    ///     Size::new(10, 1),
+    ///     Text::with(TextProperties::new().content("Hello")),
+    /// );
+    /// app.add_global_binding("exit", [Key::Ctrl('x'), Key::Ctrl('c')], App::exit);
+    ///
+    /// app.draw();
+    /// app.handle_input(Event::key_press(Key::Ctrl('x')));
+    /// app.handle_input(Event::key_press(Key::Ctrl('c')));
+    /// assert!(app.poll_state().exit());
+    /// ```
+    pub fn add_global_binding(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        keys: impl Into<KeyPattern>,
+        command: impl Fn(&mut App) + 'static,
+    ) {
+        let command_id = self.global_bindings.keymap.add(name, keys);
+        self.global_bindings
+            .commands
+            .insert(command_id, Rc::new(command));
+    }
+
+    /// Removes the app-level binding for the given keys, returning `true` if
+    /// there was one.
+    pub fn remove_global_binding(&mut self, keys: impl Into<KeyPattern>) -> bool {
+        let keymap = &mut self.global_bindings.keymap;
+        match keymap.remove(keys) {
+            Some(command_id) => {
+                if keymap.bindings().all(|(_, other)| other != command_id) {
+                    self.global_bindings.commands.remove(&command_id);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The keymap of the app-level bindings, see
+    /// [`add_global_binding`](#method.add_global_binding).
+    pub fn global_keymap(&self) -> &Keymap {
+        &self.global_bindings.keymap
+    }
+
+    /// Sets overrides for the key bindings of components' named commands,
+    /// e.g. loaded from a user's config file, see
+    /// [`KeymapOverrides`](../struct.KeymapOverrides.html).
+    ///
+    /// Overrides are applied when a component builds its keymap. Setting
+    /// them after the app is drawn, e.g. when the user edits their settings,
+    /// clears the bindings of all mounted components, s.t. they are rebuilt
+    /// with the new overrides on the next draw.
+    pub fn set_keymap_overrides(&mut self, overrides: KeymapOverrides) {
+        self.keymap_overrides = overrides;
+        self.clear_bindings(&ComponentSelector::all());
+    }
+
+    /// Returns the overrides for the key bindings of components' named
+    /// commands.
+    pub fn keymap_overrides(&self) -> &KeymapOverrides {
+        &self.keymap_overrides
+    }
+
+    /// Sets the leader key, e.g. `Space` in a modal editor, which bindings
+    /// refer to as `<leader>`, see
+    /// [`KeyPattern::leader`](../enum.KeyPattern.html#method.leader). The
+    /// leader applies to the global bindings and the bindings of all
+    /// components, mounted or not. Without a leader, bindings starting with
+    /// `<leader>` aren't matched.
+    ///
+    /// ```
+    /// # use zi::{prelude::*, KeyPattern, components::text::{Text, TextProperties}};
+    /// # use zi::{app::{App, ComponentMessage, MessageSender}, terminal::Event};
+    /// # #[derive(Clone, Debug)]
+    /// # struct MessageQueue(std::sync::mpsc::Sender<ComponentMessage>);
+    /// # impl MessageSender for MessageQueue {
+    /// #     fn send(&self, message: ComponentMessage) {
+    /// #         self.0.send(message).unwrap();
+    /// #     }
+    /// #     fn clone_box(&self) -> Box<dyn MessageSender> {
+    /// #         Box::new(self.clone())
+    /// #     }
+    /// # }
+    /// # let (sender, receiver) = std::sync::mpsc::channel();
+    /// let mut app = App::new(
+    ///     MessageQueue(sender),
+    ///     Size::new(10, 1),
+    ///     Text::with(TextProperties::new().content("Hello")),
+    /// );
+    /// app.set_leader(Some(Key::Char(' ')));
+    /// app.add_global_binding("exit", KeyPattern::leader([Key::Char('q')]), App::exit);
+    ///
+    /// app.draw();
+    /// app.handle_input(Event::key_press(Key::Char(' ')));
+    /// app.handle_input(Event::key_press(Key::Char('q')));
+    /// assert!(app.poll_state().exit());
+    /// ```
+    pub fn set_leader(&mut self, leader: Option<Key>) {
+        self.leader = leader;
+        self.global_bindings.keymap.set_leader(leader);
+        for component in self.components.values_mut() {
+            component.bindings.set_leader(leader);
+        }
+    }
+
+    /// Returns the leader key, if set.
+    pub fn leader(&self) -> Option<Key> {
+        self.leader
+    }
+
+    /// Clears the key bindings of the mounted components matching the
+    /// `selector`. Components only add their bindings while they are empty,
+    /// so they are rebuilt from scratch on the next draw, e.g. after a
+    /// change of settings the components read their bindings from. Returns
+    /// the number of components whose bindings were cleared.
+    pub fn clear_bindings(&mut self, selector: &ComponentSelector) -> usize {
+        let mut num_cleared = 0;
+        for (component_id, component) in self.components.iter_mut() {
+            if selector.matches(component_id, component, &self.subscriptions) {
+                component.bindings.clear();
+                num_cleared += 1;
+            }
+        }
+        if num_cleared > 0 {
+            self.runtime.poll_state.merge(PollState::Dirty(None));
+        }
+        num_cleared
+    }
+
+    /// Executes a command by name, as if the key sequence bound to it had been
+    /// pressed.
+    ///
+    /// The command is looked up by name in the keymap of every mounted
+    /// component matching the `selector`. Components that do not define a
+    /// command with that name are skipped. The `keys` are passed on to the
+    /// command function just like the pressed keys would be.
+    ///
+    /// Components are only mounted after the app is first drawn. Returns the
+    /// number of components the command was executed on.
+    pub fn execute_command(
+        &mut self,
+        selector: &ComponentSelector,
+        name: &str,
+        keys: &[Key],
+    ) -> usize {
+        let mut num_executed = 0;
+        let mut should_render = false;
+        for (component_id, component) in self.components.iter_mut() {
+            if !selector.matches(component_id, component, &self.subscriptions) {
+                continue;
+            }
+            let command_id = match component.bindings.keymap().command_id(name) {
+                Some(command_id) => command_id,
+                None => continue,
+            };
+            if let Some(message) = component.renderable.run_command(
+                &component.bindings,
+                command_id,
+                keys,
+                None,
+                clock_now(&self.clock),
+            ) {
+                should_render = component.update(message) || should_render;
+            }
+            num_executed += 1;
+        }
+
+        if should_render {
+            self.runtime.poll_state.merge(PollState::Dirty(None));
+        }
+        num_executed
+    }
+
+    /// Runs a read-only closure on a mounted component, returning its result.
+    ///
+    /// This is useful in integration tests and developer tools that need to
+    /// inspect the internal state of a component rather than only the
+    /// rendered canvas. Returns `None` if no mounted component of type
+    /// `ComponentT` matches the `selector`. If more than one component
+    /// matches, which one is used is unspecified; use a key to disambiguate.
+    ///
+    /// Components are only mounted after the app is first drawn.
+    pub fn with_component<ComponentT: Component, ResultT>(
+        &self,
+        selector: &ComponentSelector,
+        inspect: impl FnOnce(&ComponentT) -> ResultT,
+    ) -> Option<ResultT> {
+        self.components
+            .iter()
+            .filter(|(component_id, component)| {
+                selector.matches(component_id, component, &self.subscriptions)
+            })
+            .find_map(|(_, component)| component.renderable.as_any().downcast_ref())
+            .map(inspect)
+    }
+
+    /// Returns a handle to a mounted component of type `ComponentT` matching
+    /// the `selector`, which can be used to send it messages with
+    /// [`send_to`](#method.send_to). If more than one component matches, which
+    /// one is used is unspecified; use a key to disambiguate.
+    ///
+    /// Components are only mounted after the app is first drawn.
+    pub fn handle_of<ComponentT: Component>(
+        &self,
+        selector: &ComponentSelector,
+    ) -> Option<ComponentHandle<ComponentT>> {
+        self.components
+            .iter()
+            .find(|(component_id, component)| {
+                component_id.type_id() == TypeId::of::<ComponentT>()
+                    && selector.matches(component_id, component, &self.subscriptions)
+            })
+            .map(|(component_id, _)| ComponentHandle::new(*component_id))
+    }
+
+    /// Sends a message to the component identified by `handle`, as if it was
+    /// sent through its [`ComponentLink`](../struct.ComponentLink.html). The
+    /// message passes through the middleware and is delivered immediately.
+    /// Messages sent to a component that is no longer mounted are ignored.
+    pub fn send_to<ComponentT: Component>(
+        &mut self,
+        handle: ComponentHandle<ComponentT>,
+        message: ComponentT::Message,
+    ) {
+        self.handle_message(ComponentMessage(handle.message(message)));
+    }
+
+    /// Returns the frame of a mounted component matching the `selector`, e.g.
+    /// to position a popup relative to it. If more than one component
+    /// matches, which one is used is unspecified. Components can get their
+    /// own frame with
+    /// [`ComponentLink::own_frame`](../struct.ComponentLink.html#method.own_frame).
+    pub fn frame_of(&self, selector: &ComponentSelector) -> Option<Rect> {
+        self.components
+            .iter()
+            .find(|(component_id, component)| {
+                selector.matches(component_id, component, &self.subscriptions)
+            })
+            .map(|(_, component)| component.frame)
+    }
+
+    #[inline]
+    pub fn handle_input(&mut self, event: Event) {
+        self.record(|| RecordedEvent::Input(event));
+        self.runtime.metrics.input_events += 1;
+        match event {
+            Event::KeyPress(key, timestamp) | Event::KeyRepeat(key, timestamp) => {
+                let is_repeat = matches!(event, Event::KeyRepeat(..));
+                let gesture = self.controller.classify(key, timestamp, is_repeat);
+                self.handle_key(key, gesture, timestamp);
                 // todo: handle_event should return whether we need to rerender
                 self.runtime.poll_state.merge(PollState::Dirty(None));
             }
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Moved,
+                position,
+                ..
+            }) => {
+                self.controller.mouse_position = Some(position);
+                if self.update_hover() {
+                    self.runtime.poll_state.merge(PollState::Dirty(None));
+                }
+            }
+            Event::KeyRelease(key, _) => {
+                let mut should_render = false;
+                for component_id in self.subscriptions.focus_path.iter() {
+                    let component = self
+                        .components
+                        .get_mut(component_id)
+                        .expect("focused component to be mounted");
+                    if let Some(message) = component.renderable.key_release(key) {
+                        should_render = component.update(message) || should_render;
+                    }
+                }
+                if should_render {
+                    self.runtime.poll_state.merge(PollState::Dirty(None));
+                }
+            }
+            Event::Mouse(event) => {
+                self.controller.mouse_position = Some(event.position);
+                self.update_hover();
+                let (key, timestamp) = match event.key() {
+                    Some(key) => (key, event.timestamp),
+                    None => return,
+                };
+                let gesture = self.controller.classify(key, timestamp, false);
+                self.handle_key(key, gesture, timestamp);
+                self.runtime.poll_state.merge(PollState::Dirty(None));
+            }
+        }
+    }
+
+    /// Sends hover events to the components the mouse entered or left since
+    /// the last call. Returns `true` if any of them needs to be re-rendered.
+    #[inline]
+    fn update_hover(&mut self) -> bool {
+        let Self {
+            ref root,
+            ref mut components,
+            ref layouts,
+            ref runtime,
+            controller: ref mut input_controller,
+            ..
+        } = *self;
+        let position = match input_controller.mouse_position {
+            Some(position) => position,
+            None => return false,
+        };
+
+        let mut hovered = Vec::new();
+        let frame = Rect::new(Position::new(0, 0), runtime.screen.size());
+        root.0.hit_test(frame, 0, layouts, position, &mut hovered);
+
+        let left = input_controller
+            .hovered
+            .iter()
+            .filter(|component_id| !hovered.contains(component_id))
+            .map(|component_id| (*component_id, HoverEvent::Leave));
+        let entered = hovered
+            .iter()
+            .filter(|component_id| !input_controller.hovered.contains(component_id))
+            .map(|component_id| (*component_id, HoverEvent::Enter(position)));
+        let mut should_render = false;
+        for (component_id, event) in left.chain(entered) {
+            if let Some(component) = components.get_mut(&component_id) {
+                if let Some(message) = component.renderable.hover(event) {
+                    should_render = component.update(message) || should_render;
+                }
+            }
         }
+        input_controller.hovered = hovered;
+        should_render
     }
 
     #[inline]
-    fn handle_key(&mut self, key: Key) {
+    fn handle_key(&mut self, key: Key, gesture: KeyGesture, timestamp: Instant) {
+        self.controller.push(key);
+        self.dispatch_keys(Some(key), gesture, timestamp);
+    }
+
+    /// Resolves the pending key sequence once its prefix timeout elapsed, see
+    /// [`KeyTiming::prefix_timeout`](struct.KeyTiming.html#structfield.prefix_timeout).
+    fn expire_pending_keys(&mut self, now: Instant) {
+        match self.controller.prefix_deadline {
+            Some(deadline) if deadline <= now => self.dispatch_keys(None, KeyGesture::Press, now),
+            _ => {}
+        }
+    }
+
+    /// Dispatches the pending keys to the components on the focus path and
+    /// to the global bindings. `key` is the key just pressed, or `None` when
+    /// the pending sequence timed out. Then it's only matched with exact
+    /// bindings, the bindings it's a prefix of are ignored.
+    fn dispatch_keys(&mut self, key: Option<Key>, gesture: KeyGesture, timestamp: Instant) {
+        let resolving = key.is_none();
         let Self {
             ref mut components,
-            ref subscriptions,
+            ref mut subscriptions,
             controller: ref mut input_controller,
+            ref global_bindings,
+            leader,
             ..
         } = *self;
+        let mut handled = false;
         let mut clear_controller = true;
-        let mut binding_queries = SmallVec::<[_; 4]>::with_capacity(subscriptions.focused.len());
+        let mut binding_queries = SmallVec::<[_; 4]>::with_capacity(subscriptions.focus_path.len());
+        // The keys continuing a pending sequence, only listed if a component
+        // is notified about them
+        let mut continuations = Vec::new();
+        let list_continuations = |keymap: &Keymap, keys: &[Key]| -> Vec<KeyContinuation> {
+            if subscriptions.notify.is_empty() {
+                return Vec::new();
+            }
+            keymap
+                .continuations(keys)
+                .into_iter()
+                .map(|(key, query)| KeyContinuation::new(keymap, key, query))
+                .collect()
+        };
 
-        input_controller.push(key);
-        for component_id in subscriptions.focused.iter() {
+        // The key bubbles up the focus path until a component handles it
+        for component_id in subscriptions.focus_path.iter() {
             let focused_component = components
                 .get_mut(component_id)
                 .expect("focused component to be mounted");
@@ -293,36 +1755,89 @@ impl App {
             let binding_query = focused_component
                 .bindings
                 .keymap()
-                .check_sequence(&input_controller.keys);
-            binding_queries.push(binding_query.map(|binding_query| {
+                .check_gesture(&input_controller.keys, gesture)
+                .filter(|binding_query| !resolving || binding_query.matches().is_some())
+                .map(Cow::into_owned);
+            binding_queries.push(binding_query.as_ref().map(|binding_query| {
                 NamedBindingQuery::new(focused_component.bindings.keymap(), binding_query)
             }));
-            match focused_component
-                .bindings
-                .keymap()
-                .check_sequence(&input_controller.keys)
-            {
+            match binding_query {
                 Some(BindingQuery::Match(command_id)) => {
                     if let Some(message) = focused_component.renderable.run_command(
                         &focused_component.bindings,
-                        *command_id,
+                        command_id,
                         &input_controller.keys,
+                        input_controller.click_position(),
+                        timestamp,
                     ) {
                         focused_component.update(message);
+                        handled = true;
+                    } else {
+                        // Commands returning no message didn't handle the
+                        // key, e.g. a filter over any character
+                        handled = !focused_component.bindings.returns_message(command_id);
+                    }
+                    if handled {
+                        break;
                     }
                 }
                 Some(BindingQuery::PrefixOf(prefix_of)) => {
                     log::info!(
                         "{} ({} commands)",
-                        KeySequenceSlice::from(input_controller.keys.as_slice()),
+                        KeySequenceSlice::from(input_controller.keys.as_slice()).leader(leader),
                         prefix_of.len()
                     );
+                    continuations = list_continuations(
+                        focused_component.bindings.keymap(),
+                        &input_controller.keys,
+                    );
+                    handled = true;
+                    clear_controller = false;
+                    break;
+                }
+                None => {}
+            }
+        }
+
+        // Global bindings see the keys no focused component handled
+        let mut global_command = None;
+        if !handled {
+            let keymap = &global_bindings.keymap;
+            let binding_query = keymap
+                .check_gesture(&input_controller.keys, gesture)
+                .filter(|binding_query| !resolving || binding_query.matches().is_some());
+            binding_queries.push(
+                binding_query
+                    .as_deref()
+                    .map(|binding_query| NamedBindingQuery::new(keymap, binding_query)),
+            );
+            match binding_query.as_deref() {
+                Some(BindingQuery::Match(command_id)) => {
+                    global_command = global_bindings.commands.get(command_id).cloned();
+                    handled = true;
+                }
+                Some(BindingQuery::PrefixOf(_)) => {
+                    continuations = list_continuations(keymap, &input_controller.keys);
+                    handled = true;
                     clear_controller = false;
                 }
                 None => {}
             }
         }
 
+        // Tab and BackTab move focus between focusable components, unless
+        // a focused component handled them
+        if !handled && input_controller.keys.len() == 1 {
+            let forward = match key {
+                Some(Key::Char('\t')) => Some(true),
+                Some(Key::BackTab) => Some(false),
+                _ => None,
+            };
+            if let Some(forward) = forward {
+                subscriptions.move_tab_focus(components, forward);
+            }
+        }
+
         for component_id in subscriptions.notify.iter() {
             let notify_component = components
                 .get_mut(component_id)
@@ -330,22 +1845,50 @@ impl App {
             notify_component
                 .renderable
                 .notify_binding_queries(&binding_queries, &input_controller.keys);
+            notify_component
+                .renderable
+                .notify_continuations(&continuations, &input_controller.keys);
         }
 
         // If any component returned `BindingTransition::Clear`, we clear the controller.
         if clear_controller {
             input_controller.keys.clear();
+            input_controller.prefix_deadline = None;
+        } else {
+            input_controller.prefix_deadline = input_controller
+                .timing
+                .prefix_timeout
+                .map(|timeout| timestamp + timeout);
+        }
+
+        if let Some(command) = global_command {
+            command(self);
         }
     }
 
     #[inline]
-    fn draw_tree(&mut self, frame: Rect, generation: Generation) -> DrawStatistics {
+    fn draw_tree(
+        &mut self,
+        frame: Rect,
+        generation: Generation,
+        started: Instant,
+        now: Instant,
+    ) -> DrawStatistics {
         let Self {
             ref mut components,
             ref mut layouts,
             ref mut runtime,
             ref mut subscriptions,
             ref sender,
+            ref capabilities,
+            ref theme,
+            ref services,
+            ref clock,
+            ref mut bus,
+            ref mut timers,
+            ref mut streams,
+            ref keymap_overrides,
+            leader,
             ..
         } = *self;
 
@@ -355,22 +1898,68 @@ impl App {
         let mut pending = Vec::new();
         let mut statistics = DrawStatistics::default();
         loop {
-            let (layout, frame2, position_hash, parent_changed) = if first {
+            let (
+                layout,
+                parent,
+                parent_tab_order,
+                parent_context,
+                frame2,
+                position_hash,
+                parent_hidden,
+                parent_changed,
+            ) = if first {
                 first = false;
-                (&mut self.root, frame, 0, false)
-            } else if let Some((component_id, frame, position_hash)) = pending.pop() {
+                (
+                    &mut self.root,
+                    None,
+                    TabOrder::default(),
+                    Context::default(),
+                    frame,
+                    0,
+                    false,
+                    mem::take(&mut self.root_changed),
+                )
+            } else if let Some((component_id, frame, position_hash, hidden, context)) =
+                pending.pop()
+            {
                 let component = components
                     .get_mut(&component_id)
                     .expect("Layout is cached only for mounted components");
+                let over_budget = !component.deferred
+                    && component.renderable.render_priority() == RenderPriority::Low
+                    && runtime
+                        .frame_budget
+                        .map_or(false, |budget| started.elapsed() >= budget);
+                let throttled = match (component.renderable.throttle(), component.last_rendered) {
+                    (Some(period), Some(last_rendered)) => {
+                        now.saturating_duration_since(last_rendered) < period
+                    }
+                    _ => false,
+                };
+                let deferred = component.should_render
+                    && layouts.contains_key(&component_id)
+                    && (over_budget || throttled);
+                component.deferred = deferred && over_budget;
                 let layout = layouts
                     .entry(component_id)
-                    .or_insert_with(|| component.view());
-                let changed = component.should_render;
+                    .or_insert_with(|| component.view(now));
+                let changed = component.should_render && !deferred;
                 if changed {
-                    *layout = component.view()
+                    *layout = component.view(now)
+                } else if deferred {
+                    statistics.deferred += 1;
                 }
                 component.set_generation(generation);
-                (layout, frame, position_hash, changed)
+                (
+                    layout,
+                    Some(component_id),
+                    component.tab_order.clone(),
+                    context,
+                    frame,
+                    position_hash,
+                    hidden,
+                    changed,
+                )
             } else {
                 break;
             };
@@ -378,29 +1967,59 @@ impl App {
             layout.0.crawl(
                 frame2,
                 position_hash,
+                parent_hidden,
+                &parent_tab_order,
+                &parent_context,
                 &mut |LaidComponent {
                           frame,
                           position_hash,
+                          hidden,
+                          tab_order,
+                          context,
                           template,
                       }| {
                     let component_id = template.generate_id(position_hash);
                     let mut new_component = false;
                     let component = components.entry(component_id).or_insert_with(|| {
                         new_component = true;
-                        let (renderable, bindings) =
-                            template.create(component_id, frame, sender.clone_box());
+                        let shared_frame = SharedFrame::new(frame.into());
+                        let shared_context =
+                            SharedContext::new(ComponentContext::new(context.clone()).into());
+                        let (renderable, bindings) = template.create(
+                            component_id,
+                            frame,
+                            sender.clone_box(),
+                            capabilities.clone(),
+                            theme.clone(),
+                            shared_frame.clone(),
+                            services.clone(),
+                            shared_context.clone(),
+                            clock.clone(),
+                        );
                         MountedComponent {
                             renderable,
+                            key: template.key(),
+                            parent,
                             frame,
+                            shared_frame,
+                            shared_context,
                             bindings,
                             should_render: ShouldRender::Yes.into(),
+                            deferred: false,
+                            last_rendered: None,
+                            focused_since: None,
+                            tab_order: TabOrder::default(),
+                            num_updates: 0,
                             generation,
                         }
                     });
+                    component.parent = parent;
+                    component.tab_order = tab_order;
 
                     if !new_component {
                         let mut changed =
                             parent_changed && component.change(template.dynamic_properties());
+                        changed = component.set_context(context.clone()) || changed;
                         if frame != component.frame {
                             changed = component.resize(frame) || changed;
                         }
@@ -414,37 +2033,92 @@ impl App {
                     }
 
                     component.update_bindings();
-                    if component.bindings.focused() {
-                        subscriptions.add_focused(component_id);
+                    component.bindings.set_leader(leader);
+                    component
+                        .bindings
+                        .apply_overrides(component_id.type_name(), keymap_overrides);
+                    let focused = !hidden
+                        && (component.bindings.focused()
+                            || subscriptions.tab_focus == Some(component_id));
+                    if !focused {
+                        component.focused_since = None;
+                    } else if component.focused_since.is_none() {
+                        component.focused_since = Some(subscriptions.next_focus_order());
                     }
+                    if !hidden {
+                        if focused {
+                            subscriptions.add_focused(component_id);
+                        }
 
-                    if component.bindings.notify() {
-                        subscriptions.add_notify(component_id);
-                    }
+                        if component.bindings.focusable() && !component.tab_order.skip {
+                            subscriptions.focusable.push(component_id);
+                        }
+
+                        if component.bindings.trap_focus() {
+                            subscriptions.traps.push(component_id);
+                        }
 
-                    if let Some(message) = component.tick() {
-                        subscriptions.add_tickable(component_id, message);
+                        if component.bindings.notify() {
+                            subscriptions.add_notify(component_id);
+                        }
+
+                        if component.bindings.tickable() {
+                            subscriptions.add_tickable(component_id);
+                        }
                     }
 
-                    pending.push((component_id, frame, position_hash));
-                },
-                &mut |LaidCanvas { frame, canvas, .. }| {
-                    runtime.screen.copy_region(canvas, frame);
+                    pending.push((component_id, frame, position_hash, hidden, context));
                 },
             );
         }
 
+        if subscriptions.update_focus(components) {
+            runtime.poll_state.merge(PollState::Dirty(None));
+        }
+
+        // Draw the canvases in the layout tree back to front. This is done in
+        // a separate pass after all the components are mounted and have
+        // up-to-date layouts s.t. layers in a stack are composited in order.
+        self.root.0.paint(
+            frame,
+            0,
+            layouts,
+            &mut |LaidCanvas {
+                      frame,
+                      canvas,
+                      window,
+                      transparent,
+                  }| {
+                if transparent {
+                    runtime.screen.blend_window(canvas, window, frame.origin);
+                } else {
+                    runtime.screen.copy_window(canvas, window, frame.origin);
+                }
+            },
+        );
+
         // Drop components that are not part of the current layout tree, i.e. do
         // not appear on the screen.
         components.retain(
             |component_id,
              &mut MountedComponent {
                  generation: component_generation,
+                 num_updates,
                  ..
              }| {
                 if component_generation < generation {
                     statistics.deleted += 1;
+                    *runtime
+                        .metrics
+                        .component_updates
+                        .entry(component_id.type_name())
+                        .or_default() += num_updates;
                     layouts.remove(component_id);
+                    bus.remove_component(*component_id);
+                    timers.remove_component(*component_id);
+                    for stream in streams.remove(component_id).into_iter().flatten() {
+                        stream.cancel();
+                    }
                     false
                 } else {
                     true
@@ -456,28 +2130,265 @@ impl App {
     }
 }
 
+/// Runs an app-level command, see `App::add_global_binding`.
+type GlobalCommandFn = Rc<dyn Fn(&mut App)>;
+
+/// The app-level key bindings, see `App::add_global_binding`.
+#[derive(Default)]
+struct GlobalBindings {
+    keymap: Keymap,
+    commands: HashMap<CommandId, GlobalCommandFn>,
+}
+
 struct ComponentSubscriptions {
     focused: SmallVec<[ComponentId; 2]>,
+    /// The focused components which receive key presses, from the active
+    /// component to the root. See `update_focus_path`.
+    focus_path: SmallVec<[ComponentId; 2]>,
+    focusable: SmallVec<[ComponentId; 4]>,
+    traps: SmallVec<[ComponentId; 1]>,
     notify: SmallVec<[ComponentId; 2]>,
-    tickable: SmallVec<[TickSubscription; 2]>,
+    tickable: SmallVec<[ComponentId; 2]>,
+    /// Incremented every time a component gains focus.
+    focus_order: u64,
+    /// The focusable component which has Tab focus.
+    tab_focus: Option<ComponentId>,
+    /// The active focus traps, innermost last.
+    trap_stack: Vec<FocusTrap>,
+}
+
+/// An active focus trap with the focus to restore when it's deactivated.
+struct FocusTrap {
+    scope: ComponentId,
+    tab_focus: Option<ComponentId>,
+    active: Option<ComponentId>,
 }
 
 impl ComponentSubscriptions {
     fn new() -> Self {
         Self {
             focused: SmallVec::new(),
+            focus_path: SmallVec::new(),
+            focusable: SmallVec::new(),
+            traps: SmallVec::new(),
             notify: SmallVec::new(),
             tickable: SmallVec::new(),
+            focus_order: 0,
+            tab_focus: None,
+            trap_stack: Vec::new(),
         }
     }
 
     #[inline]
     fn clear(&mut self) {
         self.focused.clear();
+        self.focusable.clear();
+        self.traps.clear();
         self.notify.clear();
         self.tickable.clear();
     }
 
+    #[inline]
+    fn next_focus_order(&mut self) -> u64 {
+        self.focus_order += 1;
+        self.focus_order
+    }
+
+    /// Updates Tab focus and focus traps after the components were laid out
+    /// and then the focus path. Returns `true` if any component needs to be
+    /// re-rendered.
+    fn update_focus(&mut self, components: &mut HashMap<ComponentId, MountedComponent>) -> bool {
+        let mut should_render = false;
+        if let Some(tab_focus) = self.tab_focus {
+            if !self.focusable.contains(&tab_focus) {
+                should_render = self.set_tab_focus(components, None);
+            }
+        }
+
+        // Deactivated traps restore the focus from before they were
+        // activated, together with any traps nested inside them
+        if let Some(deactivated) = self
+            .trap_stack
+            .iter()
+            .position(|trap| !self.traps.contains(&trap.scope))
+        {
+            for trap in self.trap_stack.split_off(deactivated).into_iter().rev() {
+                if let Some(active) = trap.active.filter(|id| self.focused.contains(id)) {
+                    let focus_order = self.next_focus_order();
+                    components
+                        .get_mut(&active)
+                        .expect("focused component to be mounted")
+                        .focused_since = Some(focus_order);
+                }
+                if trap
+                    .tab_focus
+                    .map_or(true, |id| self.focusable.contains(&id))
+                {
+                    should_render = self.set_tab_focus(components, trap.tab_focus) || should_render;
+                }
+            }
+        }
+
+        // Newly activated traps move Tab focus inside them. Ancestors are
+        // laid out before their descendants, s.t. nested traps are pushed in
+        // order.
+        for scope in self.traps.clone() {
+            if self.trap_stack.iter().any(|trap| trap.scope == scope) {
+                continue;
+            }
+            self.trap_stack.push(FocusTrap {
+                scope,
+                tab_focus: self.tab_focus,
+                active: self.focus_path.first().copied(),
+            });
+            let inside = self
+                .tab_focus
+                .map_or(false, |id| is_descendant(components, id, scope));
+            if !inside {
+                let first = self.tab_order(components).first().copied();
+                should_render = self.set_tab_focus(components, first) || should_render;
+            }
+        }
+
+        self.update_focus_path(components);
+        should_render
+    }
+
+    /// Focused components form a tree, where the parent of a component is
+    /// its closest focused ancestor. The active component is the leaf of the
+    /// tree which gained focus most recently and the focus path goes from it
+    /// to the root. Key presses are handled by the first component on the
+    /// path with a matching binding, i.e. they bubble up from the active
+    /// component to its focused ancestors. Focused components which bubble
+    /// keys also pass them to their ancestors up to the next focused one.
+    /// While a focus trap is active, only the trapping component and its
+    /// descendants are part of the tree.
+    fn update_focus_path(&mut self, components: &HashMap<ComponentId, MountedComponent>) {
+        let scope = self.trap_stack.last().map(|trap| trap.scope);
+        let in_trap = |component_id: &ComponentId| {
+            scope.map_or(true, |scope| {
+                *component_id == scope || is_descendant(components, *component_id, scope)
+            })
+        };
+        let in_scope = |component_id: &ComponentId| {
+            self.focused.contains(component_id) && in_trap(component_id)
+        };
+        let focused_ancestors = |component_id: ComponentId| {
+            std::iter::successors(components[&component_id].parent, |parent| {
+                components[parent].parent
+            })
+            .filter(in_scope)
+        };
+        let mut has_focused_descendant = SmallVec::<[ComponentId; 2]>::new();
+        for component_id in self.focused.iter() {
+            has_focused_descendant.extend(focused_ancestors(*component_id));
+        }
+        let active = self
+            .focused
+            .iter()
+            .filter(|component_id| in_scope(component_id))
+            .filter(|component_id| !has_focused_descendant.contains(component_id))
+            .max_by_key(|component_id| components[component_id].focused_since);
+        let mut path = SmallVec::new();
+        let mut bubble_keys = false;
+        let ancestors = active.into_iter().flat_map(|&active| {
+            std::iter::successors(Some(active), |component_id| components[component_id].parent)
+        });
+        for component_id in ancestors {
+            if in_scope(&component_id) {
+                bubble_keys = components[&component_id].bindings.bubble_keys();
+                path.push(component_id);
+            } else if bubble_keys
+                && in_trap(&component_id)
+                && !components[&component_id].bindings.keymap().is_empty()
+            {
+                path.push(component_id);
+            }
+        }
+        self.focus_path = path;
+    }
+
+    /// The focusable components inside the innermost focus trap, in the
+    /// order they are visited with Tab. That's layout order, unless items set
+    /// an explicit focus order.
+    fn tab_order(
+        &self,
+        components: &HashMap<ComponentId, MountedComponent>,
+    ) -> SmallVec<[ComponentId; 4]> {
+        let scope = self.trap_stack.last().map(|trap| trap.scope);
+        let mut order: SmallVec<[ComponentId; 4]> = self
+            .focusable
+            .iter()
+            .copied()
+            .filter(|&id| scope.map_or(true, |scope| is_descendant(components, id, scope)))
+            .collect();
+        order.sort_by(|left, right| components[left].tab_order.cmp(&components[right].tab_order));
+        order
+    }
+
+    /// Moves Tab focus to the next (or previous) focusable component and
+    /// updates the focus path. Returns `true` if a component needs to be
+    /// re-rendered.
+    fn move_tab_focus(
+        &mut self,
+        components: &mut HashMap<ComponentId, MountedComponent>,
+        forward: bool,
+    ) -> bool {
+        let order = self.tab_order(components);
+        if order.is_empty() {
+            return false;
+        }
+        let current = self
+            .tab_focus
+            .and_then(|tab_focus| order.iter().position(|id| *id == tab_focus));
+        let next = match current {
+            Some(index) if forward => (index + 1) % order.len(),
+            Some(index) => (index + order.len() - 1) % order.len(),
+            None if forward => 0,
+            None => order.len() - 1,
+        };
+        let should_render = self.set_tab_focus(components, Some(order[next]));
+        self.update_focus_path(components);
+        should_render
+    }
+
+    fn set_tab_focus(
+        &mut self,
+        components: &mut HashMap<ComponentId, MountedComponent>,
+        tab_focus: Option<ComponentId>,
+    ) -> bool {
+        if self.tab_focus == tab_focus {
+            return false;
+        }
+        let mut should_render = false;
+        if let Some(previous) = self.tab_focus.take() {
+            if let Some(component) = components.get_mut(&previous) {
+                if !component.bindings.focused() {
+                    self.focused.retain(|id| *id != previous);
+                    component.focused_since = None;
+                }
+                if let Some(message) = component.renderable.focus(false) {
+                    should_render = component.update(message);
+                }
+            }
+        }
+        if let Some(next) = tab_focus {
+            let focus_order = self.next_focus_order();
+            let component = components
+                .get_mut(&next)
+                .expect("focusable component to be mounted");
+            if !self.focused.contains(&next) {
+                self.focused.push(next);
+            }
+            component.focused_since = Some(focus_order);
+            if let Some(message) = component.renderable.focus(true) {
+                should_render = component.update(message) || should_render;
+            }
+        }
+        self.tab_focus = tab_focus;
+        should_render
+    }
+
     #[inline]
     fn add_focused(&mut self, component_id: ComponentId) {
         self.focused.push(component_id);
@@ -489,17 +2400,21 @@ impl ComponentSubscriptions {
     }
 
     #[inline]
-    fn add_tickable(&mut self, component_id: ComponentId, message: DynamicMessage) {
-        self.tickable.push(TickSubscription {
-            component_id,
-            message,
-        });
+    fn add_tickable(&mut self, component_id: ComponentId) {
+        self.tickable.push(component_id);
     }
 }
 
-struct TickSubscription {
+/// Whether `scope` is an ancestor of a component.
+fn is_descendant(
+    components: &HashMap<ComponentId, MountedComponent>,
     component_id: ComponentId,
-    message: DynamicMessage,
+    scope: ComponentId,
+) -> bool {
+    std::iter::successors(components[&component_id].parent, |parent| {
+        components[parent].parent
+    })
+    .any(|ancestor| ancestor == scope)
 }
 
 impl PollState {
@@ -530,10 +2445,24 @@ type Generation = usize;
 
 struct MountedComponent {
     renderable: Box<dyn Renderable>,
+    key: Option<ComponentKey>,
+    /// The closest ancestor component, `None` for components in the root
+    /// layout.
+    parent: Option<ComponentId>,
     frame: Rect,
+    shared_frame: SharedFrame,
+    shared_context: SharedContext,
     bindings: DynamicBindings,
     generation: Generation,
     should_render: bool,
+    /// Whether re-rendering was deferred in the last frame because the frame
+    /// budget was exceeded.
+    deferred: bool,
+    last_rendered: Option<Instant>,
+    /// When the component last gained focus, `None` if it isn't focused.
+    focused_since: Option<u64>,
+    tab_order: TabOrder,
+    num_updates: u64,
 }
 
 impl MountedComponent {
@@ -545,20 +2474,38 @@ impl MountedComponent {
 
     #[inline]
     fn resize(&mut self, frame: Rect) -> bool {
+        *self
+            .shared_frame
+            .write()
+            .expect("frame lock is not poisoned") = frame;
         self.should_render = self.renderable.resize(frame).into() || self.should_render;
         self.frame = frame;
         self.should_render
     }
 
+    /// Sets the values provided to the component by the enclosing layouts.
+    #[inline]
+    fn set_context(&mut self, context: Context) -> bool {
+        let changed = self
+            .shared_context
+            .write()
+            .expect("context lock is not poisoned")
+            .set(context);
+        self.should_render = changed || self.should_render;
+        changed
+    }
+
     #[inline]
     fn update(&mut self, message: DynamicMessage) -> bool {
+        self.num_updates += 1;
         self.should_render = self.renderable.update(message).into() || self.should_render;
         self.should_render
     }
 
     #[inline]
-    fn view(&mut self) -> Layout {
+    fn view(&mut self, now: Instant) -> Layout {
         self.should_render = false;
+        self.last_rendered = Some(now);
         self.renderable.view()
     }
 
@@ -568,8 +2515,8 @@ impl MountedComponent {
     }
 
     #[inline]
-    fn tick(&self) -> Option<DynamicMessage> {
-        self.renderable.tick()
+    fn tick(&self, delta: Duration) -> Option<DynamicMessage> {
+        self.renderable.tick(delta)
     }
 
     #[inline]
@@ -580,18 +2527,104 @@ impl MountedComponent {
 
 struct InputController {
     keys: SmallVec<[Key; 8]>,
+    mouse_position: Option<Position>,
+    /// Components under the mouse, outermost first.
+    hovered: Vec<ComponentId>,
+    timing: KeyTiming,
+    /// When the pending key sequence times out, if it's a prefix.
+    prefix_deadline: Option<Instant>,
+    /// The last key pressed, when it was first pressed and last repeated
+    /// and whether it was already classified as a double press or hold.
+    last_press: Option<LastPress>,
+}
+
+struct LastPress {
+    key: Key,
+    first: Instant,
+    last: Instant,
+    presses: usize,
+    held: bool,
+}
+
+impl LastPress {
+    fn new(key: Key, now: Instant) -> Self {
+        Self {
+            key,
+            first: now,
+            last: now,
+            presses: 1,
+            held: false,
+        }
+    }
 }
 
 impl InputController {
     fn new() -> Self {
         Self {
             keys: SmallVec::new(),
+            mouse_position: None,
+            hovered: Vec::new(),
+            timing: KeyTiming::default(),
+            prefix_deadline: None,
+            last_press: None,
+        }
+    }
+
+    /// Classifies a key press using the presses before it. `is_repeat` is
+    /// `true` if the backend reported the press as a repeat of a held key.
+    fn classify(&mut self, key: Key, now: Instant, is_repeat: bool) -> KeyGesture {
+        let timing = self.timing;
+        let last_press = match self.last_press {
+            Some(ref mut last_press)
+                if last_press.key == key
+                    && (is_repeat
+                        || now.saturating_duration_since(last_press.last) <= timing.repeat) =>
+            {
+                last_press
+            }
+            _ => {
+                self.last_press = Some(LastPress::new(key, now));
+                return KeyGesture::Press;
+            }
+        };
+
+        let since_last = now.saturating_duration_since(last_press.last);
+        if !is_repeat && last_press.presses > 1 && since_last > timing.repeat_interval {
+            // Repeats of a held key follow each other quickly after the first
+            // one, a slower press is a separate tap
+            *last_press = LastPress::new(key, now);
+            return KeyGesture::Press;
+        }
+        last_press.last = now;
+        last_press.presses += 1;
+        if !is_repeat && last_press.presses == 2 && since_last <= timing.double_press {
+            // A third quick press starts a new double press
+            self.last_press = None;
+            KeyGesture::DoublePress
+        } else if !last_press.held
+            && (is_repeat || last_press.presses > 2)
+            && now.saturating_duration_since(last_press.first) >= timing.hold
+        {
+            last_press.held = true;
+            KeyGesture::Hold
+        } else {
+            KeyGesture::Press
         }
     }
 
     fn push(&mut self, key: Key) {
         self.keys.push(key);
     }
+
+    /// The position of the mouse if the pending key sequence contains a
+    /// mouse key, e.g. a click.
+    fn click_position(&self) -> Option<Position> {
+        self.keys
+            .iter()
+            .any(Key::is_mouse)
+            .then(|| self.mouse_position)
+            .flatten()
+    }
 }
 
 impl std::fmt::Display for InputController {
@@ -606,6 +2639,11 @@ impl std::fmt::Display for InputController {
                 Key::Alt(char) => write!(formatter, "A-{} ", char)?,
                 Key::F(number) => write!(formatter, "F{} ", number)?,
                 Key::Esc => write!(formatter, "ESC ")?,
+                key if key.is_mouse() => write!(
+                    formatter,
+                    "{} ",
+                    KeySequenceSlice::from(std::slice::from_ref(key))
+                )?,
                 key => write!(formatter, "{:?} ", key)?,
             }
         }
@@ -619,26 +2657,43 @@ struct DrawStatistics {
     changed: usize,
     deleted: usize,
     nop: usize,
+    deferred: usize,
 }
 
 impl std::fmt::Display for DrawStatistics {
     fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             formatter,
-            "{} new {} upd {} del {} nop",
-            self.new, self.changed, self.deleted, self.nop
+            "{} new {} upd {} del {} nop {} deferred",
+            self.new, self.changed, self.deleted, self.nop, self.deferred
         )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::mpsc;
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::mpsc,
+        time::Duration,
+    };
 
     use super::*;
+
     use crate::{
-        component::ComponentExt,
-        components::text::{Text, TextProperties},
+        components::{
+            border::{Border, BorderProperties},
+            pager::{Pager, PagerProperties},
+            search::{SearchBar, SearchBarProperties, SearchTarget},
+            text::{Text, TextProperties},
+            tooltip::{Tooltip, TooltipProperties},
+            which_key::{WhichKey, WhichKeyProperties},
+        },
+        prelude::*,
+        terminal::{ColourSupport, CursorShape, Grapheme, Modifiers, MouseButton},
+        testing::Harness,
+        DoublePress, FunctionComponent, Hooks, KeymapOverrides, SizeHint, TimerHandle,
     };
 
     #[derive(Clone, Debug)]
@@ -690,6 +2745,2804 @@ mod tests {
         }
     }
 
+    struct Counter {
+        count: usize,
+    }
+
+    impl Component for Counter {
+        type Message = usize;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self { count: 0 }
+        }
+
+        fn view(&self) -> Layout {
+            let mut canvas = Canvas::new(Size::new(10, 1));
+            canvas.draw_str(0, 0, Style::default(), &self.count.to_string());
+            canvas.into()
+        }
+
+        fn update(&mut self, increment: Self::Message) -> ShouldRender {
+            self.count += increment;
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.set_focus(true);
+            bindings.add("increment", [Key::Char('+')], |keys: &[Key]| {
+                Some(keys.len().max(1))
+            });
+        }
+    }
+
+    fn first_grapheme(canvas: &Canvas) -> &str {
+        canvas.textel(0, 0).as_ref().unwrap().grapheme.as_str()
+    }
+
+    #[test]
+    fn execute_command_by_name() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 1),
+            Counter::with(()),
+        );
+
+        // Components are mounted on the first draw
+        assert_eq!(
+            app.execute_command(&ComponentSelector::of::<Counter>(), "increment", &[]),
+            0
+        );
+        assert_eq!(first_grapheme(app.draw()), "0");
+
+        let selector = ComponentSelector::of::<Counter>();
+        assert_eq!(app.execute_command(&selector, "increment", &[]), 1);
+        assert!(app.poll_state().dirty());
+        assert_eq!(first_grapheme(app.draw()), "1");
+
+        let focused = ComponentSelector::focused();
+        assert_eq!(
+            app.execute_command(&focused, "increment", &[Key::Char('+'); 3]),
+            1
+        );
+        assert_eq!(first_grapheme(app.draw()), "4");
+
+        // Unknown commands, types or keys don't match any components
+        assert_eq!(app.execute_command(&selector, "decrement", &[]), 0);
+        assert_eq!(
+            app.execute_command(&ComponentSelector::of::<Text>(), "increment", &[]),
+            0
+        );
+        assert_eq!(
+            app.execute_command(&selector.key("other"), "increment", &[]),
+            0
+        );
+    }
+
+    #[test]
+    fn send_messages_by_handle() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 1),
+            Layout::column([
+                Item::fixed(1)(Counter::with_key("a", ())),
+                Item::fixed(1)(Counter::with_key("b", ())),
+            ]),
+        );
+        let selector = ComponentSelector::of::<Counter>().key("b");
+        assert_eq!(app.handle_of::<Counter>(&selector), None);
+
+        app.draw();
+        let handle = app.handle_of::<Counter>(&selector).unwrap();
+        app.send_to(handle, 5);
+        assert!(app.poll_state().dirty());
+        let count = |app: &App, key| {
+            app.with_component(
+                &ComponentSelector::of::<Counter>().key(key),
+                |counter: &Counter| counter.count,
+            )
+        };
+        assert_eq!((count(&app, "a"), count(&app, "b")), (Some(0), Some(5)));
+
+        // Handles are stable across renders and only match their own type
+        app.draw();
+        assert_eq!(app.handle_of::<Counter>(&selector), Some(handle));
+        assert_eq!(app.handle_of::<Text>(&selector), None);
+    }
+
+    struct Incrementer(ComponentLink<Self>);
+
+    impl Component for Incrementer {
+        type Message = usize;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self(link)
+        }
+
+        fn update(&mut self, increment: Self::Message) -> ShouldRender {
+            self.0
+                .send_to(ComponentHandle::<Counter>::keyed("b"), increment);
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+    }
+
+    #[test]
+    fn send_messages_by_key() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 3),
+            Layout::column([
+                Item::fixed(1)(Counter::with_key("a", ())),
+                Item::fixed(1)(Layout::row([Item::auto(Counter::with_key("b", ()))])),
+                Item::fixed(1)(Incrementer::with(())),
+            ]),
+        );
+        let count = |app: &App, key| {
+            app.with_component(
+                &ComponentSelector::of::<Counter>().key(key),
+                |counter: &Counter| counter.count,
+            )
+        };
+
+        // Keyed handles don't need the component to be mounted, messages sent
+        // before it is are ignored
+        let handle = ComponentHandle::<Counter>::keyed("b");
+        app.send_to(handle, 1);
+        app.draw();
+        app.send_to(handle, 2);
+        assert!(app.poll_state().dirty());
+        assert_eq!((count(&app, "a"), count(&app, "b")), (Some(0), Some(2)));
+
+        // Components can address each other by key anywhere in the tree
+        let incrementer = app
+            .handle_of::<Incrementer>(&ComponentSelector::of::<Incrementer>())
+            .unwrap();
+        app.send_to(incrementer, 3);
+        while let Ok(message) = receiver.try_recv() {
+            app.handle_message(message);
+        }
+        assert_eq!((count(&app, "a"), count(&app, "b")), (Some(0), Some(5)));
+    }
+
+    #[test]
+    fn weighted_items_share_space_in_proportion() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(11, 1),
+            Layout::row([
+                Item::weighted(2)(Counter::with_key("wide", ())),
+                Item::fixed(2)(Counter::with_key("fixed", ())),
+                Item::auto(Counter::with_key("narrow", ())),
+                Item::weighted(0)(Counter::with_key("collapsed", ())),
+            ]),
+        );
+        let widths = |app: &mut App| {
+            app.draw();
+            ["wide", "fixed", "narrow", "collapsed"].map(|key| {
+                app.frame_of(&ComponentSelector::of::<Counter>().key(key))
+                    .unwrap()
+                    .size
+                    .width
+            })
+        };
+        assert_eq!(widths(&mut app), [6, 2, 3, 0]);
+
+        // Cells left over after rounding down go to the first items
+        app.handle_resize(Size::new(10, 1));
+        assert_eq!(widths(&mut app), [6, 2, 2, 0]);
+    }
+
+    #[test]
+    fn padding_and_margins_leave_space_around_items() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(12, 6),
+            Container::row([
+                Item::fixed(3)(Counter::with_key("fixed", ())).margin(Spacing::new(0, 1, 0, 2)),
+                Item::auto(Counter::with_key("auto", ())).margin(Spacing::all(1)),
+            ])
+            .padding(Spacing::symmetric(1, 2))
+            .into(),
+        );
+        app.draw();
+        let frame_of = |key| {
+            app.frame_of(&ComponentSelector::of::<Counter>().key(key))
+                .unwrap()
+        };
+        assert_eq!(
+            frame_of("fixed"),
+            Rect::new(Position::new(4, 1), Size::new(3, 4))
+        );
+        assert_eq!(
+            frame_of("auto"),
+            Rect::new(Position::new(9, 2), Size::new(0, 2))
+        );
+    }
+
+    #[test]
+    fn justified_items_share_the_free_space() {
+        let origins = |justify: FlexJustify| {
+            let (sender, _receiver) = mpsc::channel();
+            let mut app = App::new(
+                MessageQueue::new(sender),
+                Size::new(11, 1),
+                Container::row([
+                    Item::fixed(2)(Counter::with_key("first", ())),
+                    Item::fixed(3)(Counter::with_key("second", ())),
+                    Item::fixed(1)(Counter::with_key("third", ())),
+                ])
+                .justify(justify)
+                .into(),
+            );
+            app.draw();
+            ["first", "second", "third"].map(|key| {
+                app.frame_of(&ComponentSelector::of::<Counter>().key(key))
+                    .unwrap()
+                    .origin
+                    .x
+            })
+        };
+        assert_eq!(origins(FlexJustify::Start), [0, 2, 5]);
+        assert_eq!(origins(FlexJustify::Centre), [2, 4, 7]);
+        assert_eq!(origins(FlexJustify::End), [5, 7, 10]);
+        // The cell left over after dividing the free space goes to the first gap
+        assert_eq!(origins(FlexJustify::SpaceBetween), [0, 5, 10]);
+    }
+
+    #[test]
+    fn broadcast_messages_by_type() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 2),
+            Layout::column([
+                Item::fixed(1)(Counter::with_key("a", ())),
+                Item::fixed(1)(Counter::with_key("b", ())),
+            ]),
+        );
+        app.draw();
+        let mut received = 0;
+        app.add_middleware(move |message: ComponentMessage| {
+            received += 1;
+            assert_eq!(message.message::<Counter>(), Some(&3));
+            assert_eq!(received, 1);
+            Some(message)
+        });
+        app.broadcast::<Counter>(3);
+        assert!(app.poll_state().dirty());
+
+        let count = |app: &App, key| {
+            app.with_component(
+                &ComponentSelector::of::<Counter>().key(key),
+                |counter: &Counter| counter.count,
+            )
+        };
+        assert_eq!((count(&app, "a"), count(&app, "b")), (Some(3), Some(3)));
+    }
+
+    #[test]
+    fn metrics_count_frames_messages_and_updates() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 1),
+            Counter::with(()),
+        );
+        app.draw();
+        app.record_presented(10);
+        app.broadcast::<Counter>(1);
+        app.handle_input(Event::KeyPress(Key::Char('+'), Instant::now()));
+        app.draw();
+        app.record_presented(2);
+
+        let metrics = app.metrics();
+        assert_eq!(metrics.frames_drawn, 2);
+        assert_eq!(metrics.bytes_presented, 12);
+        assert_eq!(metrics.messages_processed, 1);
+        assert_eq!(metrics.input_events, 1);
+        assert_eq!(metrics.components_mounted, 1);
+        assert_eq!(metrics.component_updates.values().sum::<u64>(), 2);
+
+        // Updates of unmounted components are still counted
+        app.set_root(Layout::empty());
+        app.draw();
+        let metrics = app.metrics();
+        assert_eq!(metrics.components_mounted, 0);
+        assert_eq!(metrics.component_updates.values().sum::<u64>(), 2);
+    }
+
+    #[test]
+    fn inspect_component_state() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 1),
+            Counter::with(()),
+        );
+        let selector = ComponentSelector::of::<Counter>();
+        assert_eq!(
+            app.with_component(&selector, |counter: &Counter| counter.count),
+            None
+        );
+
+        app.draw();
+        assert_eq!(
+            app.with_component(&selector, |counter: &Counter| counter.count),
+            Some(0)
+        );
+
+        app.execute_command(&selector, "increment", &[]);
+        assert_eq!(
+            app.with_component(&ComponentSelector::focused(), |counter: &Counter| {
+                counter.count
+            }),
+            Some(1)
+        );
+
+        // The selector and the component type need to agree
+        assert_eq!(
+            app.with_component(&ComponentSelector::of::<Text>(), |counter: &Counter| {
+                counter.count
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn replace_root_layout() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 1),
+            Counter::with(()),
+        );
+        assert_eq!(first_grapheme(app.draw()), "0");
+
+        app.set_root(Text::with(TextProperties::new().content("Hello")));
+        assert!(app.poll_state().dirty());
+        assert_eq!(first_grapheme(app.draw()), "H");
+        assert_eq!(
+            app.with_component(&ComponentSelector::of::<Counter>(), |_: &Counter| ()),
+            None
+        );
+
+        // Components at the same position receive the new properties
+        app.set_root(Text::with(TextProperties::new().content("World")));
+        assert_eq!(first_grapheme(app.draw()), "W");
+        app.handle_resize(Size::new(20, 1));
+        assert_eq!(first_grapheme(app.draw()), "W");
+    }
+
+    #[test]
+    fn hidden_components_keep_state() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 1),
+            Layout::hidden(Counter::with(())),
+        );
+        let counter = ComponentSelector::of::<Counter>();
+
+        // Hidden components are mounted, but not drawn and not focused
+        assert_eq!(first_grapheme(app.draw()), "");
+        assert_eq!(
+            app.with_component(&counter, |counter: &Counter| counter.count),
+            Some(0)
+        );
+        assert_eq!(
+            app.execute_command(&ComponentSelector::focused(), "increment", &[]),
+            0
+        );
+        assert_eq!(app.execute_command(&counter, "increment", &[]), 1);
+        assert_eq!(first_grapheme(app.draw()), "");
+
+        // Showing the component again preserves its state
+        app.set_root(Counter::with(()));
+        assert_eq!(first_grapheme(app.draw()), "1");
+    }
+
+    #[test]
+    fn stack_layers_are_drawn_back_to_front() {
+        let (sender, _receiver) = mpsc::channel();
+        let text = |key: usize, content: &'static str| {
+            Text::with_key(key, TextProperties::new().content(content))
+        };
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(4, 1),
+            Layout::stack([
+                text(0, "abcd"),
+                Layout::row([
+                    Item::fixed(1)(Layout::empty()),
+                    Item::fixed(2)(text(1, "xy")),
+                    Item::auto(Layout::empty()),
+                ]),
+            ]),
+        );
+
+        let graphemes = |canvas: &Canvas| -> String {
+            (0..4)
+                .map(|x| canvas.textel(x, 0).as_ref().unwrap().grapheme.as_str())
+                .collect()
+        };
+        assert_eq!(graphemes(app.draw()), "axyd");
+
+        // Swapping the layers keeps the components mounted, the bottom layer
+        // is now drawn on top
+        app.set_root(Layout::stack([
+            Layout::row([
+                Item::fixed(1)(Layout::empty()),
+                Item::fixed(2)(text(1, "xy")),
+                Item::auto(Layout::empty()),
+            ]),
+            text(0, "abcd"),
+        ]));
+        assert_eq!(graphemes(app.draw()), "abcd");
+    }
+
+    #[test]
+    fn unset_cells_of_upper_layers_are_transparent() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut popup = Canvas::new(Size::new(4, 1));
+        popup.draw_str(1, 0, Style::default(), "xy");
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(4, 1),
+            Layout::stack([
+                Text::with(TextProperties::new().content("abcd")),
+                popup.into(),
+            ]),
+        );
+
+        let canvas = app.draw();
+        let graphemes: String = (0..4)
+            .map(|x| canvas.textel(x, 0).as_ref().unwrap().grapheme.as_str())
+            .collect();
+        assert_eq!(graphemes, "axyd");
+    }
+
+    struct Echo;
+
+    impl FunctionComponent for Echo {
+        type Properties = &'static str;
+
+        fn view(text: &Self::Properties, hooks: &mut Hooks) -> Layout {
+            let changes = hooks.use_state(|| 0usize);
+            // The callback is only recreated when the text changes, which
+            // is what triggers the effect
+            let on_change = hooks.use_callback(*text, |_: ()| ());
+            hooks.use_effect(on_change, {
+                let changes = changes.clone();
+                move || changes.update(|changes| *changes += 1)
+            });
+            Text::with(TextProperties::new().content(format!("{}{}", text, changes.get())))
+        }
+    }
+
+    #[test]
+    fn function_components_keep_state_in_hooks() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(MessageQueue::new(sender), Size::new(10, 1), Echo::with("a"));
+        let draw = |app: &mut App| -> String {
+            let canvas = app.draw();
+            let content = (0..2)
+                .map(|x| canvas.textel(x, 0).as_ref().unwrap().grapheme.as_str())
+                .collect();
+            while let Ok(message) = receiver.try_recv() {
+                app.handle_message(message);
+            }
+            content
+        };
+
+        // The effect runs after the first render and setting the state
+        // renders the component again
+        assert_eq!(draw(&mut app), "a0");
+        assert_eq!(draw(&mut app), "a1");
+        assert_eq!(draw(&mut app), "a1");
+
+        // The effect runs again when its dependencies change
+        app.set_root(Echo::with("b"));
+        assert_eq!(draw(&mut app), "b1");
+        assert_eq!(draw(&mut app), "b2");
+    }
+
+    struct Provider(&'static str);
+
+    impl Component for Provider {
+        type Message = ();
+        type Properties = &'static str;
+
+        fn create(value: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self(value)
+        }
+
+        fn change(&mut self, value: Self::Properties) -> ShouldRender {
+            self.0 = value;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            Layout::provide(self.0, Unchanged::with(()))
+        }
+    }
+
+    /// A component which is never re-rendered by its parent
+    struct Unchanged;
+
+    impl Component for Unchanged {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self
+        }
+
+        fn change(&mut self, _: Self::Properties) -> ShouldRender {
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            ContextReader::with(())
+        }
+    }
+
+    struct ContextReader(ComponentLink<Self>);
+
+    impl Component for ContextReader {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self(link)
+        }
+
+        fn change(&mut self, _: Self::Properties) -> ShouldRender {
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            let text = self
+                .0
+                .context::<&'static str>()
+                .map_or("none", |text| *text);
+            let count = self.0.context::<usize>().map_or(0, |count| *count);
+            Text::with(TextProperties::new().content(format!("{} {}", text, count)))
+        }
+    }
+
+    #[test]
+    fn components_read_values_provided_by_enclosing_layouts() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 1),
+            ContextReader::with(()),
+        );
+        let content = |canvas: &Canvas| -> String {
+            (0..10)
+                .filter_map(|x| canvas.textel(x, 0).as_ref())
+                .map(|textel| textel.grapheme.as_str())
+                .collect::<String>()
+                .trim()
+                .to_owned()
+        };
+        assert_eq!(content(app.draw()), "none 0");
+
+        // The innermost value of a type is read, values of other types are
+        // looked up further out
+        app.set_root(Layout::provide(
+            3usize,
+            Layout::provide("outer", Provider::with("inner")),
+        ));
+        assert_eq!(content(app.draw()), "inner 3");
+
+        // Components reading a value are re-rendered when it changes, even if
+        // their parent isn't
+        app.set_root(Layout::provide(
+            3usize,
+            Layout::provide("outer", Provider::with("changed")),
+        ));
+        assert_eq!(content(app.draw()), "changed 3");
+    }
+
+    #[test]
+    fn hover_enter_and_leave() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 3),
+            Layout::row([
+                Item::fixed(5)(Tooltip::with(
+                    TooltipProperties::new("hi", || {
+                        Text::with(TextProperties::new().content("hover"))
+                    })
+                    .delay(Duration::from_secs(0)),
+                )),
+                Item::auto(Text::with(TextProperties::new().content("other"))),
+            ]),
+        );
+        let move_to = |app: &mut App, x, y| {
+            app.handle_input(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Moved,
+                position: Position::new(x, y),
+                modifiers: Modifiers::NONE,
+                timestamp: Instant::now(),
+            }));
+            app.draw();
+        };
+        let tooltip = ComponentSelector::of::<Tooltip>();
+        let is_visible = |app: &App| app.with_component(&tooltip, Tooltip::is_visible);
+
+        app.draw();
+        assert_eq!(is_visible(&app), Some(false));
+
+        // The popup is shown under the mouse, above the content
+        move_to(&mut app, 1, 0);
+        assert_eq!(is_visible(&app), Some(true));
+        let canvas = app.draw();
+        assert_eq!(canvas.textel(2, 1).as_ref().unwrap().grapheme.as_str(), "h");
+
+        // Moving over the popup keeps it open, leaving the tooltip hides it
+        move_to(&mut app, 2, 1);
+        assert_eq!(is_visible(&app), Some(true));
+        move_to(&mut app, 7, 0);
+        assert_eq!(is_visible(&app), Some(false));
+    }
+
+    #[test]
+    fn keymap_overrides_rebind_named_commands() {
+        let content: String = (0..20).map(|line| format!("line {}\n", line)).collect();
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(20, 5),
+            Pager::with(PagerProperties::new(content.as_str()).focused(true)),
+        );
+        let mut overrides = KeymapOverrides::new();
+        overrides
+            .bind("Pager", "line-down", ["x".parse().unwrap()])
+            .bind("Pager", "no-such-command", ["z".parse().unwrap()]);
+        app.set_keymap_overrides(overrides);
+
+        let press = |app: &mut App, key| {
+            app.draw();
+            app.handle_input(Event::key_press(key));
+            while let Ok(message) = receiver.try_recv() {
+                app.handle_message(message);
+            }
+            app.with_component(&ComponentSelector::of::<Pager>(), Pager::offset)
+                .unwrap()
+        };
+        assert_eq!(press(&mut app, Key::Char('j')), 0);
+        assert_eq!(press(&mut app, Key::Char('x')), 1);
+        // Commands which aren't overridden keep their bindings
+        assert_eq!(press(&mut app, Key::Char('k')), 0);
+    }
+
+    #[test]
+    fn bindings_with_a_leader_follow_the_leader_key() {
+        let content: String = (0..20).map(|line| format!("line {}\n", line)).collect();
+        let mut harness = Harness::new(
+            Size::new(20, 5),
+            Pager::with(PagerProperties::new(content.as_str()).focused(true)),
+        );
+        let mut overrides = KeymapOverrides::new();
+        overrides.bind("Pager", "line-down", ["<leader> x".parse().unwrap()]);
+        harness.app_mut().set_keymap_overrides(overrides);
+        let offset = |harness: &mut Harness| {
+            harness
+                .app()
+                .with_component(&ComponentSelector::of::<Pager>(), Pager::offset)
+                .unwrap()
+        };
+
+        // Without a leader, the binding isn't matched
+        harness.keys([Key::Char(','), Key::Char('x')]);
+        assert_eq!(offset(&mut harness), 0);
+
+        harness.app_mut().set_leader(Some(Key::Char(',')));
+        harness.keys([Key::Char(','), Key::Char('x')]);
+        assert_eq!(offset(&mut harness), 1);
+
+        // The leader of mounted components changes too
+        harness.app_mut().set_leader(Some(Key::Char(';')));
+        harness.keys([Key::Char(','), Key::Char('x')]);
+        assert_eq!(offset(&mut harness), 1);
+        harness.keys([Key::Char(';'), Key::Char('x')]);
+        assert_eq!(offset(&mut harness), 2);
+        assert_eq!(harness.app().leader(), Some(Key::Char(';')));
+    }
+
+    #[test]
+    fn bindings_change_at_runtime() {
+        let content: String = (0..20).map(|line| format!("line {}\n", line)).collect();
+        let mut harness = Harness::new(
+            Size::new(20, 5),
+            Pager::with(PagerProperties::new(content.as_str()).focused(true)),
+        );
+        let offset = |harness: &Harness| {
+            harness
+                .app()
+                .with_component(&ComponentSelector::of::<Pager>(), Pager::offset)
+                .unwrap()
+        };
+        harness.key(Key::Char('j'));
+        assert_eq!(offset(&harness), 1);
+
+        // New overrides are applied to the mounted components
+        let mut overrides = KeymapOverrides::new();
+        overrides.bind("Pager", "line-down", ["x".parse().unwrap()]);
+        harness.app_mut().set_keymap_overrides(overrides);
+        harness.keys([Key::Char('j'), Key::Char('x')]);
+        assert_eq!(offset(&harness), 2);
+
+        // Cleared bindings are rebuilt by the components
+        harness
+            .app_mut()
+            .set_keymap_overrides(KeymapOverrides::new());
+        assert_eq!(
+            harness
+                .app_mut()
+                .clear_bindings(&ComponentSelector::of::<Pager>()),
+            1
+        );
+        harness.keys([Key::Char('x'), Key::Char('j')]);
+        assert_eq!(offset(&harness), 3);
+
+        // Removed global bindings let the keys through
+        harness
+            .app_mut()
+            .add_global_binding("top", [Key::Char('t')], |app| {
+                app.execute_command(&ComponentSelector::of::<Pager>(), "first-line", &[]);
+            });
+        harness.key(Key::Char('t'));
+        assert_eq!(offset(&harness), 0);
+        assert!(harness.app_mut().remove_global_binding([Key::Char('t')]));
+        assert!(!harness.app_mut().remove_global_binding([Key::Char('t')]));
+        assert!(harness.app().global_keymap().is_empty());
+    }
+
+    #[test]
+    fn middleware_observes_and_filters_messages() {
+        let (sender, receiver) = mpsc::channel();
+        let target: Rc<dyn SearchTarget> = Rc::new("abc".to_string());
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(30, 1),
+            SearchBar::with(SearchBarProperties::new(target).focused(true)),
+        );
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let blocked = Rc::new(Cell::new(false));
+        app.add_middleware({
+            let observed = Rc::clone(&observed);
+            move |message: ComponentMessage| {
+                observed
+                    .borrow_mut()
+                    .push(message.component_type_name().unwrap());
+                Some(message)
+            }
+        });
+        app.add_middleware({
+            let blocked = Rc::clone(&blocked);
+            move |message: ComponentMessage| {
+                if blocked.get() && message.message::<SearchBar>().is_some() {
+                    None
+                } else {
+                    Some(message)
+                }
+            }
+        });
+        let mut press = |character| {
+            app.draw();
+            app.handle_input(Event::key_press(Key::Char(character)));
+            while let Ok(message) = receiver.try_recv() {
+                app.handle_message(message);
+            }
+            app.with_component(&ComponentSelector::of::<SearchBar>(), |bar: &SearchBar| {
+                bar.results().query.clone()
+            })
+            .unwrap()
+        };
+
+        assert_eq!(press('a'), "a");
+        blocked.set(true);
+        assert_eq!(press('b'), "a");
+        assert_eq!(observed.borrow().len(), 2);
+        assert!(observed.borrow()[0].ends_with("SearchBar"));
+    }
+
+    struct Failing {
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Failing {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            Self { link }
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, _: Self::Message) -> ShouldRender {
+            ShouldRender::No
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("fail", [Key::Char('x')], |this: &Self| {
+                this.link.report_error("disk full");
+                None
+            });
+        }
+    }
+
+    struct Clipper {
+        link: ComponentLink<Self>,
+        pasted: Option<String>,
+    }
+
+    impl Component for Clipper {
+        type Message = Option<String>;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            Self { link, pasted: None }
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn update(&mut self, pasted: Self::Message) -> ShouldRender {
+            self.pasted = pasted;
+            ShouldRender::No
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("copy", [Key::Ctrl('c')], |this: &Self| {
+                this.link.copy_to_clipboard("Hello");
+                None
+            });
+            bindings.add("paste", [Key::Ctrl('v')], |this: &Self| {
+                this.link.request_paste(|text| text);
+                None
+            });
+        }
+    }
+
+    #[test]
+    fn copy_to_and_paste_from_the_clipboard() {
+        let mut harness = Harness::new(Size::new(10, 1), Clipper::with(()));
+        let pasted = |harness: &mut Harness| {
+            harness
+                .app()
+                .with_component(&ComponentSelector::of::<Clipper>(), |clipper: &Clipper| {
+                    clipper.pasted.clone()
+                })
+                .unwrap()
+        };
+
+        // Without a clipboard set by the backend, the text is kept in memory
+        harness.key(Key::Ctrl('v'));
+        assert_eq!(pasted(&mut harness), None);
+        harness.keys([Key::Ctrl('c'), Key::Ctrl('v')]);
+        assert_eq!(pasted(&mut harness), Some("Hello".into()));
+
+        #[derive(Clone, Default)]
+        struct SystemClipboard(Arc<std::sync::Mutex<Vec<String>>>);
+
+        impl Clipboard for SystemClipboard {
+            fn copy(&mut self, text: &str) {
+                self.0.lock().unwrap().push(text.to_owned());
+            }
+
+            fn paste(&mut self) -> Option<String> {
+                Some("World".into())
+            }
+        }
+
+        let clipboard = SystemClipboard::default();
+        harness.app_mut().set_clipboard(clipboard.clone());
+        harness.keys([Key::Ctrl('c'), Key::Ctrl('v')]);
+        assert_eq!(pasted(&mut harness), Some("World".into()));
+        assert_eq!(*clipboard.0.lock().unwrap(), ["Hello"]);
+        assert_eq!(harness.app_mut().clipboard().paste(), Some("World".into()));
+    }
+
+    #[test]
+    fn report_errors_to_the_root() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(30, 5),
+            Failing::with(()),
+        );
+        let reported = Rc::new(RefCell::new(Vec::new()));
+        app.on_error({
+            let reported = Rc::clone(&reported);
+            move |error: &ReportedError| {
+                reported
+                    .borrow_mut()
+                    .push((error.component_type_name(), error.to_string()))
+            }
+        });
+
+        app.draw();
+        app.handle_input(Event::key_press(Key::Char('x')));
+        while let Ok(message) = receiver.try_recv() {
+            assert!(message.error().is_some());
+            app.handle_message(message);
+        }
+        assert_eq!(reported.borrow().len(), 1);
+        assert!(reported.borrow()[0].0.ends_with("Failing"));
+        assert_eq!(reported.borrow()[0].1, "disk full");
+    }
+
+    #[derive(Default)]
+    struct Clickable {
+        clicks: Vec<Position>,
+    }
+
+    impl Component for Clickable {
+        type Message = Position;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self::default()
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, position: Self::Message) -> ShouldRender {
+            self.clicks.push(position);
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add(
+                "click",
+                [Key::Click(MouseButton::Left, Modifiers::NONE)],
+                |position| position,
+            );
+            bindings.add(
+                "mark",
+                [
+                    Key::Ctrl('x'),
+                    Key::Click(MouseButton::Left, Modifiers::CTRL),
+                ],
+                |_: &Self, position: Position| Some(Position::new(position.x + 100, position.y)),
+            );
+            bindings.add(
+                "drag",
+                [Key::Drag(MouseButton::Left, Modifiers::NONE)],
+                |position: Position| Position::new(position.x + 200, position.y),
+            );
+            bindings.add(
+                "scroll",
+                [Key::ScrollDown(Modifiers::NONE)],
+                |position: Position| Position::new(position.x + 300, position.y),
+            );
+        }
+    }
+
+    #[test]
+    fn chorded_mouse_and_keyboard_bindings() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Clickable::with(()),
+        );
+        let click = |app: &mut App, x, y, modifiers| {
+            app.handle_input(Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                position: Position::new(x, y),
+                modifiers,
+                timestamp: Instant::now(),
+            }));
+        };
+
+        app.draw();
+        click(&mut app, 1, 2, Modifiers::NONE);
+        // A ctrl-click alone is not bound
+        click(&mut app, 3, 4, Modifiers::CTRL);
+        app.handle_input(Event::key_press(Key::Ctrl('x')));
+        click(&mut app, 5, 6, Modifiers::CTRL);
+        while let Ok(message) = receiver.try_recv() {
+            app.handle_message(message);
+        }
+
+        let clicks = app
+            .with_component(
+                &ComponentSelector::of::<Clickable>(),
+                |clickable: &Clickable| clickable.clicks.clone(),
+            )
+            .unwrap();
+        assert_eq!(clicks, vec![Position::new(1, 2), Position::new(105, 6)]);
+    }
+
+    #[test]
+    fn drag_release_and_scroll_bindings() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Clickable::with(()),
+        );
+        app.draw();
+        for (kind, x) in [
+            (MouseEventKind::Down(MouseButton::Left), 0),
+            (MouseEventKind::Drag(MouseButton::Left), 1),
+            (MouseEventKind::Drag(MouseButton::Left), 2),
+            (MouseEventKind::Up(MouseButton::Left), 2),
+            (MouseEventKind::ScrollDown, 3),
+            (MouseEventKind::ScrollUp, 4),
+        ] {
+            app.handle_input(Event::Mouse(MouseEvent {
+                kind,
+                position: Position::new(x, 1),
+                modifiers: Modifiers::NONE,
+                timestamp: Instant::now(),
+            }));
+        }
+
+        // Unbound mouse keys, like the release and scrolling up, are ignored
+        let clicks = app
+            .with_component(
+                &ComponentSelector::of::<Clickable>(),
+                |clickable: &Clickable| clickable.clicks.clone(),
+            )
+            .unwrap();
+        assert_eq!(
+            clicks,
+            vec![
+                Position::new(0, 1),
+                Position::new(201, 1),
+                Position::new(202, 1),
+                Position::new(303, 1),
+            ]
+        );
+        assert_eq!(
+            KeySequenceSlice::from(
+                &[
+                    Key::Release(MouseButton::Right, Modifiers::CTRL),
+                    Key::ScrollUp(Modifiers::NONE),
+                ][..]
+            )
+            .to_string(),
+            "C-RightRelease ScrollUp"
+        );
+    }
+
+    struct Typed(String);
+
+    impl Component for Typed {
+        type Message = String;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self(String::new())
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn update(&mut self, text: Self::Message) -> ShouldRender {
+            self.0.push_str(&text);
+            ShouldRender::Yes
+        }
+
+        fn key_release(&self, key: Key) -> Option<Self::Message> {
+            Some(format!("<{}>", KeySequenceSlice::from(&[key][..])))
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            bindings.add("insert", AnyCharacter, |keys: &[Key]| match *keys {
+                [Key::Char(character)] => Some(character.to_string()),
+                [Key::Grapheme(grapheme)] => Some(format!("[{}]", grapheme)),
+                _ => None,
+            });
+        }
+    }
+
+    #[test]
+    fn any_character_bindings_receive_graphemes() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(MessageQueue::new(sender), Size::new(1, 1), Typed::with(()));
+        app.draw();
+        let thumbs_up = Grapheme::new("\u{1f44d}\u{1f3fd}").unwrap();
+        for key in [Key::Char('a'), Key::Grapheme(thumbs_up), Key::Char('e')] {
+            app.handle_input(Event::key_press(key));
+        }
+        assert_eq!(
+            app.with_component(&ComponentSelector::of::<Typed>(), |typed: &Typed| {
+                typed.0.clone()
+            }),
+            Some("a[\u{1f44d}\u{1f3fd}]e".into())
+        );
+
+        assert_eq!(Grapheme::from('e').as_str(), "e");
+        assert_eq!(Grapheme::new(&"e\u{301}".repeat(8)), None);
+    }
+
+    #[test]
+    fn key_repeats_and_releases() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(MessageQueue::new(sender), Size::new(1, 1), Typed::with(()));
+        app.draw();
+        let now = Instant::now();
+        for event in [
+            Event::KeyPress(Key::Char('a'), now),
+            Event::KeyRepeat(Key::Char('a'), now),
+            Event::KeyRelease(Key::Char('a'), now),
+        ] {
+            app.handle_input(event);
+        }
+        assert_eq!(
+            app.with_component(&ComponentSelector::of::<Typed>(), |typed: &Typed| {
+                typed.0.clone()
+            }),
+            Some("aa<a>".into())
+        );
+    }
+
+    #[test]
+    fn classify_double_press_and_hold() {
+        let mut controller = InputController::new();
+        let start = Instant::now();
+        let mut press =
+            |key, millis| controller.classify(key, start + Duration::from_millis(millis), false);
+
+        assert_eq!(press(Key::Esc, 0), KeyGesture::Press);
+        assert_eq!(press(Key::Esc, 100), KeyGesture::DoublePress);
+        assert_eq!(press(Key::Esc, 200), KeyGesture::Press);
+        // A different key in between
+        assert_eq!(press(Key::Char('a'), 300), KeyGesture::Press);
+        assert_eq!(press(Key::Esc, 400), KeyGesture::Press);
+
+        // The terminal repeats a held key after a delay, then quickly
+        assert_eq!(press(Key::Down, 1000), KeyGesture::Press);
+        assert_eq!(press(Key::Down, 1500), KeyGesture::Press);
+        assert_eq!(press(Key::Down, 1530), KeyGesture::Hold);
+        assert_eq!(press(Key::Down, 1560), KeyGesture::Press);
+        // Released and pressed again
+        assert_eq!(press(Key::Down, 3000), KeyGesture::Press);
+
+        // Separate taps slower than a double press aren't a held key
+        assert_eq!(press(Key::Up, 5000), KeyGesture::Press);
+        assert_eq!(press(Key::Up, 5400), KeyGesture::Press);
+        assert_eq!(press(Key::Up, 5800), KeyGesture::Press);
+        // The last tap can still start a double press
+        assert_eq!(press(Key::Up, 6000), KeyGesture::DoublePress);
+
+        // Repeats reported by the backend are held as long as they last,
+        // and never make a double press
+        let mut repeat =
+            |key, millis| controller.classify(key, start + Duration::from_millis(millis), true);
+        assert_eq!(repeat(Key::Left, 8000), KeyGesture::Press);
+        assert_eq!(repeat(Key::Left, 8100), KeyGesture::Press);
+        assert_eq!(repeat(Key::Left, 8350), KeyGesture::Press);
+        assert_eq!(repeat(Key::Left, 8700), KeyGesture::Hold);
+        assert_eq!(repeat(Key::Left, 8800), KeyGesture::Press);
+    }
+
+    struct Quittable {
+        quits: usize,
+    }
+
+    impl Component for Quittable {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self { quits: 0 }
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, _: Self::Message) -> ShouldRender {
+            self.quits += 1;
+            ShouldRender::No
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("force-quit", DoublePress(Key::Esc), || ());
+        }
+    }
+
+    #[test]
+    fn bind_double_press() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Quittable::with(()),
+        );
+        app.set_key_timing(KeyTiming::default().double_press(Duration::from_secs(3600)));
+        let quits = |app: &App| {
+            app.with_component(
+                &ComponentSelector::of::<Quittable>(),
+                |quittable: &Quittable| quittable.quits,
+            )
+            .unwrap()
+        };
+
+        app.draw();
+        app.handle_input(Event::key_press(Key::Esc));
+        assert_eq!(quits(&app), 0);
+        app.handle_input(Event::key_press(Key::Esc));
+        assert_eq!(quits(&app), 1);
+        app.handle_input(Event::key_press(Key::Esc));
+        assert_eq!(quits(&app), 1);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[derive(Default)]
+    struct Debounced {
+        presses: Vec<Instant>,
+    }
+
+    impl Component for Debounced {
+        type Message = Instant;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self::default()
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, timestamp: Self::Message) -> ShouldRender {
+            self.presses.push(timestamp);
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add(
+                "press",
+                [Key::Char('p')],
+                |this: &Self, timestamp: Instant| match this.presses.last() {
+                    Some(last) if timestamp.duration_since(*last) < Duration::from_secs(1) => None,
+                    _ => Some(timestamp),
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn commands_receive_the_time_of_the_key_press() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Debounced::with(()),
+        );
+        let start = Instant::now();
+        app.draw();
+        for millis in [0, 500, 1200, 1300] {
+            app.handle_input(Event::KeyPress(
+                Key::Char('p'),
+                start + Duration::from_millis(millis),
+            ));
+        }
+
+        let presses = app
+            .with_component(
+                &ComponentSelector::of::<Debounced>(),
+                |debounced: &Debounced| debounced.presses.clone(),
+            )
+            .unwrap();
+        assert_eq!(presses, vec![start, start + Duration::from_millis(1200)]);
+    }
+
+    #[derive(Clone)]
+    struct FocusNode {
+        name: &'static str,
+        keys: &'static str,
+        focused: Rc<Cell<bool>>,
+        bubble_keys: bool,
+        handled: Rc<RefCell<Vec<String>>>,
+        children: Vec<FocusNode>,
+    }
+
+    struct Focusable(FocusNode);
+
+    impl Component for Focusable {
+        type Message = char;
+        type Properties = FocusNode;
+
+        fn create(node: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self(node)
+        }
+
+        fn view(&self) -> Layout {
+            if self.0.children.is_empty() {
+                return Canvas::new(Size::new(1, 1)).into();
+            }
+            Layout::row(
+                self.0
+                    .children
+                    .iter()
+                    .map(|child| Item::auto(Focusable::with_key(child.name, child.clone()))),
+            )
+        }
+
+        fn update(&mut self, key: Self::Message) -> ShouldRender {
+            self.0
+                .handled
+                .borrow_mut()
+                .push(format!("{}:{}", self.0.name, key));
+            ShouldRender::No
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(self.0.focused.get());
+            bindings.set_bubble_keys(self.0.bubble_keys);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add(
+                "handle",
+                AnyCharacter,
+                |this: &Self, keys: &[Key]| match keys {
+                    &[Key::Char(key)] if this.0.keys.contains(key) => Some(key),
+                    _ => None,
+                },
+            );
+        }
+    }
+
+    #[test]
+    fn keys_bubble_up_the_active_focus_path() {
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let node = |name, keys, children| FocusNode {
+            name,
+            keys,
+            focused: Rc::new(Cell::new(true)),
+            bubble_keys: false,
+            handled: handled.clone(),
+            children,
+        };
+        let (left, right) = (node("left", "ab", vec![]), node("right", "ab", vec![]));
+        let (left_focused, right_focused) = (left.focused.clone(), right.focused.clone());
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Focusable::with(node("root", "bc", vec![left, right])),
+        );
+        let press = |app: &mut App, key| {
+            app.draw();
+            app.handle_input(Event::key_press(Key::Char(key)));
+            handled.borrow_mut().drain(..).collect::<Vec<_>>()
+        };
+
+        // Only the sibling which gained focus last handles keys, the root
+        // handles those its descendants don't
+        assert_eq!(press(&mut app, 'b'), ["right:b"]);
+        assert_eq!(press(&mut app, 'c'), ["root:c"]);
+        assert!(press(&mut app, 'd').is_empty());
+
+        left_focused.set(false);
+        press(&mut app, 'd');
+        left_focused.set(true);
+        assert_eq!(press(&mut app, 'a'), ["left:a"]);
+
+        left_focused.set(false);
+        right_focused.set(false);
+        assert_eq!(press(&mut app, 'b'), ["root:b"]);
+    }
+
+    #[test]
+    fn keys_bubble_to_unfocused_ancestors_on_request() {
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let node = |name, keys, focused, children| FocusNode {
+            name,
+            keys,
+            focused: Rc::new(Cell::new(focused)),
+            bubble_keys: false,
+            handled: handled.clone(),
+            children,
+        };
+        let mut leaf = node("leaf", "a", true, vec![]);
+        let middle = node("middle", "m", false, vec![leaf.clone()]);
+        let root = node("root", "rz", false, vec![middle.clone()]);
+        let press = |app: &mut App, key| {
+            app.draw();
+            app.handle_input(Event::key_press(Key::Char(key)));
+            handled.borrow_mut().drain(..).collect::<Vec<_>>()
+        };
+
+        // Unfocused ancestors don't receive keys by default
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Focusable::with(root.clone()),
+        );
+        assert_eq!(press(&mut app, 'a'), ["leaf:a"]);
+        assert!(press(&mut app, 'm').is_empty());
+
+        // Keys the leaf doesn't handle bubble up, nearest ancestor first
+        leaf.bubble_keys = true;
+        let middle = FocusNode {
+            keys: "mr",
+            children: vec![leaf],
+            ..middle
+        };
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Focusable::with(FocusNode {
+                children: vec![middle],
+                ..root
+            }),
+        );
+        assert_eq!(press(&mut app, 'a'), ["leaf:a"]);
+        assert_eq!(press(&mut app, 'm'), ["middle:m"]);
+        assert_eq!(press(&mut app, 'r'), ["middle:r"]);
+        assert_eq!(press(&mut app, 'z'), ["root:z"]);
+        assert!(press(&mut app, 'x').is_empty());
+    }
+
+    #[test]
+    fn global_bindings_see_the_keys_focused_components_do_not_handle() {
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Focusable::with(FocusNode {
+                name: "root",
+                keys: "a",
+                focused: Rc::new(Cell::new(true)),
+                bubble_keys: false,
+                handled: handled.clone(),
+                children: vec![],
+            }),
+        );
+        let global = Rc::new(RefCell::new(Vec::new()));
+        for (name, keys) in [
+            ("help", vec![Key::Char('a')]),
+            ("goto", vec![Key::Ctrl('g'), Key::Char('a')]),
+        ] {
+            let global = global.clone();
+            app.add_global_binding(name, keys, move |_: &mut App| {
+                global.borrow_mut().push(name)
+            });
+        }
+        app.add_global_binding("exit", [Key::Char('q')], App::exit);
+        assert!(app.global_keymap().command_id("goto").is_some());
+
+        let press = |app: &mut App, key| {
+            app.draw();
+            app.handle_input(Event::key_press(key));
+            let handled = handled.borrow_mut().drain(..).collect::<Vec<_>>();
+            let global = global.borrow_mut().drain(..).collect::<Vec<_>>();
+            (handled, global)
+        };
+        assert_eq!(
+            press(&mut app, Key::Char('a')),
+            (vec!["root:a".into()], vec![])
+        );
+        assert_eq!(press(&mut app, Key::Ctrl('g')), (vec![], vec![]));
+        assert_eq!(press(&mut app, Key::Char('a')), (vec![], vec!["goto"]));
+        assert!(!app.poll_state().exit());
+        press(&mut app, Key::Char('q'));
+        assert!(app.poll_state().exit());
+    }
+
+    struct Saver;
+
+    impl Component for Saver {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("save-file", [Key::Ctrl('x'), Key::Ctrl('s')], || ());
+            bindings.add("find-file", [Key::Ctrl('x'), Key::Ctrl('f')], || ());
+        }
+    }
+
+    #[test]
+    fn pending_key_sequences_time_out() {
+        let mut harness = Harness::new(
+            Size::new(40, 4),
+            WhichKey::with(WhichKeyProperties::new(|| {
+                Layout::stack([Text::with(TextProperties::new()), Saver::with(())])
+            })),
+        );
+        let ran = Rc::new(Cell::new(0));
+        harness
+            .app_mut()
+            .set_key_timing(KeyTiming::default().prefix_timeout(Duration::from_secs(1)));
+        harness
+            .app_mut()
+            .add_global_binding("cancel", [Key::Ctrl('x')], {
+                let ran = ran.clone();
+                move |_: &mut App| ran.set(ran.get() + 1)
+            });
+        let is_pending = |harness: &Harness| {
+            harness
+                .app()
+                .with_component(&ComponentSelector::of::<WhichKey>(), WhichKey::is_visible)
+                .unwrap()
+        };
+
+        // The prefix of the focused component's bindings shadows the global
+        // binding until it times out
+        harness.key(Key::Ctrl('x'));
+        assert!(is_pending(&harness));
+        assert!(harness.app().next_timer().is_some());
+        harness.advance(Duration::from_millis(500));
+        assert!(is_pending(&harness));
+        harness.advance(Duration::from_millis(600));
+        assert!(!is_pending(&harness));
+        assert_eq!(ran.get(), 1);
+        assert_eq!(harness.app().next_timer(), None);
+
+        // Keys pressed in time continue the sequence
+        harness.key(Key::Ctrl('x'));
+        harness.advance(Duration::from_millis(900));
+        harness.key(Key::Ctrl('s'));
+        assert!(!is_pending(&harness));
+        harness.advance(Duration::from_secs(2));
+        assert_eq!(ran.get(), 1);
+
+        // Without a binding for the prefix itself, it's cancelled
+        assert!(harness.app_mut().remove_global_binding([Key::Ctrl('x')]));
+        harness.key(Key::Ctrl('x'));
+        harness.advance(Duration::from_secs(1));
+        assert!(!is_pending(&harness));
+        assert_eq!(harness.app().next_timer(), None);
+    }
+
+    struct Field(&'static str, Rc<RefCell<Vec<String>>>);
+
+    impl Component for Field {
+        type Message = String;
+        type Properties = (&'static str, Rc<RefCell<Vec<String>>>);
+
+        fn create((name, log): Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self(name, log)
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, event: Self::Message) -> ShouldRender {
+            self.1.borrow_mut().push(event);
+            ShouldRender::No
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focusable(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("type", [Key::Char('x')], |this: &Self| {
+                Some(format!("{}:x", this.0))
+            });
+        }
+
+        fn focus(&self, focused: bool) -> Option<Self::Message> {
+            Some(format!("{}{}", self.0, if focused { '+' } else { '-' }))
+        }
+    }
+
+    #[test]
+    fn items_set_the_tab_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let field = |name| Field::with_key(name, (name, log.clone()));
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(8, 2),
+            Layout::row([
+                Item::auto(field("a")),
+                Item::auto(field("b")).skip_focus(),
+                Item::auto(Layout::column([
+                    Item::auto(field("c")).focus_order(1),
+                    Item::auto(field("d")),
+                ])),
+                Item::auto(field("e")).focus_order(-1),
+            ]),
+        );
+        for _ in 0..5 {
+            app.draw();
+            app.handle_input(Event::key_press(Key::Char('\t')));
+        }
+        let gained = log
+            .borrow()
+            .iter()
+            .filter(|event| event.ends_with('+'))
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(gained, ["e+", "a+", "d+", "c+", "e+"]);
+    }
+
+    #[derive(Default)]
+    struct Animation {
+        elapsed: Duration,
+        frames: usize,
+    }
+
+    impl Component for Animation {
+        type Message = Duration;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self::default()
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, delta: Self::Message) -> ShouldRender {
+            self.elapsed += delta;
+            self.frames += 1;
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_tickable(self.frames < 2);
+        }
+
+        fn tick(&self, delta: Duration) -> Option<Self::Message> {
+            Some(delta)
+        }
+    }
+
+    #[test]
+    fn tick_with_elapsed_time() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Animation::with(()),
+        );
+        let animation = |app: &App| {
+            app.with_component(
+                &ComponentSelector::of::<Animation>(),
+                |animation: &Animation| (animation.elapsed, animation.frames),
+            )
+            .unwrap()
+        };
+
+        let start = Instant::now();
+        app.draw();
+        assert!(app.is_tickable());
+        for _ in 0..2 {
+            std::thread::sleep(Duration::from_millis(10));
+            app.tick();
+            app.draw();
+        }
+        let (elapsed, frames) = animation(&app);
+        assert_eq!(frames, 2);
+        assert!(elapsed >= Duration::from_millis(20));
+        assert!(elapsed <= start.elapsed());
+        // The animation is finished
+        assert!(!app.is_tickable());
+    }
+
+    struct Alarm {
+        count: usize,
+        every: TimerHandle,
+    }
+
+    impl Component for Alarm {
+        type Message = usize;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            link.send_after(Duration::from_secs(1), 100);
+            Self {
+                count: 0,
+                every: link.send_every(Duration::from_millis(50), || 1),
+            }
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn update(&mut self, increment: Self::Message) -> ShouldRender {
+            self.count += increment;
+            ShouldRender::Yes
+        }
+    }
+
+    #[test]
+    fn timers_send_messages_when_due() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(MessageQueue::new(sender), Size::new(1, 1), Alarm::with(()));
+        let count = |app: &App| {
+            app.with_component(&ComponentSelector::of::<Alarm>(), |alarm: &Alarm| {
+                alarm.count
+            })
+        };
+
+        let start = Instant::now();
+        app.draw();
+        assert_eq!(app.next_timer(), None);
+        while let Ok(message) = receiver.try_recv() {
+            app.handle_message(message);
+        }
+        let next_timer = app.next_timer().unwrap();
+        assert!(next_timer >= start + Duration::from_millis(50));
+        assert!(next_timer <= Instant::now() + Duration::from_millis(50));
+
+        app.fire_timers_at(start + Duration::from_millis(20));
+        assert_eq!(count(&app), Some(0));
+        app.fire_timers_at(start + Duration::from_millis(60));
+        assert_eq!(count(&app), Some(1));
+        assert!(app.poll_state().dirty());
+
+        // An interval which fell behind fires once, rather than in a burst
+        app.fire_timers_at(start + Duration::from_millis(1010));
+        assert_eq!(count(&app), Some(102));
+
+        let every = app
+            .with_component(&ComponentSelector::of::<Alarm>(), |alarm: &Alarm| {
+                alarm.every.clone()
+            })
+            .unwrap();
+        every.cancel();
+        assert_eq!(app.next_timer(), None);
+        app.fire_timers_at(start + Duration::from_secs(5));
+        assert_eq!(count(&app), Some(102));
+
+        // Timers are cancelled when their component is unmounted
+        app.set_root(Alarm::with_key("new", ()));
+        app.draw();
+        while let Ok(message) = receiver.try_recv() {
+            app.handle_message(message);
+        }
+        let every = app
+            .with_component(&ComponentSelector::of::<Alarm>(), |alarm: &Alarm| {
+                alarm.every.clone()
+            })
+            .unwrap();
+        assert!(app.next_timer().is_some());
+        app.set_root(Layout::empty());
+        app.draw();
+        assert!(every.is_cancelled());
+        assert_eq!(app.next_timer(), None);
+    }
+
+    struct Fetcher {
+        link: ComponentLink<Self>,
+        fetched: Vec<usize>,
+    }
+
+    enum FetcherMessage {
+        Fetch(usize),
+        Fetched(usize),
+    }
+
+    impl Component for Fetcher {
+        type Message = FetcherMessage;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            Self {
+                link,
+                fetched: Vec::new(),
+            }
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                FetcherMessage::Fetch(value) => {
+                    self.link
+                        .spawn_future(async move { FetcherMessage::Fetched(value * 2) });
+                }
+                FetcherMessage::Fetched(value) => self.fetched.push(value),
+            }
+            ShouldRender::Yes
+        }
+    }
+
+    #[test]
+    fn spawned_futures_send_their_result_to_the_component() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(1, 1),
+            Fetcher::with(()),
+        );
+        app.draw();
+        let fetcher = app
+            .handle_of::<Fetcher>(&ComponentSelector::of::<Fetcher>())
+            .unwrap();
+        let fetched = |app: &App| {
+            app.with_component(&ComponentSelector::of::<Fetcher>(), |fetcher: &Fetcher| {
+                fetcher.fetched.clone()
+            })
+            .unwrap()
+        };
+
+        // Without an executor, the task runs on a thread of its own
+        app.send_to(fetcher, FetcherMessage::Fetch(1));
+        let spawn = receiver.try_recv().unwrap();
+        app.handle_message(spawn);
+        let fetched_message = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        app.handle_message(fetched_message);
+        assert_eq!(fetched(&app), vec![2]);
+
+        // An executor set by the backend runs tasks spawned afterwards
+        let spawned = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        app.set_executor({
+            let spawned = spawned.clone();
+            move |task| {
+                spawned.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                block_on(task);
+            }
+        });
+        app.send_to(fetcher, FetcherMessage::Fetch(2));
+        while let Ok(message) = receiver.try_recv() {
+            app.handle_message(message);
+        }
+        assert_eq!(spawned.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(fetched(&app), vec![2, 4]);
+    }
+
+    /// A stream of numbers which either ends or stays pending once they are
+    /// all taken.
+    struct Numbers {
+        numbers: std::collections::VecDeque<usize>,
+        ends: bool,
+    }
+
+    impl futures_core::Stream for Numbers {
+        type Item = usize;
+
+        fn poll_next(
+            mut self: Pin<&mut Self>,
+            _context: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<usize>> {
+            match self.numbers.pop_front() {
+                Some(number) => std::task::Poll::Ready(Some(number)),
+                None if self.ends => std::task::Poll::Ready(None),
+                None => std::task::Poll::Pending,
+            }
+        }
+    }
+
+    struct Tail {
+        numbers: Vec<usize>,
+        stream: StreamHandle,
+    }
+
+    impl Component for Tail {
+        type Message = usize;
+        type Properties = bool;
+
+        fn create(ends: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            let numbers = Numbers {
+                numbers: vec![1, 2, 3].into(),
+                ends,
+            };
+            Self {
+                numbers: Vec::new(),
+                stream: link.subscribe_stream(numbers, |number| number * 10),
+            }
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn update(&mut self, number: Self::Message) -> ShouldRender {
+            self.numbers.push(number);
+            ShouldRender::Yes
+        }
+    }
+
+    #[test]
+    fn streams_send_their_items_until_unmounted() {
+        let (sender, receiver) = mpsc::channel();
+        let tail = |app: &App| {
+            app.with_component(&ComponentSelector::of::<Tail>(), |tail: &Tail| {
+                (tail.numbers.clone(), tail.stream.clone())
+            })
+            .unwrap()
+        };
+
+        // A stream which ends finishes the subscription
+        let mut app = App::new(
+            MessageQueue::new(sender.clone()),
+            Size::new(1, 1),
+            Tail::with(true),
+        );
+        app.set_executor(block_on);
+        app.draw();
+        while let Ok(message) = receiver.try_recv() {
+            app.handle_message(message);
+        }
+        let (numbers, stream) = tail(&app);
+        assert_eq!(numbers, vec![10, 20, 30]);
+        assert!(stream.is_finished());
+
+        // A stream which doesn't end is cancelled when its component is
+        // unmounted
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(1, 1),
+            Tail::with(false),
+        );
+        app.draw();
+        let spawn = receiver.try_recv().unwrap();
+        app.handle_message(spawn);
+        for _ in 0..3 {
+            let message = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            app.handle_message(message);
+        }
+        let (numbers, stream) = tail(&app);
+        assert_eq!(numbers, vec![10, 20, 30]);
+        assert!(!stream.is_finished());
+        app.set_root(Layout::empty());
+        app.draw();
+        assert!(stream.is_finished());
+    }
+
+    struct Shell {
+        link: ComponentLink<Self>,
+        content: String,
+    }
+
+    enum ShellMessage {
+        Edit,
+        Edited(String),
+    }
+
+    impl Component for Shell {
+        type Message = ShellMessage;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            Self {
+                link,
+                content: String::new(),
+            }
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                ShellMessage::Edit => self
+                    .link
+                    .run_exclusive(|| Some(ShellMessage::Edited("edited".into()))),
+                ShellMessage::Edited(content) => self.content = content,
+            }
+            ShouldRender::Yes
+        }
+    }
+
+    #[test]
+    fn exclusive_tasks_are_run_by_the_backend() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(MessageQueue::new(sender), Size::new(1, 1), Shell::with(()));
+        app.draw();
+        assert!(!app.has_exclusive_task());
+        assert!(app.take_exclusive_task().is_none());
+
+        let shell = app
+            .handle_of::<Shell>(&ComponentSelector::of::<Shell>())
+            .unwrap();
+        app.send_to(shell, ShellMessage::Edit);
+        while let Ok(message) = receiver.try_recv() {
+            app.handle_message(message);
+        }
+        assert!(app.has_exclusive_task());
+
+        // The message returned by the task is for the component requesting it
+        let task = app.take_exclusive_task().unwrap();
+        assert!(!app.has_exclusive_task());
+        let message = task.run().unwrap();
+        assert!(message.is_for::<Shell>());
+        app.handle_message(message);
+        let content = app.with_component(&ComponentSelector::of::<Shell>(), |shell: &Shell| {
+            shell.content.clone()
+        });
+        assert_eq!(content.as_deref(), Some("edited"));
+    }
+
+    struct Picker(ComponentLink<Self>);
+
+    impl Component for Picker {
+        type Message = &'static str;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            Self(link)
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn update(&mut self, selected: Self::Message) -> ShouldRender {
+            self.0.exit_with(selected.to_owned());
+            ShouldRender::No
+        }
+    }
+
+    #[test]
+    fn exit_with_a_value() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(MessageQueue::new(sender), Size::new(1, 1), Picker::with(()));
+        app.draw();
+        let picker = app
+            .handle_of::<Picker>(&ComponentSelector::of::<Picker>())
+            .unwrap();
+        app.send_to(picker, "notes.txt");
+        let exit = receiver.try_recv().unwrap();
+        assert!(exit.is_exit());
+        app.handle_message(exit);
+        assert!(app.poll_state().exit());
+        assert_eq!(
+            app.take_exit_value::<String>().as_deref(),
+            Some("notes.txt")
+        );
+        assert_eq!(app.take_exit_value::<String>(), None);
+    }
+
+    struct Viewed {
+        properties: (RenderPriority, Rc<Cell<usize>>),
+    }
+
+    impl Component for Viewed {
+        type Message = ();
+        type Properties = (RenderPriority, Rc<Cell<usize>>);
+
+        fn create(properties: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self { properties }
+        }
+
+        fn view(&self) -> Layout {
+            self.properties.1.set(self.properties.1.get() + 1);
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, _: Self::Message) -> ShouldRender {
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("update", [Key::Char('u')], || ());
+        }
+
+        fn render_priority(&self) -> RenderPriority {
+            self.properties.0
+        }
+    }
+
+    #[test]
+    fn defer_low_priority_components_over_frame_budget() {
+        let (sender, _receiver) = mpsc::channel();
+        let normal = Rc::new(Cell::new(0));
+        let low = Rc::new(Cell::new(0));
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Layout::row([
+                Item::auto(Viewed::with_key(
+                    "normal",
+                    (RenderPriority::Normal, Rc::clone(&normal)),
+                )),
+                Item::auto(Viewed::with_key(
+                    "low",
+                    (RenderPriority::Low, Rc::clone(&low)),
+                )),
+            ]),
+        );
+        app.set_frame_budget(Some(Duration::from_secs(0)));
+
+        // New components are always rendered
+        app.draw();
+        assert_eq!((normal.get(), low.get()), (1, 1));
+
+        app.execute_command(&ComponentSelector::focused(), "update", &[]);
+        app.draw();
+        assert_eq!((normal.get(), low.get()), (2, 1));
+        assert!(app.poll_state().dirty());
+
+        // Deferred components are rendered on the next frame
+        app.draw();
+        assert_eq!((normal.get(), low.get()), (2, 2));
+        assert!(!app.poll_state().dirty());
+    }
+
+    struct Progress {
+        value: usize,
+        views: Rc<Cell<usize>>,
+    }
+
+    impl Component for Progress {
+        type Message = ();
+        type Properties = Rc<Cell<usize>>;
+
+        fn create(views: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self { value: 0, views }
+        }
+
+        fn view(&self) -> Layout {
+            self.views.set(self.views.get() + 1);
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, _: Self::Message) -> ShouldRender {
+            self.value += 1;
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("advance", [Key::Char('a')], || ());
+        }
+
+        fn throttle(&self) -> Option<Duration> {
+            Some(Duration::from_millis(50))
+        }
+    }
+
+    #[test]
+    fn throttle_renders() {
+        let (sender, _receiver) = mpsc::channel();
+        let views = Rc::new(Cell::new(0));
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(10, 5),
+            Progress::with(Rc::clone(&views)),
+        );
+        let value = |app: &App| {
+            app.with_component(
+                &ComponentSelector::of::<Progress>(),
+                |progress: &Progress| progress.value,
+            )
+            .unwrap()
+        };
+
+        app.draw();
+        for _ in 0..3 {
+            app.handle_input(Event::key_press(Key::Char('a')));
+            app.draw();
+        }
+        // The updates are applied, but the component is not rendered again
+        assert_eq!(value(&app), 3);
+        assert_eq!(views.get(), 1);
+        assert!(app.poll_state().dirty());
+
+        std::thread::sleep(Duration::from_millis(60));
+        app.draw();
+        assert_eq!(views.get(), 2);
+        assert!(!app.poll_state().dirty());
+    }
+
+    struct Labelled;
+
+    impl Component for Labelled {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self
+        }
+
+        fn view(&self) -> Layout {
+            let label = Text::children(TextProperties::new().content("hi"));
+            Layout::row([
+                Item::content(Border::with(BorderProperties::new(label))),
+                Item::auto(Text::with(TextProperties::new().content("rest"))),
+                Item::content(Text::with_key(
+                    "label",
+                    TextProperties::new().content("a label too long"),
+                )),
+            ])
+        }
+    }
+
+    #[test]
+    fn content_items_are_sized_by_their_hints() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(12, 3),
+            Labelled::with(()),
+        );
+        let screen = app.draw();
+        let grapheme = |x, y| screen.textel(x, y).as_ref().unwrap().grapheme.clone();
+        // The border fits the label, the long label shrinks to the space
+        // left and the stretched item gets nothing
+        assert_eq!(grapheme(1, 1).as_str(), "h");
+        assert_eq!(grapheme(2, 1).as_str(), "i");
+        assert_eq!(grapheme(4, 0).as_str(), "a");
+
+        let canvas = || Canvas::new(Size::new(3, 1));
+        let row = Layout::row([Item::content(canvas()), Item::fixed(2)(canvas())]);
+        assert_eq!(
+            row.size_hint(Size::new(10, 10)),
+            SizeHint::exact(Size::new(5, 1))
+        );
+    }
+
+    #[test]
+    fn text_and_border_fit_their_content() {
+        let available = Size::new(80, 24);
+        let label = || Text::with(TextProperties::new().content("two\nlines"));
+        assert_eq!(
+            label().size_hint(available),
+            SizeHint::new(Size::zero(), Size::new(5, 2), Size::new(5, 2))
+        );
+
+        let border = Border::with(BorderProperties::new(label));
+        assert_eq!(border.size_hint(available).preferred, Size::new(7, 4));
+        assert_eq!(border.size_hint(available).max, Size::new(7, 4));
+
+        let titled = Border::with(
+            BorderProperties::new(label).title(Some(("a long title", Style::default()))),
+        );
+        assert_eq!(titled.size_hint(available).preferred, Size::new(16, 4));
+    }
+
+    struct Aligned(FlexAlign);
+
+    impl Component for Aligned {
+        type Message = ();
+        type Properties = FlexAlign;
+
+        fn create(align: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self(align)
+        }
+
+        fn view(&self) -> Layout {
+            Container::row([
+                Item::content(Text::with(TextProperties::new().content("hi"))),
+                Item::fixed(1)(Canvas::new(Size::new(1, 5))),
+            ])
+            .align(self.0)
+            .background(Style::normal(Colour::black(), Colour::white()))
+            .into()
+        }
+    }
+
+    #[test]
+    fn align_items_on_the_cross_axis() {
+        let grapheme = |align, x, y| {
+            let (sender, _receiver) = mpsc::channel();
+            let mut app = App::new(
+                MessageQueue::new(sender),
+                Size::new(4, 5),
+                Aligned::with(align),
+            );
+            let textel = app.draw().textel(x, y).clone();
+            textel.map(|textel| (textel.grapheme.to_string(), textel.style))
+        };
+        let text = |grapheme: &str| Some((grapheme.into(), Style::default()));
+        let background = Some((" ".into(), Style::normal(Colour::black(), Colour::white())));
+
+        assert_eq!(grapheme(FlexAlign::Stretch, 0, 0), text("h"));
+        assert_eq!(grapheme(FlexAlign::Start, 0, 0), text("h"));
+        assert_eq!(grapheme(FlexAlign::Centre, 0, 2), text("h"));
+        assert_eq!(grapheme(FlexAlign::Centre, 0, 1), background);
+        assert_eq!(grapheme(FlexAlign::End, 1, 4), text("i"));
+        // The fixed canvas fits the height of the row and past the items
+        // the background shows through
+        assert_eq!(grapheme(FlexAlign::End, 3, 0), background);
+    }
+
+    struct Tags((FlexDirection, FlexWrap, usize));
+
+    impl Component for Tags {
+        type Message = ();
+        type Properties = (FlexDirection, FlexWrap, usize);
+
+        fn create(properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self(properties)
+        }
+
+        fn view(&self) -> Layout {
+            let (direction, wrap, spacing) = self.0;
+            let tag = |text| {
+                let mut canvas = Canvas::new(Size::new(3, 1));
+                canvas.draw_str(0, 0, Style::default(), text);
+                Item::content(canvas)
+            };
+            Container::new(direction, [tag("aaa"), tag("bbb"), tag("ccc"), tag("ddd")])
+                .wrap(wrap)
+                .line_spacing(spacing)
+                .into()
+        }
+    }
+
+    #[test]
+    fn wrap_items_onto_lines() {
+        let rows = |properties| {
+            let (sender, _receiver) = mpsc::channel();
+            let mut app = App::new(
+                MessageQueue::new(sender),
+                Size::new(7, 4),
+                Tags::with(properties),
+            );
+            let screen = app.draw();
+            (0..4)
+                .map(|y| {
+                    (0..7)
+                        .map(|x| match screen.textel(x, y) {
+                            Some(textel) if !textel.grapheme.is_empty() => {
+                                textel.grapheme.as_str().to_owned()
+                            }
+                            _ => ".".into(),
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            rows((FlexDirection::Row, FlexWrap::Wrap, 1)),
+            ["aaabbb.", ".......", "cccddd.", "......."]
+        );
+        assert_eq!(
+            rows((FlexDirection::Row, FlexWrap::WrapReverse, 0)),
+            [".......", ".......", "cccddd.", "aaabbb."]
+        );
+        assert_eq!(
+            rows((FlexDirection::RowReverse, FlexWrap::Wrap, 0)),
+            ["bbbaaa.", "dddccc.", ".......", "......."]
+        );
+    }
+
+    struct ColourSample(ComponentLink<Self>);
+
+    impl Component for ColourSample {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self(link)
+        }
+
+        fn view(&self) -> Layout {
+            let sample = match self.0.capabilities().colours {
+                ColourSupport::TrueColour => "rgb",
+                _ => "ansi",
+            };
+            Text::with(TextProperties::new().content(sample))
+        }
+    }
+
+    #[test]
+    fn components_read_backend_capabilities() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(4, 1),
+            ColourSample::with(()),
+        );
+        let text = |app: &mut App| {
+            let canvas = app.draw();
+            (0..4)
+                .filter_map(|x| canvas.textel(x, 0).as_ref())
+                .map(|textel| textel.grapheme.to_string())
+                .collect::<String>()
+        };
+        assert_eq!(text(&mut app), "rgb ");
+
+        app.set_capabilities(BackendCapabilities::new().colours(ColourSupport::Ansi256));
+        assert!(app.poll_state().dirty());
+        assert_eq!(text(&mut app), "ansi");
+    }
+
+    struct Prompt {
+        focused: bool,
+        column: usize,
+    }
+
+    impl Component for Prompt {
+        type Message = ();
+        type Properties = bool;
+
+        fn create(focused: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+            Self { focused, column: 0 }
+        }
+
+        fn update(&mut self, _message: Self::Message) -> ShouldRender {
+            self.column += 1;
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content("> "))
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(self.focused);
+            if bindings.is_empty() {
+                bindings.add("type", [Key::Char('a')], || ());
+            }
+        }
+
+        fn cursor(&self) -> Option<TerminalCursor> {
+            Some(TerminalCursor::new(Position::new(self.column, 0)).shape(CursorShape::Bar))
+        }
+    }
+
+    #[test]
+    fn focused_components_place_the_terminal_cursor() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(4, 2),
+            Layout::column([
+                Item::fixed(1)(Prompt::with_key("unfocused", false)),
+                Item::fixed(1)(Prompt::with_key("focused", true)),
+            ]),
+        );
+        let cursor = |position| {
+            Some(TerminalCursor {
+                position,
+                shape: CursorShape::Bar,
+            })
+        };
+        assert_eq!(app.draw().cursor(), cursor(Position::new(0, 1)));
+
+        // The cursor follows the component even if it isn't re-rendered
+        app.handle_input(Event::key_press(Key::Char('a')));
+        assert_eq!(app.draw().cursor(), cursor(Position::new(1, 1)));
+
+        // A cursor outside the frame of the component is hidden
+        for _ in 0..3 {
+            app.handle_input(Event::key_press(Key::Char('a')));
+        }
+        assert_eq!(app.draw().cursor(), None);
+    }
+
+    struct Themed(ComponentLink<Self>);
+
+    impl Component for Themed {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self(link)
+        }
+
+        fn view(&self) -> Layout {
+            let theme = self.0.theme();
+            Text::with(
+                TextProperties::new()
+                    .content(theme.name().to_string())
+                    .style(theme.get_or("text", Style::default())),
+            )
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.set_focus(true);
+            bindings.add("light-theme", [Key::Char('l')], |this: &Self| {
+                this.0.set_theme(
+                    Theme::new("light")
+                        .style("text", Style::normal(Colour::white(), Colour::black())),
+                )
+            });
+        }
+    }
+
+    #[test]
+    fn components_are_rerendered_when_the_theme_changes() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(MessageQueue::new(sender), Size::new(5, 1), Themed::with(()));
+        let first_textel = |app: &mut App| app.draw().textel(0, 0).clone().unwrap();
+        assert_eq!(first_textel(&mut app).style, Style::default());
+
+        let dark = Style::normal(Colour::rgb(50, 48, 47), Colour::rgb(213, 196, 161));
+        app.set_theme(Theme::new("dark").style("text", dark));
+        assert!(app.poll_state().dirty());
+        let textel = first_textel(&mut app);
+        assert_eq!((textel.grapheme.as_str(), textel.style), ("d", dark));
+
+        // Components can switch the theme too
+        app.handle_input(Event::key_press(Key::Char('l')));
+        while let Ok(message) = receiver.try_recv() {
+            app.handle_message(message);
+        }
+        assert_eq!(app.theme().name(), "light");
+        let textel = first_textel(&mut app);
+        assert_eq!(
+            (textel.grapheme.as_str(), textel.style),
+            ("l", Style::normal(Colour::white(), Colour::black()))
+        );
+    }
+
+    struct Greeting(ComponentLink<Self>);
+
+    impl Component for Greeting {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self(link)
+        }
+
+        fn view(&self) -> Layout {
+            let name = self.0.service::<String>();
+            Text::with(TextProperties::new().content(name.as_deref().map_or("-", |name| name)))
+        }
+    }
+
+    #[test]
+    fn components_fetch_registered_services() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(3, 1),
+            Greeting::with(()),
+        );
+        assert_eq!(first_grapheme(app.draw()), "-");
+        assert_eq!(app.service::<String>(), None);
+
+        app.register_service(String::from("zi"));
+        app.register_service(7usize);
+        assert_eq!(
+            app.service::<String>().as_deref().map(String::as_str),
+            Some("zi")
+        );
+        assert_eq!(app.service::<usize>().as_deref(), Some(&7));
+
+        app.set_root(Greeting::with_key("other", ()));
+        assert_eq!(first_grapheme(app.draw()), "z");
+    }
+
+    #[derive(Clone)]
+    struct Selected(usize);
+
+    struct Listener {
+        selected: Option<usize>,
+    }
+
+    impl Component for Listener {
+        type Message = usize;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            link.subscribe(|Selected(index)| index);
+            Self { selected: None }
+        }
+
+        fn update(&mut self, index: Self::Message) -> ShouldRender {
+            self.selected = Some(index);
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+    }
+
+    struct Publisher(ComponentLink<Self>);
+
+    impl Component for Publisher {
+        type Message = usize;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self(link)
+        }
+
+        fn update(&mut self, index: Self::Message) -> ShouldRender {
+            self.0.publish(Selected(index));
+            ShouldRender::No
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+    }
+
+    #[test]
+    fn published_values_are_delivered_to_subscribers() {
+        let (sender, receiver) = mpsc::channel();
+        let layout = |keys: &[&'static str]| {
+            Layout::column(
+                keys.iter()
+                    .map(|key| Item::fixed(1)(Listener::with_key(*key, ())))
+                    .chain(Some(Item::fixed(1)(Publisher::with(())))),
+            )
+        };
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(1, 3),
+            layout(&["a", "b"]),
+        );
+        let selected = |app: &App, key| {
+            app.with_component(
+                &ComponentSelector::of::<Listener>().key(key),
+                |listener: &Listener| listener.selected,
+            )
+        };
+        let pump = |app: &mut App| {
+            while let Ok(message) = receiver.try_recv() {
+                app.handle_message(message);
+            }
+        };
+        app.draw();
+        pump(&mut app);
+        assert_eq!(
+            (selected(&app, "a"), selected(&app, "b")),
+            (Some(None), Some(None))
+        );
+
+        // Values published by a component or the embedder reach all subscribers
+        let publisher = app
+            .handle_of::<Publisher>(&ComponentSelector::of::<Publisher>())
+            .unwrap();
+        app.send_to(publisher, 1);
+        pump(&mut app);
+        assert!(app.poll_state().dirty());
+        assert_eq!(
+            (selected(&app, "a"), selected(&app, "b")),
+            (Some(Some(1)), Some(Some(1)))
+        );
+        app.publish(Selected(2));
+        assert_eq!(
+            (selected(&app, "a"), selected(&app, "b")),
+            (Some(Some(2)), Some(Some(2)))
+        );
+
+        // Unmounted components are unsubscribed, a remounted one subscribes anew
+        app.set_root(layout(&["a"]));
+        app.draw();
+        app.publish(Selected(3));
+        assert_eq!(
+            (selected(&app, "a"), selected(&app, "b")),
+            (Some(Some(3)), None)
+        );
+        app.set_root(layout(&["a", "b"]));
+        app.draw();
+        pump(&mut app);
+        assert_eq!(selected(&app, "b"), Some(None));
+        app.publish(Selected(4));
+        assert_eq!(
+            (selected(&app, "a"), selected(&app, "b")),
+            (Some(Some(4)), Some(Some(4)))
+        );
+    }
+
+    struct Anchor(ComponentLink<Self>);
+
+    impl Component for Anchor {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self(link)
+        }
+
+        fn resize(&mut self, _frame: Rect) -> ShouldRender {
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            let origin = self.0.own_frame().origin;
+            Text::with(TextProperties::new().content(origin.x.to_string()))
+        }
+    }
+
+    #[test]
+    fn frames_of_mounted_components() {
+        let (sender, _receiver) = mpsc::channel();
+        let mut app = App::new(
+            MessageQueue::new(sender),
+            Size::new(6, 1),
+            Layout::row([
+                Item::fixed(3)(Text::with_key("a", TextProperties::new())),
+                Item::auto(Anchor::with(())),
+            ]),
+        );
+        assert_eq!(
+            app.frame_of(&ComponentSelector::of::<Text>().key("a")),
+            None
+        );
+
+        let canvas = app.draw();
+        assert_eq!(
+            canvas
+                .textel(3, 0)
+                .as_ref()
+                .map(|textel| textel.grapheme.to_string()),
+            Some("3".into())
+        );
+        assert_eq!(
+            app.frame_of(&ComponentSelector::of::<Text>().key("a")),
+            Some(Rect::new(Position::new(0, 0), Size::new(3, 1)))
+        );
+        assert_eq!(
+            app.frame_of(&ComponentSelector::of::<Anchor>()),
+            Some(Rect::new(Position::new(3, 0), Size::new(3, 1)))
+        );
+        assert_eq!(
+            app.frame_of(&ComponentSelector::of::<Text>().key("b")),
+            None
+        );
+    }
+
     #[test]
     fn sizes() {
         eprintln!(
@@ -0,0 +1,528 @@
+//! A generic event loop for backends.
+//!
+//! A backend draws the [`Canvas`](../struct.Canvas.html) of an
+//! [`App`](../app/struct.App.html) on some surface, e.g. a terminal, and
+//! reports the user's input. Implementing the [`Backend`](trait.Backend.html)
+//! trait is enough to run an app with [`run_event_loop`](fn.run_event_loop.html),
+//! which takes care of the rest: drawing when the app is dirty, batching
+//! bursts of input, debouncing resizes, firing timers, ticking animations,
+//! running exclusive tasks with the backend suspended and replaying recorded
+//! input.
+//!
+//! Messages sent by components through their links are delivered by the
+//! backend too, as it knows how to wait for them alongside input. Backends
+//! with an asynchronous event source, e.g. a stream driven by an async
+//! runtime, block on it in [`poll_event`](trait.Backend.html#tymethod.poll_event)
+//! and spawn the futures of components on the same runtime, see
+//! [`Backend::setup`](trait.Backend.html#method.setup). The following backend
+//! renders an app without a screen, with the messages delivered through a
+//! channel:
+//!
+//! ```no_run
+//! # use std::{sync::mpsc, time::Duration};
+//! # use zi::{
+//! #     app::{ComponentMessage, MessageSender},
+//! #     backend::{self, Backend, BackendEvent},
+//! #     components::text::{Text, TextProperties},
+//! #     prelude::*,
+//! # };
+//! #[derive(Clone, Debug)]
+//! struct Sender(mpsc::Sender<ComponentMessage>);
+//!
+//! impl MessageSender for Sender {
+//!     fn send(&self, message: ComponentMessage) {
+//!         let _ = self.0.send(message);
+//!     }
+//!
+//!     fn clone_box(&self) -> Box<dyn MessageSender> {
+//!         Box::new(self.clone())
+//!     }
+//! }
+//!
+//! struct Headless {
+//!     sender: Sender,
+//!     receiver: mpsc::Receiver<ComponentMessage>,
+//!     frames: usize,
+//! }
+//!
+//! impl Backend for Headless {
+//!     type Error = std::convert::Infallible;
+//!     type Sender = Sender;
+//!
+//!     fn size(&self) -> Result<Size, Self::Error> {
+//!         Ok(Size::new(80, 24))
+//!     }
+//!
+//!     fn message_sender(&self) -> Self::Sender {
+//!         self.sender.clone()
+//!     }
+//!
+//!     fn present(&mut self, _canvas: &Canvas) -> Result<usize, Self::Error> {
+//!         self.frames += 1;
+//!         Ok(0)
+//!     }
+//!
+//!     fn poll_event(&mut self, timeout: Duration) -> Result<Option<BackendEvent>, Self::Error> {
+//!         Ok(self.receiver.recv_timeout(timeout).ok().map(BackendEvent::Message))
+//!     }
+//!
+//!     fn suspend(&mut self) -> Result<(), Self::Error> {
+//!         Ok(())
+//!     }
+//!
+//!     fn resume(&mut self) -> Result<(), Self::Error> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let (sender, receiver) = mpsc::channel();
+//! let mut headless = Headless { sender: Sender(sender), receiver, frames: 0 };
+//! backend::run_event_loop(
+//!     &mut headless,
+//!     Text::with(TextProperties::new().content("Hello, world!")),
+//! )
+//! .unwrap();
+//! # }
+//! ```
+use std::time::{Duration, Instant};
+
+use crate::{
+    app::{App, ComponentMessage, MessageSender},
+    recording::{RecordedEvent, Replay},
+    terminal::{BackendCapabilities, Canvas, Event, Size},
+    Layout,
+};
+
+/// A surface an [`App`](../app/struct.App.html) is drawn on and receives
+/// input from, see the [module documentation](index.html).
+pub trait Backend {
+    type Error;
+    /// Delivers the messages sent by components to the backend, which
+    /// returns them from [`poll_event`](#tymethod.poll_event).
+    type Sender: MessageSender;
+
+    /// Returns the size of the surface the app is drawn on.
+    fn size(&self) -> Result<Size, Self::Error>;
+
+    /// Returns what the backend supports, e.g. colours or mouse input.
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities::default()
+    }
+
+    /// Returns a sender for the messages sent by components.
+    fn message_sender(&self) -> Self::Sender;
+
+    /// Prepares the app before the event loop starts, e.g. to install an
+    /// [`Executor`](../app/trait.Executor.html), a clipboard or global key
+    /// bindings.
+    fn setup(&mut self, _app: &mut App) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Draws the canvas, returning the number of bytes written, if that
+    /// makes sense for the backend.
+    fn present(&mut self, canvas: &Canvas) -> Result<usize, Self::Error>;
+
+    /// Waits at most `timeout` for the next event, returning `None` if there
+    /// was none by then.
+    fn poll_event(&mut self, timeout: Duration) -> Result<Option<BackendEvent>, Self::Error>;
+
+    /// Hands the underlying surface over to a task which needs exclusive
+    /// access to it, e.g. an external editor, see
+    /// [`ComponentLink::run_exclusive`](../struct.ComponentLink.html#method.run_exclusive).
+    /// The event loop calls [`resume`](#tymethod.resume) when the task is
+    /// done.
+    fn suspend(&mut self) -> Result<(), Self::Error>;
+
+    /// Takes the surface back after a call to [`suspend`](#tymethod.suspend).
+    /// The app is resized and redrawn in full afterwards.
+    fn resume(&mut self) -> Result<(), Self::Error>;
+
+    /// How long to wait for more resizes before redrawing, s.t. a burst of
+    /// resizes causes a single relayout. Resizes are applied immediately by
+    /// default.
+    fn resize_debounce(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Called with the input events and resizes handled by the app, e.g. to
+    /// record them to a file, see
+    /// [`RecordingWriter`](../recording/struct.RecordingWriter.html).
+    fn record(&mut self, _event: &RecordedEvent) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Returns recorded input to replay at its original pace from when the
+    /// event loop starts, instead of the backend's input. Called once, before
+    /// the first frame.
+    fn take_replay(&mut self) -> Option<Replay> {
+        None
+    }
+}
+
+/// An event reported by a [`Backend`](trait.Backend.html).
+#[derive(Debug)]
+pub enum BackendEvent {
+    Input(Event),
+    Resize(Size),
+    /// A message sent by a component through the backend's
+    /// [`message_sender`](trait.Backend.html#tymethod.message_sender).
+    Message(ComponentMessage),
+    /// The capabilities of the backend changed, e.g. the mouse was captured.
+    Capabilities(BackendCapabilities),
+}
+
+/// Runs an app with a backend until a component stops it with
+/// [`ComponentLink::exit`](../struct.ComponentLink.html#method.exit), or until
+/// the backend fails.
+pub fn run_event_loop<BackendT: Backend>(
+    backend: &mut BackendT,
+    layout: Layout,
+) -> Result<(), BackendT::Error> {
+    run_with_exit_value::<_, ()>(backend, layout).map(|_| ())
+}
+
+/// Runs an app like [`run_event_loop`](fn.run_event_loop.html), returning the
+/// value passed to
+/// [`ComponentLink::exit_with`](../struct.ComponentLink.html#method.exit_with)
+/// by the component which stopped the app.
+///
+/// Returns `None` if the app was stopped with `ComponentLink::exit` or with a
+/// value which isn't a `ValueT`.
+pub fn run_with_exit_value<BackendT: Backend, ValueT: 'static>(
+    backend: &mut BackendT,
+    layout: Layout,
+) -> Result<Option<ValueT>, BackendT::Error> {
+    let size = backend.size()?;
+    let mut app = App::new(backend.message_sender(), size, layout);
+    app.set_capabilities(backend.capabilities());
+    backend.setup(&mut app)?;
+    backend.record(&RecordedEvent::Resize(size))?;
+    let mut replay = backend.take_replay().map(|replay| (replay, Instant::now()));
+
+    while !app.poll_state().exit() {
+        // Run the tasks which need the backend to themselves, e.g. an
+        // external editor
+        while let Some(task) = app.take_exclusive_task() {
+            backend.suspend()?;
+            let message = task.run();
+            backend.resume()?;
+            let size = backend.size()?;
+            backend.record(&RecordedEvent::Resize(size))?;
+            app.handle_resize(size);
+            if let Some(message) = message {
+                app.handle_message(message);
+            }
+        }
+
+        let canvas = app.draw();
+
+        let last_drawn = Instant::now();
+        let num_bytes_presented = backend.present(canvas)?;
+        let presented_time = last_drawn.elapsed();
+        app.record_presented(num_bytes_presented);
+
+        log::debug!(
+            "Frame: pres {:.1}ms diff {}b",
+            presented_time.as_secs_f64() * 1000.0,
+            num_bytes_presented,
+        );
+
+        poll_events_batch(backend, &mut app, &mut replay, last_drawn)?;
+    }
+
+    Ok(app.take_exit_value())
+}
+
+/// Handles events until the app needs to be redrawn, respecting
+/// `REDRAW_LATENCY`, `SUSTAINED_IO_REDRAW_LATENCY` and the backend's resize
+/// debounce window.
+fn poll_events_batch<BackendT: Backend>(
+    backend: &mut BackendT,
+    app: &mut App,
+    replay: &mut Option<(Replay, Instant)>,
+    last_drawn: Instant,
+) -> Result<(), BackendT::Error> {
+    let resize_debounce = backend.resize_debounce();
+    let mut first_event_time: Option<Instant> = None;
+    let mut last_resize: Option<Instant> = None;
+
+    while !app.poll_state().exit() && !app.has_exclusive_task() {
+        // Timers may be due if the loop was kept busy by other events
+        app.fire_timers();
+        if let Some((ref mut recorded, started)) = *replay {
+            recorded.play_until(app, started, Instant::now());
+            if recorded.is_finished() {
+                *replay = None;
+            }
+        }
+        let timeout = {
+            let since_last_drawn = last_drawn.elapsed();
+            if app.poll_state().dirty() && since_last_drawn >= REDRAW_LATENCY {
+                Duration::from_millis(0)
+            } else if app.poll_state().dirty() {
+                REDRAW_LATENCY - since_last_drawn
+            } else {
+                Duration::from_millis(if app.is_tickable() { 60 } else { 60_000 })
+            }
+        };
+        // Wait for the end of a burst of resizes before redrawing
+        let timeout = match last_resize {
+            Some(last_resize) if app.poll_state().resized() => {
+                resize_debounce.saturating_sub(last_resize.elapsed())
+            }
+            _ => timeout,
+        };
+        // Wake up for the next timer
+        let timeout = match app.next_timer() {
+            Some(next_timer) => timeout.min(next_timer.saturating_duration_since(Instant::now())),
+            None => timeout,
+        };
+        // Wake up for the next replayed event
+        let timeout = match replay {
+            Some((recorded, started)) => recorded
+                .next_deadline(*started)
+                .map_or(timeout, |deadline| {
+                    timeout.min(deadline.saturating_duration_since(Instant::now()))
+                }),
+            None => timeout,
+        };
+
+        match backend.poll_event(timeout)? {
+            Some(BackendEvent::Message(message)) => {
+                app.handle_message(message);
+                continue;
+            }
+            Some(BackendEvent::Capabilities(capabilities)) => {
+                app.set_capabilities(capabilities);
+                continue;
+            }
+            // Live input would interfere with the replay
+            Some(BackendEvent::Input(_)) if replay.is_some() => continue,
+            Some(BackendEvent::Input(event)) => {
+                backend.record(&RecordedEvent::Input(event))?;
+                app.handle_input(event);
+            }
+            Some(BackendEvent::Resize(size)) => {
+                backend.record(&RecordedEvent::Resize(size))?;
+                app.handle_resize(size);
+                last_resize = Some(Instant::now());
+            }
+            None => {
+                app.fire_timers();
+                app.tick();
+                return Ok(());
+            }
+        }
+        let force_redraw = app.poll_state().dirty()
+            && (first_event_time.get_or_insert_with(Instant::now).elapsed()
+                >= SUSTAINED_IO_REDRAW_LATENCY
+                || (app.poll_state().resized()
+                    && last_resize
+                        .map_or(true, |last_resize| last_resize.elapsed() >= resize_debounce)));
+        if force_redraw {
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+const REDRAW_LATENCY: Duration = Duration::from_millis(10);
+const SUSTAINED_IO_REDRAW_LATENCY: Duration = Duration::from_millis(100);
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::mpsc};
+
+    use super::*;
+
+    use crate::{
+        components::text::{Text, TextProperties},
+        prelude::*,
+        recording::{RecordedEntry, Recording},
+    };
+
+    #[derive(Clone, Debug)]
+    struct MessageQueue(mpsc::Sender<ComponentMessage>);
+
+    impl MessageSender for MessageQueue {
+        fn send(&self, message: ComponentMessage) {
+            self.0.send(message).unwrap();
+        }
+
+        fn clone_box(&self) -> Box<dyn MessageSender> {
+            Box::new(self.clone())
+        }
+    }
+
+    enum ChooserMessage {
+        Edit,
+        Edited(&'static str),
+        Choose,
+    }
+
+    struct Chooser {
+        chosen: &'static str,
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Chooser {
+        type Message = ChooserMessage;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            Self { chosen: "", link }
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(self.chosen))
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                ChooserMessage::Edit => self
+                    .link
+                    .run_exclusive(|| Some(ChooserMessage::Edited("notes.txt"))),
+                ChooserMessage::Edited(chosen) => self.chosen = chosen,
+                ChooserMessage::Choose => self.link.exit_with(self.chosen.to_owned()),
+            }
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("edit", [Key::Char('e')], || ChooserMessage::Edit);
+            bindings.add("choose", [Key::Char('\n')], || ChooserMessage::Choose);
+        }
+    }
+
+    /// Plays a script of events, logging what the event loop asks of it. A
+    /// `None` in the script is a timeout.
+    struct ScriptedBackend {
+        size: Size,
+        script: VecDeque<Option<BackendEvent>>,
+        sender: mpsc::Sender<ComponentMessage>,
+        receiver: mpsc::Receiver<ComponentMessage>,
+        replay: Option<Replay>,
+        log: Vec<String>,
+    }
+
+    impl Backend for ScriptedBackend {
+        type Error = ();
+        type Sender = MessageQueue;
+
+        fn size(&self) -> Result<Size, Self::Error> {
+            Ok(self.size)
+        }
+
+        fn message_sender(&self) -> Self::Sender {
+            MessageQueue(self.sender.clone())
+        }
+
+        fn present(&mut self, canvas: &Canvas) -> Result<usize, Self::Error> {
+            let line = (0..canvas.size().width)
+                .filter_map(|x| canvas.textel(x, 0).as_ref())
+                .map(|textel| textel.grapheme.as_str())
+                .collect::<String>();
+            self.log.push(format!("present {:?}", line.trim_end()));
+            Ok(0)
+        }
+
+        fn poll_event(&mut self, _: Duration) -> Result<Option<BackendEvent>, Self::Error> {
+            // Messages sent by components are delivered first, like in the
+            // scripted session
+            match self.receiver.try_recv() {
+                Ok(message) => Ok(Some(BackendEvent::Message(message))),
+                Err(_) => Ok(self.script.pop_front().flatten()),
+            }
+        }
+
+        fn suspend(&mut self) -> Result<(), Self::Error> {
+            self.log.push("suspend".to_owned());
+            Ok(())
+        }
+
+        fn resume(&mut self) -> Result<(), Self::Error> {
+            self.log.push("resume".to_owned());
+            self.size = Size::new(12, 1);
+            Ok(())
+        }
+
+        fn record(&mut self, event: &RecordedEvent) -> Result<(), Self::Error> {
+            self.log.push(format!("record {}", event));
+            Ok(())
+        }
+
+        fn take_replay(&mut self) -> Option<Replay> {
+            self.replay.take()
+        }
+    }
+
+    impl ScriptedBackend {
+        fn new(script: impl IntoIterator<Item = Option<BackendEvent>>) -> Self {
+            let (sender, receiver) = mpsc::channel();
+            Self {
+                size: Size::new(10, 1),
+                script: script.into_iter().collect(),
+                sender,
+                receiver,
+                replay: None,
+                log: Vec::new(),
+            }
+        }
+    }
+
+    #[test]
+    fn event_loop_runs_until_exit() {
+        let mut backend = ScriptedBackend::new([
+            Some(BackendEvent::Input(Event::key_press(Key::Char('e')))),
+            // Resizes are applied immediately without a debounce window
+            Some(BackendEvent::Resize(Size::new(8, 1))),
+            Some(BackendEvent::Input(Event::key_press(Key::Char('\n')))),
+        ]);
+
+        let chosen = run_with_exit_value::<_, String>(&mut backend, Chooser::with(()));
+        assert_eq!(chosen, Ok(Some("notes.txt".to_owned())));
+        assert_eq!(
+            backend.log,
+            [
+                "record resize 10x1",
+                "present \"\"",
+                "record input Char('e')",
+                "suspend",
+                "resume",
+                "record resize 12x1",
+                "present \"notes.txt\"",
+                "record resize 8x1",
+                "present \"notes.tx\"",
+                "record input Char('\\n')",
+            ]
+        );
+    }
+
+    #[test]
+    fn event_loop_replays_recorded_input() {
+        let recording = Recording {
+            entries: [(0, Key::Char('e')), (20, Key::Char('\n'))]
+                .iter()
+                .map(|&(elapsed, key)| RecordedEntry {
+                    elapsed: Duration::from_millis(elapsed),
+                    event: RecordedEvent::Input(Event::key_press(key)),
+                })
+                .collect(),
+        };
+        // Live input is ignored while replaying
+        let mut backend =
+            ScriptedBackend::new([Some(BackendEvent::Input(Event::key_press(Key::Char('x'))))]);
+        backend.replay = Some(Replay::new(&recording));
+
+        let chosen = run_with_exit_value::<_, String>(&mut backend, Chooser::with(()));
+        assert_eq!(chosen, Ok(Some("notes.txt".to_owned())));
+        assert!(!backend.log.iter().any(|entry| entry.contains("Char('x')")));
+    }
+}
@@ -2,13 +2,18 @@ use smallvec::{smallvec, SmallVec};
 use std::{
     any::{Any, TypeId},
     borrow::Cow,
+    cmp,
     collections::hash_map::HashMap,
     fmt,
+    hash::{Hash, Hasher},
     marker::PhantomData,
+    ops::RangeInclusive,
+    sync::Arc,
+    time::Instant,
 };
 
-use super::{Component, DynamicMessage};
-use crate::terminal::Key;
+use super::{keymap_config::KeymapOverrides, Component, DynamicMessage};
+use crate::terminal::{Key, Modifiers, MouseButton, Position};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct CommandId(usize);
@@ -33,6 +38,25 @@ impl NamedBindingQuery {
     }
 }
 
+/// A key which continues a pending key sequence, see
+/// [`Keymap::continuations`](struct.Keymap.html#method.continuations).
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyContinuation {
+    pub key: Key,
+    /// The command run by the key, or the commands of the longer sequences
+    /// it's a prefix of.
+    pub query: NamedBindingQuery,
+}
+
+impl KeyContinuation {
+    pub fn new(keymap: &Keymap, key: Key, query: &BindingQuery) -> Self {
+        Self {
+            key,
+            query: NamedBindingQuery::new(keymap, query),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum BindingQuery {
     Match(CommandId),
@@ -55,10 +79,41 @@ impl BindingQuery {
     }
 }
 
+/// Help text for a command, e.g. for help screens and command palettes,
+/// see [`BindingBuilder::description`](struct.BindingBuilder.html#method.description).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommandMetadata {
+    /// What the command does, e.g. `Save the current file`.
+    pub description: Option<Cow<'static, str>>,
+    /// The group of related commands it belongs to, e.g. `Files`.
+    pub category: Option<Cow<'static, str>>,
+}
+
+/// A command of a keymap with its metadata and bindings, see
+/// [`Keymap::commands`](struct.Keymap.html#method.commands).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandInfo<'a> {
+    pub id: CommandId,
+    pub name: &'a str,
+    pub metadata: &'a CommandMetadata,
+    /// The patterns bound to the command, ordered by how they are written.
+    pub patterns: Vec<&'a KeyPattern>,
+}
+
 #[derive(Debug, Default)]
 pub struct Keymap {
     names: Vec<Cow<'static, str>>,
+    /// The metadata of the commands, by id like their names.
+    metadata: Vec<CommandMetadata>,
     keymap: HashMap<KeyPattern, BindingQuery>,
+    /// The bound sequences with wildcards, in the order they were bound.
+    /// They are also in `keymap`, but are matched one by one.
+    wildcards: Vec<KeyPattern>,
+    /// The key `<leader>` stands for, see [`set_leader`](#method.set_leader).
+    leader: Option<Key>,
+    /// The patterns with a `<leader>` as they were bound. They are in
+    /// `keymap` with the leader key substituted, once it is set.
+    leader_bindings: Vec<(KeyPattern, CommandId)>,
 }
 
 impl Keymap {
@@ -70,8 +125,109 @@ impl Keymap {
         &self.names[command_id.0]
     }
 
+    pub fn metadata(&self, command_id: &CommandId) -> &CommandMetadata {
+        &self.metadata[command_id.0]
+    }
+
+    pub fn set_description(
+        &mut self,
+        command_id: CommandId,
+        description: impl Into<Cow<'static, str>>,
+    ) {
+        self.metadata[command_id.0].description = Some(description.into());
+    }
+
+    pub fn set_category(&mut self, command_id: CommandId, category: impl Into<Cow<'static, str>>) {
+        self.metadata[command_id.0].category = Some(category.into());
+    }
+
+    /// Iterates over the commands in the order they were added, with their
+    /// metadata and bindings, e.g. to generate a help screen:
+    ///
+    /// ```
+    /// # use zi::{Key, Keymap};
+    /// let mut keymap = Keymap::new();
+    /// let save = keymap.add("save", [Key::Ctrl('x'), Key::Ctrl('s')]);
+    /// keymap.set_description(save, "Save the current file");
+    /// keymap.bind_command(save, [Key::F(2)]);
+    ///
+    /// let help: Vec<_> = keymap
+    ///     .commands()
+    ///     .map(|command| {
+    ///         let keys: Vec<_> = command.patterns.iter().map(ToString::to_string).collect();
+    ///         let description = command.metadata.description.as_deref().unwrap_or("");
+    ///         format!("{:<12} {}", keys.join(", "), description)
+    ///     })
+    ///     .collect();
+    /// assert_eq!(help, ["C-x C-s, F2  Save the current file"]);
+    /// ```
+    pub fn commands(&self) -> impl Iterator<Item = CommandInfo<'_>> + '_ {
+        self.names.iter().zip(self.metadata.iter()).enumerate().map(
+            move |(index, (name, metadata))| {
+                let id = CommandId(index);
+                CommandInfo {
+                    id,
+                    name,
+                    metadata,
+                    patterns: self.patterns(&id),
+                }
+            },
+        )
+    }
+
+    /// Returns the patterns bound to a command, ordered by how they are
+    /// written.
+    pub fn patterns(&self, command_id: &CommandId) -> Vec<&KeyPattern> {
+        let mut patterns: Vec<_> = self
+            .bindings()
+            .filter(|(_, other)| other == command_id)
+            .map(|(pattern, _)| pattern)
+            .collect();
+        patterns.sort_by_cached_key(|pattern| pattern.to_string());
+        patterns
+    }
+
+    /// Returns the id of the command registered with the given name, if any.
+    pub fn command_id(&self, name: &str) -> Option<CommandId> {
+        self.names
+            .iter()
+            .position(|existing| existing == name)
+            .map(CommandId)
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.keymap.is_empty()
+        self.keymap.is_empty() && self.leader_bindings.is_empty()
+    }
+
+    /// Iterates over the bound patterns and the commands they run, in no
+    /// particular order.
+    pub fn bindings(&self) -> impl Iterator<Item = (&KeyPattern, CommandId)> + '_ {
+        self.keymap
+            .iter()
+            .filter_map(|(pattern, query)| query.matches().map(|command_id| (pattern, command_id)))
+    }
+
+    /// Returns the keys which continue the key sequence `prefix`, ordered
+    /// by key, each with the command it runs or the commands of the longer
+    /// sequences it's a prefix of. An empty prefix returns the bindings of
+    /// single keys. Sequences with wildcards aren't listed.
+    pub fn continuations(&self, prefix: &[Key]) -> Vec<(Key, &BindingQuery)> {
+        let mut continuations: Vec<_> = self
+            .keymap
+            .iter()
+            .filter_map(|(pattern, query)| match pattern {
+                KeyPattern::Keys(keys)
+                    if keys.len() == prefix.len() + 1 && keys.starts_with(prefix) =>
+                {
+                    Some((keys[prefix.len()], query))
+                }
+                _ => None,
+            })
+            .collect();
+        continuations.sort_by(|(left, _), (right, _)| {
+            left.partial_cmp(right).unwrap_or(cmp::Ordering::Equal)
+        });
+        continuations
     }
 
     pub fn add(
@@ -95,14 +251,71 @@ impl Keymap {
             .unwrap_or_else(|| (CommandId(self.names.len()), true));
         if is_new_command {
             self.names.push(name);
+            self.metadata.push(CommandMetadata::default());
         }
         (command_id, is_new_command)
     }
 
+    /// Returns the key `<leader>` stands for in patterns, if set.
+    pub fn leader(&self) -> Option<Key> {
+        self.leader
+    }
+
+    /// Sets the key `<leader>` stands for in patterns, see
+    /// [`KeyMatcher::Leader`](enum.KeyMatcher.html#variant.Leader), and
+    /// rebinds the patterns starting with it. Without a leader, these
+    /// patterns aren't matched.
+    ///
+    /// ```
+    /// # use zi::{BindingQuery, Key, KeyPattern, Keymap};
+    /// let mut keymap = Keymap::new();
+    /// let find_file = keymap.add("find-file", KeyPattern::leader([Key::Char('f')]));
+    /// assert_eq!(keymap.check_sequence(&[Key::Char(' ')]), None);
+    ///
+    /// keymap.set_leader(Some(Key::Char(' ')));
+    /// assert_eq!(
+    ///     keymap.check_sequence(&[Key::Char(' '), Key::Char('f')]).as_deref(),
+    ///     Some(&BindingQuery::Match(find_file))
+    /// );
+    /// ```
+    ///
+    /// Like overrides, patterns overlapping with other bindings once the
+    /// leader is substituted are skipped with a warning.
+    pub fn set_leader(&mut self, leader: Option<Key>) {
+        if self.leader == leader {
+            return;
+        }
+        let leader_bindings = std::mem::take(&mut self.leader_bindings);
+        for (pattern, command_id) in leader_bindings.iter() {
+            self.remove_resolved_leader(pattern, *command_id);
+        }
+        self.leader = leader;
+        for (pattern, command_id) in leader_bindings {
+            self.bind_command(command_id, pattern);
+        }
+    }
+
     pub fn bind_command(&mut self, command_id: CommandId, pattern: impl Into<KeyPattern>) {
         let name = &self.names[command_id.0];
         let pattern = pattern.into();
 
+        // Patterns with a leader are bound with the leader key substituted
+        if pattern.has_leader() {
+            if let Some(resolved) = self.resolve_leader(&pattern) {
+                match self.overlapping_command(&resolved) {
+                    Some(other_command_id) => log::warn!(
+                        "Skipping binding `{}` for `{}` as it overlaps with a binding for `{}`",
+                        resolved,
+                        name,
+                        self.names[other_command_id.0]
+                    ),
+                    None => self.bind_command(command_id, resolved),
+                }
+            }
+            self.leader_bindings.push((pattern, command_id));
+            return;
+        }
+
         // Add `BindingQuery::PrefixOf` entries for all prefixes of the key sequence
         if let Some(keys) = pattern.keys() {
             for prefix_len in 0..keys.len() {
@@ -124,6 +337,12 @@ impl Keymap {
             }
         }
 
+        if let KeyPattern::Sequence(_) = pattern {
+            if !self.keymap.contains_key(&pattern) {
+                self.wildcards.push(pattern.clone());
+            }
+        }
+
         // Add a `BindingQuery::Match` for the full key sequence
         self.keymap
             .entry(pattern.clone())
@@ -144,23 +363,250 @@ impl Keymap {
             .or_insert_with(|| BindingQuery::Match(command_id));
     }
 
-    pub fn check_sequence(&self, keys: &[Key]) -> Option<&BindingQuery> {
+    /// Removes the binding for `pattern`, returning the command it was bound
+    /// to, if any. The command itself and its other bindings are kept.
+    pub fn remove(&mut self, pattern: impl Into<KeyPattern>) -> Option<CommandId> {
+        let pattern = pattern.into();
+        if pattern.has_leader() {
+            let index = self
+                .leader_bindings
+                .iter()
+                .position(|(existing, _)| *existing == pattern)?;
+            let (_, command_id) = self.leader_bindings.remove(index);
+            self.remove_resolved_leader(&pattern, command_id);
+            return Some(command_id);
+        }
+        let command_id = match self.keymap.get(&pattern) {
+            Some(BindingQuery::Match(command_id)) => *command_id,
+            _ => return None,
+        };
+        self.keymap.remove(&pattern);
+        self.wildcards.retain(|wildcard| *wildcard != pattern);
+
+        // A command bound to several sequences with a common prefix is
+        // listed once for each of them, only one is removed
+        if let Some(keys) = pattern.keys() {
+            for prefix_len in 0..keys.len() {
+                let prefix = KeyPattern::Keys(keys.iter().copied().take(prefix_len).collect());
+                if let Some(BindingQuery::PrefixOf(prefix_of)) = self.keymap.get_mut(&prefix) {
+                    if let Some(index) = prefix_of.iter().position(|other| *other == command_id) {
+                        prefix_of.remove(index);
+                    }
+                    if prefix_of.is_empty() {
+                        self.keymap.remove(&prefix);
+                    }
+                }
+            }
+        }
+        Some(command_id)
+    }
+
+    /// Binds a command to `pattern` like
+    /// [`bind_command`](#method.bind_command), but removes the bindings
+    /// overlapping with it instead of panicking: the bindings of the prefixes
+    /// of `pattern`, of `pattern` itself and of the longer sequences it's a
+    /// prefix of. Returns the commands whose bindings were removed.
+    pub fn replace(
+        &mut self,
+        command_id: CommandId,
+        pattern: impl Into<KeyPattern>,
+    ) -> SmallVec<[CommandId; 4]> {
+        let pattern = pattern.into();
+        let resolved = self.resolve_leader(&pattern);
+        let mut overlapping: Vec<KeyPattern> = self
+            .keymap
+            .iter()
+            .filter(|(existing, query)| {
+                query.matches().is_some()
+                    && patterns_overlap(existing, resolved.as_ref().unwrap_or(&pattern))
+            })
+            .map(|(existing, _)| existing.clone())
+            .collect();
+        overlapping.sort_by_key(|existing| existing.to_string());
+
+        let mut replaced = SmallVec::new();
+        for existing in overlapping {
+            if let Some(other_command_id) = self.remove(existing) {
+                replaced.push(other_command_id);
+            }
+        }
+        self.bind_command(command_id, pattern);
+        replaced
+    }
+
+    /// Removes all the bindings of a command. The command is kept, s.t. it
+    /// can be bound again or executed by name.
+    pub fn unbind_command(&mut self, command_id: CommandId) {
+        self.keymap.retain(|_, query| match query {
+            BindingQuery::Match(other_command_id) => *other_command_id != command_id,
+            BindingQuery::PrefixOf(prefix_of) => {
+                prefix_of.retain(|other_command_id| *other_command_id != command_id);
+                !prefix_of.is_empty()
+            }
+        });
+        let keymap = &self.keymap;
+        self.wildcards
+            .retain(|wildcard| keymap.contains_key(wildcard));
+        self.leader_bindings
+            .retain(|(_, other_command_id)| *other_command_id != command_id);
+    }
+
+    /// Binds a command to `patterns`, replacing the patterns it was bound to.
+    /// Unlike [`bind_command`](#method.bind_command), patterns overlapping
+    /// with the bindings of other commands don't panic, they are skipped
+    /// with a warning as they usually come from a user's config.
+    pub fn rebind_command(
+        &mut self,
+        command_id: CommandId,
+        patterns: impl IntoIterator<Item = KeyPattern>,
+    ) {
+        self.unbind_command(command_id);
+        for pattern in patterns {
+            match self.overlapping_command(&pattern) {
+                Some(other_command_id) => log::warn!(
+                    "Skipping binding `{}` for `{}` as it overlaps with a binding for `{}`",
+                    pattern,
+                    self.names[command_id.0],
+                    self.names[other_command_id.0]
+                ),
+                None => self.bind_command(command_id, pattern),
+            }
+        }
+    }
+
+    /// Returns `pattern` with the leader key substituted for `<leader>`, or
+    /// `None` if it has no leader or the leader isn't set.
+    fn resolve_leader(&self, pattern: &KeyPattern) -> Option<KeyPattern> {
+        let leader = self.leader?;
+        match pattern {
+            KeyPattern::Sequence(matchers) if pattern.has_leader() => Some(KeyPattern::sequence(
+                matchers.iter().map(|matcher| match matcher {
+                    KeyMatcher::Leader => KeyMatcher::Key(leader),
+                    matcher => matcher.clone(),
+                }),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Removes the binding of a pattern with a leader for the current
+    /// leader key, unless it was skipped as it overlapped another binding.
+    fn remove_resolved_leader(&mut self, pattern: &KeyPattern, command_id: CommandId) {
+        if let Some(resolved) = self.resolve_leader(pattern) {
+            if self.keymap.get(&resolved).and_then(BindingQuery::matches) == Some(command_id) {
+                self.remove(resolved);
+            }
+        }
+    }
+
+    /// Returns a command whose bindings overlap with `pattern`, if any, i.e.
+    /// binding `pattern` would panic.
+    fn overlapping_command(&self, pattern: &KeyPattern) -> Option<CommandId> {
+        if let Some(keys) = pattern.keys() {
+            for prefix_len in 0..keys.len() {
+                let prefix = KeyPattern::Keys(keys.iter().copied().take(prefix_len).collect());
+                if let Some(BindingQuery::Match(command_id)) = self.keymap.get(&prefix) {
+                    return Some(*command_id);
+                }
+            }
+        }
+        match self.keymap.get(pattern) {
+            Some(BindingQuery::Match(command_id)) => Some(*command_id),
+            Some(BindingQuery::PrefixOf(prefix_of)) => prefix_of.first().copied(),
+            None => None,
+        }
+    }
+
+    /// Like [`check_sequence`](#method.check_sequence), but a single key
+    /// classified as a double press or a press-and-hold first matches the
+    /// corresponding [`DoublePress`](struct.DoublePress.html) or
+    /// [`Hold`](struct.Hold.html) pattern, if bound.
+    pub fn check_gesture(
+        &self,
+        keys: &[Key],
+        gesture: KeyGesture,
+    ) -> Option<Cow<'_, BindingQuery>> {
+        let pattern = match (keys, gesture) {
+            (&[key], KeyGesture::DoublePress) => Some(KeyPattern::DoublePress(key)),
+            (&[key], KeyGesture::Hold) => Some(KeyPattern::Hold(key)),
+            _ => None,
+        };
+        pattern
+            .and_then(|pattern| self.keymap.get(&pattern))
+            .map(Cow::Borrowed)
+            .or_else(|| self.check_sequence(keys))
+    }
+
+    /// Looks up the binding for a key sequence. Exact bindings take
+    /// precedence over sequences with wildcards, which are matched in the
+    /// order they were bound, and a full match of a sequence takes precedence
+    /// over the longer sequences it's a prefix of. Bindings for
+    /// [`AnyCharacter`](struct.AnyCharacter.html) and
+    /// [`EndsWith`](struct.EndsWith.html) come last.
+    pub fn check_sequence(&self, keys: &[Key]) -> Option<Cow<'_, BindingQuery>> {
         let pattern: KeyPattern = keys.iter().copied().into();
-        self.keymap
-            .get(&pattern)
-            .or_else(|| match keys {
-                &[Key::Char(_)] => self.keymap.get(&KeyPattern::AnyCharacter),
+        let exact = self.keymap.get(&pattern);
+        let wildcard = match exact {
+            Some(BindingQuery::Match(_)) => None,
+            _ => self.check_wildcards(keys),
+        };
+        let query = match (exact, wildcard) {
+            (Some(BindingQuery::PrefixOf(exact)), Some(BindingQuery::PrefixOf(wildcard))) => {
+                let mut prefix_of = exact.clone();
+                prefix_of.extend(wildcard);
+                Some(Cow::Owned(BindingQuery::PrefixOf(prefix_of)))
+            }
+            (Some(exact), _) => Some(Cow::Borrowed(exact)),
+            (None, Some(wildcard)) => Some(Cow::Owned(wildcard)),
+            (None, None) => None,
+        };
+        query.or_else(|| {
+            match keys {
+                &[Key::Char(_) | Key::Grapheme(_)] => self.keymap.get(&KeyPattern::AnyCharacter),
                 _ => None,
-            })
+            }
             .or_else(|| match keys {
                 &[_, key] | &[key] => self.keymap.get(&KeyPattern::EndsWith([key])),
                 _ => None,
             })
+            .map(Cow::Borrowed)
+        })
+    }
+
+    /// Matches the key sequence against the sequences with wildcards.
+    fn check_wildcards(&self, keys: &[Key]) -> Option<BindingQuery> {
+        let mut prefix_of = SmallVec::new();
+        for wildcard in self.wildcards.iter() {
+            let matchers = match wildcard {
+                KeyPattern::Sequence(matchers) => matchers,
+                _ => continue,
+            };
+            if matchers.len() < keys.len()
+                || !matchers
+                    .iter()
+                    .zip(keys)
+                    .all(|(matcher, key)| matcher.matches(key))
+            {
+                continue;
+            }
+            let command_id = self.keymap[wildcard]
+                .matches()
+                .expect("wildcards are bound to a command");
+            if matchers.len() == keys.len() {
+                return Some(BindingQuery::Match(command_id));
+            }
+            prefix_of.push(command_id);
+        }
+        (!prefix_of.is_empty()).then(|| BindingQuery::PrefixOf(prefix_of))
     }
 }
 
 #[allow(clippy::type_complexity)]
-struct DynamicCommandFn(Box<dyn Fn(&dyn Any, &[Key]) -> Option<DynamicMessage>>);
+struct DynamicCommandFn(
+    Box<dyn Fn(&dyn Any, &[Key], Option<Position>, Instant) -> Option<DynamicMessage>>,
+    /// Whether the command returns a message, see `CommandFn::RETURNS_MESSAGE`.
+    bool,
+);
 
 impl fmt::Debug for DynamicCommandFn {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -173,7 +619,13 @@ pub(crate) struct DynamicBindings {
     keymap: Keymap,
     commands: Vec<DynamicCommandFn>,
     focused: bool,
+    focusable: bool,
+    trap_focus: bool,
+    bubble_keys: bool,
     notify: bool,
+    tickable: bool,
+    /// Whether the keymap overrides of the app were applied.
+    overridden: bool,
     type_id: TypeId,
 }
 
@@ -183,7 +635,12 @@ impl DynamicBindings {
             keymap: Keymap::new(),
             commands: Vec::new(),
             focused: false,
+            focusable: false,
+            trap_focus: false,
+            bubble_keys: false,
             notify: false,
+            tickable: false,
+            overridden: false,
             type_id: TypeId::of::<ComponentT>(),
         }
     }
@@ -203,6 +660,36 @@ impl DynamicBindings {
         self.focused
     }
 
+    #[inline]
+    pub fn set_focusable(&mut self, focusable: bool) {
+        self.focusable = focusable;
+    }
+
+    #[inline]
+    pub fn focusable(&self) -> bool {
+        self.focusable
+    }
+
+    #[inline]
+    pub fn set_trap_focus(&mut self, trap_focus: bool) {
+        self.trap_focus = trap_focus;
+    }
+
+    #[inline]
+    pub fn trap_focus(&self) -> bool {
+        self.trap_focus
+    }
+
+    #[inline]
+    pub fn set_bubble_keys(&mut self, bubble_keys: bool) {
+        self.bubble_keys = bubble_keys;
+    }
+
+    #[inline]
+    pub fn bubble_keys(&self) -> bool {
+        self.bubble_keys
+    }
+
     #[inline]
     pub fn set_notify(&mut self, notify: bool) {
         self.notify = notify;
@@ -213,6 +700,54 @@ impl DynamicBindings {
         self.notify
     }
 
+    #[inline]
+    pub fn set_tickable(&mut self, tickable: bool) {
+        self.tickable = tickable;
+    }
+
+    #[inline]
+    pub fn tickable(&self) -> bool {
+        self.tickable
+    }
+
+    /// Removes all commands and bindings, s.t. the component builds its
+    /// keymap again. The keymap overrides are applied again too.
+    pub fn clear(&mut self) {
+        self.keymap = Keymap::new();
+        self.commands.clear();
+        self.overridden = false;
+    }
+
+    pub fn set_leader(&mut self, leader: Option<Key>) {
+        self.keymap.set_leader(leader);
+    }
+
+    /// Rebinds the commands overridden for the component, once the component
+    /// built its keymap.
+    pub fn apply_overrides(&mut self, type_name: &str, overrides: &KeymapOverrides) {
+        if self.overridden || self.keymap.is_empty() {
+            return;
+        }
+        self.overridden = true;
+        let names: Vec<_> = overrides
+            .commands(type_name)
+            .flat_map(|commands| commands.keys())
+            .collect();
+        for name in names {
+            let patterns = overrides
+                .patterns(type_name, name)
+                .expect("the command is overridden");
+            match self.keymap.command_id(name) {
+                Some(command_id) => self.keymap.rebind_command(command_id, patterns.to_vec()),
+                None => log::warn!(
+                    "Cannot override unknown command `{}` of {}",
+                    name,
+                    type_name
+                ),
+            }
+        }
+    }
+
     pub fn add<ComponentT: Component, const VARIANT: usize>(
         &mut self,
         name: impl Into<Cow<'static, str>>,
@@ -224,22 +759,33 @@ impl DynamicBindings {
         command_id
     }
 
-    pub fn add_command<ComponentT: Component, const VARIANT: usize>(
+    pub fn add_command<ComponentT: Component, const VARIANT: usize, CommandFnT>(
         &mut self,
         name: impl Into<Cow<'static, str>>,
-        command_fn: impl CommandFn<ComponentT, VARIANT> + 'static,
-    ) -> CommandId {
+        command_fn: CommandFnT,
+    ) -> CommandId
+    where
+        CommandFnT: CommandFn<ComponentT, VARIANT> + 'static,
+    {
         assert_eq!(self.type_id, TypeId::of::<ComponentT>());
 
         let (command_id, is_new_command) = self.keymap.add_command(name);
-        let dyn_command_fn = DynamicCommandFn(Box::new(move |erased: &dyn Any, keys: &[Key]| {
-            let component = erased
-                .downcast_ref()
-                .expect("Incorrect `Component` type when downcasting");
-            command_fn
-                .call(component, keys)
-                .map(|message| DynamicMessage(Box::new(message)))
-        }));
+        let dyn_command_fn = DynamicCommandFn(
+            Box::new(
+                move |erased: &dyn Any,
+                      keys: &[Key],
+                      position: Option<Position>,
+                      timestamp: Instant| {
+                    let component = erased
+                        .downcast_ref()
+                        .expect("Incorrect `Component` type when downcasting");
+                    command_fn
+                        .call_at(component, keys, position, timestamp)
+                        .map(|message| DynamicMessage(Box::new(message)))
+                },
+            ),
+            CommandFnT::RETURNS_MESSAGE,
+        );
         if is_new_command {
             self.commands.push(dyn_command_fn);
         } else {
@@ -258,10 +804,20 @@ impl DynamicBindings {
         component: &ComponentT,
         id: CommandId,
         keys: &[Key],
+        position: Option<Position>,
+        timestamp: Instant,
     ) -> Option<DynamicMessage> {
         assert_eq!(self.type_id, TypeId::of::<ComponentT>());
 
-        (self.commands[id.0].0)(component, keys)
+        (self.commands[id.0].0)(component, keys, position, timestamp)
+    }
+
+    /// Whether a command returns a message. If it does, not returning one
+    /// means it didn't handle the keys and they are passed on to the next
+    /// component on the focus path. Commands that don't return messages are
+    /// run for their side effects and always handle the keys.
+    pub fn returns_message(&self, id: CommandId) -> bool {
+        self.commands[id.0].1
     }
 
     pub fn typed<ComponentT: Component>(
@@ -297,6 +853,13 @@ impl<ComponentT: Component> Bindings<ComponentT> {
         self.bindings.keymap.is_empty()
     }
 
+    /// Sets whether the component has focus, i.e. whether it receives key
+    /// presses.
+    ///
+    /// Focused components form a tree. Keys are delivered to the focused
+    /// component which gained focus most recently among those without
+    /// focused descendants, and bubble up to its focused ancestors until one
+    /// of them has a binding for the key.
     #[inline]
     pub fn set_focus(&mut self, focused: bool) {
         self.bindings.set_focus(focused)
@@ -307,6 +870,49 @@ impl<ComponentT: Component> Bindings<ComponentT> {
         self.bindings.focused()
     }
 
+    /// Sets whether the component can be focused with Tab navigation. The
+    /// app moves focus between focusable components when Tab or BackTab are
+    /// pressed and no focused component handles them. The component is
+    /// notified with [`Component::focus`](trait.Component.html#method.focus)
+    /// and is treated as focused while it has Tab focus.
+    #[inline]
+    pub fn set_focusable(&mut self, focusable: bool) {
+        self.bindings.set_focusable(focusable)
+    }
+
+    #[inline]
+    pub fn focusable(&self) -> bool {
+        self.bindings.focusable()
+    }
+
+    /// Sets whether the component traps focus within itself and its
+    /// descendants, see [`FocusScope`](components/focus/struct.FocusScope.html).
+    #[inline]
+    pub fn set_trap_focus(&mut self, trap_focus: bool) {
+        self.bindings.set_trap_focus(trap_focus)
+    }
+
+    #[inline]
+    pub fn trap_focus(&self) -> bool {
+        self.bindings.trap_focus()
+    }
+
+    /// Sets whether keys which the focused component doesn't handle bubble up
+    /// to all its ancestors, nearest first, rather than only to its focused
+    /// ancestors. This lets a container handle app-level shortcuts with its
+    /// own keymap without taking focus, and without every leaf re-declaring
+    /// them. Bubbling continues up to the next focused ancestor, which
+    /// decides in turn whether to bubble keys to its own ancestors.
+    #[inline]
+    pub fn set_bubble_keys(&mut self, bubble_keys: bool) {
+        self.bindings.set_bubble_keys(bubble_keys)
+    }
+
+    #[inline]
+    pub fn bubble_keys(&self) -> bool {
+        self.bindings.bubble_keys()
+    }
+
     #[inline]
     pub fn set_notify(&mut self, notify: bool) {
         self.bindings.set_notify(notify)
@@ -317,6 +923,20 @@ impl<ComponentT: Component> Bindings<ComponentT> {
         self.bindings.notify()
     }
 
+    /// Subscribes the component to
+    /// [`Component::tick`](trait.Component.html#method.tick), e.g. while an
+    /// animation is running. Like focus, it should be set on every call to
+    /// `bindings`, before returning early if the bindings are not empty.
+    #[inline]
+    pub fn set_tickable(&mut self, tickable: bool) {
+        self.bindings.set_tickable(tickable)
+    }
+
+    #[inline]
+    pub fn tickable(&self) -> bool {
+        self.bindings.tickable()
+    }
+
     #[inline]
     pub fn add<const VARIANT: usize>(
         &mut self,
@@ -332,13 +952,87 @@ impl<ComponentT: Component> Bindings<ComponentT> {
         &mut self,
         name: impl Into<Cow<'static, str>>,
         command_fn: impl CommandFn<ComponentT, VARIANT> + 'static,
-    ) -> BindingBuilder<ComponentT> {
+    ) -> BindingBuilder<'_, ComponentT> {
         let command_id = self.bindings.add_command(name, command_fn);
         BindingBuilder {
             wrapped: self,
             command_id,
         }
     }
+
+    #[inline]
+    pub fn keymap(&self) -> &Keymap {
+        self.bindings.keymap()
+    }
+
+    /// Removes all commands and bindings. As components only add their
+    /// bindings while they are empty, this rebuilds the keymap from scratch,
+    /// e.g. after the user changed their settings:
+    ///
+    /// ```
+    /// # use zi::prelude::*;
+    /// # struct Editor { vim_mode: bool, vim_keymap: std::cell::Cell<bool> }
+    /// # impl Component for Editor {
+    /// #     type Message = ();
+    /// #     type Properties = ();
+    /// #     fn create(_: (), _: Rect, _: ComponentLink<Self>) -> Self { unimplemented!() }
+    /// #     fn view(&self) -> Layout { Layout::empty() }
+    /// fn bindings(&self, bindings: &mut Bindings<Self>) {
+    ///     if self.vim_keymap.replace(self.vim_mode) != self.vim_mode {
+    ///         bindings.clear();
+    ///     }
+    ///     if !bindings.is_empty() {
+    ///         return;
+    ///     }
+    ///     if self.vim_mode {
+    ///         bindings.add("save", [Key::Char(':'), Key::Char('w')], || ());
+    ///     } else {
+    ///         bindings.add("save", [Key::Ctrl('x'), Key::Ctrl('s')], || ());
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// Removes the binding for the given keys, returning `true` if there was
+    /// one. The command and its other bindings are kept.
+    #[inline]
+    pub fn remove(&mut self, keys: impl Into<KeyPattern>) -> bool {
+        self.bindings.keymap.remove(keys).is_some()
+    }
+
+    /// Binds the keys to a command added before, removing the bindings of
+    /// other commands they overlap with, see
+    /// [`Keymap::replace`](struct.Keymap.html#method.replace). Returns
+    /// `false` if there is no command with the given name.
+    #[inline]
+    pub fn replace(&mut self, name: &str, keys: impl Into<KeyPattern>) -> bool {
+        match self.bindings.keymap.command_id(name) {
+            Some(command_id) => {
+                self.bindings.keymap.replace(command_id, keys);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Binds a command added before to `patterns` instead of its current
+    /// bindings, see
+    /// [`Keymap::rebind_command`](struct.Keymap.html#method.rebind_command).
+    /// Returns `false` if there is no command with the given name.
+    #[inline]
+    pub fn rebind(&mut self, name: &str, patterns: impl IntoIterator<Item = KeyPattern>) -> bool {
+        match self.bindings.keymap.command_id(name) {
+            Some(command_id) => {
+                self.bindings.keymap.rebind_command(command_id, patterns);
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -352,6 +1046,25 @@ impl<ComponentT: Component> BindingBuilder<'_, ComponentT> {
         self.wrapped.bindings.bind_command(self.command_id, keys);
         self
     }
+
+    /// Describes what the command does, e.g. for help screens, see
+    /// [`Keymap::commands`](struct.Keymap.html#method.commands).
+    pub fn description(self, description: impl Into<Cow<'static, str>>) -> Self {
+        self.wrapped
+            .bindings
+            .keymap
+            .set_description(self.command_id, description);
+        self
+    }
+
+    /// Sets the group of related commands the command belongs to.
+    pub fn category(self, category: impl Into<Cow<'static, str>>) -> Self {
+        self.wrapped
+            .bindings
+            .keymap
+            .set_category(self.command_id, category);
+        self
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -359,13 +1072,74 @@ pub enum KeyPattern {
     AnyCharacter,
     EndsWith([Key; 1]),
     Keys(SmallVec<[Key; 8]>),
+    /// The same key pressed twice in quick succession.
+    DoublePress(Key),
+    /// A key held down, i.e. repeated by the terminal for a while.
+    Hold(Key),
+    /// A sequence of keys with wildcards, e.g. `C-x <any> C-s`, see
+    /// [`KeyPattern::sequence`](#method.sequence).
+    Sequence(SmallVec<[KeyMatcher; 4]>),
 }
 
 impl KeyPattern {
+    /// A sequence of keys where each key is matched by a
+    /// [`KeyMatcher`](enum.KeyMatcher.html), e.g. `C-x <any> C-s`:
+    ///
+    /// ```
+    /// # use zi::{Key, KeyMatcher, KeyPattern};
+    /// let pattern = KeyPattern::sequence([
+    ///     Key::Ctrl('x').into(),
+    ///     KeyMatcher::Any,
+    ///     Key::Ctrl('s').into(),
+    /// ]);
+    /// assert_eq!(pattern.to_string(), "C-x <any> C-s");
+    /// assert_eq!(pattern, "C-x <any> C-s".parse().unwrap());
+    /// ```
+    ///
+    /// A sequence of exact keys is the same as the pattern of the keys.
+    pub fn sequence(matchers: impl IntoIterator<Item = KeyMatcher>) -> Self {
+        let matchers: SmallVec<[KeyMatcher; 4]> = matchers.into_iter().collect();
+        let keys: Option<SmallVec<[Key; 8]>> = matchers
+            .iter()
+            .map(|matcher| match matcher {
+                KeyMatcher::Key(key) => Some(*key),
+                _ => None,
+            })
+            .collect();
+        match keys {
+            Some(keys) => Self::Keys(keys),
+            None if matchers.as_slice() == [KeyMatcher::AnyCharacter] => Self::AnyCharacter,
+            None => Self::Sequence(matchers),
+        }
+    }
+
+    /// A sequence starting with the leader key, e.g. `<leader> f f`, see
+    /// [`Keymap::set_leader`](struct.Keymap.html#method.set_leader):
+    ///
+    /// ```
+    /// # use zi::{Key, KeyPattern};
+    /// let pattern = KeyPattern::leader([Key::Char('f'), Key::Char('f')]);
+    /// assert_eq!(pattern.to_string(), "<leader> f f");
+    /// assert_eq!(pattern, "<leader> f f".parse().unwrap());
+    /// ```
+    pub fn leader(matchers: impl IntoIterator<Item = impl Into<KeyMatcher>>) -> Self {
+        Self::sequence(
+            std::iter::once(KeyMatcher::Leader).chain(matchers.into_iter().map(Into::into)),
+        )
+    }
+
+    /// Returns `true` if the pattern has a `<leader>`.
+    pub fn has_leader(&self) -> bool {
+        match self {
+            Self::Sequence(matchers) => matchers.contains(&KeyMatcher::Leader),
+            _ => false,
+        }
+    }
+
     fn keys(&self) -> Option<&[Key]> {
         match self {
-            Self::AnyCharacter => None,
-            Self::EndsWith(key) => Some(key.as_slice()),
+            Self::AnyCharacter | Self::DoublePress(_) | Self::Hold(_) | Self::Sequence(_) => None,
+            Self::EndsWith(key) => Some(&key[..]),
             Self::Keys(keys) => Some(keys.as_slice()),
         }
     }
@@ -383,12 +1157,162 @@ impl std::fmt::Display for KeyPattern {
             Self::AnyCharacter => {
                 write!(formatter, "Char(*)")
             }
-            Self::Keys(keys) => KeySequenceSlice(keys.as_slice()).fmt(formatter),
-            Self::EndsWith(keys) => KeySequenceSlice(keys.as_slice()).fmt(formatter),
+            Self::Keys(keys) => KeySequenceSlice::from(keys.as_slice()).fmt(formatter),
+            Self::EndsWith(keys) => KeySequenceSlice::from(&keys[..]).fmt(formatter),
+            Self::DoublePress(key) => {
+                write!(formatter, "double ")?;
+                KeySequenceSlice::from(std::slice::from_ref(key)).fmt(formatter)
+            }
+            Self::Hold(key) => {
+                write!(formatter, "hold ")?;
+                KeySequenceSlice::from(std::slice::from_ref(key)).fmt(formatter)
+            }
+            Self::Sequence(matchers) => {
+                for (index, matcher) in matchers.iter().enumerate() {
+                    if index > 0 {
+                        write!(formatter, " ")?;
+                    }
+                    matcher.fmt(formatter)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<KeyMatcher> for KeyPattern {
+    fn from(matcher: KeyMatcher) -> Self {
+        Self::sequence([matcher])
+    }
+}
+
+impl From<KeyPredicate> for KeyPattern {
+    fn from(predicate: KeyPredicate) -> Self {
+        Self::sequence([KeyMatcher::Predicate(predicate)])
+    }
+}
+
+/// Matches one key of a [`KeyPattern::sequence`](enum.KeyPattern.html#method.sequence).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeyMatcher {
+    /// The given key, e.g. `C-x`.
+    Key(Key),
+    /// Any key, written `<any>`.
+    Any,
+    /// Any character, like [`AnyCharacter`](struct.AnyCharacter.html),
+    /// written `<char>`.
+    AnyCharacter,
+    /// A character in an inclusive range, e.g. `'0'..='9'` for digits,
+    /// written `<0-9>`.
+    Range(char, char),
+    /// A key for which a predicate returns `true`, written `<name>`.
+    Predicate(KeyPredicate),
+    /// The leader key of the keymap, written `<leader>`. It's substituted
+    /// when the pattern is bound, see
+    /// [`Keymap::set_leader`](struct.Keymap.html#method.set_leader), so it
+    /// doesn't match any key by itself.
+    Leader,
+}
+
+impl KeyMatcher {
+    pub fn matches(&self, key: &Key) -> bool {
+        match (self, key) {
+            (Self::Key(expected), key) => expected == key,
+            (Self::Any, _) => true,
+            (Self::AnyCharacter, Key::Char(_) | Key::Grapheme(_)) => true,
+            (Self::Range(start, end), Key::Char(character)) => (start..=end).contains(&character),
+            (Self::Predicate(predicate), key) => (predicate.predicate)(key),
+            _ => false,
         }
     }
 }
 
+impl From<Key> for KeyMatcher {
+    fn from(key: Key) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<RangeInclusive<char>> for KeyMatcher {
+    fn from(range: RangeInclusive<char>) -> Self {
+        Self::Range(*range.start(), *range.end())
+    }
+}
+
+impl From<KeyPredicate> for KeyMatcher {
+    fn from(predicate: KeyPredicate) -> Self {
+        Self::Predicate(predicate)
+    }
+}
+
+impl fmt::Display for KeyMatcher {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Key(key) => KeySequenceSlice::from(std::slice::from_ref(key)).fmt(formatter),
+            Self::Any => write!(formatter, "<any>"),
+            Self::AnyCharacter => write!(formatter, "<char>"),
+            Self::Range(start, end) => write!(formatter, "<{}-{}>", start, end),
+            Self::Predicate(predicate) => write!(formatter, "<{}>", predicate.name),
+            Self::Leader => write!(formatter, "<leader>"),
+        }
+    }
+}
+
+/// A named predicate over keys, see
+/// [`KeyMatcher::Predicate`](enum.KeyMatcher.html#variant.Predicate).
+///
+/// Two predicates are equal if they are clones of each other. The name is
+/// only used to show the pattern, e.g. in a list of bindings.
+///
+/// ```
+/// # use zi::{Key, KeyPattern, KeyPredicate};
+/// let uppercase = KeyPredicate::new("upper", |key| {
+///     matches!(key, Key::Char(character) if character.is_uppercase())
+/// });
+/// assert_eq!(KeyPattern::from(uppercase).to_string(), "<upper>");
+/// ```
+#[derive(Clone)]
+pub struct KeyPredicate {
+    name: Cow<'static, str>,
+    predicate: Arc<dyn Fn(&Key) -> bool + Send + Sync>,
+}
+
+impl KeyPredicate {
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        predicate: impl Fn(&Key) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            predicate: Arc::new(predicate),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Debug for KeyPredicate {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "KeyPredicate({:?})", self.name)
+    }
+}
+
+impl PartialEq for KeyPredicate {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.predicate, &other.predicate)
+    }
+}
+
+impl Eq for KeyPredicate {}
+
+impl Hash for KeyPredicate {
+    fn hash<HasherT: Hasher>(&self, state: &mut HasherT) {
+        self.name.hash(state);
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AnyCharacter;
 
@@ -407,8 +1331,62 @@ impl From<EndsWith> for KeyPattern {
     }
 }
 
+/// Matches a key pressed twice in quick succession, e.g. `DoublePress(Key::Esc)`.
+///
+/// The first press is delivered as usual, the pattern matches on the second
+/// one. Key sequences in progress take precedence, i.e. a double `C-x` is not
+/// matched while `C-x` is the prefix of a pending sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DoublePress(pub Key);
+
+impl From<DoublePress> for KeyPattern {
+    fn from(double_press: DoublePress) -> Self {
+        Self::DoublePress(double_press.0)
+    }
+}
+
+/// Matches a key held down, i.e. once the key has been repeated for a while.
+/// Repeats are reported as such by some backends, otherwise they are told
+/// apart from separate presses by their rate, see
+/// [`KeyTiming`](app/struct.KeyTiming.html). The pattern matches once per
+/// hold, the repeats before and after are delivered as usual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hold(pub Key);
+
+impl From<Hold> for KeyPattern {
+    fn from(hold: Hold) -> Self {
+        Self::Hold(hold.0)
+    }
+}
+
+/// How the runtime classified a key press, based on the presses before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyGesture {
+    Press,
+    DoublePress,
+    Hold,
+}
+
 pub trait CommandFn<ComponentT: Component, const VARIANT: usize> {
+    /// Whether the command returns a message, `false` for commands run only
+    /// for their side effects.
+    const RETURNS_MESSAGE: bool = true;
+
     fn call(&self, component: &ComponentT, keys: &[Key]) -> Option<ComponentT::Message>;
+
+    /// Like `call`, with the position of the mouse if the key sequence
+    /// contains a mouse key, e.g. a [`Key::Click`](../../terminal/enum.Key.html#variant.Click),
+    /// and the time the last key was pressed.
+    #[inline]
+    fn call_at(
+        &self,
+        component: &ComponentT,
+        keys: &[Key],
+        _position: Option<Position>,
+        _timestamp: Instant,
+    ) -> Option<ComponentT::Message> {
+        self.call(component, keys)
+    }
 }
 
 // Specializations for callbacks that take either a component or slice with keys
@@ -451,6 +1429,8 @@ where
     ComponentT: Component,
     FnT: Fn(&ComponentT, &[Key]) + 'static,
 {
+    const RETURNS_MESSAGE: bool = false;
+
     #[inline]
     fn call(&self, component: &ComponentT, keys: &[Key]) -> Option<ComponentT::Message> {
         (self)(component, keys);
@@ -463,6 +1443,8 @@ where
     ComponentT: Component,
     FnT: Fn(&ComponentT) + 'static,
 {
+    const RETURNS_MESSAGE: bool = false;
+
     #[inline]
     fn call(&self, component: &ComponentT, _keys: &[Key]) -> Option<ComponentT::Message> {
         (self)(component);
@@ -482,19 +1464,124 @@ where
     }
 }
 
+// Specializations for callbacks that take the position of a click. They are
+// not called when there is no click, e.g. when executing a command by name
+impl<ComponentT, FnT> CommandFn<ComponentT, 6> for FnT
+where
+    ComponentT: Component,
+    FnT: Fn(&ComponentT, Position) -> Option<ComponentT::Message> + 'static,
+{
+    #[inline]
+    fn call(&self, _component: &ComponentT, _keys: &[Key]) -> Option<ComponentT::Message> {
+        None
+    }
+
+    #[inline]
+    fn call_at(
+        &self,
+        component: &ComponentT,
+        _keys: &[Key],
+        position: Option<Position>,
+        _timestamp: Instant,
+    ) -> Option<ComponentT::Message> {
+        (self)(component, position?)
+    }
+}
+
+impl<ComponentT, FnT> CommandFn<ComponentT, 7> for FnT
+where
+    ComponentT: Component,
+    FnT: Fn(Position) -> ComponentT::Message + 'static,
+{
+    #[inline]
+    fn call(&self, _component: &ComponentT, _keys: &[Key]) -> Option<ComponentT::Message> {
+        None
+    }
+
+    #[inline]
+    fn call_at(
+        &self,
+        _component: &ComponentT,
+        _keys: &[Key],
+        position: Option<Position>,
+        _timestamp: Instant,
+    ) -> Option<ComponentT::Message> {
+        Some((self)(position?))
+    }
+}
+
+// Specialization for callbacks that take the time the key was pressed, e.g.
+// for debouncing. When executing a command by name, it is the current time
+impl<ComponentT, FnT> CommandFn<ComponentT, 8> for FnT
+where
+    ComponentT: Component,
+    FnT: Fn(&ComponentT, Instant) -> Option<ComponentT::Message> + 'static,
+{
+    #[inline]
+    fn call(&self, component: &ComponentT, _keys: &[Key]) -> Option<ComponentT::Message> {
+        (self)(component, Instant::now())
+    }
+
+    #[inline]
+    fn call_at(
+        &self,
+        component: &ComponentT,
+        _keys: &[Key],
+        _position: Option<Position>,
+        timestamp: Instant,
+    ) -> Option<ComponentT::Message> {
+        (self)(component, timestamp)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct KeySequenceSlice<'a>(&'a [Key]);
+pub struct KeySequenceSlice<'a> {
+    keys: &'a [Key],
+    leader: Option<Key>,
+}
+
+impl<'a> KeySequenceSlice<'a> {
+    /// Shows the leader key as `<leader>` when it starts the sequence, e.g.
+    /// `<leader> f` instead of `SPC f`, see
+    /// [`Keymap::set_leader`](struct.Keymap.html#method.set_leader).
+    ///
+    /// ```
+    /// # use zi::{Key, KeySequenceSlice};
+    /// let keys = [Key::Char(' '), Key::Char('f')];
+    /// let slice = KeySequenceSlice::from(&keys[..]);
+    /// assert_eq!(slice.to_string(), "SPC f");
+    /// assert_eq!(slice.leader(Some(Key::Char(' '))).to_string(), "<leader> f");
+    /// ```
+    pub fn leader(self, leader: Option<Key>) -> Self {
+        Self { leader, ..self }
+    }
+}
 
 impl<'a> From<&'a [Key]> for KeySequenceSlice<'a> {
     fn from(keys: &'a [Key]) -> Self {
-        Self(keys)
+        Self { keys, leader: None }
     }
 }
 
+/// Writes the modifiers held during a mouse event as prefixes, e.g. `C-`.
+fn write_modifiers(formatter: &mut std::fmt::Formatter, modifiers: &Modifiers) -> std::fmt::Result {
+    for (held, prefix) in [
+        (modifiers.ctrl, "C-"),
+        (modifiers.alt, "A-"),
+        (modifiers.shift, "S-"),
+    ] {
+        if held {
+            write!(formatter, "{}", prefix)?;
+        }
+    }
+    Ok(())
+}
+
 impl<'a> std::fmt::Display for KeySequenceSlice<'a> {
     fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        for (index, key) in self.0.iter().enumerate() {
+        for (index, key) in self.keys.iter().enumerate() {
             match key {
+                key if index == 0 && self.leader == Some(*key) => write!(formatter, "<leader>")?,
                 Key::Char(' ') => write!(formatter, "SPC")?,
                 Key::Char('\n') => write!(formatter, "RET")?,
                 Key::Char('\t') => write!(formatter, "TAB")?,
@@ -503,9 +1590,33 @@ impl<'a> std::fmt::Display for KeySequenceSlice<'a> {
                 Key::Alt(char) => write!(formatter, "A-{}", char)?,
                 Key::F(number) => write!(formatter, "F{}", number)?,
                 Key::Esc => write!(formatter, "ESC")?,
+                Key::Grapheme(grapheme) => write!(formatter, "{}", grapheme)?,
+                Key::Click(button, modifiers)
+                | Key::Release(button, modifiers)
+                | Key::Drag(button, modifiers) => {
+                    write_modifiers(formatter, modifiers)?;
+                    match button {
+                        MouseButton::Left => {}
+                        MouseButton::Right => write!(formatter, "Right")?,
+                        MouseButton::Middle => write!(formatter, "Middle")?,
+                    }
+                    match key {
+                        Key::Click(..) => write!(formatter, "Click")?,
+                        Key::Release(..) => write!(formatter, "Release")?,
+                        _ => write!(formatter, "Drag")?,
+                    }
+                }
+                Key::ScrollUp(modifiers) => {
+                    write_modifiers(formatter, modifiers)?;
+                    write!(formatter, "ScrollUp")?
+                }
+                Key::ScrollDown(modifiers) => {
+                    write_modifiers(formatter, modifiers)?;
+                    write!(formatter, "ScrollDown")?
+                }
                 key => write!(formatter, "{:?}", key)?,
             }
-            if index < self.0.len().saturating_sub(1) {
+            if index < self.keys.len().saturating_sub(1) {
                 write!(formatter, " ")?;
             }
         }
@@ -525,10 +1636,19 @@ fn panic_on_overlapping_key_bindings(
     );
 }
 
+/// Returns `true` if the patterns can't both be bound, i.e. they are equal or
+/// the keys of one are a prefix of the keys of the other.
+fn patterns_overlap(left: &KeyPattern, right: &KeyPattern) -> bool {
+    match (left.keys(), right.keys()) {
+        (Some(left), Some(right)) => left.starts_with(right) || right.starts_with(left),
+        _ => left == right,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::prelude::*;
+    use crate::{prelude::*, terminal::Grapheme};
     use smallvec::smallvec;
     use std::{cell::RefCell, rc::Rc};
 
@@ -555,6 +1675,311 @@ mod tests {
         assert_ne!(left_id, right_id);
         let alternate_left_id = keymap.add("left", [Key::Ctrl('b')]);
         assert_eq!(left_id, alternate_left_id);
+        assert_eq!(keymap.command_id("left"), Some(left_id));
+        assert_eq!(keymap.command_id("right"), Some(right_id));
+        assert_eq!(keymap.command_id("up"), None);
+    }
+
+    #[test]
+    fn keymap_continuations_group_by_next_key() {
+        let mut keymap = Keymap::new();
+        let save = keymap.add("save", [Key::Ctrl('x'), Key::Ctrl('s')]);
+        let open = keymap.add("open", [Key::Ctrl('x'), Key::Ctrl('f')]);
+        let split = keymap.add("split", [Key::Ctrl('x'), Key::Char('2')]);
+        let close = keymap.add("close", [Key::Ctrl('x'), Key::Char('4'), Key::Char('0')]);
+        let quit = keymap.add("quit", [Key::Char('q')]);
+
+        assert_eq!(
+            keymap.continuations(&[Key::Ctrl('x')]),
+            vec![
+                (Key::Char('2'), &BindingQuery::Match(split)),
+                (Key::Char('4'), &BindingQuery::PrefixOf(smallvec![close])),
+                (Key::Ctrl('f'), &BindingQuery::Match(open)),
+                (Key::Ctrl('s'), &BindingQuery::Match(save)),
+            ]
+        );
+        assert_eq!(
+            keymap.continuations(&[]),
+            vec![
+                (Key::Char('q'), &BindingQuery::Match(quit)),
+                (
+                    Key::Ctrl('x'),
+                    &BindingQuery::PrefixOf(smallvec![save, open, split, close])
+                ),
+            ]
+        );
+        assert!(keymap.continuations(&[Key::Char('q')]).is_empty());
+
+        let mut bindings: Vec<_> = keymap
+            .bindings()
+            .map(|(pattern, command_id)| (pattern.to_string(), keymap.name(&command_id)))
+            .collect();
+        bindings.sort();
+        assert_eq!(
+            bindings,
+            vec![
+                ("C-x 2".to_string(), "split"),
+                ("C-x 4 0".to_string(), "close"),
+                ("C-x C-f".to_string(), "open"),
+                ("C-x C-s".to_string(), "save"),
+                ("q".to_string(), "quit"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keymap_substitutes_the_leader_key() {
+        let mut keymap = Keymap::new();
+        let find = keymap.add("find", KeyPattern::leader([Key::Char('f')]));
+        let buffers = keymap.add("buffers", KeyPattern::leader([Key::Char('b')]));
+        let down = keymap.add("down", [Key::Char('j')]);
+        assert!(!keymap.is_empty());
+        assert_eq!(keymap.check_sequence(&[Key::Char(' ')]), None);
+
+        keymap.set_leader(Some(Key::Char(' ')));
+        assert_eq!(
+            keymap.check_sequence(&[Key::Char(' ')]).as_deref(),
+            Some(&BindingQuery::PrefixOf(smallvec![find, buffers]))
+        );
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Char(' '), Key::Char('f')])
+                .as_deref(),
+            Some(&BindingQuery::Match(find))
+        );
+
+        // Changing the leader rebinds the sequences, skipping the ones
+        // overlapping with other bindings
+        keymap.set_leader(Some(Key::Char('j')));
+        assert_eq!(keymap.check_sequence(&[Key::Char(' ')]), None);
+        assert_eq!(
+            keymap.check_sequence(&[Key::Char('j')]).as_deref(),
+            Some(&BindingQuery::Match(down))
+        );
+        keymap.set_leader(Some(Key::Char(',')));
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Char(','), Key::Char('b')])
+                .as_deref(),
+            Some(&BindingQuery::Match(buffers))
+        );
+
+        // Bindings with a leader are removed by their pattern
+        assert_eq!(
+            keymap.remove(KeyPattern::leader([Key::Char('f')])),
+            Some(find)
+        );
+        assert_eq!(
+            keymap.check_sequence(&[Key::Char(',')]).as_deref(),
+            Some(&BindingQuery::PrefixOf(smallvec![buffers]))
+        );
+        keymap.unbind_command(buffers);
+        assert_eq!(keymap.check_sequence(&[Key::Char(',')]), None);
+        keymap.set_leader(None);
+        assert_eq!(
+            keymap
+                .commands()
+                .map(|command| command.patterns.len())
+                .sum::<usize>(),
+            1
+        );
+    }
+
+    #[test]
+    fn keymap_remove_and_replace_bindings() {
+        let mut keymap = Keymap::new();
+        let save = keymap.add("save", [Key::Ctrl('x'), Key::Ctrl('s')]);
+        keymap.bind_command(save, [Key::Ctrl('x'), Key::Char('s')]);
+        let open = keymap.add("open", [Key::Ctrl('x'), Key::Ctrl('f')]);
+        let quit = keymap.add("quit", [Key::Char('q')]);
+
+        // Removing one of two sequences with a common prefix keeps the other
+        assert_eq!(keymap.remove([Key::Ctrl('x'), Key::Char('s')]), Some(save));
+        assert_eq!(keymap.remove([Key::Ctrl('x'), Key::Char('s')]), None);
+        assert_eq!(keymap.remove([Key::Ctrl('x')]), None);
+        assert_eq!(
+            keymap.check_sequence(&[Key::Ctrl('x')]).as_deref(),
+            Some(&BindingQuery::PrefixOf(smallvec![save, open]))
+        );
+
+        // Binding a prefix replaces the sequences it's a prefix of
+        assert_eq!(
+            keymap.replace(quit, [Key::Ctrl('x')]).as_slice(),
+            &[open, save]
+        );
+        assert_eq!(
+            keymap.check_sequence(&[Key::Ctrl('x')]).as_deref(),
+            Some(&BindingQuery::Match(quit))
+        );
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Ctrl('x'), Key::Ctrl('f')])
+                .as_deref(),
+            None
+        );
+
+        // Binding a sequence replaces the bindings of its prefixes
+        assert_eq!(
+            keymap
+                .replace(save, [Key::Ctrl('x'), Key::Ctrl('s')])
+                .as_slice(),
+            &[quit]
+        );
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Ctrl('x'), Key::Ctrl('s')])
+                .as_deref(),
+            Some(&BindingQuery::Match(save))
+        );
+
+        keymap.unbind_command(save);
+        assert_eq!(keymap.check_sequence(&[Key::Ctrl('x')]).as_deref(), None);
+        assert_eq!(
+            keymap.check_sequence(&[Key::Char('q')]).as_deref(),
+            Some(&BindingQuery::Match(quit))
+        );
+        assert_eq!(keymap.command_id("save"), Some(save));
+    }
+
+    #[test]
+    fn keymap_matches_wildcards_ranges_and_predicates() {
+        let mut keymap = Keymap::new();
+        let save = keymap.add("save", [Key::Ctrl('x'), Key::Ctrl('s')]);
+        let save_as = keymap.add(
+            "save-as",
+            KeyPattern::sequence([
+                Key::Ctrl('x').into(),
+                KeyMatcher::Any,
+                Key::Ctrl('s').into(),
+            ]),
+        );
+        let count = keymap.add("count", KeyMatcher::from('0'..='9'));
+        let upper = keymap.add(
+            "upper",
+            KeyPredicate::new(
+                "upper",
+                |key| matches!(key, Key::Char(character) if character.is_uppercase()),
+            ),
+        );
+        let insert = keymap.add("insert", AnyCharacter);
+
+        // Exact bindings and wildcards are both prefixes of the sequence
+        assert_eq!(
+            keymap.check_sequence(&[Key::Ctrl('x')]).as_deref(),
+            Some(&BindingQuery::PrefixOf(smallvec![save, save_as]))
+        );
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Ctrl('x'), Key::Char('a')])
+                .as_deref(),
+            Some(&BindingQuery::PrefixOf(smallvec![save_as]))
+        );
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Ctrl('x'), Key::Up, Key::Ctrl('s')])
+                .as_deref(),
+            Some(&BindingQuery::Match(save_as))
+        );
+        // Exact bindings take precedence
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Ctrl('x'), Key::Ctrl('s')])
+                .as_deref(),
+            Some(&BindingQuery::Match(save))
+        );
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Ctrl('x'), Key::Up, Key::Ctrl('f')])
+                .as_deref(),
+            None
+        );
+
+        // Ranges and predicates take precedence over any character
+        assert_eq!(
+            keymap.check_sequence(&[Key::Char('7')]).as_deref(),
+            Some(&BindingQuery::Match(count))
+        );
+        assert_eq!(
+            keymap.check_sequence(&[Key::Char('Q')]).as_deref(),
+            Some(&BindingQuery::Match(upper))
+        );
+        assert_eq!(
+            keymap.check_sequence(&[Key::Char('q')]).as_deref(),
+            Some(&BindingQuery::Match(insert))
+        );
+
+        assert_eq!(keymap.remove(KeyMatcher::from('0'..='9')), Some(count));
+        assert_eq!(
+            keymap.check_sequence(&[Key::Char('7')]).as_deref(),
+            Some(&BindingQuery::Match(insert))
+        );
+
+        // Wildcards are written in angle brackets
+        for (text, pattern) in [
+            (
+                "C-x <any> C-s",
+                KeyPattern::sequence([
+                    Key::Ctrl('x').into(),
+                    KeyMatcher::Any,
+                    Key::Ctrl('s').into(),
+                ]),
+            ),
+            (
+                "<0-9> <char>",
+                KeyPattern::sequence([('0'..='9').into(), KeyMatcher::AnyCharacter]),
+            ),
+            ("C-x <", [Key::Ctrl('x'), Key::Char('<')].into()),
+        ] {
+            assert_eq!(text.parse::<KeyPattern>(), Ok(pattern.clone()));
+            assert_eq!(pattern.to_string(), text);
+        }
+        assert_eq!("<char>".parse(), Ok(KeyPattern::AnyCharacter));
+        assert_eq!(
+            KeyPattern::sequence([Key::Ctrl('x').into(), Key::Char('s').into()]),
+            [Key::Ctrl('x'), Key::Char('s')].into()
+        );
+        assert!("<9-0>".parse::<KeyPattern>().is_err());
+        assert!("<upper>".parse::<KeyPattern>().is_err());
+    }
+
+    #[test]
+    fn commands_are_listed_with_their_metadata() {
+        let mut bindings = DynamicBindings::new::<Empty>();
+        bindings.typed(|bindings: &mut Bindings<Empty>| {
+            bindings
+                .command("save", || ())
+                .with([Key::Ctrl('x'), Key::Ctrl('s')])
+                .with([Key::F(2)])
+                .description("Save the current file")
+                .category("Files");
+            bindings.add("quit", [Key::Char('q')], || ());
+        });
+
+        let commands: Vec<_> = bindings.keymap().commands().collect();
+        let metadata = CommandMetadata {
+            description: Some("Save the current file".into()),
+            category: Some("Files".into()),
+        };
+        assert_eq!(
+            commands,
+            [
+                CommandInfo {
+                    id: bindings.keymap().command_id("save").unwrap(),
+                    name: "save",
+                    metadata: &metadata,
+                    patterns: vec![
+                        &[Key::Ctrl('x'), Key::Ctrl('s')].into(),
+                        &[Key::F(2)].into()
+                    ],
+                },
+                CommandInfo {
+                    id: bindings.keymap().command_id("quit").unwrap(),
+                    name: "quit",
+                    metadata: &CommandMetadata::default(),
+                    patterns: vec![&[Key::Char('q')].into()],
+                },
+            ]
+        );
     }
 
     #[test]
@@ -573,33 +1998,188 @@ mod tests {
 
         // Check no key sequence is a prefix of test-command
         assert_eq!(
-            controller.keymap().check_sequence(&[]),
+            controller.keymap().check_sequence(&[]).as_deref(),
             Some(&BindingQuery::PrefixOf(smallvec![test_command_id]))
         );
         // Check C-x is a prefix of test-command
         assert_eq!(
-            controller.keymap().check_sequence(&[Key::Ctrl('x')]),
+            controller
+                .keymap()
+                .check_sequence(&[Key::Ctrl('x')])
+                .as_deref(),
             Some(&BindingQuery::PrefixOf(smallvec![test_command_id]))
         );
         // Check C-x C-f is a match for test-command
         assert_eq!(
             controller
                 .keymap()
-                .check_sequence(&[Key::Ctrl('x'), Key::Ctrl('f')]),
+                .check_sequence(&[Key::Ctrl('x'), Key::Ctrl('f')])
+                .as_deref(),
             Some(&BindingQuery::Match(test_command_id))
         );
 
         // Check C-f doesn't match any command
-        assert_eq!(controller.keymap().check_sequence(&[Key::Ctrl('f')]), None);
+        assert_eq!(
+            controller
+                .keymap()
+                .check_sequence(&[Key::Ctrl('f')])
+                .as_deref(),
+            None
+        );
         // Check C-x C-x doesn't match any command
         assert_eq!(
             controller
                 .keymap()
-                .check_sequence(&[Key::Ctrl('x'), Key::Ctrl('x')]),
+                .check_sequence(&[Key::Ctrl('x'), Key::Ctrl('x')])
+                .as_deref(),
             None
         );
 
-        controller.execute_command(&Empty, test_command_id, &[]);
+        controller.execute_command(&Empty, test_command_id, &[], None, Instant::now());
         assert!(*called.borrow(), "set-controller wasn't called");
     }
+
+    #[test]
+    fn keymap_gestures_fall_back_to_sequences() {
+        let mut keymap = Keymap::new();
+        let close_id = keymap.add("close", [Key::Esc]);
+        let quit_id = keymap.add("force-quit", DoublePress(Key::Esc));
+        let scroll_id = keymap.add("fast-scroll", Hold(Key::Down));
+
+        assert_eq!(
+            keymap
+                .check_gesture(&[Key::Esc], KeyGesture::Press)
+                .as_deref(),
+            Some(&BindingQuery::Match(close_id))
+        );
+        assert_eq!(
+            keymap
+                .check_gesture(&[Key::Esc], KeyGesture::DoublePress)
+                .as_deref(),
+            Some(&BindingQuery::Match(quit_id))
+        );
+        assert_eq!(
+            keymap
+                .check_gesture(&[Key::Down], KeyGesture::Hold)
+                .as_deref(),
+            Some(&BindingQuery::Match(scroll_id))
+        );
+        // A held key without a hold binding is a regular press
+        assert_eq!(
+            keymap
+                .check_gesture(&[Key::Esc], KeyGesture::Hold)
+                .as_deref(),
+            Some(&BindingQuery::Match(close_id))
+        );
+        assert_eq!(
+            keymap
+                .check_gesture(&[Key::Down], KeyGesture::Press)
+                .as_deref(),
+            None
+        );
+    }
+
+    #[test]
+    fn keys_and_patterns_round_trip_through_text() {
+        let keys = [
+            Key::Char('a'),
+            Key::Char(' '),
+            Key::Char('\n'),
+            Key::Ctrl('x'),
+            Key::Ctrl('-'),
+            Key::Alt('f'),
+            Key::F(12),
+            Key::Esc,
+            Key::PageDown,
+            Key::Grapheme(Grapheme::new("\u{1f44d}\u{1f3fd}").unwrap()),
+            Key::Click(MouseButton::Left, Modifiers::NONE),
+            Key::Drag(MouseButton::Right, Modifiers::CTRL | Modifiers::SHIFT),
+            Key::ScrollUp(Modifiers::ALT),
+        ];
+        for key in keys {
+            assert_eq!(key.to_string().parse::<Key>(), Ok(key));
+        }
+        assert_eq!(Key::Ctrl('x').to_string(), "C-x");
+        assert_eq!(
+            "C-RightRelease".parse(),
+            Ok(Key::Release(MouseButton::Right, Modifiers::CTRL))
+        );
+        assert!("F13".parse::<Key>().is_err());
+        assert!("C-xy".parse::<Key>().is_err());
+
+        let patterns: [KeyPattern; 4] = [
+            [Key::Ctrl('x'), Key::Char(' '), Key::Ctrl('s')].into(),
+            AnyCharacter.into(),
+            DoublePress(Key::Esc).into(),
+            Hold(Key::Char('j')).into(),
+        ];
+        for pattern in patterns {
+            assert_eq!(pattern.to_string().parse(), Ok(pattern));
+        }
+        assert_eq!(
+            "C-x  SPC C-s".parse::<KeyPattern>().unwrap().to_string(),
+            "C-x SPC C-s"
+        );
+        assert_eq!(
+            "C-x Ctrl-q".parse::<KeyPattern>().unwrap_err().to_string(),
+            "invalid key `Ctrl-q`"
+        );
+        assert!("".parse::<KeyPattern>().is_err());
+    }
+
+    #[test]
+    fn rebind_commands_without_overlaps() {
+        let mut keymap = Keymap::new();
+        let save = keymap.add("save", [Key::Ctrl('x'), Key::Ctrl('s')]);
+        let quit = keymap.add("quit", [Key::Ctrl('x'), Key::Ctrl('c')]);
+
+        // Overlapping patterns are skipped
+        keymap.rebind_command(
+            save,
+            [
+                [Key::Ctrl('s')].into(),
+                [Key::Ctrl('x')].into(),
+                [Key::Ctrl('x'), Key::Ctrl('c'), Key::Char('s')].into(),
+            ],
+        );
+        assert_eq!(
+            keymap.check_sequence(&[Key::Ctrl('s')]).as_deref(),
+            Some(&BindingQuery::Match(save))
+        );
+        assert_eq!(
+            keymap
+                .check_sequence(&[Key::Ctrl('x'), Key::Ctrl('s')])
+                .as_deref(),
+            None
+        );
+        assert_eq!(
+            keymap.check_sequence(&[Key::Ctrl('x')]).as_deref(),
+            Some(&BindingQuery::PrefixOf(smallvec![quit]))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_keymap_overrides() {
+        let overrides: crate::KeymapOverrides =
+            serde_json::from_str(r#"{"Pager": {"line-down": ["j", "C-n"], "search": "C-s"}}"#)
+                .unwrap();
+        let mut expected = crate::KeymapOverrides::new();
+        expected
+            .bind(
+                "Pager",
+                "line-down",
+                [[Key::Char('j')].into(), [Key::Ctrl('n')].into()],
+            )
+            .bind("Pager", "search", [[Key::Ctrl('s')].into()]);
+        assert_eq!(overrides, expected);
+
+        let json = serde_json::to_string(&overrides).unwrap();
+        assert_eq!(
+            json,
+            r#"{"Pager":{"line-down":["j","C-n"],"search":["C-s"]}}"#
+        );
+        assert_eq!(serde_json::to_string(&Key::Alt('x')).unwrap(), r#""A-x""#);
+        assert!(serde_json::from_str::<KeyPattern>(r#""C-x C-""#).is_err());
+    }
 }
@@ -0,0 +1,105 @@
+//! The message bus of the app, see
+//! [`ComponentLink::subscribe`](../struct.ComponentLink.html#method.subscribe)
+//! and [`ComponentLink::publish`](../struct.ComponentLink.html#method.publish).
+use std::{
+    any::{self, Any, TypeId},
+    collections::HashMap,
+};
+
+use super::{
+    template::{ComponentId, DynamicMessage},
+    Component,
+};
+
+/// Turns a published value into a message for a subscriber.
+type MessageFn = Box<dyn Fn(&(dyn Any + Send)) -> DynamicMessage + Send>;
+
+/// A request to deliver the values published on a topic to a component.
+pub(crate) struct Subscription {
+    pub(crate) topic: TypeId,
+    pub(crate) component_id: ComponentId,
+    message: MessageFn,
+}
+
+impl Subscription {
+    pub(crate) fn new<ComponentT: Component, TopicT: Clone + Send + 'static>(
+        component_id: ComponentId,
+        message: impl Fn(TopicT) -> ComponentT::Message + Send + 'static,
+    ) -> Self {
+        Self {
+            topic: TypeId::of::<TopicT>(),
+            component_id,
+            message: Box::new(move |value| {
+                DynamicMessage(Box::new(message(
+                    value
+                        .downcast_ref::<TopicT>()
+                        .expect("Incorrect topic type when downcasting")
+                        .clone(),
+                )))
+            }),
+        }
+    }
+}
+
+/// A value published on the topic of its type.
+pub(crate) struct Publication {
+    pub(crate) topic: TypeId,
+    pub(crate) topic_name: &'static str,
+    pub(crate) value: Box<dyn Any + Send>,
+}
+
+impl Publication {
+    pub(crate) fn new<TopicT: Clone + Send + 'static>(value: TopicT) -> Self {
+        Self {
+            topic: TypeId::of::<TopicT>(),
+            topic_name: any::type_name::<TopicT>(),
+            value: Box::new(value),
+        }
+    }
+}
+
+/// The subscriptions of the mounted components, by topic. A component has at
+/// most one subscription per topic.
+#[derive(Default)]
+pub(crate) struct MessageBus {
+    topics: HashMap<TypeId, Vec<Subscription>>,
+}
+
+impl MessageBus {
+    pub(crate) fn subscribe(&mut self, subscription: Subscription) {
+        let subscriptions = self.topics.entry(subscription.topic).or_default();
+        subscriptions.retain(|existing| existing.component_id != subscription.component_id);
+        subscriptions.push(subscription);
+    }
+
+    pub(crate) fn unsubscribe(&mut self, topic: TypeId, component_id: ComponentId) {
+        if let Some(subscriptions) = self.topics.get_mut(&topic) {
+            subscriptions.retain(|existing| existing.component_id != component_id);
+        }
+    }
+
+    /// Removes the subscriptions of a component which is no longer mounted.
+    pub(crate) fn remove_component(&mut self, component_id: ComponentId) {
+        for subscriptions in self.topics.values_mut() {
+            subscriptions.retain(|existing| existing.component_id != component_id);
+        }
+    }
+
+    /// Returns the messages to deliver to the subscribers of a publication,
+    /// in the order they subscribed.
+    pub(crate) fn messages<'a>(
+        &'a self,
+        publication: &'a Publication,
+    ) -> impl Iterator<Item = (ComponentId, DynamicMessage)> + 'a {
+        self.topics
+            .get(&publication.topic)
+            .into_iter()
+            .flatten()
+            .map(move |subscription| {
+                (
+                    subscription.component_id,
+                    (subscription.message)(&*publication.value),
+                )
+            })
+    }
+}
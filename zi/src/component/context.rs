@@ -0,0 +1,97 @@
+//! Values provided by a layout to the components inside it, see
+//! [`Layout::provide`](../struct.Layout.html#method.provide).
+use std::{
+    any::{Any, TypeId},
+    sync::{Arc, RwLock},
+};
+
+/// The values provided to a component by the layouts enclosing it, innermost
+/// first. Cloning is cheap as the values of the enclosing scopes are shared.
+#[derive(Clone, Default)]
+pub(crate) struct Context(Option<Arc<ContextScope>>);
+
+struct ContextScope {
+    type_id: TypeId,
+    value: Arc<dyn Any + Send + Sync>,
+    parent: Context,
+}
+
+impl Context {
+    /// Returns a context nested inside this one, which provides `value`.
+    pub(crate) fn provide(&self, type_id: TypeId, value: Arc<dyn Any + Send + Sync>) -> Self {
+        Self(Some(Arc::new(ContextScope {
+            type_id,
+            value,
+            parent: self.clone(),
+        })))
+    }
+
+    /// Returns the innermost value of type `ValueT`, if any.
+    pub(crate) fn get<ValueT: Send + Sync + 'static>(&self) -> Option<Arc<ValueT>> {
+        let mut context = self;
+        while let Some(ref scope) = context.0 {
+            if scope.type_id == TypeId::of::<ValueT>() {
+                return scope.value.clone().downcast().ok();
+            }
+            context = &scope.parent;
+        }
+        None
+    }
+
+    /// Whether both contexts provide the same values. Values are compared by
+    /// identity, a layout providing a new value on every render changes the
+    /// context every time.
+    fn provides_same(&self, other: &Self) -> bool {
+        let (mut left, mut right) = (self, other);
+        loop {
+            match (&left.0, &right.0) {
+                (None, None) => return true,
+                (Some(left_scope), Some(right_scope)) => {
+                    if Arc::ptr_eq(left_scope, right_scope) {
+                        return true;
+                    }
+                    if left_scope.type_id != right_scope.type_id
+                        || !Arc::ptr_eq(&left_scope.value, &right_scope.value)
+                    {
+                        return false;
+                    }
+                    left = &left_scope.parent;
+                    right = &right_scope.parent;
+                }
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// The context of a mounted component, shared by the app and its link.
+pub(crate) struct ComponentContext {
+    context: Context,
+    /// Whether the component read any value from its context. Only those
+    /// components are re-rendered when the context changes.
+    used: bool,
+}
+
+impl ComponentContext {
+    pub(crate) fn new(context: Context) -> Self {
+        Self {
+            context,
+            used: false,
+        }
+    }
+
+    pub(crate) fn get<ValueT: Send + Sync + 'static>(&mut self) -> Option<Arc<ValueT>> {
+        self.used = true;
+        self.context.get()
+    }
+
+    /// Replaces the context, returning whether the component has to be
+    /// re-rendered as it used a context which changed.
+    pub(crate) fn set(&mut self, context: Context) -> bool {
+        let changed = self.used && !self.context.provides_same(&context);
+        self.context = context;
+        changed
+    }
+}
+
+pub(crate) type SharedContext = Arc<RwLock<ComponentContext>>;
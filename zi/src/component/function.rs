@@ -0,0 +1,271 @@
+//! Function components, components defined by a single `view` function which
+//! keeps its state in hooks.
+//!
+//! Small presentational components often only need properties and a bit of
+//! local state. Instead of a struct, a message type and a `Component` impl,
+//! a function component implements [`FunctionComponent::view`], which calls
+//! hooks on [`Hooks`](struct.Hooks.html) for anything it needs to remember
+//! between renders:
+//!
+//!   - [`use_state`](struct.Hooks.html#method.use_state) for state which
+//!     re-renders the component when it is set.
+//!   - [`use_callback`](struct.Hooks.html#method.use_callback) for callbacks
+//!     which stay the same (and compare equal) as long as their dependencies
+//!     don't change, s.t. children taking them in their properties aren't
+//!     re-rendered needlessly.
+//!   - [`use_effect`](struct.Hooks.html#method.use_effect) for side effects
+//!     which run after a render whenever their dependencies change.
+//!
+//! Hooks are identified by the order they are called in, so they must be
+//! called in the same order on every render, i.e. not conditionally or in
+//! loops with a varying number of iterations.
+//!
+//! ```
+//! # use zi::{components::text::{Text, TextProperties}, prelude::*, FunctionComponent, Hooks};
+//! struct Greeting;
+//!
+//! impl FunctionComponent for Greeting {
+//!     type Properties = String;
+//!
+//!     fn view(name: &String, hooks: &mut Hooks) -> Layout {
+//!         let visits = hooks.use_state(|| 0usize);
+//!         let name = name.clone();
+//!         hooks.use_effect(name.clone(), {
+//!             let visits = visits.clone();
+//!             move || visits.update(|visits| *visits += 1)
+//!         });
+//!         Text::with(TextProperties::new().content(format!("Hello {} ({})", name, visits.get())))
+//!     }
+//! }
+//!
+//! let layout = Layout::row([Item::auto(Greeting::with("Zi".into()))]);
+//! ```
+use std::{
+    any::{self, Any},
+    cell::{Ref, RefCell},
+    fmt,
+    marker::PhantomData,
+    rc::Rc,
+};
+
+use super::{
+    layout::{ComponentExt, ComponentKey},
+    Callback, Component, ComponentLink, Layout, ShouldRender,
+};
+use crate::terminal::Rect;
+
+/// A component defined by a function of its properties, see the
+/// [module documentation](index.html).
+pub trait FunctionComponent: Sized + 'static {
+    /// The inputs to the component passed in by its parent.
+    type Properties: 'static;
+
+    /// Returns the layout of the component. It's called whenever the
+    /// properties change or a state hook is set.
+    fn view(properties: &Self::Properties, hooks: &mut Hooks) -> Layout;
+
+    /// Creates a definition of the function component from its properties,
+    /// like [`ComponentExt::with`](../layout/trait.ComponentExt.html#method.with).
+    fn with(properties: Self::Properties) -> Layout {
+        Function::<Self>::with(properties)
+    }
+
+    /// Creates a definition of the function component from its properties,
+    /// using a custom identity specified by a key, like
+    /// [`ComponentExt::with_key`](../layout/trait.ComponentExt.html#method.with_key).
+    fn with_key(key: impl Into<ComponentKey>, properties: Self::Properties) -> Layout {
+        Function::<Self>::with_key(key, properties)
+    }
+}
+
+/// The component which renders a [`FunctionComponent`](trait.FunctionComponent.html)
+/// and holds the state of its hooks.
+pub struct Function<FunctionT: FunctionComponent> {
+    properties: FunctionT::Properties,
+    hooks: RefCell<HookStore>,
+    render: Callback<()>,
+    _function: PhantomData<fn() -> FunctionT>,
+}
+
+impl<FunctionT: FunctionComponent> Component for Function<FunctionT> {
+    // Sent by the hooks to re-render the component
+    type Message = ();
+    type Properties = FunctionT::Properties;
+
+    fn create(properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            hooks: RefCell::new(HookStore::default()),
+            render: link.callback(|()| ()),
+            _function: PhantomData,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, _message: Self::Message) -> ShouldRender {
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let (layout, effects) = {
+            let mut store = self.hooks.borrow_mut();
+            store.next = 0;
+            let mut hooks = Hooks {
+                store: &mut store,
+                render: &self.render,
+                effects: Vec::new(),
+            };
+            let layout = FunctionT::view(&self.properties, &mut hooks);
+            (layout, hooks.effects)
+        };
+        // Effects run once the hooks are released, s.t. they can set state
+        for effect in effects {
+            effect();
+        }
+        layout
+    }
+}
+
+#[derive(Default)]
+struct HookStore {
+    hooks: Vec<Box<dyn Any>>,
+    next: usize,
+}
+
+/// The hooks of a function component, passed to
+/// [`FunctionComponent::view`](trait.FunctionComponent.html#tymethod.view).
+pub struct Hooks<'a> {
+    store: &'a mut HookStore,
+    render: &'a Callback<()>,
+    effects: Vec<Box<dyn FnOnce()>>,
+}
+
+impl<'a> Hooks<'a> {
+    /// Returns state which is kept between renders, created by `initial` on
+    /// the first render. Setting the state re-renders the component.
+    pub fn use_state<StateT: 'static>(
+        &mut self,
+        initial: impl FnOnce() -> StateT,
+    ) -> State<StateT> {
+        let render = self.render.clone();
+        self.use_hook(|| State {
+            value: Rc::new(RefCell::new(initial())),
+            render,
+        })
+        .clone()
+    }
+
+    /// Returns a callback which is only recreated when `dependencies` change.
+    /// Until then, the callback returned is a clone of the first one and
+    /// compares equal to it.
+    pub fn use_callback<InputT, OutputT, DependenciesT>(
+        &mut self,
+        dependencies: DependenciesT,
+        callback: impl Fn(InputT) -> OutputT + 'static,
+    ) -> Callback<InputT, OutputT>
+    where
+        InputT: 'static,
+        OutputT: 'static,
+        DependenciesT: PartialEq + 'static,
+    {
+        let memo = self.use_hook(|| None::<(DependenciesT, Callback<InputT, OutputT>)>);
+        match memo {
+            Some((ref memoised, ref callback)) if *memoised == dependencies => callback.clone(),
+            _ => memo
+                .insert((dependencies, Callback::from(callback)))
+                .1
+                .clone(),
+        }
+    }
+
+    /// Runs an effect after the component renders, on the first render and
+    /// then whenever `dependencies` change.
+    pub fn use_effect<DependenciesT>(
+        &mut self,
+        dependencies: DependenciesT,
+        effect: impl FnOnce() + 'static,
+    ) where
+        DependenciesT: PartialEq + 'static,
+    {
+        let memo = self.use_hook(|| None::<DependenciesT>);
+        if memo.as_ref() != Some(&dependencies) {
+            *memo = Some(dependencies);
+            self.effects.push(Box::new(effect));
+        }
+    }
+
+    /// Returns the next hook, created by `create` on the first render.
+    fn use_hook<HookT: 'static>(&mut self, create: impl FnOnce() -> HookT) -> &mut HookT {
+        let index = self.store.next;
+        self.store.next += 1;
+        if index == self.store.hooks.len() {
+            self.store.hooks.push(Box::new(create()));
+        }
+        self.store.hooks[index].downcast_mut().unwrap_or_else(|| {
+            panic!(
+                "hook {} is not a `{}`, hooks must be called in the same order on every render",
+                index,
+                any::type_name::<HookT>()
+            )
+        })
+    }
+}
+
+/// State kept by a function component between renders, see
+/// [`Hooks::use_state`](struct.Hooks.html#method.use_state).
+///
+/// It is a cheap handle which can be cloned into callbacks and effects.
+/// Setting the state re-renders the component.
+pub struct State<StateT> {
+    value: Rc<RefCell<StateT>>,
+    render: Callback<()>,
+}
+
+impl<StateT> State<StateT> {
+    /// Returns a copy of the current value.
+    pub fn get(&self) -> StateT
+    where
+        StateT: Clone,
+    {
+        self.value.borrow().clone()
+    }
+
+    /// Borrows the current value.
+    pub fn borrow(&self) -> Ref<'_, StateT> {
+        self.value.borrow()
+    }
+
+    /// Replaces the value and re-renders the component.
+    pub fn set(&self, value: StateT) {
+        *self.value.borrow_mut() = value;
+        self.render.emit(());
+    }
+
+    /// Modifies the value in place and re-renders the component.
+    pub fn update(&self, update: impl FnOnce(&mut StateT)) {
+        update(&mut self.value.borrow_mut());
+        self.render.emit(());
+    }
+}
+
+impl<StateT> Clone for State<StateT> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            render: self.render.clone(),
+        }
+    }
+}
+
+impl<StateT: fmt::Debug> fmt::Debug for State<StateT> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_tuple("State")
+            .field(&*self.value.borrow())
+            .finish()
+    }
+}
@@ -0,0 +1,436 @@
+//! Keys and key patterns written as text, and keymap overrides which rebind
+//! the named commands of components, e.g. loaded from a user's config file.
+//!
+//! Keys are written like the status bar shows them, e.g. `C-x`, `A-f`, `SPC`,
+//! `RET`, `TAB`, `ESC`, `F5`, `Left`, `PageDown` or `C-RightClick`. A key
+//! pattern is a sequence of keys separated by spaces, e.g. `C-x C-s`, or one
+//! of `double <key>`, `hold <key>` and `Char(*)` for any character. Sequences
+//! may contain wildcards: `<any>` for any key, `<char>` for any character
+//! and ranges of characters like `<0-9>`, and the leader key of the app,
+//! written `<leader>`, e.g. `<leader> f f`.
+use std::{collections::BTreeMap, error::Error, fmt, str::FromStr};
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::bindings::{KeyMatcher, KeyPattern, KeySequenceSlice};
+use crate::terminal::{Grapheme, Key, Modifiers, MouseButton};
+
+/// Overrides for the key bindings of components' named commands.
+///
+/// Overrides are grouped by component, named by its type, e.g. `Pager`, or
+/// by its full type name, e.g. `zi::components::pager::Pager`. The patterns
+/// of an overridden command replace the ones it was bound to by the
+/// component, commands which aren't overridden keep theirs.
+///
+/// With the `serde` feature, overrides can be deserialized from any format
+/// supported by serde, e.g. TOML or JSON, where a command is bound to a
+/// single pattern or to a list of patterns:
+///
+/// ```toml
+/// [Pager]
+/// down = ["j", "C-n"]
+/// search = "C-s"
+/// ```
+///
+/// Overrides are set with
+/// [`App::set_keymap_overrides`](app/struct.App.html#method.set_keymap_overrides).
+///
+/// ```
+/// # use zi::{KeymapOverrides, terminal::Key};
+/// let mut overrides = KeymapOverrides::new();
+/// overrides
+///     .bind("Pager", "down", ["j".parse().unwrap(), "C-n".parse().unwrap()])
+///     .bind("Pager", "search", ["C-s".parse().unwrap()]);
+///
+/// assert_eq!(
+///     overrides.patterns("zi::components::pager::Pager", "down"),
+///     Some(&[[Key::Char('j')].into(), [Key::Ctrl('n')].into()][..])
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeymapOverrides {
+    components: BTreeMap<String, BTreeMap<String, Vec<KeyPattern>>>,
+}
+
+impl KeymapOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Binds a command of a component to `patterns`, replacing any earlier
+    /// override of the same command.
+    pub fn bind(
+        &mut self,
+        component: impl Into<String>,
+        command: impl Into<String>,
+        patterns: impl IntoIterator<Item = KeyPattern>,
+    ) -> &mut Self {
+        self.components
+            .entry(component.into())
+            .or_default()
+            .insert(command.into(), patterns.into_iter().collect());
+        self
+    }
+
+    /// Adds the overrides of `other`, which take precedence over these.
+    pub fn merge(&mut self, other: KeymapOverrides) {
+        for (component, commands) in other.components {
+            self.components
+                .entry(component)
+                .or_default()
+                .extend(commands);
+        }
+    }
+
+    /// The patterns a command of a component is bound to, if overridden. The
+    /// component is named by its full type name, as returned by
+    /// `std::any::type_name`.
+    pub fn patterns<'a>(&'a self, type_name: &'a str, command: &str) -> Option<&'a [KeyPattern]> {
+        self.commands(type_name)
+            .find_map(|commands| commands.get(command))
+            .map(Vec::as_slice)
+    }
+
+    /// The overridden commands of a component, by full type name first and
+    /// then by type name.
+    pub(crate) fn commands<'a>(
+        &'a self,
+        type_name: &'a str,
+    ) -> impl Iterator<Item = &'a BTreeMap<String, Vec<KeyPattern>>> + 'a {
+        let short_name = short_type_name(type_name);
+        self.components.get(type_name).into_iter().chain(
+            Some(short_name)
+                .filter(|short_name| *short_name != type_name)
+                .and_then(|short_name| self.components.get(short_name)),
+        )
+    }
+}
+
+/// The name of a type without its path and generic arguments, e.g. `Pager`
+/// for `zi::components::pager::Pager`.
+fn short_type_name(type_name: &str) -> &str {
+    let without_generics = type_name.split('<').next().unwrap_or(type_name);
+    without_generics
+        .rsplit("::")
+        .next()
+        .unwrap_or(without_generics)
+}
+
+/// An error returned when parsing a key or a key pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseKeyError {
+    input: String,
+}
+
+impl ParseKeyError {
+    fn new(input: &str) -> Self {
+        Self {
+            input: input.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "invalid key `{}`", self.input)
+    }
+}
+
+impl Error for ParseKeyError {}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let key = match input {
+            "SPC" => Key::Char(' '),
+            "RET" => Key::Char('\n'),
+            "TAB" => Key::Char('\t'),
+            "ESC" => Key::Esc,
+            "Backspace" => Key::Backspace,
+            "Left" => Key::Left,
+            "Right" => Key::Right,
+            "Up" => Key::Up,
+            "Down" => Key::Down,
+            "Home" => Key::Home,
+            "End" => Key::End,
+            "PageUp" => Key::PageUp,
+            "PageDown" => Key::PageDown,
+            "BackTab" => Key::BackTab,
+            "Delete" => Key::Delete,
+            "Insert" => Key::Insert,
+            "Null" => Key::Null,
+            _ => return parse_compound_key(input).ok_or_else(|| ParseKeyError::new(input)),
+        };
+        Ok(key)
+    }
+}
+
+/// Parses characters, graphemes, function keys, `C-` and `A-` modified
+/// characters and mouse keys.
+fn parse_compound_key(input: &str) -> Option<Key> {
+    let mut graphemes = input.graphemes(true);
+    let first = graphemes.next()?;
+    if graphemes.next().is_none() {
+        let mut chars = first.chars();
+        let character = chars.next()?;
+        return Some(match chars.next() {
+            None => Key::Char(character),
+            Some(_) => Key::Grapheme(Grapheme::new(first)?),
+        });
+    }
+
+    if let Some(number) = input.strip_prefix('F') {
+        return match number.parse() {
+            Ok(number @ 1..=12) => Some(Key::F(number)),
+            _ => None,
+        };
+    }
+
+    let single_char = |rest: &str| {
+        let mut chars = rest.chars();
+        let character = chars.next()?;
+        chars.next().is_none().then(|| character)
+    };
+    if let Some(character) = input.strip_prefix("C-").and_then(single_char) {
+        return Some(Key::Ctrl(character));
+    }
+    if let Some(character) = input.strip_prefix("A-").and_then(single_char) {
+        return Some(Key::Alt(character));
+    }
+
+    let mut modifiers = Modifiers::NONE;
+    let mut rest = input;
+    loop {
+        if let Some(stripped) = rest.strip_prefix("C-") {
+            modifiers.ctrl = true;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("A-") {
+            modifiers.alt = true;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("S-") {
+            modifiers.shift = true;
+            rest = stripped;
+        } else {
+            break;
+        }
+    }
+    match rest {
+        "ScrollUp" => return Some(Key::ScrollUp(modifiers)),
+        "ScrollDown" => return Some(Key::ScrollDown(modifiers)),
+        _ => {}
+    }
+    let (button, action) = if let Some(action) = rest.strip_prefix("Right") {
+        (MouseButton::Right, action)
+    } else if let Some(action) = rest.strip_prefix("Middle") {
+        (MouseButton::Middle, action)
+    } else {
+        (MouseButton::Left, rest)
+    };
+    match action {
+        "Click" => Some(Key::Click(button, modifiers)),
+        "Release" => Some(Key::Release(button, modifiers)),
+        "Drag" => Some(Key::Drag(button, modifiers)),
+        _ => None,
+    }
+}
+
+impl FromStr for KeyPattern {
+    type Err = ParseKeyError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if input == "Char(*)" {
+            return Ok(Self::AnyCharacter);
+        }
+        if let Some(key) = input.strip_prefix("double ") {
+            return Ok(Self::DoublePress(key.trim().parse()?));
+        }
+        if let Some(key) = input.strip_prefix("hold ") {
+            return Ok(Self::Hold(key.trim().parse()?));
+        }
+        let matchers = input
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<KeyMatcher>, _>>()?;
+        if matchers.is_empty() {
+            return Err(ParseKeyError::new(input));
+        }
+        Ok(Self::sequence(matchers))
+    }
+}
+
+impl FromStr for KeyMatcher {
+    type Err = ParseKeyError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let wildcard = match input
+            .strip_prefix('<')
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            Some(wildcard) if !wildcard.is_empty() => wildcard,
+            // `<` and `>` are keys too
+            _ => return input.parse().map(Self::Key),
+        };
+        match wildcard {
+            "any" => return Ok(Self::Any),
+            "char" => return Ok(Self::AnyCharacter),
+            "leader" => return Ok(Self::Leader),
+            _ => {}
+        }
+        let mut chars = wildcard.chars();
+        match (chars.next(), chars.next(), chars.next(), chars.next()) {
+            (Some(start), Some('-'), Some(end), None) if start <= end => {
+                Ok(Self::Range(start, end))
+            }
+            _ => Err(ParseKeyError::new(input)),
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        KeySequenceSlice::from(std::slice::from_ref(self)).fmt(formatter)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serialization {
+    use serde::{
+        de::{self, SeqAccess, Visitor},
+        ser::SerializeSeq,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+    use std::{collections::BTreeMap, fmt};
+
+    use super::{KeyPattern, KeymapOverrides};
+    use crate::terminal::Key;
+
+    impl Serialize for Key {
+        fn serialize<SerializerT: Serializer>(
+            &self,
+            serializer: SerializerT,
+        ) -> Result<SerializerT::Ok, SerializerT::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Key {
+        fn deserialize<DeserializerT: Deserializer<'de>>(
+            deserializer: DeserializerT,
+        ) -> Result<Self, DeserializerT::Error> {
+            let input = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+            input.parse().map_err(de::Error::custom)
+        }
+    }
+
+    impl Serialize for KeyPattern {
+        fn serialize<SerializerT: Serializer>(
+            &self,
+            serializer: SerializerT,
+        ) -> Result<SerializerT::Ok, SerializerT::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for KeyPattern {
+        fn deserialize<DeserializerT: Deserializer<'de>>(
+            deserializer: DeserializerT,
+        ) -> Result<Self, DeserializerT::Error> {
+            let input = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+            input.parse().map_err(de::Error::custom)
+        }
+    }
+
+    impl Serialize for KeymapOverrides {
+        fn serialize<SerializerT: Serializer>(
+            &self,
+            serializer: SerializerT,
+        ) -> Result<SerializerT::Ok, SerializerT::Error> {
+            let components: BTreeMap<_, BTreeMap<_, _>> = self
+                .components
+                .iter()
+                .map(|(component, commands)| {
+                    let commands = commands
+                        .iter()
+                        .map(|(command, patterns)| (command, Patterns(patterns.clone())))
+                        .collect();
+                    (component, commands)
+                })
+                .collect();
+            components.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for KeymapOverrides {
+        fn deserialize<DeserializerT: Deserializer<'de>>(
+            deserializer: DeserializerT,
+        ) -> Result<Self, DeserializerT::Error> {
+            let components =
+                BTreeMap::<String, BTreeMap<String, Patterns>>::deserialize(deserializer)?
+                    .into_iter()
+                    .map(|(component, commands)| {
+                        let commands = commands
+                            .into_iter()
+                            .map(|(command, patterns)| (command, patterns.0))
+                            .collect();
+                        (component, commands)
+                    })
+                    .collect();
+            Ok(Self { components })
+        }
+    }
+
+    /// The patterns a command is bound to, written as a single pattern or
+    /// as a list of patterns.
+    struct Patterns(Vec<KeyPattern>);
+
+    impl Serialize for Patterns {
+        fn serialize<SerializerT: Serializer>(
+            &self,
+            serializer: SerializerT,
+        ) -> Result<SerializerT::Ok, SerializerT::Error> {
+            let mut sequence = serializer.serialize_seq(Some(self.0.len()))?;
+            for pattern in self.0.iter() {
+                sequence.serialize_element(pattern)?;
+            }
+            sequence.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Patterns {
+        fn deserialize<DeserializerT: Deserializer<'de>>(
+            deserializer: DeserializerT,
+        ) -> Result<Self, DeserializerT::Error> {
+            deserializer.deserialize_any(PatternsVisitor)
+        }
+    }
+
+    struct PatternsVisitor;
+
+    impl<'de> Visitor<'de> for PatternsVisitor {
+        type Value = Patterns;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a key pattern or a list of key patterns")
+        }
+
+        fn visit_str<ErrorT: de::Error>(self, input: &str) -> Result<Patterns, ErrorT> {
+            Ok(Patterns(vec![input.parse().map_err(ErrorT::custom)?]))
+        }
+
+        fn visit_seq<AccessT: SeqAccess<'de>>(
+            self,
+            mut sequence: AccessT,
+        ) -> Result<Patterns, AccessT::Error> {
+            let mut patterns = Vec::new();
+            while let Some(pattern) = sequence.next_element()? {
+                patterns.push(pattern);
+            }
+            Ok(Patterns(patterns))
+        }
+    }
+}
@@ -2,16 +2,20 @@
 
 use smallvec::SmallVec;
 use std::{
+    any::{Any, TypeId},
     cmp,
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
     hash::{Hash, Hasher},
+    sync::Arc,
 };
 
 use super::{
-    template::{ComponentDef, DynamicTemplate},
-    Component,
+    context::Context,
+    template::{ComponentDef, ComponentId, DynamicTemplate},
+    Children, Component,
 };
-use crate::terminal::{Canvas, Position, Rect, Size};
+use crate::terminal::{Canvas, Position, Rect, RectExt, Size, Style};
 
 pub trait ComponentExt: Component {
     /// Creates a component definition from its `Properties`.
@@ -32,12 +36,26 @@ pub trait ComponentExt: Component {
         ))))
     }
 
+    /// Creates [`Children`](../struct.Children.html) which render the
+    /// component with clones of its `Properties`, e.g. to pass it to a
+    /// wrapper component.
+    fn children(properties: Self::Properties) -> Children
+    where
+        Self::Properties: Clone,
+    {
+        Children::new(move || Self::with(properties.clone()))
+    }
+
     fn item_with(flex: FlexBasis, properties: Self::Properties) -> Item {
         Item {
             flex,
             node: Layout(LayoutNode::Component(DynamicTemplate(Box::new(
                 ComponentDef::<Self>::new(None, properties),
             )))),
+            hint: None,
+            margin: Spacing::zero(),
+            focus_order: 0,
+            skip_focus: false,
         }
     }
 
@@ -51,6 +69,10 @@ pub trait ComponentExt: Component {
             node: Layout(LayoutNode::Component(DynamicTemplate(Box::new(
                 ComponentDef::<Self>::new(Some(key.into()), properties),
             )))),
+            hint: None,
+            margin: Spacing::zero(),
+            focus_order: 0,
+            skip_focus: false,
         }
     }
 }
@@ -61,6 +83,13 @@ impl<T: Component> ComponentExt for T {}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ComponentKey(usize);
 
+/// Keys created from strings are displayed as their hash.
+impl fmt::Display for ComponentKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.0)
+    }
+}
+
 impl From<usize> for ComponentKey {
     fn from(key: usize) -> Self {
         Self(key)
@@ -83,6 +112,8 @@ impl From<&str> for ComponentKey {
 ///      [`Container`](./Container).
 ///   3. A canvas which corresponds to the raw content in a region, represented
 ///      by [`Canvas`](./Canvas).
+///   4. A stack of `Layout`s drawn on top of each other, represented by
+///      [`Stack`](./Stack).
 pub struct Layout(pub(crate) LayoutNode);
 
 impl Layout {
@@ -147,66 +178,459 @@ impl Layout {
     pub fn row_reverse(items: impl IntoIterator<Item = Item>) -> Self {
         Container::row_reverse(items).into()
     }
+
+    /// Keeps a layout mounted without displaying it.
+    ///
+    /// The components in a hidden layout are created and laid out as usual
+    /// and they keep their state, but nothing they draw reaches the screen
+    /// and they don't receive input or ticks. Hiding or showing a layout does
+    /// not change the identity of the components inside it, which makes this
+    /// useful to preserve the state of inactive screens or tabs.
+    #[inline]
+    pub fn hidden(layout: impl Into<Layout>) -> Self {
+        Self(LayoutNode::Hidden(Box::new(layout.into().0)))
+    }
+
+    /// Creates a stack of layers which are drawn on top of each other.
+    ///
+    /// Every layer takes up the whole frame of the stack. Layers are drawn
+    /// back to front, i.e. the last layer is on top. This is the building
+    /// block for anything that floats above other content, e.g. windows or
+    /// popups.
+    ///
+    /// Layers are transparent where they don't draw anything: parts of the
+    /// frame without a component or canvas and the cells of a canvas which
+    /// are left unset, i.e. have an empty grapheme like the cells of a
+    /// new canvas, show the layers below.
+    ///
+    /// Like for the items of a container, the identity of a component inside
+    /// a stack depends on its type and key, but not on the index of its layer.
+    /// Reordering the layers keeps the components mounted provided they are
+    /// distinguishable by type or key.
+    ///
+    /// This is a utility function that builds a stack and converts it to a
+    /// `Layout`. It is equivalent to calling `Stack::new(layers).into()`.
+    #[inline]
+    pub fn stack(layers: impl IntoIterator<Item = Layout>) -> Self {
+        Stack::new(layers).into()
+    }
+
+    /// Provides a value to the components inside a layout, which they read
+    /// with [`ComponentLink::context`](../struct.ComponentLink.html#method.context)
+    /// without it being passed down through the properties of every
+    /// component in between. Values are looked up by their type, a layout
+    /// nested inside another one providing a value of the same type hides
+    /// the outer value.
+    ///
+    /// Providing a value doesn't change the identity of the components in
+    /// the layout. A new value is provided every time the enclosing
+    /// component renders, which re-renders the components that read it.
+    ///
+    /// ```
+    /// # use zi::{components::text::{Text, TextProperties}, prelude::*};
+    /// struct Config {
+    ///     show_line_numbers: bool,
+    /// }
+    ///
+    /// let layout = Layout::provide(
+    ///     Config {
+    ///         show_line_numbers: true,
+    ///     },
+    ///     Text::with(TextProperties::new().content("reads the config")),
+    /// );
+    /// ```
+    #[inline]
+    pub fn provide<ValueT: Send + Sync + 'static>(
+        value: ValueT,
+        layout: impl Into<Layout>,
+    ) -> Self {
+        Self(LayoutNode::Provided(Box::new(Provided {
+            type_id: TypeId::of::<ValueT>(),
+            value: Arc::new(value),
+            node: layout.into().0,
+        })))
+    }
+
+    /// Creates a layout which shows a window onto content larger than its
+    /// frame.
+    ///
+    /// The content is laid out at its preferred size (but at least the size
+    /// of the frame) and the part starting at `offset` is drawn, clipped to
+    /// the frame. Scrolling doesn't resize the components inside, their
+    /// frames are in the coordinates of the content as if it was drawn
+    /// unscrolled at the origin of the frame.
+    #[inline]
+    pub fn scrolled(offset: Position, layout: impl Into<Layout>) -> Self {
+        Self(LayoutNode::Scrolled(Box::new(Scrolled {
+            offset,
+            node: layout.into().0,
+        })))
+    }
+
+    /// Creates a layout which doesn't draw anything.
+    ///
+    /// This is useful as a spacer inside a layer of a stack as it leaves the
+    /// content of the layers below visible.
+    #[inline]
+    pub fn empty() -> Self {
+        Self(LayoutNode::Empty)
+    }
+
+    /// The size the layout would like to have given the `available` space.
+    ///
+    /// Components report their [`Component::size_hint`](crate::Component::size_hint),
+    /// canvases have an exact size, containers add up their items along the
+    /// main axis and stacks fit their largest layer.
+    pub fn size_hint(&self, available: Size) -> SizeHint {
+        self.0.size_hint(available)
+    }
 }
 
 pub(crate) enum LayoutNode {
     Container(Box<Container>),
+    Stack(Box<Stack>),
     Component(DynamicTemplate),
     Canvas(Canvas),
+    Hidden(Box<LayoutNode>),
+    Scrolled(Box<Scrolled>),
+    Provided(Box<Provided>),
+    Empty,
+}
+
+pub(crate) struct Provided {
+    type_id: TypeId,
+    value: Arc<dyn Any + Send + Sync>,
+    node: LayoutNode,
+}
+
+pub(crate) struct Scrolled {
+    offset: Position,
+    node: LayoutNode,
+}
+
+impl Scrolled {
+    /// The frame of the unscrolled content.
+    fn content_frame(&self, frame: Rect) -> Rect {
+        let preferred = self.node.size_hint(frame.size).preferred;
+        Rect::new(frame.origin, preferred.max(frame.size))
+    }
+
+    /// The visible part of the content, in the coordinates of the content
+    /// frame.
+    fn visible(&self, frame: Rect) -> Rect {
+        Rect::new(
+            Position::new(
+                frame.origin.x + self.offset.x,
+                frame.origin.y + self.offset.y,
+            ),
+            frame.size,
+        )
+    }
 }
 
 impl LayoutNode {
+    /// Visits all components in the layout tree, computing their frames and
+    /// position hashes.
     pub(crate) fn crawl(
         &mut self,
         frame: Rect,
         position_hash: u64,
+        hidden: bool,
+        tab_order: &TabOrder,
+        context: &Context,
         view_fn: &mut impl FnMut(LaidComponent),
-        draw_fn: &mut impl FnMut(LaidCanvas),
     ) {
-        let mut hasher = DefaultHasher::new();
-        hasher.write_u64(position_hash);
         match self {
             Self::Container(container) => {
-                hasher.write_u64(Self::CONTAINER_HASH);
+                let position_hash = child_position_hash(position_hash, Self::CONTAINER_HASH);
+                container.resolve_hints(frame.size);
+                container.fill_background(frame.size);
+                let frames = container.frames(frame);
+                let mut crawl_child = |child: &mut Item, frame: Rect| {
+                    let tab_order = tab_order.child(child, frame);
+                    child
+                        .node
+                        .0
+                        .crawl(frame, position_hash, hidden, &tab_order, context, view_fn);
+                };
                 if container.direction.is_reversed() {
-                    let frames: SmallVec<[_; ITEMS_INLINE_SIZE]> =
-                        splits_iter(frame, container.direction, container.children.iter().rev())
-                            .collect();
                     for (child, frame) in container.children.iter_mut().rev().zip(frames) {
-                        // hasher.write_u64(Self::CONTAINER_ITEM_HASH);
-                        child.node.0.crawl(frame, hasher.finish(), view_fn, draw_fn);
+                        crawl_child(child, frame);
                     }
                 } else {
-                    let frames: SmallVec<[_; ITEMS_INLINE_SIZE]> =
-                        splits_iter(frame, container.direction, container.children.iter())
-                            .collect();
                     for (child, frame) in container.children.iter_mut().zip(frames) {
-                        // hasher.write_u64(Self::CONTAINER_ITEM_HASH);
-                        child.node.0.crawl(frame, hasher.finish(), view_fn, draw_fn);
+                        crawl_child(child, frame);
                     }
                 }
             }
-            Self::Component(template) => {
-                template.component_type_id().hash(&mut hasher);
-                if let Some(key) = template.key() {
-                    key.hash(&mut hasher);
+            Self::Stack(stack) => {
+                let position_hash = child_position_hash(position_hash, Self::STACK_HASH);
+                for layer in stack.layers.iter_mut() {
+                    layer
+                        .0
+                        .crawl(frame, position_hash, hidden, tab_order, context, view_fn);
                 }
+            }
+            Self::Component(template) => {
                 view_fn(LaidComponent {
                     frame,
-                    position_hash: hasher.finish(),
+                    position_hash: component_position_hash(position_hash, template),
+                    hidden,
+                    tab_order: tab_order.clone(),
+                    context: context.clone(),
                     template,
                 });
             }
+            Self::Hidden(node) => {
+                // Hidden nodes don't contribute to the position hash s.t.
+                // hiding a layout doesn't change the identity of its components
+                node.crawl(frame, position_hash, true, tab_order, context, view_fn);
+            }
+            Self::Provided(provided) => {
+                // Like hidden nodes, providers don't contribute to the
+                // position hash
+                let context = context.provide(provided.type_id, provided.value.clone());
+                provided
+                    .node
+                    .crawl(frame, position_hash, hidden, tab_order, &context, view_fn);
+            }
+            Self::Scrolled(scrolled) => {
+                let position_hash = child_position_hash(position_hash, Self::SCROLLED_HASH);
+                let frame = scrolled.content_frame(frame);
+                scrolled
+                    .node
+                    .crawl(frame, position_hash, hidden, tab_order, context, view_fn);
+            }
+            Self::Canvas(_) | Self::Empty => {}
+        };
+    }
+
+    /// Visits all canvases in the layout tree back to front, descending into
+    /// the (cached) layouts of the components. Hidden nodes are skipped.
+    pub(crate) fn paint<'a>(
+        &'a self,
+        frame: Rect,
+        position_hash: u64,
+        layouts: &'a HashMap<ComponentId, Layout>,
+        draw_fn: &mut impl FnMut(LaidCanvas<'a>),
+    ) {
+        match self {
+            Self::Container(container) => {
+                let position_hash = child_position_hash(position_hash, Self::CONTAINER_HASH);
+                if let Some(canvas) = container.background_canvas.as_ref() {
+                    draw_fn(LaidCanvas {
+                        frame,
+                        canvas,
+                        window: Rect::new(Position::zero(), canvas.size()),
+                        transparent: false,
+                    });
+                }
+                let frames = container.frames(frame);
+                if container.direction.is_reversed() {
+                    for (child, frame) in container.children.iter().rev().zip(frames) {
+                        child.node.0.paint(frame, position_hash, layouts, draw_fn);
+                    }
+                } else {
+                    for (child, frame) in container.children.iter().zip(frames) {
+                        child.node.0.paint(frame, position_hash, layouts, draw_fn);
+                    }
+                }
+            }
+            Self::Stack(stack) => {
+                let position_hash = child_position_hash(position_hash, Self::STACK_HASH);
+                let mut layers = stack.layers.iter();
+                if let Some(bottom) = layers.next() {
+                    bottom.0.paint(frame, position_hash, layouts, draw_fn);
+                }
+                // The cells left unset by the layers above show the layers
+                // below them
+                let mut transparent: &mut dyn FnMut(LaidCanvas<'a>) =
+                    &mut |laid: LaidCanvas<'a>| {
+                        draw_fn(LaidCanvas {
+                            transparent: true,
+                            ..laid
+                        })
+                    };
+                for layer in layers {
+                    layer
+                        .0
+                        .paint(frame, position_hash, layouts, &mut transparent);
+                }
+            }
+            Self::Component(template) => {
+                let position_hash = component_position_hash(position_hash, template);
+                if let Some(layout) = layouts.get(&template.generate_id(position_hash)) {
+                    layout.0.paint(frame, position_hash, layouts, draw_fn);
+                }
+            }
             Self::Canvas(canvas) => {
-                draw_fn(LaidCanvas { frame, canvas });
+                draw_fn(LaidCanvas {
+                    frame,
+                    canvas,
+                    window: Rect::new(Position::zero(), canvas.size()),
+                    transparent: false,
+                });
+            }
+            Self::Scrolled(scrolled) => {
+                let position_hash = child_position_hash(position_hash, Self::SCROLLED_HASH);
+                let visible = scrolled.visible(frame);
+                // The callback is passed as a trait object to avoid
+                // instantiating `paint` recursively for nested scrolled layouts
+                let mut clipped: &mut dyn FnMut(LaidCanvas<'a>) = &mut |laid: LaidCanvas<'a>| {
+                    if let Some(laid) = laid.clip(visible, frame.origin) {
+                        draw_fn(laid);
+                    }
+                };
+                scrolled.node.paint(
+                    scrolled.content_frame(frame),
+                    position_hash,
+                    layouts,
+                    &mut clipped,
+                );
             }
+            Self::Provided(provided) => {
+                provided.node.paint(frame, position_hash, layouts, draw_fn);
+            }
+            Self::Hidden(_) | Self::Empty => {}
         };
     }
 
-    // Some random number to initialise the hash (0 would also do, but hopefully
-    // this is less pathological if a simpler hash function is used for
-    // `DefaultHasher`).
+    /// Collects the components under a position on the screen, outermost
+    /// first. Layers of a stack are tested front to back and the first one
+    /// that draws anything at the position covers the layers below it.
+    ///
+    /// Returns `true` if any canvas was hit.
+    pub(crate) fn hit_test(
+        &self,
+        frame: Rect,
+        position_hash: u64,
+        layouts: &HashMap<ComponentId, Layout>,
+        position: Position,
+        hits: &mut Vec<ComponentId>,
+    ) -> bool {
+        if !frame.contains(position) {
+            return false;
+        }
+        match self {
+            Self::Container(container) => {
+                let position_hash = child_position_hash(position_hash, Self::CONTAINER_HASH);
+                let frames = container.frames(frame);
+                let mut children: SmallVec<[_; ITEMS_INLINE_SIZE]> =
+                    container.children.iter().collect();
+                if container.direction.is_reversed() {
+                    children.reverse();
+                }
+                let hit = children
+                    .into_iter()
+                    .zip(frames)
+                    .find(|(_, frame)| frame.contains(position))
+                    .map_or(false, |(child, frame)| {
+                        child
+                            .node
+                            .0
+                            .hit_test(frame, position_hash, layouts, position, hits)
+                    });
+                hit || container.background_canvas.is_some()
+            }
+            Self::Stack(stack) => {
+                let position_hash = child_position_hash(position_hash, Self::STACK_HASH);
+                stack.layers.iter().rev().any(|layer| {
+                    layer
+                        .0
+                        .hit_test(frame, position_hash, layouts, position, hits)
+                })
+            }
+            Self::Component(template) => {
+                let position_hash = component_position_hash(position_hash, template);
+                let component_id = template.generate_id(position_hash);
+                let layout = match layouts.get(&component_id) {
+                    Some(layout) => layout,
+                    None => return false,
+                };
+                let num_hits = hits.len();
+                hits.push(component_id);
+                let hit = layout
+                    .0
+                    .hit_test(frame, position_hash, layouts, position, hits);
+                if !hit {
+                    hits.truncate(num_hits);
+                }
+                hit
+            }
+            Self::Scrolled(scrolled) => {
+                let position_hash = child_position_hash(position_hash, Self::SCROLLED_HASH);
+                let position = Position::new(
+                    position.x + scrolled.offset.x,
+                    position.y + scrolled.offset.y,
+                );
+                scrolled.node.hit_test(
+                    scrolled.content_frame(frame),
+                    position_hash,
+                    layouts,
+                    position,
+                    hits,
+                )
+            }
+            Self::Provided(provided) => {
+                provided
+                    .node
+                    .hit_test(frame, position_hash, layouts, position, hits)
+            }
+            Self::Canvas(_) => true,
+            Self::Hidden(_) | Self::Empty => false,
+        }
+    }
+
+    fn size_hint(&self, available: Size) -> SizeHint {
+        match self {
+            Self::Container(container) => container.size_hint(available),
+            Self::Stack(stack) => stack
+                .layers
+                .iter()
+                .map(|layer| layer.size_hint(available))
+                .fold(SizeHint::exact(Size::zero()), |hint, layer| SizeHint {
+                    min: hint.min.max(layer.min),
+                    preferred: hint.preferred.max(layer.preferred),
+                    max: hint.max.max(layer.max),
+                }),
+            Self::Component(template) => template.size_hint(available),
+            Self::Canvas(canvas) => SizeHint::exact(canvas.size()),
+            Self::Hidden(node) => node.size_hint(available),
+            Self::Provided(provided) => provided.node.size_hint(available),
+            // Scrolled content can shrink to nothing
+            Self::Scrolled(scrolled) => SizeHint {
+                min: Size::zero(),
+                ..scrolled.node.size_hint(available)
+            },
+            Self::Empty => SizeHint::default(),
+        }
+    }
+
+    // Some random numbers to initialise the hash (0 would also do, but
+    // hopefully this is less pathological if a simpler hash function is used
+    // for `DefaultHasher`).
     const CONTAINER_HASH: u64 = 0x5aa2d5349a05cde8;
+    const STACK_HASH: u64 = 0x1d6b7c0f3e89a452;
+    const SCROLLED_HASH: u64 = 0x7f2c4e91b05d3a6c;
+}
+
+#[inline]
+fn child_position_hash(position_hash: u64, node_hash: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_u64(position_hash);
+    hasher.write_u64(node_hash);
+    hasher.finish()
+}
+
+#[inline]
+fn component_position_hash(position_hash: u64, template: &DynamicTemplate) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_u64(position_hash);
+    template.component_type_id().hash(&mut hasher);
+    if let Some(key) = template.key() {
+        key.hash(&mut hasher);
+    }
+    hasher.finish()
 }
 
 impl From<Canvas> for Layout {
@@ -222,6 +646,14 @@ type Items = SmallVec<[Item; ITEMS_INLINE_SIZE]>;
 pub struct Container {
     children: Items,
     direction: FlexDirection,
+    justify: FlexJustify,
+    align: FlexAlign,
+    wrap: FlexWrap,
+    line_spacing: usize,
+    line_packing: FlexAlign,
+    padding: Spacing,
+    background: Option<Style>,
+    background_canvas: Option<Canvas>,
 }
 
 impl Container {
@@ -248,6 +680,14 @@ impl Container {
         Self {
             children: items.into_iter().collect(),
             direction,
+            justify: FlexJustify::Start,
+            align: FlexAlign::Stretch,
+            wrap: FlexWrap::NoWrap,
+            line_spacing: 0,
+            line_packing: FlexAlign::Start,
+            padding: Spacing::zero(),
+            background: None,
+            background_canvas: None,
         }
     }
 
@@ -257,6 +697,14 @@ impl Container {
         Self {
             children: SmallVec::new(),
             direction,
+            justify: FlexJustify::Start,
+            align: FlexAlign::Stretch,
+            wrap: FlexWrap::NoWrap,
+            line_spacing: 0,
+            line_packing: FlexAlign::Start,
+            padding: Spacing::zero(),
+            background: None,
+            background_canvas: None,
         }
     }
 
@@ -322,6 +770,407 @@ impl Container {
     pub fn row_reverse(items: impl IntoIterator<Item = Item>) -> Self {
         Self::new(FlexDirection::RowReverse, items)
     }
+
+    /// Sets how items are placed along the main axis when they don't fill
+    /// the container, i.e. when there are no `Auto` or `Weight` items, see
+    /// [`FlexJustify`](enum.FlexJustify.html).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// # fn main() {
+    /// let buttons: Layout = Container::row([
+    ///     Item::fixed(6)(Text::with_key("ok", TextProperties::new().content("[ Ok ]"))),
+    ///     Item::fixed(10)(Text::with_key("cancel", TextProperties::new().content("[ Cancel ]"))),
+    /// ])
+    /// .justify(FlexJustify::SpaceBetween)
+    /// .into();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn justify(mut self, justify: FlexJustify) -> Self {
+        self.justify = justify;
+        self
+    }
+
+    /// Sets how items are placed along the cross axis, i.e. vertically in a
+    /// row and horizontally in a column. Unless the alignment is `Stretch`,
+    /// fixed and content items take up their hinted size along the cross
+    /// axis, see [`FlexAlign`](enum.FlexAlign.html).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// # fn main() {
+    /// let toolbar: Layout = Container::row([
+    ///     Item::content(Text::with(TextProperties::new().content("Label"))),
+    ///     Item::auto(Canvas::new(Size::new(1, 3))),
+    /// ])
+    /// .align(FlexAlign::Centre)
+    /// .background(Style::default())
+    /// .into();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn align(mut self, align: FlexAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Leaves empty space between the edges of the container and its items.
+    /// The padding is filled with the background of the container, if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// # fn main() {
+    /// let dialog: Layout = Container::column([
+    ///     Item::fixed(1)(Text::with_key("title", TextProperties::new().content("Save?"))),
+    ///     Item::fixed(1)(Text::with_key("hint", TextProperties::new().content("y/n")))
+    ///         .margin(Spacing::new(1, 0, 0, 0)),
+    /// ])
+    /// .padding(Spacing::symmetric(1, 2))
+    /// .background(Style::default())
+    /// .into();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn padding(mut self, padding: Spacing) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Fills the space not covered by items with a style, e.g. around items
+    /// aligned on the cross axis.
+    #[inline]
+    pub fn background(mut self, style: Style) -> Self {
+        self.background = Some(style);
+        self
+    }
+
+    /// Wraps items onto multiple lines when they don't fit along the main
+    /// axis, see [`FlexWrap`](enum.FlexWrap.html). Items are broken into lines
+    /// by their fixed size, their hinted size for content items and their
+    /// minimum hinted size for auto items.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// # fn main() {
+    /// let tags = ["rust", "terminal", "ui", "flexbox"];
+    /// let cloud: Layout = Container::row(tags.iter().map(|tag| {
+    ///     Item::content(Text::with_key(*tag, TextProperties::new().content(*tag)))
+    /// }))
+    /// .wrap(FlexWrap::Wrap)
+    /// .line_spacing(1)
+    /// .into();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn wrap(mut self, wrap: FlexWrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Sets the number of empty rows (or columns) between the lines of a
+    /// wrapping container.
+    #[inline]
+    pub fn line_spacing(mut self, spacing: usize) -> Self {
+        self.line_spacing = spacing;
+        self
+    }
+
+    /// Sets how the lines of a wrapping container are placed along the cross
+    /// axis when they don't fill it. With `Stretch` the free space is shared
+    /// between the lines. The default is `Start`.
+    #[inline]
+    pub fn pack_lines(mut self, packing: FlexAlign) -> Self {
+        self.line_packing = packing;
+        self
+    }
+}
+
+impl Container {
+    /// Asks the items with `FlexBasis::Content` for their size hints. This is
+    /// done when the container is first crawled, before the properties of the
+    /// components are used up. The hints are kept s.t. painting, hit testing
+    /// and crawling a cached layout again all see the same frames.
+    fn resolve_hints(&mut self, available: Size) {
+        let available = self.padding.shrink_size(available);
+        let align = self.align;
+        let wrapped = self.wrap != FlexWrap::NoWrap;
+        for child in self.children.iter_mut() {
+            let hinted = wrapped
+                || match child.flex {
+                    FlexBasis::Auto | FlexBasis::Weight(_) => false,
+                    FlexBasis::Fixed(_) => align != FlexAlign::Stretch,
+                    FlexBasis::Content => true,
+                };
+            if hinted && child.hint.is_none() {
+                let hint = child.node.size_hint(child.margin.shrink_size(available));
+                child.hint = Some(child.margin.grow_hint(hint));
+            }
+        }
+    }
+
+    /// Prepares the canvas painted under the items when the container has a
+    /// background.
+    fn fill_background(&mut self, size: Size) {
+        let style = match self.background {
+            Some(style) => style,
+            None => return,
+        };
+        if self.background_canvas.as_ref().map(Canvas::size) != Some(size) {
+            let mut canvas = Canvas::new(size);
+            canvas.clear(style);
+            self.background_canvas = Some(canvas);
+        }
+    }
+
+    /// Shrinks the frame of an item along the cross axis to its hinted size
+    /// and places it according to the alignment of the container.
+    fn align_frame(&self, frame: Rect, hint: Option<SizeHint>) -> Rect {
+        let hint = match (self.align, hint) {
+            (FlexAlign::Stretch, _) | (_, None) => return frame,
+            (_, Some(hint)) => hint,
+        };
+        let cross = self.direction.cross();
+        let available = cross.dimension(frame.size);
+        let min = cross.dimension(hint.min);
+        let size = cmp::min(
+            available,
+            available.clamp(min, cmp::max(min, cross.dimension(hint.max))),
+        );
+        let offset = match self.align {
+            FlexAlign::Stretch | FlexAlign::Start => 0,
+            FlexAlign::Centre => (available - size) / 2,
+            FlexAlign::End => available - size,
+        };
+        let mut aligned = frame;
+        cross.set_dimension(&mut aligned.size, size);
+        cross.offset(&mut aligned, offset);
+        aligned
+    }
+
+    /// Moves the frames of a line of items along the main axis according to
+    /// the justification of the container. The frames are in the order they
+    /// are laid out and are packed at the start of the line.
+    fn justify_frames(&self, line: Rect, frames: &mut [Rect]) {
+        let direction = self.direction;
+        let used = frames.iter().fold(0usize, |used, frame| {
+            used.saturating_add(direction.dimension(frame.size))
+        });
+        let free = direction.dimension(line.size).saturating_sub(used);
+        if free == 0 || frames.is_empty() {
+            return;
+        }
+        let (start, gap) = match self.justify {
+            FlexJustify::Start => return,
+            FlexJustify::Centre => (free / 2, 0),
+            FlexJustify::End => (free, 0),
+            FlexJustify::SpaceBetween => (0, free / cmp::max(frames.len() - 1, 1)),
+        };
+        // The cells left over after dividing the free space go to the first gaps
+        let mut remainder = match self.justify {
+            FlexJustify::SpaceBetween if frames.len() > 1 => free - gap * (frames.len() - 1),
+            _ => 0,
+        };
+        let mut offset = start;
+        for frame in frames.iter_mut() {
+            direction.offset(frame, offset);
+            offset += gap;
+            if remainder > 0 {
+                remainder -= 1;
+                offset += 1;
+            }
+        }
+    }
+
+    /// The frame of an item inside the space allotted to it, i.e. aligned
+    /// and without its margin.
+    #[inline]
+    fn item_frame(&self, frame: Rect, child: &Item) -> Rect {
+        child.margin.shrink(self.align_frame(frame, child.hint))
+    }
+
+    fn size_hint(&self, available: Size) -> SizeHint {
+        let available = self.padding.shrink_size(available);
+        let direction = self.direction;
+        let (mut min, mut preferred, mut max) = (0usize, 0usize, 0usize);
+        let mut cross = SizeHint::exact(Size::zero());
+        for child in self.children.iter() {
+            let mut hint = child.node.size_hint(child.margin.shrink_size(available));
+            match child.flex {
+                FlexBasis::Auto | FlexBasis::Weight(_) => {
+                    direction.set_dimension(&mut hint.min, 0);
+                    direction.set_dimension(&mut hint.max, usize::MAX);
+                }
+                FlexBasis::Fixed(size) => {
+                    direction.set_dimension(&mut hint.min, size);
+                    direction.set_dimension(&mut hint.preferred, size);
+                    direction.set_dimension(&mut hint.max, size);
+                }
+                FlexBasis::Content => {}
+            }
+            let hint = child.margin.grow_hint(hint);
+            min = min.saturating_add(direction.dimension(hint.min));
+            preferred = preferred.saturating_add(direction.dimension(hint.preferred));
+            max = max.saturating_add(direction.dimension(hint.max));
+            cross.min = cross.min.max(hint.min);
+            cross.preferred = cross.preferred.max(hint.preferred);
+            cross.max = cross.max.max(hint.max);
+        }
+        direction.set_dimension(&mut cross.min, min);
+        direction.set_dimension(&mut cross.preferred, preferred);
+        direction.set_dimension(&mut cross.max, max);
+        self.padding.grow_hint(cross)
+    }
+
+    /// Computes the frames of the children in the order they are laid out,
+    /// i.e. reversed for reversed directions.
+    #[inline]
+    fn frames(&self, frame: Rect) -> SmallVec<[Rect; ITEMS_INLINE_SIZE]> {
+        let frame = self.padding.shrink(frame);
+        if self.wrap != FlexWrap::NoWrap {
+            self.wrapped_frames(frame)
+        } else if self.direction.is_reversed() {
+            let mut frames: SmallVec<[Rect; ITEMS_INLINE_SIZE]> =
+                splits_iter(frame, self.direction, self.children.iter().rev()).collect();
+            self.justify_frames(frame, &mut frames);
+            frames
+                .into_iter()
+                .zip(self.children.iter().rev())
+                .map(|(frame, child)| self.item_frame(frame, child))
+                .collect()
+        } else {
+            let mut frames: SmallVec<[Rect; ITEMS_INLINE_SIZE]> =
+                splits_iter(frame, self.direction, self.children.iter()).collect();
+            self.justify_frames(frame, &mut frames);
+            frames
+                .into_iter()
+                .zip(self.children.iter())
+                .map(|(frame, child)| self.item_frame(frame, child))
+                .collect()
+        }
+    }
+
+    /// Computes the frames of the children of a wrapping container, in the
+    /// same order as `frames`.
+    ///
+    /// Items are broken into lines by their size along the main axis, i.e.
+    /// the fixed size, the hinted size for content items and the minimum
+    /// hinted size for auto items. Each line is as thick as its thickest
+    /// item and is then laid out like a container that doesn't wrap.
+    fn wrapped_frames(&self, frame: Rect) -> SmallVec<[Rect; ITEMS_INLINE_SIZE]> {
+        let direction = self.direction;
+        let cross = direction.cross();
+        let main_size = direction.dimension(frame.size);
+        let cross_size = cross.dimension(frame.size);
+        let clamped = |direction: FlexDirection, hint: SizeHint| {
+            let min = direction.dimension(hint.min);
+            let max = cmp::max(min, direction.dimension(hint.max));
+            direction.dimension(hint.preferred).clamp(min, max)
+        };
+
+        // Break the items into lines of (start, end, thickness)
+        let mut lines: SmallVec<[(usize, usize, usize); ITEMS_INLINE_SIZE]> = SmallVec::new();
+        let (mut start, mut line_size, mut thickness) = (0, 0usize, 1);
+        for (index, child) in self.children.iter().enumerate() {
+            let hint = child.hint.unwrap_or_default();
+            let size = match child.flex {
+                FlexBasis::Auto | FlexBasis::Weight(_) => direction.dimension(hint.min),
+                FlexBasis::Fixed(size) => size.saturating_add(child.margin.along(direction)),
+                FlexBasis::Content => clamped(direction, hint),
+            };
+            if index > start && line_size.saturating_add(size) > main_size {
+                lines.push((start, index, thickness));
+                start = index;
+                line_size = 0;
+                thickness = 1;
+            }
+            line_size = line_size.saturating_add(size);
+            thickness = cmp::max(thickness, clamped(cross, hint));
+        }
+        if start < self.children.len() {
+            lines.push((start, self.children.len(), thickness));
+        }
+
+        // Pack the lines along the cross axis
+        let spacing = self.line_spacing * lines.len().saturating_sub(1);
+        let total_thickness = lines.iter().fold(spacing, |total, (_, _, thickness)| {
+            total.saturating_add(*thickness)
+        });
+        let free = cross_size.saturating_sub(total_thickness);
+        let (mut position, stretch) = match self.line_packing {
+            FlexAlign::Stretch => (0, free.checked_div(lines.len()).unwrap_or(0)),
+            FlexAlign::Start => (0, 0),
+            FlexAlign::Centre => (free / 2, 0),
+            FlexAlign::End => (free, 0),
+        };
+        let mut remainder = match self.line_packing {
+            FlexAlign::Stretch => free - stretch * lines.len(),
+            _ => 0,
+        };
+
+        let mut frames: SmallVec<[Rect; ITEMS_INLINE_SIZE]> =
+            SmallVec::from_elem(Rect::zero(), self.children.len());
+        for (start, end, thickness) in lines {
+            let mut thickness = thickness + stretch;
+            if remainder > 0 {
+                remainder -= 1;
+                thickness += 1;
+            }
+            let offset = match self.wrap {
+                FlexWrap::WrapReverse => cross_size.saturating_sub(position + thickness),
+                FlexWrap::NoWrap | FlexWrap::Wrap => position,
+            };
+            position += thickness + self.line_spacing;
+
+            let line_frame = match cross {
+                FlexDirection::Row | FlexDirection::RowReverse => Rect::new(
+                    Position::new(frame.origin.x + offset, frame.origin.y),
+                    Size::new(thickness, frame.size.height),
+                ),
+                FlexDirection::Column | FlexDirection::ColumnReverse => Rect::new(
+                    Position::new(frame.origin.x, frame.origin.y + offset),
+                    Size::new(frame.size.width, thickness),
+                ),
+            };
+            let line = self.children[start..end].iter();
+            let indices = start..end;
+            let mut splits: SmallVec<[Rect; ITEMS_INLINE_SIZE]> = if direction.is_reversed() {
+                splits_iter(line_frame, direction, line.rev()).collect()
+            } else {
+                splits_iter(line_frame, direction, line).collect()
+            };
+            self.justify_frames(line_frame, &mut splits);
+            if direction.is_reversed() {
+                for (index, split) in indices.rev().zip(splits) {
+                    frames[index] = self
+                        .item_frame(split, &self.children[index])
+                        .intersect(&frame);
+                }
+            } else {
+                for (index, split) in indices.zip(splits) {
+                    frames[index] = self
+                        .item_frame(split, &self.children[index])
+                        .intersect(&frame);
+                }
+            }
+        }
+        if direction.is_reversed() {
+            frames.reverse();
+        }
+        frames
+    }
 }
 
 impl From<Container> for Layout {
@@ -330,6 +1179,35 @@ impl From<Container> for Layout {
     }
 }
 
+/// A stack of layers drawn on top of each other, see
+/// [`Layout::stack`](struct.Layout.html#method.stack).
+pub struct Stack {
+    layers: SmallVec<[Layout; ITEMS_INLINE_SIZE]>,
+}
+
+impl Stack {
+    /// Creates a new stack with the provided layers, the last one on top.
+    #[inline]
+    pub fn new(layers: impl IntoIterator<Item = Layout>) -> Self {
+        Self {
+            layers: layers.into_iter().collect(),
+        }
+    }
+
+    /// Adds a layer on top of the existing ones.
+    #[inline]
+    pub fn push(&mut self, layer: impl Into<Layout>) -> &mut Self {
+        self.layers.push(layer.into());
+        self
+    }
+}
+
+impl From<Stack> for Layout {
+    fn from(stack: Stack) -> Self {
+        Layout(LayoutNode::Stack(Box::new(stack)))
+    }
+}
+
 /// Represents a flex item, a layout tree nested inside a container.
 ///
 /// An `Item` consists of a `Layout` and an associated `FlexBasis`. The latter
@@ -338,6 +1216,12 @@ impl From<Container> for Layout {
 pub struct Item {
     node: Layout,
     flex: FlexBasis,
+    // The size hint of items with `FlexBasis::Content`, resolved when the
+    // container is crawled
+    hint: Option<SizeHint>,
+    margin: Spacing,
+    focus_order: i32,
+    skip_focus: bool,
 }
 
 impl Item {
@@ -348,6 +1232,10 @@ impl Item {
         Item {
             node: layout.into(),
             flex: FlexBasis::Auto,
+            hint: None,
+            margin: Spacing::zero(),
+            focus_order: 0,
+            skip_focus: false,
         }
     }
 
@@ -360,8 +1248,81 @@ impl Item {
         move |layout| Item {
             node: layout.into(),
             flex: FlexBasis::Fixed(size),
+            hint: None,
+            margin: Spacing::zero(),
+            focus_order: 0,
+            skip_focus: false,
+        }
+    }
+
+    /// Creates an item that will share the available space with other
+    /// stretched items in proportion to its weight, see
+    /// [`FlexBasis::Weight`](enum.FlexBasis.html#variant.Weight).
+    ///
+    /// ```
+    /// # use zi::prelude::*;
+    /// # use zi::components::text::{Text, TextProperties};
+    /// // The editor takes two thirds of the width and the sidebar one third
+    /// let layout = Layout::row([
+    ///     Item::weighted(2)(Text::with(TextProperties::new().content("editor"))),
+    ///     Item::weighted(1)(Text::with(TextProperties::new().content("sidebar"))),
+    /// ]);
+    /// ```
+    #[inline]
+    pub fn weighted<LayoutT>(weight: u16) -> impl FnOnce(LayoutT) -> Item
+    where
+        LayoutT: Into<Layout>,
+    {
+        move |layout| Item {
+            node: layout.into(),
+            flex: FlexBasis::Weight(weight),
+            hint: None,
+            margin: Spacing::zero(),
+            focus_order: 0,
+            skip_focus: false,
         }
     }
+
+    /// Creates an item sized to fit its content, as given by the
+    /// [`size_hint`](Layout::size_hint) of the layout.
+    #[inline]
+    pub fn content(layout: impl Into<Layout>) -> Item {
+        Item {
+            node: layout.into(),
+            flex: FlexBasis::Content,
+            hint: None,
+            margin: Spacing::zero(),
+            focus_order: 0,
+            skip_focus: false,
+        }
+    }
+
+    /// Leaves empty space around the item, outside of its frame. The margin
+    /// is added to the size of the item, e.g. a fixed item of size 3 with a
+    /// margin of 1 on either side takes up 5 cells.
+    #[inline]
+    pub fn margin(mut self, margin: Spacing) -> Item {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets when the focusable components inside the item are visited with
+    /// Tab, relative to those in sibling items. Items are visited in
+    /// increasing order and items with the same order in layout order. The
+    /// default order is 0.
+    #[inline]
+    pub fn focus_order(mut self, order: i32) -> Item {
+        self.focus_order = order;
+        self
+    }
+
+    /// Excludes the components inside the item from Tab navigation. They
+    /// can still be focused by other means.
+    #[inline]
+    pub fn skip_focus(mut self) -> Item {
+        self.skip_focus = true;
+        self
+    }
 }
 
 /// Enum to control the size of an item inside a container.
@@ -369,6 +1330,219 @@ impl Item {
 pub enum FlexBasis {
     Auto,
     Fixed(usize),
+    /// Stretched like `Auto`, but taking a share of the space proportional
+    /// to the weight, e.g. items with weights 2 and 1 split it 2:1. `Auto`
+    /// items have a weight of 1.
+    Weight(u16),
+    /// Sized along the main axis by the size hint of the layout. Content
+    /// items get their preferred size first and shrink towards their minimum
+    /// size if there isn't enough space. If the container has no `Auto`
+    /// items, content items grow towards their maximum size to fill it.
+    Content,
+}
+
+/// Empty space along each edge of a rectangle, used for the padding of a
+/// [`Container`](struct.Container.html) and the margin of an
+/// [`Item`](struct.Item.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Spacing {
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize,
+    pub left: usize,
+}
+
+impl Spacing {
+    /// Creates spacing with the sizes of the edges in clockwise order, like
+    /// in CSS.
+    #[inline]
+    pub const fn new(top: usize, right: usize, bottom: usize, left: usize) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// No spacing.
+    #[inline]
+    pub const fn zero() -> Self {
+        Self::all(0)
+    }
+
+    /// The same spacing along all edges.
+    #[inline]
+    pub const fn all(size: usize) -> Self {
+        Self::new(size, size, size, size)
+    }
+
+    /// Spacing above and below (`vertical`) and to the left and right
+    /// (`horizontal`).
+    #[inline]
+    pub const fn symmetric(vertical: usize, horizontal: usize) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    /// The total spacing along the main axis of a direction, e.g. left and
+    /// right for a row.
+    #[inline]
+    fn along(self, direction: FlexDirection) -> usize {
+        match direction {
+            FlexDirection::Row | FlexDirection::RowReverse => self.left.saturating_add(self.right),
+            FlexDirection::Column | FlexDirection::ColumnReverse => {
+                self.top.saturating_add(self.bottom)
+            }
+        }
+    }
+
+    /// Removes the spacing from the edges of a frame. Spacing which doesn't
+    /// fit leaves an empty frame.
+    fn shrink(self, frame: Rect) -> Rect {
+        let size = self.shrink_size(frame.size);
+        let origin = Position::new(
+            frame.origin.x + cmp::min(self.left, frame.size.width),
+            frame.origin.y + cmp::min(self.top, frame.size.height),
+        );
+        Rect::new(origin, size)
+    }
+
+    #[inline]
+    fn shrink_size(self, size: Size) -> Size {
+        Size::new(
+            size.width.saturating_sub(self.along(FlexDirection::Row)),
+            size.height
+                .saturating_sub(self.along(FlexDirection::Column)),
+        )
+    }
+
+    #[inline]
+    fn grow_size(self, size: Size) -> Size {
+        Size::new(
+            size.width.saturating_add(self.along(FlexDirection::Row)),
+            size.height
+                .saturating_add(self.along(FlexDirection::Column)),
+        )
+    }
+
+    #[inline]
+    fn grow_hint(self, hint: SizeHint) -> SizeHint {
+        SizeHint::new(
+            self.grow_size(hint.min),
+            self.grow_size(hint.preferred),
+            self.grow_size(hint.max),
+        )
+    }
+}
+
+/// The size a layout would like to have, see
+/// [`Component::size_hint`](crate::Component::size_hint).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SizeHint {
+    pub min: Size,
+    pub preferred: Size,
+    pub max: Size,
+}
+
+impl SizeHint {
+    #[inline]
+    pub fn new(min: Size, preferred: Size, max: Size) -> Self {
+        Self {
+            min,
+            preferred,
+            max,
+        }
+    }
+
+    /// Content that can neither shrink nor grow.
+    #[inline]
+    pub fn exact(size: Size) -> Self {
+        Self::new(size, size, size)
+    }
+
+    /// Content that prefers a size, but can shrink to nothing or grow to fill
+    /// any available space.
+    #[inline]
+    pub fn preferred(size: Size) -> Self {
+        Self {
+            preferred: size,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SizeHint {
+    fn default() -> Self {
+        Self::new(
+            Size::zero(),
+            Size::zero(),
+            Size::new(usize::MAX, usize::MAX),
+        )
+    }
+}
+
+/// Enum to control how items are placed along the cross axis of a container.
+///
+/// With `Stretch`, every item takes up the whole cross size of the container.
+/// Otherwise, fixed and content items take up their hinted size clamped to
+/// the available space, i.e. they still stretch up to their maximum size.
+/// Items with `FlexBasis::Auto` or `FlexBasis::Weight` always stretch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FlexAlign {
+    Stretch,
+    /// Top of a row or left of a column.
+    Start,
+    Centre,
+    /// Bottom of a row or right of a column.
+    End,
+}
+
+/// Enum to control how items are placed along the main axis of a container
+/// when they don't fill it. This is only the case if there are no items with
+/// `FlexBasis::Auto` or `FlexBasis::Weight`, as those stretch to fill the
+/// container, and content items have grown to their maximum size.
+///
+/// The axis is not reversed for reversed directions, e.g. `Start` places the
+/// items of a `RowReverse` container on the left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FlexJustify {
+    /// Left of a row or top of a column.
+    Start,
+    Centre,
+    /// Right of a row or bottom of a column.
+    End,
+    /// The first and last items are placed at the edges of the container and
+    /// the free space is shared equally between the items.
+    SpaceBetween,
+}
+
+impl Default for FlexJustify {
+    fn default() -> Self {
+        Self::Start
+    }
+}
+
+/// Enum to control whether the items of a container are laid out on a single
+/// line or wrap onto multiple lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FlexWrap {
+    NoWrap,
+    /// Lines are stacked along the cross axis, e.g. top to bottom in a row.
+    Wrap,
+    /// Lines are stacked in reverse, e.g. bottom to top in a row.
+    WrapReverse,
+}
+
+impl Default for FlexWrap {
+    fn default() -> Self {
+        Self::NoWrap
+    }
+}
+
+impl Default for FlexAlign {
+    fn default() -> Self {
+        Self::Stretch
+    }
 }
 
 /// Enum to control how items are placed in a container. It defines the main
@@ -399,17 +1573,113 @@ impl FlexDirection {
             FlexDirection::ColumnReverse => size.height,
         }
     }
+
+    /// The direction of the cross axis.
+    #[inline]
+    fn cross(self) -> FlexDirection {
+        match self {
+            FlexDirection::Row | FlexDirection::RowReverse => FlexDirection::Column,
+            FlexDirection::Column | FlexDirection::ColumnReverse => FlexDirection::Row,
+        }
+    }
+
+    #[inline]
+    fn set_dimension(self, size: &mut Size, value: usize) {
+        match self {
+            FlexDirection::Row | FlexDirection::RowReverse => size.width = value,
+            FlexDirection::Column | FlexDirection::ColumnReverse => size.height = value,
+        }
+    }
+
+    /// Moves a rectangle along the axis.
+    #[inline]
+    fn offset(self, rect: &mut Rect, offset: usize) {
+        match self {
+            FlexDirection::Row | FlexDirection::RowReverse => rect.origin.x += offset,
+            FlexDirection::Column | FlexDirection::ColumnReverse => rect.origin.y += offset,
+        }
+    }
 }
 
 pub(crate) struct LaidComponent<'a> {
     pub frame: Rect,
     pub position_hash: u64,
+    pub hidden: bool,
+    pub tab_order: TabOrder,
+    pub context: Context,
     pub template: &'a mut DynamicTemplate,
 }
 
+/// The position of a component in the Tab navigation order, given by the
+/// focus order and origin of every item enclosing it, outermost first.
+/// Comparing them orders components depth first, visiting sibling items by
+/// their focus order and then by their position.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct TabOrder {
+    items: SmallVec<[(i32, usize, usize); 4]>,
+    /// Whether an enclosing item is skipped by Tab navigation.
+    pub skip: bool,
+}
+
+impl TabOrder {
+    fn child(&self, item: &Item, frame: Rect) -> Self {
+        let mut items = self.items.clone();
+        items.push((item.focus_order, frame.origin.y, frame.origin.x));
+        Self {
+            items,
+            skip: self.skip || item.skip_focus,
+        }
+    }
+}
+
 pub(crate) struct LaidCanvas<'a> {
     pub frame: Rect,
     pub canvas: &'a Canvas,
+    /// The part of the canvas to draw at the origin of `frame`.
+    pub window: Rect,
+    /// Whether the canvas is in an upper layer of a stack, which lets the
+    /// cells it leaves unset show what is drawn below.
+    pub transparent: bool,
+}
+
+impl<'a> LaidCanvas<'a> {
+    /// Clips the canvas to the `visible` rectangle and moves it s.t. the top
+    /// left corner of `visible` ends up at `origin`. Returns `None` if
+    /// nothing is left to draw.
+    fn clip(self, visible: Rect, origin: Position) -> Option<Self> {
+        let placed = Rect::new(self.frame.origin, self.window.size);
+        let shown = placed.intersect(&visible);
+        if shown.is_empty() {
+            return None;
+        }
+        Some(Self {
+            frame: Rect::new(
+                Position::new(
+                    origin.x + shown.origin.x - visible.origin.x,
+                    origin.y + shown.origin.y - visible.origin.y,
+                ),
+                shown.size,
+            ),
+            canvas: self.canvas,
+            window: Rect::new(
+                Position::new(
+                    self.window.origin.x + shown.origin.x - placed.origin.x,
+                    self.window.origin.y + shown.origin.y - placed.origin.y,
+                ),
+                shown.size,
+            ),
+            transparent: self.transparent,
+        })
+    }
+}
+
+/// The weight of a stretched item.
+#[inline]
+fn stretch_weight(flex: FlexBasis) -> usize {
+    match flex {
+        FlexBasis::Weight(weight) => weight as usize,
+        _ => 1,
+    }
 }
 
 #[inline]
@@ -420,54 +1690,114 @@ fn splits_iter<'a>(
 ) -> impl Iterator<Item = Rect> + 'a {
     let total_size = direction.dimension(frame.size);
 
+    // Content items start at their preferred size, clamped to their minimum
+    // and maximum size
+    let mut content_sizes: SmallVec<[(usize, usize, usize); ITEMS_INLINE_SIZE]> = children
+        .clone()
+        .filter(|child| child.flex == FlexBasis::Content)
+        .map(|child| {
+            let hint = child.hint.unwrap_or_default();
+            let min = direction.dimension(hint.min);
+            let max = cmp::max(min, direction.dimension(hint.max));
+            (
+                min,
+                direction.dimension(hint.preferred).clamp(min, max),
+                max,
+            )
+        })
+        .collect();
+
     // Compute how much space is available for stretched components
-    let (stretched_budget, num_stretched_children, total_fixed_size) = {
-        let mut stretched_budget = total_size;
+    let (num_stretched_children, total_weight, total_fixed_size) = {
         let mut num_stretched_children = 0;
-        let mut total_fixed_size = 0;
+        let mut total_weight = 0usize;
+        let mut total_fixed_size = 0usize;
         for child in children.clone() {
             match child.flex {
-                FlexBasis::Auto => {
+                FlexBasis::Auto | FlexBasis::Weight(_) => {
                     num_stretched_children += 1;
+                    total_weight += stretch_weight(child.flex);
                 }
                 FlexBasis::Fixed(size) => {
-                    stretched_budget = stretched_budget.saturating_sub(size);
-                    total_fixed_size += size;
+                    total_fixed_size = total_fixed_size
+                        .saturating_add(size)
+                        .saturating_add(child.margin.along(direction));
                 }
+                FlexBasis::Content => {}
             }
         }
-        (stretched_budget, num_stretched_children, total_fixed_size)
+        (num_stretched_children, total_weight, total_fixed_size)
     };
 
-    // Divvy up the space equaly between stretched components.
-    let stretched_size = if num_stretched_children > 0 {
-        stretched_budget / num_stretched_children
-    } else {
-        0
+    // Shrink content items towards their minimum size if they don't fit, or
+    // grow them towards their maximum size to fill the container when there
+    // are no stretched items
+    let total_content_size = content_sizes
+        .iter()
+        .fold(0usize, |total, (_, size, _)| total.saturating_add(*size));
+    let budget = total_size.saturating_sub(total_fixed_size);
+    if total_content_size > budget {
+        let mut excess = total_content_size - budget;
+        for (min, size, _) in content_sizes.iter_mut().rev() {
+            let shrink = cmp::min(excess, *size - *min);
+            *size -= shrink;
+            excess -= shrink;
+        }
+    } else if num_stretched_children == 0 {
+        let mut slack = budget - total_content_size;
+        for (_, size, max) in content_sizes.iter_mut() {
+            let grow = cmp::min(slack, *max - *size);
+            *size += grow;
+            slack -= grow;
+        }
+    }
+    let total_fixed_size = content_sizes
+        .iter()
+        .fold(total_fixed_size, |total, (_, size, _)| {
+            total.saturating_add(*size)
+        });
+    let stretched_budget = total_size.saturating_sub(total_fixed_size);
+
+    // Divvy up the space between stretched components in proportion to their
+    // weights. The cells left over after rounding down go to the first ones.
+    let stretched_size = move |weight: usize| {
+        (stretched_budget as u128 * weight as u128)
+            .checked_div(total_weight as u128)
+            .unwrap_or(0) as usize
     };
-    let mut remainder =
-        total_size.saturating_sub(num_stretched_children * stretched_size + total_fixed_size);
+    let mut remainder = children
+        .clone()
+        .filter(|child| matches!(child.flex, FlexBasis::Auto | FlexBasis::Weight(_)))
+        .fold(stretched_budget, |remainder, child| {
+            remainder - stretched_size(stretch_weight(child.flex))
+        });
     let mut remaining_size = total_size;
+    let mut content_sizes = content_sizes.into_iter().map(|(_, size, _)| size);
 
     children
-        .map(move |child| match child.flex {
-            FlexBasis::Auto => {
-                let offset = total_size - remaining_size;
-                let size = if remainder > 0 {
-                    remainder -= 1;
-                    stretched_size + 1
-                } else {
-                    stretched_size
-                };
-                remaining_size -= size;
-                (offset, size)
-            }
-            FlexBasis::Fixed(size) => {
-                let offset = total_size - remaining_size;
-                let size = cmp::min(remaining_size, size);
-                remaining_size -= size;
-                (offset, size)
-            }
+        .map(move |child| {
+            let offset = total_size - remaining_size;
+            let size = match child.flex {
+                FlexBasis::Auto | FlexBasis::Weight(_) => {
+                    let weight = stretch_weight(child.flex);
+                    if remainder > 0 && weight > 0 {
+                        remainder -= 1;
+                        stretched_size(weight) + 1
+                    } else {
+                        stretched_size(weight)
+                    }
+                }
+                FlexBasis::Fixed(size) => cmp::min(
+                    remaining_size,
+                    size.saturating_add(child.margin.along(direction)),
+                ),
+                FlexBasis::Content => cmp::min(
+                    remaining_size,
+                    content_sizes.next().expect("one size per content item"),
+                ),
+            };
+            remaining_size -= size;
+            (offset, size)
         })
         .map(move |(offset, size)| match direction {
             FlexDirection::Row | FlexDirection::RowReverse => Rect::new(
@@ -1,26 +1,86 @@
 //! Defines the `Component` trait and related types.
 pub mod bindings;
+pub(crate) mod bus;
+pub(crate) mod context;
+pub mod function;
+pub(crate) mod keymap_config;
 pub mod layout;
+pub(crate) mod stream;
 pub(crate) mod template;
+pub(crate) mod timer;
 
-pub use self::layout::{ComponentExt, Layout};
+pub use self::{
+    layout::{Layout, SizeHint},
+    stream::StreamHandle,
+    timer::TimerHandle,
+};
 
 use std::{
-    any::{self, TypeId},
+    any::{self, Any, TypeId},
+    collections::HashMap,
+    error::Error,
     fmt,
+    future::Future,
     marker::PhantomData,
     rc::Rc,
+    sync::{Arc, RwLock},
+    thread,
+    time::{Duration, Instant},
 };
 
 use self::{
-    bindings::{Bindings, NamedBindingQuery},
+    bindings::{Bindings, KeyContinuation, NamedBindingQuery},
+    bus::{Publication, Subscription},
+    context::SharedContext,
+    keymap_config::KeymapOverrides,
+    layout::ComponentKey,
+    stream::StreamTask,
     template::{ComponentId, DynamicMessage},
+    timer::Timer,
 };
+use futures_core::Stream;
+
 use crate::{
-    app::{ComponentMessage, MessageSender},
-    terminal::{Key, Rect},
+    app::{ComponentMessage, ExclusiveTask, MessageSender, Task},
+    terminal::{BackendCapabilities, HoverEvent, Key, Rect, Size, TerminalCursor},
+    theme::Theme,
 };
 
+/// The capabilities of the backend, shared by the app and all component links.
+pub(crate) type SharedCapabilities = Arc<RwLock<BackendCapabilities>>;
+
+/// The current theme, shared by the app and all component links.
+pub(crate) type SharedTheme = Arc<RwLock<Arc<Theme>>>;
+
+/// The frame of a mounted component, shared by the app and its links.
+pub(crate) type SharedFrame = Arc<RwLock<Rect>>;
+
+/// The time of the app's clock, shared by the app and all component links. It
+/// follows the real clock while `None`, tests set it to simulate time.
+pub(crate) type SharedClock = Arc<RwLock<Option<Instant>>>;
+
+pub(crate) fn clock_now(clock: &SharedClock) -> Instant {
+    clock
+        .read()
+        .expect("clock lock is not poisoned")
+        .unwrap_or_else(Instant::now)
+}
+
+/// The services registered with the app, shared by the app and all component
+/// links. Services are keyed by their type.
+pub(crate) type SharedServices = Arc<RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>;
+
+pub(crate) fn get_service<ServiceT: Send + Sync + 'static>(
+    services: &SharedServices,
+) -> Option<Arc<ServiceT>> {
+    services
+        .read()
+        .expect("services lock is not poisoned")
+        .get(&TypeId::of::<ServiceT>())
+        .cloned()
+        .and_then(|service| service.downcast().ok())
+}
+
 /// Components are the building blocks of the UI in Zi.
 ///
 /// The trait describes stateful components and their lifecycle. This is the
@@ -94,9 +154,97 @@ pub trait Component: Sized + 'static {
 
     fn notify_binding_queries(&self, _queries: &[Option<NamedBindingQuery>], _keys: &[Key]) {}
 
-    fn tick(&self) -> Option<Self::Message> {
+    /// Called after [`notify_binding_queries`](#method.notify_binding_queries)
+    /// with the keys which continue the pending key sequence, as bound by the
+    /// component which handled it, e.g. to list them in a popup. The
+    /// continuations are empty when no key sequence is pending.
+    fn notify_continuations(&self, _continuations: &[KeyContinuation], _keys: &[Key]) {}
+
+    /// Called when the mouse enters or leaves the frame of the component. If
+    /// a message is returned, it is passed on to `update`.
+    fn hover(&self, _event: HoverEvent) -> Option<Self::Message> {
+        None
+    }
+
+    /// Called when the component gains (`true`) or loses (`false`) focus
+    /// through Tab navigation, see
+    /// [`Bindings::set_focusable`](struct.Bindings.html#method.set_focusable).
+    /// If a message is returned, it is passed on to `update`.
+    fn focus(&self, _focused: bool) -> Option<Self::Message> {
+        None
+    }
+
+    /// The terminal cursor to show while the component is on the focus
+    /// path, with a position relative to the frame of the component. Text
+    /// inputs and editors use it to place the real cursor, which terminals
+    /// blink and screen readers follow.
+    ///
+    /// The components on the focus path are asked after every draw, starting
+    /// with the active one, and the first cursor inside its frame is shown.
+    /// The cursor is hidden if no component requests one.
+    fn cursor(&self) -> Option<TerminalCursor> {
+        None
+    }
+
+    /// Called when a key is released while the component is on the focus
+    /// path, e.g. to stop panning when an arrow key held down is released.
+    /// Key presses are delivered through [`bindings`](#method.bindings).
+    ///
+    /// Releases are only reported by some backends, see
+    /// [`Event::KeyRelease`](terminal/enum.Event.html#variant.KeyRelease).
+    /// If a message is returned, it is passed on to `update`.
+    fn key_release(&self, _key: Key) -> Option<Self::Message> {
+        None
+    }
+
+    /// Called on every [`App::tick`](app/struct.App.html#method.tick)
+    /// while the component is tickable, see
+    /// [`Bindings::set_tickable`](struct.Bindings.html#method.set_tickable).
+    /// `delta` is the time elapsed since the previous tick, s.t. animations
+    /// advance correctly regardless of how often the backend ticks. If a
+    /// message is returned, it is passed on to `update`.
+    fn tick(&self, _delta: Duration) -> Option<Self::Message> {
+        None
+    }
+
+    /// Called when any component reports an error using
+    /// [`ComponentLink::report_error`](struct.ComponentLink.html#method.report_error).
+    /// If a message is returned, it is passed on to `update`. Used by
+    /// components that display errors, e.g.
+    /// [`ErrorToast`](components/error/struct.ErrorToast.html).
+    fn error(&self, _error: &ReportedError) -> Option<Self::Message> {
+        None
+    }
+
+    /// How urgently the component needs to be re-rendered. When the app has
+    /// a frame budget (see
+    /// [`App::set_frame_budget`](app/struct.App.html#method.set_frame_budget))
+    /// and drawing exceeds it, re-rendering low priority components is
+    /// deferred to the next frame.
+    fn render_priority(&self) -> RenderPriority {
+        RenderPriority::Normal
+    }
+
+    /// The minimum time between two renders of the component. Useful for
+    /// components which update at a high frequency, e.g. when showing
+    /// progress streamed from a background task. Messages are still passed on
+    /// to `update` as they arrive, but even if it returns `ShouldRender::Yes`
+    /// every time, the component is re-rendered at most once per period.
+    fn throttle(&self) -> Option<Duration> {
         None
     }
+
+    /// The size the component would like to have given the `available` space,
+    /// consulted by containers for items with
+    /// [`FlexBasis::Content`](crate::FlexBasis::Content).
+    ///
+    /// Frames are computed before components are created or changed, so the
+    /// hint is derived from the properties rather than from an instance. The
+    /// default has no intrinsic size, i.e. the component collapses next to
+    /// `Auto` items and grows to fill the container otherwise.
+    fn size_hint(_properties: &Self::Properties, _available: Size) -> SizeHint {
+        SizeHint::default()
+    }
 }
 
 /// Callback wrapper. Useful for passing callbacks in child components
@@ -160,18 +308,111 @@ where
     }
 }
 
+/// The child layouts of a wrapper component, e.g. the content inside a
+/// [`Border`](../components/border/struct.Border.html), passed in its
+/// `Properties`.
+///
+/// A `Layout` is consumed when it is laid out, so `Children` holds a function
+/// which creates the layout every time the component renders it. Like
+/// [`Callback`](struct.Callback.html), it is cheap to clone and two
+/// `Children` are equal if they are clones of each other. Closures and the
+/// properties of components convert into `Children`:
+///
+/// ```
+/// # use zi::{components::text::{Text, TextProperties}, prelude::*, Children};
+/// let label = Text::children(TextProperties::new().content("label"));
+/// let row = Children::from(|| Layout::row([Item::auto(Text::with(TextProperties::new()))]));
+/// assert_eq!(label.clone(), label);
+/// assert_ne!(label, row);
+/// ```
+pub struct Children(Rc<dyn Fn() -> Layout>);
+
+impl Children {
+    /// Creates children from a function returning their layout.
+    #[inline]
+    pub fn new(layout: impl Fn() -> Layout + 'static) -> Self {
+        Self(Rc::new(layout))
+    }
+
+    /// No children, their layout is empty.
+    #[inline]
+    pub fn empty() -> Self {
+        Self::new(Layout::empty)
+    }
+
+    /// Creates the layout of the children.
+    #[inline]
+    pub fn view(&self) -> Layout {
+        (self.0)()
+    }
+}
+
+impl Clone for Children {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl Default for Children {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl fmt::Debug for Children {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "Children(@ {:?})", Rc::as_ptr(&self.0))
+    }
+}
+
+impl PartialEq for Children {
+    fn eq(&self, other: &Self) -> bool {
+        // Only the data pointers are compared, see `Callback`
+        std::ptr::eq(
+            self.0.as_ref() as *const _ as *const (),
+            other.0.as_ref() as *const _ as *const (),
+        )
+    }
+}
+
+impl<FnT> From<FnT> for Children
+where
+    FnT: Fn() -> Layout + 'static,
+{
+    fn from(layout: FnT) -> Self {
+        Self::new(layout)
+    }
+}
+
 /// A context for sending messages to a component or the runtime.
 ///
 /// It can be used in a multi-threaded environment (implements `Sync` and
 /// `Send`). Additionally, it can send messages to the runtime, in particular
 /// it's used to gracefully stop a running [`App`](struct.App.html).
-#[derive(Debug)]
 pub struct ComponentLink<ComponentT> {
     sender: Box<dyn MessageSender>,
+    capabilities: SharedCapabilities,
+    theme: SharedTheme,
+    frame: SharedFrame,
+    services: SharedServices,
+    context: SharedContext,
+    clock: SharedClock,
     component_id: ComponentId,
     _component: PhantomData<fn() -> ComponentT>,
 }
 
+// `Debug` is impl'ed manually as deriving it would require the component type
+// to implement `Debug` too.
+impl<ComponentT> fmt::Debug for ComponentLink<ComponentT> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ComponentLink")
+            .field("sender", &self.sender)
+            .field("component_id", &self.component_id)
+            .finish()
+    }
+}
+
 impl<ComponentT: Component> ComponentLink<ComponentT> {
     /// Sends a message to the component.
     pub fn send(&self, message: ComponentT::Message) {
@@ -181,6 +422,69 @@ impl<ComponentT: Component> ComponentLink<ComponentT> {
         )));
     }
 
+    /// Sends a message to another mounted component, identified by its
+    /// [`ComponentHandle`](struct.ComponentHandle.html). Messages sent to a
+    /// component that is no longer mounted are ignored by the runtime.
+    pub fn send_to<OtherT: Component>(
+        &self,
+        handle: ComponentHandle<OtherT>,
+        message: OtherT::Message,
+    ) {
+        self.sender.send(ComponentMessage(handle.message(message)));
+    }
+
+    /// Sends a copy of a message to every mounted component of type
+    /// `OtherT`, e.g. to notify all panels of a theme change.
+    pub fn broadcast<OtherT: Component>(&self, message: OtherT::Message)
+    where
+        OtherT::Message: Clone,
+    {
+        let broadcast = Broadcast::new::<OtherT>(message);
+        self.sender
+            .send(ComponentMessage(LinkMessage::Broadcast(broadcast)));
+    }
+
+    /// Subscribes the component to the values of type `TopicT` published by
+    /// any component with [`publish`](#method.publish). Each value is turned
+    /// into a message for the component by `message`.
+    ///
+    /// Topics are identified by their type, so unrelated components can
+    /// communicate without threading callbacks through their common
+    /// ancestors. Subscribing again to the same topic replaces the previous
+    /// subscription. Subscriptions end when the component is unmounted.
+    pub fn subscribe<TopicT: Clone + Send + 'static>(
+        &self,
+        message: impl Fn(TopicT) -> ComponentT::Message + Send + 'static,
+    ) {
+        self.sender.send(ComponentMessage(LinkMessage::Subscribe(
+            Subscription::new::<ComponentT, TopicT>(self.component_id, message),
+        )));
+    }
+
+    /// Ends the subscription of the component to the topic `TopicT`.
+    pub fn unsubscribe<TopicT: 'static>(&self) {
+        self.sender.send(ComponentMessage(LinkMessage::Unsubscribe(
+            TypeId::of::<TopicT>(),
+            self.component_id,
+        )));
+    }
+
+    /// Publishes a value on the topic of its type. A copy of the value is
+    /// delivered to every component subscribed to the topic, see
+    /// [`subscribe`](#method.subscribe).
+    pub fn publish<TopicT: Clone + Send + 'static>(&self, value: TopicT) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::Publish(Publication::new(
+                value,
+            ))));
+    }
+
+    /// Returns a handle to the linked component which other components or the
+    /// embedder can use to send it messages.
+    pub fn handle(&self) -> ComponentHandle<ComponentT> {
+        ComponentHandle::new(self.component_id)
+    }
+
     /// Creates a `Callback` which will send a message to the linked component's
     /// update method when invoked.
     pub fn callback<InputT>(
@@ -191,19 +495,283 @@ impl<ComponentT: Component> ComponentLink<ComponentT> {
         Callback(Rc::new(move |input| link.send(callback(input))))
     }
 
+    /// Runs a task on a background thread, passing it a link to the
+    /// component.
+    ///
+    /// Use this for blocking work like reading from a file or waiting for a
+    /// subprocess. The task can send any number of messages to the component
+    /// using the link, e.g. as it makes progress. Messages sent after the
+    /// component was dropped are ignored by the runtime.
+    pub fn spawn(
+        &self,
+        task: impl FnOnce(ComponentLink<ComponentT>) + Send + 'static,
+    ) -> thread::JoinHandle<()> {
+        let link = self.clone();
+        thread::spawn(move || task(link))
+    }
+
+    /// Sends a message to the component once `delay` has elapsed. The
+    /// returned handle can be used to cancel it before then.
+    ///
+    /// Timers are run by the app's event loop, they don't need a thread
+    /// each. Timers are cancelled when the component is unmounted.
+    pub fn send_after(&self, delay: Duration, message: ComponentT::Message) -> TimerHandle {
+        let mut message = Some(message);
+        self.schedule(delay, None, move || {
+            message
+                .take()
+                .map(|message| DynamicMessage(Box::new(message)))
+        })
+    }
+
+    /// Sends a message created by `message` to the component every
+    /// `interval`, starting one interval from now, until the returned handle
+    /// is cancelled or the component is unmounted.
+    ///
+    /// If the app falls behind, e.g. while the terminal is busy, missed
+    /// messages are not sent in a burst afterwards, the timer fires once and
+    /// continues one interval later.
+    pub fn send_every(
+        &self,
+        interval: Duration,
+        message: impl Fn() -> ComponentT::Message + Send + 'static,
+    ) -> TimerHandle {
+        self.schedule(interval, Some(interval), move || {
+            Some(DynamicMessage(Box::new(message())))
+        })
+    }
+
+    fn schedule(
+        &self,
+        delay: Duration,
+        interval: Option<Duration>,
+        message: impl FnMut() -> Option<DynamicMessage> + Send + 'static,
+    ) -> TimerHandle {
+        let (timer, handle) = Timer::new(self.component_id, self.now() + delay, interval, message);
+        self.sender
+            .send(ComponentMessage(LinkMessage::Timer(timer)));
+        handle
+    }
+
+    /// Spawns a future, e.g. a network request, which resolves to a message
+    /// for the component.
+    ///
+    /// The future is run by the executor of the backend, see
+    /// [`App::set_executor`](app/struct.App.html#method.set_executor), so it
+    /// can use the backend's async runtime. Messages for a component which
+    /// was dropped in the meantime are ignored by the runtime.
+    pub fn spawn_future(&self, future: impl Future<Output = ComponentT::Message> + Send + 'static) {
+        let link = self.clone();
+        self.sender.send(ComponentMessage(LinkMessage::Spawn(
+            self.component_id,
+            Box::pin(async move {
+                let message = future.await;
+                link.send(message);
+            }),
+            None,
+        )));
+    }
+
+    /// Consumes a stream, e.g. the lines of a log file being tailed or the
+    /// messages of a websocket, sending each item to the component as the
+    /// message returned by `message`.
+    ///
+    /// Like [`spawn_future`](#method.spawn_future), the stream is polled by
+    /// the executor of the backend. The subscription is cancelled, dropping
+    /// the stream, when the component is unmounted or the returned handle is
+    /// cancelled.
+    pub fn subscribe_stream<StreamT>(
+        &self,
+        stream: StreamT,
+        message: impl Fn(StreamT::Item) -> ComponentT::Message + Send + 'static,
+    ) -> StreamHandle
+    where
+        StreamT: Stream + Send + 'static,
+    {
+        let link = self.clone();
+        let handle = StreamHandle::new();
+        let task = StreamTask::new(stream, move |item| link.send(message(item)), handle.clone());
+        self.sender.send(ComponentMessage(LinkMessage::Spawn(
+            self.component_id,
+            Box::pin(task),
+            Some(handle.clone()),
+        )));
+        handle
+    }
+
+    /// Runs a blocking task which needs exclusive access to the terminal, e.g.
+    /// spawning `$EDITOR` to edit a file.
+    ///
+    /// The backend suspends itself, restoring the terminal to its normal
+    /// state, runs the task on the main thread and then resumes. The message
+    /// returned by the task, if any, is sent to the component. This method
+    /// only sends a request and returns immediately.
+    pub fn run_exclusive(
+        &self,
+        task: impl FnOnce() -> Option<ComponentT::Message> + Send + 'static,
+    ) {
+        self.sender.send(ComponentMessage(LinkMessage::RunExclusive(
+            ExclusiveTask::new(self.component_id, move || {
+                task().map(|message| DynamicMessage(Box::new(message)))
+            }),
+        )));
+    }
+
+    /// Copies text to the clipboard set by the backend with
+    /// [`App::set_clipboard`](app/struct.App.html#method.set_clipboard),
+    /// e.g. the selection of a text view.
+    pub fn copy_to_clipboard(&self, text: impl Into<String>) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::CopyToClipboard(text.into())));
+    }
+
+    /// Requests the text in the clipboard. The message returned by `message`
+    /// for the text is sent to the component, the text is `None` if the
+    /// clipboard is empty or can't be read. This method only sends a request
+    /// and returns immediately.
+    pub fn request_paste(
+        &self,
+        message: impl FnOnce(Option<String>) -> ComponentT::Message + Send + 'static,
+    ) {
+        self.sender.send(ComponentMessage(LinkMessage::Paste(
+            self.component_id,
+            Box::new(move |text| DynamicMessage(Box::new(message(text)))),
+        )));
+    }
+
+    /// Reports an error, e.g. an IO or network failure, to the `App` runtime.
+    ///
+    /// The error is passed to the handler set with
+    /// [`App::on_error`](app/struct.App.html#method.on_error) and to every
+    /// mounted component implementing [`Component::error`](trait.Component.html#method.error),
+    /// like [`ErrorToast`](components/error/struct.ErrorToast.html). This
+    /// allows deeply nested components to surface failures without each parent
+    /// wiring an error callback.
+    pub fn report_error(&self, error: impl Into<Box<dyn Error + Send + Sync>>) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::Error(ReportedError {
+                component_type_name: self.component_id.type_name(),
+                error: error.into(),
+            })));
+    }
+
     /// Sends a message to the `App` runtime requesting it to stop executing.
     ///
     /// This method only sends a message and returns immediately, the app will
     /// stop asynchronously and may deliver other pending messages before
     /// exiting.
     pub fn exit(&self) {
-        self.sender.send(ComponentMessage(LinkMessage::Exit));
+        self.sender.send(ComponentMessage(LinkMessage::Exit(None)));
+    }
+
+    /// Requests the `App` runtime to stop executing, like
+    /// [`exit`](#method.exit), returning a value to the code which started
+    /// it, e.g. the file selected in a picker.
+    ///
+    /// The value is returned by backends which support it, like
+    /// `zi_term::Crossterm::run_with_exit_value`, or can be taken with
+    /// [`App::take_exit_value`](app/struct.App.html#method.take_exit_value).
+    pub fn exit_with<ValueT: Send + 'static>(&self, value: ValueT) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::Exit(Some(Box::new(value)))));
+    }
+
+    /// Returns the capabilities of the backend, e.g. to check whether the
+    /// terminal supports true colour or graphics before using them.
+    ///
+    /// Backends can update the capabilities while the app is running, in
+    /// which case all components are re-rendered.
+    pub fn capabilities(&self) -> BackendCapabilities {
+        *self
+            .capabilities
+            .read()
+            .expect("capabilities lock is not poisoned")
+    }
+
+    /// Returns the current [`Theme`](theme/struct.Theme.html) of the app.
+    /// Components look up their styles in it when they are rendered, as all
+    /// components are re-rendered when the theme changes.
+    pub fn theme(&self) -> Arc<Theme> {
+        self.theme
+            .read()
+            .expect("theme lock is not poisoned")
+            .clone()
+    }
+
+    /// Sends a message to the `App` runtime requesting it to switch to a
+    /// new theme, see
+    /// [`App::set_theme`](app/struct.App.html#method.set_theme).
+    pub fn set_theme(&self, theme: Theme) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::SetTheme(theme)));
+    }
+
+    /// Sends a message to the `App` runtime requesting it to rebind
+    /// components' named commands, see
+    /// [`App::set_keymap_overrides`](app/struct.App.html#method.set_keymap_overrides).
+    pub fn set_keymap_overrides(&self, overrides: KeymapOverrides) {
+        self.sender
+            .send(ComponentMessage(LinkMessage::SetKeymapOverrides(overrides)));
     }
 
-    pub(crate) fn new(sender: Box<dyn MessageSender>, component_id: ComponentId) -> Self {
+    /// Returns the current time of the app's clock, e.g. to time animations or
+    /// to find out how long ago something happened. It's the real time,
+    /// unless the app is driven by a
+    /// [`testing::Harness`](testing/struct.Harness.html) which simulates it.
+    pub fn now(&self) -> Instant {
+        clock_now(&self.clock)
+    }
+
+    /// Returns the current frame of the component, e.g. to position a popup
+    /// next to it. See also
+    /// [`App::frame_of`](app/struct.App.html#method.frame_of).
+    pub fn own_frame(&self) -> Rect {
+        *self.frame.read().expect("frame lock is not poisoned")
+    }
+
+    /// Returns the service of type `ServiceT` registered with
+    /// [`App::register_service`](app/struct.App.html#method.register_service),
+    /// e.g. a database handle or an HTTP client shared by many components.
+    /// Returns `None` if no such service was registered.
+    pub fn service<ServiceT: Send + Sync + 'static>(&self) -> Option<Arc<ServiceT>> {
+        get_service(&self.services)
+    }
+
+    /// Returns the innermost value of type `ValueT` provided by the layouts
+    /// enclosing the component with
+    /// [`Layout::provide`](struct.Layout.html#method.provide), e.g. a
+    /// configuration or a data store handle. Returns `None` if no enclosing
+    /// layout provides one.
+    ///
+    /// Once a component reads its context, it is re-rendered whenever the
+    /// values provided to it change.
+    pub fn context<ValueT: Send + Sync + 'static>(&self) -> Option<Arc<ValueT>> {
+        self.context
+            .write()
+            .expect("context lock is not poisoned")
+            .get()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        sender: Box<dyn MessageSender>,
+        capabilities: SharedCapabilities,
+        theme: SharedTheme,
+        frame: SharedFrame,
+        services: SharedServices,
+        context: SharedContext,
+        clock: SharedClock,
+        component_id: ComponentId,
+    ) -> Self {
         assert_eq!(TypeId::of::<ComponentT>(), component_id.type_id());
         Self {
             sender,
+            capabilities,
+            theme,
+            frame,
+            services,
+            context,
+            clock,
             component_id,
             _component: PhantomData,
         }
@@ -214,6 +782,12 @@ impl<ComponentT> Clone for ComponentLink<ComponentT> {
     fn clone(&self) -> Self {
         Self {
             sender: self.sender.clone_box(),
+            capabilities: self.capabilities.clone(),
+            theme: self.theme.clone(),
+            frame: self.frame.clone(),
+            services: self.services.clone(),
+            context: self.context.clone(),
+            clock: self.clock.clone(),
             component_id: self.component_id,
             _component: PhantomData,
         }
@@ -226,6 +800,138 @@ impl<ComponentT> PartialEq for ComponentLink<ComponentT> {
     }
 }
 
+/// A handle to a mounted component, used to send it messages from outside
+/// its subtree with [`App::send_to`](app/struct.App.html#method.send_to) or
+/// [`ComponentLink::send_to`](struct.ComponentLink.html#method.send_to).
+///
+/// A handle obtained with
+/// [`ComponentLink::handle`](struct.ComponentLink.html#method.handle) or
+/// [`App::handle_of`](app/struct.App.html#method.handle_of) is derived from the
+/// component's position in the tree and its
+/// [`ComponentKey`](struct.ComponentKey.html), if any. It remains valid as long
+/// as the component stays mounted.
+///
+/// A handle can also be created up front from a key with
+/// [`ComponentHandle::keyed`](#method.keyed), without access to the
+/// component. It addresses whichever component of the type is mounted with
+/// that key when a message is delivered, anywhere in the tree.
+///
+/// Unlike a `ComponentLink`, a handle is `Copy` and can be compared and
+/// hashed. Handles are equal if they address the component the same way, a
+/// keyed handle is never equal to one obtained from a mounted component.
+pub struct ComponentHandle<ComponentT> {
+    target: HandleTarget,
+    _component: PhantomData<fn() -> ComponentT>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum HandleTarget {
+    Id(ComponentId),
+    Key(ComponentKey),
+}
+
+impl<ComponentT> ComponentHandle<ComponentT> {
+    pub(crate) fn new(component_id: ComponentId) -> Self {
+        Self {
+            target: HandleTarget::Id(component_id),
+            _component: PhantomData,
+        }
+    }
+}
+
+impl<ComponentT: Component> ComponentHandle<ComponentT> {
+    /// Creates a handle to the mounted component of type `ComponentT` with
+    /// the given key (see
+    /// [`ComponentExt::with_key`](trait.ComponentExt.html#method.with_key)),
+    /// wherever it is in the tree.
+    ///
+    /// Keys used this way should be unique among the mounted components of
+    /// the type, if more than one matches, which one receives a message is
+    /// unspecified. Messages are ignored while no component matches.
+    ///
+    /// ```
+    /// # use zi::{prelude::*, ComponentHandle};
+    /// # struct Status;
+    /// # impl Component for Status {
+    /// #     type Message = String;
+    /// #     type Properties = ();
+    /// #     fn create(_: (), _: Rect, _: ComponentLink<Self>) -> Self { Self }
+    /// #     fn view(&self) -> Layout { Layout::empty() }
+    /// # }
+    /// const STATUS: &str = "status";
+    ///
+    /// // The status bar is mounted with a well known key ...
+    /// let status_bar = Status::with_key(STATUS, ());
+    ///
+    /// // ... which any component can use to send it messages with
+    /// // `link.send_to(ComponentHandle::<Status>::keyed(STATUS), message)`
+    /// let handle = ComponentHandle::<Status>::keyed(STATUS);
+    /// assert_eq!(handle, ComponentHandle::keyed(STATUS));
+    /// ```
+    pub fn keyed(key: impl Into<ComponentKey>) -> Self {
+        Self {
+            target: HandleTarget::Key(key.into()),
+            _component: PhantomData,
+        }
+    }
+
+    /// Addresses a message to the component.
+    pub(crate) fn message(self, message: ComponentT::Message) -> LinkMessage {
+        let message = DynamicMessage(Box::new(message));
+        match self.target {
+            HandleTarget::Id(component_id) => LinkMessage::Component(component_id, message),
+            HandleTarget::Key(key) => LinkMessage::Keyed(KeyedMessage {
+                type_id: TypeId::of::<ComponentT>(),
+                type_name: any::type_name::<ComponentT>(),
+                key,
+                message,
+            }),
+        }
+    }
+}
+
+// The traits below are impl'ed manually as deriving them would require the
+// component type to implement them too.
+impl<ComponentT> Clone for ComponentHandle<ComponentT> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<ComponentT> Copy for ComponentHandle<ComponentT> {}
+
+impl<ComponentT> PartialEq for ComponentHandle<ComponentT> {
+    fn eq(&self, other: &Self) -> bool {
+        self.target == other.target
+    }
+}
+
+impl<ComponentT> Eq for ComponentHandle<ComponentT> {}
+
+impl<ComponentT> std::hash::Hash for ComponentHandle<ComponentT> {
+    fn hash<HasherT: std::hash::Hasher>(&self, hasher: &mut HasherT) {
+        self.target.hash(hasher);
+    }
+}
+
+impl<ComponentT> fmt::Debug for ComponentHandle<ComponentT> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_tuple("ComponentHandle")
+            .field(&self.target)
+            .finish()
+    }
+}
+
+/// How urgently a component needs to be re-rendered, see
+/// [`Component::render_priority`](trait.Component.html#method.render_priority).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RenderPriority {
+    /// Can be rendered a frame late, e.g. a minimap or a status bar.
+    Low,
+    Normal,
+}
+
 /// Type to indicate whether a component should be rendered again.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ShouldRender {
@@ -265,9 +971,106 @@ impl std::ops::BitAnd for ShouldRender {
     }
 }
 
+/// An error reported by a component using
+/// [`ComponentLink::report_error`](struct.ComponentLink.html#method.report_error).
+#[derive(Debug)]
+pub struct ReportedError {
+    component_type_name: &'static str,
+    error: Box<dyn Error + Send + Sync>,
+}
+
+impl ReportedError {
+    /// The type name of the component which reported the error. Like
+    /// `std::any::type_name`, it should only be used for debugging.
+    pub fn component_type_name(&self) -> &'static str {
+        self.component_type_name
+    }
+
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error
+    }
+}
+
+impl fmt::Display for ReportedError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.error)
+    }
+}
+
+impl Error for ReportedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.error)
+    }
+}
+
+/// A message sent to all mounted components of a type. It holds a single
+/// message which is cloned for each component it's delivered to.
+pub(crate) struct Broadcast {
+    pub(crate) type_id: TypeId,
+    pub(crate) type_name: &'static str,
+    pub(crate) message: DynamicMessage,
+    clone: fn(&DynamicMessage) -> DynamicMessage,
+}
+
+impl Broadcast {
+    pub(crate) fn new<ComponentT: Component>(message: ComponentT::Message) -> Self
+    where
+        ComponentT::Message: Clone,
+    {
+        Self {
+            type_id: TypeId::of::<ComponentT>(),
+            type_name: any::type_name::<ComponentT>(),
+            message: DynamicMessage(Box::new(message)),
+            clone: |message| {
+                DynamicMessage(Box::new(
+                    message
+                        .0
+                        .downcast_ref::<ComponentT::Message>()
+                        .expect("Incorrect `Message` type when downcasting")
+                        .clone(),
+                ))
+            },
+        }
+    }
+
+    /// Returns a copy of the broadcast message.
+    pub(crate) fn message(&self) -> DynamicMessage {
+        (self.clone)(&self.message)
+    }
+}
+
+/// A message sent to the mounted component of a type with a key, see
+/// `ComponentHandle::keyed`.
+pub(crate) struct KeyedMessage {
+    pub(crate) type_id: TypeId,
+    pub(crate) type_name: &'static str,
+    pub(crate) key: ComponentKey,
+    pub(crate) message: DynamicMessage,
+}
+
+pub(crate) type PasteFn = Box<dyn FnOnce(Option<String>) -> DynamicMessage + Send>;
+
 pub(crate) enum LinkMessage {
     Component(ComponentId, DynamicMessage),
-    Exit,
+    Keyed(KeyedMessage),
+    Broadcast(Broadcast),
+    Subscribe(Subscription),
+    Unsubscribe(TypeId, ComponentId),
+    Publish(Publication),
+    Timer(Timer),
+    /// A task spawned by a component, with the handle to cancel it when the
+    /// component is unmounted if it consumes a stream.
+    Spawn(ComponentId, Task, Option<StreamHandle>),
+    RunExclusive(ExclusiveTask),
+    CopyToClipboard(String),
+    /// A request for the text in the clipboard, which is passed to the
+    /// function to make the message sent to the component.
+    Paste(ComponentId, PasteFn),
+    Error(ReportedError),
+    SetTheme(Theme),
+    SetKeymapOverrides(KeymapOverrides),
+    /// A request to stop the app, with the value to return if any.
+    Exit(Option<Box<dyn Any + Send>>),
 }
 
 impl std::fmt::Debug for LinkMessage {
@@ -279,7 +1082,39 @@ impl std::fmt::Debug for LinkMessage {
                 "Component({:?}, DynamicMessage(...) @ {:?})",
                 id, &*message.0 as *const _
             ),
-            Self::Exit => write!(formatter, "Exit"),
+            Self::Keyed(keyed) => write!(
+                formatter,
+                "Keyed({}, {}, DynamicMessage(...) @ {:?})",
+                keyed.type_name, keyed.key, &*keyed.message.0 as *const _
+            ),
+            Self::Broadcast(broadcast) => write!(
+                formatter,
+                "Broadcast({}, DynamicMessage(...) @ {:?})",
+                broadcast.type_name, &*broadcast.message.0 as *const _
+            ),
+            Self::Subscribe(subscription) => write!(
+                formatter,
+                "Subscribe({:?}, {:?})",
+                subscription.topic, subscription.component_id
+            ),
+            Self::Unsubscribe(topic, component_id) => {
+                write!(formatter, "Unsubscribe({:?}, {:?})", topic, component_id)
+            }
+            Self::Publish(publication) => write!(
+                formatter,
+                "Publish({}, DynamicMessage(...) @ {:?})",
+                publication.topic_name, &*publication.value as *const _
+            ),
+            Self::Timer(timer) => write!(formatter, "{:?}", timer),
+            Self::RunExclusive(task) => write!(formatter, "RunExclusive({:?})", task),
+            Self::CopyToClipboard(text) => write!(formatter, "CopyToClipboard({:?})", text),
+            Self::Paste(id, _) => write!(formatter, "Paste({:?}, PasteFn(...))", id),
+            Self::Spawn(id, ..) => write!(formatter, "Spawn({:?}, Task(...))", id),
+            Self::Error(error) => write!(formatter, "Error({:?})", error),
+            Self::SetTheme(theme) => write!(formatter, "SetTheme({:?})", theme.name()),
+            Self::SetKeymapOverrides(_) => write!(formatter, "SetKeymapOverrides(...)"),
+            Self::Exit(None) => write!(formatter, "Exit"),
+            Self::Exit(Some(_)) => write!(formatter, "Exit(...)"),
         }
     }
 }
@@ -0,0 +1,130 @@
+//! Streams consumed by components, see
+//! [`ComponentLink::subscribe_stream`](../struct.ComponentLink.html#method.subscribe_stream).
+use futures_core::Stream;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+/// The number of items a stream task forwards before yielding to other
+/// tasks, s.t. a stream which is always ready doesn't starve them.
+const ITEMS_PER_POLL: usize = 32;
+
+/// A handle to a stream consumed by a component, used to cancel the
+/// subscription.
+///
+/// Dropping the handle does not cancel the subscription. Subscriptions are
+/// cancelled automatically when the component is unmounted.
+#[derive(Clone)]
+pub struct StreamHandle {
+    state: Arc<StreamState>,
+}
+
+impl StreamHandle {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::new(StreamState {
+                finished: AtomicBool::new(false),
+                waker: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Cancels the subscription. The stream is dropped without being polled
+    /// again and no more messages are sent once this returns.
+    pub fn cancel(&self) {
+        self.state.finished.store(true, Ordering::SeqCst);
+        if let Some(waker) = self
+            .state
+            .waker
+            .lock()
+            .expect("waker lock is not poisoned")
+            .take()
+        {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` once the stream ended or the subscription was
+    /// cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.state.finished.load(Ordering::SeqCst)
+    }
+}
+
+impl fmt::Debug for StreamHandle {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("StreamHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+struct StreamState {
+    finished: AtomicBool,
+    /// The waker of the task consuming the stream, to stop it promptly when
+    /// the subscription is cancelled.
+    waker: Mutex<Option<Waker>>,
+}
+
+/// A task which forwards the items of a stream until it ends or is
+/// cancelled.
+pub(crate) struct StreamTask<StreamT, ForwardT> {
+    stream: Pin<Box<StreamT>>,
+    forward: ForwardT,
+    handle: StreamHandle,
+}
+
+impl<StreamT, ForwardT> StreamTask<StreamT, ForwardT> {
+    pub(crate) fn new(stream: StreamT, forward: ForwardT, handle: StreamHandle) -> Self {
+        Self {
+            stream: Box::pin(stream),
+            forward,
+            handle,
+        }
+    }
+}
+
+// The stream is pinned on the heap and nothing else is structurally pinned,
+// so the task can be moved freely.
+impl<StreamT, ForwardT> Unpin for StreamTask<StreamT, ForwardT> {}
+
+impl<StreamT, ForwardT> Future for StreamTask<StreamT, ForwardT>
+where
+    StreamT: Stream,
+    ForwardT: FnMut(StreamT::Item),
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        let task = self.get_mut();
+        *task
+            .handle
+            .state
+            .waker
+            .lock()
+            .expect("waker lock is not poisoned") = Some(context.waker().clone());
+        for _ in 0..ITEMS_PER_POLL {
+            if task.handle.is_finished() {
+                return Poll::Ready(());
+            }
+            match task.stream.as_mut().poll_next(context) {
+                Poll::Ready(Some(item)) => (task.forward)(item),
+                Poll::Ready(None) => {
+                    task.handle.state.finished.store(true, Ordering::SeqCst);
+                    return Poll::Ready(());
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        context.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
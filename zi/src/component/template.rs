@@ -2,14 +2,17 @@ use std::{
     any::{Any, TypeId},
     hash::{Hash, Hasher},
     ops::{Deref, DerefMut},
+    time::{Duration, Instant},
 };
 
 use super::{
-    bindings::{CommandId, DynamicBindings, NamedBindingQuery},
-    layout::{ComponentKey, Layout},
-    Component, ComponentLink, MessageSender, ShouldRender,
+    bindings::{CommandId, DynamicBindings, KeyContinuation, NamedBindingQuery},
+    context::SharedContext,
+    layout::{ComponentKey, Layout, SizeHint},
+    Component, ComponentLink, MessageSender, RenderPriority, ReportedError, SharedCapabilities,
+    SharedClock, SharedFrame, SharedServices, SharedTheme, ShouldRender,
 };
-use crate::terminal::{Key, Rect};
+use crate::terminal::{HoverEvent, Key, Position, Rect, Size, TerminalCursor};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct ComponentId {
@@ -85,6 +88,8 @@ impl DerefMut for DynamicTemplate {
 }
 
 pub(crate) trait Renderable {
+    fn as_any(&self) -> &dyn Any;
+
     fn change(&mut self, properties: DynamicProperties) -> ShouldRender;
 
     fn resize(&mut self, frame: Rect) -> ShouldRender;
@@ -97,17 +102,40 @@ pub(crate) trait Renderable {
 
     fn notify_binding_queries(&self, bindings: &[Option<NamedBindingQuery>], keys: &[Key]);
 
+    fn notify_continuations(&self, continuations: &[KeyContinuation], keys: &[Key]);
+
     fn run_command(
         &self,
         bindings: &DynamicBindings,
         command_id: CommandId,
         pressed: &[Key],
+        position: Option<Position>,
+        timestamp: Instant,
     ) -> Option<DynamicMessage>;
 
-    fn tick(&self) -> Option<DynamicMessage>;
+    fn hover(&self, event: HoverEvent) -> Option<DynamicMessage>;
+
+    fn focus(&self, focused: bool) -> Option<DynamicMessage>;
+
+    fn key_release(&self, key: Key) -> Option<DynamicMessage>;
+
+    fn cursor(&self) -> Option<TerminalCursor>;
+
+    fn tick(&self, delta: Duration) -> Option<DynamicMessage>;
+
+    fn error(&self, error: &ReportedError) -> Option<DynamicMessage>;
+
+    fn render_priority(&self) -> RenderPriority;
+
+    fn throttle(&self) -> Option<Duration>;
 }
 
 impl<ComponentT: Component> Renderable for ComponentT {
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
     #[inline]
     fn update(&mut self, message: DynamicMessage) -> ShouldRender {
         <Self as Component>::update(
@@ -149,19 +177,60 @@ impl<ComponentT: Component> Renderable for ComponentT {
         <Self as Component>::notify_binding_queries(self, bindings, keys);
     }
 
+    fn notify_continuations(&self, continuations: &[KeyContinuation], keys: &[Key]) {
+        <Self as Component>::notify_continuations(self, continuations, keys);
+    }
+
     #[inline]
     fn run_command(
         &self,
         bindings: &DynamicBindings,
         command_id: CommandId,
         keys: &[Key],
+        position: Option<Position>,
+        timestamp: Instant,
     ) -> Option<DynamicMessage> {
-        bindings.execute_command(self, command_id, keys)
+        bindings.execute_command(self, command_id, keys, position, timestamp)
+    }
+
+    #[inline]
+    fn hover(&self, event: HoverEvent) -> Option<DynamicMessage> {
+        <Self as Component>::hover(self, event).map(|message| DynamicMessage(Box::new(message)))
+    }
+
+    #[inline]
+    fn focus(&self, focused: bool) -> Option<DynamicMessage> {
+        <Self as Component>::focus(self, focused).map(|message| DynamicMessage(Box::new(message)))
     }
 
     #[inline]
-    fn tick(&self) -> Option<DynamicMessage> {
-        <Self as Component>::tick(self).map(|message| DynamicMessage(Box::new(message)))
+    fn key_release(&self, key: Key) -> Option<DynamicMessage> {
+        <Self as Component>::key_release(self, key).map(|message| DynamicMessage(Box::new(message)))
+    }
+
+    #[inline]
+    fn cursor(&self) -> Option<TerminalCursor> {
+        <Self as Component>::cursor(self)
+    }
+
+    #[inline]
+    fn tick(&self, delta: Duration) -> Option<DynamicMessage> {
+        <Self as Component>::tick(self, delta).map(|message| DynamicMessage(Box::new(message)))
+    }
+
+    #[inline]
+    fn error(&self, error: &ReportedError) -> Option<DynamicMessage> {
+        <Self as Component>::error(self, error).map(|message| DynamicMessage(Box::new(message)))
+    }
+
+    #[inline]
+    fn render_priority(&self) -> RenderPriority {
+        <Self as Component>::render_priority(self)
+    }
+
+    #[inline]
+    fn throttle(&self) -> Option<Duration> {
+        <Self as Component>::throttle(self)
     }
 }
 
@@ -172,14 +241,25 @@ pub(crate) trait Template {
 
     fn generate_id(&self, id: u64) -> ComponentId;
 
+    #[allow(clippy::too_many_arguments)]
     fn create(
         &mut self,
         id: ComponentId,
         frame: Rect,
         sender: Box<dyn MessageSender>,
+        capabilities: SharedCapabilities,
+        theme: SharedTheme,
+        shared_frame: SharedFrame,
+        services: SharedServices,
+        context: SharedContext,
+        clock: SharedClock,
     ) -> (Box<dyn Renderable + 'static>, DynamicBindings);
 
     fn dynamic_properties(&mut self) -> DynamicProperties;
+
+    /// The size hint of the component, or the default hint if the properties
+    /// were already used.
+    fn size_hint(&self, available: Size) -> SizeHint;
 }
 
 pub(crate) struct ComponentDef<ComponentT: Component> {
@@ -224,8 +304,23 @@ impl<ComponentT: Component> Template for ComponentDef<ComponentT> {
         component_id: ComponentId,
         frame: Rect,
         sender: Box<dyn MessageSender>,
+        capabilities: SharedCapabilities,
+        theme: SharedTheme,
+        shared_frame: SharedFrame,
+        services: SharedServices,
+        context: SharedContext,
+        clock: SharedClock,
     ) -> (Box<dyn Renderable>, DynamicBindings) {
-        let link = ComponentLink::new(sender, component_id);
+        let link = ComponentLink::new(
+            sender,
+            capabilities,
+            theme,
+            shared_frame,
+            services,
+            context,
+            clock,
+            component_id,
+        );
         (
             Box::new(ComponentT::create(self.properties_unwrap(), frame, link)),
             DynamicBindings::new::<ComponentT>(),
@@ -236,4 +331,12 @@ impl<ComponentT: Component> Template for ComponentDef<ComponentT> {
     fn dynamic_properties(&mut self) -> DynamicProperties {
         DynamicProperties(Box::new(self.properties_unwrap()))
     }
+
+    #[inline]
+    fn size_hint(&self, available: Size) -> SizeHint {
+        self.properties
+            .as_ref()
+            .map(|properties| ComponentT::size_hint(properties, available))
+            .unwrap_or_default()
+    }
 }
@@ -0,0 +1,164 @@
+//! Timers scheduled by components, see
+//! [`ComponentLink::send_after`](../struct.ComponentLink.html#method.send_after)
+//! and [`ComponentLink::send_every`](../struct.ComponentLink.html#method.send_every).
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::template::{ComponentId, DynamicMessage};
+
+/// A handle to a timer scheduled by a component, used to cancel it.
+///
+/// Dropping the handle does not cancel the timer. Timers are also cancelled
+/// when the component which scheduled them is unmounted.
+#[derive(Clone)]
+pub struct TimerHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TimerHandle {
+    /// Cancels the timer. No more messages are sent once this returns.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the timer was cancelled, either with
+    /// [`cancel`](#method.cancel) or by unmounting the component.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for TimerHandle {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("TimerHandle")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Creates the message sent when a timer fires, `None` once it has no more
+/// messages to send.
+type MessageFn = Box<dyn FnMut() -> Option<DynamicMessage> + Send>;
+
+pub(crate) struct Timer {
+    component_id: ComponentId,
+    deadline: Instant,
+    /// The period of a repeating timer, `None` for a timer firing once.
+    interval: Option<Duration>,
+    message: MessageFn,
+    cancelled: Arc<AtomicBool>,
+    /// Whether a timer firing once has fired.
+    fired: bool,
+}
+
+impl Timer {
+    pub(crate) fn new(
+        component_id: ComponentId,
+        deadline: Instant,
+        interval: Option<Duration>,
+        message: impl FnMut() -> Option<DynamicMessage> + Send + 'static,
+    ) -> (Self, TimerHandle) {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let timer = Self {
+            component_id,
+            deadline,
+            interval,
+            message: Box::new(message),
+            cancelled: cancelled.clone(),
+            fired: false,
+        };
+        (timer, TimerHandle { cancelled })
+    }
+
+    pub(crate) fn component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn is_active(&self) -> bool {
+        !self.fired && !self.is_cancelled()
+    }
+}
+
+impl fmt::Debug for Timer {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Timer")
+            .field("component_id", &self.component_id)
+            .field("deadline", &self.deadline)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+/// The timers scheduled by the mounted components.
+#[derive(Default)]
+pub(crate) struct Timers {
+    timers: Vec<Timer>,
+}
+
+impl Timers {
+    pub(crate) fn schedule(&mut self, timer: Timer) {
+        self.timers.push(timer);
+    }
+
+    /// Returns when the next timer which wasn't cancelled fires.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.timers
+            .iter()
+            .filter(|timer| timer.is_active())
+            .map(|timer| timer.deadline)
+            .min()
+    }
+
+    /// Cancels the timers of a component which is no longer mounted.
+    pub(crate) fn remove_component(&mut self, component_id: ComponentId) {
+        self.timers.retain(|timer| {
+            if timer.component_id == component_id {
+                timer.cancelled.store(true, Ordering::Relaxed);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Returns the messages of the timers due at `now`, in the order of their
+    /// deadlines. Repeating timers are rescheduled, they fire at most once per
+    /// call even if they are more than one period behind.
+    pub(crate) fn fire(&mut self, now: Instant) -> Vec<(ComponentId, DynamicMessage)> {
+        self.timers.retain(Timer::is_active);
+        self.timers.sort_by_key(|timer| timer.deadline);
+        let mut messages = Vec::new();
+        for timer in self.timers.iter_mut() {
+            if timer.deadline > now {
+                break;
+            }
+            match (timer.message)() {
+                Some(message) => messages.push((timer.component_id, message)),
+                None => timer.interval = None,
+            }
+            match timer.interval {
+                Some(interval) => {
+                    timer.deadline += interval;
+                    if timer.deadline <= now {
+                        timer.deadline = now + interval;
+                    }
+                }
+                None => timer.fired = true,
+            }
+        }
+        self.timers.retain(Timer::is_active);
+        messages
+    }
+}
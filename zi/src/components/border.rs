@@ -1,20 +1,22 @@
-use std::iter;
+use std::{cmp, iter};
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
-    Callback, Canvas, Component, ComponentLink, Item, Layout, Rect, ShouldRender, Size, Style,
+    terminal::SizeExt, Canvas, Children, Component, ComponentLink, Item, Layout, Rect,
+    ShouldRender, Size, SizeHint, Style,
 };
 
 pub struct BorderProperties {
-    pub component: Callback<(), Layout>,
+    pub component: Children,
     pub style: Style,
     pub stroke: BorderStroke,
     pub title: Option<(String, Style)>,
 }
 
 impl BorderProperties {
-    pub fn new(component: impl Fn() -> Layout + 'static) -> Self {
+    pub fn new(component: impl Into<Children>) -> Self {
         Self {
-            component: (move |_| component()).into(),
+            component: component.into(),
             style: Style::default(),
             stroke: BorderStroke::default(),
             title: None,
@@ -60,6 +62,25 @@ impl Component for Border {
         ShouldRender::Yes
     }
 
+    /// The size hint of the content plus the border, wide enough to show the
+    /// title if there is one.
+    fn size_hint(properties: &Self::Properties, available: Size) -> SizeHint {
+        let hint = properties
+            .component
+            .view()
+            .size_hint(available.offset(-2, -2));
+        let title_width = properties
+            .title
+            .as_ref()
+            .map_or(0, |(title, _)| UnicodeWidthStr::width(title.as_str()) + 4);
+        let preferred = hint.preferred.offset(2, 2);
+        SizeHint::new(
+            hint.min.offset(2, 2),
+            Size::new(cmp::max(preferred.width, title_width), preferred.height),
+            hint.max.offset(2, 2).max(Size::new(title_width, 0)),
+        )
+    }
+
     fn view(&self) -> Layout {
         let stroke = &self.properties.stroke;
 
@@ -123,7 +144,7 @@ impl Component for Border {
             Item::fixed(1)(top_border),
             Item::auto(Layout::row([
                 Item::fixed(1)(left_border),
-                Item::auto(self.properties.component.emit(())),
+                Item::auto(self.properties.component.view()),
                 Item::fixed(1)(right_border),
             ])),
             Item::fixed(1)(bottom_border),
@@ -0,0 +1,663 @@
+//! A side-by-side diff viewer.
+//!
+//! A [`Diff`](struct.Diff.html) is computed once, either from two texts or by
+//! parsing a unified diff, and it is shown by the
+//! [`DiffView`](struct.DiffView.html) component. Deletions and additions in
+//! the same hunk are aligned on the same rows and both sides always scroll
+//! together.
+use std::{cmp, fmt::Write, rc::Rc};
+
+use super::scroll::{ScrollModel, ScrollSubscription};
+use crate::{
+    Bindings, Canvas, Colour, Component, ComponentLink, Item, Key, Layout, Position, Rect,
+    ShouldRender, Size, Style,
+};
+
+/// A line on one side of a diff, with its 1-based line number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffLine {
+    pub number: usize,
+    pub text: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffRowKind {
+    /// The line is the same on both sides.
+    Context,
+    /// A line was removed from the old text.
+    Removed,
+    /// A line was added to the new text.
+    Added,
+    /// A line was removed and another one added in its place.
+    Changed,
+    /// Unchanged lines omitted by a unified diff.
+    Separator,
+}
+
+/// A row of a side-by-side diff.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffRow {
+    pub kind: DiffRowKind,
+    pub old: Option<DiffLine>,
+    pub new: Option<DiffLine>,
+}
+
+/// The aligned rows of a side-by-side diff and the rows where hunks start.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    rows: Vec<DiffRow>,
+    hunks: Vec<usize>,
+}
+
+impl Diff {
+    /// Computes the line by line diff between two texts.
+    pub fn from_texts(old: &str, new: &str) -> Self {
+        let old: Vec<_> = old.lines().collect();
+        let new: Vec<_> = new.lines().collect();
+        let mut builder = DiffBuilder::default();
+        for edit in diff_lines(&old, &new) {
+            match edit {
+                Edit::Equal(old_index, new_index) => builder.context(
+                    DiffLine {
+                        number: old_index + 1,
+                        text: old[old_index].into(),
+                    },
+                    DiffLine {
+                        number: new_index + 1,
+                        text: new[new_index].into(),
+                    },
+                ),
+                Edit::Delete(old_index) => builder.removed(DiffLine {
+                    number: old_index + 1,
+                    text: old[old_index].into(),
+                }),
+                Edit::Insert(new_index) => builder.added(DiffLine {
+                    number: new_index + 1,
+                    text: new[new_index].into(),
+                }),
+            }
+        }
+        builder.finish()
+    }
+
+    /// Parses a unified diff, e.g. the output of `diff -u` or `git diff` for
+    /// a single file. Lines before the first hunk header are ignored.
+    pub fn parse_unified(unified: &str) -> Self {
+        let mut builder = DiffBuilder::default();
+        let (mut old_number, mut new_number) = (0, 0);
+        let mut in_hunk = false;
+        for line in unified.lines() {
+            if let Some(header) = line.strip_prefix("@@") {
+                if let Some((old_start, new_start)) = parse_hunk_header(header) {
+                    if in_hunk || old_start > 1 || new_start > 1 {
+                        builder.separator();
+                    }
+                    old_number = old_start;
+                    new_number = new_start;
+                    in_hunk = true;
+                }
+                continue;
+            }
+            if !in_hunk {
+                continue;
+            }
+            if let Some(text) = line.strip_prefix('-') {
+                builder.removed(DiffLine {
+                    number: old_number,
+                    text: text.into(),
+                });
+                old_number += 1;
+            } else if let Some(text) = line.strip_prefix('+') {
+                builder.added(DiffLine {
+                    number: new_number,
+                    text: text.into(),
+                });
+                new_number += 1;
+            } else if line.is_empty() || line.starts_with(' ') {
+                // Some tools strip the trailing space of empty context lines
+                let text = line.get(1..).unwrap_or("");
+                builder.context(
+                    DiffLine {
+                        number: old_number,
+                        text: text.into(),
+                    },
+                    DiffLine {
+                        number: new_number,
+                        text: text.into(),
+                    },
+                );
+                old_number += 1;
+                new_number += 1;
+            }
+            // Other lines, e.g. "\ No newline at end of file" are ignored
+        }
+        builder.finish()
+    }
+
+    pub fn rows(&self) -> &[DiffRow] {
+        &self.rows
+    }
+
+    /// The indices of the rows where each hunk starts.
+    pub fn hunks(&self) -> &[usize] {
+        &self.hunks
+    }
+
+    /// Returns `true` if the two sides are the same.
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+/// Accumulates rows, aligning consecutive removed and added lines.
+#[derive(Default)]
+struct DiffBuilder {
+    diff: Diff,
+    removed: Vec<DiffLine>,
+    added: Vec<DiffLine>,
+}
+
+impl DiffBuilder {
+    fn context(&mut self, old: DiffLine, new: DiffLine) {
+        self.flush();
+        self.diff.rows.push(DiffRow {
+            kind: DiffRowKind::Context,
+            old: Some(old),
+            new: Some(new),
+        });
+    }
+
+    fn removed(&mut self, line: DiffLine) {
+        self.removed.push(line);
+    }
+
+    fn added(&mut self, line: DiffLine) {
+        self.added.push(line);
+    }
+
+    fn separator(&mut self) {
+        self.flush();
+        self.diff.rows.push(DiffRow {
+            kind: DiffRowKind::Separator,
+            old: None,
+            new: None,
+        });
+    }
+
+    fn flush(&mut self) {
+        if self.removed.is_empty() && self.added.is_empty() {
+            return;
+        }
+        self.diff.hunks.push(self.diff.rows.len());
+        let num_rows = cmp::max(self.removed.len(), self.added.len());
+        let mut removed = self.removed.drain(..);
+        let mut added = self.added.drain(..);
+        for _ in 0..num_rows {
+            let (old, new) = (removed.next(), added.next());
+            let kind = match (&old, &new) {
+                (Some(_), Some(_)) => DiffRowKind::Changed,
+                (Some(_), None) => DiffRowKind::Removed,
+                _ => DiffRowKind::Added,
+            };
+            self.diff.rows.push(DiffRow { kind, old, new });
+        }
+    }
+
+    fn finish(mut self) -> Diff {
+        self.flush();
+        self.diff
+    }
+}
+
+/// Parses the line numbers from the rest of a hunk header, i.e. `-a,b +c,d @@`.
+fn parse_hunk_header(header: &str) -> Option<(usize, usize)> {
+    let mut ranges = header.split_whitespace();
+    let parse_start = |range: Option<&str>, sign: char| -> Option<usize> {
+        range?.strip_prefix(sign)?.split(',').next()?.parse().ok()
+    };
+    let old_start = parse_start(ranges.next(), '-')?;
+    let new_start = parse_start(ranges.next(), '+')?;
+    Some((old_start, new_start))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Computes a shortest edit script between two sequences of lines using
+/// Myers' algorithm.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let index = |k: isize| (k + max + 1) as usize;
+
+    // Forward pass, recording the furthest reaching paths for every edit
+    // distance
+    let mut furthest = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+    'search: for distance in 0..=max {
+        trace.push(furthest.clone());
+        for k in (-distance..=distance).step_by(2) {
+            let mut x = if k == -distance
+                || (k != distance && furthest[index(k - 1)] < furthest[index(k + 1)])
+            {
+                furthest[index(k + 1)]
+            } else {
+                furthest[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Backtrack from the end to recover the edits
+    let mut edits = Vec::with_capacity(cmp::max(n, m) as usize);
+    let (mut x, mut y) = (n, m);
+    for (distance, furthest) in trace.iter().enumerate().rev() {
+        let distance = distance as isize;
+        let k = x - y;
+        let previous_k = if k == -distance
+            || (k != distance && furthest[index(k - 1)] < furthest[index(k + 1)])
+        {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = furthest[index(previous_k)];
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize, y as usize));
+        }
+        if distance > 0 {
+            if x == previous_x {
+                edits.push(Edit::Insert(previous_y as usize));
+            } else {
+                edits.push(Edit::Delete(previous_x as usize));
+            }
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    edits.reverse();
+    edits
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffStyle {
+    pub context: Style,
+    pub removed: Style,
+    pub added: Style,
+    /// The style of the empty side of added and removed rows.
+    pub filler: Style,
+    /// The style of the line numbers, separators and the divider.
+    pub gutter: Style,
+}
+
+impl Default for DiffStyle {
+    fn default() -> Self {
+        const DARK0_HARD: Colour = Colour::rgb(29, 32, 33);
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const DARK1: Colour = Colour::rgb(60, 56, 54);
+        const GRAY: Colour = Colour::rgb(146, 131, 116);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const DARK_RED: Colour = Colour::rgb(92, 44, 40);
+        const DARK_GREEN: Colour = Colour::rgb(58, 72, 36);
+
+        Self {
+            context: Style::normal(DARK0_SOFT, LIGHT2),
+            removed: Style::normal(DARK_RED, LIGHT2),
+            added: Style::normal(DARK_GREEN, LIGHT2),
+            filler: Style::normal(DARK0_HARD, GRAY),
+            gutter: Style::normal(DARK1, GRAY),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DiffViewProperties {
+    pub diff: Rc<Diff>,
+    pub style: DiffStyle,
+    pub focused: bool,
+    /// Shares the scroll offset with other components.
+    pub scroll: Option<ScrollModel>,
+}
+
+impl DiffViewProperties {
+    pub fn new(diff: impl Into<Rc<Diff>>) -> Self {
+        Self {
+            diff: diff.into(),
+            style: DiffStyle::default(),
+            focused: false,
+            scroll: None,
+        }
+    }
+
+    pub fn style(mut self, style: DiffStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: ScrollModel) -> Self {
+        self.scroll = Some(scroll);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    Scroll(isize),
+    ScrollPages(isize),
+    FirstRow,
+    LastRow,
+    NextHunk,
+    PreviousHunk,
+    Scrolled,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Old,
+    New,
+}
+
+pub struct DiffView {
+    properties: DiffViewProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    scroll: ScrollModel,
+    scroll_subscription: ScrollSubscription,
+}
+
+impl DiffView {
+    /// The index of the first visible row.
+    pub fn offset(&self) -> usize {
+        self.scroll.offset()
+    }
+
+    fn update_extent(&self) {
+        self.scroll
+            .set_extent(self.properties.diff.rows.len(), self.frame.size.height);
+    }
+
+    fn draw_side(&self, side: Side, size: Size) -> Canvas {
+        let DiffStyle {
+            context,
+            removed,
+            added,
+            filler,
+            gutter,
+        } = self.properties.style;
+        let rows = &self.properties.diff.rows;
+        let max_number = rows
+            .iter()
+            .filter_map(|row| match side {
+                Side::Old => row.old.as_ref(),
+                Side::New => row.new.as_ref(),
+            })
+            .map(|line| line.number)
+            .max()
+            .unwrap_or(0);
+        let gutter_width = max_number.to_string().len() + 1;
+
+        let mut canvas = Canvas::new(size);
+        canvas.clear(filler);
+        let mut number = String::new();
+        for (y, row) in rows
+            .iter()
+            .skip(self.offset())
+            .take(size.height)
+            .enumerate()
+        {
+            let line = match side {
+                Side::Old => row.old.as_ref(),
+                Side::New => row.new.as_ref(),
+            };
+            let style = match (row.kind, side, line) {
+                (DiffRowKind::Separator, _, _) => {
+                    canvas.clear_region(
+                        Rect::new(Position::new(0, y), Size::new(size.width, 1)),
+                        gutter,
+                    );
+                    canvas.draw_str(0, y, gutter, &"┈".repeat(size.width));
+                    continue;
+                }
+                (_, _, None) => continue,
+                (DiffRowKind::Context, _, _) => context,
+                (_, Side::Old, _) => removed,
+                (_, Side::New, _) => added,
+            };
+            let line = line.expect("rows without a line are skipped");
+
+            number.clear();
+            let _ = write!(number, "{:>width$} ", line.number, width = gutter_width - 1);
+            let x = canvas.draw_str(0, y, gutter, &number);
+            canvas.clear_region(
+                Rect::new(
+                    Position::new(x, y),
+                    Size::new(size.width.saturating_sub(x), 1),
+                ),
+                style,
+            );
+            canvas.draw_str(x, y, style, &line.text.replace('\t', "    "));
+        }
+        canvas
+    }
+}
+
+impl Component for DiffView {
+    type Message = Message;
+    type Properties = DiffViewProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let scroll = properties.scroll.clone().unwrap_or_default();
+        let scroll_subscription = scroll.subscribe(link.callback(|_| Message::Scrolled));
+        let view = Self {
+            properties,
+            frame,
+            link,
+            scroll,
+            scroll_subscription,
+        };
+        view.update_extent();
+        view
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties.scroll != properties.scroll {
+            self.scroll = properties.scroll.clone().unwrap_or_default();
+            self.scroll_subscription = self
+                .scroll
+                .subscribe(self.link.callback(|_| Message::Scrolled));
+        }
+        self.properties = properties;
+        self.update_extent();
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        self.update_extent();
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        let hunks = &self.properties.diff.hunks;
+        let offset = self.scroll.offset();
+        match message {
+            Message::Scroll(rows) => self.scroll.scroll_by(rows),
+            Message::ScrollPages(pages) => self
+                .scroll
+                .scroll_by(pages * cmp::max(self.frame.size.height, 1) as isize),
+            Message::FirstRow => self.scroll.set_offset(0),
+            Message::LastRow => self.scroll.set_offset(self.scroll.max_offset()),
+            Message::NextHunk => {
+                if let Some(&hunk) = hunks.iter().find(|&&hunk| hunk > offset) {
+                    self.scroll.set_offset(hunk);
+                }
+            }
+            Message::PreviousHunk => {
+                if let Some(&hunk) = hunks.iter().rev().find(|&&hunk| hunk < offset) {
+                    self.scroll.set_offset(hunk);
+                }
+            }
+            Message::Scrolled => {}
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let width = self.frame.size.width.saturating_sub(DIVIDER_WIDTH);
+        let height = self.frame.size.height;
+        let old_width = width / 2;
+        let new_width = width - old_width;
+
+        let mut divider = Canvas::new(Size::new(DIVIDER_WIDTH, height));
+        divider.clear_with(self.properties.style.gutter, "│");
+
+        Layout::row([
+            Item::fixed(old_width)(self.draw_side(Side::Old, Size::new(old_width, height))),
+            Item::fixed(DIVIDER_WIDTH)(divider),
+            Item::auto(self.draw_side(Side::New, Size::new(new_width, height))),
+        ])
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("row-down", || Message::Scroll(1))
+            .with([Key::Char('j')])
+            .with([Key::Down])
+            .with([Key::Ctrl('n')]);
+        bindings
+            .command("row-up", || Message::Scroll(-1))
+            .with([Key::Char('k')])
+            .with([Key::Up])
+            .with([Key::Ctrl('p')]);
+        bindings
+            .command("page-down", || Message::ScrollPages(1))
+            .with([Key::Char(' ')])
+            .with([Key::PageDown])
+            .with([Key::Ctrl('v')]);
+        bindings
+            .command("page-up", || Message::ScrollPages(-1))
+            .with([Key::Char('b')])
+            .with([Key::PageUp])
+            .with([Key::Alt('v')]);
+        bindings
+            .command("first-row", || Message::FirstRow)
+            .with([Key::Char('g')])
+            .with([Key::Alt('<')])
+            .with([Key::Home]);
+        bindings
+            .command("last-row", || Message::LastRow)
+            .with([Key::Char('G')])
+            .with([Key::Alt('>')])
+            .with([Key::End]);
+        bindings
+            .command("next-hunk", || Message::NextHunk)
+            .with([Key::Char('n')])
+            .with([Key::Char(']')]);
+        bindings
+            .command("previous-hunk", || Message::PreviousHunk)
+            .with([Key::Char('p')])
+            .with([Key::Char('[')]);
+    }
+}
+
+const DIVIDER_WIDTH: usize = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(diff: &Diff) -> Vec<(DiffRowKind, Option<usize>, Option<usize>)> {
+        diff.rows()
+            .iter()
+            .map(|row| {
+                (
+                    row.kind,
+                    row.old.as_ref().map(|line| line.number),
+                    row.new.as_ref().map(|line| line.number),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diff_texts_aligns_changes() {
+        use DiffRowKind::*;
+
+        let diff = Diff::from_texts("a\nb\nc\nd\ne\n", "a\nB\nc\nd\nd2\ne\n");
+        assert_eq!(
+            numbers(&diff),
+            vec![
+                (Context, Some(1), Some(1)),
+                (Changed, Some(2), Some(2)),
+                (Context, Some(3), Some(3)),
+                (Context, Some(4), Some(4)),
+                (Added, None, Some(5)),
+                (Context, Some(5), Some(6)),
+            ]
+        );
+        assert_eq!(diff.hunks(), &[1, 4]);
+
+        assert!(Diff::from_texts("same\n", "same\n").is_empty());
+        assert_eq!(
+            numbers(&Diff::from_texts("", "new\n")),
+            vec![(Added, None, Some(1))]
+        );
+    }
+
+    #[test]
+    fn parse_unified_diff() {
+        use DiffRowKind::*;
+
+        let unified = "\
+--- a/file
++++ b/file
+@@ -10,3 +10,2 @@ fn main() {
+ context
+-removed
+-also removed
++added
+@@ -20 +19,2 @@
+ more
++appended
+";
+        let diff = Diff::parse_unified(unified);
+        assert_eq!(
+            numbers(&diff),
+            vec![
+                (Separator, None, None),
+                (Context, Some(10), Some(10)),
+                (Changed, Some(11), Some(11)),
+                (Removed, Some(12), None),
+                (Separator, None, None),
+                (Context, Some(20), Some(19)),
+                (Added, None, Some(20)),
+            ]
+        );
+        assert_eq!(diff.hunks(), &[2, 6]);
+        assert_eq!(diff.rows()[2].new.as_ref().unwrap().text, "added");
+    }
+}
@@ -0,0 +1,230 @@
+//! A wrapper that shows the errors reported by components as toasts.
+use std::{
+    cmp,
+    time::{Duration, Instant},
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::place;
+use crate::{
+    Bindings, Canvas, Children, Colour, Component, ComponentLink, Layout, Position, Rect,
+    ReportedError, ShouldRender, Size, Style,
+};
+
+pub struct ErrorToastProperties {
+    pub content: Children,
+    pub style: Style,
+    /// How long a toast is shown for.
+    pub duration: Duration,
+    /// The maximum number of toasts shown at once, older ones are dismissed
+    /// first.
+    pub max_toasts: usize,
+}
+
+impl ErrorToastProperties {
+    pub fn new(content: impl Into<Children>) -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const BRIGHT_RED: Colour = Colour::rgb(251, 73, 52);
+
+        Self {
+            content: content.into(),
+            style: Style::bold(BRIGHT_RED, DARK0_SOFT),
+            duration: Duration::from_secs(5),
+            max_toasts: 3,
+        }
+    }
+
+    pub fn style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn max_toasts(mut self, max_toasts: usize) -> Self {
+        self.max_toasts = max_toasts;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Error(String),
+    Tick,
+}
+
+/// Wraps a layout, usually the root of the app, and shows every error
+/// reported with
+/// [`ComponentLink::report_error`](../../struct.ComponentLink.html#method.report_error)
+/// in a toast at the bottom right of its frame, above the content. Toasts are
+/// dismissed after a while.
+pub struct ErrorToast {
+    properties: ErrorToastProperties,
+    frame: Rect,
+    toasts: Vec<(Instant, String)>,
+    link: ComponentLink<Self>,
+}
+
+impl ErrorToast {
+    /// The errors currently shown, oldest first.
+    pub fn errors(&self) -> impl Iterator<Item = &str> {
+        self.toasts.iter().map(|(_, error)| error.as_str())
+    }
+}
+
+impl Component for ErrorToast {
+    type Message = Message;
+    type Properties = ErrorToastProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            toasts: Vec::new(),
+            link,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::Error(error) => {
+                self.toasts.push((self.link.now(), error));
+                let excess = self.toasts.len().saturating_sub(self.properties.max_toasts);
+                self.toasts.drain(..excess);
+            }
+            Message::Tick => {
+                let duration = self.properties.duration;
+                let num_toasts = self.toasts.len();
+                let now = self.link.now();
+                self.toasts
+                    .retain(|(since, _)| now.saturating_duration_since(*since) < duration);
+                if self.toasts.len() == num_toasts {
+                    return ShouldRender::No;
+                }
+            }
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let style = self.properties.style;
+        let max_width = self.frame.size.width.saturating_sub(2 * MARGIN);
+        let mut layers = vec![self.properties.content.view()];
+        let mut bottom = self.frame.size.height.saturating_sub(MARGIN);
+        for (_, error) in self.toasts.iter().rev() {
+            if bottom == 0 {
+                break;
+            }
+            let text = error.lines().next().unwrap_or("");
+            let size = Size::new(cmp::min(text.width() + 2 * PADDING, max_width), 1);
+            let mut canvas = Canvas::new(size);
+            canvas.clear(style);
+            canvas.draw_str(PADDING, 0, style, text);
+
+            bottom -= 1;
+            let origin = Position::new(
+                self.frame.size.width.saturating_sub(size.width + MARGIN),
+                bottom,
+            );
+            layers.push(place(Rect::new(origin, size), canvas.into()));
+            bottom = bottom.saturating_sub(SPACING);
+        }
+        Layout::stack(layers)
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_tickable(!self.toasts.is_empty());
+    }
+
+    fn tick(&self, _delta: Duration) -> Option<Self::Message> {
+        Some(Message::Tick)
+    }
+
+    fn error(&self, error: &ReportedError) -> Option<Self::Message> {
+        Some(Message::Error(error.to_string()))
+    }
+}
+
+const MARGIN: usize = 1;
+const PADDING: usize = 1;
+const SPACING: usize = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::ComponentSelector,
+        components::text::{Text, TextProperties},
+        testing::Harness,
+        ComponentExt, Key,
+    };
+
+    struct Failing {
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Failing {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            Self { link }
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new())
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("fail", [Key::Char('x')], |this: &Self| {
+                this.link.report_error("disk full");
+                None
+            });
+        }
+    }
+
+    #[test]
+    fn shows_reported_errors_until_dismissed() {
+        let mut harness = Harness::new(
+            Size::new(30, 5),
+            ErrorToast::with(ErrorToastProperties::new(|| Failing::with(()))),
+        );
+        let errors = |harness: &Harness| {
+            harness
+                .app()
+                .with_component(
+                    &ComponentSelector::of::<ErrorToast>(),
+                    |toast: &ErrorToast| toast.errors().map(String::from).collect::<Vec<_>>(),
+                )
+                .unwrap()
+        };
+
+        harness.key(Key::Char('x'));
+        assert_eq!(errors(&harness), vec!["disk full"]);
+        // The toast is drawn at the bottom right, above the content
+        assert_eq!(harness.line(3), format!("{:>28}", "disk full"));
+
+        harness.advance(Duration::from_secs(4));
+        assert_eq!(errors(&harness), vec!["disk full"]);
+        harness.advance(Duration::from_secs(1));
+        assert!(errors(&harness).is_empty());
+        assert_eq!(harness.line(3), "");
+    }
+}
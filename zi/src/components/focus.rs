@@ -0,0 +1,155 @@
+//! Constraining focus to part of the UI, e.g. a modal dialog.
+//!
+//! While a [`FocusScope`](struct.FocusScope.html) is active, key presses are
+//! only delivered to the focused components inside it and Tab navigation
+//! cycles through the focusable components inside it. When the scope is
+//! deactivated or unmounted, the focus from before it was activated is
+//! restored.
+//!
+//! ```
+//! # use zi::prelude::*;
+//! # use zi::components::{
+//! #     focus::{FocusScope, FocusScopeProperties},
+//! #     text::{Text, TextProperties},
+//! # };
+//! let dialog = FocusScope::with(FocusScopeProperties::new(|| {
+//!     Text::with(TextProperties::new().content("Are you sure?"))
+//! }));
+//! ```
+use crate::{Bindings, Children, Component, ComponentLink, Layout, Rect, ShouldRender};
+
+#[derive(Clone, PartialEq)]
+pub struct FocusScopeProperties {
+    pub content: Children,
+    /// Whether the scope traps focus, `true` by default.
+    pub active: bool,
+}
+
+impl FocusScopeProperties {
+    pub fn new(content: impl Into<Children>) -> Self {
+        Self {
+            content: content.into(),
+            active: true,
+        }
+    }
+
+    pub fn active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+}
+
+/// Traps focus within its content while active.
+pub struct FocusScope {
+    properties: FocusScopeProperties,
+}
+
+impl Component for FocusScope {
+    type Message = ();
+    type Properties = FocusScopeProperties;
+
+    fn create(properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+        Self { properties }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            self.properties = properties;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn view(&self) -> Layout {
+        self.properties.content.view()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_trap_focus(self.properties.active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{testing::Harness, Canvas, ComponentExt, Item, Key, Size};
+
+    struct Field(&'static str, Rc<RefCell<Vec<String>>>);
+
+    impl Component for Field {
+        type Message = String;
+        type Properties = (&'static str, Rc<RefCell<Vec<String>>>);
+
+        fn create((name, log): Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self(name, log)
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, event: Self::Message) -> ShouldRender {
+            self.1.borrow_mut().push(event);
+            ShouldRender::No
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focusable(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("type", [Key::Char('x')], |this: &Self| {
+                Some(format!("{}:x", this.0))
+            });
+        }
+
+        fn focus(&self, focused: bool) -> Option<Self::Message> {
+            Some(format!("{}{}", self.0, if focused { '+' } else { '-' }))
+        }
+    }
+
+    #[test]
+    fn tab_navigation_is_trapped_by_the_scope() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let layout = |active| {
+            let log = log.clone();
+            let field = move |name| Field::with_key(name, (name, log.clone()));
+            let dialog = FocusScopeProperties::new({
+                let field = field.clone();
+                move || Layout::column([Item::auto(field("c")), Item::auto(field("d"))])
+            })
+            .active(active);
+            Layout::row([
+                Item::auto(field("a")),
+                Item::auto(field("b")),
+                Item::auto(FocusScope::with(dialog)),
+            ])
+        };
+        let mut harness = Harness::new(Size::new(9, 4), layout(false));
+        let press = |harness: &mut Harness, keys: &[Key]| {
+            harness.keys(keys.iter().copied());
+            log.borrow_mut().drain(..).collect::<Vec<_>>()
+        };
+        let tab = Key::Char('\t');
+
+        assert_eq!(press(&mut harness, &[tab, tab]), ["a+", "a-", "b+"]);
+        assert_eq!(press(&mut harness, &[Key::Char('x')]), ["b:x"]);
+        assert_eq!(press(&mut harness, &[Key::BackTab]), ["b-", "a+"]);
+
+        // Activating the scope moves focus inside it, Tab cycles within it
+        harness.app_mut().set_root(layout(true));
+        harness.canvas();
+        assert_eq!(press(&mut harness, &[]), ["a-", "c+"]);
+        assert_eq!(press(&mut harness, &[tab, tab]), ["c-", "d+", "d-", "c+"]);
+        assert_eq!(press(&mut harness, &[Key::Char('x')]), ["c:x"]);
+
+        // Deactivating it restores the previous focus
+        harness.app_mut().set_root(layout(false));
+        harness.canvas();
+        assert_eq!(press(&mut harness, &[]), ["c-", "a+"]);
+        assert_eq!(press(&mut harness, &[Key::Char('x')]), ["a:x"]);
+    }
+}
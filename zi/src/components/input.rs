@@ -3,6 +3,7 @@ use unicode_width::UnicodeWidthStr;
 
 use crate::{
     layout::Layout,
+    terminal::Grapheme,
     text::{cursor, CharIndex, TextStorage},
     AnyCharacter, Bindings, Callback, Canvas, Colour, Component, ComponentLink, Key, Rect,
     ShouldRender, Style,
@@ -95,6 +96,12 @@ impl Component for Input {
                 cursor.move_right(&new_content);
                 content_change = Some(new_content);
             }
+            Message::InsertGrapheme(grapheme) => {
+                let mut new_content = self.properties.content.clone();
+                cursor.insert_chars(&mut new_content, grapheme.chars());
+                cursor.move_right(&new_content);
+                content_change = Some(new_content);
+            }
             Message::DeleteBackward => {
                 let mut new_content = self.properties.content.clone();
                 cursor.backspace(&mut new_content);
@@ -188,12 +195,13 @@ impl Component for Input {
         bindings.add(
             "insert-character",
             AnyCharacter,
-            |keys: &[Key]| match keys {
-                &[Key::Char(character)]
+            |keys: &[Key]| match *keys {
+                [Key::Char(character)]
                     if character != '\n' && character != '\r' && character != '\t' =>
                 {
                     Some(Message::InsertChar(character))
                 }
+                [Key::Grapheme(grapheme)] => Some(Message::InsertGrapheme(grapheme)),
                 _ => None,
             },
         );
@@ -205,6 +213,7 @@ pub enum Message {
     CursorLeft,
     CursorRight,
     InsertChar(char),
+    InsertGrapheme(Grapheme),
     DeleteBackward,
     DeleteForward,
     StartOfLine,
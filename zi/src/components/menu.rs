@@ -0,0 +1,599 @@
+//! A menu bar with dropdown menus.
+//!
+//! The [`MenuBar`](struct.MenuBar.html) draws the titles of its menus on the
+//! top row, above some content. F10 opens the first menu when the menu bar is
+//! focused. While a menu is open, its dropdown traps focus and is navigated
+//! with the arrow keys: Up and Down select an item, Left and Right move
+//! between menus or in and out of submenus, Enter activates the selected item
+//! and Esc closes the innermost dropdown. Accelerators are only shown next to
+//! the items, binding them is up to the application.
+//!
+//! ```
+//! # use zi::prelude::*;
+//! # use zi::components::{
+//! #     menu::{Menu, MenuBar, MenuBarProperties, MenuItem},
+//! #     text::{Text, TextProperties},
+//! # };
+//! let menu_bar = MenuBar::with(
+//!     MenuBarProperties::new(|| Text::with(TextProperties::new().content("Document")))
+//!         .menu(
+//!             Menu::new("File")
+//!                 .item(MenuItem::new("Save").accelerator("C-x C-s"))
+//!                 .item(
+//!                     MenuItem::new("Export")
+//!                         .submenu([MenuItem::new("HTML"), MenuItem::new("PDF")]),
+//!                 ),
+//!         )
+//!         .focused(true),
+//! );
+//! ```
+use std::cmp;
+use unicode_width::UnicodeWidthStr;
+
+use super::place;
+use crate::{
+    Bindings, Callback, Canvas, Children, Colour, Component, ComponentExt, ComponentLink, Item,
+    Key, Layout, Position, Rect, ShouldRender, Size, Style,
+};
+
+#[derive(Clone)]
+pub struct MenuItem {
+    pub label: String,
+    /// The keys that run the same action, shown next to the label.
+    pub accelerator: Option<String>,
+    /// Called when the item is activated.
+    pub on_select: Option<Callback<()>>,
+    /// Items of a submenu which opens next to the item.
+    pub submenu: Vec<MenuItem>,
+}
+
+impl MenuItem {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            accelerator: None,
+            on_select: None,
+            submenu: Vec::new(),
+        }
+    }
+
+    pub fn accelerator(mut self, accelerator: impl Into<String>) -> Self {
+        self.accelerator = Some(accelerator.into());
+        self
+    }
+
+    pub fn on_select(mut self, on_select: impl Into<Callback<()>>) -> Self {
+        self.on_select = Some(on_select.into());
+        self
+    }
+
+    pub fn submenu(mut self, items: impl IntoIterator<Item = MenuItem>) -> Self {
+        self.submenu.extend(items);
+        self
+    }
+}
+
+#[derive(Clone)]
+pub struct Menu {
+    pub title: String,
+    pub items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn item(mut self, item: MenuItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn items(mut self, items: impl IntoIterator<Item = MenuItem>) -> Self {
+        self.items.extend(items);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MenuBarStyle {
+    /// The style of the menu bar and of the titles of closed menus.
+    pub bar: Style,
+    /// The style of the title of the open menu.
+    pub active: Style,
+    pub dropdown: Style,
+    pub selected: Style,
+    pub accelerator: Style,
+}
+
+impl Default for MenuBarStyle {
+    fn default() -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const DARK1: Colour = Colour::rgb(60, 56, 54);
+        const GRAY_245: Colour = Colour::rgb(146, 131, 116);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
+
+        Self {
+            bar: Style::normal(DARK0_SOFT, LIGHT2),
+            active: Style::bold(BRIGHT_BLUE, DARK0_SOFT),
+            dropdown: Style::normal(DARK1, LIGHT2),
+            selected: Style::bold(BRIGHT_BLUE, DARK0_SOFT),
+            accelerator: Style::normal(DARK1, GRAY_245),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MenuBarProperties {
+    /// The layout drawn under the menu bar.
+    pub content: Children,
+    pub menus: Vec<Menu>,
+    pub style: MenuBarStyle,
+    pub focused: bool,
+}
+
+impl MenuBarProperties {
+    pub fn new(content: impl Into<Children>) -> Self {
+        Self {
+            content: content.into(),
+            menus: Vec::new(),
+            style: MenuBarStyle::default(),
+            focused: false,
+        }
+    }
+
+    pub fn menu(mut self, menu: Menu) -> Self {
+        self.menus.push(menu);
+        self
+    }
+
+    pub fn menus(mut self, menus: impl IntoIterator<Item = Menu>) -> Self {
+        self.menus.extend(menus);
+        self
+    }
+
+    pub fn style(mut self, style: MenuBarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// Opens a menu by index.
+    Open(usize),
+    /// Closes all dropdowns.
+    Close,
+    /// Closes the innermost dropdown.
+    Back,
+    /// Opens the submenu of the selected item, or the next menu if the item
+    /// doesn't have one.
+    Right,
+    /// Closes the innermost submenu, or opens the previous menu if no
+    /// submenu is open.
+    Left,
+    NextItem,
+    PreviousItem,
+    /// Activates the selected item or opens its submenu.
+    Activate,
+}
+
+pub struct MenuBar {
+    properties: MenuBarProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    /// The open menu, if any.
+    open: Option<usize>,
+    /// The selected item in each open dropdown, outermost first.
+    path: Vec<usize>,
+}
+
+impl MenuBar {
+    /// Returns the index of the open menu and the index of the selected item
+    /// in each open dropdown, outermost first.
+    pub fn open_menu(&self) -> Option<(usize, &[usize])> {
+        self.open.map(|menu| (menu, self.path.as_slice()))
+    }
+
+    /// The items of each open dropdown, outermost first.
+    fn dropdowns(&self) -> Vec<&[MenuItem]> {
+        let menu = match self.open.and_then(|menu| self.properties.menus.get(menu)) {
+            Some(menu) => menu,
+            None => return Vec::new(),
+        };
+        let mut dropdowns = vec![menu.items.as_slice()];
+        for &selected in self.path.iter() {
+            match dropdowns.last().and_then(|items| items.get(selected)) {
+                Some(item) if !item.submenu.is_empty() => dropdowns.push(&item.submenu),
+                _ => break,
+            }
+        }
+        dropdowns.truncate(self.path.len());
+        dropdowns
+    }
+
+    fn selected_item(&self) -> Option<&MenuItem> {
+        let items = *self.dropdowns().last()?;
+        items.get(*self.path.last()?)
+    }
+
+    fn open(&mut self, menu: usize) -> ShouldRender {
+        if menu >= self.properties.menus.len() {
+            return ShouldRender::No;
+        }
+        self.open = Some(menu);
+        self.path = vec![0];
+        ShouldRender::Yes
+    }
+
+    fn close(&mut self) -> ShouldRender {
+        if self.open.take().is_none() {
+            return ShouldRender::No;
+        }
+        self.path.clear();
+        ShouldRender::Yes
+    }
+
+    fn cycle_menu(&mut self, forward: bool) -> ShouldRender {
+        let num_menus = self.properties.menus.len();
+        match self.open {
+            Some(menu) if forward => self.open((menu + 1) % num_menus),
+            Some(menu) => self.open((menu + num_menus - 1) % num_menus),
+            None => ShouldRender::No,
+        }
+    }
+
+    fn cycle_item(&mut self, forward: bool) -> ShouldRender {
+        let num_items = match self.dropdowns().last() {
+            Some(items) if !items.is_empty() => items.len(),
+            _ => return ShouldRender::No,
+        };
+        let selected = self.path.last_mut().expect("an open dropdown");
+        *selected = if forward {
+            (*selected + 1) % num_items
+        } else {
+            (*selected + num_items - 1) % num_items
+        };
+        ShouldRender::Yes
+    }
+
+    /// The offset of each menu title on the bar.
+    fn title_offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.properties.menus.iter().scan(0, |offset, menu| {
+            let title_offset = *offset;
+            *offset += UnicodeWidthStr::width(menu.title.as_str()) + 2 * PADDING;
+            Some(title_offset)
+        })
+    }
+
+    fn draw_bar(&self) -> Canvas {
+        let style = self.properties.style;
+        let mut canvas = Canvas::new(Size::new(self.frame.size.width, 1));
+        canvas.clear(style.bar);
+        for (index, (menu, offset)) in self
+            .properties
+            .menus
+            .iter()
+            .zip(self.title_offsets())
+            .enumerate()
+        {
+            let title_style = if self.open == Some(index) {
+                style.active
+            } else {
+                style.bar
+            };
+            let width = UnicodeWidthStr::width(menu.title.as_str()) + 2 * PADDING;
+            canvas.clear_region(
+                Rect::new(Position::new(offset, 0), Size::new(width, 1)),
+                title_style,
+            );
+            canvas.draw_str(offset + PADDING, 0, title_style, &menu.title);
+        }
+        canvas
+    }
+
+    /// Draws the open dropdowns, each next to the selected item of the one
+    /// before it. The frames are relative to the menu bar.
+    fn draw_dropdowns(&self) -> Vec<(Rect, Canvas)> {
+        let style = self.properties.style;
+        let bounds = self.frame.size;
+        let mut origin = Position::new(
+            self.open
+                .and_then(|menu| self.title_offsets().nth(menu))
+                .unwrap_or(0),
+            1,
+        );
+        let mut dropdowns = Vec::new();
+        for (items, &selected) in self.dropdowns().into_iter().zip(self.path.iter()) {
+            let label_width = items
+                .iter()
+                .map(|item| UnicodeWidthStr::width(item.label.as_str()))
+                .max()
+                .unwrap_or(0);
+            let accelerator_width = items
+                .iter()
+                .filter_map(|item| item.accelerator.as_deref())
+                .map(UnicodeWidthStr::width)
+                .max()
+                .map_or(0, |width| width + 2);
+            let submenu_width = if items.iter().any(|item| !item.submenu.is_empty()) {
+                2
+            } else {
+                0
+            };
+            let size = Size::new(
+                label_width + accelerator_width + submenu_width + 2 * PADDING,
+                items.len(),
+            )
+            .min(bounds);
+            let frame = Rect::new(
+                Position::new(
+                    cmp::min(origin.x, bounds.width - size.width),
+                    cmp::min(origin.y, bounds.height - size.height),
+                ),
+                size,
+            );
+
+            let mut canvas = Canvas::new(size);
+            canvas.clear(style.dropdown);
+            for (y, item) in items.iter().enumerate() {
+                let (item_style, accelerator_style) = if y == selected {
+                    (style.selected, style.selected)
+                } else {
+                    (style.dropdown, style.accelerator)
+                };
+                canvas.clear_region(
+                    Rect::new(Position::new(0, y), Size::new(size.width, 1)),
+                    item_style,
+                );
+                canvas.draw_str(PADDING, y, item_style, &item.label);
+                if let Some(accelerator) = item.accelerator.as_deref() {
+                    canvas.draw_str(PADDING + label_width + 2, y, accelerator_style, accelerator);
+                }
+                if !item.submenu.is_empty() {
+                    canvas.draw_str(size.width - PADDING - 1, y, item_style, "▸");
+                }
+            }
+            dropdowns.push((frame, canvas));
+
+            origin = Position::new(frame.origin.x + frame.size.width, frame.origin.y + selected);
+        }
+        dropdowns
+    }
+}
+
+impl Component for MenuBar {
+    type Message = Message;
+    type Properties = MenuBarProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            link,
+            open: None,
+            path: Vec::new(),
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        // Close the dropdowns whose items are gone
+        let num_open = self.dropdowns().len();
+        self.path.truncate(num_open);
+        if self.path.is_empty() {
+            self.open = None;
+        }
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::Open(menu) => self.open(menu),
+            Message::Close => self.close(),
+            Message::Back if self.path.len() > 1 => {
+                self.path.pop();
+                ShouldRender::Yes
+            }
+            Message::Back => self.close(),
+            Message::Right => match self.selected_item() {
+                Some(item) if !item.submenu.is_empty() => {
+                    self.path.push(0);
+                    ShouldRender::Yes
+                }
+                _ => self.cycle_menu(true),
+            },
+            Message::Left if self.path.len() > 1 => {
+                self.path.pop();
+                ShouldRender::Yes
+            }
+            Message::Left => self.cycle_menu(false),
+            Message::NextItem => self.cycle_item(true),
+            Message::PreviousItem => self.cycle_item(false),
+            Message::Activate => match self.selected_item() {
+                Some(item) if !item.submenu.is_empty() => {
+                    self.path.push(0);
+                    ShouldRender::Yes
+                }
+                Some(item) => {
+                    let on_select = item.on_select.clone();
+                    self.close();
+                    if let Some(on_select) = on_select {
+                        on_select.emit(());
+                    }
+                    ShouldRender::Yes
+                }
+                None => ShouldRender::No,
+            },
+        }
+    }
+
+    fn view(&self) -> Layout {
+        let content = self.properties.content.view();
+        let base = Layout::column([Item::fixed(1)(self.draw_bar()), Item::auto(content)]);
+        if self.open.is_none() {
+            return Layout::stack([base]);
+        }
+
+        let dropdowns = self.draw_dropdowns();
+        let dropdowns = Dropdowns::with(DropdownsProperties {
+            content: Children::new(move || {
+                Layout::stack(
+                    dropdowns
+                        .iter()
+                        .map(|(frame, canvas)| place(*frame, canvas.clone().into())),
+                )
+            }),
+            on_message: self.link.callback(|message| message),
+        });
+        Layout::stack([base, dropdowns])
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings.add("open-menu", [Key::F(10)], || Message::Open(0));
+    }
+}
+
+struct DropdownsProperties {
+    content: Children,
+    on_message: Callback<Message>,
+}
+
+/// The open dropdowns of a menu bar. They trap focus rather than the menu
+/// bar itself, as the menu bar also contains the layout underneath.
+struct Dropdowns {
+    properties: DropdownsProperties,
+}
+
+impl Component for Dropdowns {
+    type Message = ();
+    type Properties = DropdownsProperties;
+
+    fn create(properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+        Self { properties }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        self.properties.content.view()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(true);
+        bindings.set_trap_focus(true);
+        if !bindings.is_empty() {
+            return;
+        }
+        let commands = [
+            ("next-menu-item", [Key::Down], Message::NextItem),
+            ("next-menu-item", [Key::Ctrl('n')], Message::NextItem),
+            ("previous-menu-item", [Key::Up], Message::PreviousItem),
+            (
+                "previous-menu-item",
+                [Key::Ctrl('p')],
+                Message::PreviousItem,
+            ),
+            ("menu-right", [Key::Right], Message::Right),
+            ("menu-left", [Key::Left], Message::Left),
+            ("activate-menu-item", [Key::Char('\n')], Message::Activate),
+            ("close-dropdown", [Key::Esc], Message::Back),
+            ("close-menu", [Key::F(10)], Message::Close),
+        ];
+        for (name, keys, message) in commands {
+            bindings.add(name, keys, move |this: &Self| {
+                this.properties.on_message.emit(message);
+            });
+        }
+    }
+}
+
+const PADDING: usize = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        components::text::{Text, TextProperties},
+        testing::Harness,
+    };
+
+    #[test]
+    fn keyboard_navigation() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let item = |label: &'static str| {
+            let log = log.clone();
+            MenuItem::new(label).on_select(move |_| log.borrow_mut().push(label))
+        };
+        let menus = [
+            Menu::new("File")
+                .item(item("save").accelerator("C-s"))
+                .item(MenuItem::new("export").submenu([item("html"), item("pdf")])),
+            Menu::new("Edit").item(item("undo")),
+        ];
+        let mut harness = Harness::new(
+            Size::new(24, 5),
+            MenuBar::with(
+                MenuBarProperties::new(|| Text::with(TextProperties::new().content("text")))
+                    .menus(menus)
+                    .focused(true),
+            ),
+        );
+        let mut press = |keys: &[Key]| {
+            harness.keys(keys.iter().copied());
+            let rows = (0..4).map(|y| harness.line(y)).collect::<Vec<_>>();
+            (rows, log.borrow_mut().drain(..).collect::<Vec<_>>())
+        };
+
+        let (rows, _) = press(&[]);
+        assert_eq!(rows, [" File  Edit", "text", "", ""]);
+
+        // Dropdowns show accelerators and open submenus next to the item
+        let (rows, _) = press(&[Key::F(10), Key::Down, Key::Right]);
+        assert_eq!(
+            rows,
+            [
+                " File  Edit",
+                " save    C-s",
+                " export      ▸  html",
+                "                pdf",
+            ]
+        );
+
+        let (rows, selected) = press(&[Key::Down, Key::Char('\n')]);
+        assert_eq!(selected, ["pdf"]);
+        assert_eq!(rows[1], "text");
+
+        // Left and Right move between menus, Esc closes the dropdown
+        assert_eq!(
+            press(&[Key::F(10), Key::Right, Key::Char('\n')]).1,
+            ["undo"]
+        );
+        let (rows, selected) = press(&[Key::F(10), Key::Left, Key::Left, Key::Esc]);
+        assert!(selected.is_empty());
+        assert_eq!(rows[1], "text");
+    }
+}
@@ -0,0 +1,241 @@
+//! A compressed overview of a large text buffer.
+//!
+//! The [`Minimap`](struct.Minimap.html) draws every line of the content as a
+//! row of dots, on if the corresponding character is not whitespace. It is
+//! linked to the main view of the content through a shared
+//! [`ScrollModel`](../scroll/struct.ScrollModel.html) and it highlights the
+//! lines visible in the main view.
+use ropey::Rope;
+use std::cmp;
+
+use super::scroll::{ScrollModel, ScrollSubscription};
+use crate::{Canvas, Component, ComponentLink, Layout, Rect, RenderPriority, ShouldRender, Style};
+
+/// How the content is compressed into terminal cells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinimapMode {
+    /// Braille patterns, each cell shows 2 characters from 4 lines.
+    Braille,
+    /// Half blocks, each cell shows 1 character from 2 lines.
+    Block,
+}
+
+impl MinimapMode {
+    /// The number of (characters, lines) shown in a cell.
+    fn dots(self) -> (usize, usize) {
+        match self {
+            Self::Braille => (2, 4),
+            Self::Block => (1, 2),
+        }
+    }
+
+    fn cell(self, dots: u8) -> char {
+        match self {
+            Self::Braille => char::from_u32(BRAILLE_BASE + dots as u32).unwrap_or(' '),
+            Self::Block => [' ', '▀', '▄', '█'][dots as usize & 0b11],
+        }
+    }
+
+    /// The bit of a dot inside a cell.
+    fn dot(self, x: usize, y: usize) -> u8 {
+        match self {
+            // Braille dots are numbered top to bottom, left column first, with
+            // the bottom row added last
+            Self::Braille => match (x, y) {
+                (0, 3) => 0x40,
+                (1, 3) => 0x80,
+                (x, y) => 1 << (3 * x + y),
+            },
+            Self::Block => 1 << y,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MinimapProperties {
+    pub content: Rope,
+    pub scroll: ScrollModel,
+    pub mode: MinimapMode,
+    pub style: Style,
+    /// The style of the lines visible in the main view.
+    pub viewport_style: Style,
+}
+
+impl MinimapProperties {
+    pub fn new(content: Rope, scroll: ScrollModel) -> Self {
+        Self {
+            content,
+            scroll,
+            mode: MinimapMode::Braille,
+            style: Style::default(),
+            viewport_style: Style::default(),
+        }
+    }
+
+    pub fn mode(mut self, mode: MinimapMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    pub fn viewport_style(mut self, style: impl Into<Style>) -> Self {
+        self.viewport_style = style.into();
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    Scrolled,
+}
+
+pub struct Minimap {
+    properties: MinimapProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    scroll_subscription: ScrollSubscription,
+}
+
+impl Minimap {
+    /// The first line of the content shown in the minimap. If the content
+    /// doesn't fit, the minimap scrolls proportionally with the main view.
+    fn first_line(&self, capacity: usize) -> usize {
+        let scroll = &self.properties.scroll;
+        let total = self.properties.content.len_lines();
+        let max_offset = scroll.max_offset();
+        if total <= capacity || max_offset == 0 {
+            return 0;
+        }
+        cmp::min(
+            scroll.offset() * (total - capacity) / max_offset,
+            total - capacity,
+        )
+    }
+}
+
+impl Component for Minimap {
+    type Message = Message;
+    type Properties = MinimapProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let scroll_subscription = properties
+            .scroll
+            .subscribe(link.callback(|_| Message::Scrolled));
+        Self {
+            properties,
+            frame,
+            link,
+            scroll_subscription,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties.scroll != properties.scroll {
+            self.scroll_subscription = properties
+                .scroll
+                .subscribe(self.link.callback(|_| Message::Scrolled));
+        }
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, _message: Self::Message) -> ShouldRender {
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let MinimapProperties {
+            ref content,
+            ref scroll,
+            mode,
+            style,
+            viewport_style,
+        } = self.properties;
+        let size = self.frame.size;
+        let (dots_x, dots_y) = mode.dots();
+        let first_line = self.first_line(size.height * dots_y);
+        let total = content.len_lines();
+        let viewport = scroll.offset()..scroll.offset() + scroll.viewport();
+
+        let mut canvas = Canvas::new(size);
+        canvas.clear(style);
+        let mut cells = vec![0u8; size.width];
+        for y in 0..size.height {
+            let lines = first_line + y * dots_y..cmp::min(first_line + (y + 1) * dots_y, total);
+            if lines.start >= total {
+                break;
+            }
+
+            cells.iter_mut().for_each(|cell| *cell = 0);
+            for (dot_y, line_index) in lines.clone().enumerate() {
+                let line = content.line(line_index);
+                for (column, character) in line.chars().take(size.width * dots_x).enumerate() {
+                    if !character.is_whitespace() {
+                        cells[column / dots_x] |= mode.dot(column % dots_x, dot_y);
+                    }
+                }
+            }
+
+            let style = if lines.start < viewport.end && viewport.start < lines.end {
+                viewport_style
+            } else {
+                style
+            };
+            let mut buffer = [0; 4];
+            for (x, cell) in cells.iter().enumerate() {
+                canvas.draw_str(x, y, style, mode.cell(*cell).encode_utf8(&mut buffer));
+            }
+        }
+        canvas.into()
+    }
+
+    fn render_priority(&self) -> RenderPriority {
+        RenderPriority::Low
+    }
+}
+
+const BRAILLE_BASE: u32 = 0x2800;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::Harness, Colour, ComponentExt, Size};
+
+    #[test]
+    fn follows_shared_scroll() {
+        let scroll = ScrollModel::new();
+        let content = Rope::from("a\nb c\n\nd\n");
+        let mut harness = Harness::new(
+            Size::new(3, 2),
+            Minimap::with(
+                MinimapProperties::new(content, scroll.clone())
+                    .mode(MinimapMode::Block)
+                    .viewport_style(Style::bold(Colour::black(), Colour::white())),
+            ),
+        );
+        let is_highlighted = |canvas: &Canvas, y| canvas.textel(0, y).as_ref().unwrap().style.bold;
+
+        // Every cell shows two lines, the first one on top
+        scroll.set_extent(5, 2);
+        assert_eq!(harness.line(0), "█ ▄");
+        assert_eq!(harness.line(1), "▄");
+        let canvas = harness.canvas();
+        assert!(is_highlighted(canvas, 0));
+        assert!(!is_highlighted(canvas, 1));
+
+        // Scrolling the shared model moves the viewport indicator
+        scroll.set_offset(2);
+        let canvas = harness.canvas();
+        assert!(!is_highlighted(canvas, 0));
+        assert!(is_highlighted(canvas, 1));
+    }
+}
@@ -1,6 +1,46 @@
 //! A collection of reusable components useful as building blocks.
 
 pub mod border;
+pub mod diff;
+pub mod error;
+pub mod focus;
 pub mod input;
+pub mod menu;
+pub mod minimap;
+pub mod modal;
+pub mod pager;
+pub mod process;
+pub mod router;
+pub mod scaffold;
+pub mod scroll;
+pub mod scroll_view;
+pub mod scrollbar;
+pub mod search;
 pub mod select;
+pub mod spinner;
+pub mod split;
+pub mod status;
+pub mod table;
 pub mod text;
+pub mod text_area;
+pub mod tooltip;
+pub mod viewport;
+pub mod which_key;
+pub mod windows;
+pub mod workspace;
+
+use crate::{Item, Layout, Rect};
+
+/// Positions a layout at a given frame by surrounding it with empty spacers.
+/// Used inside a layer of a stack, it leaves the layers below visible.
+pub(crate) fn place(frame: Rect, layout: Layout) -> Layout {
+    Layout::column([
+        Item::fixed(frame.origin.y)(Layout::empty()),
+        Item::fixed(frame.size.height)(Layout::row([
+            Item::fixed(frame.origin.x)(Layout::empty()),
+            Item::fixed(frame.size.width)(layout),
+            Item::auto(Layout::empty()),
+        ])),
+        Item::auto(Layout::empty()),
+    ])
+}
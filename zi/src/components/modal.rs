@@ -0,0 +1,353 @@
+//! A modal dialog drawn on top of a layout.
+//!
+//! While it is open, a [`Modal`](struct.Modal.html) obscures the content
+//! underneath with a backdrop and shows a bordered dialog centred above it.
+//! The dialog traps focus, s.t. key presses and Tab navigation stay inside it,
+//! and Esc closes it. Closing restores the focus from before it was opened.
+//!
+//! ```
+//! # use zi::prelude::*;
+//! # use zi::components::{
+//! #     modal::{Modal, ModalProperties},
+//! #     text::{Text, TextProperties},
+//! # };
+//! let modal = Modal::with(
+//!     ModalProperties::new(
+//!         || Text::with(TextProperties::new().content("Document")),
+//!         || Text::with(TextProperties::new().content("Discard changes?")),
+//!     )
+//!     .title("Confirm")
+//!     .open(true),
+//! );
+//! ```
+use std::cmp;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    border::{Border, BorderProperties},
+    place,
+};
+use crate::{
+    terminal::SizeExt, Bindings, Callback, Canvas, Children, Colour, Component, ComponentExt,
+    ComponentLink, Key, Layout, Position, Rect, ShouldRender, Size, Style,
+};
+
+pub struct ModalProperties {
+    /// The layout the dialog is drawn on top of.
+    pub content: Children,
+    /// The content of the dialog, inside its border.
+    pub dialog: Children,
+    pub title: Option<String>,
+    /// The size of the dialog including its border. If not set, the dialog
+    /// fits the preferred size of its content.
+    pub size: Option<Size>,
+    pub style: Style,
+    /// The style of the backdrop covering the content while the dialog is
+    /// open.
+    pub backdrop: Style,
+    pub open: bool,
+    pub on_close: Option<Callback<()>>,
+}
+
+impl ModalProperties {
+    pub fn new(content: impl Into<Children>, dialog: impl Into<Children>) -> Self {
+        const DARK0_HARD: Colour = Colour::rgb(29, 32, 33);
+        const DARK2: Colour = Colour::rgb(80, 73, 69);
+
+        Self {
+            content: content.into(),
+            dialog: dialog.into(),
+            title: None,
+            size: None,
+            style: Style::default(),
+            backdrop: Style::normal(DARK0_HARD, DARK2),
+            open: false,
+            on_close: None,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    pub fn backdrop(mut self, style: impl Into<Style>) -> Self {
+        self.backdrop = style.into();
+        self
+    }
+
+    pub fn open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Called when the dialog is closed with Esc. Parents should set `open`
+    /// to `false` in response, a closed dialog is not shown again until it
+    /// is reopened.
+    pub fn on_close(mut self, on_close: impl Into<Callback<()>>) -> Self {
+        self.on_close = Some(on_close.into());
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    Close,
+}
+
+/// Shows a dialog centred above a layout which traps focus while open.
+pub struct Modal {
+    properties: ModalProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    visible: bool,
+}
+
+impl Modal {
+    /// Returns `true` if the dialog is currently shown.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn dialog_frame(&self) -> Rect {
+        let bounds = self.frame.size;
+        let size = self
+            .properties
+            .size
+            .unwrap_or_else(|| {
+                let hint = self
+                    .properties
+                    .dialog
+                    .view()
+                    .size_hint(bounds.offset(-2, -2));
+                let title_width = self
+                    .properties
+                    .title
+                    .as_ref()
+                    .map_or(0, |title| UnicodeWidthStr::width(title.as_str()) + 4);
+                Size::new(
+                    cmp::max(hint.preferred.width + 2, title_width),
+                    hint.preferred.height + 2,
+                )
+            })
+            .min(bounds);
+        Rect::new(
+            Position::new(
+                (bounds.width - size.width) / 2,
+                (bounds.height - size.height) / 2,
+            ),
+            size,
+        )
+    }
+}
+
+impl Component for Modal {
+    type Message = Message;
+    type Properties = ModalProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            visible: properties.open,
+            properties,
+            frame,
+            link,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if properties.open != self.properties.open {
+            self.visible = properties.open;
+        }
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::Close if self.visible => {
+                self.visible = false;
+                if let Some(on_close) = self.properties.on_close.as_ref() {
+                    on_close.emit(());
+                }
+                ShouldRender::Yes
+            }
+            Message::Close => ShouldRender::No,
+        }
+    }
+
+    fn view(&self) -> Layout {
+        let content = self.properties.content.view();
+        if !self.visible {
+            return Layout::stack([content]);
+        }
+
+        let mut backdrop = Canvas::new(self.frame.size);
+        backdrop.clear(self.properties.backdrop);
+        let dialog = Dialog::with(DialogProperties {
+            content: self.properties.dialog.clone(),
+            title: self.properties.title.clone(),
+            style: self.properties.style,
+            on_close: self.link.callback(|_| Message::Close),
+        });
+
+        // The content is kept in the same layer whether the dialog is open or
+        // not, s.t. opening the dialog doesn't remount it.
+        Layout::stack([content, backdrop.into(), place(self.dialog_frame(), dialog)])
+    }
+}
+
+struct DialogProperties {
+    content: Children,
+    title: Option<String>,
+    style: Style,
+    on_close: Callback<()>,
+}
+
+/// The bordered dialog of a modal. It traps focus rather than the modal
+/// itself, as the modal also contains the layout underneath.
+struct Dialog {
+    properties: DialogProperties,
+}
+
+impl Component for Dialog {
+    type Message = ();
+    type Properties = DialogProperties;
+
+    fn create(properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+        Self { properties }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let DialogProperties {
+            ref content,
+            ref title,
+            style,
+            ..
+        } = self.properties;
+        Border::with(
+            BorderProperties::new(content.clone())
+                .style(style)
+                .title(title.as_ref().map(|title| (title.clone(), style))),
+        )
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(true);
+        bindings.set_trap_focus(true);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings.add("close-modal", [Key::Esc], |this: &Self| {
+            this.properties.on_close.emit(());
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{testing::Harness, Item};
+
+    struct Field(&'static str, Rc<RefCell<Vec<String>>>);
+
+    impl Component for Field {
+        type Message = String;
+        type Properties = (&'static str, Rc<RefCell<Vec<String>>>);
+
+        fn create((name, log): Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self(name, log)
+        }
+
+        fn view(&self) -> Layout {
+            Canvas::new(Size::new(1, 1)).into()
+        }
+
+        fn update(&mut self, event: Self::Message) -> ShouldRender {
+            self.1.borrow_mut().push(event);
+            ShouldRender::No
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focusable(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("type", [Key::Char('x')], |this: &Self| {
+                Some(format!("{}:x", this.0))
+            });
+        }
+
+        fn focus(&self, focused: bool) -> Option<Self::Message> {
+            Some(format!("{}{}", self.0, if focused { '+' } else { '-' }))
+        }
+    }
+
+    #[test]
+    fn trap_focus_until_closed_with_esc() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let backdrop = Style::normal(Colour::rgb(1, 1, 1), Colour::rgb(2, 2, 2));
+        let layout = |open| {
+            let on_close = {
+                let log = log.clone();
+                move |_| log.borrow_mut().push("closed".into())
+            };
+            let log = log.clone();
+            let field = move |name| Field::with_key(name, (name, log.clone()));
+            Modal::with(
+                ModalProperties::new(
+                    {
+                        let field = field.clone();
+                        move || Layout::row([Item::auto(field("a")), Item::auto(field("b"))])
+                    },
+                    move || field("c"),
+                )
+                .backdrop(backdrop)
+                .open(open)
+                .on_close(on_close),
+            )
+        };
+        let mut harness = Harness::new(Size::new(8, 5), layout(false));
+        let press = |harness: &mut Harness, keys: &[Key]| {
+            harness.keys(keys.iter().copied()).canvas();
+            log.borrow_mut().drain(..).collect::<Vec<_>>()
+        };
+
+        assert_eq!(press(&mut harness, &[Key::Char('\t')]), ["a+"]);
+
+        // Opening the modal moves focus to the dialog and covers the content
+        // with a backdrop
+        harness.app_mut().set_root(layout(true));
+        assert_eq!(press(&mut harness, &[]), ["a-", "c+"]);
+        assert_eq!(press(&mut harness, &[Key::Char('x')]), ["c:x"]);
+        let canvas = harness.canvas();
+        assert_eq!(canvas.textel(0, 0).as_ref().unwrap().style, backdrop);
+
+        // Esc closes it and restores the previous focus
+        assert_eq!(press(&mut harness, &[Key::Esc]), ["closed", "c-", "a+"]);
+        harness.app_mut().set_root(layout(false));
+        assert!(press(&mut harness, &[]).is_empty());
+        assert_eq!(press(&mut harness, &[Key::Char('x')]), ["a:x"]);
+    }
+}
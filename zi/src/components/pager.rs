@@ -0,0 +1,617 @@
+//! A less-like viewer for large text buffers.
+//!
+//! The [`Pager`](struct.Pager.html) component shows a read-only text buffer
+//! with a status line at the bottom. It supports less-style navigation: moving
+//! by lines or pages, searching with `/` (matches are highlighted, `n` and `N`
+//! move between them) and jumping to a line with `:`.
+use ropey::Rope;
+use std::cmp;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    scroll::{ScrollModel, ScrollSubscription},
+    search::{line_text, SearchMatch, SearchTarget},
+};
+use crate::{
+    terminal::Grapheme, AnyCharacter, Bindings, Callback, Canvas, Colour, Component, ComponentExt,
+    ComponentLink, Item, Key, Layout, Rect, ShouldRender, Size, Style,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PagerStyle {
+    pub content: Style,
+    pub status: Style,
+    pub highlight: Style,
+    pub current_match: Style,
+}
+
+impl Default for PagerStyle {
+    fn default() -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const DARK2: Colour = Colour::rgb(80, 73, 69);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const BRIGHT_YELLOW: Colour = Colour::rgb(250, 189, 47);
+        const BRIGHT_ORANGE: Colour = Colour::rgb(254, 128, 25);
+
+        Self {
+            content: Style::normal(DARK0_SOFT, LIGHT2),
+            status: Style::normal(DARK2, LIGHT2),
+            highlight: Style::normal(BRIGHT_YELLOW, DARK0_SOFT),
+            current_match: Style::bold(BRIGHT_ORANGE, DARK0_SOFT),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PagerProperties {
+    pub content: Rope,
+    pub style: PagerStyle,
+    pub focused: bool,
+    /// Shares the scroll offset with other components, e.g. a minimap.
+    pub scroll: Option<ScrollModel>,
+    /// Called when quitting the pager with `q`.
+    pub on_close: Option<Callback<()>>,
+}
+
+impl PagerProperties {
+    pub fn new(content: impl Into<Rope>) -> Self {
+        Self {
+            content: content.into(),
+            style: PagerStyle::default(),
+            focused: false,
+            scroll: None,
+            on_close: None,
+        }
+    }
+
+    pub fn style(mut self, style: PagerStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: ScrollModel) -> Self {
+        self.scroll = Some(scroll);
+        self
+    }
+
+    pub fn on_close(mut self, on_close: impl Into<Callback<()>>) -> Self {
+        self.on_close = Some(on_close.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Scroll(isize),
+    ScrollPages(isize),
+    FirstLine,
+    LastLine,
+    StartSearch,
+    StartGoToLine,
+    SubmitPrompt(String),
+    CancelPrompt,
+    NextMatch,
+    PreviousMatch,
+    Close,
+    Scrolled,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Prompt {
+    Search,
+    GoToLine,
+}
+
+impl Prompt {
+    fn prefix(self) -> char {
+        match self {
+            Self::Search => '/',
+            Self::GoToLine => ':',
+        }
+    }
+}
+
+pub struct Pager {
+    properties: PagerProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    scroll: ScrollModel,
+    scroll_subscription: ScrollSubscription,
+    prompt: Option<Prompt>,
+    query: String,
+    matches: Vec<SearchMatch>,
+    current_match: Option<usize>,
+    status: Option<String>,
+}
+
+impl Pager {
+    /// The current search query, empty if there's no active search.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The number of matches of the current search query.
+    pub fn num_matches(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// The index of the first visible line.
+    pub fn offset(&self) -> usize {
+        self.scroll.offset()
+    }
+
+    fn num_lines(&self) -> usize {
+        num_lines(&self.properties.content)
+    }
+
+    fn num_visible_lines(&self) -> usize {
+        self.frame.size.height.saturating_sub(STATUS_HEIGHT)
+    }
+
+    fn update_extent(&self) {
+        self.scroll
+            .set_extent(self.num_lines(), self.num_visible_lines());
+    }
+
+    fn subscribe_to_scroll(&mut self) {
+        self.scroll = self.properties.scroll.clone().unwrap_or_default();
+        self.scroll_subscription = self
+            .scroll
+            .subscribe(self.link.callback(|_| Message::Scrolled));
+    }
+
+    fn search(&mut self, query: String) {
+        self.query = query;
+        self.matches = SearchTarget::find(&self.properties.content, &self.query);
+        self.current_match = None;
+        if self.query.is_empty() {
+            return;
+        }
+
+        // Jump to the first match on a visible line or below
+        let offset = self.scroll.offset();
+        match self.matches.iter().position(|found| found.line >= offset) {
+            Some(index) => self.select_match(index),
+            None if !self.matches.is_empty() => self.select_match(0),
+            None => self.status = Some("Pattern not found".into()),
+        }
+    }
+
+    fn select_match(&mut self, index: usize) {
+        self.current_match = Some(index);
+        self.scroll.set_offset(self.matches[index].line);
+    }
+
+    fn move_to_match(&mut self, forward: bool) {
+        let num_matches = self.matches.len();
+        if num_matches == 0 {
+            self.status = Some(if self.query.is_empty() {
+                "No previous search".into()
+            } else {
+                "Pattern not found".into()
+            });
+            return;
+        }
+        let index = match (self.current_match, forward) {
+            (Some(index), true) => (index + 1) % num_matches,
+            (Some(index), false) => (index + num_matches - 1) % num_matches,
+            (None, true) => 0,
+            (None, false) => num_matches - 1,
+        };
+        self.select_match(index);
+    }
+
+    fn draw_content(&self, canvas: &mut Canvas) {
+        let PagerStyle {
+            content: content_style,
+            highlight,
+            current_match,
+            ..
+        } = self.properties.style;
+        let content = &self.properties.content;
+        let offset = self.scroll.offset();
+        let num_visible_lines = cmp::min(
+            self.num_visible_lines(),
+            self.num_lines().saturating_sub(offset),
+        );
+
+        canvas.clear(content_style);
+        let first_match = self.matches.partition_point(|found| found.line < offset);
+        let mut matches = self.matches[first_match..].iter().enumerate().peekable();
+        for y in 0..num_visible_lines {
+            let line_index = offset + y;
+            let line = content.line(line_index);
+            let text = line_text(line);
+            canvas.draw_str(0, y, content_style, &text);
+
+            while let Some((index, found)) = matches.next_if(|(_, found)| found.line == line_index)
+            {
+                let style = if self.current_match == Some(first_match + index) {
+                    current_match
+                } else {
+                    highlight
+                };
+                let start = char_to_byte(&text, found.start);
+                let end = char_to_byte(&text, found.end);
+                canvas.draw_str(
+                    UnicodeWidthStr::width(&text[..start]),
+                    y,
+                    style,
+                    &text[start..end],
+                );
+            }
+        }
+    }
+
+    fn draw_status(&self, canvas: &mut Canvas) {
+        let style = self.properties.style.status;
+        let num_lines = self.num_lines();
+        let offset = self.scroll.offset();
+        let last_visible = cmp::min(offset + self.num_visible_lines(), num_lines);
+
+        let position = if num_lines <= self.num_visible_lines() {
+            format!("{} lines (All)", num_lines)
+        } else {
+            format!(
+                "lines {}-{}/{} {}%",
+                offset + 1,
+                last_visible,
+                num_lines,
+                last_visible * 100 / num_lines
+            )
+        };
+        let info = match (self.status.as_ref(), self.current_match) {
+            (Some(status), _) => status.clone(),
+            (None, Some(index)) => {
+                format!("/{} [{}/{}]", self.query, index + 1, self.matches.len())
+            }
+            (None, None) => String::new(),
+        };
+
+        canvas.clear(style);
+        canvas.draw_str(0, 0, style, &info);
+        let width = canvas.size().width;
+        let position_width = UnicodeWidthStr::width(position.as_str());
+        canvas.draw_str(width.saturating_sub(position_width), 0, style, &position);
+    }
+}
+
+impl Component for Pager {
+    type Message = Message;
+    type Properties = PagerProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let scroll = properties.scroll.clone().unwrap_or_default();
+        let scroll_subscription = scroll.subscribe(link.callback(|_| Message::Scrolled));
+        let pager = Self {
+            properties,
+            frame,
+            link,
+            scroll,
+            scroll_subscription,
+            prompt: None,
+            query: String::new(),
+            matches: Vec::new(),
+            current_match: None,
+            status: None,
+        };
+        pager.update_extent();
+        pager
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        let scroll_changed = self.properties.scroll != properties.scroll;
+        let content_changed = self.properties.content != properties.content;
+        self.properties = properties;
+        if scroll_changed {
+            self.subscribe_to_scroll();
+        }
+        if content_changed {
+            let query = std::mem::take(&mut self.query);
+            self.matches = SearchTarget::find(&self.properties.content, &query);
+            self.query = query;
+            self.current_match = None;
+        }
+        self.update_extent();
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        self.update_extent();
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        // Status messages are shown until the next command
+        if message != Message::Scrolled {
+            self.status = None;
+        }
+        match message {
+            Message::Scroll(lines) => self.scroll.scroll_by(lines),
+            Message::ScrollPages(pages) => self
+                .scroll
+                .scroll_by(pages * cmp::max(self.num_visible_lines(), 1) as isize),
+            Message::FirstLine => self.scroll.set_offset(0),
+            Message::LastLine => self.scroll.set_offset(self.scroll.max_offset()),
+            Message::StartSearch => self.prompt = Some(Prompt::Search),
+            Message::StartGoToLine => self.prompt = Some(Prompt::GoToLine),
+            Message::SubmitPrompt(input) => match self.prompt.take() {
+                Some(Prompt::Search) => self.search(input),
+                Some(Prompt::GoToLine) => match input.trim().parse::<usize>() {
+                    Ok(line) => self.scroll.set_offset(line.saturating_sub(1)),
+                    Err(_) => self.status = Some(format!("Invalid line number: {}", input)),
+                },
+                None => {}
+            },
+            Message::CancelPrompt => self.prompt = None,
+            Message::NextMatch => self.move_to_match(true),
+            Message::PreviousMatch => self.move_to_match(false),
+            Message::Close => {
+                if let Some(on_close) = self.properties.on_close.as_ref() {
+                    on_close.emit(());
+                }
+            }
+            Message::Scrolled => {}
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let mut content = Canvas::new(Size::new(self.frame.size.width, self.num_visible_lines()));
+        self.draw_content(&mut content);
+
+        let status = match self.prompt {
+            Some(prompt) => PromptLine::with(PromptLineProperties {
+                prefix: prompt.prefix(),
+                style: self.properties.style.status,
+                on_submit: self.link.callback(Message::SubmitPrompt),
+                on_cancel: self.link.callback(|_| Message::CancelPrompt),
+            }),
+            None => {
+                let mut status = Canvas::new(Size::new(self.frame.size.width, STATUS_HEIGHT));
+                self.draw_status(&mut status);
+                status.into()
+            }
+        };
+
+        Layout::column([Item::auto(content), Item::fixed(STATUS_HEIGHT)(status)])
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        // While prompting, the keys go to the prompt line instead
+        bindings.set_focus(self.properties.focused && self.prompt.is_none());
+        if !bindings.is_empty() {
+            return;
+        }
+
+        bindings
+            .command("line-down", || Message::Scroll(1))
+            .with([Key::Char('j')])
+            .with([Key::Char('e')])
+            .with([Key::Char('\n')])
+            .with([Key::Down])
+            .with([Key::Ctrl('n')]);
+        bindings
+            .command("line-up", || Message::Scroll(-1))
+            .with([Key::Char('k')])
+            .with([Key::Char('y')])
+            .with([Key::Up])
+            .with([Key::Ctrl('p')]);
+        bindings
+            .command("page-down", || Message::ScrollPages(1))
+            .with([Key::Char(' ')])
+            .with([Key::Char('f')])
+            .with([Key::PageDown])
+            .with([Key::Ctrl('v')])
+            .with([Key::Ctrl('f')]);
+        bindings
+            .command("page-up", || Message::ScrollPages(-1))
+            .with([Key::Char('b')])
+            .with([Key::PageUp])
+            .with([Key::Alt('v')])
+            .with([Key::Ctrl('b')]);
+        bindings
+            .command("half-page-down", |this: &Self| {
+                Some(Message::Scroll((this.num_visible_lines() / 2) as isize))
+            })
+            .with([Key::Char('d')])
+            .with([Key::Ctrl('d')]);
+        bindings
+            .command("half-page-up", |this: &Self| {
+                Some(Message::Scroll(-((this.num_visible_lines() / 2) as isize)))
+            })
+            .with([Key::Char('u')])
+            .with([Key::Ctrl('u')]);
+        bindings
+            .command("first-line", || Message::FirstLine)
+            .with([Key::Char('g')])
+            .with([Key::Char('<')])
+            .with([Key::Alt('<')])
+            .with([Key::Home]);
+        bindings
+            .command("last-line", || Message::LastLine)
+            .with([Key::Char('G')])
+            .with([Key::Char('>')])
+            .with([Key::Alt('>')])
+            .with([Key::End]);
+
+        bindings.add("search", [Key::Char('/')], || Message::StartSearch);
+        bindings.add("next-match", [Key::Char('n')], || Message::NextMatch);
+        bindings.add("previous-match", [Key::Char('N')], || {
+            Message::PreviousMatch
+        });
+        bindings.add("go-to-line", [Key::Char(':')], || Message::StartGoToLine);
+        bindings.add("close", [Key::Char('q')], || Message::Close);
+    }
+}
+
+/// The number of lines in a buffer, not counting the empty line after a
+/// trailing newline.
+fn num_lines(content: &Rope) -> usize {
+    let len_lines = content.len_lines();
+    if len_lines > 1 && content.line(len_lines - 1).len_chars() == 0 {
+        len_lines - 1
+    } else {
+        len_lines
+    }
+}
+
+fn char_to_byte(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(byte_index, _)| byte_index)
+}
+
+struct PromptLineProperties {
+    prefix: char,
+    style: Style,
+    on_submit: Callback<String>,
+    on_cancel: Callback<()>,
+}
+
+/// A single line text input for the search and go to line prompts.
+struct PromptLine {
+    properties: PromptLineProperties,
+    frame: Rect,
+    input: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PromptMessage {
+    Insert(Grapheme),
+    DeleteBackward,
+    Submit,
+    Cancel,
+}
+
+impl Component for PromptLine {
+    type Message = PromptMessage;
+    type Properties = PromptLineProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            input: String::new(),
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            PromptMessage::Insert(grapheme) => self.input.push_str(&grapheme),
+            // Like in less, deleting past the start of the input cancels it
+            PromptMessage::DeleteBackward if self.input.is_empty() => {
+                self.properties.on_cancel.emit(())
+            }
+            PromptMessage::DeleteBackward => {
+                let last_grapheme = self.input.grapheme_indices(true).next_back();
+                if let Some((index, _)) = last_grapheme {
+                    self.input.truncate(index);
+                }
+            }
+            PromptMessage::Submit => self
+                .properties
+                .on_submit
+                .emit(std::mem::take(&mut self.input)),
+            PromptMessage::Cancel => self.properties.on_cancel.emit(()),
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let style = self.properties.style;
+        let mut canvas = Canvas::new(self.frame.size);
+        canvas.clear(style);
+        let mut buffer = [0; 4];
+        let mut x = canvas.draw_str(0, 0, style, self.properties.prefix.encode_utf8(&mut buffer));
+        x += canvas.draw_str(x, 0, style, &self.input);
+        canvas.draw_str(x, 0, style.invert(), " ");
+        canvas.into()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(true);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings.add("submit", [Key::Char('\n')], || PromptMessage::Submit);
+        bindings
+            .command("cancel", || PromptMessage::Cancel)
+            .with([Key::Esc])
+            .with([Key::Ctrl('g')]);
+        bindings.add("delete-backward", [Key::Backspace], || {
+            PromptMessage::DeleteBackward
+        });
+        bindings.add(
+            "insert-character",
+            AnyCharacter,
+            |keys: &[Key]| match *keys {
+                [Key::Char(character)] if !character.is_control() => {
+                    Some(PromptMessage::Insert(character.into()))
+                }
+                [Key::Grapheme(grapheme)] => Some(PromptMessage::Insert(grapheme)),
+                _ => None,
+            },
+        );
+    }
+}
+
+const STATUS_HEIGHT: usize = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::ComponentSelector, testing::Harness};
+
+    #[test]
+    fn search_and_go_to_line() {
+        let content: String = (0..20)
+            .map(|line| match line {
+                5 | 12 => format!("a Foo {}\n", line),
+                _ => format!("line {}\n", line),
+            })
+            .collect();
+        let mut harness = Harness::new(
+            Size::new(20, 4),
+            Pager::with(PagerProperties::new(content.as_str()).focused(true)),
+        );
+        let pager = ComponentSelector::of::<Pager>();
+        let mut press = |keys: &str| {
+            harness
+                .type_str(keys)
+                .app()
+                .with_component(&pager, |pager: &Pager| {
+                    (pager.offset(), pager.num_matches())
+                })
+                .unwrap()
+        };
+
+        assert_eq!(press("jj"), (2, 0));
+
+        // Searching jumps to the first match and `n` cycles through them
+        assert_eq!(press("/foo\n"), (5, 2));
+        assert_eq!(press("n"), (12, 2));
+        assert_eq!(press("n"), (5, 2));
+
+        // Jumping to a line shows it at the top
+        assert_eq!(press(":8\n"), (7, 2));
+        assert_eq!(press("G"), (17, 2));
+    }
+}
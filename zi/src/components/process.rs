@@ -0,0 +1,517 @@
+//! A view of the output of a subprocess.
+//!
+//! The [`ProcessOutput`](struct.ProcessOutput.html) component runs a command
+//! in the background and shows the lines it writes to stdout and stderr as
+//! they arrive. The view follows the output while scrolled to the bottom, like
+//! `tail -f`.
+use std::{
+    cmp,
+    ffi::OsString,
+    io::{self, BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{Arc, Mutex},
+};
+
+use super::scroll::{ScrollModel, ScrollSubscription};
+use crate::{
+    terminal::ScrollBuffer, Bindings, Callback, Canvas, Colour, Component, ComponentLink, Key,
+    Layout, Rect, ShouldRender, Style,
+};
+
+/// The command run by a [`ProcessOutput`](struct.ProcessOutput.html)
+/// component. Unlike `std::process::Command` it can be cloned and compared,
+/// the process is restarted whenever the command changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessCommand {
+    pub program: OsString,
+    pub args: Vec<OsString>,
+    pub current_dir: Option<PathBuf>,
+}
+
+impl ProcessCommand {
+    pub fn new(program: impl Into<OsString>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            current_dir: None,
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<OsString>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn current_dir(mut self, current_dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(current_dir.into());
+        self
+    }
+
+    fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(current_dir) = self.current_dir.as_ref() {
+            command.current_dir(current_dir);
+        }
+        command
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcessOutputStyle {
+    pub stdout: Style,
+    pub stderr: Style,
+    /// The style of the line reporting how the process exited.
+    pub status: Style,
+}
+
+impl Default for ProcessOutputStyle {
+    fn default() -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const BRIGHT_RED: Colour = Colour::rgb(251, 73, 52);
+        const GRAY: Colour = Colour::rgb(146, 131, 116);
+
+        Self {
+            stdout: Style::normal(DARK0_SOFT, LIGHT2),
+            stderr: Style::normal(DARK0_SOFT, BRIGHT_RED),
+            status: Style::bold(DARK0_SOFT, GRAY),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ProcessOutputProperties {
+    pub command: ProcessCommand,
+    /// The style of the output. Lines are styled when they are received, a
+    /// new style only applies to the output that follows.
+    pub style: ProcessOutputStyle,
+    /// The maximum number of lines kept, older lines are dropped.
+    pub scrollback: usize,
+    pub focused: bool,
+    /// Shares the scroll offset with other components.
+    pub scroll: Option<ScrollModel>,
+    /// Called when the process exits, or with an error if it couldn't be run.
+    pub on_exit: Option<Callback<Result<ExitStatus, String>>>,
+}
+
+impl ProcessOutputProperties {
+    pub fn new(command: ProcessCommand) -> Self {
+        Self {
+            command,
+            style: ProcessOutputStyle::default(),
+            scrollback: 10_000,
+            focused: false,
+            scroll: None,
+            on_exit: None,
+        }
+    }
+
+    pub fn style(mut self, style: ProcessOutputStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn scrollback(mut self, scrollback: usize) -> Self {
+        self.scrollback = scrollback;
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: ScrollModel) -> Self {
+        self.scroll = Some(scroll);
+        self
+    }
+
+    pub fn on_exit(mut self, on_exit: impl Into<Callback<Result<ExitStatus, String>>>) -> Self {
+        self.on_exit = Some(on_exit.into());
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum Message {
+    Output {
+        run: usize,
+        stream: OutputStream,
+        line: String,
+    },
+    Exited {
+        run: usize,
+        result: io::Result<ExitStatus>,
+    },
+    Scroll(isize),
+    ScrollPages(isize),
+    FirstLine,
+    LastLine,
+    Kill,
+    Restart,
+    Scrolled,
+}
+
+/// Runs a command in the background and shows its output.
+///
+/// Lines from stdout and stderr are interleaved in the order they are read
+/// and styled according to the stream. Only the last `scrollback` lines are
+/// kept. The process is killed when the component is dropped.
+pub struct ProcessOutput {
+    properties: ProcessOutputProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    scroll: ScrollModel,
+    scroll_subscription: ScrollSubscription,
+    lines: ScrollBuffer,
+    status: Option<String>,
+    child: Option<Arc<Mutex<Child>>>,
+    // Incremented every time the process is (re)started, messages from
+    // earlier runs are ignored
+    run: usize,
+}
+
+impl ProcessOutput {
+    /// The last lines written by the process.
+    pub fn lines(&self) -> &ScrollBuffer {
+        &self.lines
+    }
+
+    /// Returns `true` until the process exits.
+    pub fn is_running(&self) -> bool {
+        self.status.is_none()
+    }
+
+    /// The index of the first visible line.
+    pub fn offset(&self) -> usize {
+        self.scroll.offset()
+    }
+
+    fn start(&mut self) {
+        self.kill();
+        self.run += 1;
+        self.lines.clear();
+        self.status = None;
+        self.update_extent(false);
+
+        let run = self.run;
+        let mut child = match self.properties.command.to_command().spawn() {
+            Ok(child) => child,
+            Err(error) => {
+                self.link.send(Message::Exited {
+                    run,
+                    result: Err(error),
+                });
+                return;
+            }
+        };
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let child = Arc::new(Mutex::new(child));
+        self.child = Some(Arc::clone(&child));
+
+        let stderr_reader = stderr.map(|stderr| {
+            self.link
+                .spawn(move |link| read_lines(&link, run, OutputStream::Stderr, stderr))
+        });
+        self.link.spawn(move |link| {
+            if let Some(stdout) = stdout {
+                read_lines(&link, run, OutputStream::Stdout, stdout);
+            }
+            if let Some(stderr_reader) = stderr_reader {
+                let _ = stderr_reader.join();
+            }
+            let result = child.lock().expect("poisoned child process lock").wait();
+            link.send(Message::Exited { run, result });
+        });
+    }
+
+    fn kill(&mut self) {
+        // If the lock is taken, the process has closed its output and it is
+        // being waited on
+        if let Some(child) = self.child.take() {
+            if let Ok(mut child) = child.try_lock() {
+                let _ = child.kill();
+            }
+        }
+    }
+
+    fn num_rows(&self) -> usize {
+        self.lines.len() + self.status.iter().count()
+    }
+
+    /// Updates the extent of the scroll model. If `follow` is set, the view is
+    /// scrolled to the bottom.
+    fn update_extent(&self, follow: bool) {
+        self.scroll
+            .set_extent(self.num_rows(), self.frame.size.height);
+        if follow {
+            self.scroll.set_offset(self.scroll.max_offset());
+        }
+    }
+
+    fn is_following(&self) -> bool {
+        self.scroll.offset() >= self.scroll.max_offset()
+    }
+}
+
+fn read_lines(
+    link: &ComponentLink<ProcessOutput>,
+    run: usize,
+    stream: OutputStream,
+    reader: impl Read,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buffer);
+                link.send(Message::Output {
+                    run,
+                    stream,
+                    line: line.trim_end_matches(&['\n', '\r'][..]).into(),
+                });
+            }
+        }
+    }
+}
+
+impl Component for ProcessOutput {
+    type Message = Message;
+    type Properties = ProcessOutputProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let scroll = properties.scroll.clone().unwrap_or_default();
+        let scroll_subscription = scroll.subscribe(link.callback(|_| Message::Scrolled));
+        let lines = ScrollBuffer::new(properties.scrollback);
+        let mut output = Self {
+            properties,
+            frame,
+            link,
+            scroll,
+            scroll_subscription,
+            lines,
+            status: None,
+            child: None,
+            run: 0,
+        };
+        output.start();
+        output
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties.scroll != properties.scroll {
+            self.scroll = properties.scroll.clone().unwrap_or_default();
+            self.scroll_subscription = self
+                .scroll
+                .subscribe(self.link.callback(|_| Message::Scrolled));
+        }
+        let restart = self.properties.command != properties.command;
+        self.lines.set_capacity(properties.scrollback);
+        self.properties = properties;
+        if restart {
+            self.start();
+        } else {
+            self.update_extent(false);
+        }
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        let follow = self.is_following();
+        self.frame = frame;
+        self.update_extent(follow);
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::Output { run, stream, line } if run == self.run => {
+                let follow = self.is_following();
+                let style = match stream {
+                    OutputStream::Stdout => self.properties.style.stdout,
+                    OutputStream::Stderr => self.properties.style.stderr,
+                };
+                let offset = self.scroll.offset();
+                let evicted = self.lines.evicted();
+                self.lines.push_line((style, line.replace('\t', "    ")));
+                // Keep the same lines in view when old ones are dropped
+                let dropped = self.lines.evicted() - evicted;
+                if !follow && dropped > 0 {
+                    self.scroll.set_offset(offset.saturating_sub(dropped));
+                }
+                self.update_extent(follow);
+            }
+            Message::Exited { run, result } if run == self.run => {
+                self.child = None;
+                let result = result.map_err(|error| {
+                    format!(
+                        "Could not run {}: {}",
+                        self.properties.command.program.to_string_lossy(),
+                        error
+                    )
+                });
+                self.status = Some(match result {
+                    Ok(status) => match status.code() {
+                        Some(code) => format!("[exited with code {}]", code),
+                        None => "[terminated by a signal]".into(),
+                    },
+                    Err(ref error) => format!("[{}]", error),
+                });
+                let follow = self.is_following();
+                self.update_extent(follow);
+                if let Some(on_exit) = self.properties.on_exit.as_ref() {
+                    on_exit.emit(result);
+                }
+            }
+            Message::Output { .. } | Message::Exited { .. } => return ShouldRender::No,
+            Message::Scroll(lines) => self.scroll.scroll_by(lines),
+            Message::ScrollPages(pages) => self
+                .scroll
+                .scroll_by(pages * cmp::max(self.frame.size.height, 1) as isize),
+            Message::FirstLine => self.scroll.set_offset(0),
+            Message::LastLine => self.scroll.set_offset(self.scroll.max_offset()),
+            Message::Kill => self.kill(),
+            Message::Restart => self.start(),
+            Message::Scrolled => {}
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let ProcessOutputStyle { stdout, status, .. } = self.properties.style;
+        let mut canvas = Canvas::new(self.frame.size);
+        canvas.clear(stdout);
+        self.lines.draw(&mut canvas, self.offset());
+        if let Some(line) = self.status.as_deref() {
+            if let Some(y) = self.lines.len().checked_sub(self.offset()) {
+                canvas.draw_str(0, y, status, line);
+            }
+        }
+        canvas.into()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("line-down", || Message::Scroll(1))
+            .with([Key::Char('j')])
+            .with([Key::Down])
+            .with([Key::Ctrl('n')]);
+        bindings
+            .command("line-up", || Message::Scroll(-1))
+            .with([Key::Char('k')])
+            .with([Key::Up])
+            .with([Key::Ctrl('p')]);
+        bindings
+            .command("page-down", || Message::ScrollPages(1))
+            .with([Key::Char(' ')])
+            .with([Key::PageDown])
+            .with([Key::Ctrl('v')]);
+        bindings
+            .command("page-up", || Message::ScrollPages(-1))
+            .with([Key::Char('b')])
+            .with([Key::PageUp])
+            .with([Key::Alt('v')]);
+        bindings
+            .command("first-line", || Message::FirstLine)
+            .with([Key::Char('g')])
+            .with([Key::Home]);
+        bindings
+            .command("last-line", || Message::LastLine)
+            .with([Key::Char('G')])
+            .with([Key::End]);
+        bindings
+            .command("kill", || Message::Kill)
+            .with([Key::Ctrl('k')]);
+        bindings
+            .command("restart", || Message::Restart)
+            .with([Key::Char('r')]);
+    }
+}
+
+impl Drop for ProcessOutput {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use crate::{app::ComponentSelector, testing::Harness, ComponentExt, Size};
+
+    #[cfg(unix)]
+    #[test]
+    fn streams_lines_and_exit_status() {
+        let exit_code = Rc::new(Cell::new(None));
+        let command = ProcessCommand::new("sh")
+            .arg("-c")
+            .arg("echo out; echo err >&2; exit 3");
+        let mut harness = Harness::new(
+            Size::new(20, 5),
+            ProcessOutput::with(ProcessOutputProperties::new(command).on_exit({
+                let exit_code = Rc::clone(&exit_code);
+                move |result: Result<ExitStatus, String>| exit_code.set(result.unwrap().code())
+            })),
+        );
+        harness.canvas();
+        while exit_code.get().is_none() {
+            assert!(
+                harness.wait_for_message(Duration::from_secs(5)),
+                "the process didn't exit"
+            );
+        }
+
+        let mut lines = harness
+            .app()
+            .with_component(
+                &ComponentSelector::of::<ProcessOutput>(),
+                |output: &ProcessOutput| {
+                    assert!(!output.is_running());
+                    output
+                        .lines()
+                        .lines()
+                        .map(|line| line.spans().to_vec())
+                        .collect::<Vec<_>>()
+                },
+            )
+            .unwrap();
+        // The order of lines from different streams is not deterministic
+        lines.sort_by(|left, right| left[0].1.cmp(&right[0].1));
+        let style = ProcessOutputStyle::default();
+        assert_eq!(
+            lines,
+            vec![
+                vec![(style.stderr, "err".to_string())],
+                vec![(style.stdout, "out".to_string())],
+            ]
+        );
+        assert_eq!(exit_code.get(), Some(3));
+    }
+}
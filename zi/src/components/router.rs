@@ -0,0 +1,332 @@
+//! A navigation stack of named screens.
+//!
+//! The [`Router`](struct.Router.html) component shows the screen at the top
+//! of its stack. Screens further down the stack are kept mounted, but hidden,
+//! such that they preserve their state when navigating back to them. Screens
+//! navigate using a [`Navigator`](struct.Navigator.html) handle which is
+//! passed in when a screen is instantiated.
+//!
+//! ```no_run
+//! # use zi::prelude::*;
+//! # use zi::components::{
+//! #     router::{Router, RouterProperties},
+//! #     text::{Text, TextProperties},
+//! # };
+//! let router = Router::with(
+//!     RouterProperties::new("home")
+//!         .route("home", |_navigator| {
+//!             Text::with(TextProperties::new().content("Home"))
+//!         })
+//!         .route("settings", |_navigator| {
+//!             Text::with(TextProperties::new().content("Settings"))
+//!         }),
+//! );
+//! ```
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{Callback, Component, ComponentExt, ComponentLink, Item, Layout, Rect, ShouldRender};
+
+/// A handle used by screens to navigate between routes.
+///
+/// It is a thin wrapper over the [`ComponentLink`](../../struct.ComponentLink.html)
+/// of the router. As such it is cheap to clone and it can be sent to other
+/// threads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Navigator {
+    link: ComponentLink<Router>,
+}
+
+impl Navigator {
+    /// Shows a new screen on top of the current one.
+    pub fn push(&self, route: impl Into<Cow<'static, str>>) {
+        self.link.send(Message::Push(route.into()));
+    }
+
+    /// Goes back to the previous screen. The root screen is never popped.
+    pub fn pop(&self) {
+        self.link.send(Message::Pop);
+    }
+
+    /// Replaces the current screen with a new one.
+    pub fn replace(&self, route: impl Into<Cow<'static, str>>) {
+        self.link.send(Message::Replace(route.into()));
+    }
+}
+
+#[derive(Clone)]
+pub struct RouterProperties {
+    pub initial: Cow<'static, str>,
+    pub routes: HashMap<Cow<'static, str>, Callback<Navigator, Layout>>,
+    pub on_change: Option<Callback<Cow<'static, str>>>,
+}
+
+impl RouterProperties {
+    pub fn new(initial: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            initial: initial.into(),
+            routes: HashMap::new(),
+            on_change: None,
+        }
+    }
+
+    pub fn route(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        screen: impl Fn(Navigator) -> Layout + 'static,
+    ) -> Self {
+        self.routes.insert(name.into(), screen.into());
+        self
+    }
+
+    pub fn on_change(mut self, on_change: impl Into<Callback<Cow<'static, str>>>) -> Self {
+        self.on_change = Some(on_change.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Push(Cow<'static, str>),
+    Pop,
+    Replace(Cow<'static, str>),
+}
+
+struct Entry {
+    route: Cow<'static, str>,
+    key: usize,
+}
+
+pub struct Router {
+    properties: RouterProperties,
+    navigator: Navigator,
+    stack: Vec<Entry>,
+    next_key: usize,
+}
+
+impl Router {
+    /// Returns the name of the route currently shown.
+    pub fn current(&self) -> &str {
+        &self
+            .stack
+            .last()
+            .expect("router stack is never empty")
+            .route
+    }
+
+    fn new_entry(&mut self, route: Cow<'static, str>) -> Option<Entry> {
+        if !self.properties.routes.contains_key(&route) {
+            log::warn!("Tried navigating to an unknown route `{}`", route);
+            return None;
+        }
+        let key = self.next_key;
+        self.next_key += 1;
+        Some(Entry { route, key })
+    }
+}
+
+impl Component for Router {
+    type Message = Message;
+    type Properties = RouterProperties;
+
+    fn create(properties: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+        let initial = properties.initial.clone();
+        if !properties.routes.contains_key(&initial) {
+            log::warn!(
+                "The initial route `{}` is unknown, nothing is shown",
+                initial
+            );
+        }
+        Self {
+            properties,
+            navigator: Navigator { link },
+            stack: vec![Entry {
+                route: initial,
+                key: 0,
+            }],
+            next_key: 1,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::Push(route) => match self.new_entry(route) {
+                Some(entry) => self.stack.push(entry),
+                None => return ShouldRender::No,
+            },
+            Message::Pop if self.stack.len() > 1 => {
+                self.stack.pop();
+            }
+            Message::Pop => return ShouldRender::No,
+            Message::Replace(route) => match self.new_entry(route) {
+                Some(entry) => {
+                    *self.stack.last_mut().expect("router stack is never empty") = entry;
+                }
+                None => return ShouldRender::No,
+            },
+        }
+
+        if let Some(on_change) = self.properties.on_change.as_ref() {
+            on_change.emit(self.current().to_owned().into());
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        // Every screen has a unique key and all of them are part of the same
+        // container s.t. they keep their identity (and hence state) when they
+        // are hidden and shown again.
+        let top = self.stack.len() - 1;
+        Layout::column(self.stack.iter().enumerate().filter_map(|(index, entry)| {
+            let screen = self.properties.routes.get(&entry.route)?;
+            let screen = Screen::with_key(
+                entry.key,
+                ScreenProperties {
+                    screen: screen.clone(),
+                    navigator: self.navigator.clone(),
+                },
+            );
+            Some(if index == top {
+                Item::auto(screen)
+            } else {
+                Item::fixed(0)(Layout::hidden(screen))
+            })
+        }))
+    }
+}
+
+struct ScreenProperties {
+    screen: Callback<Navigator, Layout>,
+    navigator: Navigator,
+}
+
+struct Screen {
+    properties: ScreenProperties,
+}
+
+impl Component for Screen {
+    type Message = ();
+    type Properties = ScreenProperties;
+
+    fn create(properties: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+        Self { properties }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        self.properties
+            .screen
+            .emit(self.properties.navigator.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        app::ComponentSelector,
+        components::text::{Text, TextProperties},
+        testing::Harness,
+        Size,
+    };
+
+    type Changes = Rc<RefCell<Vec<String>>>;
+
+    fn router(initial: &'static str) -> (Harness, Changes) {
+        let changes = Changes::default();
+        let screen = |name: &'static str| {
+            move |_: Navigator| Text::with(TextProperties::new().content(name))
+        };
+        let harness = Harness::new(
+            Size::new(10, 1),
+            Router::with(
+                RouterProperties::new(initial)
+                    .route("home", screen("home"))
+                    .route("settings", screen("settings"))
+                    .route("about", screen("about"))
+                    .on_change({
+                        let changes = Rc::clone(&changes);
+                        move |route: Cow<'static, str>| changes.borrow_mut().push(route.into())
+                    }),
+            ),
+        );
+        (harness, changes)
+    }
+
+    fn navigator(harness: &mut Harness) -> Navigator {
+        harness.canvas();
+        harness
+            .app()
+            .with_component(&ComponentSelector::of::<Router>(), |router: &Router| {
+                router.navigator.clone()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn push_and_pop_screens() {
+        let (mut harness, changes) = router("home");
+        let navigator = navigator(&mut harness);
+        assert_eq!(harness.line(0), "home");
+
+        navigator.push("settings");
+        assert_eq!(harness.line(0), "settings");
+        navigator.push("about");
+        assert_eq!(harness.line(0), "about");
+        navigator.pop();
+        assert_eq!(harness.line(0), "settings");
+        navigator.pop();
+        assert_eq!(harness.line(0), "home");
+
+        // The root screen is never popped
+        navigator.pop();
+        assert_eq!(harness.line(0), "home");
+        assert_eq!(*changes.borrow(), ["settings", "about", "settings", "home"]);
+    }
+
+    #[test]
+    fn replace_the_current_screen() {
+        let (mut harness, changes) = router("home");
+        let navigator = navigator(&mut harness);
+
+        navigator.push("settings");
+        navigator.replace("about");
+        assert_eq!(harness.line(0), "about");
+
+        // The replaced screen isn't on the stack anymore
+        navigator.pop();
+        assert_eq!(harness.line(0), "home");
+        assert_eq!(*changes.borrow(), ["settings", "about", "home"]);
+    }
+
+    #[test]
+    fn unknown_routes_are_ignored() {
+        let (mut harness, changes) = router("home");
+        let navigator = navigator(&mut harness);
+
+        navigator.push("nowhere");
+        navigator.replace("nowhere");
+        assert_eq!(harness.line(0), "home");
+        assert!(changes.borrow().is_empty());
+    }
+
+    #[test]
+    fn unknown_initial_route_shows_nothing() {
+        let (mut harness, changes) = router("nowhere");
+        let navigator = navigator(&mut harness);
+        assert_eq!(harness.line(0), "");
+        navigator.replace("settings");
+        assert_eq!(harness.line(0), "settings");
+        assert_eq!(*changes.borrow(), ["settings"]);
+    }
+}
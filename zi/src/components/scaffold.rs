@@ -0,0 +1,187 @@
+//! Application chrome with a header, a footer, two sidebars and a body.
+//!
+//! ```
+//! # use zi::prelude::*;
+//! # use zi::components::{
+//! #     scaffold::{Scaffold, ScaffoldProperties},
+//! #     text::{Text, TextProperties},
+//! # };
+//! let label = |text: &'static str| move || Text::with(TextProperties::new().content(text));
+//! let app = Scaffold::with(
+//!     ScaffoldProperties::new(label("body"))
+//!         .header(1, label("title"))
+//!         .footer(1, label("status"))
+//!         .left_sidebar(20, label("files"))
+//!         .collapse_width(60),
+//! );
+//! ```
+use super::split::Pane;
+use crate::{Children, Component, ComponentExt, ComponentLink, Item, Layout, Rect, ShouldRender};
+
+/// A region of the scaffold with its size along the edge it's attached to,
+/// i.e. the height of the header and footer and the width of the sidebars.
+#[derive(Clone, PartialEq)]
+pub struct Slot {
+    pub content: Children,
+    pub size: usize,
+}
+
+impl Slot {
+    pub fn new(size: usize, content: impl Into<Children>) -> Self {
+        Self {
+            content: content.into(),
+            size,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ScaffoldProperties {
+    pub body: Children,
+    pub header: Option<Slot>,
+    pub footer: Option<Slot>,
+    pub left_sidebar: Option<Slot>,
+    pub right_sidebar: Option<Slot>,
+    /// The sidebars are collapsed when the scaffold is narrower than this.
+    pub collapse_width: usize,
+}
+
+impl ScaffoldProperties {
+    pub fn new(body: impl Into<Children>) -> Self {
+        Self {
+            body: body.into(),
+            header: None,
+            footer: None,
+            left_sidebar: None,
+            right_sidebar: None,
+            collapse_width: 0,
+        }
+    }
+
+    pub fn header(mut self, height: usize, content: impl Into<Children>) -> Self {
+        self.header = Some(Slot::new(height, content));
+        self
+    }
+
+    pub fn footer(mut self, height: usize, content: impl Into<Children>) -> Self {
+        self.footer = Some(Slot::new(height, content));
+        self
+    }
+
+    pub fn left_sidebar(mut self, width: usize, content: impl Into<Children>) -> Self {
+        self.left_sidebar = Some(Slot::new(width, content));
+        self
+    }
+
+    pub fn right_sidebar(mut self, width: usize, content: impl Into<Children>) -> Self {
+        self.right_sidebar = Some(Slot::new(width, content));
+        self
+    }
+
+    /// Collapses the sidebars when the scaffold is narrower than `width`.
+    /// Collapsed sidebars are hidden rather than unmounted, so they keep their
+    /// state until there is enough space to show them again.
+    pub fn collapse_width(mut self, width: usize) -> Self {
+        self.collapse_width = width;
+        self
+    }
+}
+
+pub struct Scaffold {
+    properties: ScaffoldProperties,
+    frame: Rect,
+}
+
+impl Scaffold {
+    /// Whether the sidebars are currently collapsed.
+    pub fn collapsed(&self) -> bool {
+        self.frame.size.width < self.properties.collapse_width
+    }
+
+    fn sidebar(&self, key: &str, slot: Option<&Slot>) -> Option<Item> {
+        let slot = slot?;
+        let pane = Pane::with_key(key, slot.content.clone());
+        Some(if self.collapsed() {
+            Item::fixed(0)(Layout::hidden(pane))
+        } else {
+            Item::fixed(slot.size)(pane)
+        })
+    }
+}
+
+impl Component for Scaffold {
+    type Message = ();
+    type Properties = ScaffoldProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
+        Self { properties, frame }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            self.properties = properties;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        // The regions have fixed sizes, only collapsing the sidebars changes
+        // the layout
+        let collapsed = self.collapsed();
+        self.frame = frame;
+        (collapsed != self.collapsed()).into()
+    }
+
+    fn view(&self) -> Layout {
+        let properties = &self.properties;
+        let middle = Layout::row(
+            self.sidebar("left-sidebar", properties.left_sidebar.as_ref())
+                .into_iter()
+                .chain(Some(Item::auto(Pane::with_key(
+                    "body",
+                    properties.body.clone(),
+                ))))
+                .chain(self.sidebar("right-sidebar", properties.right_sidebar.as_ref())),
+        );
+        let edge = |key: &str, slot: Option<&Slot>| {
+            slot.map(|slot| Item::fixed(slot.size)(Pane::with_key(key, slot.content.clone())))
+        };
+        Layout::column(
+            edge("header", properties.header.as_ref())
+                .into_iter()
+                .chain(Some(Item::auto(middle)))
+                .chain(edge("footer", properties.footer.as_ref())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::text::{Text, TextProperties},
+        testing::Harness,
+        Size,
+    };
+
+    #[test]
+    fn collapses_sidebars_when_narrow() {
+        let label = |text: &'static str| move || Text::with(TextProperties::new().content(text));
+        let mut harness = Harness::new(
+            Size::new(10, 4),
+            Scaffold::with(
+                ScaffoldProperties::new(label("body"))
+                    .header(1, label("head"))
+                    .footer(1, label("foot"))
+                    .left_sidebar(2, label("side"))
+                    .collapse_width(8),
+            ),
+        );
+        assert_eq!(harness.text(), "head\nsibody\n\nfoot");
+
+        harness.resize(Size::new(6, 4));
+        assert_eq!(harness.text(), "head\nbody\n\nfoot");
+    }
+}
@@ -0,0 +1,228 @@
+//! A scroll position that can be shared between components.
+//!
+//! A [`ScrollModel`](struct.ScrollModel.html) is a cheap to clone handle to a
+//! scroll offset. Components showing different views of the same content
+//! (e.g. side-by-side diff panes, or some code and its minimap) can share a
+//! model to keep their offsets in sync. Whenever the offset or the extent of
+//! the content changes, all subscribers are notified, typically by sending a
+//! message to their component using a callback from a
+//! [`ComponentLink`](../../struct.ComponentLink.html).
+use std::{
+    cell::RefCell,
+    cmp, fmt,
+    rc::{Rc, Weak},
+};
+
+use crate::Callback;
+
+/// A shared scroll offset, measured in lines (or items) from the start of the
+/// content.
+///
+/// The extent of the content (the total number of lines and how many of them
+/// are visible at once) is set by the component that owns the main view of the
+/// content and it is used to clamp the offset. Other components typically
+/// only read the extent, e.g. to draw a viewport indicator.
+#[derive(Clone)]
+pub struct ScrollModel(Rc<RefCell<ScrollState>>);
+
+impl ScrollModel {
+    pub fn new() -> Self {
+        Self(Rc::new(RefCell::new(ScrollState {
+            offset: 0,
+            total: 0,
+            viewport: 0,
+            subscribers: Vec::new(),
+            next_subscriber_id: 0,
+        })))
+    }
+
+    /// The index of the first visible line.
+    pub fn offset(&self) -> usize {
+        self.0.borrow().offset
+    }
+
+    /// The total number of lines of the content.
+    pub fn total(&self) -> usize {
+        self.0.borrow().total
+    }
+
+    /// The number of lines visible at once in the main view.
+    pub fn viewport(&self) -> usize {
+        self.0.borrow().viewport
+    }
+
+    /// The largest offset s.t. the viewport is still filled with content.
+    pub fn max_offset(&self) -> usize {
+        let state = self.0.borrow();
+        state.total.saturating_sub(state.viewport)
+    }
+
+    /// Scrolls to the given offset, clamped to `max_offset()`. Subscribers are
+    /// notified if the offset changed.
+    pub fn set_offset(&self, offset: usize) {
+        let offset = cmp::min(offset, self.max_offset());
+        self.update(|state| state.offset = offset);
+    }
+
+    /// Scrolls by a number of lines, towards the end for positive values and
+    /// towards the start for negative ones.
+    pub fn scroll_by(&self, delta: isize) {
+        let offset = self.offset();
+        self.set_offset(if delta < 0 {
+            offset.saturating_sub(delta.unsigned_abs())
+        } else {
+            offset.saturating_add(delta as usize)
+        });
+    }
+
+    /// Updates the extent of the content. The offset is clamped to the new
+    /// extent. Subscribers are notified if anything changed.
+    pub fn set_extent(&self, total: usize, viewport: usize) {
+        self.update(|state| {
+            state.total = total;
+            state.viewport = viewport;
+            state.offset = cmp::min(state.offset, total.saturating_sub(viewport));
+        });
+    }
+
+    /// Registers a callback called with the new offset whenever the offset or
+    /// the extent changes.
+    ///
+    /// The callback is unregistered when the returned subscription is dropped,
+    /// components should store it alongside their state.
+    pub fn subscribe(&self, on_change: impl Into<Callback<usize>>) -> ScrollSubscription {
+        let mut state = self.0.borrow_mut();
+        let id = state.next_subscriber_id;
+        state.next_subscriber_id += 1;
+        state.subscribers.push((id, on_change.into()));
+        ScrollSubscription {
+            state: Rc::downgrade(&self.0),
+            id,
+        }
+    }
+
+    fn update(&self, update: impl FnOnce(&mut ScrollState)) {
+        let (offset, subscribers) = {
+            let mut state = self.0.borrow_mut();
+            let old = (state.offset, state.total, state.viewport);
+            update(&mut state);
+            if (state.offset, state.total, state.viewport) == old {
+                return;
+            }
+            // Callbacks are run after releasing the borrow as they may query
+            // the model
+            let subscribers: Vec<_> = state
+                .subscribers
+                .iter()
+                .map(|(_, callback)| callback.clone())
+                .collect();
+            (state.offset, subscribers)
+        };
+        for callback in subscribers {
+            callback.emit(offset);
+        }
+    }
+}
+
+impl Default for ScrollModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for ScrollModel {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for ScrollModel {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.0.borrow();
+        formatter
+            .debug_struct("ScrollModel")
+            .field("offset", &state.offset)
+            .field("total", &state.total)
+            .field("viewport", &state.viewport)
+            .field("subscribers", &state.subscribers.len())
+            .finish()
+    }
+}
+
+/// Keeps a callback registered with a [`ScrollModel`](struct.ScrollModel.html)
+/// until dropped.
+pub struct ScrollSubscription {
+    state: Weak<RefCell<ScrollState>>,
+    id: usize,
+}
+
+impl Drop for ScrollSubscription {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            state
+                .borrow_mut()
+                .subscribers
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+struct ScrollState {
+    offset: usize,
+    total: usize,
+    viewport: usize,
+    subscribers: Vec<(usize, Callback<usize>)>,
+    next_subscriber_id: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_is_clamped_to_extent() {
+        let model = ScrollModel::new();
+        model.set_offset(10);
+        assert_eq!(model.offset(), 0);
+
+        model.set_extent(100, 20);
+        model.set_offset(90);
+        assert_eq!(model.offset(), 80);
+        model.scroll_by(-100);
+        assert_eq!(model.offset(), 0);
+        model.scroll_by(5);
+        assert_eq!(model.offset(), 5);
+
+        // Shrinking the content clamps the offset
+        model.set_extent(10, 8);
+        assert_eq!(model.offset(), 2);
+    }
+
+    #[test]
+    fn subscribers_are_notified_of_changes() {
+        let model = ScrollModel::new();
+        model.set_extent(100, 10);
+
+        let offsets = Rc::new(RefCell::new(Vec::new()));
+        let subscription = {
+            let offsets = Rc::clone(&offsets);
+            model.subscribe(move |offset| offsets.borrow_mut().push(offset))
+        };
+
+        // Handles share the same state
+        let other = model.clone();
+        other.set_offset(3);
+        other.set_offset(3);
+        model.scroll_by(2);
+        assert_eq!(*offsets.borrow(), vec![3, 5]);
+
+        // Changes to the extent are notified too
+        model.set_extent(100, 20);
+        model.set_extent(100, 20);
+        assert_eq!(*offsets.borrow(), vec![3, 5, 5]);
+
+        drop(subscription);
+        model.set_offset(0);
+        assert_eq!(*offsets.borrow(), vec![3, 5, 5]);
+    }
+}
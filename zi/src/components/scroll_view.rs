@@ -0,0 +1,236 @@
+//! A scrollable container for content larger than its frame.
+//!
+//! Unlike a [`Viewport`](../viewport/struct.Viewport.html), which asks the
+//! content to render only what is visible, a
+//! [`ScrollView`](struct.ScrollView.html) wraps an arbitrary layout. The
+//! layout is laid out at its preferred size and clipped to the frame of the
+//! scroll view, which is convenient for content that is not too large, e.g.
+//! a form or a settings page with more fields than fit on the screen.
+use std::cmp;
+
+use super::scroll::{ScrollModel, ScrollSubscription};
+use crate::{
+    terminal::Modifiers, Bindings, Children, Component, ComponentLink, Key, Layout, Position, Rect,
+    ShouldRender, Size,
+};
+
+pub struct ScrollViewProperties {
+    pub content: Children,
+    pub focused: bool,
+    /// Shares the vertical scroll offset with other components.
+    pub scroll: Option<ScrollModel>,
+}
+
+impl ScrollViewProperties {
+    pub fn new(content: impl Into<Children>) -> Self {
+        Self {
+            content: content.into(),
+            focused: false,
+            scroll: None,
+        }
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: ScrollModel) -> Self {
+        self.scroll = Some(scroll);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    Scroll(isize),
+    ScrollColumns(isize),
+    ScrollPages(isize),
+    FirstRow,
+    LastRow,
+    Scrolled,
+}
+
+pub struct ScrollView {
+    properties: ScrollViewProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    scroll: ScrollModel,
+    scroll_subscription: ScrollSubscription,
+    column: usize,
+    content_size: Size,
+}
+
+impl ScrollView {
+    /// The size of the content, as it is laid out before clipping.
+    pub fn content_size(&self) -> Size {
+        self.content_size
+    }
+
+    fn max_column(&self) -> usize {
+        self.content_size
+            .width
+            .saturating_sub(self.frame.size.width)
+    }
+
+    fn update_extent(&mut self) {
+        self.content_size = self
+            .properties
+            .content
+            .view()
+            .size_hint(self.frame.size)
+            .preferred
+            .max(self.frame.size);
+        self.scroll
+            .set_extent(self.content_size.height, self.frame.size.height);
+        self.column = cmp::min(self.column, self.max_column());
+    }
+}
+
+impl Component for ScrollView {
+    type Message = Message;
+    type Properties = ScrollViewProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let scroll = properties.scroll.clone().unwrap_or_default();
+        let scroll_subscription = scroll.subscribe(link.callback(|_| Message::Scrolled));
+        let mut scroll_view = Self {
+            properties,
+            frame,
+            link,
+            scroll,
+            scroll_subscription,
+            column: 0,
+            content_size: Size::zero(),
+        };
+        scroll_view.update_extent();
+        scroll_view
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties.scroll != properties.scroll {
+            self.scroll = properties.scroll.clone().unwrap_or_default();
+            self.scroll_subscription = self
+                .scroll
+                .subscribe(self.link.callback(|_| Message::Scrolled));
+        }
+        self.properties = properties;
+        self.update_extent();
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        self.update_extent();
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::Scroll(rows) => self.scroll.scroll_by(rows),
+            Message::ScrollColumns(columns) => {
+                let column = if columns < 0 {
+                    self.column.saturating_sub(columns.unsigned_abs())
+                } else {
+                    self.column.saturating_add(columns as usize)
+                };
+                let column = cmp::min(column, self.max_column());
+                if column == self.column {
+                    return ShouldRender::No;
+                }
+                self.column = column;
+            }
+            Message::ScrollPages(pages) => self
+                .scroll
+                .scroll_by(pages * cmp::max(self.frame.size.height, 1) as isize),
+            Message::FirstRow => self.scroll.set_offset(0),
+            Message::LastRow => self.scroll.set_offset(self.scroll.max_offset()),
+            Message::Scrolled => {}
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        Layout::scrolled(
+            Position::new(self.column, self.scroll.offset()),
+            self.properties.content.view(),
+        )
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("row-down", || Message::Scroll(1))
+            .with([Key::Down])
+            .with([Key::Ctrl('n')])
+            .with([Key::ScrollDown(Modifiers::NONE)]);
+        bindings
+            .command("row-up", || Message::Scroll(-1))
+            .with([Key::Up])
+            .with([Key::Ctrl('p')])
+            .with([Key::ScrollUp(Modifiers::NONE)]);
+        bindings
+            .command("column-right", || Message::ScrollColumns(1))
+            .with([Key::Right])
+            .with([Key::Ctrl('f')]);
+        bindings
+            .command("column-left", || Message::ScrollColumns(-1))
+            .with([Key::Left])
+            .with([Key::Ctrl('b')]);
+        bindings
+            .command("page-down", || Message::ScrollPages(1))
+            .with([Key::PageDown])
+            .with([Key::Ctrl('v')]);
+        bindings
+            .command("page-up", || Message::ScrollPages(-1))
+            .with([Key::PageUp])
+            .with([Key::Alt('v')]);
+        bindings
+            .command("first-row", || Message::FirstRow)
+            .with([Key::Alt('<')])
+            .with([Key::Home]);
+        bindings
+            .command("last-row", || Message::LastRow)
+            .with([Key::Alt('>')])
+            .with([Key::End]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::text::{Text, TextProperties},
+        testing::Harness,
+        ComponentExt, Item,
+    };
+
+    #[test]
+    fn clips_its_content() {
+        let content = || {
+            Layout::column((0..10).map(|index| {
+                Item::fixed(1)(Text::with_key(
+                    index,
+                    TextProperties::new().content(format!("line {}", index)),
+                ))
+            }))
+        };
+        let mut harness = Harness::new(
+            Size::new(6, 3),
+            ScrollView::with(ScrollViewProperties::new(content).focused(true)),
+        );
+
+        harness.key(Key::Down);
+        assert_eq!(harness.text(), "line 1\nline 2\nline 3");
+        harness.key(Key::Alt('>'));
+        assert_eq!(harness.text(), "line 7\nline 8\nline 9");
+        harness.key(Key::PageUp);
+        assert_eq!(harness.text(), "line 4\nline 5\nline 6");
+        // The content is as wide as the frame, there is nothing to scroll
+        harness.key(Key::Right);
+        assert_eq!(harness.text(), "line 4\nline 5\nline 6");
+    }
+}
@@ -0,0 +1,344 @@
+//! A vertical or horizontal scrollbar.
+//!
+//! A [`Scrollbar`](struct.Scrollbar.html) draws a thumb over a track, sized
+//! and positioned from the `total` length of some content, how much of it is
+//! visible at once (the `viewport`) and the current `offset`. Clicking or
+//! dragging along the track requests a new offset. The extent can either be
+//! passed as properties, or read from a
+//! [`ScrollModel`](../scroll/struct.ScrollModel.html) shared with the
+//! component showing the content (e.g. a
+//! [`Select`](../select/struct.Select.html)), in which case the scrollbar
+//! updates the model directly.
+use std::{cmp, ops::Range};
+
+use super::scroll::{ScrollModel, ScrollSubscription};
+use crate::{
+    terminal::{Modifiers, MouseButton},
+    Bindings, Callback, Canvas, Colour, Component, ComponentLink, Key, Layout, Position, Rect,
+    ShouldRender, Size, SizeHint, Style,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    Vertical,
+    Horizontal,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollbarStyle {
+    pub track: Style,
+    pub thumb: Style,
+}
+
+impl Default for ScrollbarStyle {
+    fn default() -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const DARK3: Colour = Colour::rgb(102, 92, 84);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+
+        Self {
+            track: Style::normal(DARK0_SOFT, LIGHT2),
+            thumb: Style::normal(DARK3, LIGHT2),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ScrollbarProperties {
+    pub style: ScrollbarStyle,
+    pub orientation: Orientation,
+    /// The length of the content.
+    pub total: usize,
+    /// How much of the content is visible at once.
+    pub viewport: usize,
+    /// The position of the first visible line (or column) of the content.
+    pub offset: usize,
+    /// Called with the offset requested by clicking or dragging.
+    pub on_change: Option<Callback<usize>>,
+    /// Like key presses, clicks are delivered to the focused components.
+    pub focused: bool,
+    /// Reads the extent from a shared scroll model instead of the properties
+    /// above.
+    pub scroll: Option<ScrollModel>,
+}
+
+impl ScrollbarProperties {
+    pub fn new(orientation: Orientation) -> Self {
+        Self {
+            style: ScrollbarStyle::default(),
+            orientation,
+            total: 0,
+            viewport: 0,
+            offset: 0,
+            on_change: None,
+            focused: false,
+            scroll: None,
+        }
+    }
+
+    pub fn style(mut self, style: ScrollbarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn extent(mut self, total: usize, viewport: usize, offset: usize) -> Self {
+        self.total = total;
+        self.viewport = viewport;
+        self.offset = offset;
+        self
+    }
+
+    pub fn on_change(mut self, on_change: impl Into<Callback<usize>>) -> Self {
+        self.on_change = Some(on_change.into());
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: ScrollModel) -> Self {
+        self.scroll = Some(scroll);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// Scroll s.t. the thumb is centred at a position on screen.
+    ScrollTo(Position),
+    Scrolled,
+}
+
+pub struct Scrollbar {
+    properties: ScrollbarProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    scroll_subscription: Option<ScrollSubscription>,
+}
+
+impl Scrollbar {
+    /// The total, viewport and offset, either from the properties or from
+    /// the shared scroll model.
+    fn extent(&self) -> (usize, usize, usize) {
+        match self.properties.scroll {
+            Some(ref scroll) => (scroll.total(), scroll.viewport(), scroll.offset()),
+            None => (
+                self.properties.total,
+                self.properties.viewport,
+                self.properties.offset,
+            ),
+        }
+    }
+
+    /// The length of the track, i.e. the size of the frame along the
+    /// orientation of the scrollbar.
+    fn length(&self) -> usize {
+        match self.properties.orientation {
+            Orientation::Vertical => self.frame.size.height,
+            Orientation::Horizontal => self.frame.size.width,
+        }
+    }
+
+    fn subscribe_to_scroll(&mut self) {
+        self.scroll_subscription = self
+            .properties
+            .scroll
+            .as_ref()
+            .map(|scroll| scroll.subscribe(self.link.callback(|_| Message::Scrolled)));
+    }
+}
+
+impl Component for Scrollbar {
+    type Message = Message;
+    type Properties = ScrollbarProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let mut scrollbar = Self {
+            properties,
+            frame,
+            link,
+            scroll_subscription: None,
+        };
+        scrollbar.subscribe_to_scroll();
+        scrollbar
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            let scroll_changed = self.properties.scroll != properties.scroll;
+            self.properties = properties;
+            if scroll_changed {
+                self.subscribe_to_scroll();
+            }
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        let position = match message {
+            Message::ScrollTo(position) => position,
+            Message::Scrolled => return ShouldRender::Yes,
+        };
+        let along = match self.properties.orientation {
+            Orientation::Vertical => position.y.saturating_sub(self.frame.origin.y),
+            Orientation::Horizontal => position.x.saturating_sub(self.frame.origin.x),
+        };
+        let (total, viewport, current_offset) = self.extent();
+        let new_offset = offset_at(self.length(), total, viewport, along);
+        if new_offset != current_offset {
+            if let Some(scroll) = self.properties.scroll.as_ref() {
+                scroll.set_offset(new_offset);
+            }
+            if let Some(on_change) = self.properties.on_change.as_mut() {
+                on_change.emit(new_offset);
+            }
+        }
+        ShouldRender::No
+    }
+
+    fn size_hint(properties: &Self::Properties, available: Size) -> SizeHint {
+        match properties.orientation {
+            Orientation::Vertical => SizeHint::new(
+                Size::new(1, 1),
+                Size::new(1, available.height),
+                Size::new(1, usize::MAX),
+            ),
+            Orientation::Horizontal => SizeHint::new(
+                Size::new(1, 1),
+                Size::new(available.width, 1),
+                Size::new(usize::MAX, 1),
+            ),
+        }
+    }
+
+    fn view(&self) -> Layout {
+        let (total, viewport, offset) = self.extent();
+        let thumb = thumb(self.length(), total, viewport, offset);
+        let thumb_region = match self.properties.orientation {
+            Orientation::Vertical => Rect::new(
+                Position::new(0, thumb.start),
+                Size::new(self.frame.size.width, thumb.len()),
+            ),
+            Orientation::Horizontal => Rect::new(
+                Position::new(thumb.start, 0),
+                Size::new(thumb.len(), self.frame.size.height),
+            ),
+        };
+
+        let mut canvas = Canvas::new(self.frame.size);
+        canvas.clear(self.properties.style.track);
+        canvas.clear_region(thumb_region, self.properties.style.thumb);
+        canvas.into()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("scroll-to", Message::ScrollTo)
+            .with([Key::Click(MouseButton::Left, Modifiers::NONE)])
+            .with([Key::Drag(MouseButton::Left, Modifiers::NONE)]);
+    }
+}
+
+/// The cells covered by the thumb on a track of a given length. The thumb is
+/// at least one cell long and fills the track if the whole content is
+/// visible.
+fn thumb(length: usize, total: usize, viewport: usize, offset: usize) -> Range<usize> {
+    if total <= viewport {
+        return 0..length;
+    }
+    let size = cmp::min(cmp::max(length * viewport / total, 1), length);
+    let max_offset = total - viewport;
+    let start = ((length - size) * cmp::min(offset, max_offset) + max_offset / 2) / max_offset;
+    start..start + size
+}
+
+/// The offset which centres the thumb at a position along the track.
+fn offset_at(length: usize, total: usize, viewport: usize, along: usize) -> usize {
+    let max_offset = total.saturating_sub(viewport);
+    let size = thumb(length, total, viewport, 0).len();
+    let free = length - size;
+    if free == 0 {
+        return 0;
+    }
+    let start = cmp::min(along.saturating_sub(size / 2), free);
+    (start * max_offset + free / 2) / free
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        terminal::{Event, MouseEvent, MouseEventKind},
+        testing::Harness,
+        ComponentExt,
+    };
+
+    #[test]
+    fn thumb_is_proportional_to_the_viewport() {
+        assert_eq!(thumb(10, 100, 20, 0), 0..2);
+        assert_eq!(thumb(10, 100, 20, 40), 4..6);
+        assert_eq!(thumb(10, 100, 20, 80), 8..10);
+        assert_eq!(thumb(10, 1000, 1, 999), 9..10);
+        assert_eq!(thumb(10, 5, 20, 0), 0..10);
+
+        // Clicking on the thumb's position scrolls back to its offset
+        assert_eq!(offset_at(10, 100, 20, 5), 40);
+        assert_eq!(offset_at(10, 100, 20, 0), 0);
+        assert_eq!(offset_at(10, 100, 20, 9), 80);
+        assert_eq!(offset_at(10, 5, 20, 3), 0);
+    }
+
+    #[test]
+    fn follows_and_moves_a_shared_scroll() {
+        let scroll = ScrollModel::new();
+        scroll.set_extent(100, 20);
+        let mut harness = Harness::new(
+            Size::new(1, 10),
+            Scrollbar::with(
+                ScrollbarProperties::new(Orientation::Vertical)
+                    .focused(true)
+                    .scroll(scroll.clone()),
+            ),
+        );
+        let thumb = |harness: &mut Harness| {
+            let canvas = harness.canvas();
+            (0..10)
+                .filter(|&y| {
+                    canvas.textel(0, y).as_ref().unwrap().style == ScrollbarStyle::default().thumb
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(thumb(&mut harness), [0, 1]);
+        scroll.set_offset(80);
+        assert_eq!(thumb(&mut harness), [8, 9]);
+
+        for (kind, y) in [
+            (MouseEventKind::Down(MouseButton::Left), 5),
+            (MouseEventKind::Drag(MouseButton::Left), 3),
+        ] {
+            let timestamp = harness.now();
+            harness.input(Event::Mouse(MouseEvent {
+                kind,
+                position: Position::new(0, y),
+                modifiers: Modifiers::NONE,
+                timestamp,
+            }));
+        }
+        assert_eq!(scroll.offset(), 20);
+        assert_eq!(thumb(&mut harness), [2, 3]);
+    }
+}
@@ -0,0 +1,471 @@
+//! Incremental search over the content of another component.
+//!
+//! The [`SearchBar`](struct.SearchBar.html) pairs a text input with a
+//! [`SearchTarget`](trait.SearchTarget.html), which is implemented by
+//! whatever is searched (some text, the rows of a table, the items of a list).
+//! As the query is typed, the search bar finds all the matches in the target
+//! and emits their positions s.t. the component showing the target can
+//! highlight them and scroll to the current one.
+use ropey::{Rope, RopeSlice};
+use std::{cmp, rc::Rc};
+
+use super::input::{Cursor, Input, InputChange, InputProperties, InputStyle};
+use crate::{
+    Bindings, Callback, Canvas, Colour, Component, ComponentExt, ComponentLink, Item, Key, Layout,
+    Rect, ShouldRender, Size, Style,
+};
+
+/// The position of a match. `line` is the index of the line (or row, or item)
+/// of the target and the range is in chars relative to the start of the line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Content that can be searched by a [`SearchBar`](struct.SearchBar.html).
+pub trait SearchTarget {
+    /// Returns all the matches of a non-empty query, in order.
+    fn find(&self, query: &str) -> Vec<SearchMatch>;
+}
+
+impl SearchTarget for Rope {
+    fn find(&self, query: &str) -> Vec<SearchMatch> {
+        find_in_lines(self.lines().map(line_text), query)
+    }
+}
+
+impl SearchTarget for str {
+    fn find(&self, query: &str) -> Vec<SearchMatch> {
+        find_in_lines(self.lines(), query)
+    }
+}
+
+impl SearchTarget for String {
+    fn find(&self, query: &str) -> Vec<SearchMatch> {
+        SearchTarget::find(self.as_str(), query)
+    }
+}
+
+/// Every item is searched as a line, e.g. the items of a list.
+impl<T: AsRef<str>> SearchTarget for [T] {
+    fn find(&self, query: &str) -> Vec<SearchMatch> {
+        find_in_lines(self.iter(), query)
+    }
+}
+
+impl<T: AsRef<str>> SearchTarget for Vec<T> {
+    fn find(&self, query: &str) -> Vec<SearchMatch> {
+        SearchTarget::find(self.as_slice(), query)
+    }
+}
+
+/// Finds all the occurrences of the query, line by line. The search is case
+/// insensitive unless the query contains uppercase characters.
+///
+/// This is a building block for implementing
+/// [`SearchTarget`](trait.SearchTarget.html), e.g. for a table the lines could
+/// be its rows with the cells joined by spaces.
+pub fn find_in_lines(
+    lines: impl IntoIterator<Item = impl AsRef<str>>,
+    query: &str,
+) -> Vec<SearchMatch> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let normalise = |text: &str| -> Vec<char> {
+        if case_sensitive {
+            text.chars().collect()
+        } else {
+            // Lowercase char by char s.t. char offsets are preserved
+            text.chars()
+                .map(|character| character.to_lowercase().next().unwrap_or(character))
+                .collect()
+        }
+    };
+    let query = normalise(query);
+
+    let mut matches = Vec::new();
+    for (line_index, line) in lines.into_iter().enumerate() {
+        let line = normalise(line.as_ref());
+        let mut start = 0;
+        while start + query.len() <= line.len() {
+            if line[start..start + query.len()] == query[..] {
+                matches.push(SearchMatch {
+                    line: line_index,
+                    start,
+                    end: start + query.len(),
+                });
+                start += query.len();
+            } else {
+                start += 1;
+            }
+        }
+    }
+    matches
+}
+
+/// The text of a line without the line ending and with tabs expanded to spaces
+/// (one per tab s.t. char offsets are preserved).
+pub(crate) fn line_text(line: RopeSlice) -> String {
+    line.chars()
+        .take_while(|&character| character != '\n' && character != '\r')
+        .map(|character| if character == '\t' { ' ' } else { character })
+        .collect()
+}
+
+/// The outcome of a search, emitted by the search bar whenever the query, the
+/// target or the current match change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchResults {
+    pub query: String,
+    pub matches: Rc<[SearchMatch]>,
+    /// The index of the current match in `matches`.
+    pub current: Option<usize>,
+}
+
+impl SearchResults {
+    pub fn current_match(&self) -> Option<SearchMatch> {
+        self.current.map(|index| self.matches[index])
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchBarStyle {
+    pub input: InputStyle,
+    pub prompt: Style,
+    /// The style of the match counter.
+    pub status: Style,
+    /// The style of the match counter when there are no matches.
+    pub not_found: Style,
+}
+
+impl Default for SearchBarStyle {
+    fn default() -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const GRAY: Colour = Colour::rgb(146, 131, 116);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const BRIGHT_RED: Colour = Colour::rgb(251, 73, 52);
+
+        Self {
+            input: InputStyle::default(),
+            prompt: Style::bold(DARK0_SOFT, LIGHT2),
+            status: Style::normal(DARK0_SOFT, GRAY),
+            not_found: Style::normal(DARK0_SOFT, BRIGHT_RED),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SearchBarProperties {
+    pub target: Rc<dyn SearchTarget>,
+    pub style: SearchBarStyle,
+    pub prompt: String,
+    pub focused: bool,
+    pub on_change: Option<Callback<SearchResults>>,
+    /// Called when the search is dismissed with `Esc`.
+    pub on_close: Option<Callback<()>>,
+}
+
+impl SearchBarProperties {
+    /// The target is compared by pointer to decide whether it changed, pass
+    /// in a new `Rc` when the content is modified.
+    pub fn new(target: Rc<dyn SearchTarget>) -> Self {
+        Self {
+            target,
+            style: SearchBarStyle::default(),
+            prompt: "Search: ".into(),
+            focused: false,
+            on_change: None,
+            on_close: None,
+        }
+    }
+
+    pub fn style(mut self, style: SearchBarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn on_change(mut self, on_change: impl Into<Callback<SearchResults>>) -> Self {
+        self.on_change = Some(on_change.into());
+        self
+    }
+
+    pub fn on_close(mut self, on_close: impl Into<Callback<()>>) -> Self {
+        self.on_close = Some(on_close.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    InputChange(InputChange),
+    NextMatch,
+    PreviousMatch,
+    Close,
+}
+
+/// A single line search input with a match counter, see the
+/// [module documentation](index.html).
+pub struct SearchBar {
+    properties: SearchBarProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    content: Rope,
+    cursor: Cursor,
+    results: SearchResults,
+}
+
+impl SearchBar {
+    pub fn results(&self) -> &SearchResults {
+        &self.results
+    }
+
+    fn search(&mut self) {
+        let query: String = self.content.chars().filter(|&c| c != '\n').collect();
+        let matches: Rc<[SearchMatch]> = if query.is_empty() {
+            Rc::new([])
+        } else {
+            self.properties.target.find(&query).into()
+        };
+        // Stay on the same match if it's still there, e.g. when the target
+        // changes or the query is extended
+        let previous = self.results.current_match();
+        let current = match previous {
+            _ if matches.is_empty() => None,
+            Some(previous) => Some(
+                matches
+                    .iter()
+                    .position(|found| (found.line, found.start) >= (previous.line, previous.start))
+                    .unwrap_or(0),
+            ),
+            None => Some(0),
+        };
+        self.results = SearchResults {
+            query,
+            matches,
+            current,
+        };
+        self.emit();
+    }
+
+    fn move_to_match(&mut self, forward: bool) {
+        let num_matches = self.results.matches.len();
+        if num_matches == 0 {
+            return;
+        }
+        self.results.current = Some(match (self.results.current, forward) {
+            (Some(index), true) => (index + 1) % num_matches,
+            (Some(index), false) => (index + num_matches - 1) % num_matches,
+            (None, true) => 0,
+            (None, false) => num_matches - 1,
+        });
+        self.emit();
+    }
+
+    fn emit(&self) {
+        if let Some(on_change) = self.properties.on_change.as_ref() {
+            on_change.emit(self.results.clone());
+        }
+    }
+
+    fn status(&self) -> (String, Style) {
+        let SearchBarStyle {
+            status, not_found, ..
+        } = self.properties.style;
+        match self.results.current {
+            _ if self.results.query.is_empty() => (String::new(), status),
+            Some(index) => (
+                format!(" {}/{} ", index + 1, self.results.matches.len()),
+                status,
+            ),
+            None => (" No matches ".into(), not_found),
+        }
+    }
+}
+
+impl Component for SearchBar {
+    type Message = Message;
+    type Properties = SearchBarProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            link,
+            content: Rope::new(),
+            cursor: Cursor::new(),
+            results: SearchResults::default(),
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        let target_changed = !Rc::ptr_eq(&self.properties.target, &properties.target);
+        self.properties = properties;
+        if target_changed && !self.results.query.is_empty() {
+            self.search();
+        }
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::InputChange(InputChange { content, cursor }) => {
+                self.cursor = cursor;
+                if let Some(content) = content {
+                    self.content = content;
+                    self.search();
+                }
+            }
+            Message::NextMatch => self.move_to_match(true),
+            Message::PreviousMatch => self.move_to_match(false),
+            Message::Close => {
+                if let Some(on_close) = self.properties.on_close.as_ref() {
+                    on_close.emit(());
+                }
+            }
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let SearchBarProperties {
+            ref style,
+            ref prompt,
+            focused,
+            ..
+        } = self.properties;
+        let width = self.frame.size.width;
+
+        let prompt_width = cmp::min(prompt.chars().count(), width);
+        let mut prompt_canvas = Canvas::new(Size::new(prompt_width, 1));
+        prompt_canvas.clear(style.prompt);
+        prompt_canvas.draw_str(0, 0, style.prompt, prompt);
+
+        let (status, status_style) = self.status();
+        let status_width = cmp::min(status.chars().count(), width - prompt_width);
+        let mut status_canvas = Canvas::new(Size::new(status_width, 1));
+        status_canvas.clear(status_style);
+        status_canvas.draw_str(0, 0, status_style, &status);
+
+        Layout::row([
+            Item::fixed(prompt_width)(prompt_canvas),
+            Item::auto(Input::with(InputProperties {
+                style: style.input.clone(),
+                content: self.content.clone(),
+                cursor: self.cursor.clone(),
+                on_change: Some(self.link.callback(Message::InputChange)),
+                focused,
+            })),
+            Item::fixed(status_width)(status_canvas),
+        ])
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("next-match", || Message::NextMatch)
+            .with([Key::Char('\n')])
+            .with([Key::Ctrl('s')])
+            .with([Key::Down]);
+        bindings
+            .command("previous-match", || Message::PreviousMatch)
+            .with([Key::Ctrl('r')])
+            .with([Key::Up]);
+        bindings
+            .command("close", || Message::Close)
+            .with([Key::Esc])
+            .with([Key::Ctrl('g')]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    use crate::{testing::Harness, ComponentExt, Size};
+
+    #[test]
+    fn smart_case_matches() {
+        let lines = ["Foo foo", "bar", "FOOfoo"];
+        let found = |query| {
+            lines
+                .find(query)
+                .into_iter()
+                .map(|found| (found.line, found.start))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(found("foo"), vec![(0, 0), (0, 4), (2, 0), (2, 3)]);
+        assert_eq!(found("Foo"), vec![(0, 0)]);
+        assert!(found("").is_empty());
+
+        let rope = Rope::from("a\tb\nab\n");
+        assert_eq!(
+            rope.find("a b"),
+            vec![SearchMatch {
+                line: 0,
+                start: 0,
+                end: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn finds_matches_incrementally() {
+        let results = Rc::new(RefCell::new(SearchResults::default()));
+        let target: Rc<dyn SearchTarget> = Rc::new(vec!["apple", "banana", "Pineapple"]);
+        let mut harness = Harness::new(
+            Size::new(30, 1),
+            SearchBar::with(SearchBarProperties::new(target).focused(true).on_change({
+                let results = Rc::clone(&results);
+                move |new_results| *results.borrow_mut() = new_results
+            })),
+        );
+        let current = || {
+            let results = results.borrow();
+            (results.matches.len(), results.current_match())
+        };
+
+        harness.key(Key::Char('a'));
+        assert_eq!(current().0, 5);
+        harness.keys([Key::Char('p'), Key::Char('p')]);
+        assert_eq!(
+            current(),
+            (
+                2,
+                Some(SearchMatch {
+                    line: 0,
+                    start: 0,
+                    end: 3
+                })
+            )
+        );
+        harness.key(Key::Char('\n'));
+        assert_eq!(current().1.map(|found| found.line), Some(2));
+        harness.keys([Key::Up, Key::Up]);
+        assert_eq!(current().1.map(|found| found.line), Some(2));
+        harness.key(Key::Char('x'));
+        assert_eq!(current(), (0, None));
+    }
+}
@@ -1,9 +1,12 @@
 use std::{cmp, iter};
 
-use super::text::{Text, TextProperties};
+use super::{
+    scroll::{ScrollModel, ScrollSubscription},
+    text::{Text, TextProperties},
+};
 use crate::{
     Bindings, Callback, Component, ComponentExt, ComponentLink, FlexDirection, Item, Key, Layout,
-    Rect, ShouldRender, Style,
+    Rect, ShouldRender, Size, SizeHint, Style,
 };
 
 #[derive(Clone, PartialEq)]
@@ -16,6 +19,8 @@ pub struct SelectProperties {
     pub item_size: usize,
     pub selected: usize,
     pub on_change: Option<Callback<usize>>,
+    /// Shares the scroll offset with other components.
+    pub scroll: Option<ScrollModel>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -26,12 +31,15 @@ pub enum Message {
     LastItem,
     NextPage,
     PreviousPage,
+    Scrolled(usize),
 }
 
 pub struct Select {
     properties: SelectProperties,
     frame: Rect,
     offset: usize,
+    link: ComponentLink<Self>,
+    scroll_subscription: Option<ScrollSubscription>,
 }
 
 impl Select {
@@ -46,6 +54,18 @@ impl Select {
         } else if selected < self.offset {
             self.offset = selected;
         }
+
+        if let Some(scroll) = self.properties.scroll.as_ref() {
+            scroll.set_extent(self.properties.num_items, num_visible_items);
+            scroll.set_offset(self.offset);
+        }
+    }
+
+    fn subscribe_to_scroll(&mut self) {
+        self.scroll_subscription = self.properties.scroll.as_ref().map(|scroll| {
+            self.offset = scroll.offset();
+            scroll.subscribe(self.link.callback(Message::Scrolled))
+        });
     }
 }
 
@@ -53,19 +73,26 @@ impl Component for Select {
     type Message = Message;
     type Properties = SelectProperties;
 
-    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
         let mut select = Self {
             properties,
             frame,
             offset: 0,
+            link,
+            scroll_subscription: None,
         };
+        select.subscribe_to_scroll();
         select.ensure_selected_item_in_view();
         select
     }
 
     fn change(&mut self, properties: Self::Properties) -> ShouldRender {
         if self.properties != properties {
+            let scroll_changed = self.properties.scroll != properties.scroll;
             self.properties = properties;
+            if scroll_changed {
+                self.subscribe_to_scroll();
+            }
             self.ensure_selected_item_in_view();
             ShouldRender::Yes
         } else {
@@ -100,6 +127,12 @@ impl Component for Select {
             (Message::PreviousPage, false) | (Message::NextPage, true) => {
                 current_selected.saturating_sub(self.frame.size.height)
             }
+            (Message::Scrolled(offset), _) => {
+                // Another component sharing the scroll model moved it
+                let changed = self.offset != offset;
+                self.offset = offset;
+                return changed.into();
+            }
         };
         if current_selected != new_selected {
             if let Some(on_change) = self.properties.on_change.as_mut() {
@@ -109,6 +142,15 @@ impl Component for Select {
         ShouldRender::No
     }
 
+    fn size_hint(properties: &Self::Properties, available: Size) -> SizeHint {
+        let height = properties.num_items * properties.item_size;
+        SizeHint::new(
+            Size::new(0, cmp::min(properties.item_size, height)),
+            Size::new(available.width, height),
+            Size::new(usize::MAX, height),
+        )
+    }
+
     fn view(&self) -> Layout {
         let num_visible_items = cmp::min(
             self.properties.num_items.saturating_sub(self.offset),
@@ -0,0 +1,255 @@
+//! An animated spinner, e.g. to show that a background task is running.
+//!
+//! The [`Spinner`](struct.Spinner.html) is also a small example of a tick
+//! driven animation. It subscribes to ticks with
+//! [`Bindings::set_tickable`](../../struct.Bindings.html#method.set_tickable)
+//! only while spinning, s.t. an idle app doesn't wake up. Every tick adds the
+//! elapsed time to the current frame and the spinner advances once a full
+//! interval has passed, which keeps the animation speed independent of how
+//! often the backend ticks.
+use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    Bindings, Canvas, Component, ComponentLink, Layout, Rect, ShouldRender, Size, SizeHint, Style,
+};
+
+/// The frames of a spinner's animation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpinnerFrames {
+    Braille,
+    Dots,
+    Line,
+    Custom(Vec<String>),
+}
+
+impl SpinnerFrames {
+    const BRAILLE: &'static [&'static str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    const DOTS: &'static [&'static str] = &["   ", ".  ", ".. ", "..."];
+    const LINE: &'static [&'static str] = &["-", "\\", "|", "/"];
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Braille => Self::BRAILLE.len(),
+            Self::Dots => Self::DOTS.len(),
+            Self::Line => Self::LINE.len(),
+            Self::Custom(frames) => frames.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The frame at `index`, wrapping around at the end of the animation.
+    pub fn get(&self, index: usize) -> &str {
+        if self.is_empty() {
+            return "";
+        }
+        let index = index % self.len();
+        match self {
+            Self::Braille => Self::BRAILLE[index],
+            Self::Dots => Self::DOTS[index],
+            Self::Line => Self::LINE[index],
+            Self::Custom(frames) => &frames[index],
+        }
+    }
+
+    /// The width of the widest frame.
+    fn width(&self) -> usize {
+        (0..self.len())
+            .map(|index| UnicodeWidthStr::width(self.get(index)))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Default for SpinnerFrames {
+    fn default() -> Self {
+        Self::Braille
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpinnerProperties {
+    pub frames: SpinnerFrames,
+    /// How long each frame is shown for.
+    pub interval: Duration,
+    pub style: Style,
+    /// Text shown after the spinner.
+    pub label: Option<String>,
+    /// Whether the spinner is animated. A stopped spinner shows its first
+    /// frame.
+    pub spinning: bool,
+}
+
+impl SpinnerProperties {
+    pub fn new() -> Self {
+        Self {
+            frames: SpinnerFrames::default(),
+            interval: Duration::from_millis(80),
+            style: Style::default(),
+            label: None,
+            spinning: true,
+        }
+    }
+
+    pub fn frames(mut self, frames: SpinnerFrames) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn spinning(mut self, spinning: bool) -> Self {
+        self.spinning = spinning;
+        self
+    }
+}
+
+impl Default for SpinnerProperties {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Spinner {
+    properties: SpinnerProperties,
+    frame: Rect,
+    index: usize,
+    /// The time the current frame has been shown for.
+    elapsed: Duration,
+}
+
+impl Component for Spinner {
+    type Message = Duration;
+    type Properties = SpinnerProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            index: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            if !properties.spinning || self.properties.frames != properties.frames {
+                self.index = 0;
+                self.elapsed = Duration::ZERO;
+            }
+            self.properties = properties;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, delta: Self::Message) -> ShouldRender {
+        let frames = advance(&mut self.elapsed, delta, self.properties.interval);
+        if frames == 0 || self.properties.frames.len() < 2 {
+            return ShouldRender::No;
+        }
+        self.index = (self.index + frames) % self.properties.frames.len();
+        ShouldRender::Yes
+    }
+
+    fn size_hint(properties: &Self::Properties, _available: Size) -> SizeHint {
+        let label = properties
+            .label
+            .as_deref()
+            .map_or(0, |label| 1 + UnicodeWidthStr::width(label));
+        SizeHint::exact(Size::new(properties.frames.width() + label, 1))
+    }
+
+    fn view(&self) -> Layout {
+        let SpinnerProperties {
+            ref frames,
+            style,
+            ref label,
+            ..
+        } = self.properties;
+
+        let mut canvas = Canvas::new(self.frame.size);
+        canvas.clear(style);
+        canvas.draw_str(0, 0, style, frames.get(self.index));
+        if let Some(label) = label {
+            canvas.draw_str(frames.width() + 1, 0, style, label);
+        }
+        canvas.into()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_tickable(self.properties.spinning);
+    }
+
+    fn tick(&self, delta: Duration) -> Option<Self::Message> {
+        Some(delta)
+    }
+}
+
+/// Adds the time elapsed since the last tick to the time the current frame has
+/// been shown for and returns how many frames to advance by.
+fn advance(elapsed: &mut Duration, delta: Duration, interval: Duration) -> usize {
+    if interval.is_zero() {
+        return 1;
+    }
+    *elapsed += delta;
+    let frames = (elapsed.as_nanos() / interval.as_nanos()) as usize;
+    *elapsed = Duration::from_nanos((elapsed.as_nanos() % interval.as_nanos()) as u64);
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advances_once_per_interval() {
+        let interval = Duration::from_millis(80);
+        let mut elapsed = Duration::ZERO;
+        assert_eq!(
+            advance(&mut elapsed, Duration::from_millis(50), interval),
+            0
+        );
+        assert_eq!(
+            advance(&mut elapsed, Duration::from_millis(50), interval),
+            1
+        );
+        assert_eq!(elapsed, Duration::from_millis(20));
+        assert_eq!(
+            advance(&mut elapsed, Duration::from_millis(300), interval),
+            4
+        );
+        assert_eq!(elapsed, Duration::from_millis(0));
+    }
+
+    #[test]
+    fn frames_wrap_around() {
+        let frames = SpinnerFrames::Line;
+        assert_eq!(frames.get(0), "-");
+        assert_eq!(frames.get(5), "\\");
+        assert_eq!(SpinnerFrames::Dots.width(), 3);
+        assert_eq!(SpinnerFrames::Custom(vec![]).get(3), "");
+    }
+}
@@ -0,0 +1,383 @@
+//! A container with two panes separated by a divider, moved with key bindings
+//! or by dragging it with the mouse.
+use std::cmp;
+
+use crate::{
+    terminal::{Modifiers, MouseButton},
+    Bindings, Callback, Canvas, Children, Component, ComponentExt, ComponentLink, FlexDirection,
+    Item, Key, Layout, Position, Rect, ShouldRender, Size, Style,
+};
+
+#[derive(Clone, PartialEq)]
+pub struct SplitPaneProperties {
+    pub first: Children,
+    pub second: Children,
+    pub direction: FlexDirection,
+    pub ratio: f32,
+    pub min_size: usize,
+    pub style: Style,
+    pub focused: bool,
+    pub on_change: Option<Callback<f32>>,
+}
+
+impl SplitPaneProperties {
+    pub fn new(
+        direction: FlexDirection,
+        first: impl Into<Children>,
+        second: impl Into<Children>,
+    ) -> Self {
+        Self {
+            first: first.into(),
+            second: second.into(),
+            direction,
+            ratio: 0.5,
+            min_size: 1,
+            style: Style::default(),
+            focused: false,
+            on_change: None,
+        }
+    }
+
+    /// Creates a split pane with the panes laid out from left to right.
+    pub fn row(first: impl Into<Children>, second: impl Into<Children>) -> Self {
+        Self::new(FlexDirection::Row, first, second)
+    }
+
+    /// Creates a split pane with the panes laid out from top to bottom.
+    pub fn column(first: impl Into<Children>, second: impl Into<Children>) -> Self {
+        Self::new(FlexDirection::Column, first, second)
+    }
+
+    /// The initial fraction of the available space taken by the first pane.
+    pub fn ratio(mut self, ratio: f32) -> Self {
+        self.ratio = ratio;
+        self
+    }
+
+    /// The minimum size of either pane along the split direction.
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Called with the new ratio whenever the divider is moved. Parents can use
+    /// it to persist the ratio.
+    pub fn on_change(mut self, on_change: impl Into<Callback<f32>>) -> Self {
+        self.on_change = Some(on_change.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Moves the divider by a number of cells, towards the end for positive
+    /// values and towards the start for negative ones.
+    Move(isize),
+    /// Moves the divider s.t. the first pane takes the given fraction of the
+    /// available space.
+    SetRatio(f32),
+    /// Moves the divider to the position of the mouse pointer, relative to
+    /// the top left corner of the screen.
+    DragTo(Position),
+}
+
+pub struct SplitPane {
+    properties: SplitPaneProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    ratio: f32,
+}
+
+impl SplitPane {
+    /// The space shared by the two panes, i.e. excluding the divider.
+    fn available(&self) -> usize {
+        self.properties
+            .direction
+            .dimension(self.frame.size)
+            .saturating_sub(DIVIDER_SIZE)
+    }
+
+    /// The size of the first pane, respecting the minimum size of both panes.
+    fn first_size(&self) -> usize {
+        let available = self.available();
+        let min_size = cmp::min(self.properties.min_size, available / 2);
+        let first = (self.ratio * available as f32).round() as usize;
+        cmp::max(min_size, cmp::min(first, available - min_size))
+    }
+
+    /// Moves the divider s.t. the first pane has the given size, respecting
+    /// the minimum size of both panes.
+    fn set_first_size(&mut self, first: isize) -> ShouldRender {
+        let available = self.available();
+        if available == 0 {
+            return ShouldRender::No;
+        }
+        let min_size = cmp::min(self.properties.min_size, available / 2) as isize;
+        let first = first.clamp(min_size, available as isize - min_size);
+        self.set_ratio(first as f32 / available as f32)
+    }
+
+    fn set_ratio(&mut self, ratio: f32) -> ShouldRender {
+        let ratio = ratio.clamp(0.0, 1.0);
+        if ratio == self.ratio {
+            return ShouldRender::No;
+        }
+        self.ratio = ratio;
+        if let Some(on_change) = self.properties.on_change.as_ref() {
+            on_change.emit(ratio);
+        }
+        ShouldRender::Yes
+    }
+}
+
+impl Component for SplitPane {
+    type Message = Message;
+    type Properties = SplitPaneProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let ratio = properties.ratio;
+        Self {
+            properties,
+            frame,
+            link,
+            ratio,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        // The ratio in the properties is only the initial value, after that
+        // the divider position is owned by the component.
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::Move(delta) => self.set_first_size(self.first_size() as isize + delta),
+            Message::SetRatio(ratio) => self.set_ratio(ratio),
+            Message::DragTo(position) => {
+                let frame = self.link.own_frame();
+                let direction = self.properties.direction;
+                let along = match direction {
+                    FlexDirection::Row | FlexDirection::RowReverse => {
+                        position.x as isize - frame.origin.x as isize
+                    }
+                    FlexDirection::Column | FlexDirection::ColumnReverse => {
+                        position.y as isize - frame.origin.y as isize
+                    }
+                };
+                // The first pane is after the divider in reversed directions
+                let first = if direction.is_reversed() {
+                    self.available() as isize - along
+                } else {
+                    along
+                };
+                self.set_first_size(first)
+            }
+        }
+    }
+
+    fn view(&self) -> Layout {
+        let direction = self.properties.direction;
+        let (divider_size, stroke) = match direction {
+            FlexDirection::Row | FlexDirection::RowReverse => {
+                (Size::new(DIVIDER_SIZE, self.frame.size.height), "│")
+            }
+            FlexDirection::Column | FlexDirection::ColumnReverse => {
+                (Size::new(self.frame.size.width, DIVIDER_SIZE), "─")
+            }
+        };
+        let mut divider = Canvas::new(divider_size);
+        divider.clear_with(self.properties.style, stroke);
+
+        Layout::container(
+            direction,
+            [
+                Item::fixed(self.first_size())(Pane::with_key(0, self.properties.first.clone())),
+                Item::fixed(DIVIDER_SIZE)(divider),
+                Item::auto(Pane::with_key(1, self.properties.second.clone())),
+            ],
+        )
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("grow-first-pane", || Message::Move(1))
+            .with([Key::Alt('=')])
+            .with([Key::Alt('+')]);
+        bindings.add("shrink-first-pane", [Key::Alt('-')], || Message::Move(-1));
+        bindings.add("balance-panes", [Key::Alt('0')], || Message::SetRatio(0.5));
+        bindings.add(
+            "drag-divider",
+            [Key::Drag(MouseButton::Left, Modifiers::NONE)],
+            Message::DragTo,
+        );
+    }
+}
+
+/// Wraps the content of a pane. The two panes are keyed differently s.t. the
+/// components inside them have distinct identities, even if they have the same
+/// type.
+pub(crate) struct Pane {
+    content: Children,
+}
+
+impl Component for Pane {
+    type Message = ();
+    type Properties = Children;
+
+    fn create(content: Self::Properties, _frame: Rect, _link: ComponentLink<Self>) -> Self {
+        Self { content }
+    }
+
+    fn change(&mut self, content: Self::Properties) -> ShouldRender {
+        self.content = content;
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        self.content.view()
+    }
+}
+
+const DIVIDER_SIZE: usize = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        components::text::{Text, TextProperties},
+        terminal::{Event, MouseEvent, MouseEventKind},
+        testing::Harness,
+    };
+
+    type Ratios = Rc<RefCell<Vec<f32>>>;
+
+    /// A focused split pane 11 cells wide, i.e. 10 cells shared by the panes,
+    /// after `offset` cells of other content.
+    fn split(direction: FlexDirection, offset: usize, min_size: usize) -> (Harness, Ratios) {
+        let ratios = Ratios::default();
+        let text = |content: &'static str| Text::with(TextProperties::new().content(content));
+        let properties = SplitPaneProperties::new(direction, move || text("a"), move || text("b"))
+            .min_size(min_size)
+            .focused(true)
+            .on_change({
+                let ratios = Rc::clone(&ratios);
+                move |ratio| ratios.borrow_mut().push(ratio)
+            });
+        let harness = match direction {
+            FlexDirection::Row | FlexDirection::RowReverse => Harness::new(
+                Size::new(offset + 11, 1),
+                Layout::row([
+                    Item::fixed(offset)(text("")),
+                    Item::auto(SplitPane::with(properties)),
+                ]),
+            ),
+            FlexDirection::Column | FlexDirection::ColumnReverse => Harness::new(
+                Size::new(1, offset + 11),
+                Layout::column([
+                    Item::fixed(offset)(text("")),
+                    Item::auto(SplitPane::with(properties)),
+                ]),
+            ),
+        };
+        (harness, ratios)
+    }
+
+    fn divider(harness: &mut Harness) -> usize {
+        let text = harness.text();
+        match text.lines().count() {
+            1 => text.chars().position(|c| c == '│').unwrap(),
+            _ => text.lines().position(|line| line == "─").unwrap(),
+        }
+    }
+
+    fn drag(harness: &mut Harness, x: usize, y: usize) {
+        let event = Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Drag(MouseButton::Left),
+            position: Position::new(x, y),
+            modifiers: Modifiers::NONE,
+            timestamp: harness.now(),
+        });
+        harness.input(event);
+    }
+
+    #[test]
+    fn grow_shrink_and_balance() {
+        let (mut harness, ratios) = split(FlexDirection::Row, 0, 1);
+        assert_eq!(harness.line(0), "a    │b");
+
+        harness.key(Key::Alt('='));
+        assert_eq!(divider(&mut harness), 6);
+        harness.keys([Key::Alt('-'), Key::Alt('-')]);
+        assert_eq!(divider(&mut harness), 4);
+        harness.key(Key::Alt('0'));
+        assert_eq!(divider(&mut harness), 5);
+
+        // Balancing balanced panes doesn't change anything
+        harness.key(Key::Alt('0'));
+        assert_eq!(*ratios.borrow(), [0.6, 0.5, 0.4, 0.5]);
+    }
+
+    #[test]
+    fn min_size_is_respected() {
+        let (mut harness, ratios) = split(FlexDirection::Row, 0, 3);
+
+        harness.keys([Key::Alt('-'); 5]);
+        assert_eq!(divider(&mut harness), 3);
+        harness.keys([Key::Alt('='); 10]);
+        assert_eq!(divider(&mut harness), 7);
+        assert_eq!(*ratios.borrow(), [0.4, 0.3, 0.4, 0.5, 0.6, 0.7]);
+    }
+
+    #[test]
+    fn drag_the_divider() {
+        let (mut harness, ratios) = split(FlexDirection::Row, 4, 2);
+        assert_eq!(divider(&mut harness), 9);
+
+        // The pointer is relative to the screen, the split pane starts at 4
+        drag(&mut harness, 7, 0);
+        assert_eq!(divider(&mut harness), 7);
+        drag(&mut harness, 0, 0);
+        assert_eq!(divider(&mut harness), 6);
+        drag(&mut harness, 20, 0);
+        assert_eq!(divider(&mut harness), 12);
+        assert_eq!(*ratios.borrow(), [0.3, 0.2, 0.8]);
+
+        let (mut harness, ratios) = split(FlexDirection::Column, 2, 1);
+        drag(&mut harness, 0, 10);
+        assert_eq!(divider(&mut harness), 10);
+        assert_eq!(*ratios.borrow(), [0.8]);
+    }
+
+    #[test]
+    fn drag_the_divider_of_reversed_panes() {
+        let (mut harness, ratios) = split(FlexDirection::RowReverse, 0, 1);
+        assert_eq!(harness.line(0), "b    │a");
+
+        drag(&mut harness, 2, 0);
+        assert_eq!(harness.line(0), "b │a");
+        assert_eq!(*ratios.borrow(), [0.8]);
+    }
+}
@@ -0,0 +1,316 @@
+//! A status bar with key sequence feedback.
+//!
+//! The [`StatusBar`](struct.StatusBar.html) shows some mode text on the left
+//! and a list of items on the right. Between them, it echoes the key sequence
+//! being typed while it is the prefix of a binding, e.g. `C-x -`, and the
+//! name of the command that ran once the sequence matches, similar to the
+//! echo area in Emacs. It subscribes to the binding queries of the focused
+//! components with
+//! [`Bindings::set_notify`](../../struct.Bindings.html#method.set_notify).
+//!
+//! ```
+//! # use zi::prelude::*;
+//! # use zi::components::status::{StatusBar, StatusBarProperties};
+//! let status_bar = StatusBar::with(
+//!     StatusBarProperties::new()
+//!         .mode("NORMAL")
+//!         .item("main.rs")
+//!         .item("12:4"),
+//! );
+//! ```
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    component::bindings::KeySequenceSlice, Bindings, Canvas, Colour, Component, ComponentLink, Key,
+    Layout, NamedBindingQuery, Rect, ShouldRender, Size, SizeHint, Style,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatusBarStyle {
+    pub bar: Style,
+    pub mode: Style,
+    pub keys: Style,
+    pub command: Style,
+    pub item: Style,
+}
+
+impl Default for StatusBarStyle {
+    fn default() -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const GRAY_245: Colour = Colour::rgb(146, 131, 116);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
+        const BRIGHT_YELLOW: Colour = Colour::rgb(250, 189, 47);
+
+        Self {
+            bar: Style::normal(DARK0_SOFT, LIGHT2),
+            mode: Style::bold(BRIGHT_BLUE, DARK0_SOFT),
+            keys: Style::bold(DARK0_SOFT, BRIGHT_YELLOW),
+            command: Style::normal(DARK0_SOFT, GRAY_245),
+            item: Style::normal(DARK0_SOFT, LIGHT2),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusBarProperties {
+    pub mode: Option<String>,
+    /// Items shown on the right, in order.
+    pub items: Vec<String>,
+    pub style: StatusBarStyle,
+    /// Whether to show the pending key sequence and the matched command,
+    /// `true` by default.
+    pub show_keys: bool,
+}
+
+impl StatusBarProperties {
+    pub fn new() -> Self {
+        Self {
+            mode: None,
+            items: Vec::new(),
+            style: StatusBarStyle::default(),
+            show_keys: true,
+        }
+    }
+
+    pub fn mode(mut self, mode: impl Into<String>) -> Self {
+        self.mode = Some(mode.into());
+        self
+    }
+
+    pub fn item(mut self, item: impl Into<String>) -> Self {
+        self.items.push(item.into());
+        self
+    }
+
+    pub fn items(mut self, items: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.items.extend(items.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn style(mut self, style: StatusBarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn show_keys(mut self, show_keys: bool) -> Self {
+        self.show_keys = show_keys;
+        self
+    }
+}
+
+impl Default for StatusBarProperties {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The outcome of the last key press, as reported by the focused components.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyFeedback {
+    /// The keys are the prefix of one or more bindings.
+    Pending { keys: String, num_commands: usize },
+    /// The keys ran a command.
+    Matched {
+        keys: String,
+        command: Cow<'static, str>,
+    },
+}
+
+impl KeyFeedback {
+    /// Interprets the binding queries of the components on the focus path.
+    /// The last component with a binding for the keys is the one which
+    /// handled them.
+    fn new(queries: &[Option<NamedBindingQuery>], keys: &[Key]) -> Option<Self> {
+        let keys = KeySequenceSlice::from(keys).to_string();
+        match queries.iter().rev().find_map(Option::as_ref)? {
+            NamedBindingQuery::Match(command) => Some(Self::Matched {
+                keys,
+                command: command.clone(),
+            }),
+            NamedBindingQuery::PrefixOf(commands) => Some(Self::Pending {
+                keys,
+                num_commands: commands.len(),
+            }),
+        }
+    }
+}
+
+pub struct StatusBar {
+    properties: StatusBarProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    feedback: Option<KeyFeedback>,
+}
+
+impl StatusBar {
+    /// Returns the feedback for the last key press, if any.
+    pub fn feedback(&self) -> Option<&KeyFeedback> {
+        self.feedback.as_ref()
+    }
+}
+
+impl Component for StatusBar {
+    type Message = Option<KeyFeedback>;
+    type Properties = StatusBarProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            link,
+            feedback: None,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            self.properties = properties;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, feedback: Self::Message) -> ShouldRender {
+        if self.feedback != feedback {
+            self.feedback = feedback;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn size_hint(_properties: &Self::Properties, available: Size) -> SizeHint {
+        SizeHint::exact(Size::new(available.width, 1))
+    }
+
+    fn view(&self) -> Layout {
+        let StatusBarProperties {
+            ref mode,
+            ref items,
+            style,
+            show_keys,
+        } = self.properties;
+
+        let mut canvas = Canvas::new(self.frame.size);
+        canvas.clear(style.bar);
+
+        let mut offset = 0;
+        if let Some(mode) = mode {
+            offset += canvas.draw_str(offset, 0, style.mode, &format!(" {} ", mode));
+        }
+        match self.feedback.as_ref().filter(|_| show_keys) {
+            Some(KeyFeedback::Pending { keys, .. }) => {
+                canvas.draw_str(offset + 1, 0, style.keys, &format!("{} -", keys));
+            }
+            Some(KeyFeedback::Matched { keys, command }) => {
+                offset += 1 + canvas.draw_str(offset + 1, 0, style.keys, keys);
+                canvas.draw_str(offset + 1, 0, style.command, command);
+            }
+            None => {}
+        }
+
+        // The items are right-aligned and drawn over the key feedback if the
+        // bar is too narrow
+        let items = items
+            .iter()
+            .map(|item| format!(" {} ", item))
+            .collect::<String>();
+        let width = UnicodeWidthStr::width(items.as_str());
+        canvas.draw_str(
+            self.frame.size.width.saturating_sub(width),
+            0,
+            style.item,
+            &items,
+        );
+        canvas.into()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_notify(self.properties.show_keys);
+    }
+
+    fn notify_binding_queries(&self, queries: &[Option<NamedBindingQuery>], keys: &[Key]) {
+        self.link.send(KeyFeedback::new(queries, keys));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::ComponentSelector, testing::Harness, ComponentExt, Item};
+
+    struct Saver;
+
+    impl Component for Saver {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("save-file", [Key::Ctrl('x'), Key::Ctrl('s')], || ());
+            bindings.add("find-file", [Key::Ctrl('x'), Key::Ctrl('f')], || ());
+        }
+    }
+
+    #[test]
+    fn shows_pending_keys_and_matched_commands() {
+        let mut harness = Harness::new(
+            Size::new(32, 2),
+            Layout::column([
+                Item::auto(Saver::with(())),
+                Item::fixed(1)(StatusBar::with(
+                    StatusBarProperties::new().mode("EDIT").item("1:1"),
+                )),
+            ]),
+        );
+        let mut press = |key| {
+            let feedback = harness.key(key).app().with_component(
+                &ComponentSelector::of::<StatusBar>(),
+                |status_bar: &StatusBar| status_bar.feedback().cloned(),
+            );
+            (feedback.flatten(), harness.line(1))
+        };
+
+        let (feedback, status) = press(Key::Ctrl('x'));
+        assert_eq!(
+            feedback,
+            Some(KeyFeedback::Pending {
+                keys: "C-x".into(),
+                num_commands: 2
+            })
+        );
+        assert_eq!(status, " EDIT  C-x -                1:1");
+
+        let (feedback, status) = press(Key::Ctrl('s'));
+        assert_eq!(
+            feedback,
+            Some(KeyFeedback::Matched {
+                keys: "C-x C-s".into(),
+                command: "save-file".into()
+            })
+        );
+        assert_eq!(status, " EDIT  C-x C-s save-file    1:1");
+
+        // Unbound keys clear the feedback
+        assert_eq!(press(Key::Char('a')).0, None);
+    }
+}
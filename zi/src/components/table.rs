@@ -0,0 +1,485 @@
+//! A table with a header and rows of aligned cells.
+//!
+//! The [`Table`](struct.Table.html) is described by a list of
+//! [`Column`](struct.Column.html)s and a callback which returns the cells of
+//! a row by index. Only the visible rows are requested, so tables with many
+//! rows are cheap to draw. Like [`Select`](../select/struct.Select.html), the
+//! selected row is controlled by the parent, which is notified when the user
+//! selects another row.
+use std::cmp;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::scroll::{ScrollModel, ScrollSubscription};
+use crate::{
+    terminal::{Modifiers, MouseButton},
+    Bindings, Callback, Canvas, Colour, Component, ComponentLink, Key, Layout, Position, Rect,
+    ShouldRender, Size, SizeHint, Style,
+};
+
+/// How the width of a column is chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnWidth {
+    /// A fixed number of columns.
+    Fixed(usize),
+    /// An equal share of the space left after the fixed columns.
+    Auto,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    pub header: String,
+    pub width: ColumnWidth,
+}
+
+impl Column {
+    pub fn fixed(header: impl Into<String>, width: usize) -> Self {
+        Self {
+            header: header.into(),
+            width: ColumnWidth::Fixed(width),
+        }
+    }
+
+    pub fn auto(header: impl Into<String>) -> Self {
+        Self {
+            header: header.into(),
+            width: ColumnWidth::Auto,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableStyle {
+    pub header: Style,
+    pub row: Style,
+    /// The style of every other row.
+    pub stripe: Style,
+    pub selected: Style,
+}
+
+impl Default for TableStyle {
+    fn default() -> Self {
+        const DARK0_HARD: Colour = Colour::rgb(29, 32, 33);
+        const DARK0: Colour = Colour::rgb(40, 40, 40);
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
+
+        Self {
+            header: Style::bold(DARK0_HARD, BRIGHT_BLUE),
+            row: Style::normal(DARK0, LIGHT2),
+            stripe: Style::normal(DARK0_SOFT, LIGHT2),
+            selected: Style::bold(BRIGHT_BLUE, DARK0_SOFT),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct TableProperties {
+    pub style: TableStyle,
+    pub columns: Vec<Column>,
+    pub num_rows: usize,
+    /// Returns the cells of a row, one for each column.
+    pub row_at: Callback<usize, Vec<String>>,
+    pub selected: usize,
+    pub on_change: Option<Callback<usize>>,
+    pub focused: bool,
+    /// Shares the scroll offset with other components.
+    pub scroll: Option<ScrollModel>,
+}
+
+impl TableProperties {
+    pub fn new(
+        columns: impl Into<Vec<Column>>,
+        num_rows: usize,
+        row_at: impl Into<Callback<usize, Vec<String>>>,
+    ) -> Self {
+        Self {
+            style: TableStyle::default(),
+            columns: columns.into(),
+            num_rows,
+            row_at: row_at.into(),
+            selected: 0,
+            on_change: None,
+            focused: false,
+            scroll: None,
+        }
+    }
+
+    pub fn style(mut self, style: TableStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn selected(mut self, selected: usize) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    pub fn on_change(mut self, on_change: impl Into<Callback<usize>>) -> Self {
+        self.on_change = Some(on_change.into());
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: ScrollModel) -> Self {
+        self.scroll = Some(scroll);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    NextRow,
+    PreviousRow,
+    FirstRow,
+    LastRow,
+    NextPage,
+    PreviousPage,
+    SelectRow(usize),
+    Scrolled(usize),
+}
+
+pub struct Table {
+    properties: TableProperties,
+    frame: Rect,
+    offset: usize,
+    link: ComponentLink<Self>,
+    scroll_subscription: Option<ScrollSubscription>,
+}
+
+impl Table {
+    /// The number of rows visible at once, below the header.
+    fn num_visible_rows(&self) -> usize {
+        self.frame.size.height.saturating_sub(1)
+    }
+
+    fn ensure_selected_row_in_view(&mut self) {
+        let selected = self.properties.selected;
+        let num_visible_rows = self.num_visible_rows();
+
+        self.offset = cmp::min(self.offset, selected);
+        if num_visible_rows > 0 && selected >= self.offset + num_visible_rows {
+            self.offset = selected + 1 - num_visible_rows;
+        }
+
+        if let Some(scroll) = self.properties.scroll.as_ref() {
+            scroll.set_extent(self.properties.num_rows, num_visible_rows);
+            scroll.set_offset(self.offset);
+        }
+    }
+
+    fn subscribe_to_scroll(&mut self) {
+        self.scroll_subscription = self.properties.scroll.as_ref().map(|scroll| {
+            self.offset = scroll.offset();
+            scroll.subscribe(self.link.callback(Message::Scrolled))
+        });
+    }
+
+    /// The index of the row drawn at a position on screen, if any.
+    fn row_at_position(&self, position: Position) -> Option<usize> {
+        let y = position.y.checked_sub(self.frame.origin.y)?;
+        let row = self.offset + y.checked_sub(1)?;
+        (y < self.frame.size.height && row < self.properties.num_rows).then(|| row)
+    }
+}
+
+impl Component for Table {
+    type Message = Message;
+    type Properties = TableProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let mut table = Self {
+            properties,
+            frame,
+            offset: 0,
+            link,
+            scroll_subscription: None,
+        };
+        table.subscribe_to_scroll();
+        table.ensure_selected_row_in_view();
+        table
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            let scroll_changed = self.properties.scroll != properties.scroll;
+            self.properties = properties;
+            if scroll_changed {
+                self.subscribe_to_scroll();
+            }
+            self.ensure_selected_row_in_view();
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        self.ensure_selected_row_in_view();
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        let current_selected = self.properties.selected;
+        let last_row = self.properties.num_rows.saturating_sub(1);
+        let page = cmp::max(self.num_visible_rows(), 1);
+        let new_selected = match message {
+            Message::NextRow => cmp::min(current_selected + 1, last_row),
+            Message::PreviousRow => current_selected.saturating_sub(1),
+            Message::FirstRow => 0,
+            Message::LastRow => last_row,
+            Message::NextPage => cmp::min(current_selected + page, last_row),
+            Message::PreviousPage => current_selected.saturating_sub(page),
+            Message::SelectRow(row) => cmp::min(row, last_row),
+            Message::Scrolled(offset) => {
+                // Another component sharing the scroll model moved it
+                let changed = self.offset != offset;
+                self.offset = offset;
+                return changed.into();
+            }
+        };
+        if current_selected != new_selected {
+            if let Some(on_change) = self.properties.on_change.as_mut() {
+                on_change.emit(new_selected)
+            }
+        }
+        ShouldRender::No
+    }
+
+    fn size_hint(properties: &Self::Properties, available: Size) -> SizeHint {
+        let height = properties.num_rows + 1;
+        SizeHint::new(
+            Size::new(0, cmp::min(2, height)),
+            Size::new(available.width, height),
+            Size::new(usize::MAX, height),
+        )
+    }
+
+    fn view(&self) -> Layout {
+        let TableProperties {
+            ref style,
+            ref columns,
+            num_rows,
+            ref row_at,
+            selected,
+            ..
+        } = self.properties;
+        let Size { width, height, .. } = self.frame.size;
+        let widths = column_widths(columns, width);
+
+        let mut canvas = Canvas::new(self.frame.size);
+        canvas.clear(style.row);
+        let mut draw_row = |y: usize, style: Style, cells: &mut dyn Iterator<Item = &str>| {
+            canvas.clear_region(Rect::new(Position::new(0, y), Size::new(width, 1)), style);
+            let mut x = 0;
+            for (cell, &cell_width) in cells.zip(widths.iter()) {
+                canvas.draw_str(x, y, style, truncate(cell, cell_width));
+                x += cell_width + 1;
+            }
+        };
+
+        draw_row(
+            0,
+            style.header,
+            &mut columns.iter().map(|column| column.header.as_str()),
+        );
+        let num_visible_rows = cmp::min(
+            num_rows.saturating_sub(self.offset),
+            height.saturating_sub(1),
+        );
+        for (y, index) in (self.offset..self.offset + num_visible_rows).enumerate() {
+            let row_style = if index == selected {
+                style.selected
+            } else if index % 2 == 1 {
+                style.stripe
+            } else {
+                style.row
+            };
+            let cells = row_at.emit(index);
+            draw_row(y + 1, row_style, &mut cells.iter().map(String::as_str));
+        }
+
+        canvas.into()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("next-row", || Message::NextRow)
+            .with([Key::Ctrl('n')])
+            .with([Key::Down])
+            .with([Key::ScrollDown(Modifiers::NONE)]);
+        bindings
+            .command("previous-row", || Message::PreviousRow)
+            .with([Key::Ctrl('p')])
+            .with([Key::Up])
+            .with([Key::ScrollUp(Modifiers::NONE)]);
+        bindings.add("first-row", [Key::Alt('<')], || Message::FirstRow);
+        bindings.add("last-row", [Key::Alt('>')], || Message::LastRow);
+        bindings
+            .command("next-page", || Message::NextPage)
+            .with([Key::Ctrl('v')])
+            .with([Key::PageDown]);
+        bindings
+            .command("previous-page", || Message::PreviousPage)
+            .with([Key::Alt('v')])
+            .with([Key::PageUp]);
+        bindings.add(
+            "select-row",
+            [Key::Click(MouseButton::Left, Modifiers::NONE)],
+            |this: &Self, position: Position| {
+                this.row_at_position(position).map(Message::SelectRow)
+            },
+        );
+    }
+}
+
+/// Splits the available width between the columns, leaving one space
+/// between adjacent columns. Auto columns share the remaining space equally,
+/// the first ones taking any leftover.
+fn column_widths(columns: &[Column], width: usize) -> Vec<usize> {
+    let num_auto = columns
+        .iter()
+        .filter(|column| column.width == ColumnWidth::Auto)
+        .count();
+    let fixed: usize = columns
+        .iter()
+        .map(|column| match column.width {
+            ColumnWidth::Fixed(width) => width,
+            ColumnWidth::Auto => 0,
+        })
+        .sum();
+    let remaining = width.saturating_sub(fixed + columns.len().saturating_sub(1));
+
+    let mut auto_index = 0;
+    columns
+        .iter()
+        .map(|column| match column.width {
+            ColumnWidth::Fixed(width) => width,
+            ColumnWidth::Auto => {
+                auto_index += 1;
+                remaining / num_auto + usize::from(auto_index <= remaining % num_auto)
+            }
+        })
+        .collect()
+}
+
+/// The longest prefix of `text` which fits in `width` columns.
+fn truncate(text: &str, width: usize) -> &str {
+    let mut visual_offset = 0;
+    for (byte_offset, grapheme) in text.grapheme_indices(true) {
+        visual_offset += UnicodeWidthStr::width(grapheme);
+        if visual_offset > width {
+            return &text[..byte_offset];
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        terminal::{Event, MouseEvent, MouseEventKind},
+        testing::Harness,
+        ComponentExt,
+    };
+
+    #[test]
+    fn columns_share_the_available_width() {
+        let columns = [
+            Column::fixed("id", 3),
+            Column::auto("name"),
+            Column::auto("owner"),
+            Column::fixed("size", 4),
+        ];
+        assert_eq!(column_widths(&columns, 20), vec![3, 5, 5, 4]);
+        assert_eq!(column_widths(&columns, 21), vec![3, 6, 5, 4]);
+        assert_eq!(column_widths(&columns, 5), vec![3, 0, 0, 4]);
+        assert_eq!(truncate("a中文b", 4), "a中");
+        assert_eq!(truncate("abc", 4), "abc");
+    }
+
+    struct Files {
+        selected: usize,
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Files {
+        type Message = usize;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self { selected: 0, link }
+        }
+
+        fn update(&mut self, selected: Self::Message) -> ShouldRender {
+            self.selected = selected;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            let columns = [Column::auto("name"), Column::fixed("size", 2)];
+            Table::with(
+                TableProperties::new(columns, 5, |index| {
+                    vec![format!("file{}.txt", index), format!("{}k", index)]
+                })
+                .selected(self.selected)
+                .focused(true)
+                .on_change(self.link.callback(|selected| selected)),
+            )
+        }
+    }
+
+    #[test]
+    fn rows_are_aligned_and_selectable() {
+        let mut harness = Harness::new(Size::new(10, 3), Files::with(()));
+        let rows = |harness: &mut Harness| {
+            (0..3)
+                .map(|y| {
+                    let style = harness.canvas().textel(0, y).as_ref().unwrap().style;
+                    (harness.line(y), style)
+                })
+                .collect::<Vec<_>>()
+        };
+        let style = TableStyle::default();
+
+        assert_eq!(
+            rows(&mut harness),
+            [
+                ("name    si".into(), style.header),
+                ("file0.t 0k".into(), style.selected),
+                ("file1.t 1k".into(), style.stripe),
+            ]
+        );
+
+        // Moving the selection past the last visible row scrolls the table
+        harness.keys([Key::Down, Key::Down, Key::Down]);
+        assert_eq!(
+            rows(&mut harness)[1..],
+            [
+                ("file2.t 2k".into(), style.row),
+                ("file3.t 3k".into(), style.selected),
+            ]
+        );
+
+        let timestamp = harness.now();
+        harness.input(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            position: Position::new(4, 1),
+            modifiers: Modifiers::NONE,
+            timestamp,
+        }));
+        assert_eq!(rows(&mut harness)[1].1, style.selected);
+    }
+}
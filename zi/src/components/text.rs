@@ -1,6 +1,8 @@
 use unicode_width::UnicodeWidthStr;
 
-use crate::{layout::Layout, Canvas, Component, ComponentLink, Rect, ShouldRender, Size, Style};
+use crate::{
+    layout::Layout, Canvas, Component, ComponentLink, Rect, ShouldRender, Size, SizeHint, Style,
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TextAlign {
@@ -146,6 +148,44 @@ impl Component for Text {
 
         canvas.into()
     }
+
+    /// The intrinsic size of the text block, i.e. the width of the longest
+    /// line by the number of lines. Word wrapped text that doesn't fit is
+    /// as wide as the available space and as high as the wrapped lines.
+    fn size_hint(properties: &Self::Properties, available: Size) -> SizeHint {
+        let mut size = text_block_size(&properties.content);
+        if properties.wrap == TextWrap::Word && size.width > available.width {
+            size = Size::new(
+                available.width,
+                properties
+                    .content
+                    .lines()
+                    .map(|line| wrapped_height(line, available.width))
+                    .sum(),
+            );
+        }
+        SizeHint::new(Size::zero(), size, size)
+    }
+}
+
+/// The number of rows taken by a line when wrapped at word boundaries, as
+/// drawn by `Text::view`.
+fn wrapped_height(line: &str, width: usize) -> usize {
+    let mut height = 1;
+    let mut cursor_x = 0;
+    for word in line.split_whitespace() {
+        let word_width = UnicodeWidthStr::width(word);
+        if cursor_x > 0 {
+            if cursor_x >= width || word_width > width.saturating_sub(cursor_x + 1) {
+                height += 1;
+                cursor_x = 0;
+            } else {
+                cursor_x += 1;
+            }
+        }
+        cursor_x += word_width;
+    }
+    height
 }
 
 fn text_block_size(text: &str) -> Size {
@@ -0,0 +1,549 @@
+//! A multi-line text editing component.
+//!
+//! Like [`Input`](../input/struct.Input.html), a
+//! [`TextArea`](struct.TextArea.html) is controlled by its parent: it doesn't
+//! modify its content or cursor, instead it emits an
+//! [`InputChange`](../input/struct.InputChange.html) and expects the parent to
+//! pass the new content and cursor back as properties. Long lines are soft
+//! wrapped by default and the view scrolls to keep the cursor visible. The
+//! default bindings follow emacs.
+use ropey::{Rope, RopeSlice};
+use std::{borrow::Cow, cmp, ops::Range};
+use unicode_width::UnicodeWidthStr;
+
+use super::input::{Cursor, InputChange, InputStyle};
+use crate::{
+    terminal::Grapheme,
+    text::{cursor, CharIndex, TextStorage},
+    AnyCharacter, Bindings, Callback, Canvas, Component, ComponentLink, Key, Layout, Position,
+    Rect, ShouldRender,
+};
+
+#[derive(Clone, PartialEq)]
+pub struct TextAreaProperties {
+    pub style: InputStyle,
+    pub content: Rope,
+    pub cursor: Cursor,
+    pub on_change: Option<Callback<InputChange>>,
+    pub focused: bool,
+    /// Whether lines longer than the width of the text area are continued on
+    /// the next row. Otherwise, the view scrolls horizontally.
+    pub wrap: bool,
+}
+
+impl TextAreaProperties {
+    pub fn new(content: Rope, cursor: Cursor) -> Self {
+        Self {
+            style: InputStyle::default(),
+            content,
+            cursor,
+            on_change: None,
+            focused: true,
+            wrap: true,
+        }
+    }
+
+    pub fn style(mut self, style: InputStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn on_change(mut self, on_change: impl Into<Callback<InputChange>>) -> Self {
+        self.on_change = Some(on_change.into());
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    PageUp,
+    PageDown,
+    StartOfLine,
+    EndOfLine,
+    StartOfBuffer,
+    EndOfBuffer,
+    InsertChar(char),
+    InsertGrapheme(Grapheme),
+    DeleteBackward,
+    DeleteForward,
+    KillLine,
+}
+
+pub struct TextArea {
+    properties: TextAreaProperties,
+    frame: Rect,
+    /// The first visible row and column.
+    scroll: Position,
+    /// The column the cursor returns to when moving vertically across
+    /// shorter rows.
+    goal_x: Option<usize>,
+}
+
+impl TextArea {
+    fn rows(&self) -> Vec<Range<usize>> {
+        visual_rows(
+            &self.properties.content,
+            self.frame.size.width,
+            self.properties.wrap,
+        )
+    }
+
+    fn ensure_cursor_in_view(&mut self) {
+        let rows = self.rows();
+        let (row, x) = locate(&self.properties.content, &rows, self.cursor_index());
+        let Rect { size, .. } = self.frame;
+
+        self.scroll.y = cmp::min(self.scroll.y, row);
+        if row >= self.scroll.y + size.height {
+            self.scroll.y = (row + 1).saturating_sub(size.height);
+        }
+
+        if self.properties.wrap {
+            self.scroll.x = 0;
+        } else {
+            self.scroll.x = cmp::min(self.scroll.x, x);
+            if x >= self.scroll.x + size.width {
+                self.scroll.x = (x + 1).saturating_sub(size.width);
+            }
+        }
+    }
+
+    fn cursor_index(&self) -> usize {
+        self.properties.cursor.range().start.0
+    }
+
+    /// Moves the cursor by a number of rows, towards the end for positive
+    /// values. Returns the column the cursor is aiming for.
+    fn move_vertically(&self, cursor: &mut Cursor, delta: isize, goal_x: Option<usize>) -> usize {
+        let content = &self.properties.content;
+        let rows = self.rows();
+        let (row, x) = locate(content, &rows, self.cursor_index());
+        let goal_x = goal_x.unwrap_or(x);
+        if rows.is_empty() {
+            return goal_x;
+        }
+
+        let target = if delta < 0 {
+            row.saturating_sub(delta.unsigned_abs())
+        } else {
+            cmp::min(row.saturating_add(delta as usize), rows.len() - 1)
+        };
+        if target != row {
+            cursor.move_to(content, CharIndex(char_at(content, &rows[target], goal_x)));
+        }
+        goal_x
+    }
+}
+
+impl Component for TextArea {
+    type Message = Message;
+    type Properties = TextAreaProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
+        let mut text_area = Self {
+            properties,
+            frame,
+            scroll: Position::new(0, 0),
+            goal_x: None,
+        };
+        text_area.ensure_cursor_in_view();
+        text_area
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            self.properties = properties;
+            self.ensure_cursor_in_view();
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        self.ensure_cursor_in_view();
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        let content = &self.properties.content;
+        let mut cursor = self.properties.cursor.clone();
+        let mut content_change = None;
+        let goal_x = self.goal_x.take();
+        let page = cmp::max(self.frame.size.height, 1) as isize;
+        match message {
+            Message::CursorLeft => {
+                cursor.move_left(content);
+            }
+            Message::CursorRight => {
+                cursor.move_right(content);
+            }
+            Message::CursorUp => {
+                self.goal_x = Some(self.move_vertically(&mut cursor, -1, goal_x));
+            }
+            Message::CursorDown => {
+                self.goal_x = Some(self.move_vertically(&mut cursor, 1, goal_x));
+            }
+            Message::PageUp => {
+                self.goal_x = Some(self.move_vertically(&mut cursor, -page, goal_x));
+            }
+            Message::PageDown => {
+                self.goal_x = Some(self.move_vertically(&mut cursor, page, goal_x));
+            }
+            Message::StartOfLine => {
+                cursor.move_to_start_of_line(content);
+            }
+            Message::EndOfLine => {
+                cursor.move_to_end_of_line(content);
+            }
+            Message::StartOfBuffer => {
+                cursor.move_to_start_of_buffer(content);
+            }
+            Message::EndOfBuffer => {
+                cursor.move_to_end_of_buffer(content);
+            }
+            Message::InsertChar(character) => {
+                let mut new_content = content.clone();
+                cursor.insert_char(&mut new_content, character);
+                cursor.move_right(&new_content);
+                content_change = Some(new_content);
+            }
+            Message::InsertGrapheme(grapheme) => {
+                let mut new_content = content.clone();
+                cursor.insert_chars(&mut new_content, grapheme.chars());
+                cursor.move_right(&new_content);
+                content_change = Some(new_content);
+            }
+            Message::DeleteBackward => {
+                let mut new_content = content.clone();
+                cursor.backspace(&mut new_content);
+                content_change = Some(new_content);
+            }
+            Message::DeleteForward => {
+                let mut new_content = content.clone();
+                cursor.delete(&mut new_content);
+                content_change = Some(new_content);
+            }
+            Message::KillLine => {
+                let start = cmp::min(cursor.range().start.0, content.len_chars());
+                let line = content.char_to_line(start);
+                let end_of_line = content.line_to_char(line + 1);
+                // Kill the rest of the line, or join it with the next one if
+                // the cursor is already at the end
+                let end = if end_of_line > start + 1 {
+                    end_of_line - 1
+                } else {
+                    end_of_line
+                };
+                if start < end {
+                    let mut new_content = content.clone();
+                    new_content.remove(start..end);
+                    cursor::ensure_trailing_newline_with_content(&mut new_content);
+                    cursor.move_to(&new_content, CharIndex(start));
+                    content_change = Some(new_content);
+                }
+            }
+        }
+
+        if let Some(on_change) = self.properties.on_change.as_mut() {
+            on_change.emit(InputChange {
+                cursor,
+                content: content_change,
+            });
+        }
+
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let Self {
+            properties:
+                TextAreaProperties {
+                    ref content,
+                    ref cursor,
+                    ref style,
+                    ..
+                },
+            frame: Rect { size, .. },
+            scroll,
+            ..
+        } = *self;
+
+        let mut canvas = Canvas::new(size);
+        canvas.clear(style.content);
+        if content.len_chars() == 0 {
+            canvas.draw_str(0, 0, style.cursor, " ");
+        }
+
+        let rows = self.rows();
+        for (y, row) in rows.iter().skip(scroll.y).take(size.height).enumerate() {
+            let mut char_offset = row.start;
+            let mut x = 0;
+            for grapheme in content.slice(row.clone()).graphemes() {
+                let len_chars = grapheme.len_chars();
+                let text = Cow::from(grapheme);
+                let width = grapheme_width(&text);
+                if x >= scroll.x && x + width <= scroll.x + size.width {
+                    canvas.draw_str(
+                        x - scroll.x,
+                        y,
+                        if cursor.range().contains(&CharIndex(char_offset)) {
+                            style.cursor
+                        } else {
+                            style.content
+                        },
+                        if UnicodeWidthStr::width(text.as_ref()) > 0 && !is_control(&text) {
+                            text.as_ref()
+                        } else {
+                            " "
+                        },
+                    );
+                }
+                x += width;
+                char_offset += len_chars;
+            }
+        }
+
+        canvas.into()
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("left", || Message::CursorLeft)
+            .with([Key::Ctrl('b')])
+            .with([Key::Left]);
+        bindings
+            .command("right", || Message::CursorRight)
+            .with([Key::Ctrl('f')])
+            .with([Key::Right]);
+        bindings
+            .command("up", || Message::CursorUp)
+            .with([Key::Ctrl('p')])
+            .with([Key::Up]);
+        bindings
+            .command("down", || Message::CursorDown)
+            .with([Key::Ctrl('n')])
+            .with([Key::Down]);
+        bindings
+            .command("page-up", || Message::PageUp)
+            .with([Key::Alt('v')])
+            .with([Key::PageUp]);
+        bindings
+            .command("page-down", || Message::PageDown)
+            .with([Key::Ctrl('v')])
+            .with([Key::PageDown]);
+        bindings
+            .command("start-of-line", || Message::StartOfLine)
+            .with([Key::Ctrl('a')])
+            .with([Key::Home]);
+        bindings
+            .command("end-of-line", || Message::EndOfLine)
+            .with([Key::Ctrl('e')])
+            .with([Key::End]);
+        bindings.add("start-of-buffer", [Key::Alt('<')], || {
+            Message::StartOfBuffer
+        });
+        bindings.add("end-of-buffer", [Key::Alt('>')], || Message::EndOfBuffer);
+        bindings
+            .command("delete-forward", || Message::DeleteForward)
+            .with([Key::Ctrl('d')])
+            .with([Key::Delete]);
+        bindings.add("delete-backward", [Key::Backspace], || {
+            Message::DeleteBackward
+        });
+        bindings.add("kill-line", [Key::Ctrl('k')], || Message::KillLine);
+        bindings.add("newline", [Key::Char('\n')], || Message::InsertChar('\n'));
+        bindings.add(
+            "insert-character",
+            AnyCharacter,
+            |keys: &[Key]| match *keys {
+                [Key::Char(character)]
+                    if character != '\n' && character != '\r' && character != '\t' =>
+                {
+                    Some(Message::InsertChar(character))
+                }
+                [Key::Grapheme(grapheme)] => Some(Message::InsertGrapheme(grapheme)),
+                _ => None,
+            },
+        );
+    }
+}
+
+/// Splits the content into the rows shown on screen, as char ranges. A row
+/// ends after a newline or, when wrapping, before the first grapheme which
+/// doesn't fit in `width`.
+fn visual_rows(content: &Rope, width: usize, wrap: bool) -> Vec<Range<usize>> {
+    let mut rows = Vec::new();
+    let (mut start, mut offset, mut x) = (0, 0, 0);
+    for grapheme in content.graphemes() {
+        let grapheme_width = grapheme_width(&Cow::from(grapheme));
+        if wrap && x > 0 && x + grapheme_width > width {
+            rows.push(start..offset);
+            start = offset;
+            x = 0;
+        }
+        offset += grapheme.len_chars();
+        x += grapheme_width;
+        if is_newline(grapheme) {
+            rows.push(start..offset);
+            start = offset;
+            x = 0;
+        }
+    }
+    if start < offset {
+        rows.push(start..offset);
+    }
+    rows
+}
+
+/// Returns the row containing a char index and its column in that row.
+fn locate(content: &Rope, rows: &[Range<usize>], char_index: usize) -> (usize, usize) {
+    let row = cmp::min(
+        rows.partition_point(|row| row.end <= char_index),
+        rows.len().saturating_sub(1),
+    );
+    let x = rows.get(row).map_or(0, |range| {
+        let end = cmp::min(char_index, range.end);
+        content
+            .slice(range.start..cmp::max(range.start, end))
+            .graphemes()
+            .map(|grapheme| grapheme_width(&Cow::from(grapheme)))
+            .sum()
+    });
+    (row, x)
+}
+
+/// Returns the char index of the grapheme of a row found at a column, or of
+/// the last grapheme of the row if it is shorter.
+fn char_at(content: &Rope, row: &Range<usize>, goal_x: usize) -> usize {
+    let (mut char_offset, mut x) = (row.start, 0);
+    for grapheme in content.slice(row.clone()).graphemes() {
+        x += grapheme_width(&Cow::from(grapheme));
+        if x > goal_x || char_offset + grapheme.len_chars() >= row.end {
+            break;
+        }
+        char_offset += grapheme.len_chars();
+    }
+    char_offset
+}
+
+/// The number of columns taken by a grapheme. Control characters and other
+/// zero width graphemes take one column, s.t. the cursor can sit on them.
+fn grapheme_width(grapheme: &str) -> usize {
+    if is_control(grapheme) {
+        1
+    } else {
+        cmp::max(UnicodeWidthStr::width(grapheme), 1)
+    }
+}
+
+fn is_control(grapheme: &str) -> bool {
+    grapheme.chars().any(char::is_control)
+}
+
+fn is_newline(grapheme: RopeSlice) -> bool {
+    grapheme.chars().last() == Some('\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::Harness, ComponentExt, Size};
+
+    #[test]
+    fn rows_wrap_long_lines() {
+        let content = Rope::from("abcdef\ngh\n\nij");
+        assert_eq!(
+            visual_rows(&content, 4, true),
+            vec![0..4, 4..7, 7..10, 10..11, 11..13]
+        );
+        assert_eq!(
+            visual_rows(&content, 4, false),
+            vec![0..7, 7..10, 10..11, 11..13]
+        );
+        assert_eq!(visual_rows(&Rope::from("ab\n"), 4, true), vec![0..3]);
+        assert!(visual_rows(&Rope::new(), 4, true).is_empty());
+
+        // Wide graphemes are not split across rows
+        let content = Rope::from("a中文b\n");
+        assert_eq!(visual_rows(&content, 4, true), vec![0..2, 2..5]);
+    }
+
+    #[test]
+    fn vertical_movement_keeps_the_column() {
+        let content = Rope::from("abcdef\nx\nabcdef\n");
+        let rows = visual_rows(&content, 10, true);
+        assert_eq!(locate(&content, &rows, 4), (0, 4));
+        assert_eq!(char_at(&content, &rows[1], 4), 8);
+        assert_eq!(char_at(&content, &rows[2], 4), 13);
+        assert_eq!(locate(&content, &rows, 16), (2, 7));
+    }
+
+    struct Editor {
+        content: Rope,
+        cursor: Cursor,
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Editor {
+        type Message = InputChange;
+        type Properties = &'static str;
+
+        fn create(content: Self::Properties, _frame: Rect, link: ComponentLink<Self>) -> Self {
+            Self {
+                content: content.into(),
+                cursor: Cursor::new(),
+                link,
+            }
+        }
+
+        fn update(&mut self, change: Self::Message) -> ShouldRender {
+            if let Some(content) = change.content {
+                self.content = content;
+            }
+            self.cursor = change.cursor;
+            ShouldRender::Yes
+        }
+
+        fn view(&self) -> Layout {
+            TextArea::with(
+                TextAreaProperties::new(self.content.clone(), self.cursor.clone())
+                    .on_change(self.link.callback(|change| change)),
+            )
+        }
+    }
+
+    #[test]
+    fn wraps_and_scrolls_to_the_cursor() {
+        let mut harness = Harness::new(Size::new(4, 2), Editor::with("abcdef\ngh\nij\n"));
+        assert_eq!(harness.text(), "abcd\nef");
+
+        harness.keys([Key::Ctrl('n'), Key::Ctrl('n')]);
+        assert_eq!(harness.text(), "ef\ngh");
+        harness.keys([Key::Down, Key::Char('x')]);
+        assert_eq!(harness.text(), "gh\nxij");
+        harness.key(Key::Ctrl('k'));
+        assert_eq!(harness.text(), "gh\nx");
+        harness.key(Key::Alt('<'));
+        assert_eq!(harness.text(), "abcd\nef");
+    }
+}
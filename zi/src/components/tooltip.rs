@@ -0,0 +1,214 @@
+//! A wrapper that shows a small popup when hovered with the mouse.
+use std::{
+    cmp,
+    time::{Duration, Instant},
+};
+use unicode_width::UnicodeWidthStr;
+
+use super::place;
+use crate::{
+    terminal::{HoverEvent, PositionExt},
+    Bindings, Canvas, Children, Component, ComponentLink, Layout, Position, Rect, ShouldRender,
+    Size, Style,
+};
+
+pub struct TooltipProperties {
+    pub content: Children,
+    pub text: String,
+    pub style: Style,
+    /// How long the mouse needs to rest over the content before the tooltip
+    /// is shown.
+    pub delay: Duration,
+}
+
+impl TooltipProperties {
+    pub fn new(text: impl Into<String>, content: impl Into<Children>) -> Self {
+        Self {
+            content: content.into(),
+            text: text.into(),
+            style: Style::default(),
+            delay: Duration::from_millis(500),
+        }
+    }
+
+    pub fn style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    Hover(HoverEvent),
+    Tick,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Idle,
+    Pending { since: Instant, anchor: Position },
+    Visible { anchor: Position },
+}
+
+/// Wraps a layout and shows a popup with some text next to the mouse after it
+/// rests over the layout for a while. The popup is drawn in a layer above the
+/// content and it is kept inside the frame of the tooltip.
+pub struct Tooltip {
+    properties: TooltipProperties,
+    frame: Rect,
+    state: State,
+    link: ComponentLink<Self>,
+}
+
+impl Tooltip {
+    /// Returns `true` if the popup is currently shown.
+    pub fn is_visible(&self) -> bool {
+        matches!(self.state, State::Visible { .. })
+    }
+
+    fn popup_frame(&self, anchor: Position) -> Rect {
+        let lines = self.properties.text.lines();
+        let text_width = lines.clone().map(UnicodeWidthStr::width).max().unwrap_or(0);
+        let size = Size::new(
+            cmp::min(text_width + 2 * PADDING, self.frame.size.width),
+            cmp::min(lines.count(), self.frame.size.height),
+        );
+
+        // Show the popup under the mouse if it fits, otherwise above it
+        let anchor = anchor.saturating_sub(self.frame.origin);
+        let y = if anchor.y + 1 + size.height <= self.frame.size.height {
+            anchor.y + 1
+        } else {
+            anchor.y.saturating_sub(size.height)
+        };
+        let x = cmp::min(anchor.x, self.frame.size.width - size.width);
+        Rect::new(Position::new(x, y), size)
+    }
+}
+
+impl Component for Tooltip {
+    type Message = Message;
+    type Properties = TooltipProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            state: State::Idle,
+            link,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        self.state = match (message, self.state) {
+            (Message::Hover(HoverEvent::Enter(anchor)), _) => {
+                if self.properties.delay == Duration::from_secs(0) {
+                    State::Visible { anchor }
+                } else {
+                    State::Pending {
+                        since: self.link.now(),
+                        anchor,
+                    }
+                }
+            }
+            (Message::Hover(HoverEvent::Leave), State::Idle) => return ShouldRender::No,
+            (Message::Hover(HoverEvent::Leave), _) => State::Idle,
+            (Message::Tick, State::Pending { since, anchor })
+                if self.link.now().saturating_duration_since(since) >= self.properties.delay =>
+            {
+                State::Visible { anchor }
+            }
+            (Message::Tick, _) => return ShouldRender::No,
+        };
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let popup = match self.state {
+            State::Visible { anchor } => {
+                let frame = self.popup_frame(anchor);
+                let mut canvas = Canvas::new(frame.size);
+                canvas.clear(self.properties.style);
+                for (y, line) in self.properties.text.lines().enumerate() {
+                    canvas.draw_str(PADDING, y, self.properties.style, line);
+                }
+                place(frame, canvas.into())
+            }
+            State::Idle | State::Pending { .. } => Layout::empty(),
+        };
+        Layout::stack([self.properties.content.view(), popup])
+    }
+
+    fn hover(&self, event: HoverEvent) -> Option<Self::Message> {
+        Some(Message::Hover(event))
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_tickable(matches!(self.state, State::Pending { .. }));
+    }
+
+    fn tick(&self, _delta: Duration) -> Option<Self::Message> {
+        Some(Message::Tick)
+    }
+}
+
+const PADDING: usize = 1;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::ComponentSelector,
+        components::text::{Text, TextProperties},
+        terminal::{Event, Modifiers, MouseEvent, MouseEventKind},
+        testing::Harness,
+        ComponentExt,
+    };
+
+    #[test]
+    fn shown_after_the_delay() {
+        let mut harness = Harness::new(
+            Size::new(10, 3),
+            Tooltip::with(
+                TooltipProperties::new("hi", || Text::with(TextProperties::new().content("hover")))
+                    .delay(Duration::from_millis(500)),
+            ),
+        );
+        let is_visible = |harness: &Harness| {
+            harness
+                .app()
+                .with_component(&ComponentSelector::of::<Tooltip>(), Tooltip::is_visible)
+                .unwrap()
+        };
+        let event = Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Moved,
+            position: Position::new(1, 0),
+            modifiers: Modifiers::NONE,
+            timestamp: harness.now(),
+        });
+        harness.input(event);
+
+        // The simulated clock only moves when advanced
+        harness.advance(Duration::from_millis(400));
+        assert!(!is_visible(&harness));
+        assert_eq!(harness.line(1), "");
+        harness.advance(Duration::from_millis(100));
+        assert!(is_visible(&harness));
+        assert_eq!(harness.line(1), "  hi");
+    }
+}
@@ -0,0 +1,302 @@
+//! A scrollable window onto content too large to render at once.
+//!
+//! Editors and tables can have a virtual size far larger than what fits on
+//! the screen. Instead of drawing the entire content to a canvas and clipping
+//! it, a [`Viewport`](struct.Viewport.html) asks the content to render only
+//! the visible window, given as a rectangle in the content's coordinates.
+//!
+//! ```
+//! # use zi::{components::viewport::VirtualContent, Canvas, Layout, Rect, Size, Style};
+//! /// A multiplication table with a million rows.
+//! struct Table;
+//!
+//! impl VirtualContent for Table {
+//!     fn size(&self) -> Size {
+//!         Size::new(80, 1_000_000)
+//!     }
+//!
+//!     fn render(&self, window: Rect) -> Layout {
+//!         let mut canvas = Canvas::new(window.size);
+//!         for y in 0..window.size.height {
+//!             let row = window.origin.y + y;
+//!             let line = format!("{} x 2 = {}", row, row * 2);
+//!             canvas.draw_str(0, y, Style::default(), line.get(window.origin.x..).unwrap_or(""));
+//!         }
+//!         canvas.into()
+//!     }
+//! }
+//! ```
+use std::{cmp, rc::Rc};
+
+use super::{
+    place,
+    scroll::{ScrollModel, ScrollSubscription},
+};
+use crate::{
+    Bindings, Canvas, Component, ComponentLink, Key, Layout, Position, Rect, ShouldRender, Size,
+    Style,
+};
+
+/// Content with a virtual size which renders only the parts that are visible.
+pub trait VirtualContent {
+    /// The size of the whole content.
+    fn size(&self) -> Size;
+
+    /// Renders the visible part of the content. `window` is in the content's
+    /// coordinates and is always contained in `size()`.
+    fn render(&self, window: Rect) -> Layout;
+}
+
+pub struct ViewportProperties {
+    pub content: Rc<dyn VirtualContent>,
+    /// The style of the area not covered by content, when the content is
+    /// smaller than the viewport.
+    pub style: Style,
+    pub focused: bool,
+    /// Shares the vertical scroll offset with other components.
+    pub scroll: Option<ScrollModel>,
+}
+
+impl ViewportProperties {
+    pub fn new(content: Rc<dyn VirtualContent>) -> Self {
+        Self {
+            content,
+            style: Style::default(),
+            focused: false,
+            scroll: None,
+        }
+    }
+
+    pub fn style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn scroll(mut self, scroll: ScrollModel) -> Self {
+        self.scroll = Some(scroll);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    Scroll(isize),
+    ScrollColumns(isize),
+    ScrollPages(isize),
+    FirstRow,
+    LastRow,
+    Scrolled,
+}
+
+pub struct Viewport {
+    properties: ViewportProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    scroll: ScrollModel,
+    scroll_subscription: ScrollSubscription,
+    column: usize,
+}
+
+impl Viewport {
+    /// The visible part of the content, in the content's coordinates.
+    pub fn window(&self) -> Rect {
+        let size = self.properties.content.size();
+        let origin = Position::new(self.column, self.scroll.offset());
+        Rect::new(
+            origin,
+            Size::new(
+                cmp::min(self.frame.size.width, size.width.saturating_sub(origin.x)),
+                cmp::min(self.frame.size.height, size.height.saturating_sub(origin.y)),
+            ),
+        )
+    }
+
+    fn max_column(&self) -> usize {
+        let width = self.properties.content.size().width;
+        width.saturating_sub(self.frame.size.width)
+    }
+
+    fn update_extent(&mut self) {
+        self.scroll.set_extent(
+            self.properties.content.size().height,
+            self.frame.size.height,
+        );
+        self.column = cmp::min(self.column, self.max_column());
+    }
+}
+
+impl Component for Viewport {
+    type Message = Message;
+    type Properties = ViewportProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        let scroll = properties.scroll.clone().unwrap_or_default();
+        let scroll_subscription = scroll.subscribe(link.callback(|_| Message::Scrolled));
+        let mut viewport = Self {
+            properties,
+            frame,
+            link,
+            scroll,
+            scroll_subscription,
+            column: 0,
+        };
+        viewport.update_extent();
+        viewport
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties.scroll != properties.scroll {
+            self.scroll = properties.scroll.clone().unwrap_or_default();
+            self.scroll_subscription = self
+                .scroll
+                .subscribe(self.link.callback(|_| Message::Scrolled));
+        }
+        self.properties = properties;
+        self.update_extent();
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        self.update_extent();
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::Scroll(rows) => self.scroll.scroll_by(rows),
+            Message::ScrollColumns(columns) => {
+                let column = if columns < 0 {
+                    self.column.saturating_sub(columns.unsigned_abs())
+                } else {
+                    self.column.saturating_add(columns as usize)
+                };
+                let column = cmp::min(column, self.max_column());
+                if column == self.column {
+                    return ShouldRender::No;
+                }
+                self.column = column;
+            }
+            Message::ScrollPages(pages) => self
+                .scroll
+                .scroll_by(pages * cmp::max(self.frame.size.height, 1) as isize),
+            Message::FirstRow => self.scroll.set_offset(0),
+            Message::LastRow => self.scroll.set_offset(self.scroll.max_offset()),
+            Message::Scrolled => {}
+        }
+        ShouldRender::Yes
+    }
+
+    fn view(&self) -> Layout {
+        let window = self.window();
+        let content = self.properties.content.render(window);
+        if window.size == self.frame.size {
+            return content;
+        }
+
+        let mut background = Canvas::new(self.frame.size);
+        background.clear(self.properties.style);
+        Layout::stack([
+            background.into(),
+            place(Rect::new(Position::new(0, 0), window.size), content),
+        ])
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings
+            .command("row-down", || Message::Scroll(1))
+            .with([Key::Down])
+            .with([Key::Ctrl('n')]);
+        bindings
+            .command("row-up", || Message::Scroll(-1))
+            .with([Key::Up])
+            .with([Key::Ctrl('p')]);
+        bindings
+            .command("column-right", || Message::ScrollColumns(1))
+            .with([Key::Right])
+            .with([Key::Ctrl('f')]);
+        bindings
+            .command("column-left", || Message::ScrollColumns(-1))
+            .with([Key::Left])
+            .with([Key::Ctrl('b')]);
+        bindings
+            .command("page-down", || Message::ScrollPages(1))
+            .with([Key::PageDown])
+            .with([Key::Ctrl('v')]);
+        bindings
+            .command("page-up", || Message::ScrollPages(-1))
+            .with([Key::PageUp])
+            .with([Key::Alt('v')]);
+        bindings
+            .command("first-row", || Message::FirstRow)
+            .with([Key::Alt('<')])
+            .with([Key::Home]);
+        bindings
+            .command("last-row", || Message::LastRow)
+            .with([Key::Alt('>')])
+            .with([Key::End]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    use crate::{testing::Harness, ComponentExt};
+
+    struct Huge {
+        rendered: RefCell<Vec<Rect>>,
+    }
+
+    impl VirtualContent for Huge {
+        fn size(&self) -> Size {
+            Size::new(12, 1_000_000_000)
+        }
+
+        fn render(&self, window: Rect) -> Layout {
+            self.rendered.borrow_mut().push(window);
+            let mut canvas = Canvas::new(window.size);
+            canvas.clear_with(Style::default(), "x");
+            canvas.into()
+        }
+    }
+
+    #[test]
+    fn renders_only_the_visible_window() {
+        let huge = Rc::new(Huge {
+            rendered: RefCell::new(Vec::new()),
+        });
+        let mut harness = Harness::new(
+            Size::new(10, 5),
+            Viewport::with(ViewportProperties::new(huge.clone()).focused(true)),
+        );
+        let window = |harness: &mut Harness| {
+            harness.canvas();
+            *huge.rendered.borrow().last().unwrap()
+        };
+        let at = |x, y| Rect::new(Position::new(x, y), Size::new(10, 5));
+
+        assert_eq!(window(&mut harness), at(0, 0));
+        harness.key(Key::Down);
+        assert_eq!(window(&mut harness), at(0, 1));
+        harness.keys([Key::Right, Key::Right]);
+        assert_eq!(window(&mut harness), at(2, 1));
+        // Scrolling right stops at the edge of the content
+        let num_rendered = huge.rendered.borrow().len();
+        harness.key(Key::Right);
+        assert_eq!(window(&mut harness), at(2, 1));
+        assert_eq!(huge.rendered.borrow().len(), num_rendered);
+        harness.key(Key::End);
+        assert_eq!(window(&mut harness), at(2, 1_000_000_000 - 5));
+    }
+}
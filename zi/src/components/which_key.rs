@@ -0,0 +1,300 @@
+//! A popup listing the keys which continue a pending key sequence.
+//!
+//! [`WhichKey`](struct.WhichKey.html) wraps a layout and, while a key
+//! sequence like `C-x` is the prefix of some bindings, shows a panel at the
+//! bottom with the possible next keys and the commands they run, similar to
+//! `which-key` in Emacs. Keys which start longer sequences are shown as a
+//! group with the number of commands in it. The continuations are reported by
+//! the component which handled the prefix, see
+//! [`Component::notify_continuations`](../../trait.Component.html#method.notify_continuations).
+//!
+//! ```
+//! # use zi::prelude::*;
+//! # use zi::components::{
+//! #     pager::{Pager, PagerProperties},
+//! #     which_key::{WhichKey, WhichKeyProperties},
+//! # };
+//! let pager = WhichKey::with(WhichKeyProperties::new(|| {
+//!     Pager::with(PagerProperties::new("Hello, world!").focused(true))
+//! }));
+//! ```
+use std::cmp;
+use unicode_width::UnicodeWidthStr;
+
+use super::place;
+use crate::{
+    component::bindings::KeySequenceSlice, Bindings, Canvas, Children, Colour, Component,
+    ComponentLink, Key, KeyContinuation, Layout, NamedBindingQuery, Position, Rect, ShouldRender,
+    Size, Style,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WhichKeyStyle {
+    pub panel: Style,
+    /// The pending key sequence, in the first line of the panel.
+    pub prefix: Style,
+    pub key: Style,
+    pub command: Style,
+    /// Keys which start longer sequences.
+    pub group: Style,
+}
+
+impl Default for WhichKeyStyle {
+    fn default() -> Self {
+        const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+        const GRAY_245: Colour = Colour::rgb(146, 131, 116);
+        const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+        const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
+        const BRIGHT_YELLOW: Colour = Colour::rgb(250, 189, 47);
+
+        Self {
+            panel: Style::normal(DARK0_SOFT, LIGHT2),
+            prefix: Style::bold(DARK0_SOFT, GRAY_245),
+            key: Style::bold(DARK0_SOFT, BRIGHT_YELLOW),
+            command: Style::normal(DARK0_SOFT, LIGHT2),
+            group: Style::normal(DARK0_SOFT, BRIGHT_BLUE),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WhichKeyProperties {
+    pub content: Children,
+    pub style: WhichKeyStyle,
+}
+
+impl WhichKeyProperties {
+    pub fn new(content: impl Into<Children>) -> Self {
+        Self {
+            content: content.into(),
+            style: WhichKeyStyle::default(),
+        }
+    }
+
+    pub fn style(mut self, style: WhichKeyStyle) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+/// A key continuing the pending sequence, as shown in the panel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub key: String,
+    /// The name of the command, or a description of the group of commands
+    /// started by the key, e.g. `+3 commands`.
+    pub description: String,
+    pub is_group: bool,
+}
+
+impl Entry {
+    fn new(continuation: &KeyContinuation) -> Self {
+        let key = KeySequenceSlice::from(std::slice::from_ref(&continuation.key)).to_string();
+        match continuation.query {
+            NamedBindingQuery::Match(ref command) => Self {
+                key,
+                description: command.to_string(),
+                is_group: false,
+            },
+            NamedBindingQuery::PrefixOf(ref commands) => Self {
+                key,
+                description: match commands.len() {
+                    1 => "+1 command".to_string(),
+                    num_commands => format!("+{} commands", num_commands),
+                },
+                is_group: true,
+            },
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.key.width() + SEPARATOR.width() + self.description.width()
+    }
+}
+
+/// The pending key sequence and its continuations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pending {
+    pub keys: String,
+    pub entries: Vec<Entry>,
+}
+
+pub struct WhichKey {
+    properties: WhichKeyProperties,
+    frame: Rect,
+    link: ComponentLink<Self>,
+    pending: Option<Pending>,
+}
+
+impl WhichKey {
+    /// Returns `true` if the panel is currently shown.
+    pub fn is_visible(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Returns the pending key sequence and its continuations, if any.
+    pub fn pending(&self) -> Option<&Pending> {
+        self.pending.as_ref()
+    }
+
+    fn view_panel(&self, pending: &Pending) -> Layout {
+        let style = self.properties.style;
+        let width = self.frame.size.width;
+        let column_width = pending.entries.iter().map(Entry::width).max().unwrap_or(0) + COLUMN_GAP;
+        let num_columns = cmp::max(width / column_width, 1);
+        let num_rows = (pending.entries.len() + num_columns - 1) / num_columns;
+        let height = cmp::min(num_rows + 1, self.frame.size.height);
+
+        let mut canvas = Canvas::new(Size::new(width, height));
+        canvas.clear(style.panel);
+        canvas.draw_str(1, 0, style.prefix, &format!("{} -", pending.keys));
+        // The entries fill the columns top to bottom, like in a menu
+        for (index, entry) in pending.entries.iter().enumerate() {
+            let x = 1 + (index / num_rows) * column_width;
+            let y = 1 + index % num_rows;
+            let mut offset = x + canvas.draw_str(x, y, style.key, &entry.key);
+            offset += canvas.draw_str(offset, y, style.command, SEPARATOR);
+            let description_style = if entry.is_group {
+                style.group
+            } else {
+                style.command
+            };
+            canvas.draw_str(offset, y, description_style, &entry.description);
+        }
+
+        let frame = Rect::new(
+            Position::new(0, self.frame.size.height - height),
+            canvas.size(),
+        );
+        place(frame, canvas.into())
+    }
+}
+
+impl Component for WhichKey {
+    type Message = Option<Pending>;
+    type Properties = WhichKeyProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, link: ComponentLink<Self>) -> Self {
+        Self {
+            properties,
+            frame,
+            link,
+            pending: None,
+        }
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        if self.properties != properties {
+            self.properties = properties;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, pending: Self::Message) -> ShouldRender {
+        if self.pending != pending {
+            self.pending = pending;
+            ShouldRender::Yes
+        } else {
+            ShouldRender::No
+        }
+    }
+
+    fn view(&self) -> Layout {
+        let panel = match self.pending {
+            Some(ref pending) if self.frame.size.height > 0 => self.view_panel(pending),
+            _ => Layout::empty(),
+        };
+        Layout::stack([self.properties.content.view(), panel])
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_notify(true);
+    }
+
+    fn notify_continuations(&self, continuations: &[KeyContinuation], keys: &[Key]) {
+        let pending = if continuations.is_empty() {
+            None
+        } else {
+            Some(Pending {
+                keys: KeySequenceSlice::from(keys).to_string(),
+                entries: continuations.iter().map(Entry::new).collect(),
+            })
+        };
+        self.link.send(pending);
+    }
+}
+
+const SEPARATOR: &str = " → ";
+const COLUMN_GAP: usize = 2;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::ComponentSelector,
+        components::text::{Text, TextProperties},
+        testing::Harness,
+        ComponentExt,
+    };
+
+    struct Saver;
+
+    impl Component for Saver {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+            Self
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("save-file", [Key::Ctrl('x'), Key::Ctrl('s')], || ());
+            bindings.add("find-file", [Key::Ctrl('x'), Key::Ctrl('f')], || ());
+        }
+    }
+
+    #[test]
+    fn lists_the_continuations_of_pending_keys() {
+        let mut harness = Harness::new(
+            Size::new(40, 4),
+            WhichKey::with(WhichKeyProperties::new(|| {
+                Layout::stack([Text::with(TextProperties::new()), Saver::with(())])
+            })),
+        );
+        let which_key = ComponentSelector::of::<WhichKey>();
+        let is_visible = |harness: &Harness| {
+            harness
+                .app()
+                .with_component(&which_key, WhichKey::is_visible)
+                .unwrap()
+        };
+        assert_eq!(harness.text(), "\n\n\n");
+
+        harness.key(Key::Ctrl('x'));
+        assert!(is_visible(&harness));
+        assert_eq!(harness.line(2), " C-x -");
+        assert_eq!(harness.line(3), " C-f → find-file  C-s → save-file");
+
+        // The panel is hidden once the sequence matches or is cleared
+        harness.key(Key::Ctrl('s'));
+        assert!(!is_visible(&harness));
+        assert_eq!(harness.text(), "\n\n\n");
+        harness.keys([Key::Ctrl('x'), Key::Char('a')]);
+        assert!(!is_visible(&harness));
+    }
+}
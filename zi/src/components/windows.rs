@@ -0,0 +1,543 @@
+//! Floating windows stacked above a base layout.
+//!
+//! The [`WindowManager`](struct.WindowManager.html) component draws a base
+//! layout and a list of bordered windows on top of it. Windows can be moved,
+//! resized, cycled through and closed using key bindings. The last window
+//! activated is drawn on top of the others and it is the only one whose
+//! content is told it is active, s.t. focus follows activation.
+//!
+//! ```no_run
+//! # use zi::prelude::*;
+//! # use zi::components::{
+//! #     text::{Text, TextProperties},
+//! #     windows::{WindowManager, WindowManagerProperties, WindowProperties},
+//! # };
+//! let windows = WindowManager::with(
+//!     WindowManagerProperties::new(|| Text::with(TextProperties::new().content("Desktop")))
+//!         .window(
+//!             WindowProperties::new("notes", "Notes", |_active| {
+//!                 Text::with(TextProperties::new().content("Hello"))
+//!             })
+//!             .frame(Rect::new(Position::new(2, 1), Size::new(30, 10))),
+//!         )
+//!         .focused(true),
+//! );
+//! ```
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+};
+
+use super::{
+    border::{Border, BorderProperties},
+    place,
+    workspace::WindowArrangement,
+};
+use crate::{
+    terminal::{PositionExt, SizeExt},
+    Bindings, Callback, Children, Component, ComponentExt, ComponentKey, ComponentLink, Key,
+    Layout, Position, Rect, ShouldRender, Size, Style,
+};
+
+#[derive(Clone)]
+pub struct WindowProperties {
+    pub key: ComponentKey,
+    pub title: String,
+    /// The initial frame of the window, relative to the window manager. After
+    /// the window is opened, its frame is owned by the window manager.
+    pub frame: Rect,
+    /// Builds the content of the window, the argument is `true` if the window
+    /// is the active one.
+    pub content: Callback<bool, Layout>,
+}
+
+impl WindowProperties {
+    pub fn new(
+        key: impl Into<ComponentKey>,
+        title: impl Into<String>,
+        content: impl Fn(bool) -> Layout + 'static,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            title: title.into(),
+            frame: Rect::new(Position::new(0, 0), Size::new(40, 12)),
+            content: content.into(),
+        }
+    }
+
+    pub fn frame(mut self, frame: Rect) -> Self {
+        self.frame = frame;
+        self
+    }
+}
+
+#[derive(Clone)]
+pub struct WindowManagerProperties {
+    pub base: Children,
+    pub windows: Vec<WindowProperties>,
+    pub style: Style,
+    pub active_style: Style,
+    pub focused: bool,
+    pub on_close: Option<Callback<ComponentKey>>,
+    /// A saved arrangement to restore, see
+    /// [`arrangement`](struct.WindowManagerProperties.html#method.arrangement).
+    pub arrangement: Option<WindowArrangement>,
+    pub on_arrange: Option<Callback<WindowArrangement>>,
+}
+
+impl WindowManagerProperties {
+    pub fn new(base: impl Into<Children>) -> Self {
+        Self {
+            base: base.into(),
+            windows: Vec::new(),
+            style: Style::default(),
+            active_style: Style::default(),
+            focused: false,
+            on_close: None,
+            arrangement: None,
+            on_arrange: None,
+        }
+    }
+
+    pub fn window(mut self, window: WindowProperties) -> Self {
+        self.windows.push(window);
+        self
+    }
+
+    pub fn windows(mut self, windows: impl IntoIterator<Item = WindowProperties>) -> Self {
+        self.windows.extend(windows);
+        self
+    }
+
+    /// The style of the borders of inactive windows.
+    pub fn style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// The style of the border of the active window.
+    pub fn active_style(mut self, style: impl Into<Style>) -> Self {
+        self.active_style = style.into();
+        self
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Called with the key of a window when it is closed. Parents should
+    /// remove the window from the properties in response, a closed window
+    /// is not shown again until then.
+    pub fn on_close(mut self, on_close: impl Into<Callback<ComponentKey>>) -> Self {
+        self.on_close = Some(on_close.into());
+        self
+    }
+
+    /// Restores a saved arrangement. Windows in the arrangement are opened
+    /// with the saved frames and stacked in the saved order, below any other
+    /// windows.
+    pub fn arrangement(mut self, arrangement: WindowArrangement) -> Self {
+        self.arrangement = Some(arrangement);
+        self
+    }
+
+    /// Called with the new arrangement whenever windows are moved, resized,
+    /// reordered or closed. Parents can use it to persist the arrangement.
+    pub fn on_arrange(mut self, on_arrange: impl Into<Callback<WindowArrangement>>) -> Self {
+        self.on_arrange = Some(on_arrange.into());
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// Brings a window to the top and makes it the active one.
+    Activate(ComponentKey),
+    /// Activates the next window, i.e. the one at the bottom of the stack.
+    CycleNext,
+    /// Activates the previous window, i.e. the one below the active window.
+    CyclePrevious,
+    /// Closes the active window.
+    Close,
+    /// Moves the active window by a number of cells.
+    Move(isize, isize),
+    /// Grows (or shrinks for negative values) the active window by a number of
+    /// cells.
+    Resize(isize, isize),
+}
+
+pub struct WindowManager {
+    properties: WindowManagerProperties,
+    frame: Rect,
+    /// Keys of the open windows from bottom to top, the last one is active.
+    order: Vec<ComponentKey>,
+    frames: HashMap<ComponentKey, Rect>,
+    closed: HashSet<ComponentKey>,
+}
+
+impl WindowManager {
+    /// Returns the key of the active window, if any window is open.
+    pub fn active(&self) -> Option<ComponentKey> {
+        self.order.last().copied()
+    }
+
+    /// Returns the keys of the open windows from bottom to top.
+    pub fn windows(&self) -> &[ComponentKey] {
+        &self.order
+    }
+
+    /// Returns the current frame of an open window.
+    pub fn window_frame(&self, key: ComponentKey) -> Option<Rect> {
+        self.frames.get(&key).map(|frame| self.clamp(*frame))
+    }
+
+    /// Returns the open windows and their frames from bottom to top.
+    pub fn arrangement(&self) -> WindowArrangement {
+        WindowArrangement {
+            windows: self
+                .order
+                .iter()
+                .filter_map(|&key| Some((key, self.window_frame(key)?)))
+                .collect(),
+        }
+    }
+
+    /// Synchronises the open windows with the ones in the properties. New
+    /// windows are opened on top and become active.
+    fn reconcile(&mut self) {
+        let Self {
+            ref properties,
+            ref mut order,
+            ref mut frames,
+            ref mut closed,
+            ..
+        } = *self;
+        let keys: HashSet<_> = properties.windows.iter().map(|window| window.key).collect();
+        order.retain(|key| keys.contains(key));
+        frames.retain(|key, _| keys.contains(key));
+        closed.retain(|key| keys.contains(key));
+        let mut opened: Vec<_> = properties
+            .windows
+            .iter()
+            .filter(|window| !frames.contains_key(&window.key) && !closed.contains(&window.key))
+            .collect();
+        let arrangement = properties.arrangement.as_ref();
+        opened.sort_by_key(|window| {
+            arrangement
+                .and_then(|arrangement| arrangement.position(window.key))
+                .unwrap_or(usize::MAX)
+        });
+        for window in opened {
+            let frame = arrangement
+                .and_then(|arrangement| arrangement.frame(window.key))
+                .unwrap_or(window.frame);
+            frames.insert(window.key, frame);
+            order.push(window.key);
+        }
+    }
+
+    /// Keeps a window inside the frame of the window manager.
+    fn clamp(&self, frame: Rect) -> Rect {
+        let bounds = self.frame.size;
+        let width = cmp::min(cmp::max(frame.size.width, MIN_SIZE.width), bounds.width);
+        let height = cmp::min(cmp::max(frame.size.height, MIN_SIZE.height), bounds.height);
+        Rect::new(
+            Position::new(
+                cmp::min(frame.origin.x, bounds.width - width),
+                cmp::min(frame.origin.y, bounds.height - height),
+            ),
+            Size::new(width, height),
+        )
+    }
+
+    fn update_active_frame(&mut self, update: impl FnOnce(Rect) -> Rect) -> ShouldRender {
+        let frame = match self.active().and_then(|key| self.frames.get(&key)) {
+            Some(frame) => self.clamp(*frame),
+            None => return ShouldRender::No,
+        };
+        let new_frame = self.clamp(update(frame));
+        if new_frame == frame {
+            return ShouldRender::No;
+        }
+        let key = self.active().expect("an active window");
+        self.frames.insert(key, new_frame);
+        ShouldRender::Yes
+    }
+
+    fn update_windows(&mut self, message: Message) -> ShouldRender {
+        match message {
+            Message::Activate(key) => match self.order.iter().position(|&other| other == key) {
+                Some(index) if index + 1 < self.order.len() => {
+                    let key = self.order.remove(index);
+                    self.order.push(key);
+                    ShouldRender::Yes
+                }
+                _ => ShouldRender::No,
+            },
+            Message::CycleNext if self.order.len() > 1 => {
+                self.order.rotate_left(1);
+                ShouldRender::Yes
+            }
+            Message::CyclePrevious if self.order.len() > 1 => {
+                self.order.rotate_right(1);
+                ShouldRender::Yes
+            }
+            Message::CycleNext | Message::CyclePrevious => ShouldRender::No,
+            Message::Close => match self.order.pop() {
+                Some(key) => {
+                    self.frames.remove(&key);
+                    self.closed.insert(key);
+                    if let Some(on_close) = self.properties.on_close.as_ref() {
+                        on_close.emit(key);
+                    }
+                    ShouldRender::Yes
+                }
+                None => ShouldRender::No,
+            },
+            Message::Move(dx, dy) => {
+                self.update_active_frame(|frame| Rect::new(frame.origin.offset(dx, dy), frame.size))
+            }
+            Message::Resize(dx, dy) => {
+                self.update_active_frame(|frame| Rect::new(frame.origin, frame.size.offset(dx, dy)))
+            }
+        }
+    }
+}
+
+impl Component for WindowManager {
+    type Message = Message;
+    type Properties = WindowManagerProperties;
+
+    fn create(properties: Self::Properties, frame: Rect, _link: ComponentLink<Self>) -> Self {
+        let mut manager = Self {
+            properties,
+            frame,
+            order: Vec::new(),
+            frames: HashMap::new(),
+            closed: HashSet::new(),
+        };
+        manager.reconcile();
+        manager
+    }
+
+    fn change(&mut self, properties: Self::Properties) -> ShouldRender {
+        self.properties = properties;
+        self.reconcile();
+        ShouldRender::Yes
+    }
+
+    fn resize(&mut self, frame: Rect) -> ShouldRender {
+        self.frame = frame;
+        ShouldRender::Yes
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        let should_render = self.update_windows(message);
+        if let (ShouldRender::Yes, Some(on_arrange)) =
+            (should_render, self.properties.on_arrange.as_ref())
+        {
+            on_arrange.emit(self.arrangement());
+        }
+        should_render
+    }
+
+    fn view(&self) -> Layout {
+        let active = self.active();
+        let windows = self.order.iter().filter_map(|&key| {
+            let window = self
+                .properties
+                .windows
+                .iter()
+                .find(|window| window.key == key)?;
+            let frame = self.clamp(*self.frames.get(&key)?);
+            let is_active = active == Some(key);
+            let style = if is_active {
+                self.properties.active_style
+            } else {
+                self.properties.style
+            };
+            let content = window.content.clone();
+            let window = Border::with_key(
+                key,
+                BorderProperties::new(move || content.emit(is_active))
+                    .style(style)
+                    .title(Some((window.title.clone(), style))),
+            );
+            Some(place(frame, window))
+        });
+
+        // All windows are in their own layer of the same stack. They are keyed
+        // by the window key s.t. reordering them keeps them mounted.
+        Layout::stack(std::iter::once(self.properties.base.view()).chain(windows))
+    }
+
+    fn bindings(&self, bindings: &mut Bindings<Self>) {
+        bindings.set_focus(self.properties.focused);
+        if !bindings.is_empty() {
+            return;
+        }
+        bindings.add("next-window", [Key::Alt('n')], || Message::CycleNext);
+        bindings.add("previous-window", [Key::Alt('p')], || {
+            Message::CyclePrevious
+        });
+        bindings.add("close-window", [Key::Alt('q')], || Message::Close);
+
+        bindings.add("move-window-left", [Key::Alt('h')], || Message::Move(-1, 0));
+        bindings.add("move-window-down", [Key::Alt('j')], || Message::Move(0, 1));
+        bindings.add("move-window-up", [Key::Alt('k')], || Message::Move(0, -1));
+        bindings.add("move-window-right", [Key::Alt('l')], || Message::Move(1, 0));
+
+        bindings.add("shrink-window-width", [Key::Alt('H')], || {
+            Message::Resize(-1, 0)
+        });
+        bindings.add("grow-window-height", [Key::Alt('J')], || {
+            Message::Resize(0, 1)
+        });
+        bindings.add("shrink-window-height", [Key::Alt('K')], || {
+            Message::Resize(0, -1)
+        });
+        bindings.add("grow-window-width", [Key::Alt('L')], || {
+            Message::Resize(1, 0)
+        });
+    }
+}
+
+/// The smallest window that still fits its border.
+const MIN_SIZE: Size = Size::new(3, 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{app::ComponentSelector, testing::Harness};
+
+    fn frame(x: usize, y: usize, width: usize, height: usize) -> Rect {
+        Rect::new(Position::new(x, y), Size::new(width, height))
+    }
+
+    fn windows(frames: &[(usize, Rect)]) -> WindowManagerProperties {
+        WindowManagerProperties::new(Layout::empty)
+            .windows(frames.iter().map(|&(key, frame)| {
+                WindowProperties::new(key, "", |_| Layout::empty()).frame(frame)
+            }))
+            .focused(true)
+    }
+
+    fn inspect<ResultT>(
+        harness: &mut Harness,
+        inspect: impl FnOnce(&WindowManager) -> ResultT,
+    ) -> ResultT {
+        // Draw first, s.t. the window manager is mounted
+        harness.canvas();
+        harness
+            .app()
+            .with_component(&ComponentSelector::of::<WindowManager>(), inspect)
+            .unwrap()
+    }
+
+    #[test]
+    fn windows_are_moved_and_resized_within_the_screen() {
+        let mut harness = Harness::new(
+            Size::new(20, 10),
+            WindowManager::with(windows(&[(1, frame(0, 0, 5, 3)), (2, frame(2, 2, 40, 12))])),
+        );
+        let frame_of = |harness: &mut Harness, key: usize| {
+            inspect(harness, |manager| manager.window_frame(key.into()).unwrap())
+        };
+        // Windows larger than the screen are shrunk to fit
+        assert_eq!(frame_of(&mut harness, 2), frame(0, 0, 20, 10));
+        harness.key(Key::Alt('n'));
+        assert_eq!(inspect(&mut harness, WindowManager::active), Some(1.into()));
+
+        // Moving stops at the edges of the screen
+        harness.keys([Key::Alt('h'), Key::Alt('k')]);
+        assert_eq!(frame_of(&mut harness, 1), frame(0, 0, 5, 3));
+        harness.keys(std::iter::repeat(Key::Alt('l')).take(20));
+        harness.keys(std::iter::repeat(Key::Alt('j')).take(20));
+        assert_eq!(frame_of(&mut harness, 1), frame(15, 7, 5, 3));
+
+        // Growing a window at the edge moves it back inside, shrinking stops
+        // at the smallest size which fits the border
+        harness.keys([Key::Alt('L'), Key::Alt('J')]);
+        assert_eq!(frame_of(&mut harness, 1), frame(14, 6, 6, 4));
+        harness.keys(std::iter::repeat(Key::Alt('H')).take(5));
+        assert_eq!(frame_of(&mut harness, 1), frame(14, 6, 3, 4));
+
+        // Windows are kept inside the screen when it's resized
+        harness.resize(Size::new(10, 5));
+        assert_eq!(frame_of(&mut harness, 1), frame(7, 1, 3, 4));
+        assert_eq!(frame_of(&mut harness, 2), frame(0, 0, 10, 5));
+    }
+
+    #[test]
+    fn cycle_through_windows() {
+        let mut harness = Harness::new(
+            Size::new(20, 10),
+            WindowManager::with(windows(&[
+                (1, frame(0, 0, 5, 3)),
+                (2, frame(0, 0, 5, 3)),
+                (3, frame(0, 0, 5, 3)),
+            ])),
+        );
+        let stack = |harness: &mut Harness| {
+            inspect(harness, |manager| {
+                (manager.windows().to_vec(), manager.active())
+            })
+        };
+        assert_eq!(
+            stack(&mut harness),
+            (vec![1.into(), 2.into(), 3.into()], Some(3.into()))
+        );
+
+        // The next window is the one at the bottom, it's brought to the top
+        harness.key(Key::Alt('n'));
+        assert_eq!(
+            stack(&mut harness),
+            (vec![2.into(), 3.into(), 1.into()], Some(1.into()))
+        );
+        harness.keys([Key::Alt('p'), Key::Alt('p')]);
+        assert_eq!(
+            stack(&mut harness),
+            (vec![3.into(), 1.into(), 2.into()], Some(2.into()))
+        );
+    }
+
+    #[test]
+    fn close_the_active_and_the_last_window() {
+        let closed = Rc::new(RefCell::new(Vec::new()));
+        let layout = || {
+            let closed = closed.clone();
+            WindowManager::with(
+                windows(&[(1, frame(0, 0, 5, 3)), (2, frame(4, 4, 5, 3))])
+                    .on_close(move |key| closed.borrow_mut().push(key)),
+            )
+        };
+        let mut harness = Harness::new(Size::new(20, 10), layout());
+
+        // Closing the active window activates the one below it
+        harness.key(Key::Alt('q'));
+        assert_eq!(*closed.borrow(), [2.into()]);
+        assert_eq!(inspect(&mut harness, WindowManager::active), Some(1.into()));
+        assert_eq!(
+            inspect(&mut harness, |manager| manager.window_frame(2.into())),
+            None
+        );
+
+        // After the last window is closed, there is nothing left to close,
+        // move or cycle through
+        harness.key(Key::Alt('q'));
+        assert_eq!(*closed.borrow(), [2.into(), 1.into()]);
+        assert_eq!(inspect(&mut harness, WindowManager::active), None);
+        harness.keys([Key::Alt('q'), Key::Alt('l'), Key::Alt('n')]);
+        assert_eq!(closed.borrow().len(), 2);
+        assert!(inspect(&mut harness, |manager| manager
+            .windows()
+            .is_empty()));
+
+        // Closed windows stay closed until the parent removes them
+        harness.app_mut().set_root(layout());
+        assert_eq!(inspect(&mut harness, WindowManager::active), None);
+    }
+}
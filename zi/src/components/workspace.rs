@@ -0,0 +1,374 @@
+//! Saving and restoring the arrangement of split panes and windows.
+//!
+//! A [`PaneTree`](enum.PaneTree.html) describes nested
+//! [`SplitPane`](../split/struct.SplitPane.html)s: their directions, ratios
+//! and the keys of the panes at the leaves. A
+//! [`WindowArrangement`](struct.WindowArrangement.html) describes the order
+//! and frames of the windows of a
+//! [`WindowManager`](../windows/struct.WindowManager.html). Both can be
+//! written to and parsed from strings s.t. applications can persist them.
+//!
+//! ```
+//! # use zi::components::workspace::PaneTree;
+//! let tree: PaneTree = "(row 0.3 1 (column 0.5 2 3))".parse().unwrap();
+//! assert_eq!(tree.to_string(), "(row 0.3 1 (column 0.5 2 3))");
+//! ```
+use std::{error::Error, fmt, rc::Rc, str::FromStr};
+
+use super::split::{SplitPane, SplitPaneProperties};
+use crate::{Callback, ComponentExt, ComponentKey, FlexDirection, Layout, Position, Rect, Size};
+
+/// A tree of split panes with the key of a pane at every leaf.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaneTree {
+    Pane(ComponentKey),
+    Split {
+        direction: FlexDirection,
+        ratio: f32,
+        first: Box<PaneTree>,
+        second: Box<PaneTree>,
+    },
+}
+
+impl PaneTree {
+    pub fn pane(key: impl Into<ComponentKey>) -> Self {
+        Self::Pane(key.into())
+    }
+
+    pub fn split(direction: FlexDirection, ratio: f32, first: PaneTree, second: PaneTree) -> Self {
+        Self::Split {
+            direction,
+            ratio,
+            first: Box::new(first),
+            second: Box::new(second),
+        }
+    }
+
+    /// The keys of the panes, in order.
+    pub fn panes(&self) -> Vec<ComponentKey> {
+        let mut panes = Vec::new();
+        self.collect_panes(&mut panes);
+        panes
+    }
+
+    /// Builds nested split panes, calling `pane` for the content of each
+    /// leaf. Whenever a divider is moved, `on_change` is called with the tree
+    /// updated with the new ratio, which the parent can persist.
+    pub fn view(
+        &self,
+        pane: impl Into<Callback<ComponentKey, Layout>>,
+        on_change: impl Into<Callback<PaneTree>>,
+    ) -> Layout {
+        let root = Rc::new(self.clone());
+        self.view_at(&root, Vec::new(), pane.into(), on_change.into())
+    }
+
+    fn view_at(
+        &self,
+        root: &Rc<PaneTree>,
+        path: Vec<bool>,
+        pane: Callback<ComponentKey, Layout>,
+        on_change: Callback<PaneTree>,
+    ) -> Layout {
+        let (direction, ratio, first, second) = match self {
+            Self::Pane(key) => return pane.emit(*key),
+            Self::Split {
+                direction,
+                ratio,
+                first,
+                second,
+            } => (*direction, *ratio, first, second),
+        };
+        let child = |subtree: &PaneTree, second: bool| {
+            let (subtree, root, pane, on_change) = (
+                subtree.clone(),
+                root.clone(),
+                pane.clone(),
+                on_change.clone(),
+            );
+            let mut path = path.clone();
+            path.push(second);
+            move || subtree.view_at(&root, path.clone(), pane.clone(), on_change.clone())
+        };
+        let properties =
+            SplitPaneProperties::new(direction, child(first, false), child(second, true))
+                .ratio(ratio)
+                .on_change({
+                    let root = root.clone();
+                    move |ratio| {
+                        let mut tree = (*root).clone();
+                        tree.set_ratio(&path, ratio);
+                        on_change.emit(tree);
+                    }
+                });
+        SplitPane::with(properties)
+    }
+
+    /// Sets the ratio of the split at a path of first (`false`) or second
+    /// (`true`) children.
+    fn set_ratio(&mut self, path: &[bool], new_ratio: f32) {
+        if let Self::Split {
+            ratio,
+            first,
+            second,
+            ..
+        } = self
+        {
+            match path.split_first() {
+                None => *ratio = new_ratio,
+                Some((false, path)) => first.set_ratio(path, new_ratio),
+                Some((true, path)) => second.set_ratio(path, new_ratio),
+            }
+        }
+    }
+
+    fn collect_panes(&self, panes: &mut Vec<ComponentKey>) {
+        match self {
+            Self::Pane(key) => panes.push(*key),
+            Self::Split { first, second, .. } => {
+                first.collect_panes(panes);
+                second.collect_panes(panes);
+            }
+        }
+    }
+
+    fn parse(tokens: &mut impl Iterator<Item = String>) -> Result<Self, ParseWorkspaceError> {
+        let token = tokens
+            .next()
+            .ok_or_else(|| ParseWorkspaceError::new("unexpected end"))?;
+        if token != "(" {
+            return Ok(Self::Pane(parse_number::<usize>(&token)?.into()));
+        }
+        let direction = match tokens.next().as_deref() {
+            Some("row") => FlexDirection::Row,
+            Some("row-reverse") => FlexDirection::RowReverse,
+            Some("column") => FlexDirection::Column,
+            Some("column-reverse") => FlexDirection::ColumnReverse,
+            other => {
+                return Err(ParseWorkspaceError::new(format!(
+                    "expected a direction, found {:?}",
+                    other
+                )))
+            }
+        };
+        let ratio = parse_number(&tokens.next().unwrap_or_default())?;
+        let first = Self::parse(tokens)?;
+        let second = Self::parse(tokens)?;
+        match tokens.next().as_deref() {
+            Some(")") => Ok(Self::split(direction, ratio, first, second)),
+            other => Err(ParseWorkspaceError::new(format!(
+                "expected `)`, found {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for PaneTree {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Pane(key) => write!(formatter, "{}", key),
+            Self::Split {
+                direction,
+                ratio,
+                first,
+                second,
+            } => {
+                let direction = match direction {
+                    FlexDirection::Row => "row",
+                    FlexDirection::RowReverse => "row-reverse",
+                    FlexDirection::Column => "column",
+                    FlexDirection::ColumnReverse => "column-reverse",
+                };
+                write!(formatter, "({} {} {} {})", direction, ratio, first, second)
+            }
+        }
+    }
+}
+
+impl FromStr for PaneTree {
+    type Err = ParseWorkspaceError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut tokens = text
+            .replace('(', " ( ")
+            .replace(')', " ) ")
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .into_iter();
+        let tree = Self::parse(&mut tokens)?;
+        match tokens.next() {
+            None => Ok(tree),
+            Some(token) => Err(ParseWorkspaceError::new(format!(
+                "unexpected {:?} after the tree",
+                token
+            ))),
+        }
+    }
+}
+
+/// The open windows of a window manager with their frames, from bottom to
+/// top.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WindowArrangement {
+    pub windows: Vec<(ComponentKey, Rect)>,
+}
+
+impl WindowArrangement {
+    /// The frame of a window, if it's part of the arrangement.
+    pub fn frame(&self, key: ComponentKey) -> Option<Rect> {
+        self.windows
+            .iter()
+            .find(|(other, _)| *other == key)
+            .map(|(_, frame)| *frame)
+    }
+
+    /// The position of a window from the bottom, if it's part of the
+    /// arrangement.
+    pub fn position(&self, key: ComponentKey) -> Option<usize> {
+        self.windows.iter().position(|(other, _)| *other == key)
+    }
+}
+
+/// One window per line, with its key, origin and size, e.g. `7 2 1 40 12`.
+impl fmt::Display for WindowArrangement {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (key, frame) in self.windows.iter() {
+            writeln!(
+                formatter,
+                "{} {} {} {} {}",
+                key, frame.origin.x, frame.origin.y, frame.size.width, frame.size.height
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for WindowArrangement {
+    type Err = ParseWorkspaceError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let windows = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let numbers = line
+                    .split_whitespace()
+                    .map(parse_number)
+                    .collect::<Result<Vec<usize>, _>>()?;
+                match numbers[..] {
+                    [key, x, y, width, height] => Ok((
+                        key.into(),
+                        Rect::new(Position::new(x, y), Size::new(width, height)),
+                    )),
+                    _ => Err(ParseWorkspaceError::new(format!(
+                        "expected a key, origin and size, found {:?}",
+                        line
+                    ))),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { windows })
+    }
+}
+
+/// Error returned when parsing a saved pane tree or window arrangement fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseWorkspaceError(String);
+
+impl ParseWorkspaceError {
+    fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for ParseWorkspaceError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "invalid workspace layout: {}", self.0)
+    }
+}
+
+impl Error for ParseWorkspaceError {}
+
+fn parse_number<T: FromStr>(token: &str) -> Result<T, ParseWorkspaceError> {
+    token
+        .parse()
+        .map_err(|_| ParseWorkspaceError::new(format!("expected a number, found {:?}", token)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    use crate::{
+        components::windows::{WindowManager, WindowManagerProperties, WindowProperties},
+        testing::Harness,
+        Key,
+    };
+
+    #[test]
+    fn round_trip_pane_tree_and_window_arrangement() {
+        let mut tree = PaneTree::split(
+            FlexDirection::Row,
+            0.25,
+            PaneTree::pane(1),
+            PaneTree::split(
+                FlexDirection::ColumnReverse,
+                0.5,
+                PaneTree::pane(2),
+                PaneTree::pane(3),
+            ),
+        );
+        assert_eq!(tree.to_string(), "(row 0.25 1 (column-reverse 0.5 2 3))");
+        assert_eq!(tree.to_string().parse::<PaneTree>(), Ok(tree.clone()));
+        assert_eq!(tree.panes(), vec![1.into(), 2.into(), 3.into()]);
+
+        tree.set_ratio(&[true], 0.75);
+        assert_eq!(tree.to_string(), "(row 0.25 1 (column-reverse 0.75 2 3))");
+        assert!("(row 0.5 1)".parse::<PaneTree>().is_err());
+        assert!("(diagonal 0.5 1 2)".parse::<PaneTree>().is_err());
+
+        let arrangement = WindowArrangement {
+            windows: vec![
+                (4.into(), Rect::new(Position::new(2, 1), Size::new(40, 12))),
+                (7.into(), Rect::new(Position::new(0, 0), Size::new(3, 3))),
+            ],
+        };
+        assert_eq!(arrangement.to_string(), "4 2 1 40 12\n7 0 0 3 3\n");
+        assert_eq!(arrangement.to_string().parse(), Ok(arrangement));
+        assert!("4 2 1".parse::<WindowArrangement>().is_err());
+    }
+
+    #[test]
+    fn restore_and_save_window_arrangement() {
+        let saved = Rc::new(RefCell::new(Vec::new()));
+        let frame = |x, y| Rect::new(Position::new(x, y), Size::new(5, 3));
+        let window =
+            |key: usize| WindowProperties::new(key, "", |_| Layout::empty()).frame(frame(0, 0));
+        let mut harness = Harness::new(
+            Size::new(20, 10),
+            WindowManager::with(
+                WindowManagerProperties::new(Layout::empty)
+                    .windows([window(1), window(2)])
+                    .arrangement("2 4 4 5 3\n1 1 1 5 3".parse().unwrap())
+                    .on_arrange({
+                        let saved = saved.clone();
+                        move |arrangement| saved.borrow_mut().push(arrangement)
+                    })
+                    .focused(true),
+            ),
+        );
+        harness.key(Key::Alt('l'));
+
+        let saved = saved.borrow();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(
+            saved[0],
+            WindowArrangement {
+                windows: vec![(2.into(), frame(4, 4)), (1.into(), frame(2, 1))]
+            }
+        );
+    }
+}
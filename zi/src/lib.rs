@@ -142,21 +142,44 @@
 //! repository.
 
 pub mod app;
+pub mod backend;
 pub mod components;
+pub mod recording;
+#[cfg(feature = "scripting")]
+pub mod scripting;
 pub mod terminal;
+pub mod testing;
+pub mod theme;
+pub mod util;
+#[cfg(feature = "fs-watch")]
+pub mod watch;
 
 pub use component::{
-    bindings::{AnyCharacter, BindingQuery, Bindings, EndsWith, Keymap, NamedBindingQuery},
-    layout::{self, ComponentExt, ComponentKey, Container, FlexBasis, FlexDirection, Item},
-    Callback, Component, ComponentLink, Layout, ShouldRender,
+    bindings::{
+        AnyCharacter, BindingBuilder, BindingQuery, Bindings, CommandId, CommandInfo,
+        CommandMetadata, DoublePress, EndsWith, Hold, KeyContinuation, KeyGesture, KeyMatcher,
+        KeyPattern, KeyPredicate, KeySequenceSlice, Keymap, NamedBindingQuery,
+    },
+    function::{self, FunctionComponent, Hooks, State},
+    keymap_config::{KeymapOverrides, ParseKeyError},
+    layout::{
+        self, ComponentExt, ComponentKey, Container, FlexAlign, FlexBasis, FlexDirection,
+        FlexJustify, FlexWrap, Item, SizeHint, Spacing, Stack,
+    },
+    Callback, Children, Component, ComponentHandle, ComponentLink, Layout, RenderPriority,
+    ReportedError, ShouldRender, StreamHandle, TimerHandle,
 };
 pub use terminal::{Background, Canvas, Colour, Foreground, Key, Position, Rect, Size, Style};
+pub use theme::Theme;
+#[cfg(feature = "macros")]
+pub use zi_macros::Properties;
 
 pub mod prelude {
     //! The Zi prelude.
+    pub use super::terminal::{PositionExt, RectExt, SizeExt};
     pub use super::{
-        AnyCharacter, Bindings, Component, ComponentExt, ComponentLink, Container, FlexBasis,
-        FlexDirection, Item, Layout, ShouldRender,
+        AnyCharacter, Bindings, Component, ComponentExt, ComponentLink, Container, FlexAlign,
+        FlexBasis, FlexDirection, FlexJustify, FlexWrap, Item, Layout, ShouldRender, Spacing,
     };
     pub use super::{Background, Canvas, Colour, Foreground, Key, Position, Rect, Size, Style};
 }
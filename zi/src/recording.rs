@@ -0,0 +1,888 @@
+//! Recording and replaying the events handled by an [`App`](../app/struct.App.html).
+//!
+//! A recording is started with
+//! [`App::start_recording`](../app/struct.App.html#method.start_recording).
+//! From then on, every input event, resize and component message handled by
+//! the app is logged with the time elapsed since the recording started. The
+//! [`Recording`](struct.Recording.html) returned by
+//! [`App::stop_recording`](../app/struct.App.html#method.stop_recording) can
+//! be inspected, or replayed step by step against a fresh app using
+//! [`Replay`](struct.Replay.html) to travel through the states the UI went
+//! through.
+//!
+//! Component messages are not required to implement `Clone`, so by default
+//! they are recorded with their destination only. When replaying, the
+//! messages sent by components in reaction to the replayed input are
+//! delivered as usual by the app's message sender, while messages which
+//! originated outside the app (e.g. from a background thread) are not sent
+//! again.
+//!
+//! If the messages of a component type implement `Clone`, an app can opt in
+//! to recording copies of them with
+//! [`App::record_messages`](../app/struct.App.html#method.record_messages).
+//! [`Replay`](struct.Replay.html) re-delivers the copies in their original
+//! order, wherever the messages came from. To not deliver them twice, the app
+//! ignores the messages sent to components of the type through its message
+//! sender until the replay is finished, so the sender should be drained after
+//! every step.
+//!
+//! Input events and resizes can also be saved with [`Recording::write`](struct.Recording.html#method.write) and
+//! loaded with [`Recording::read`](struct.Recording.html#method.read), e.g. to
+//! reproduce a bug reported by a user or as the script of an integration
+//! test. Backends log input to a file as it happens with a
+//! [`RecordingWriter`](struct.RecordingWriter.html) and feed a recording
+//! back into an app at its original pace with
+//! [`Replay::play_until`](struct.Replay.html#method.play_until).
+//!
+//! Recordings are saved as text, one event per line, prefixed with the
+//! number of microseconds elapsed since the recording started:
+//!
+//! ```
+//! # use zi::{recording::{RecordedEvent, Recording}, terminal::{Event, Key}};
+//! let saved = "\
+//! ## zi recording
+//! 0 resize 80 24
+//! 1200000 press char:6a
+//! 1350000 press ctrl:78
+//! 1400000 mouse down:left 10 3 ctrl
+//! 1450000 press click:left:ctrl
+//! ";
+//! let recording = Recording::read(saved.as_bytes()).unwrap();
+//! assert_eq!(recording.entries.len(), 5);
+//! assert!(matches!(
+//!     recording.entries[2].event,
+//!     RecordedEvent::Input(Event::KeyPress(Key::Ctrl('x'), _))
+//! ));
+//!
+//! let mut written = Vec::new();
+//! recording.write(&mut written).unwrap();
+//! assert_eq!(String::from_utf8(written).unwrap(), saved);
+//! ```
+use std::{
+    any::TypeId,
+    collections::HashSet,
+    fmt,
+    io::{self, BufRead, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    app::App,
+    component::template::{ComponentId, DynamicMessage},
+    terminal::{Event, Grapheme, Key, Modifiers, MouseButton, MouseEvent, MouseEventKind},
+    Position, Size,
+};
+
+/// An event handled by an app while recording.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordedEvent {
+    Input(Event),
+    Resize(Size),
+    /// A message sent to a component, identified by its type and position.
+    /// A copy of the message is kept if the app records the messages of the
+    /// component's type.
+    Message {
+        component: String,
+        message: Option<RecordedMessage>,
+    },
+    /// An error reported by a component.
+    Error(String),
+    /// A request to stop the app.
+    Exit,
+}
+
+impl fmt::Display for RecordedEvent {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Input(Event::KeyPress(key, _)) => write!(formatter, "input {:?}", key),
+            Self::Input(Event::KeyRepeat(key, _)) => write!(formatter, "input {:?} repeat", key),
+            Self::Input(Event::KeyRelease(key, _)) => {
+                write!(formatter, "input {:?} release", key)
+            }
+            Self::Input(Event::Mouse(event)) => write!(
+                formatter,
+                "input {:?} at {}x{}",
+                event.kind, event.position.x, event.position.y
+            ),
+            Self::Resize(size) => write!(formatter, "resize {}x{}", size.width, size.height),
+            Self::Message { component, .. } => write!(formatter, "message to {}", component),
+            Self::Error(error) => write!(formatter, "error {}", error),
+            Self::Exit => write!(formatter, "exit"),
+        }
+    }
+}
+
+/// A copy of a message sent to a component whose messages are recorded, see
+/// [`App::record_messages`](../app/struct.App.html#method.record_messages).
+///
+/// Recorded messages are equal if they are sent to the same component, their
+/// contents aren't compared.
+pub struct RecordedMessage {
+    component_id: ComponentId,
+    message: DynamicMessage,
+    clone: CloneMessage,
+}
+
+/// Copies a type-erased message, see [`clone_message`](fn.clone_message.html).
+pub(crate) type CloneMessage = fn(&DynamicMessage) -> DynamicMessage;
+
+impl RecordedMessage {
+    pub(crate) fn new(
+        component_id: ComponentId,
+        message: &DynamicMessage,
+        clone: CloneMessage,
+    ) -> Self {
+        Self {
+            component_id,
+            message: clone(message),
+            clone,
+        }
+    }
+
+    pub(crate) fn component_id(&self) -> ComponentId {
+        self.component_id
+    }
+
+    /// Returns a copy of the recorded message.
+    pub(crate) fn message(&self) -> DynamicMessage {
+        (self.clone)(&self.message)
+    }
+}
+
+impl Clone for RecordedMessage {
+    fn clone(&self) -> Self {
+        Self::new(self.component_id, &self.message, self.clone)
+    }
+}
+
+impl PartialEq for RecordedMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.component_id == other.component_id
+    }
+}
+
+impl fmt::Debug for RecordedMessage {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("RecordedMessage")
+            .field("component", &format_args!("{}", self.component_id))
+            .finish()
+    }
+}
+
+pub(crate) fn clone_message<MessageT: Clone + Send + 'static>(
+    message: &DynamicMessage,
+) -> DynamicMessage {
+    DynamicMessage(Box::new(
+        message
+            .0
+            .downcast_ref::<MessageT>()
+            .expect("Incorrect `Message` type when downcasting")
+            .clone(),
+    ))
+}
+
+/// A recorded event with the time elapsed since the recording started.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedEntry {
+    pub elapsed: Duration,
+    pub event: RecordedEvent,
+}
+
+/// The log of events handled by an app while recording.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    pub entries: Vec<RecordedEntry>,
+}
+
+impl Recording {
+    /// The entries which can be re-delivered when replaying, i.e. input
+    /// events, resizes and the messages recorded with a copy.
+    pub fn replayable(&self) -> impl Iterator<Item = &RecordedEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| is_replayable(&entry.event))
+    }
+
+    /// Writes the input events and resizes as text, see the
+    /// [module documentation](index.html) for the format. Messages are
+    /// skipped, even when recorded with a copy.
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{}", HEADER)?;
+        for entry in self.entries.iter().filter(|entry| is_saved(&entry.event)) {
+            write_entry(&mut writer, entry)?;
+        }
+        writer.flush()
+    }
+
+    /// Reads a recording written by [`write`](#method.write) or by a
+    /// [`RecordingWriter`](struct.RecordingWriter.html). Blank lines and
+    /// lines starting with `#` are skipped.
+    ///
+    /// Input events are timestamped as if the recording started when it's
+    /// read, replaying with
+    /// [`Replay::play_until`](struct.Replay.html#method.play_until)
+    /// timestamps them again.
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let started = Instant::now();
+        let mut entries = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_entry(line, started).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid recorded event on line {}: {}", index + 1, line),
+                )
+            })?;
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// Writes the input events and resizes handled by an app to a file (or any
+/// other writer) as they happen, in the format read by
+/// [`Recording::read`](struct.Recording.html#method.read).
+///
+/// Each event is flushed when it's written, s.t. the recording survives the
+/// app crashing.
+pub struct RecordingWriter<WriterT: Write> {
+    writer: WriterT,
+    started: Instant,
+}
+
+impl<WriterT: Write> RecordingWriter<WriterT> {
+    /// Starts a recording now, writing its header.
+    pub fn new(mut writer: WriterT) -> io::Result<Self> {
+        writeln!(writer, "{}", HEADER)?;
+        writer.flush()?;
+        Ok(Self {
+            writer,
+            started: Instant::now(),
+        })
+    }
+
+    /// Writes an event with the time elapsed since the recording started.
+    /// Events which can't be saved, e.g. component messages, are skipped.
+    pub fn write(&mut self, event: &RecordedEvent) -> io::Result<()> {
+        if !is_saved(event) {
+            return Ok(());
+        }
+        write_entry(
+            &mut self.writer,
+            &RecordedEntry {
+                elapsed: self.started.elapsed(),
+                event: event.clone(),
+            },
+        )?;
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> WriterT {
+        self.writer
+    }
+}
+
+impl<WriterT: Write> fmt::Debug for RecordingWriter<WriterT> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("RecordingWriter")
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+/// Replays a recording against an app, one event at a time.
+///
+/// To travel back in time, create a fresh app with the same root layout and
+/// replay it up to an earlier step.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    entries: Vec<RecordedEntry>,
+    position: usize,
+    /// The types of the components whose messages are re-delivered.
+    component_types: HashSet<TypeId>,
+}
+
+impl Replay {
+    pub fn new(recording: &Recording) -> Self {
+        let entries: Vec<_> = recording.replayable().cloned().collect();
+        let component_types = entries
+            .iter()
+            .filter_map(|entry| match entry.event {
+                RecordedEvent::Message {
+                    message: Some(ref message),
+                    ..
+                } => Some(message.component_id().type_id()),
+                _ => None,
+            })
+            .collect();
+        Self {
+            entries,
+            position: 0,
+            component_types,
+        }
+    }
+
+    /// Re-delivers the next event to the app and returns it, or returns
+    /// `None` if the replay is finished. The app is drawn before delivering
+    /// input, s.t. components are mounted and their bindings are up to date.
+    /// Input events keep their original timestamps.
+    pub fn step(&mut self, app: &mut App) -> Option<&RecordedEntry> {
+        let entry = self.entries.get(self.position)?;
+        self.position += 1;
+        let is_finished = self.is_finished();
+        app.set_replayed_component_types((!is_finished).then(|| &self.component_types));
+        match entry.event {
+            RecordedEvent::Input(event) => {
+                app.draw();
+                app.handle_input(event);
+            }
+            RecordedEvent::Resize(size) => app.handle_resize(size),
+            RecordedEvent::Message {
+                message: Some(ref message),
+                ..
+            } => app.replay_message(message),
+            RecordedEvent::Message { message: None, .. }
+            | RecordedEvent::Error(_)
+            | RecordedEvent::Exit => {}
+        }
+        Some(entry)
+    }
+
+    /// Re-delivers the events which are due at `now` when replaying at the
+    /// original pace from `started`, and returns how many were delivered.
+    /// Input events are timestamped with the time they are due, s.t. double
+    /// presses and holds are replayed faithfully.
+    pub fn play_until(&mut self, app: &mut App, started: Instant, now: Instant) -> usize {
+        let mut num_delivered = 0;
+        while let Some(entry) = self.entries.get_mut(self.position) {
+            let due = started + entry.elapsed;
+            if due > now {
+                break;
+            }
+            if let RecordedEvent::Input(ref mut event) = entry.event {
+                *event = event.with_timestamp(due);
+            }
+            self.step(app);
+            num_delivered += 1;
+        }
+        num_delivered
+    }
+
+    /// When the next event is due when replaying at the original pace from
+    /// `started`, or `None` if the replay is finished.
+    pub fn next_deadline(&self, started: Instant) -> Option<Instant> {
+        self.entries
+            .get(self.position)
+            .map(|entry| started + entry.elapsed)
+    }
+
+    /// Replays events until `position` events were delivered in total.
+    pub fn step_to(&mut self, app: &mut App, position: usize) {
+        while self.position < position && self.step(app).is_some() {}
+    }
+
+    /// The number of events delivered so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The total number of events to replay.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.entries.len()
+    }
+}
+
+/// Accumulates entries while an app is recording.
+pub(crate) struct Recorder {
+    started: Instant,
+    recording: Recording,
+}
+
+impl Recorder {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            recording: Recording::default(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, event: RecordedEvent) {
+        self.recording.entries.push(RecordedEntry {
+            elapsed: self.started.elapsed(),
+            event,
+        });
+    }
+
+    pub(crate) fn finish(self) -> Recording {
+        self.recording
+    }
+}
+
+fn is_replayable(event: &RecordedEvent) -> bool {
+    is_saved(event)
+        || matches!(
+            event,
+            RecordedEvent::Message {
+                message: Some(_),
+                ..
+            }
+        )
+}
+
+fn is_saved(event: &RecordedEvent) -> bool {
+    matches!(event, RecordedEvent::Input(_) | RecordedEvent::Resize(_))
+}
+
+const HEADER: &str = "# zi recording";
+
+fn write_entry(writer: &mut impl Write, entry: &RecordedEntry) -> io::Result<()> {
+    write!(writer, "{} ", entry.elapsed.as_micros())?;
+    match entry.event {
+        RecordedEvent::Input(Event::KeyPress(key, _)) => {
+            writeln!(writer, "press {}", KeyToken(key))
+        }
+        RecordedEvent::Input(Event::KeyRepeat(key, _)) => {
+            writeln!(writer, "repeat {}", KeyToken(key))
+        }
+        RecordedEvent::Input(Event::KeyRelease(key, _)) => {
+            writeln!(writer, "release {}", KeyToken(key))
+        }
+        RecordedEvent::Input(Event::Mouse(event)) => {
+            let kind = match event.kind {
+                MouseEventKind::Moved => "moved".into(),
+                MouseEventKind::Down(button) => format!("down:{}", button_name(button)),
+                MouseEventKind::Up(button) => format!("up:{}", button_name(button)),
+                MouseEventKind::Drag(button) => format!("drag:{}", button_name(button)),
+                MouseEventKind::ScrollUp => "scroll-up".into(),
+                MouseEventKind::ScrollDown => "scroll-down".into(),
+            };
+            writeln!(
+                writer,
+                "mouse {} {} {} {}",
+                kind,
+                event.position.x,
+                event.position.y,
+                ModifiersToken(event.modifiers)
+            )
+        }
+        RecordedEvent::Resize(size) => writeln!(writer, "resize {} {}", size.width, size.height),
+        RecordedEvent::Message { .. } | RecordedEvent::Error(_) | RecordedEvent::Exit => Ok(()),
+    }
+}
+
+fn parse_entry(line: &str, started: Instant) -> Option<RecordedEntry> {
+    let mut tokens = line.split_whitespace();
+    let elapsed = Duration::from_micros(tokens.next()?.parse().ok()?);
+    let timestamp = started + elapsed;
+    let event = match tokens.next()? {
+        "press" => RecordedEvent::Input(Event::KeyPress(parse_key(tokens.next()?)?, timestamp)),
+        "repeat" => RecordedEvent::Input(Event::KeyRepeat(parse_key(tokens.next()?)?, timestamp)),
+        "release" => RecordedEvent::Input(Event::KeyRelease(parse_key(tokens.next()?)?, timestamp)),
+        "mouse" => {
+            let kind = match split_argument(tokens.next()?) {
+                ("moved", None) => MouseEventKind::Moved,
+                ("down", Some(button)) => MouseEventKind::Down(parse_button(button)?),
+                ("up", Some(button)) => MouseEventKind::Up(parse_button(button)?),
+                ("drag", Some(button)) => MouseEventKind::Drag(parse_button(button)?),
+                ("scroll-up", None) => MouseEventKind::ScrollUp,
+                ("scroll-down", None) => MouseEventKind::ScrollDown,
+                _ => return None,
+            };
+            let x = tokens.next()?.parse().ok()?;
+            let y = tokens.next()?.parse().ok()?;
+            RecordedEvent::Input(Event::Mouse(MouseEvent {
+                kind,
+                position: Position::new(x, y),
+                modifiers: parse_modifiers(tokens.next()?)?,
+                timestamp,
+            }))
+        }
+        "resize" => {
+            let width = tokens.next()?.parse().ok()?;
+            let height = tokens.next()?.parse().ok()?;
+            RecordedEvent::Resize(Size::new(width, height))
+        }
+        _ => return None,
+    };
+    match tokens.next() {
+        Some(_) => None,
+        None => Some(RecordedEntry { elapsed, event }),
+    }
+}
+
+/// Writes a key as a single token without whitespace. Characters are written
+/// as hex code points, as they may be whitespace themselves.
+struct KeyToken(Key);
+
+impl fmt::Display for KeyToken {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Key::Backspace => write!(formatter, "backspace"),
+            Key::Left => write!(formatter, "left"),
+            Key::Right => write!(formatter, "right"),
+            Key::Up => write!(formatter, "up"),
+            Key::Down => write!(formatter, "down"),
+            Key::Home => write!(formatter, "home"),
+            Key::End => write!(formatter, "end"),
+            Key::PageUp => write!(formatter, "page-up"),
+            Key::PageDown => write!(formatter, "page-down"),
+            Key::BackTab => write!(formatter, "back-tab"),
+            Key::Delete => write!(formatter, "delete"),
+            Key::Insert => write!(formatter, "insert"),
+            Key::F(number) => write!(formatter, "f{}", number),
+            Key::Char(character) => write!(formatter, "char:{:x}", character as u32),
+            Key::Alt(character) => write!(formatter, "alt:{:x}", character as u32),
+            Key::Ctrl(character) => write!(formatter, "ctrl:{:x}", character as u32),
+            Key::Null => write!(formatter, "null"),
+            Key::Esc => write!(formatter, "esc"),
+            Key::Click(button, modifiers) => write!(
+                formatter,
+                "click:{}:{}",
+                button_name(button),
+                ModifiersToken(modifiers)
+            ),
+            Key::Release(button, modifiers) => write!(
+                formatter,
+                "release:{}:{}",
+                button_name(button),
+                ModifiersToken(modifiers)
+            ),
+            Key::Drag(button, modifiers) => write!(
+                formatter,
+                "drag:{}:{}",
+                button_name(button),
+                ModifiersToken(modifiers)
+            ),
+            Key::ScrollUp(modifiers) => {
+                write!(formatter, "scroll-up:{}", ModifiersToken(modifiers))
+            }
+            Key::ScrollDown(modifiers) => {
+                write!(formatter, "scroll-down:{}", ModifiersToken(modifiers))
+            }
+            Key::Grapheme(grapheme) => {
+                write!(formatter, "grapheme:")?;
+                for (index, character) in grapheme.chars().enumerate() {
+                    if index > 0 {
+                        write!(formatter, "+")?;
+                    }
+                    write!(formatter, "{:x}", character as u32)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_key(token: &str) -> Option<Key> {
+    let parse_char = |hex: &str| char::from_u32(u32::from_str_radix(hex, 16).ok()?);
+    let parse_mouse = |argument: Option<&str>| {
+        let (button, modifiers) = split_argument(argument?);
+        Some((parse_button(button)?, parse_modifiers(modifiers?)?))
+    };
+    Some(match split_argument(token) {
+        ("backspace", None) => Key::Backspace,
+        ("left", None) => Key::Left,
+        ("right", None) => Key::Right,
+        ("up", None) => Key::Up,
+        ("down", None) => Key::Down,
+        ("home", None) => Key::Home,
+        ("end", None) => Key::End,
+        ("page-up", None) => Key::PageUp,
+        ("page-down", None) => Key::PageDown,
+        ("back-tab", None) => Key::BackTab,
+        ("delete", None) => Key::Delete,
+        ("insert", None) => Key::Insert,
+        ("null", None) => Key::Null,
+        ("esc", None) => Key::Esc,
+        ("char", Some(hex)) => Key::Char(parse_char(hex)?),
+        ("alt", Some(hex)) => Key::Alt(parse_char(hex)?),
+        ("ctrl", Some(hex)) => Key::Ctrl(parse_char(hex)?),
+        ("click", argument) => {
+            let (button, modifiers) = parse_mouse(argument)?;
+            Key::Click(button, modifiers)
+        }
+        ("release", argument) => {
+            let (button, modifiers) = parse_mouse(argument)?;
+            Key::Release(button, modifiers)
+        }
+        ("drag", argument) => {
+            let (button, modifiers) = parse_mouse(argument)?;
+            Key::Drag(button, modifiers)
+        }
+        ("scroll-up", Some(modifiers)) => Key::ScrollUp(parse_modifiers(modifiers)?),
+        ("scroll-down", Some(modifiers)) => Key::ScrollDown(parse_modifiers(modifiers)?),
+        ("grapheme", Some(hexes)) => {
+            let grapheme = hexes
+                .split('+')
+                .map(parse_char)
+                .collect::<Option<String>>()?;
+            Key::Grapheme(Grapheme::new(&grapheme)?)
+        }
+        (name, None) if name.starts_with('f') => Key::F(name[1..].parse().ok()?),
+        _ => return None,
+    })
+}
+
+/// Splits a token at its first `:`, e.g. `char:61` into `char` and `61`.
+fn split_argument(token: &str) -> (&str, Option<&str>) {
+    match token.find(':') {
+        Some(index) => (&token[..index], Some(&token[index + 1..])),
+        None => (token, None),
+    }
+}
+
+fn button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+    }
+}
+
+fn parse_button(name: &str) -> Option<MouseButton> {
+    match name {
+        "left" => Some(MouseButton::Left),
+        "right" => Some(MouseButton::Right),
+        "middle" => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+/// Writes modifiers as e.g. `ctrl+shift`, or `-` if none are held.
+struct ModifiersToken(Modifiers);
+
+impl fmt::Display for ModifiersToken {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let names = [
+            (self.0.ctrl, "ctrl"),
+            (self.0.alt, "alt"),
+            (self.0.shift, "shift"),
+        ]
+        .iter()
+        .filter(|(held, _)| *held)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>();
+        if names.is_empty() {
+            write!(formatter, "-")
+        } else {
+            write!(formatter, "{}", names.join("+"))
+        }
+    }
+}
+
+fn parse_modifiers(token: &str) -> Option<Modifiers> {
+    let mut modifiers = Modifiers::NONE;
+    if token == "-" {
+        return Some(modifiers);
+    }
+    for name in token.split('+') {
+        match name {
+            "ctrl" => modifiers.ctrl = true,
+            "alt" => modifiers.alt = true,
+            "shift" => modifiers.shift = true,
+            _ => return None,
+        }
+    }
+    Some(modifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::ComponentSelector,
+        components::pager::{Pager, PagerProperties},
+        prelude::*,
+        testing::Harness,
+    };
+
+    fn pager(content: &str) -> Layout {
+        Pager::with(PagerProperties::new(content).focused(true))
+    }
+
+    fn offset(harness: &Harness) -> usize {
+        harness
+            .app()
+            .with_component(&ComponentSelector::of::<Pager>(), Pager::offset)
+            .unwrap()
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let content: String = (0..20).map(|line| format!("line {}\n", line)).collect();
+        let mut harness = Harness::new(Size::new(20, 5), pager(&content));
+        harness.app_mut().start_recording();
+        harness.resize(Size::new(20, 4));
+        harness.keys([Key::Char('j'); 3]);
+        let recording = harness.app_mut().stop_recording().unwrap();
+        assert!(!harness.app().is_recording());
+        assert_eq!(offset(&harness), 3);
+        assert_eq!(recording.replayable().count(), 4);
+        assert!(recording.entries.iter().any(|entry| matches!(
+            entry.event,
+            RecordedEvent::Message { ref component, message: None } if component.contains("Pager")
+        )));
+
+        // Replaying against a fresh app goes through the same states
+        let mut harness = Harness::new(Size::new(20, 5), pager(&content));
+        let mut replay = Replay::new(&recording);
+        replay.step_to(harness.app_mut(), 3);
+        harness.settle();
+        assert_eq!(offset(&harness), 2);
+        while replay.step(harness.app_mut()).is_some() {
+            harness.settle();
+        }
+        assert!(replay.is_finished());
+        assert_eq!(offset(&harness), 3);
+    }
+
+    #[derive(Clone, Debug)]
+    enum TallyMessage {
+        Press,
+        Add(usize),
+    }
+
+    struct Tally {
+        count: usize,
+        link: ComponentLink<Self>,
+    }
+
+    impl Component for Tally {
+        type Message = TallyMessage;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            Self { count: 0, link }
+        }
+
+        fn view(&self) -> Layout {
+            let mut canvas = Canvas::new(Size::new(10, 1));
+            canvas.draw_str(0, 0, Style::default(), &self.count.to_string());
+            canvas.into()
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                // Sent through the app's message sender in reaction to input
+                TallyMessage::Press => self.link.send(TallyMessage::Add(1)),
+                TallyMessage::Add(increment) => self.count += increment,
+            }
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.set_focus(true);
+            bindings.add("press", [Key::Char('+')], || TallyMessage::Press);
+        }
+    }
+
+    #[test]
+    fn replay_recorded_messages() {
+        let record = |record_messages| {
+            let mut harness = Harness::new(Size::new(10, 1), Tally::with(()));
+            if record_messages {
+                harness.app_mut().record_messages::<Tally>();
+            }
+            harness.app_mut().start_recording();
+            harness.key(Key::Char('+'));
+            // A message which originated outside the app, e.g. from a
+            // background thread
+            let handle = harness
+                .app()
+                .handle_of::<Tally>(&ComponentSelector::of::<Tally>())
+                .unwrap();
+            harness.send_to(handle, TallyMessage::Add(100));
+            harness.key(Key::Char('+'));
+            assert_eq!(harness.line(0), "102");
+            harness.app_mut().stop_recording().unwrap()
+        };
+        let replay = |recording: &Recording| {
+            let mut harness = Harness::new(Size::new(10, 1), Tally::with(()));
+            let mut replay = Replay::new(recording);
+            let mut lines = Vec::new();
+            while replay.step(harness.app_mut()).is_some() {
+                harness.settle();
+                lines.push(harness.line(0));
+            }
+            lines
+        };
+
+        // Without copies, only the messages sent in reaction to input are
+        // delivered again
+        let recording = record(false);
+        assert_eq!(recording.replayable().count(), 2);
+        assert_eq!(replay(&recording), ["1", "2"]);
+
+        // With copies, every message is re-delivered once, in order
+        let recording = record(true);
+        assert_eq!(recording.replayable().count(), 5);
+        assert_eq!(replay(&recording), ["0", "1", "101", "101", "102"]);
+
+        // Messages can't be saved
+        let mut written = Vec::new();
+        recording.write(&mut written).unwrap();
+        assert_eq!(Recording::read(&written[..]).unwrap().entries.len(), 2);
+    }
+
+    #[test]
+    fn replay_saved_recordings_at_their_original_pace() {
+        let content: String = (0..20).map(|line| format!("line {}\n", line)).collect();
+
+        let mut writer = RecordingWriter::new(Vec::new()).unwrap();
+        writer
+            .write(&RecordedEvent::Resize(Size::new(20, 4)))
+            .unwrap();
+        writer
+            .write(&RecordedEvent::Input(Event::key_press(Key::PageDown)))
+            .unwrap();
+        writer.write(&RecordedEvent::Exit).unwrap();
+        let written = Recording::read(&writer.into_inner()[..]).unwrap();
+        assert_eq!(written.entries.len(), 2);
+
+        let saved = "\
+            # A pager scrolled down twice\n\
+            0 resize 20 4\n\
+            100000 press char:6a\n\
+            \n\
+            200000 press char:6a\n";
+        let recording = Recording::read(saved.as_bytes()).unwrap();
+        assert!(Recording::read("0 press char:zz".as_bytes()).is_err());
+
+        let mut harness = Harness::new(Size::new(20, 5), pager(&content));
+        let started = Instant::now();
+        let mut replay = Replay::new(&recording);
+        let mut play_until = |harness: &mut Harness, elapsed| {
+            let num_delivered = replay.play_until(
+                harness.app_mut(),
+                started,
+                started + Duration::from_millis(elapsed),
+            );
+            harness.settle();
+            (num_delivered, replay.next_deadline(started))
+        };
+        assert_eq!(
+            play_until(&mut harness, 150),
+            (2, Some(started + Duration::from_millis(200)))
+        );
+        assert_eq!(offset(&harness), 1);
+        assert_eq!(play_until(&mut harness, 200), (1, None));
+        assert_eq!(offset(&harness), 2);
+    }
+}
@@ -0,0 +1,227 @@
+//! App-level commands implemented in an embedded scripting language.
+//!
+//! Zi doesn't depend on an interpreter. Instead, an app implements the
+//! [`ScriptEngine`](trait.ScriptEngine.html) trait for the language it embeds,
+//! e.g. rhai or Lua, and [`Scripts`](struct.Scripts.html) binds the commands
+//! defined by the loaded scripts as global bindings of the app, see
+//! [`App::add_global_binding`](../app/struct.App.html#method.add_global_binding).
+//! Scripts can be reloaded and their bindings reinstalled while the app runs,
+//! s.t. users can extend it with their own commands, e.g. from a config
+//! directory watched with the `fs-watch` feature.
+//!
+//! This module requires the `scripting` feature.
+//!
+//! ```
+//! # use std::sync::mpsc;
+//! # use zi::{app::{App, ComponentMessage, MessageSender}, prelude::*, terminal::Event};
+//! # use zi::components::text::{Text, TextProperties};
+//! # #[derive(Clone, Debug)]
+//! # struct MessageQueue(mpsc::Sender<ComponentMessage>);
+//! # impl MessageSender for MessageQueue {
+//! #     fn send(&self, message: ComponentMessage) {
+//! #         self.0.send(message).unwrap();
+//! #     }
+//! #     fn clone_box(&self) -> Box<dyn MessageSender> {
+//! #         Box::new(self.clone())
+//! #     }
+//! # }
+//! use zi::{scripting::{ScriptEngine, Scripts}, KeyPattern};
+//!
+//! /// A toy language, each line of a script binds keys to a built-in command,
+//! /// e.g. `C-x C-c exit`.
+//! #[derive(Default)]
+//! struct Toy {
+//!     lines: Vec<String>,
+//! }
+//!
+//! impl ScriptEngine for Toy {
+//!     type Error = String;
+//!
+//!     fn bindings(&self) -> Vec<(String, KeyPattern)> {
+//!         self.lines
+//!             .iter()
+//!             .filter_map(|line| {
+//!                 let (keys, command) = line.rsplit_once(' ')?;
+//!                 Some((command.to_owned(), keys.parse().ok()?))
+//!             })
+//!             .collect()
+//!     }
+//!
+//!     fn run(&mut self, command: &str, app: &mut App) -> Result<(), Self::Error> {
+//!         match command {
+//!             "exit" => {
+//!                 app.exit();
+//!                 Ok(())
+//!             }
+//!             _ => Err(format!("unknown command `{}`", command)),
+//!         }
+//!     }
+//! }
+//!
+//! # let (sender, _receiver) = mpsc::channel();
+//! let mut app = App::new(
+//!     MessageQueue(sender),
+//!     Size::new(10, 1),
+//!     Text::with(TextProperties::new().content("Hello")),
+//! );
+//! let mut scripts = Scripts::new(Toy::default());
+//! scripts.engine_mut().lines.push("C-x C-c exit".into());
+//! scripts.install(&mut app);
+//!
+//! app.draw();
+//! app.handle_input(Event::key_press(Key::Ctrl('x')));
+//! app.handle_input(Event::key_press(Key::Ctrl('c')));
+//! assert!(app.poll_state().exit());
+//! ```
+use std::{
+    cell::{RefCell, RefMut},
+    fmt,
+    rc::Rc,
+};
+
+use crate::{app::App, KeyPattern};
+
+/// An interpreter for scripts which define app-level commands.
+pub trait ScriptEngine: 'static {
+    /// The error returned when a command fails, it's logged as a warning.
+    type Error: fmt::Display;
+
+    /// The commands defined by the loaded scripts and the keys they are bound
+    /// to, e.g. collected from calls to a `bind` function in the scripts.
+    fn bindings(&self) -> Vec<(String, KeyPattern)>;
+
+    /// Runs a command defined by the scripts. The engine exposes the parts of
+    /// the app it needs to its language, e.g. to
+    /// [`exit`](../app/struct.App.html#method.exit), to
+    /// [`publish`](../app/struct.App.html#method.publish) a value or to run
+    /// the commands of components with
+    /// [`execute_command`](../app/struct.App.html#method.execute_command).
+    fn run(&mut self, command: &str, app: &mut App) -> Result<(), Self::Error>;
+}
+
+/// Binds the commands of a [`ScriptEngine`](trait.ScriptEngine.html) as
+/// global bindings of an app.
+pub struct Scripts<EngineT: ScriptEngine> {
+    engine: Rc<RefCell<EngineT>>,
+    /// The keys bound by the last call to [`install`](#method.install).
+    installed: Vec<KeyPattern>,
+}
+
+impl<EngineT: ScriptEngine> Scripts<EngineT> {
+    pub fn new(engine: EngineT) -> Self {
+        Self {
+            engine: Rc::new(RefCell::new(engine)),
+            installed: Vec::new(),
+        }
+    }
+
+    /// The engine, e.g. to load or reload scripts before
+    /// [`install`](#method.install)ing their bindings.
+    pub fn engine_mut(&self) -> RefMut<'_, EngineT> {
+        self.engine.borrow_mut()
+    }
+
+    /// Binds the commands defined by the scripts, replacing the bindings
+    /// installed previously. Like other global bindings, they only see the
+    /// keys the focused components don't handle and the keys of a binding
+    /// which overlap with those of another one are skipped.
+    pub fn install(&mut self, app: &mut App) {
+        self.uninstall(app);
+        let bindings = self.engine.borrow().bindings();
+        for (command, keys) in bindings {
+            let engine = Rc::clone(&self.engine);
+            let name = command.clone();
+            app.add_global_binding(command, keys.clone(), move |app: &mut App| {
+                // A command can't run another one while it's running
+                let mut engine = match engine.try_borrow_mut() {
+                    Ok(engine) => engine,
+                    Err(_) => {
+                        log::warn!("Script command `{}` can't run from a script", name);
+                        return;
+                    }
+                };
+                if let Err(error) = engine.run(&name, app) {
+                    log::warn!("Script command `{}` failed: {}", name, error);
+                }
+            });
+            self.installed.push(keys);
+        }
+    }
+
+    /// Removes the bindings installed by [`install`](#method.install).
+    pub fn uninstall(&mut self, app: &mut App) {
+        for keys in self.installed.drain(..) {
+            app.remove_global_binding(keys);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    use crate::{
+        components::text::{Text, TextProperties},
+        testing::Harness,
+        ComponentExt, Key, Size,
+    };
+
+    #[derive(Default)]
+    struct Counter {
+        bindings: Vec<(String, KeyPattern)>,
+        runs: Rc<Cell<usize>>,
+    }
+
+    impl ScriptEngine for Counter {
+        type Error = String;
+
+        fn bindings(&self) -> Vec<(String, KeyPattern)> {
+            self.bindings.clone()
+        }
+
+        fn run(&mut self, command: &str, _app: &mut App) -> Result<(), Self::Error> {
+            match command {
+                "count" => {
+                    self.runs.set(self.runs.get() + 1);
+                    Ok(())
+                }
+                _ => Err(format!("unknown command `{}`", command)),
+            }
+        }
+    }
+
+    #[test]
+    fn install_and_reinstall_script_commands() {
+        let mut harness = Harness::new(Size::new(10, 1), Text::with(TextProperties::new()));
+        let engine = Counter::default();
+        let runs = Rc::clone(&engine.runs);
+        let mut scripts = Scripts::new(engine);
+
+        scripts.engine_mut().bindings = vec![
+            ("count".into(), [Key::Ctrl('c')].into()),
+            ("missing".into(), [Key::Ctrl('e')].into()),
+        ];
+        scripts.install(harness.app_mut());
+        harness.key(Key::Ctrl('c'));
+        // Failing commands are logged
+        harness.key(Key::Ctrl('e'));
+        assert_eq!(runs.get(), 1);
+
+        // Reinstalling replaces the keys bound previously
+        scripts.engine_mut().bindings = vec![("count".into(), [Key::Ctrl('n')].into())];
+        scripts.install(harness.app_mut());
+        harness.key(Key::Ctrl('c'));
+        harness.key(Key::Ctrl('n'));
+        assert_eq!(runs.get(), 2);
+        assert!(harness
+            .app()
+            .global_keymap()
+            .check_sequence(&[Key::Ctrl('e')])
+            .is_none());
+
+        scripts.uninstall(harness.app_mut());
+        harness.key(Key::Ctrl('n'));
+        assert_eq!(runs.get(), 2);
+    }
+}
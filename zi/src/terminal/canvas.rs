@@ -1,10 +1,10 @@
 use smallstr::SmallString;
-use std::{self, cmp, iter};
+use std::{self, cmp, iter, sync::Arc};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
-use super::{Position, Size};
-use crate::terminal::Rect;
+use super::{Colour, Image, ImagePlacement, Position, Size, TerminalCursor};
+use crate::terminal::{Rect, RectExt};
 
 /// An extended grapheme cluster represented as a `SmallString`.
 pub type GraphemeCluster = SmallString<[u8; 16]>;
@@ -15,6 +15,9 @@ pub type GraphemeCluster = SmallString<[u8; 16]>;
 pub struct Textel {
     pub grapheme: GraphemeCluster,
     pub style: Style,
+    /// The target of the hyperlink the textel is part of, if any. Backends
+    /// which support OSC 8 hyperlinks make the text clickable.
+    pub link: Option<Arc<str>>,
 }
 
 /// A lightweight abstract terminal. All components in Zi ultimately draw to a
@@ -24,6 +27,8 @@ pub struct Textel {
 pub struct Canvas {
     buffer: Vec<Option<Textel>>,
     size: Size,
+    images: Vec<ImagePlacement>,
+    cursor: Option<TerminalCursor>,
 }
 
 impl Canvas {
@@ -42,6 +47,8 @@ impl Canvas {
                 .take(size.area())
                 .collect(),
             size,
+            images: Vec::new(),
+            cursor: None,
         }
     }
 
@@ -60,14 +67,36 @@ impl Canvas {
         self.buffer.as_mut_slice()
     }
 
+    /// The images drawn on the canvas, see
+    /// [`draw_image`](#method.draw_image).
+    #[inline]
+    pub fn images(&self) -> &[ImagePlacement] {
+        self.images.as_slice()
+    }
+
+    /// The terminal cursor shown by the backend, hidden if `None`. The app
+    /// sets it on the canvas it draws, using the cursor requested by the
+    /// focused components. It isn't copied to other canvases.
+    #[inline]
+    pub fn cursor(&self) -> Option<TerminalCursor> {
+        self.cursor
+    }
+
+    #[inline]
+    pub fn set_cursor(&mut self, cursor: Option<TerminalCursor>) {
+        self.cursor = cursor;
+    }
+
     #[inline]
     pub fn resize(&mut self, size: Size) {
         self.buffer.resize(size.area(), Default::default());
         self.size = size;
+        self.images.clear();
     }
 
     #[inline]
     pub fn clear_region(&mut self, region: Rect, style: Style) {
+        self.remove_images(region);
         let y_range =
             region.origin.y..cmp::min(region.origin.y + region.size.height, self.size.height);
         let x_range =
@@ -81,6 +110,7 @@ impl Canvas {
 
     #[inline]
     pub fn clear(&mut self, style: Style) {
+        self.images.clear();
         self.buffer
             .iter_mut()
             .for_each(|textel| clear_textel(textel, style, " "))
@@ -88,6 +118,7 @@ impl Canvas {
 
     #[inline]
     pub fn clear_with(&mut self, style: Style, content: &str) {
+        self.images.clear();
         self.buffer
             .iter_mut()
             .for_each(|textel| clear_textel(textel, style, content))
@@ -105,6 +136,49 @@ impl Canvas {
         y: usize,
         style: Style,
         graphemes: impl Iterator<Item = impl Into<GraphemeCluster>>,
+    ) -> usize {
+        self.draw_linked_graphemes(x, y, style, None, graphemes)
+    }
+
+    /// Draws text which links to `target`, e.g. a URL. On terminals without
+    /// support for hyperlinks, the text is drawn as with
+    /// [`draw_str`](#method.draw_str).
+    ///
+    /// ```
+    /// # use zi::{Canvas, Size, Style, Colour};
+    /// let mut canvas = Canvas::new(Size::new(10, 1));
+    /// let style = Style::normal(Colour::black(), Colour::white());
+    /// canvas.draw_link(0, 0, style, "zi", "https://docs.rs/zi");
+    /// assert_eq!(
+    ///     canvas.textel(0, 0).as_ref().and_then(|textel| textel.link.as_deref()),
+    ///     Some("https://docs.rs/zi")
+    /// );
+    /// ```
+    #[inline]
+    pub fn draw_link(
+        &mut self,
+        x: usize,
+        y: usize,
+        style: Style,
+        text: &str,
+        target: impl Into<Arc<str>>,
+    ) -> usize {
+        self.draw_linked_graphemes(
+            x,
+            y,
+            style,
+            Some(target.into()),
+            UnicodeSegmentation::graphemes(text, true),
+        )
+    }
+
+    fn draw_linked_graphemes(
+        &mut self,
+        x: usize,
+        y: usize,
+        style: Style,
+        link: Option<Arc<str>>,
+        graphemes: impl Iterator<Item = impl Into<GraphemeCluster>>,
     ) -> usize {
         if y >= self.size.height || x >= self.size.width {
             return 0;
@@ -125,7 +199,11 @@ impl Canvas {
                 continue;
             }
 
-            self.buffer[current_offset] = Some(Textel { grapheme, style });
+            self.buffer[current_offset] = Some(Textel {
+                grapheme,
+                style,
+                link: link.clone(),
+            });
 
             let num_modified = cmp::min(grapheme_width, max_offset - current_offset);
             self.buffer[current_offset + 1..current_offset + num_modified]
@@ -156,6 +234,124 @@ impl Canvas {
                 )
                 .for_each(|(textel, other)| *textel = other.clone());
         }
+        self.copy_images(
+            source,
+            Rect::new(Position::zero(), source.size),
+            region.origin,
+        );
+    }
+
+    /// Copies the part of `source` inside `window` s.t. its top left corner
+    /// is at `position`, clipped to the size of the canvas.
+    #[inline]
+    pub fn copy_window(&mut self, source: &Self, window: Rect, position: Position) {
+        let window = window.intersect(&Rect::new(Position::zero(), source.size));
+        let width = cmp::min(
+            window.size.width,
+            self.size.width.saturating_sub(position.x),
+        );
+        let height = cmp::min(
+            window.size.height,
+            self.size.height.saturating_sub(position.y),
+        );
+
+        for dy in 0..height {
+            let target = (position.y + dy) * self.size.width + position.x;
+            let source_start = (window.origin.y + dy) * source.size.width + window.origin.x;
+            self.buffer[target..target + width]
+                .clone_from_slice(&source.buffer[source_start..source_start + width]);
+        }
+        self.copy_images(source, window, position);
+    }
+
+    /// Like [`copy_window`](#method.copy_window), but cells of `source`
+    /// which are unset, i.e. have an empty grapheme, are skipped s.t. the
+    /// content below shows through.
+    #[inline]
+    pub fn blend_window(&mut self, source: &Self, window: Rect, position: Position) {
+        let window = window.intersect(&Rect::new(Position::zero(), source.size));
+        let width = cmp::min(
+            window.size.width,
+            self.size.width.saturating_sub(position.x),
+        );
+        let height = cmp::min(
+            window.size.height,
+            self.size.height.saturating_sub(position.y),
+        );
+
+        for dy in 0..height {
+            let target = (position.y + dy) * self.size.width + position.x;
+            let source_start = (window.origin.y + dy) * source.size.width + window.origin.x;
+            self.buffer[target..target + width]
+                .iter_mut()
+                .zip(&source.buffer[source_start..source_start + width])
+                .filter(|(_, textel)| !is_unset(textel))
+                .for_each(|(textel, other)| *textel = other.clone());
+        }
+        self.copy_images(source, window, position);
+    }
+
+    /// Draws an image scaled to fill a region of cells.
+    ///
+    /// The region is drawn with half block characters, two pixels per cell,
+    /// which is how the image appears on terminals without graphics support.
+    /// Backends which support a graphics protocol draw the image itself over
+    /// the region, see [`images`](#method.images). Anything copied over the
+    /// region afterwards, e.g. a popup in a layer above, hides the image.
+    pub fn draw_image(&mut self, region: Rect, image: &Image) {
+        let region = region.intersect(&Rect::new(Position::zero(), self.size));
+        if region.is_empty() {
+            return;
+        }
+        let blocks = image.resized(Size::new(region.size.width, 2 * region.size.height));
+        for y in 0..region.size.height {
+            for x in 0..region.size.width {
+                *self.textel_mut(region.origin.x + x, region.origin.y + y) = Some(Textel {
+                    grapheme: UPPER_HALF_BLOCK.into(),
+                    style: Style::normal(
+                        blocks.blended_colour(x, 2 * y + 1),
+                        blocks.blended_colour(x, 2 * y),
+                    ),
+                    link: None,
+                });
+            }
+        }
+        self.remove_images(region);
+        self.images.push(ImagePlacement {
+            region,
+            image: image.clone(),
+        });
+    }
+
+    /// Copies the images of `source` which are entirely inside `window`,
+    /// replacing the images under the area they are copied to.
+    fn copy_images(&mut self, source: &Self, window: Rect, position: Position) {
+        let window = window.intersect(&Rect::new(Position::zero(), source.size));
+        let bounds = Rect::new(Position::zero(), self.size);
+        self.remove_images(Rect::new(position, window.size).intersect(&bounds));
+        for placement in source.images.iter() {
+            if !window.contains_rect(&placement.region) {
+                continue;
+            }
+            let region = Rect::new(
+                Position::new(
+                    position.x + placement.region.origin.x - window.origin.x,
+                    position.y + placement.region.origin.y - window.origin.y,
+                ),
+                placement.region.size,
+            );
+            if bounds.contains_rect(&region) {
+                self.images.push(ImagePlacement {
+                    region,
+                    image: placement.image.clone(),
+                });
+            }
+        }
+    }
+
+    fn remove_images(&mut self, region: Rect) {
+        self.images
+            .retain(|placement| !placement.region.intersects(&region));
     }
 
     #[inline]
@@ -197,12 +393,29 @@ impl std::fmt::Display for Canvas {
 
 /// Specifies how content should be styled. This represents a subset of the ANSI
 /// available styles which is widely supported by terminal emulators.
+///
+/// Attributes can be combined with the `with_*` methods
+///
+/// ```
+/// # use zi::{Colour, Style};
+/// let style = Style::normal(Colour::black(), Colour::white())
+///     .with_italic()
+///     .with_strikethrough();
+/// assert!(style.italic && style.strikethrough && !style.bold);
+/// ```
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Style {
     pub background: Background,
     pub foreground: Foreground,
     pub bold: bool,
     pub underline: bool,
+    pub italic: bool,
+    pub strikethrough: bool,
+    /// Drawn with a faint foreground, not supported by all terminals.
+    pub dim: bool,
+    /// Swaps the foreground and background colours when drawn.
+    pub reverse: bool,
+    pub blink: bool,
 }
 
 impl Style {
@@ -219,37 +432,32 @@ impl Style {
             foreground,
             bold: false,
             underline: false,
+            italic: false,
+            strikethrough: false,
+            dim: false,
+            reverse: false,
+            blink: false,
         }
     }
 
     #[inline]
     pub const fn bold(background: Background, foreground: Foreground) -> Self {
-        Self {
-            background,
-            foreground,
-            bold: true,
-            underline: false,
-        }
+        Self::normal(background, foreground).with_bold()
     }
 
     #[inline]
     pub const fn underline(background: Background, foreground: Foreground) -> Self {
-        Self {
-            background,
-            foreground,
-            bold: false,
-            underline: true,
-        }
+        Self::normal(background, foreground).with_underline()
+    }
+
+    #[inline]
+    pub const fn italic(background: Background, foreground: Foreground) -> Self {
+        Self::normal(background, foreground).with_italic()
     }
 
     #[inline]
     pub const fn same_colour(colour: Colour) -> Self {
-        Self {
-            background: colour,
-            foreground: colour,
-            bold: false,
-            underline: false,
-        }
+        Self::normal(colour, colour)
     }
 
     #[inline]
@@ -257,52 +465,57 @@ impl Style {
         Self {
             background: self.foreground,
             foreground: self.background,
-            bold: self.bold,
-            underline: self.underline,
+            ..self
         }
     }
-}
 
-impl Default for Style {
     #[inline]
-    fn default() -> Self {
-        Self::default()
+    pub const fn with_bold(mut self) -> Self {
+        self.bold = true;
+        self
     }
-}
 
-/// An RGB encoded colour, 1-byte per channel.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Colour {
-    pub red: u8,
-    pub green: u8,
-    pub blue: u8,
-}
+    #[inline]
+    pub const fn with_underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
 
-impl Colour {
-    /// Creates a colour from the provided RGB values.
     #[inline]
-    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
-        Self { red, green, blue }
+    pub const fn with_italic(mut self) -> Self {
+        self.italic = true;
+        self
     }
 
-    /// Returns black.
     #[inline]
-    pub const fn black() -> Self {
-        Self {
-            red: 0,
-            green: 0,
-            blue: 0,
-        }
+    pub const fn with_strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
     }
 
-    /// Returns white.
     #[inline]
-    pub const fn white() -> Self {
-        Self {
-            red: 255,
-            green: 255,
-            blue: 255,
-        }
+    pub const fn with_dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    #[inline]
+    pub const fn with_reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    #[inline]
+    pub const fn with_blink(mut self) -> Self {
+        self.blink = true;
+        self
+    }
+}
+
+impl Default for Style {
+    #[inline]
+    fn default() -> Self {
+        Self::default()
     }
 }
 
@@ -312,21 +525,31 @@ pub type Background = Colour;
 /// Type alias for foreground colours.
 pub type Foreground = Colour;
 
+/// Whether nothing was drawn in a cell. The cells covered by wide graphemes
+/// are `None` and count as set.
+#[inline]
+fn is_unset(textel: &Option<Textel>) -> bool {
+    matches!(textel, Some(textel) if textel.grapheme.is_empty())
+}
+
 #[inline]
 fn clear_textel(textel: &mut Option<Textel>, style: Style, value: &str) {
     match *textel {
         Some(Textel {
             style: ref mut textel_style,
             ref mut grapheme,
+            ref mut link,
         }) => {
             *textel_style = style;
             grapheme.clear();
             grapheme.push_str(value);
+            *link = None;
         }
         _ => {
             *textel = Some(Textel {
                 style,
                 grapheme: " ".into(),
+                link: None,
             });
         }
     }
@@ -386,7 +609,75 @@ const UPPER_HALF_BLOCK: &str = "▀";
 
 #[cfg(test)]
 mod tests {
-    use super::{GraphemeCluster, Style, Textel};
+    use super::{Canvas, Colour, GraphemeCluster, Image, Position, Rect, Size, Style, Textel};
+
+    #[test]
+    fn images_are_composited_with_the_canvas() {
+        let (red, blue) = (Colour::rgb(255, 0, 0), Colour::rgb(0, 0, 255));
+        let image = Image::from_fn(
+            Size::new(2, 2),
+            |position| {
+                if position.y == 0 {
+                    red
+                } else {
+                    blue
+                }
+            },
+        );
+        let mut source = Canvas::new(Size::new(4, 2));
+        source.draw_image(Rect::new(Position::new(1, 0), Size::new(2, 1)), &image);
+        let textel = source.textel(1, 0).as_ref().unwrap();
+        assert_eq!(textel.grapheme.as_str(), "▀");
+        assert_eq!(textel.style, Style::normal(blue, red));
+
+        // Copied images are moved with the canvas
+        let mut screen = Canvas::new(Size::new(8, 4));
+        screen.copy_window(
+            &source,
+            Rect::new(Position::zero(), source.size()),
+            Position::new(3, 2),
+        );
+        assert_eq!(screen.images().len(), 1);
+        assert_eq!(
+            screen.images()[0].region,
+            Rect::new(Position::new(4, 2), Size::new(2, 1))
+        );
+
+        // Drawing over an image hides it, and clipped images are dropped
+        screen.copy_window(
+            &Canvas::new(Size::new(1, 1)),
+            Rect::new(Position::zero(), Size::new(1, 1)),
+            Position::new(5, 2),
+        );
+        assert!(screen.images().is_empty());
+        screen.copy_window(
+            &source,
+            Rect::new(Position::zero(), Size::new(2, 1)),
+            Position::zero(),
+        );
+        assert!(screen.images().is_empty());
+    }
+
+    #[test]
+    fn links_are_removed_when_drawn_over() {
+        let style = Style::default();
+        let link = |canvas: &Canvas, x| {
+            canvas
+                .textel(x, 0)
+                .as_ref()
+                .and_then(|textel| textel.link.as_deref().map(String::from))
+        };
+        let mut canvas = Canvas::new(Size::new(6, 1));
+        assert_eq!(canvas.draw_link(1, 0, style, "zi", "https://docs.rs/zi"), 2);
+        assert_eq!(link(&canvas, 0), None);
+        assert_eq!(link(&canvas, 2).as_deref(), Some("https://docs.rs/zi"));
+
+        canvas.draw_str(2, 0, style, "x");
+        assert_eq!(link(&canvas, 1).as_deref(), Some("https://docs.rs/zi"));
+        assert_eq!(link(&canvas, 2), None);
+        canvas.clear(style);
+        assert_eq!(link(&canvas, 1), None);
+    }
 
     #[test]
     fn size_of_style() {
@@ -0,0 +1,85 @@
+use super::Size;
+
+/// The features supported by the backend and the terminal it draws to.
+///
+/// Backends set them with
+/// [`App::set_capabilities`](../app/struct.App.html#method.set_capabilities)
+/// and components read them with
+/// [`ComponentLink::capabilities`](../struct.ComponentLink.html#method.capabilities)
+/// s.t. they can degrade gracefully, e.g. draw an image with block characters
+/// when the terminal doesn't support graphics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BackendCapabilities {
+    /// The colours the terminal can display.
+    pub colours: ColourSupport,
+    /// Whether mouse events are reported.
+    pub mouse: bool,
+    /// Whether the terminal supports OSC 8 hyperlinks.
+    pub hyperlinks: bool,
+    /// The protocol used to draw images, if any.
+    pub graphics: Option<GraphicsProtocol>,
+    /// The size of a cell in pixels, if the terminal reports it.
+    pub cell_pixel_size: Option<Size>,
+}
+
+impl BackendCapabilities {
+    /// The capabilities of a terminal with true colour support and mouse
+    /// reporting, but no hyperlinks or graphics.
+    pub const fn new() -> Self {
+        Self {
+            colours: ColourSupport::TrueColour,
+            mouse: true,
+            hyperlinks: false,
+            graphics: None,
+            cell_pixel_size: None,
+        }
+    }
+
+    pub fn colours(mut self, colours: ColourSupport) -> Self {
+        self.colours = colours;
+        self
+    }
+
+    pub fn mouse(mut self, mouse: bool) -> Self {
+        self.mouse = mouse;
+        self
+    }
+
+    pub fn hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+
+    pub fn graphics(mut self, graphics: Option<GraphicsProtocol>) -> Self {
+        self.graphics = graphics;
+        self
+    }
+
+    pub fn cell_pixel_size(mut self, size: Option<Size>) -> Self {
+        self.cell_pixel_size = size;
+        self
+    }
+}
+
+impl Default for BackendCapabilities {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The colours a terminal can display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColourSupport {
+    Monochrome,
+    Ansi16,
+    Ansi256,
+    TrueColour,
+}
+
+/// A protocol for drawing images in a terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+    ITerm2,
+}
@@ -0,0 +1,246 @@
+use std::cmp;
+
+use super::ColourSupport;
+
+/// A colour, either an RGB value or an entry of the terminal's palette.
+///
+/// RGB colours are only displayed as is by terminals with true colour
+/// support. Backends use [`downsample`](#method.downsample) to replace them
+/// with the closest colour the terminal supports.
+///
+/// ```
+/// # use zi::{terminal::{BaseColour, ColourSupport}, Colour};
+/// let orange = Colour::rgb(254, 128, 25);
+/// assert_eq!(orange.downsample(ColourSupport::Ansi256), Colour::Indexed(208));
+/// assert_eq!(
+///     orange.downsample(ColourSupport::Ansi16),
+///     Colour::Base(BaseColour::Yellow)
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Colour {
+    /// The default foreground or background colour of the terminal.
+    Default,
+    /// One of the 16 colours of the terminal's palette.
+    Base(BaseColour),
+    /// An index in the 256 colour palette. The first 16 entries are the base
+    /// colours, followed by a 6x6x6 colour cube and a grayscale ramp.
+    Indexed(u8),
+    /// An RGB encoded colour, 1-byte per channel.
+    Rgb { red: u8, green: u8, blue: u8 },
+}
+
+impl Colour {
+    /// Creates a colour from the provided RGB values.
+    #[inline]
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Self::Rgb { red, green, blue }
+    }
+
+    /// Returns black.
+    #[inline]
+    pub const fn black() -> Self {
+        Self::rgb(0, 0, 0)
+    }
+
+    /// Returns white.
+    #[inline]
+    pub const fn white() -> Self {
+        Self::rgb(255, 255, 255)
+    }
+
+    /// The RGB value of the colour. Palette colours are converted using the
+    /// default xterm palette, the default colour has no RGB value.
+    pub const fn to_rgb(self) -> Option<(u8, u8, u8)> {
+        match self {
+            Self::Default => None,
+            Self::Base(base) => Some(BASE_PALETTE[base as usize]),
+            Self::Indexed(index) => Some(indexed_to_rgb(index)),
+            Self::Rgb { red, green, blue } => Some((red, green, blue)),
+        }
+    }
+
+    /// Returns the closest colour which can be displayed with the given
+    /// colour support. Colours which are supported are returned unchanged.
+    pub fn downsample(self, support: ColourSupport) -> Self {
+        match (support, self) {
+            (ColourSupport::TrueColour, _) | (_, Self::Default) => self,
+            (ColourSupport::Monochrome, _) => Self::Default,
+            (ColourSupport::Ansi256, Self::Base(_) | Self::Indexed(_)) => self,
+            (ColourSupport::Ansi256, Self::Rgb { red, green, blue }) => {
+                Self::Indexed(nearest_indexed(red, green, blue))
+            }
+            (ColourSupport::Ansi16, Self::Base(_)) => self,
+            (ColourSupport::Ansi16, Self::Indexed(index)) if index < 16 => {
+                Self::Base(BaseColour::ALL[index as usize])
+            }
+            (ColourSupport::Ansi16, Self::Indexed(_) | Self::Rgb { .. }) => {
+                let (red, green, blue) = self.to_rgb().unwrap_or_default();
+                Self::Base(nearest_base(red, green, blue))
+            }
+        }
+    }
+}
+
+impl From<BaseColour> for Colour {
+    fn from(base: BaseColour) -> Self {
+        Self::Base(base)
+    }
+}
+
+/// The 16 colours of a terminal's palette. Their appearance depends on the
+/// terminal's theme.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BaseColour {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl BaseColour {
+    /// All the base colours, in the order of their index in the palette.
+    pub const ALL: [BaseColour; 16] = [
+        Self::Black,
+        Self::Red,
+        Self::Green,
+        Self::Yellow,
+        Self::Blue,
+        Self::Magenta,
+        Self::Cyan,
+        Self::White,
+        Self::BrightBlack,
+        Self::BrightRed,
+        Self::BrightGreen,
+        Self::BrightYellow,
+        Self::BrightBlue,
+        Self::BrightMagenta,
+        Self::BrightCyan,
+        Self::BrightWhite,
+    ];
+
+    /// The index of the colour in the palette.
+    #[inline]
+    pub const fn index(self) -> u8 {
+        self as u8
+    }
+}
+
+/// The base colours of xterm, used to find the closest base colour.
+const BASE_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The intensity of each channel of the 6x6x6 colour cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+const fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASE_PALETTE[index as usize],
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// The closest colour in the colour cube or the grayscale ramp. The base
+/// colours are skipped as terminals change them with their theme.
+fn nearest_indexed(red: u8, green: u8, blue: u8) -> u8 {
+    let cube_level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&level| (CUBE_LEVELS[level] as i32 - channel as i32).abs())
+            .unwrap_or(0) as u8
+    };
+    let cube = 16 + 36 * cube_level(red) + 6 * cube_level(green) + cube_level(blue);
+
+    let average = (red as usize + green as usize + blue as usize) / 3;
+    let gray = 232 + cmp::min(average.saturating_sub(3) / 10, 23) as u8;
+
+    cmp::min_by_key(cube, gray, |&index| {
+        distance((red, green, blue), indexed_to_rgb(index))
+    })
+}
+
+fn nearest_base(red: u8, green: u8, blue: u8) -> BaseColour {
+    BaseColour::ALL
+        .into_iter()
+        .min_by_key(|&base| distance((red, green, blue), BASE_PALETTE[base as usize]))
+        .unwrap_or(BaseColour::Black)
+}
+
+#[inline]
+fn distance(left: (u8, u8, u8), right: (u8, u8, u8)) -> u32 {
+    let square = |left: u8, right: u8| (left as i32 - right as i32).pow(2) as u32;
+    square(left.0, right.0) + square(left.1, right.1) + square(left.2, right.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BaseColour, Colour, ColourSupport};
+
+    #[test]
+    fn colours_are_downsampled_to_the_closest_supported_colour() {
+        let gray = Colour::rgb(100, 101, 99);
+        assert_eq!(gray.downsample(ColourSupport::TrueColour), gray);
+        assert_eq!(
+            gray.downsample(ColourSupport::Ansi256),
+            Colour::Indexed(241)
+        );
+        assert_eq!(
+            gray.downsample(ColourSupport::Ansi16),
+            Colour::Base(BaseColour::BrightBlack)
+        );
+        assert_eq!(
+            Colour::rgb(0, 0, 255).downsample(ColourSupport::Ansi256),
+            Colour::Indexed(21)
+        );
+        assert_eq!(
+            Colour::Indexed(21).downsample(ColourSupport::Ansi16),
+            Colour::Base(BaseColour::Blue)
+        );
+        assert_eq!(
+            Colour::Indexed(9).downsample(ColourSupport::Ansi16),
+            Colour::Base(BaseColour::BrightRed)
+        );
+        assert_eq!(
+            Colour::Base(BaseColour::Cyan).downsample(ColourSupport::Monochrome),
+            Colour::Default
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use super::Position;
+
+/// The shape of the terminal cursor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    Block,
+    Bar,
+    Underline,
+}
+
+impl Default for CursorShape {
+    fn default() -> Self {
+        Self::Block
+    }
+}
+
+/// The position and shape of the terminal cursor, see
+/// [`Component::cursor`](../trait.Component.html#method.cursor).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TerminalCursor {
+    pub position: Position,
+    pub shape: CursorShape,
+}
+
+impl TerminalCursor {
+    /// A block cursor at a position.
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            shape: CursorShape::Block,
+        }
+    }
+
+    pub fn shape(mut self, shape: CursorShape) -> Self {
+        self.shape = shape;
+        self
+    }
+}
@@ -0,0 +1,168 @@
+//! Geometry helpers for the euclid types used by Zi.
+//!
+//! `Rect`, `Position` and `Size` are re-exported from euclid, which already
+//! provides `Rect::union`, `Rect::contains`, `Rect::intersection`,
+//! `Size::clamp` and arithmetic between positions and sizes. The extension
+//! traits in this module add the operations on unsigned coordinates that
+//! components otherwise reimplement ad hoc.
+use std::cmp;
+
+use super::{Position, Rect, Size};
+
+/// Extension methods for [`Rect`](type.Rect.html).
+pub trait RectExt {
+    /// The overlap of two rectangles, or an empty rectangle if they don't
+    /// overlap.
+    fn intersect(&self, other: &Rect) -> Rect;
+
+    /// Shrinks the rectangle by `width` on the left and right, and by
+    /// `height` on the top and bottom. The size saturates at zero.
+    fn inset(&self, width: usize, height: usize) -> Rect;
+
+    /// Splits the rectangle into a left part `width` columns wide and a right
+    /// part with the remaining columns.
+    fn split_horizontally(&self, width: usize) -> (Rect, Rect);
+
+    /// Splits the rectangle into a top part `height` rows high and a bottom
+    /// part with the remaining rows.
+    fn split_vertically(&self, height: usize) -> (Rect, Rect);
+}
+
+impl RectExt for Rect {
+    fn intersect(&self, other: &Rect) -> Rect {
+        self.intersection(other).unwrap_or_else(|| {
+            Rect::new(
+                Position::new(
+                    cmp::max(self.origin.x, other.origin.x),
+                    cmp::max(self.origin.y, other.origin.y),
+                ),
+                Size::zero(),
+            )
+        })
+    }
+
+    fn inset(&self, width: usize, height: usize) -> Rect {
+        let width = cmp::min(width, self.size.width / 2);
+        let height = cmp::min(height, self.size.height / 2);
+        Rect::new(
+            Position::new(self.origin.x + width, self.origin.y + height),
+            Size::new(self.size.width - 2 * width, self.size.height - 2 * height),
+        )
+    }
+
+    fn split_horizontally(&self, width: usize) -> (Rect, Rect) {
+        let width = cmp::min(width, self.size.width);
+        (
+            Rect::new(self.origin, Size::new(width, self.size.height)),
+            Rect::new(
+                Position::new(self.origin.x + width, self.origin.y),
+                Size::new(self.size.width - width, self.size.height),
+            ),
+        )
+    }
+
+    fn split_vertically(&self, height: usize) -> (Rect, Rect) {
+        let height = cmp::min(height, self.size.height);
+        (
+            Rect::new(self.origin, Size::new(self.size.width, height)),
+            Rect::new(
+                Position::new(self.origin.x, self.origin.y + height),
+                Size::new(self.size.width, self.size.height - height),
+            ),
+        )
+    }
+}
+
+/// Extension methods for [`Position`](type.Position.html).
+pub trait PositionExt {
+    /// Moves the position by a signed offset, saturating at zero.
+    fn offset(&self, dx: isize, dy: isize) -> Position;
+
+    /// Subtracts the coordinates of another position, saturating at zero.
+    /// E.g. converts a position on the screen to one relative to the origin
+    /// of a frame.
+    fn saturating_sub(&self, other: Position) -> Position;
+}
+
+impl PositionExt for Position {
+    fn offset(&self, dx: isize, dy: isize) -> Position {
+        Position::new(offset(self.x, dx), offset(self.y, dy))
+    }
+
+    fn saturating_sub(&self, other: Position) -> Position {
+        Position::new(
+            self.x.saturating_sub(other.x),
+            self.y.saturating_sub(other.y),
+        )
+    }
+}
+
+/// Extension methods for [`Size`](type.Size.html).
+pub trait SizeExt {
+    /// Grows or shrinks the size by a signed amount, saturating at zero.
+    fn offset(&self, dwidth: isize, dheight: isize) -> Size;
+
+    /// Subtracts another size, saturating at zero.
+    fn saturating_sub(&self, other: Size) -> Size;
+}
+
+impl SizeExt for Size {
+    fn offset(&self, dwidth: isize, dheight: isize) -> Size {
+        Size::new(offset(self.width, dwidth), offset(self.height, dheight))
+    }
+
+    fn saturating_sub(&self, other: Size) -> Size {
+        Size::new(
+            self.width.saturating_sub(other.width),
+            self.height.saturating_sub(other.height),
+        )
+    }
+}
+
+fn offset(value: usize, delta: isize) -> usize {
+    if delta < 0 {
+        value.saturating_sub(delta.unsigned_abs())
+    } else {
+        value.saturating_add(delta as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: usize, y: usize, width: usize, height: usize) -> Rect {
+        Rect::new(Position::new(x, y), Size::new(width, height))
+    }
+
+    #[test]
+    fn rect_operations() {
+        let frame = rect(2, 1, 10, 6);
+        assert_eq!(frame.intersect(&rect(8, 4, 10, 10)), rect(8, 4, 4, 3));
+        assert_eq!(frame.intersect(&rect(20, 0, 1, 1)).size, Size::zero());
+        assert_eq!(frame.inset(1, 2), rect(3, 3, 8, 2));
+        assert_eq!(frame.inset(6, 6), rect(7, 4, 0, 0));
+        assert_eq!(
+            frame.split_horizontally(3),
+            (rect(2, 1, 3, 6), rect(5, 1, 7, 6))
+        );
+        assert_eq!(
+            frame.split_vertically(10),
+            (rect(2, 1, 10, 6), rect(2, 7, 10, 0))
+        );
+    }
+
+    #[test]
+    fn position_and_size_arithmetic() {
+        assert_eq!(Position::new(3, 1).offset(-5, 2), Position::new(0, 3));
+        assert_eq!(
+            Position::new(3, 1).saturating_sub(Position::new(1, 2)),
+            Position::new(2, 0)
+        );
+        assert_eq!(Size::new(3, 1).offset(1, -2), Size::new(4, 0));
+        assert_eq!(
+            Size::new(3, 4).saturating_sub(Size::new(1, 5)),
+            Size::new(2, 0)
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::{cmp, fmt, sync::Arc};
+
+use super::{Colour, Position, Rect, Size};
+
+/// An image made of RGBA pixels, 4 bytes per pixel in row-major order.
+///
+/// Cloning an image is cheap as the pixels are shared. Images are drawn on a
+/// [`Canvas`](struct.Canvas.html) with
+/// [`draw_image`](struct.Canvas.html#method.draw_image).
+#[derive(Clone)]
+pub struct Image {
+    size: Size,
+    pixels: Arc<[u8]>,
+}
+
+impl Image {
+    /// Creates an image from RGBA pixels.
+    ///
+    /// Panics if the number of bytes doesn't match the size of the image.
+    pub fn new(size: Size, rgba: impl Into<Vec<u8>>) -> Self {
+        let rgba = rgba.into();
+        assert_eq!(
+            rgba.len(),
+            size.area() * 4,
+            "expected 4 bytes per pixel for an image of size {}x{}",
+            size.width,
+            size.height
+        );
+        Self {
+            size,
+            pixels: rgba.into(),
+        }
+    }
+
+    /// Creates an opaque image with the colour of each pixel given by a
+    /// function. The default colour is drawn as black.
+    pub fn from_fn(size: Size, mut colour_at: impl FnMut(Position) -> Colour) -> Self {
+        let mut rgba = Vec::with_capacity(size.area() * 4);
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let (red, green, blue) =
+                    colour_at(Position::new(x, y)).to_rgb().unwrap_or_default();
+                rgba.extend_from_slice(&[red, green, blue, 255]);
+            }
+        }
+        Self::new(size, rgba)
+    }
+
+    /// The size of the image in pixels.
+    #[inline]
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// The RGBA bytes of the image.
+    #[inline]
+    pub fn rgba(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The RGBA value of a pixel.
+    #[inline]
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * self.size.width + x) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[offset..offset + 4]);
+        pixel
+    }
+
+    /// Scales the image to a new size, sampling the nearest pixel.
+    pub fn resized(&self, size: Size) -> Self {
+        if size == self.size {
+            return self.clone();
+        }
+        let mut rgba = Vec::with_capacity(size.area() * 4);
+        if !self.size.is_empty() {
+            for y in 0..size.height {
+                let source_y = cmp::min(y * self.size.height / size.height, self.size.height - 1);
+                for x in 0..size.width {
+                    let source_x = cmp::min(x * self.size.width / size.width, self.size.width - 1);
+                    rgba.extend_from_slice(&self.pixel(source_x, source_y));
+                }
+            }
+        } else {
+            rgba.resize(size.area() * 4, 0);
+        }
+        Self::new(size, rgba)
+    }
+
+    /// The colour of a pixel, blended onto black according to its alpha.
+    #[inline]
+    pub(crate) fn blended_colour(&self, x: usize, y: usize) -> Colour {
+        let [red, green, blue, alpha] = self.pixel(x, y);
+        let blend = |channel: u8| (channel as u16 * alpha as u16 / 255) as u8;
+        Colour::rgb(blend(red), blend(green), blend(blue))
+    }
+}
+
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && (Arc::ptr_eq(&self.pixels, &other.pixels) || self.pixels == other.pixels)
+    }
+}
+
+impl Eq for Image {}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Image({}x{})", self.size.width, self.size.height)
+    }
+}
+
+/// An image drawn over a region of cells of a canvas. The image is scaled to
+/// fill the region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImagePlacement {
+    pub region: Rect,
+    pub image: Image,
+}
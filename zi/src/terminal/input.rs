@@ -1,7 +1,159 @@
+use std::{fmt, ops::Deref, time::Instant};
+
+use super::Position;
+
 /// Input event
-#[derive(Debug)]
+///
+/// Events carry a monotonic timestamp of when they happened, as reported by
+/// the backend. Components can use it for timing sensitive behaviour, e.g.
+/// debouncing, without measuring the time they are handled at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Event {
-    KeyPress(Key),
+    KeyPress(Key, Instant),
+    /// A key held down was repeated. Backends which can't tell repeats apart
+    /// from presses send them as `KeyPress`.
+    KeyRepeat(Key, Instant),
+    /// A key was released. Only sent by backends which support it, e.g. with
+    /// the kitty keyboard protocol, see
+    /// [`Component::key_release`](../trait.Component.html#method.key_release).
+    KeyRelease(Key, Instant),
+    Mouse(MouseEvent),
+}
+
+impl Event {
+    /// A key press which happened now.
+    pub fn key_press(key: Key) -> Self {
+        Self::KeyPress(key, Instant::now())
+    }
+
+    /// When the event happened.
+    pub fn timestamp(&self) -> Instant {
+        match self {
+            Self::KeyPress(_, timestamp)
+            | Self::KeyRepeat(_, timestamp)
+            | Self::KeyRelease(_, timestamp) => *timestamp,
+            Self::Mouse(event) => event.timestamp,
+        }
+    }
+
+    /// The same event, happening at another time, e.g. when it's replayed.
+    pub fn with_timestamp(self, timestamp: Instant) -> Self {
+        match self {
+            Self::KeyPress(key, _) => Self::KeyPress(key, timestamp),
+            Self::KeyRepeat(key, _) => Self::KeyRepeat(key, timestamp),
+            Self::KeyRelease(key, _) => Self::KeyRelease(key, timestamp),
+            Self::Mouse(event) => Self::Mouse(MouseEvent { timestamp, ..event }),
+        }
+    }
+}
+
+/// Mouse input, the position is relative to the top left corner of the
+/// screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    pub position: Position,
+    /// The modifier keys held during the event.
+    pub modifiers: Modifiers,
+    /// When the event happened.
+    pub timestamp: Instant,
+}
+
+impl MouseEvent {
+    /// The key dispatched to key bindings for this event, e.g. a
+    /// [`Key::Click`](enum.Key.html#variant.Click) when a button is pressed.
+    /// Returns `None` when the mouse moved without any button held.
+    pub fn key(&self) -> Option<Key> {
+        let modifiers = self.modifiers;
+        Some(match self.kind {
+            MouseEventKind::Moved => return None,
+            MouseEventKind::Down(button) => Key::Click(button, modifiers),
+            MouseEventKind::Up(button) => Key::Release(button, modifiers),
+            MouseEventKind::Drag(button) => Key::Drag(button, modifiers),
+            MouseEventKind::ScrollUp => Key::ScrollUp(modifiers),
+            MouseEventKind::ScrollDown => Key::ScrollDown(modifiers),
+        })
+    }
+}
+
+/// The kind of a mouse event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEventKind {
+    /// The mouse moved without any button pressed.
+    Moved,
+    /// A mouse button was pressed.
+    Down(MouseButton),
+    /// A mouse button was released.
+    Up(MouseButton),
+    /// The mouse moved while a button was held down.
+    Drag(MouseButton),
+    /// The mouse wheel was scrolled up, away from the user.
+    ScrollUp,
+    /// The mouse wheel was scrolled down, towards the user.
+    ScrollDown,
+}
+
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// The modifier keys held during a mouse event, e.g. for `Ctrl+Click`.
+#[derive(Debug, Default, Clone, Copy, PartialOrd, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    pub const CTRL: Self = Self {
+        ctrl: true,
+        alt: false,
+        shift: false,
+    };
+    pub const ALT: Self = Self {
+        ctrl: false,
+        alt: true,
+        shift: false,
+    };
+    pub const SHIFT: Self = Self {
+        ctrl: false,
+        alt: false,
+        shift: true,
+    };
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self {
+            ctrl: self.ctrl || other.ctrl,
+            alt: self.alt || other.alt,
+            shift: self.shift || other.shift,
+        }
+    }
+}
+
+/// Sent to a component when the mouse enters or leaves its frame.
+///
+/// A component is hovered if the mouse is inside its frame and it is not
+/// covered by a higher layer of a stack. The ancestors of a hovered component
+/// are hovered too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HoverEvent {
+    /// The mouse entered the frame of the component at the given position.
+    Enter(Position),
+    /// The mouse left the frame of the component.
+    Leave,
 }
 
 /// Keyboard input. It aims to match what a terminal supports.
@@ -47,4 +199,105 @@ pub enum Key {
     Null,
     /// Esc key.
     Esc,
+    /// A mouse click with the modifier keys held, e.g. `Ctrl+Click`.
+    ///
+    /// Clicks are dispatched to key bindings like any other key, s.t. they can
+    /// be mixed with keys in a sequence. The position of the click is passed
+    /// to command functions which take a `Position`.
+    Click(MouseButton, Modifiers),
+    /// A mouse button was released. Like clicks, releases and the other mouse
+    /// keys below are dispatched to key bindings with their position.
+    Release(MouseButton, Modifiers),
+    /// The mouse moved while a button was held down.
+    Drag(MouseButton, Modifiers),
+    /// The mouse wheel was scrolled up.
+    ScrollUp(Modifiers),
+    /// The mouse wheel was scrolled down.
+    ScrollDown(Modifiers),
+    /// Text input of an extended grapheme cluster made of more than one
+    /// `char`, e.g. an emoji with a skin tone modifier or a letter followed by
+    /// combining marks. Single `char` input is sent as a `Key::Char`.
+    ///
+    /// Like `Key::Char`, graphemes are matched by
+    /// [`AnyCharacter`](../struct.AnyCharacter.html) bindings.
+    Grapheme(Grapheme),
+}
+
+impl Key {
+    /// Returns `true` if the key was sent by the mouse, e.g. a click.
+    pub fn is_mouse(&self) -> bool {
+        matches!(
+            self,
+            Self::Click(..)
+                | Self::Release(..)
+                | Self::Drag(..)
+                | Self::ScrollUp(_)
+                | Self::ScrollDown(_)
+        )
+    }
+}
+
+/// An extended grapheme cluster typed by the user, see
+/// [`Key::Grapheme`](enum.Key.html#variant.Grapheme).
+///
+/// The grapheme is stored inline, s.t. keys can be copied. It holds up to
+/// [`Grapheme::CAPACITY`](#associatedconstant.CAPACITY) bytes, which fits
+/// letters with combining marks, flags and emoji with a skin tone modifier,
+/// but not the longest emoji sequences. Backends send the `char`s of longer
+/// graphemes individually.
+#[derive(Clone, Copy, PartialOrd, PartialEq, Eq, Hash)]
+pub struct Grapheme {
+    len: u8,
+    bytes: [u8; Self::CAPACITY],
+}
+
+impl Grapheme {
+    /// The maximum length of a grapheme in bytes.
+    pub const CAPACITY: usize = 15;
+
+    /// Creates a grapheme from a string, returning `None` if it is longer
+    /// than [`CAPACITY`](#associatedconstant.CAPACITY) bytes. The string is
+    /// not checked to be a single grapheme cluster.
+    pub fn new(grapheme: &str) -> Option<Self> {
+        if grapheme.len() > Self::CAPACITY {
+            return None;
+        }
+        let mut bytes = [0; Self::CAPACITY];
+        bytes[..grapheme.len()].copy_from_slice(grapheme.as_bytes());
+        Some(Self {
+            len: grapheme.len() as u8,
+            bytes,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize])
+            .expect("a grapheme is created from a valid string")
+    }
+}
+
+impl From<char> for Grapheme {
+    fn from(character: char) -> Self {
+        Self::new(character.encode_utf8(&mut [0; 4])).expect("a char fits in a grapheme")
+    }
+}
+
+impl Deref for Grapheme {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for Grapheme {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), formatter)
+    }
+}
+
+impl fmt::Display for Grapheme {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
 }
@@ -4,10 +4,16 @@
 //! via their child components and their descendants. At the bottom of the
 //! component hierarchy, low level components would draw directly on a canvas.
 
-pub use canvas::{
-    Background, Canvas, Colour, Foreground, GraphemeCluster, SquarePixelGrid, Style, Textel,
+pub use canvas::{Background, Canvas, Foreground, GraphemeCluster, SquarePixelGrid, Style, Textel};
+pub use capabilities::{BackendCapabilities, ColourSupport, GraphicsProtocol};
+pub use colour::{BaseColour, Colour};
+pub use cursor::{CursorShape, TerminalCursor};
+pub use geometry::{PositionExt, RectExt, SizeExt};
+pub use image::{Image, ImagePlacement};
+pub use input::{
+    Event, Grapheme, HoverEvent, Key, Modifiers, MouseButton, MouseEvent, MouseEventKind,
 };
-pub use input::{Event, Key};
+pub use scroll_buffer::{ScrollBuffer, StyledLine};
 
 /// A 2D rectangle with usize coordinates. Re-exported from
 /// [euclid](https://docs.rs/euclid).
@@ -22,4 +28,10 @@ pub type Position = euclid::default::Point2D<usize>;
 pub type Size = euclid::default::Size2D<usize>;
 
 pub(crate) mod canvas;
+pub(crate) mod capabilities;
+pub(crate) mod colour;
+pub(crate) mod cursor;
+pub(crate) mod geometry;
+pub(crate) mod image;
 pub(crate) mod input;
+pub(crate) mod scroll_buffer;
@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+
+use super::{Canvas, Style};
+
+/// A line of text made of spans with different styles.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyledLine {
+    spans: Vec<(Style, String)>,
+}
+
+impl StyledLine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a span of text, which shouldn't contain newlines.
+    pub fn push(&mut self, style: Style, text: impl Into<String>) {
+        let text = text.into();
+        match self.spans.last_mut() {
+            Some((last_style, last_text)) if *last_style == style => last_text.push_str(&text),
+            _ => self.spans.push((style, text)),
+        }
+    }
+
+    pub fn spans(&self) -> &[(Style, String)] {
+        &self.spans
+    }
+
+    /// The text of the line without styles.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|(_, text)| text.as_str()).collect()
+    }
+
+    /// Draws the line on a row of the canvas, starting at column `x`.
+    pub fn draw(&self, canvas: &mut Canvas, x: usize, y: usize) {
+        let mut x = x;
+        for (style, text) in self.spans.iter() {
+            if x >= canvas.size().width {
+                break;
+            }
+            x += canvas.draw_str(x, y, *style, text);
+        }
+    }
+}
+
+impl<TextT: Into<String>> From<(Style, TextT)> for StyledLine {
+    fn from((style, text): (Style, TextT)) -> Self {
+        let mut line = Self::new();
+        line.push(style, text);
+        line
+    }
+}
+
+/// An append-only buffer of styled lines with bounded memory, e.g. the
+/// scrollback of a log viewer.
+///
+/// When the buffer is full, the oldest lines are evicted as new lines are
+/// added. Lines are indexed from the oldest line still in the buffer, the
+/// number of lines evicted so far can be used to keep positions stable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScrollBuffer {
+    lines: VecDeque<StyledLine>,
+    capacity: usize,
+    evicted: usize,
+    // Whether the last line was terminated by a newline, otherwise `write`
+    // continues it
+    terminated: bool,
+}
+
+impl ScrollBuffer {
+    /// Creates an empty buffer holding at most `capacity` lines.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity,
+            evicted: 0,
+            terminated: true,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the capacity, evicting the oldest lines if needed.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    /// The number of lines in the buffer.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The total number of lines evicted since the buffer was created.
+    pub fn evicted(&self) -> usize {
+        self.evicted
+    }
+
+    pub fn get(&self, index: usize) -> Option<&StyledLine> {
+        self.lines.get(index)
+    }
+
+    /// Iterates over the lines, oldest first.
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &StyledLine> + ExactSizeIterator {
+        self.lines.iter()
+    }
+
+    /// Appends a complete line.
+    pub fn push_line(&mut self, line: impl Into<StyledLine>) {
+        self.lines.push_back(line.into());
+        self.terminated = true;
+        self.evict();
+    }
+
+    /// Appends text like a terminal would, continuing the last line unless it
+    /// ended with a newline and starting a new line after every `\n`.
+    pub fn write(&mut self, style: Style, text: &str) {
+        for (index, segment) in text.split('\n').enumerate() {
+            let terminated = index > 0 || self.terminated;
+            if terminated {
+                self.lines.push_back(StyledLine::new());
+            }
+            if !segment.is_empty() {
+                self.lines
+                    .back_mut()
+                    .expect("a line was pushed above")
+                    .push(style, segment);
+            }
+        }
+        // A trailing newline ends the last line without starting a new one
+        if text.ends_with('\n') {
+            self.lines.pop_back();
+            self.terminated = true;
+        } else {
+            self.terminated = false;
+        }
+        self.evict();
+    }
+
+    pub fn clear(&mut self) {
+        self.evicted += self.lines.len();
+        self.lines.clear();
+        self.terminated = true;
+    }
+
+    /// Draws the lines starting at `offset` on the canvas, one per row.
+    pub fn draw(&self, canvas: &mut Canvas, offset: usize) {
+        let height = canvas.size().height;
+        for (y, line) in self.lines.iter().skip(offset).take(height).enumerate() {
+            line.draw(canvas, 0, y);
+        }
+    }
+
+    /// Draws the last lines that fit on the canvas, i.e. the tail of the
+    /// buffer.
+    pub fn draw_tail(&self, canvas: &mut Canvas) {
+        self.draw(canvas, self.tail_offset(canvas.size().height));
+    }
+
+    /// The offset of the first line shown when drawing the tail of the buffer
+    /// on `height` rows.
+    pub fn tail_offset(&self, height: usize) -> usize {
+        self.lines.len().saturating_sub(height)
+    }
+
+    fn evict(&mut self) {
+        while self.lines.len() > self.capacity {
+            self.lines.pop_front();
+            self.evicted += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Colour, Size};
+
+    fn texts(buffer: &ScrollBuffer) -> Vec<String> {
+        buffer.lines().map(StyledLine::text).collect()
+    }
+
+    #[test]
+    fn write_and_evict() {
+        let style = Style::normal(Colour::black(), Colour::white());
+        let mut buffer = ScrollBuffer::new(3);
+        buffer.write(style, "one\ntw");
+        buffer.write(style, "o\nthree\n");
+        assert_eq!(texts(&buffer), vec!["one", "two", "three"]);
+
+        buffer.push_line((style, "four"));
+        buffer.write(style, "five");
+        assert_eq!(texts(&buffer), vec!["three", "four", "five"]);
+        assert_eq!(buffer.evicted(), 2);
+        assert_eq!(buffer.get(2).unwrap().spans().len(), 1);
+
+        let mut canvas = Canvas::new(Size::new(5, 2));
+        buffer.draw_tail(&mut canvas);
+        let row = |canvas: &Canvas, y| -> String {
+            (0..4)
+                .map(|x| canvas.textel(x, y).as_ref().unwrap().grapheme.as_str())
+                .collect()
+        };
+        assert_eq!(row(&canvas, 0), "four");
+        assert_eq!(row(&canvas, 1), "five");
+    }
+}
@@ -0,0 +1,377 @@
+//! A headless harness for testing components without a backend.
+//!
+//! [`Harness`](struct.Harness.html) wraps an [`App`](../app/struct.App.html)
+//! with an in-memory message queue. Tests feed it scripted input events and
+//! messages, advance a simulated clock to drive ticks and timers, and inspect
+//! the resulting [`Canvas`](../struct.Canvas.html), e.g. as lines of text.
+//!
+//! Messages sent by components through their links are delivered after every
+//! step, so the harness is always settled when it's inspected. Tasks
+//! requested with
+//! [`ComponentLink::run_exclusive`](../struct.ComponentLink.html#method.run_exclusive)
+//! are run right away, as there is no terminal to suspend.
+//!
+//! ```
+//! # use zi::{components::text::{Text, TextProperties}, prelude::*, testing::Harness};
+//! let mut harness = Harness::new(
+//!     Size::new(12, 2),
+//!     Text::with(TextProperties::new().content("Hello, world")),
+//! );
+//! assert_eq!(harness.line(0), "Hello, world");
+//! assert_eq!(harness.text(), "Hello, world\n");
+//!
+//! harness.resize(Size::new(5, 1));
+//! assert_eq!(harness.text(), "Hello");
+//! ```
+use std::{
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    app::{App, ComponentMessage, MessageSender},
+    terminal::{Canvas, Event, Key, Size},
+    Component, ComponentHandle, Layout,
+};
+
+/// Drives an [`App`](../app/struct.App.html) in tests, see the
+/// [module documentation](index.html).
+pub struct Harness {
+    app: App,
+    receiver: mpsc::Receiver<ComponentMessage>,
+    /// The current time of the simulated clock.
+    now: Instant,
+}
+
+impl Harness {
+    /// Creates a harness rendering the `root` layout on a canvas of the given
+    /// size. Components are mounted when the harness is first drawn, e.g. by
+    /// inspecting it or sending input.
+    pub fn new(size: Size, root: Layout) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = App::new(QueueSender(sender), size, root);
+        let now = Instant::now();
+        app.set_now(now);
+        Self { app, receiver, now }
+    }
+
+    /// The app driven by the harness, e.g. to inspect a component with
+    /// [`App::with_component`](../app/struct.App.html#method.with_component).
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// The app driven by the harness, e.g. to set a theme or register a
+    /// service. Messages sent as a result are delivered on the next step.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// The current time of the simulated clock, which is shared with the app
+    /// and its components, see
+    /// [`ComponentLink::now`](../struct.ComponentLink.html#method.now). It
+    /// stands still, except when moved forward with
+    /// [`advance`](#method.advance).
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+
+    /// Delivers an input event, after drawing s.t. all components are
+    /// mounted and their bindings are up to date.
+    pub fn input(&mut self, event: Event) -> &mut Self {
+        self.draw();
+        self.app.handle_input(event);
+        self.settle();
+        self
+    }
+
+    /// Presses a key at the current time of the simulated clock.
+    pub fn key(&mut self, key: Key) -> &mut Self {
+        let event = Event::KeyPress(key, self.now());
+        self.input(event)
+    }
+
+    /// Presses keys one after the other.
+    pub fn keys(&mut self, keys: impl IntoIterator<Item = Key>) -> &mut Self {
+        for key in keys {
+            self.key(key);
+        }
+        self
+    }
+
+    /// Types text, pressing a `Key::Char` for each character.
+    pub fn type_str(&mut self, text: &str) -> &mut Self {
+        self.keys(text.chars().map(Key::Char))
+    }
+
+    /// Resizes the canvas, as if the terminal was resized.
+    pub fn resize(&mut self, size: Size) -> &mut Self {
+        self.app.handle_resize(size);
+        self.settle();
+        self
+    }
+
+    /// Sends a message to a mounted component, see
+    /// [`App::send_to`](../app/struct.App.html#method.send_to).
+    pub fn send_to<ComponentT: Component>(
+        &mut self,
+        handle: ComponentHandle<ComponentT>,
+        message: ComponentT::Message,
+    ) -> &mut Self {
+        self.app.send_to(handle, message);
+        self.settle();
+        self
+    }
+
+    /// Advances the simulated clock, ticking the tickable components once
+    /// with the whole duration and firing the timers which are due by then,
+    /// in the order of their deadlines. To tick once per frame, advance by
+    /// one frame at a time.
+    pub fn advance(&mut self, duration: Duration) -> &mut Self {
+        self.draw();
+        self.now += duration;
+        self.app.set_now(self.now);
+        if self.app.is_tickable() {
+            self.app.tick_elapsed(duration);
+        }
+        self.settle();
+        // Fire the timers at their deadlines, s.t. a repeating timer fires
+        // once for every period skipped
+        let mut previous = None;
+        while let Some(deadline) = self.app.next_timer() {
+            if deadline > self.now() || previous == Some(deadline) {
+                break;
+            }
+            self.app.fire_timers_at(deadline);
+            self.settle();
+            previous = Some(deadline);
+        }
+        self
+    }
+
+    /// Delivers the messages sent by components so far and runs their
+    /// exclusive tasks. Returns the number of messages delivered.
+    ///
+    /// This is done after every step, it's only needed after using
+    /// [`app_mut`](#method.app_mut) directly.
+    pub fn settle(&mut self) -> usize {
+        let mut num_messages = 0;
+        loop {
+            while let Ok(message) = self.receiver.try_recv() {
+                self.app.handle_message(message);
+                num_messages += 1;
+            }
+            match self.app.take_exclusive_task() {
+                Some(task) => {
+                    if let Some(message) = task.run() {
+                        self.app.handle_message(message);
+                        num_messages += 1;
+                    }
+                }
+                None => break,
+            }
+        }
+        num_messages
+    }
+
+    /// Waits up to `timeout` for a message sent from another thread, e.g. by
+    /// a spawned future, and delivers it along with any other pending ones.
+    /// Returns `false` if no message arrived in time.
+    pub fn wait_for_message(&mut self, timeout: Duration) -> bool {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(message) => {
+                self.app.handle_message(message);
+                self.settle();
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Draws the app and returns its canvas. Messages sent by components
+    /// while they are mounted are delivered and the app is redrawn if needed.
+    pub fn canvas(&mut self) -> &Canvas {
+        self.draw();
+        self.app.draw()
+    }
+
+    /// Returns the text on the canvas, one line per row with trailing spaces
+    /// removed. Unset cells are read as spaces.
+    pub fn text(&mut self) -> String {
+        let height = self.canvas().size().height;
+        (0..height)
+            .map(|y| self.line(y))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the text on row `y` of the canvas with trailing spaces
+    /// removed, see [`text`](#method.text).
+    pub fn line(&mut self, y: usize) -> String {
+        let canvas = self.canvas();
+        let mut line = String::new();
+        let mut covered = 0;
+        for x in 0..canvas.size().width {
+            let grapheme = canvas
+                .textel(x, y)
+                .as_ref()
+                .map(|textel| textel.grapheme.as_str())
+                .filter(|grapheme| !grapheme.is_empty());
+            match grapheme {
+                // The cells covered by a wide grapheme are unset
+                None if covered > 0 => {}
+                None => line.push(' '),
+                Some(grapheme) => {
+                    line.push_str(grapheme);
+                    covered = grapheme.width();
+                }
+            }
+            covered = covered.saturating_sub(1);
+        }
+        line.truncate(line.trim_end().len());
+        line
+    }
+
+    fn draw(&mut self) {
+        self.settle();
+        if self.app.poll_state().dirty() {
+            self.app.draw();
+            // Components may send messages as they are mounted
+            if self.settle() > 0 && self.app.poll_state().dirty() {
+                self.app.draw();
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct QueueSender(mpsc::Sender<ComponentMessage>);
+
+impl MessageSender for QueueSender {
+    fn send(&self, message: ComponentMessage) {
+        // The harness may be dropped before a background thread is done
+        let _ = self.0.send(message);
+    }
+
+    fn clone_box(&self) -> Box<dyn MessageSender> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        components::text::{Text, TextProperties},
+        prelude::*,
+        terminal::Rect,
+    };
+
+    struct Stopwatch {
+        typed: String,
+        seconds: usize,
+    }
+
+    enum StopwatchMessage {
+        Type(char),
+        Second,
+    }
+
+    impl Component for Stopwatch {
+        type Message = StopwatchMessage;
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            link.send_every(Duration::from_secs(1), || StopwatchMessage::Second);
+            Self {
+                typed: String::new(),
+                seconds: 0,
+            }
+        }
+
+        fn view(&self) -> Layout {
+            Text::with(TextProperties::new().content(format!("{} {}", self.typed, self.seconds)))
+        }
+
+        fn update(&mut self, message: Self::Message) -> ShouldRender {
+            match message {
+                StopwatchMessage::Type(character) => self.typed.push(character),
+                StopwatchMessage::Second => self.seconds += 1,
+            }
+            ShouldRender::Yes
+        }
+
+        fn bindings(&self, bindings: &mut Bindings<Self>) {
+            bindings.set_focus(true);
+            if !bindings.is_empty() {
+                return;
+            }
+            bindings.add("type", AnyCharacter, |keys: &[Key]| match *keys {
+                [Key::Char(character)] => Some(StopwatchMessage::Type(character)),
+                _ => None,
+            });
+        }
+    }
+
+    #[test]
+    fn harness_scripts_input_and_time() {
+        let mut harness = Harness::new(Size::new(8, 2), Stopwatch::with(()));
+        assert_eq!(harness.text(), " 0\n");
+
+        harness.type_str("hey").advance(Duration::from_millis(2500));
+        assert_eq!(harness.line(0), "hey 2");
+
+        harness.resize(Size::new(4, 1));
+        assert_eq!(harness.text(), "hey");
+    }
+
+    struct Elapsed {
+        link: ComponentLink<Self>,
+        since: Instant,
+        fired: bool,
+    }
+
+    impl Component for Elapsed {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+            link.send_after(Duration::from_millis(250), ());
+            Self {
+                since: link.now(),
+                link,
+                fired: false,
+            }
+        }
+
+        fn view(&self) -> Layout {
+            let elapsed = self.link.now().duration_since(self.since);
+            Text::with(TextProperties::new().content(format!(
+                "{} {}",
+                elapsed.as_millis(),
+                self.fired
+            )))
+        }
+
+        fn update(&mut self, _: Self::Message) -> ShouldRender {
+            self.fired = true;
+            ShouldRender::Yes
+        }
+    }
+
+    #[test]
+    fn simulated_clock_only_moves_when_advanced() {
+        let mut harness = Harness::new(Size::new(10, 1), Elapsed::with(()));
+        let start = harness.now();
+        assert_eq!(harness.line(0), "0 false");
+
+        std::thread::sleep(Duration::from_millis(10));
+        harness.advance(Duration::from_millis(200));
+        assert_eq!(harness.now(), start + Duration::from_millis(200));
+        assert_eq!(harness.line(0), "0 false");
+
+        harness.advance(Duration::from_millis(50));
+        assert_eq!(harness.line(0), "250 true");
+    }
+}
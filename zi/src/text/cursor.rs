@@ -144,6 +144,18 @@ impl Cursor {
         self.visual_horizontal_offset = None;
     }
 
+    /// Moves the cursor to the grapheme containing `char_index`.
+    pub fn move_to<'a>(&mut self, text: &impl TextStorage<'a>, char_index: CharIndex) {
+        let grapheme_end = text.next_grapheme_boundary(char_index);
+        let grapheme_start = text.prev_grapheme_boundary(grapheme_end);
+        self.range = if grapheme_start != grapheme_end {
+            grapheme_start..grapheme_end
+        } else {
+            CharIndex(0)..CharIndex(1)
+        };
+        self.visual_horizontal_offset = None;
+    }
+
     pub fn insert_char<'a>(&mut self, text: &mut impl TextStorageMut<'a>, character: char) {
         text.insert_char(self.range.start, character);
         ensure_trailing_newline_with_content(text);
@@ -0,0 +1,78 @@
+//! Themes, named styles shared by all components.
+//!
+//! A [`Theme`](struct.Theme.html) maps the names of style slots, e.g.
+//! `"text"` or `"selection"`, to a [`Style`](../struct.Style.html). The app
+//! holds the current theme, which components read with
+//! [`ComponentLink::theme`](../struct.ComponentLink.html#method.theme)
+//! instead of each defining their own colours. The theme is swapped at
+//! runtime with [`App::set_theme`](../app/struct.App.html#method.set_theme)
+//! or [`ComponentLink::set_theme`](../struct.ComponentLink.html#method.set_theme),
+//! which re-renders all components.
+//!
+//! ```
+//! # use zi::{theme::Theme, Colour, Style};
+//! const DARK0_SOFT: Colour = Colour::rgb(50, 48, 47);
+//! const LIGHT2: Colour = Colour::rgb(213, 196, 161);
+//! const BRIGHT_BLUE: Colour = Colour::rgb(131, 165, 152);
+//!
+//! let theme = Theme::new("gruvbox-dark")
+//!     .style("text", Style::normal(DARK0_SOFT, LIGHT2))
+//!     .style("selection", Style::bold(BRIGHT_BLUE, DARK0_SOFT));
+//!
+//! assert_eq!(theme.get("text"), Some(Style::normal(DARK0_SOFT, LIGHT2)));
+//! assert_eq!(theme.get_or("border", Style::default()), Style::default());
+//! ```
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::Style;
+
+/// A named set of styles, keyed by the names of their slots.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Theme {
+    name: Cow<'static, str>,
+    styles: HashMap<Cow<'static, str>, Style>,
+}
+
+impl Theme {
+    /// Creates a theme without any styles.
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            styles: HashMap::new(),
+        }
+    }
+
+    /// Sets the style of a slot.
+    pub fn style(mut self, slot: impl Into<Cow<'static, str>>, style: Style) -> Self {
+        self.set_style(slot, style);
+        self
+    }
+
+    /// Sets the style of a slot.
+    pub fn set_style(&mut self, slot: impl Into<Cow<'static, str>>, style: Style) {
+        self.styles.insert(slot.into(), style);
+    }
+
+    /// Returns the name of the theme.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the style of a slot, if the theme has one.
+    pub fn get(&self, slot: &str) -> Option<Style> {
+        self.styles.get(slot).copied()
+    }
+
+    /// Returns the style of a slot or `fallback` if the theme doesn't have
+    /// one, e.g. the default style of a component.
+    pub fn get_or(&self, slot: &str, fallback: Style) -> Style {
+        self.get(slot).unwrap_or(fallback)
+    }
+
+    /// Returns the names of the slots and their styles, in arbitrary order.
+    pub fn styles(&self) -> impl Iterator<Item = (&str, Style)> {
+        self.styles
+            .iter()
+            .map(|(slot, style)| (slot.as_ref(), *style))
+    }
+}
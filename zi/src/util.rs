@@ -0,0 +1,232 @@
+//! Utilities for implementing components.
+//!
+//! # Undo and redo
+//!
+//! [`History`](struct.History.html) is a bounded undo stack for the state of
+//! a component. The recommended pattern is to keep the editable state in a
+//! (cheap to clone) value, record the state before every change and have
+//! dedicated `Undo` and `Redo` messages:
+//!
+//! ```
+//! # use ropey::Rope;
+//! # use zi::{prelude::*, util::History};
+//! struct Editor {
+//!     content: Rope,
+//!     history: History<Rope>,
+//! }
+//!
+//! enum Message {
+//!     Insert(char),
+//!     Undo,
+//!     Redo,
+//! }
+//!
+//! impl Component for Editor {
+//!     type Message = Message;
+//!     type Properties = ();
+//!
+//!     fn create(_: Self::Properties, _: Rect, _: ComponentLink<Self>) -> Self {
+//!         Self {
+//!             content: Rope::new(),
+//!             history: History::new(100),
+//!         }
+//!     }
+//!
+//!     fn update(&mut self, message: Self::Message) -> ShouldRender {
+//!         match message {
+//!             Message::Insert(character) => {
+//!                 // Typing in quick succession is undone in one step
+//!                 self.history.record_coalesced(self.content.clone(), "insert");
+//!                 let end = self.content.len_chars();
+//!                 self.content.insert_char(end, character);
+//!             }
+//!             Message::Undo => match self.history.undo(self.content.clone()) {
+//!                 Some(content) => self.content = content,
+//!                 None => return ShouldRender::No,
+//!             },
+//!             Message::Redo => match self.history.redo(self.content.clone()) {
+//!                 Some(content) => self.content = content,
+//!                 None => return ShouldRender::No,
+//!             },
+//!         }
+//!         ShouldRender::Yes
+//!     }
+//!
+//!     fn view(&self) -> Layout {
+//!         Canvas::new(Size::new(10, 1)).into()
+//!     }
+//!
+//!     fn bindings(&self, bindings: &mut Bindings<Self>) {
+//!         bindings.set_focus(true);
+//!         if !bindings.is_empty() {
+//!             return;
+//!         }
+//!         bindings.add("undo", [Key::Ctrl('z')], || Message::Undo);
+//!         bindings.add("redo", [Key::Ctrl('y')], || Message::Redo);
+//!     }
+//! }
+//! ```
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// A bounded undo stack of past states, with a redo stack.
+///
+/// Changes of the same kind made in quick succession, e.g. typing a word, can
+/// be coalesced s.t. they are undone in a single step. See the
+/// [module documentation](index.html) for an example.
+#[derive(Clone, Debug)]
+pub struct History<T> {
+    undo: VecDeque<T>,
+    redo: Vec<T>,
+    capacity: usize,
+    coalesce_within: Duration,
+    // The group and time of the last coalesced change
+    last_group: Option<(&'static str, Instant)>,
+}
+
+impl<T> History<T> {
+    /// Creates an empty history remembering at most `capacity` past states.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            capacity,
+            coalesce_within: Duration::from_secs(1),
+            last_group: None,
+        }
+    }
+
+    /// The maximum time between two changes of the same group for them to be
+    /// coalesced. Defaults to 1 second.
+    pub fn coalesce_within(mut self, duration: Duration) -> Self {
+        self.coalesce_within = duration;
+        self
+    }
+
+    /// Records the state before a change, it will be restored by the next
+    /// undo. Clears the redo stack.
+    pub fn record(&mut self, before: T) {
+        self.last_group = None;
+        self.push(before);
+    }
+
+    /// Records the state before a change, unless the previous change was
+    /// recorded with the same group less than `coalesce_within` ago. In that
+    /// case both changes are undone in one step.
+    pub fn record_coalesced(&mut self, before: T, group: &'static str) {
+        let now = Instant::now();
+        let coalesce = matches!(
+            self.last_group,
+            Some((last_group, last_time))
+                if last_group == group && now.duration_since(last_time) <= self.coalesce_within
+        );
+        self.last_group = Some((group, now));
+        if coalesce && !self.undo.is_empty() {
+            self.redo.clear();
+        } else {
+            self.push(before);
+        }
+    }
+
+    /// Stops coalescing with the previous change, e.g. when the cursor moves
+    /// between two insertions.
+    pub fn break_group(&mut self) {
+        self.last_group = None;
+    }
+
+    /// Restores the last recorded state. The current state is pushed onto the
+    /// redo stack. Returns `None` if there is nothing to undo.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        self.last_group = None;
+        Some(previous)
+    }
+
+    /// Restores the last undone state. The current state is pushed onto the
+    /// undo stack. Returns `None` if there is nothing to redo.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        self.last_group = None;
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_group = None;
+    }
+
+    fn push(&mut self, before: T) {
+        self.redo.clear();
+        if self.capacity == 0 {
+            return;
+        }
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(before);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history = History::new(2);
+        let mut state = 0;
+        for next in 1..=3 {
+            history.record(state);
+            state = next;
+        }
+
+        // Only the last two states are remembered
+        state = history.undo(state).unwrap();
+        assert_eq!(state, 2);
+        state = history.undo(state).unwrap();
+        assert_eq!(state, 1);
+        assert_eq!(history.undo(state), None);
+
+        state = history.redo(state).unwrap();
+        assert_eq!(state, 2);
+
+        // A new change discards the redo stack
+        history.record(state);
+        state = 10;
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(state), Some(2));
+    }
+
+    #[test]
+    fn coalesce_changes_in_the_same_group() {
+        let mut history = History::new(10).coalesce_within(Duration::from_secs(3600));
+        let mut state = String::new();
+        for character in "ab".chars() {
+            history.record_coalesced(state.clone(), "insert");
+            state.push(character);
+        }
+        history.record_coalesced(state.clone(), "delete");
+        state.pop();
+        history.break_group();
+        history.record_coalesced(state.clone(), "delete");
+        state.pop();
+
+        assert_eq!(history.undo(state.clone()).as_deref(), Some("a"));
+        assert_eq!(history.undo("a".into()).as_deref(), Some("ab"));
+        assert_eq!(history.undo("ab".into()).as_deref(), Some(""));
+        assert!(!history.can_undo());
+    }
+}
@@ -0,0 +1,525 @@
+//! Turns changes to files on disk into component messages.
+//!
+//! A [`FileWatcher`](struct.FileWatcher.html) polls a set of files and
+//! directories on a background thread and sends a message to a component
+//! whenever some of them are created, modified or removed. It is useful for
+//! components that show the contents of the filesystem, e.g. file browsers or
+//! log viewers, to refresh automatically.
+//!
+//! Watchers can also hot reload a theme or keymap overrides from config files
+//! with [`FileWatcher::reload_theme`](struct.FileWatcher.html#method.reload_theme)
+//! and [`FileWatcher::reload_keymap`](struct.FileWatcher.html#method.reload_keymap),
+//! s.t. users can iterate on their colours and bindings without restarting
+//! the app.
+//!
+//! This module requires the `fs-watch` feature.
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use zi::{prelude::*, watch::{FileChange, FileWatcher, WatchHandle}};
+//! struct LogViewer {
+//!     watch: WatchHandle,
+//! }
+//!
+//! enum Message {
+//!     Changed(Vec<FileChange>),
+//! }
+//!
+//! impl Component for LogViewer {
+//!     type Message = Message;
+//!     type Properties = ();
+//!
+//!     fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+//!         let watch = FileWatcher::new(["/var/log/syslog"])
+//!             .interval(Duration::from_secs(1))
+//!             .watch(&link, Message::Changed);
+//!         Self { watch }
+//!     }
+//!
+//!     fn update(&mut self, message: Self::Message) -> ShouldRender {
+//!         match message {
+//!             // Reload the changed files
+//!             Message::Changed(_changes) => ShouldRender::Yes,
+//!         }
+//!     }
+//!
+//!     fn view(&self) -> Layout {
+//!         Canvas::new(Size::new(10, 1)).into()
+//!     }
+//! }
+//! ```
+//!
+//! The root component of an app is a good place to hot reload its config.
+//! Config files are parsed by the app, e.g. keymap overrides from TOML with
+//! the `serde` feature:
+//!
+//! ```no_run
+//! # use zi::{prelude::*, watch::{FileWatcher, WatchHandle}, KeymapOverrides};
+//! # fn parse_toml(_: &str) -> Result<KeymapOverrides, std::io::Error> { unimplemented!() }
+//! struct Root {
+//!     keymap: WatchHandle,
+//! }
+//!
+//! impl Component for Root {
+//!     type Message = ();
+//!     type Properties = ();
+//!
+//!     fn create(_: Self::Properties, _: Rect, link: ComponentLink<Self>) -> Self {
+//!         // e.g. `|text| toml::from_str(text)`
+//!         let keymap = FileWatcher::new(["keymap.toml"]).reload_keymap(&link, parse_toml);
+//!         Self { keymap }
+//!     }
+//!
+//!     fn view(&self) -> Layout {
+//!         Canvas::new(Size::new(10, 1)).into()
+//!     }
+//! }
+//! ```
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{theme::Theme, Component, ComponentLink, KeymapOverrides};
+
+/// A change to a watched path.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FileChange {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+}
+
+impl FileChange {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Created(path) | Self::Modified(path) | Self::Removed(path) => path,
+        }
+    }
+}
+
+/// Watches a set of files and directories for changes.
+///
+/// Directories are watched recursively. Paths don't have to exist when the
+/// watcher starts, they are reported as created when they appear.
+#[derive(Clone, Debug)]
+pub struct FileWatcher {
+    paths: Vec<PathBuf>,
+    interval: Duration,
+}
+
+impl FileWatcher {
+    pub fn new(paths: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+            interval: Duration::from_millis(500),
+        }
+    }
+
+    /// How often the paths are checked for changes. Defaults to 500ms.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Starts watching on a background thread. Every time some of the watched
+    /// paths change, the changes are passed to `on_change` and the returned
+    /// message is sent to the component.
+    ///
+    /// The watcher stops when the returned handle is dropped, store it in the
+    /// component's state.
+    pub fn watch<ComponentT: Component>(
+        self,
+        link: &ComponentLink<ComponentT>,
+        on_change: impl Fn(Vec<FileChange>) -> ComponentT::Message + Send + 'static,
+    ) -> WatchHandle {
+        let snapshot = Snapshot::new(&self.paths);
+        self.spawn(link, snapshot, move |link, changes| {
+            link.send(on_change(changes))
+        })
+    }
+
+    /// Loads a theme from the watched files and switches the app to it with
+    /// [`ComponentLink::set_theme`](../struct.ComponentLink.html#method.set_theme),
+    /// right away and every time the files change.
+    ///
+    /// The files are parsed with `parse` in the order they were given, the
+    /// styles of later files override those of earlier ones, e.g. a user's
+    /// tweaks to a base theme. Files which don't exist are skipped and the
+    /// theme is kept if none of them do.
+    ///
+    /// Errors reading or parsing a file are reported with
+    /// [`ComponentLink::report_error`](../struct.ComponentLink.html#method.report_error)
+    /// and the current theme is kept until the files are fixed.
+    pub fn reload_theme<ComponentT: Component, ErrorT>(
+        self,
+        link: &ComponentLink<ComponentT>,
+        parse: impl Fn(&str) -> Result<Theme, ErrorT> + Send + 'static,
+    ) -> WatchHandle
+    where
+        ErrorT: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.reload(link, parse, |link, themes| {
+            let theme = themes.into_iter().reduce(|mut theme, overrides| {
+                for (slot, style) in overrides.styles() {
+                    theme.set_style(slot.to_owned(), style);
+                }
+                theme
+            });
+            if let Some(theme) = theme {
+                link.set_theme(theme);
+            }
+        })
+    }
+
+    /// Loads keymap overrides from the watched files and sets them with
+    /// [`ComponentLink::set_keymap_overrides`](../struct.ComponentLink.html#method.set_keymap_overrides),
+    /// right away and every time the files change.
+    ///
+    /// The files are parsed with `parse` in the order they were given, the
+    /// overrides of later files take precedence, see
+    /// [`KeymapOverrides::merge`](../struct.KeymapOverrides.html#method.merge).
+    /// Files which don't exist are skipped, if none of them do the default
+    /// bindings are restored.
+    ///
+    /// Errors reading or parsing a file are reported with
+    /// [`ComponentLink::report_error`](../struct.ComponentLink.html#method.report_error)
+    /// and the current overrides are kept until the files are fixed.
+    pub fn reload_keymap<ComponentT: Component, ErrorT>(
+        self,
+        link: &ComponentLink<ComponentT>,
+        parse: impl Fn(&str) -> Result<KeymapOverrides, ErrorT> + Send + 'static,
+    ) -> WatchHandle
+    where
+        ErrorT: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.reload(link, parse, |link, keymaps| {
+            let mut overrides = KeymapOverrides::new();
+            for keymap in keymaps {
+                overrides.merge(keymap);
+            }
+            link.set_keymap_overrides(overrides);
+        })
+    }
+
+    /// Parses the watched files and passes the results to `apply` when
+    /// called and every time the files change.
+    fn reload<ComponentT: Component, ConfigT, ErrorT>(
+        self,
+        link: &ComponentLink<ComponentT>,
+        parse: impl Fn(&str) -> Result<ConfigT, ErrorT> + Send + 'static,
+        apply: impl Fn(&ComponentLink<ComponentT>, Vec<ConfigT>) + Send + 'static,
+    ) -> WatchHandle
+    where
+        ErrorT: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let paths = self.paths.clone();
+        let load = move |link: &ComponentLink<ComponentT>| {
+            let mut configs = Vec::with_capacity(paths.len());
+            for path in paths.iter() {
+                let config = match fs::read_to_string(path) {
+                    Ok(text) => parse(&text).map_err(Into::into),
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+                    Err(error) => Err(error.into()),
+                };
+                match config {
+                    Ok(config) => configs.push(config),
+                    Err(error) => {
+                        link.report_error(format!("Cannot reload {}: {}", path.display(), error));
+                        return;
+                    }
+                }
+            }
+            apply(link, configs);
+        };
+        // Snapshot the files before loading them, s.t. changes made while
+        // they are loaded aren't missed
+        let snapshot = Snapshot::new(&self.paths);
+        load(link);
+        self.spawn(link, snapshot, move |link, _changes| load(link))
+    }
+
+    /// Polls the watched paths on a background thread, passing the changes
+    /// since `snapshot` was taken to `on_change`.
+    fn spawn<ComponentT: Component>(
+        self,
+        link: &ComponentLink<ComponentT>,
+        mut snapshot: Snapshot,
+        on_change: impl Fn(&ComponentLink<ComponentT>, Vec<FileChange>) + Send + 'static,
+    ) -> WatchHandle {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let handle = WatchHandle {
+            stopped: Arc::clone(&stopped),
+        };
+        link.spawn(move |link| loop {
+            let started = Instant::now();
+            while started.elapsed() < self.interval {
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                thread::sleep(std::cmp::min(self.interval, MAX_SLEEP));
+            }
+
+            let current = Snapshot::new(&self.paths);
+            let changes = snapshot.changes(&current);
+            snapshot = current;
+            if !changes.is_empty() && !stopped.load(Ordering::Relaxed) {
+                on_change(&link, changes);
+            }
+        });
+        handle
+    }
+}
+
+/// Keeps a [`FileWatcher`](struct.FileWatcher.html) running until dropped.
+#[derive(Debug)]
+pub struct WatchHandle {
+    stopped: Arc<AtomicBool>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The modification time and size of every file under the watched paths.
+struct Snapshot(HashMap<PathBuf, (Option<SystemTime>, u64)>);
+
+impl Snapshot {
+    fn new(paths: &[PathBuf]) -> Self {
+        let mut snapshot = Self(HashMap::new());
+        for path in paths {
+            // Paths may disappear while we're reading them, they'll be picked
+            // up again on the next poll
+            let _ = snapshot.add(path);
+        }
+        snapshot
+    }
+
+    fn add(&mut self, path: &Path) -> io::Result<()> {
+        let metadata = fs::metadata(path)?;
+        if metadata.is_dir() {
+            for entry in fs::read_dir(path)? {
+                let _ = self.add(&entry?.path());
+            }
+        } else {
+            self.0.insert(
+                path.to_path_buf(),
+                (metadata.modified().ok(), metadata.len()),
+            );
+        }
+        Ok(())
+    }
+
+    fn changes(&self, current: &Snapshot) -> Vec<FileChange> {
+        let mut changes: Vec<_> = current
+            .0
+            .iter()
+            .filter_map(|(path, stat)| match self.0.get(path) {
+                None => Some(FileChange::Created(path.clone())),
+                Some(previous) if previous != stat => Some(FileChange::Modified(path.clone())),
+                Some(_) => None,
+            })
+            .chain(
+                self.0
+                    .keys()
+                    .filter(|path| !current.0.contains_key(*path))
+                    .map(|path| FileChange::Removed(path.clone())),
+            )
+            .collect();
+        changes.sort_by(|left, right| left.path().cmp(right.path()));
+        changes
+    }
+}
+
+// Upper bound on how long the watcher thread sleeps before checking whether
+// it was stopped
+const MAX_SLEEP: Duration = Duration::from_millis(50);
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{prelude::*, testing::Harness, KeyPattern, ParseKeyError};
+
+    #[test]
+    fn snapshot_changes() {
+        let root = std::env::temp_dir().join(format!("zi-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("nested")).unwrap();
+        fs::write(root.join("kept"), "kept").unwrap();
+        fs::write(root.join("modified"), "before").unwrap();
+        fs::write(root.join("nested/removed"), "removed").unwrap();
+        let missing = root.join("missing");
+
+        let paths = [root.clone(), missing.clone()];
+        let before = Snapshot::new(&paths);
+        fs::write(root.join("modified"), "after, with a different size").unwrap();
+        fs::remove_file(root.join("nested/removed")).unwrap();
+        fs::write(root.join("nested/created"), "created").unwrap();
+        fs::write(&missing, "").unwrap();
+        let after = Snapshot::new(&paths);
+
+        let changes = before.changes(&after);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                FileChange::Created(missing),
+                FileChange::Modified(root.join("modified")),
+                FileChange::Created(root.join("nested/created")),
+                FileChange::Removed(root.join("nested/removed")),
+            ]
+        );
+        assert!(after.changes(&after).is_empty());
+    }
+
+    /// Parses a theme with a slot per line.
+    fn parse_theme(text: &str) -> Result<Theme, String> {
+        text.lines().try_fold(Theme::new("test"), |theme, line| {
+            match line.split_once(' ') {
+                Some((slot, "bold")) => {
+                    Ok(theme.style(slot.to_owned(), Style::default().with_bold()))
+                }
+                None if !line.is_empty() => Ok(theme.style(line.to_owned(), Style::default())),
+                _ => Err(format!("invalid slot `{}`", line)),
+            }
+        })
+    }
+
+    /// Parses keymap overrides with a `component command pattern` per line.
+    fn parse_keymap(text: &str) -> Result<KeymapOverrides, ParseKeyError> {
+        let mut overrides = KeymapOverrides::new();
+        for line in text.lines() {
+            let mut parts = line.splitn(3, ' ');
+            let (component, command) = (parts.next().unwrap(), parts.next().unwrap());
+            overrides.bind(component, command, [parts.next().unwrap().parse()?]);
+        }
+        Ok(overrides)
+    }
+
+    struct Config {
+        _theme: WatchHandle,
+        _keymap: WatchHandle,
+    }
+
+    impl Component for Config {
+        type Message = ();
+        type Properties = [PathBuf; 3];
+
+        fn create(
+            [base, user, keymap]: Self::Properties,
+            _: Rect,
+            link: ComponentLink<Self>,
+        ) -> Self {
+            let interval = Duration::from_millis(10);
+            Self {
+                _theme: FileWatcher::new([base, user])
+                    .interval(interval)
+                    .reload_theme(&link, parse_theme),
+                _keymap: FileWatcher::new([keymap])
+                    .interval(interval)
+                    .reload_keymap(&link, parse_keymap),
+            }
+        }
+
+        fn view(&self) -> Layout {
+            Layout::empty()
+        }
+    }
+
+    #[test]
+    fn reload_theme_and_keymap() {
+        let root = std::env::temp_dir().join(format!("zi-reload-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let (base, user, keymap) = (root.join("base"), root.join("user"), root.join("keymap"));
+        fs::write(&base, "text\nselection").unwrap();
+
+        let mut harness = Harness::new(
+            Size::new(10, 1),
+            Config::with([base, user.clone(), keymap.clone()]),
+        );
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let reported = Arc::clone(&errors);
+        harness
+            .app_mut()
+            .on_error(move |error| reported.lock().unwrap().push(error.to_string()));
+        let styles = |harness: &Harness| {
+            let mut styles: Vec<_> = harness
+                .app()
+                .theme()
+                .styles()
+                .map(|(slot, style)| (slot.to_owned(), style))
+                .collect();
+            styles.sort_by(|left, right| left.0.cmp(&right.0));
+            styles
+        };
+        let patterns = |harness: &Harness| {
+            harness
+                .app()
+                .keymap_overrides()
+                .patterns("Pager", "line-down")
+                .map(<[KeyPattern]>::to_vec)
+        };
+        let wait =
+            |harness: &mut Harness| assert!(harness.wait_for_message(Duration::from_secs(5)));
+        // Files are replaced like editors save them, s.t. the watcher doesn't
+        // see them half written
+        let write = |path: &Path, contents: &str| {
+            let temporary = root.join("temporary");
+            fs::write(&temporary, contents).unwrap();
+            fs::rename(&temporary, path).unwrap();
+        };
+
+        // The files are loaded right away, missing ones are skipped
+        harness.canvas();
+        assert_eq!(
+            styles(&harness),
+            [
+                ("selection".to_owned(), Style::default()),
+                ("text".to_owned(), Style::default())
+            ]
+        );
+        assert!(harness.app().keymap_overrides().is_empty());
+
+        // Later files override the styles of earlier ones
+        write(&user, "selection bold\nborder");
+        wait(&mut harness);
+        assert_eq!(
+            styles(&harness),
+            [
+                ("border".to_owned(), Style::default()),
+                ("selection".to_owned(), Style::default().with_bold()),
+                ("text".to_owned(), Style::default())
+            ]
+        );
+
+        // Errors are reported and the current theme is kept
+        write(&user, "selection italic");
+        wait(&mut harness);
+        assert_eq!(styles(&harness).len(), 3);
+        assert_eq!(errors.lock().unwrap().len(), 1);
+        assert!(errors.lock().unwrap()[0].contains("invalid slot `selection italic`"));
+
+        write(&keymap, "Pager line-down C-n");
+        wait(&mut harness);
+        assert_eq!(patterns(&harness), Some(vec!["C-n".parse().unwrap()]));
+
+        // Removing the keymap restores the default bindings
+        fs::remove_file(&keymap).unwrap();
+        wait(&mut harness);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(patterns(&harness), None);
+    }
+}